@@ -1,28 +1,52 @@
 use crate::{
     cli::args::{
-        BundlerAndUoPoolArgs, BundlerArgs, CreateWalletArgs, MetricsArgs, RpcArgs, StorageType,
-        UoPoolArgs,
+        BundlerAndUoPoolArgs, BundlerArgs, CreateWalletArgs, HealthArgs, MetricsArgs, RpcArgs,
+        StorageType, UoPoolArgs,
     },
     utils::unwrap_path_or_home,
 };
 use alloy_chains::{Chain, NamedChain};
-use ethers::{providers::Middleware, types::Address};
+use ethers::{
+    providers::Middleware,
+    signers::Signer as _,
+    types::{Address, U256},
+};
 use parking_lot::RwLock;
-use silius_bundler::{ConditionalClient, EthereumClient, FastlaneClient, FlashbotsClient};
-use silius_contracts::EntryPoint;
+#[cfg(feature = "kafka")]
+use silius_bundler::KafkaSink;
+#[cfg(feature = "nats")]
+use silius_bundler::NatsSink;
+#[cfg(feature = "sqlite")]
+use silius_bundler::SubmissionLog;
+use silius_bundler::{
+    BundleEventSink, BundleReporter, BundleSizeOptimizer, BundleTracker, ConditionalClient,
+    EthereumClient, FastlaneClient, FlashbotsClient, MempoolMonitor, MevShareClient,
+    ProfitabilityFilter, Signer,
+};
+use silius_contracts::{detect_entry_point_version, EntryPoint, EntryPointVersion};
 use silius_grpc::{
     bundler_client::BundlerClient, bundler_service_run, uo_pool_client::UoPoolClient,
     uopool_service_run,
 };
 use silius_mempool::{
     init_env,
-    validate::validator::{new_canonical, new_canonical_unsafe},
-    CodeHashes, DatabaseTable, EntitiesReputation, Mempool, Reputation, UserOperations,
-    UserOperationsByEntity, UserOperationsBySender, WriteMap,
+    validate::{
+        sanity::paymaster_balance::PaymasterBalance,
+        simulation_trace::{
+            delegatecall_target::DelegatecallTargetCheck, inner_call_gas::InnerCallGas,
+        },
+        validator::{new_canonical, new_canonical_unsafe},
+    },
+    BlacklistedEntities, CodeHashes, DatabaseTable, EntitiesReputation, EntryPointOverhead,
+    Mempool, Reputation, UserOperationReceipts, UserOperations, UserOperationsByAggregator,
+    UserOperationsByEntity, UserOperationsByNonce, UserOperationsBySender, WhitelistedEntities,
+    WriteMap,
 };
+#[cfg(feature = "sqlite")]
+use silius_mempool::{sqlite::tables as sqlite_tables, SqliteEnv, SqliteTable};
 use silius_metrics::{launch_metrics_exporter, mempool::MetricsHandler};
 use silius_primitives::{
-    bundler::BundleStrategy,
+    bundler::{BundleReporterType, BundleStrategy, SignerType},
     constants::{
         entry_point,
         fastlane_relay_endpoints::{FASTLANE_POLYGON, POLYGON_NODE},
@@ -36,13 +60,15 @@ use silius_primitives::{
     provider::{create_http_provider, BlockStream},
     reputation::ReputationEntry,
     simulation::CodeHash,
-    UserOperationHash, UserOperationSigned, Wallet,
+    UserOperation, UserOperationHash, UserOperationReceipt, Wallet,
 };
 use silius_rpc::{
     debug_api::{DebugApiServer, DebugApiServerImpl},
     eth_api::{EthApiServer, EthApiServerImpl},
+    middleware::CorsConfig,
+    silius_api::{SiliusApiServer, SiliusApiServerImpl},
     web3_api::{Web3ApiServer, Web3ApiServerImpl},
-    JsonRpcServer, JsonRpcServerType,
+    HealthServer, JsonRpcServer, JsonRpcServerType,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -60,6 +86,7 @@ pub async fn launch_bundler<M, N>(
     uopool_args: UoPoolArgs,
     common_args: BundlerAndUoPoolArgs,
     rpc_args: RpcArgs,
+    health_args: HealthArgs,
     metrics_args: MetricsArgs,
     eth_client: Arc<M>,
     eth_bundle_client: Arc<N>,
@@ -71,7 +98,7 @@ where
 {
     launch_uopool(
         uopool_args.clone(),
-        eth_client,
+        eth_client.clone(),
         block_streams,
         common_args.chain,
         common_args.entry_points.clone(),
@@ -97,6 +124,12 @@ where
     )
     .await?;
 
+    launch_health(
+        health_args,
+        format!("http://{}", SocketAddr::new(uopool_args.uopool_addr, uopool_args.uopool_port)),
+        Some(eth_client),
+    );
+
     if metrics_args.enable_metrics {
         launch_metrics_exporter(metrics_args.listen_addr(), metrics_args.custom_label_value);
     }
@@ -104,6 +137,27 @@ where
     Ok(())
 }
 
+/// Starts the health check server, unless it's disabled (a failing health check server doesn't
+/// stop the bundler)
+pub fn launch_health<M>(
+    health_args: HealthArgs,
+    uopool_grpc_listen_address: String,
+    eth_client: Option<Arc<M>>,
+) where
+    M: Middleware + 'static,
+{
+    if health_args.disable_health {
+        return;
+    }
+
+    if let Err(e) =
+        HealthServer::new(health_args.listen_addr(), uopool_grpc_listen_address, eth_client)
+            .and_then(|server| server.start())
+    {
+        warn!("Failed to start the health check server on {}: {e:?}", health_args.listen_addr());
+    }
+}
+
 pub async fn launch_bundling<M>(
     args: BundlerArgs,
     eth_client: Arc<M>,
@@ -125,44 +179,34 @@ where
 
     let chain_id = eth_client.get_chainid().await?.as_u64();
     let chain_conn = Chain::from(chain_id);
-
-    let wallet: Wallet;
-
-    if let Some(mnemonic_file) = args.mnemonic_file {
-        if args.bundle_strategy == BundleStrategy::Flashbots {
-            wallet = Wallet::from_file(mnemonic_file.into(), chain_id, true)
-                .map_err(|error| eyre::format_err!("Could not load mnemonic file: {}", error))?;
-            info!("Wallet Signer {:?}", wallet.signer);
-            info!("Flashbots Signer {:?}", wallet.flashbots_signer);
-        } else {
-            wallet = Wallet::from_file(mnemonic_file.into(), chain_id, false)
-                .map_err(|error| eyre::format_err!("Could not load mnemonic file: {}", error))?;
-            info!("{:?}", wallet.signer);
+    let entry_points = entry_point_versions(eth_client.as_ref(), entry_points, None).await?;
+
+    // only the Ethereum client signs through the signer, the other strategies need the wallet
+    let (wallet, signer) = match args.signer_type {
+        SignerType::Mnemonic => {
+            let wallet = load_wallet(&args, chain_id)?;
+            let signer = Signer::from(wallet.signer.clone());
+            (Some(wallet), signer)
         }
-    } else if let Some(private_key) = args.private_key {
-        if args.bundle_strategy == BundleStrategy::Flashbots {
-            wallet = Wallet::from_private_key(
-                private_key.as_str(),
-                chain_id,
-                true,
-                args.flashbots_private_key.as_deref(),
-            )
-            .map_err(|error| {
-                eyre::format_err!("Could not load from private key or flashbots key: {}", error)
-            })?;
-            info!("Wallet Signer {:?}", wallet.signer);
-            info!("Flashbots Signer {:?}", wallet.flashbots_signer);
-        } else {
-            if args.flashbots_private_key.is_some() {
-                info!("Flashbots key is ignored since send bundle mode is not Flashbots");
-            }
-            wallet = Wallet::from_private_key(private_key.as_str(), chain_id, false, None)
-                .map_err(|error| eyre::format_err!("Could not load from private key: {}", error))?;
-            info!("{:?}", wallet.signer);
+        #[cfg(feature = "aws-kms")]
+        SignerType::AwsKms => {
+            let key_id = args
+                .aws_kms_key_id
+                .clone()
+                .ok_or_else(|| eyre::format_err!("AWS KMS signer requires the key id"))?;
+            (None, Signer::aws_kms_from_env(key_id, chain_id).await?)
         }
-    } else {
-        panic!("Neither mnemonic file nor private key was found");
-    }
+        #[cfg(feature = "ledger")]
+        SignerType::Ledger => {
+            (None, Signer::ledger(args.ledger_derivation_path.clone(), chain_id).await?)
+        }
+        #[allow(unreachable_patterns)]
+        signer_type => {
+            return Err(eyre::format_err!("{signer_type:?} signer is not enabled in this build"))
+        }
+    };
+    let address = signer.address();
+    info!("Bundler account {address:?} ({:?} signer)", args.signer_type);
 
     info!("Connecting to uopool gRPC service...");
     let uopool_grpc_client = UoPoolClient::connect(uopool_grpc_listen_address).await?;
@@ -170,12 +214,42 @@ where
 
     let bundle_interval = if args.manual_bundle_mode { None } else { Some(args.bundle_interval) };
 
+    let bundle_reporter = create_bundle_reporter(&args).await?;
+    let mempool_monitor = args.mempool_high_watermark.map(|high_watermark| {
+        MempoolMonitor::new(
+            high_watermark,
+            args.mempool_low_watermark.unwrap_or(high_watermark / 2),
+        )
+    });
+
+    #[cfg(feature = "sqlite")]
+    let submission_log = match args.submission_log_path.as_ref() {
+        Some(path) => {
+            info!("Logging bundle submissions to {path:?}");
+            Some(Arc::new(SubmissionLog::connect(path).await?))
+        }
+        None => None,
+    };
+
+    let profitability_filter = args.enable_profitability_filter.then(|| {
+        ProfitabilityFilter::new(
+            args.min_net_fee,
+            args.min_bundle_revenue_multiplier,
+            args.always_submit,
+        )
+    });
+
     match args.bundle_strategy {
         BundleStrategy::EthereumClient => {
-            let client = Arc::new(EthereumClient::new(eth_client.clone(), wallet.clone()));
+            let client = Arc::new(EthereumClient::with_signer(
+                eth_client.clone(),
+                signer,
+                BundleTracker::new(args.resubmit_interval_blocks, args.max_resubmit_attempts)
+                    .with_cancel_on_max_bumps(args.enable_cancel_on_max_bumps),
+            ));
             bundler_service_run(
                 SocketAddr::new(args.bundler_addr, args.bundler_port),
-                wallet,
+                address,
                 entry_points,
                 chain_conn,
                 args.beneficiary,
@@ -186,13 +260,21 @@ where
                 uopool_grpc_client,
                 metrics_args.enable_metrics,
                 args.enable_access_list,
+                args.use_multicall_bundler,
+                bundle_reporter,
+                mempool_monitor,
+                args.mempool_monitor_interval,
+                profitability_filter,
+                #[cfg(feature = "sqlite")]
+                submission_log.clone(),
             );
         }
         BundleStrategy::Conditional => {
-            let client = Arc::new(ConditionalClient::new(eth_client.clone(), wallet.clone()));
+            let wallet = wallet.ok_or_else(|| unsupported_signer(args.signer_type))?;
+            let client = Arc::new(ConditionalClient::new(eth_client.clone(), wallet));
             bundler_service_run(
                 SocketAddr::new(args.bundler_addr, args.bundler_port),
-                wallet,
+                address,
                 entry_points,
                 chain_conn,
                 args.beneficiary,
@@ -203,30 +285,76 @@ where
                 uopool_grpc_client,
                 metrics_args.enable_metrics,
                 args.enable_access_list,
+                args.use_multicall_bundler,
+                bundle_reporter,
+                mempool_monitor,
+                args.mempool_monitor_interval,
+                profitability_filter,
+                #[cfg(feature = "sqlite")]
+                submission_log.clone(),
             );
         }
         BundleStrategy::Flashbots => {
-            let relay_endpoints: Vec<String> = match chain_conn
-                .named()
-                .expect("Flashbots is only supported on Mainnet and Sepolia")
-            {
-                NamedChain::Mainnet => {
-                    vec![flashbots_relay_endpoints::FLASHBOTS.into()]
-                }
-                NamedChain::Sepolia => {
-                    vec![flashbots_relay_endpoints::FLASHBOTS_SEPOLIA.into()]
+            let wallet = wallet.ok_or_else(|| unsupported_signer(args.signer_type))?;
+            let relay_endpoints: Vec<String> = if !args.flashbots_relay_endpoints.is_empty() {
+                args.flashbots_relay_endpoints.clone()
+            } else {
+                match chain_conn
+                    .named()
+                    .expect("Flashbots is only supported on Mainnet and Sepolia")
+                {
+                    NamedChain::Mainnet => {
+                        vec![flashbots_relay_endpoints::FLASHBOTS.into()]
+                    }
+                    NamedChain::Sepolia => {
+                        vec![flashbots_relay_endpoints::FLASHBOTS_SEPOLIA.into()]
+                    }
+                    _ => panic!("Flashbots is only supported on Mainnet and Sepolia"),
                 }
-                _ => panic!("Flashbots is only supported on Mainnet and Sepolia"),
             };
 
-            let client = Arc::new(FlashbotsClient::new(
-                eth_client.clone(),
-                Some(relay_endpoints),
-                wallet.clone(),
-            )?);
+            let client =
+                Arc::new(FlashbotsClient::new(eth_client.clone(), Some(relay_endpoints), wallet)?);
             bundler_service_run(
                 SocketAddr::new(args.bundler_addr, args.bundler_port),
-                wallet,
+                address,
+                entry_points,
+                chain_conn,
+                args.beneficiary,
+                args.min_balance,
+                bundle_interval,
+                eth_client,
+                client,
+                uopool_grpc_client,
+                metrics_args.enable_metrics,
+                args.enable_access_list,
+                args.use_multicall_bundler,
+                bundle_reporter,
+                mempool_monitor,
+                args.mempool_monitor_interval,
+                profitability_filter,
+                #[cfg(feature = "sqlite")]
+                submission_log.clone(),
+            );
+        }
+        BundleStrategy::MevShare => {
+            let wallet = wallet.ok_or_else(|| unsupported_signer(args.signer_type))?;
+            let endpoint = match args.mev_share_endpoint {
+                Some(endpoint) => endpoint,
+                None => match chain_conn
+                    .named()
+                    .expect("MEV-Share is only supported on Mainnet and Sepolia")
+                {
+                    NamedChain::Mainnet => flashbots_relay_endpoints::MEV_SHARE.into(),
+                    NamedChain::Sepolia => flashbots_relay_endpoints::MEV_SHARE_SEPOLIA.into(),
+                    _ => panic!("MEV-Share is only supported on Mainnet and Sepolia"),
+                },
+            };
+
+            let client = Arc::new(MevShareClient::new(eth_client.clone(), endpoint, wallet)?);
+            bundler_service_run(
+                SocketAddr::new(args.bundler_addr, args.bundler_port),
+                address,
                 entry_points,
                 chain_conn,
                 args.beneficiary,
@@ -237,9 +365,17 @@ where
                 uopool_grpc_client,
                 metrics_args.enable_metrics,
                 args.enable_access_list,
+                args.use_multicall_bundler,
+                bundle_reporter,
+                mempool_monitor,
+                args.mempool_monitor_interval,
+                profitability_filter,
+                #[cfg(feature = "sqlite")]
+                submission_log.clone(),
             );
         }
         BundleStrategy::Fastlane => {
+            let wallet = wallet.ok_or_else(|| unsupported_signer(args.signer_type))?;
             let relay_endpoint: String =
                 match chain_conn.named().expect("Fastlane is only supported on Polygon mainnet") {
                     NamedChain::Polygon => FASTLANE_POLYGON.into(),
@@ -254,12 +390,12 @@ where
                 eth_client.clone(),
                 polygon_client,
                 relay_client,
-                wallet.clone(),
+                wallet,
             ));
 
             bundler_service_run(
                 SocketAddr::new(args.bundler_addr, args.bundler_port),
-                wallet,
+                address,
                 entry_points,
                 chain_conn,
                 args.beneficiary,
@@ -270,6 +406,13 @@ where
                 uopool_grpc_client,
                 metrics_args.enable_metrics,
                 args.enable_access_list,
+                args.use_multicall_bundler,
+                bundle_reporter,
+                mempool_monitor,
+                args.mempool_monitor_interval,
+                profitability_filter,
+                #[cfg(feature = "sqlite")]
+                submission_log.clone(),
             );
         }
     }
@@ -279,6 +422,25 @@ where
     Ok(())
 }
 
+/// Pairs the entry points with their versions, detected from the deployed entry points unless the
+/// version is set
+async fn entry_point_versions<M: Middleware + 'static>(
+    eth_client: &M,
+    entry_points: Vec<Address>,
+    version: Option<EntryPointVersion>,
+) -> eyre::Result<Vec<(Address, EntryPointVersion)>> {
+    let mut versions = Vec::with_capacity(entry_points.len());
+    for ep in entry_points {
+        let ep_version = match version {
+            Some(version) => version,
+            None => detect_entry_point_version(ep, eth_client).await?,
+        };
+        info!("Entry point {ep:?} has version {ep_version}");
+        versions.push((ep, ep_version));
+    }
+    Ok(versions)
+}
+
 pub async fn launch_uopool<M>(
     args: UoPoolArgs,
     eth_client: Arc<M>,
@@ -311,6 +473,10 @@ where
         Address::from_str(entry_point::ADDRESS).expect("address should be valid"),
     );
 
+    // the validator is set to the entry point of each mempool by the builder
+    let entry_points =
+        entry_point_versions(eth_client.as_ref(), entry_points, args.entry_point_version).await?;
+
     let (mempool, reputation) = match args.storage_type {
         StorageType::Database => {
             let env = Arc::new(
@@ -323,7 +489,10 @@ where
                 ))),
                 Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
                 Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+                Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
                 Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+                Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+                Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env.clone())),
             );
             let mut reputation = Reputation::new(
                 MIN_INCLUSION_RATE_DENOMINATOR,
@@ -340,13 +509,19 @@ where
             for whiteaddr in args.whitelist.iter() {
                 reputation.add_whitelist(whiteaddr);
             }
+            // addresses whitelisted or blacklisted at runtime are persisted (the CLI whitelist
+            // is added before, so it isn't)
+            let reputation = reputation.with_entity_lists(
+                Box::new(DatabaseTable::<WriteMap, WhitelistedEntities>::new(env.clone())),
+                Box::new(DatabaseTable::<WriteMap, BlacklistedEntities>::new(env)),
+            )?;
             (mempool, reputation)
         }
         StorageType::Memory => {
             let mempool = Mempool::new(
                 Box::new(Arc::new(RwLock::new(MetricsHandler::new(HashMap::<
                     UserOperationHash,
-                    UserOperationSigned,
+                    UserOperation,
                 >::default())))),
                 Box::new(Arc::new(RwLock::new(
                     HashMap::<Address, HashSet<UserOperationHash>>::default(),
@@ -354,9 +529,51 @@ where
                 Box::new(Arc::new(RwLock::new(
                     HashMap::<Address, HashSet<UserOperationHash>>::default(),
                 ))),
+                Box::new(Arc::new(RwLock::new(
+                    HashMap::<Address, HashSet<UserOperationHash>>::default(),
+                ))),
                 Box::new(Arc::new(RwLock::new(
                     HashMap::<UserOperationHash, Vec<CodeHash>>::default(),
                 ))),
+                Box::new(Arc::new(RwLock::new(
+                    HashMap::<(Address, U256), UserOperationHash>::default(),
+                ))),
+                Box::new(Arc::new(RwLock::new(
+                    HashMap::<UserOperationHash, UserOperationReceipt>::default(),
+                ))),
+            );
+            let reputation = Reputation::new(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                args.min_stake,
+                MIN_UNSTAKE_DELAY.into(),
+                Arc::new(RwLock::new(HashSet::<Address>::default())),
+                Arc::new(RwLock::new(HashSet::<Address>::default())),
+                Box::new(Arc::new(RwLock::new(MetricsHandler::new(HashMap::<
+                    Address,
+                    ReputationEntry,
+                >::default())))),
+            );
+            (mempool, reputation)
+        }
+        #[cfg(feature = "sqlite")]
+        StorageType::Sqlite => {
+            let path = datadir.join(silius_primitives::constants::storage::SQLITE_FILE_NAME);
+            let env =
+                Arc::new(SqliteEnv::connect(&path.to_string_lossy()).expect("Init sqlite failed"));
+            let mempool = Mempool::new(
+                Box::new(MetricsHandler::new(SqliteTable::<sqlite_tables::UserOperations>::new(
+                    env.clone(),
+                ))),
+                Box::new(SqliteTable::<sqlite_tables::UserOperationsBySender>::new(env.clone())),
+                Box::new(SqliteTable::<sqlite_tables::UserOperationsByEntity>::new(env.clone())),
+                Box::new(SqliteTable::<sqlite_tables::UserOperationsByAggregator>::new(
+                    env.clone(),
+                )),
+                Box::new(SqliteTable::<sqlite_tables::CodeHashes>::new(env.clone())),
+                Box::new(SqliteTable::<sqlite_tables::UserOperationsByNonce>::new(env.clone())),
+                Box::new(SqliteTable::<sqlite_tables::UserOperationReceipts>::new(env)),
             );
             let reputation = Reputation::new(
                 MIN_INCLUSION_RATE_DENOMINATOR,
@@ -374,6 +591,9 @@ where
             (mempool, reputation)
         }
     };
+    let bundle_size_optimizer = (args.min_op_value_gwei > 0)
+        .then(|| BundleSizeOptimizer::new(U256::from(args.min_op_value_gwei) * U256::exp10(9)));
+
     match args.uopool_mode {
         silius_primitives::UoPoolMode::Standard => {
             let validator = new_canonical(
@@ -381,6 +601,26 @@ where
                 chain,
                 args.max_verification_gas,
                 args.min_priority_fee_per_gas,
+                args.max_calldata_gas,
+                args.max_uo_calldata_size,
+            )
+            .with_simulation_cache_size(args.simulation_cache_size)
+            .with_max_mempool_age(Duration::from_secs(args.max_mempool_age))
+            .with_paymaster_balance(
+                args.paymaster_deposit_multiplier
+                    .map(|min_deposit_multiplier| PaymasterBalance { min_deposit_multiplier }),
+            )
+            .with_sender_balance(args.enable_sender_balance_check)
+            .with_call_type_check(args.enable_call_type_check)
+            .with_inner_call_gas(
+                args.min_inner_call_gas_ratio.map(|min_gas_ratio| InnerCallGas { min_gas_ratio }),
+            )
+            .with_delegatecall_target_check(
+                args.enable_delegatecall_target_check.then(|| {
+                    DelegatecallTargetCheck::new(
+                        args.trusted_implementations.iter().copied().collect(),
+                    )
+                }),
             );
 
             uopool_service_run(
@@ -391,9 +631,27 @@ where
                 block_streams,
                 chain,
                 args.max_verification_gas,
+                EntryPointOverhead {
+                    fixed_overhead: args.entry_point_fixed_overhead,
+                    per_op_overhead: args.entry_point_per_op_overhead,
+                },
                 mempool,
                 reputation,
                 validator,
+                args.min_priority_fee_per_gas,
+                args.priority_fee_tip_percent,
+                args.default_sig_size,
+                args.estimation_binary_search_rounds,
+                Duration::from_secs(args.max_mempool_age),
+                args.max_mempool_size,
+                args.max_bundle_gas.into(),
+                args.bundle_selector,
+                args.max_ops_per_unstaked_sender,
+                args.max_ops_per_staked_sender,
+                args.entity_stake_requirements(),
+                args.max_parallel_validations,
+                args.reorg_depth_limit,
+                bundle_size_optimizer,
                 p2p_config,
                 metrics_args.enable_metrics,
             )
@@ -406,7 +664,16 @@ where
                 chain,
                 args.max_verification_gas,
                 args.min_priority_fee_per_gas,
-            );
+                args.max_calldata_gas,
+                args.max_uo_calldata_size,
+            )
+            .with_simulation_cache_size(args.simulation_cache_size)
+            .with_max_mempool_age(Duration::from_secs(args.max_mempool_age))
+            .with_paymaster_balance(
+                args.paymaster_deposit_multiplier
+                    .map(|min_deposit_multiplier| PaymasterBalance { min_deposit_multiplier }),
+            )
+            .with_sender_balance(args.enable_sender_balance_check);
             uopool_service_run(
                 SocketAddr::new(args.uopool_addr, args.uopool_port),
                 args.uopool_mode,
@@ -415,9 +682,27 @@ where
                 block_streams,
                 chain,
                 args.max_verification_gas,
+                EntryPointOverhead {
+                    fixed_overhead: args.entry_point_fixed_overhead,
+                    per_op_overhead: args.entry_point_per_op_overhead,
+                },
                 mempool,
                 reputation,
                 validator,
+                args.min_priority_fee_per_gas,
+                args.priority_fee_tip_percent,
+                args.default_sig_size,
+                args.estimation_binary_search_rounds,
+                Duration::from_secs(args.max_mempool_age),
+                args.max_mempool_size,
+                args.max_bundle_gas.into(),
+                args.bundle_selector,
+                args.max_ops_per_unstaked_sender,
+                args.max_ops_per_staked_sender,
+                args.entity_stake_requirements(),
+                args.max_parallel_validations,
+                args.reorg_depth_limit,
+                bundle_size_optimizer,
                 p2p_config,
                 metrics_args.enable_metrics,
             )
@@ -449,13 +734,22 @@ pub async fn launch_rpc(
         args.ws_addr,
         args.ws_port,
     )
-    .with_cors(&args.http_corsdomain, JsonRpcServerType::Http)
-    .with_cors(&args.ws_origins, JsonRpcServerType::Ws);
+    .with_cors(CorsConfig::from_domains(&args.http_corsdomain)?, JsonRpcServerType::Http)?
+    .with_cors(CorsConfig::from_domains(&args.ws_origins)?, JsonRpcServerType::Ws)?
+    .with_dedup();
 
     if let Some(eth_client_proxy_address) = args.eth_client_proxy_address.clone() {
         server = server.with_proxy(eth_client_proxy_address);
     }
 
+    if args.http_max_requests_per_minute.is_some() || !args.http_method_rate_limits.is_empty() {
+        server = server.with_rate_limit(
+            args.http_max_requests_per_minute.unwrap_or(u64::MAX),
+            args.http_method_rate_limits.iter().cloned().collect(),
+            args.http_trusted_proxies.iter().cloned().collect(),
+        );
+    }
+
     if metrics_args.enable_metrics {
         info!("Enabling json rpc server metrics.");
         server = server.with_metrics()
@@ -490,6 +784,21 @@ pub async fn launch_rpc(
         }
     }
 
+    if args.is_api_method_enabled("silius") {
+        if http_api.contains("silius") {
+            server.add_methods(
+                SiliusApiServerImpl { uopool_grpc_client: uopool_grpc_client.clone() }.into_rpc(),
+                JsonRpcServerType::Http,
+            )?;
+        }
+        if ws_api.contains("silius") {
+            server.add_methods(
+                SiliusApiServerImpl { uopool_grpc_client: uopool_grpc_client.clone() }.into_rpc(),
+                JsonRpcServerType::Ws,
+            )?;
+        }
+    }
+
     if args.is_api_method_enabled("debug") {
         info!("Connecting to bundling gRPC service...");
         let bundler_grpc_client = BundlerClient::connect(bundler_grpc_listen_address).await?;
@@ -544,6 +853,90 @@ pub fn create_wallet(args: CreateWalletArgs) -> eyre::Result<()> {
     Ok(())
 }
 
+fn unsupported_signer(signer_type: SignerType) -> eyre::Report {
+    eyre::format_err!("{signer_type:?} signer is only supported by the ethereum-client strategy")
+}
+
+/// Loads the wallet of the bundler from the mnemonic file or the private key
+fn load_wallet(args: &BundlerArgs, chain_id: u64) -> eyre::Result<Wallet> {
+    let wallet: Wallet;
+
+    if let Some(mnemonic_file) = args.mnemonic_file.clone() {
+        if matches!(args.bundle_strategy, BundleStrategy::Flashbots | BundleStrategy::MevShare) {
+            wallet = Wallet::from_file(mnemonic_file.into(), chain_id, true)
+                .map_err(|error| eyre::format_err!("Could not load mnemonic file: {}", error))?;
+            info!("Wallet Signer {:?}", wallet.signer);
+            info!("Flashbots Signer {:?}", wallet.flashbots_signer);
+        } else {
+            wallet = Wallet::from_file(mnemonic_file.into(), chain_id, false)
+                .map_err(|error| eyre::format_err!("Could not load mnemonic file: {}", error))?;
+            info!("{:?}", wallet.signer);
+        }
+    } else if let Some(private_key) = &args.private_key {
+        if matches!(args.bundle_strategy, BundleStrategy::Flashbots | BundleStrategy::MevShare) {
+            wallet = Wallet::from_private_key(
+                private_key.as_str(),
+                chain_id,
+                true,
+                args.flashbots_private_key.as_deref(),
+            )
+            .map_err(|error| {
+                eyre::format_err!("Could not load from private key or flashbots key: {}", error)
+            })?;
+            info!("Wallet Signer {:?}", wallet.signer);
+            info!("Flashbots Signer {:?}", wallet.flashbots_signer);
+        } else {
+            if args.flashbots_private_key.is_some() {
+                info!("Flashbots key is ignored since send bundle mode is not Flashbots");
+            }
+            wallet = Wallet::from_private_key(private_key.as_str(), chain_id, false, None)
+                .map_err(|error| eyre::format_err!("Could not load from private key: {}", error))?;
+            info!("{:?}", wallet.signer);
+        }
+    } else {
+        return Err(eyre::format_err!("Neither mnemonic file nor private key was found"));
+    }
+
+    Ok(wallet)
+}
+
+async fn create_bundle_reporter(args: &BundlerArgs) -> eyre::Result<Option<Arc<BundleReporter>>> {
+    let sink: Option<Box<dyn BundleEventSink>> = match args.bundle_reporter_type {
+        BundleReporterType::None => None,
+        #[cfg(feature = "kafka")]
+        BundleReporterType::Kafka => {
+            let brokers = args
+                .bundle_reporter_url
+                .as_deref()
+                .ok_or_else(|| eyre::format_err!("Kafka bundle reporter requires brokers url"))?;
+            Some(Box::new(KafkaSink::new(brokers, args.bundle_reporter_topic.clone())?))
+        }
+        #[cfg(feature = "nats")]
+        BundleReporterType::Nats => {
+            let url = args
+                .bundle_reporter_url
+                .as_deref()
+                .ok_or_else(|| eyre::format_err!("NATS bundle reporter requires server url"))?;
+            Some(Box::new(NatsSink::connect(url, args.bundle_reporter_topic.clone()).await?))
+        }
+        #[allow(unreachable_patterns)]
+        reporter_type => {
+            return Err(eyre::format_err!(
+                "{reporter_type:?} bundle reporter is not enabled in this build"
+            ))
+        }
+    };
+
+    if sink.is_some() {
+        info!(
+            "Reporting bundle submissions to {:?} {}",
+            args.bundle_reporter_type, args.bundle_reporter_topic
+        );
+    }
+
+    Ok(sink.map(|sink| Arc::new(BundleReporter::new(sink))))
+}
+
 async fn check_connected_chain<M>(
     eth_client: Arc<M>,
     chain: Option<NamedChain>,
@@ -1,29 +1,43 @@
 use crate::utils::{
-    parse_address, parse_bundle_strategy, parse_duration, parse_enr, parse_label_value, parse_u256,
-    parse_uopool_mode,
+    parse_address, parse_bundle_reporter_type, parse_bundle_selector_type, parse_bundle_strategy,
+    parse_duration, parse_enr, parse_entry_point_version, parse_label_value,
+    parse_method_rate_limit, parse_signer_type, parse_u256, parse_uopool_mode,
 };
 use alloy_chains::{Chain, NamedChain};
 use clap::{ArgGroup, Parser, ValueEnum};
 use discv5::Enr;
 use ethers::types::{Address, U256};
 use expanded_pathbuf::ExpandedPathBuf;
+use silius_contracts::EntryPointVersion;
+use silius_mempool::StakeRequirements;
 use silius_metrics::label::LabelValue;
 use silius_p2p::{
     config::{gossipsub_config, Config, ConfigBuilder},
     listen_addr::{ListenAddr, ListenAddress},
 };
 use silius_primitives::{
-    bundler::BundleStrategy,
+    bundler::{BundleReporterType, BundleStrategy, SignerType},
     chain::ChainSpec,
     constants::{
-        bundler::BUNDLE_INTERVAL,
+        bundler::{BUNDLE_INTERVAL, MAX_RESUBMIT_ATTEMPTS, RESUBMIT_INTERVAL_BLOCKS},
         grpc::{BUNDLER_PORT, MEMPOOL_PORT},
-        p2p::{NODE_ENR_FILE_NAME, NODE_KEY_FILE_NAME},
-        rpc::{HTTP_PORT, WS_PORT},
+        mempool::{
+            DEFAULT_SIGNATURE_SIZE, ESTIMATION_BINARY_SEARCH_ROUNDS, MAX_BUNDLE_GAS,
+            MAX_MEMPOOL_AGE, MAX_MEMPOOL_SIZE, MAX_OPS_PER_STAKED_SENDER,
+            MAX_OPS_PER_UNSTAKED_SENDER, MAX_PARALLEL_VALIDATIONS, REORG_DEPTH_LIMIT,
+        },
+        p2p::{MAX_USER_OPERATIONS_PER_PEER, NODE_ENR_FILE_NAME, NODE_KEY_FILE_NAME},
+        rpc::{HEALTH_PORT, HTTP_PORT, WS_PORT},
+        validation::{
+            entities::{AGGREGATOR, FACTORY, PAYMASTER, SENDER},
+            sanity::MAX_CALL_DATA_SIZE,
+            simulation::SIMULATION_CACHE_SIZE,
+        },
     },
-    UoPoolMode,
+    BundleSelectorType, UoPoolMode,
 };
 use std::{
+    collections::HashMap,
     net::{IpAddr, Ipv4Addr, SocketAddr},
     path::{Path, PathBuf},
     time::Duration,
@@ -33,11 +47,13 @@ use std::{
 pub enum StorageType {
     Database,
     Memory,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
 }
 
 /// Bundler CLI args
 #[derive(Debug, Clone, Parser, PartialEq)]
-#[clap(group(ArgGroup::new("account").required(true).args(&["mnemonic_file", "private_key"])))]
+#[clap(group(ArgGroup::new("account").args(&["mnemonic_file", "private_key"])))]
 pub struct BundlerArgs {
     /// Bundler gRPC address to listen on.
     #[clap(long = "bundler.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
@@ -59,6 +75,22 @@ pub struct BundlerArgs {
     #[clap(long, conflicts_with = "mnemonic_file")]
     pub flashbots_private_key: Option<String>,
 
+    /// Sets where the signing key of the bundler is held.
+    ///
+    /// By default, this option is set to `mnemonic` (the key from the mnemonic file or the private
+    /// key). Signing with `aws-kms` or `ledger` requires building with the feature of the same
+    /// name and is only supported by the `ethereum-client` bundle strategy.
+    #[clap(long, default_value = "mnemonic", value_parser=parse_signer_type)]
+    pub signer_type: SignerType,
+
+    /// Id of the AWS KMS key the bundles are signed with.
+    #[clap(long, required_if_eq("signer_type", "aws-kms"))]
+    pub aws_kms_key_id: Option<String>,
+
+    /// Derivation path of the Ledger account the bundles are signed with.
+    #[clap(long, default_value = "m/44'/60'/0'/0/0")]
+    pub ledger_derivation_path: String,
+
     /// The bundler beneficiary address.
     #[clap(long, value_parser=parse_address)]
     pub beneficiary: Address,
@@ -95,9 +127,120 @@ pub struct BundlerArgs {
     #[clap(long)]
     pub eth_client_bundle_address: Option<String>,
 
+    /// Sets the MEV-Share relay endpoint (used with the `mev-share` bundle strategy).
+    ///
+    /// By default, this is the Flashbots relay of the chain (Mainnet or Sepolia).
+    #[clap(long)]
+    pub mev_share_endpoint: Option<String>,
+
+    /// Sets the Flashbots relay endpoints in priority order (used with the `flashbots` bundle
+    /// strategy). A relay that fails 3 times in a row is skipped for 60 seconds.
+    ///
+    /// By default, this is the Flashbots relay of the chain (Mainnet or Sepolia).
+    #[clap(long, value_delimiter = ',')]
+    pub flashbots_relay_endpoints: Vec<String>,
+
     /// Indicates whether the access list is enabled.
     #[clap(long)]
     pub enable_access_list: bool,
+
+    /// Sends bundles of all entry points in a single Multicall3 transaction.
+    ///
+    /// By default, this option is set to false.
+    /// - To enable: `--use-multicall-bundler`.
+    /// - To disable: no `--use-multicall-bundler` flag.
+    #[clap(long)]
+    pub use_multicall_bundler: bool,
+
+    /// Sets where bundle submission events are reported.
+    ///
+    /// By default, this option is set to `none`. Reporting to `kafka` or `nats` requires building
+    /// with the feature of the same name.
+    #[clap(long, default_value = "none", value_parser=parse_bundle_reporter_type)]
+    pub bundle_reporter_type: BundleReporterType,
+
+    /// Kafka brokers or NATS server URL of the bundle reporter.
+    #[clap(long)]
+    pub bundle_reporter_url: Option<String>,
+
+    /// Kafka topic or NATS subject the bundle submission events are reported to.
+    #[clap(long, default_value = "silius.bundles")]
+    pub bundle_reporter_topic: String,
+
+    /// Mempool size above which operators are alerted about the mempool congestion (warning is
+    /// already logged at 80% of it).
+    ///
+    /// By default, the mempool size isn't monitored.
+    #[clap(long)]
+    pub mempool_high_watermark: Option<usize>,
+
+    /// Mempool size below which the mempool congestion is over.
+    ///
+    /// By default, this is half of the `mempool-high-watermark`.
+    #[clap(long)]
+    pub mempool_low_watermark: Option<usize>,
+
+    /// Interval of checking the mempool size in milliseconds.
+    #[clap(long, default_value = "10000", value_parser=parse_duration)]
+    pub mempool_monitor_interval: Duration,
+
+    /// Indicates whether unprofitable bundles are skipped (see `--min-net-fee`,
+    /// `--min-bundle-revenue-multiplier` and `--always-submit`).
+    ///
+    /// By default, this option is set to false.
+    /// - To enable: `--enable-profitability-filter`.
+    /// - To disable: no `--enable-profitability-filter` flag.
+    #[clap(long)]
+    pub enable_profitability_filter: bool,
+
+    /// Minimum fee (in wei) the bundler has to earn on top of the bundle transaction cost.
+    /// Bundles earning less aren't submitted, and their user operations stay in the mempool for
+    /// the next bundle. Also available as `--min-bundle-profit`.
+    ///
+    /// By default, this option is set to `0`.
+    #[clap(long, alias = "min-bundle-profit", default_value = "0", value_parser=parse_u256)]
+    pub min_net_fee: U256,
+
+    /// Fees collected from a bundle have to be at least the bundle transaction cost times this
+    /// multiplier, otherwise the bundle is skipped.
+    ///
+    /// By default, this option is set to `1.0` (break-even).
+    #[clap(long, default_value_t = 1.0)]
+    pub min_bundle_revenue_multiplier: f64,
+
+    /// Submits unprofitable bundles as well (they are only logged).
+    ///
+    /// By default, this option is set to false.
+    /// - To enable: `--always-submit`.
+    /// - To disable: no `--always-submit` flag.
+    #[clap(long)]
+    pub always_submit: bool,
+
+    /// Path of the SQLite database every submitted bundle is logged to.
+    ///
+    /// By default, the submitted bundles aren't logged (requires the `sqlite` feature).
+    #[cfg(feature = "sqlite")]
+    #[clap(long)]
+    pub submission_log_path: Option<PathBuf>,
+
+    /// Number of blocks a bundle transaction can stay pending before it's resubmitted with fees
+    /// bumped by 10% (only for the `ethereum-client` bundle strategy).
+    #[clap(long, default_value_t = RESUBMIT_INTERVAL_BLOCKS)]
+    pub resubmit_interval_blocks: u64,
+
+    /// Number of resubmissions after which a bundle transaction is dropped. Its user operations
+    /// stay in the mempool for the next bundles.
+    #[clap(long, default_value_t = MAX_RESUBMIT_ATTEMPTS)]
+    pub max_resubmit_attempts: u8,
+
+    /// Cancels a bundle transaction dropped after `--max-resubmit-attempts` resubmissions with a
+    /// zero-value self-transfer at the same nonce.
+    ///
+    /// By default, this option is set to false.
+    /// - To enable: `--enable-cancel-on-max-bumps`.
+    /// - To disable: no `--enable-cancel-on-max-bumps` flag.
+    #[clap(long)]
+    pub enable_cancel_on_max_bumps: bool,
 }
 
 /// UoPool CLI args
@@ -116,7 +259,8 @@ pub struct UoPoolArgs {
     pub datadir: Option<ExpandedPathBuf>,
 
     /// The storage type which is used for mempool and repution
-    /// Currently, silius support `databse` and `memory` type
+    /// Currently, silius support `databse` and `memory` type (and `sqlite` when built with the
+    /// `sqlite` feature, the reputation is kept in memory then)
     #[clap(value_enum, default_value_t = StorageType::Database)]
     pub storage_type: StorageType,
 
@@ -124,14 +268,204 @@ pub struct UoPoolArgs {
     #[clap(long, default_value="5000000", value_parser=parse_u256)]
     pub max_verification_gas: U256,
 
+    /// Maximum total gas of a bundle: the gas limits (verification, call and pre-verification)
+    /// of the user operations plus the fixed entry point overhead. User operations that don't fit
+    /// are left for the next bundle.
+    ///
+    /// By default, this option is set to `10000000`.
+    #[clap(long, default_value_t = MAX_BUNDLE_GAS)]
+    pub max_bundle_gas: u64,
+
+    /// Max allowed calldata gas of the call data of one user operation (4 gas per zero byte and 16
+    /// gas per non-zero byte).
+    #[clap(long, default_value="100000", value_parser=parse_u256)]
+    pub max_calldata_gas: U256,
+
+    /// Max allowed size of the call data of one user operation in bytes.
+    ///
+    /// By default, this option is set to `3500`.
+    #[clap(long, default_value_t = MAX_CALL_DATA_SIZE)]
+    pub max_uo_calldata_size: usize,
+
+    /// Gas consumed by the entry point once per bundle (reserved out of the bundle gas limit).
+    #[clap(long, default_value = "10000", value_parser=parse_u256)]
+    pub entry_point_fixed_overhead: U256,
+
+    /// Gas consumed by the entry point for each user operation in the bundle.
+    #[clap(long, default_value = "8000", value_parser=parse_u256)]
+    pub entry_point_per_op_overhead: U256,
+
     /// Minimum stake required for entities.
     #[clap(long, value_parser=parse_u256, default_value = "1")]
     pub min_stake: U256,
 
+    /// Minimum stake required for staked accounts (overrides `--min-stake`).
+    #[clap(long, value_parser=parse_u256)]
+    pub account_min_stake: Option<U256>,
+
+    /// Minimum stake required for factories (overrides `--min-stake`).
+    #[clap(long, value_parser=parse_u256)]
+    pub factory_min_stake: Option<U256>,
+
+    /// Minimum stake required for paymasters (overrides `--min-stake`).
+    #[clap(long, value_parser=parse_u256)]
+    pub paymaster_min_stake: Option<U256>,
+
+    /// Minimum stake required for aggregators (overrides `--min-stake`).
+    #[clap(long, value_parser=parse_u256)]
+    pub aggregator_min_stake: Option<U256>,
+
+    /// Minimum unstake delay (in seconds) required for staked accounts.
+    ///
+    /// If not set, the unstake delay of the entity type isn't enforced.
+    #[clap(long)]
+    pub account_min_unstake_delay: Option<u64>,
+
+    /// Minimum unstake delay (in seconds) required for factories.
+    ///
+    /// If not set, the unstake delay of the entity type isn't enforced.
+    #[clap(long)]
+    pub factory_min_unstake_delay: Option<u64>,
+
+    /// Minimum unstake delay (in seconds) required for paymasters.
+    ///
+    /// If not set, the unstake delay of the entity type isn't enforced.
+    #[clap(long)]
+    pub paymaster_min_unstake_delay: Option<u64>,
+
+    /// Minimum unstake delay (in seconds) required for aggregators.
+    ///
+    /// If not set, the unstake delay of the entity type isn't enforced.
+    #[clap(long)]
+    pub aggregator_min_unstake_delay: Option<u64>,
+
     /// Minimum priority fee per gas.
     #[clap(long, value_parser=parse_u256, default_value = "0")]
     pub min_priority_fee_per_gas: U256,
 
+    /// Percentage added on top of the max priority fee per gas suggested to wallets.
+    #[clap(long, default_value = "10")]
+    pub priority_fee_tip_percent: u64,
+
+    /// Minimum size (in bytes) of the dummy signature the pre-verification gas is estimated with.
+    /// A longer signature sent with the user operation is estimated with its own size.
+    ///
+    /// By default, this option is set to `65` (ECDSA signature). Accounts with ERC-1271
+    /// signatures of other sizes should set the expected size.
+    #[clap(long, default_value_t = DEFAULT_SIGNATURE_SIZE)]
+    pub default_sig_size: usize,
+
+    /// Maximum number of rounds of the binary search for the estimated verification gas limit.
+    ///
+    /// By default, this option is set to `16`. More rounds give a tighter estimate for wide
+    /// verification gas ranges at the cost of more simulations per estimation.
+    #[clap(long, default_value_t = ESTIMATION_BINARY_SEARCH_ROUNDS)]
+    pub estimation_binary_search_rounds: u64,
+
+    /// Time (in seconds) a user operation can stay in the mempool before it's evicted.
+    ///
+    /// By default, this option is set to `3600` (1 hour).
+    #[clap(long, default_value_t = MAX_MEMPOOL_AGE)]
+    pub max_mempool_age: u64,
+
+    /// Maximum number of user operations in the mempool. When the mempool is full, a new user
+    /// operation evicts the least profitable one if it pays more, otherwise it's rejected.
+    ///
+    /// By default, this option is set to `10000`.
+    #[clap(long, default_value_t = MAX_MEMPOOL_SIZE)]
+    pub max_mempool_size: usize,
+
+    /// Strategy picking the bundle candidates out of the mempool: `greedy` (highest effective
+    /// gas price first), `simulation` (drops the user operations failing `simulateHandleOp`) or
+    /// `knapsack` (maximizes the total fee under the bundle gas budget).
+    ///
+    /// By default, this option is set to `greedy`.
+    #[clap(long, default_value = "greedy", value_parser=parse_bundle_selector_type)]
+    pub bundle_selector: BundleSelectorType,
+
+    /// Maximum number of user operations an unstaked sender can have in the mempool.
+    ///
+    /// By default, this option is set to `1`.
+    #[clap(long, default_value_t = MAX_OPS_PER_UNSTAKED_SENDER)]
+    pub max_ops_per_unstaked_sender: usize,
+
+    /// Maximum number of user operations a staked sender can have in the mempool.
+    ///
+    /// By default, this option is set to `4`.
+    #[clap(long, default_value_t = MAX_OPS_PER_STAKED_SENDER)]
+    pub max_ops_per_staked_sender: usize,
+
+    /// Maximum number of user operations of a batch (e.g. received over gRPC from another
+    /// bundler) that are validated concurrently.
+    ///
+    /// By default, this option is set to `8`.
+    #[clap(long, default_value_t = MAX_PARALLEL_VALIDATIONS)]
+    pub max_parallel_validations: usize,
+
+    /// Maximum number of blocks a chain reorganization is followed back. User operations
+    /// included in the reorged out blocks are added back to the mempool, and the ones whose nonce
+    /// is no longer valid are evicted.
+    ///
+    /// By default, this option is set to `64`.
+    #[clap(long, default_value_t = REORG_DEPTH_LIMIT)]
+    pub reorg_depth_limit: u64,
+
+    /// Number of user operations whose simulation results are cached until the next block, so
+    /// that resubmitting an unchanged user operation doesn't simulate it again.
+    ///
+    /// By default, this option is set to `500`. Set to `0` to disable the cache.
+    #[clap(long, default_value_t = SIMULATION_CACHE_SIZE)]
+    pub simulation_cache_size: usize,
+
+    /// Minimum fee (in gwei) a user operation has to pay to be included in a bundle. Cheaper
+    /// user operations are left out, unless the bundle would be empty without them.
+    ///
+    /// By default, this option is set to `0` (disabled).
+    #[clap(long = "min-op-value-gwei", default_value = "0")]
+    pub min_op_value_gwei: u64,
+
+    /// Multiplier of the worst-case gas cost of a user operation the deposit of its paymaster
+    /// has to cover (e.g. `2` for a 2x buffer).
+    ///
+    /// If not set, the paymaster deposit check is disabled.
+    #[clap(long)]
+    pub paymaster_deposit_multiplier: Option<u32>,
+
+    /// Whether to reject user operations without a paymaster whose sender's entry point deposit
+    /// doesn't cover the prefund.
+    ///
+    /// By default, this option is set to false.
+    /// - To enable: `--enable-sender-balance-check`.
+    #[clap(long)]
+    pub enable_sender_balance_check: bool,
+
+    /// Whether to reject user operations whose validation makes `CALLCODE` or `DELEGATECALL`
+    /// calls to contracts other than the sender's implementation (only in the standard mode).
+    ///
+    /// By default, this option is set to false.
+    /// - To enable: `--enable-call-type-check`.
+    #[clap(long)]
+    pub enable_call_type_check: bool,
+
+    /// Minimum percentage of the available gas the calls made during validation have to forward
+    /// (only in the standard mode).
+    ///
+    /// If not set, the inner call gas check is disabled.
+    #[clap(long)]
+    pub min_inner_call_gas_ratio: Option<u8>,
+
+    /// Whether to restrict the targets of `DELEGATECALL` during validation to the sender, its
+    /// implementation and the trusted implementations (only in the standard mode).
+    ///
+    /// By default, this option is set to false.
+    /// - To enable: `--enable-delegatecall-target-check`.
+    #[clap(long)]
+    pub enable_delegatecall_target_check: bool,
+
+    /// Addresses of implementations every account may `DELEGATECALL` to.
+    #[clap(long, value_delimiter=',', value_parser = parse_address)]
+    pub trusted_implementations: Vec<Address>,
+
     /// Addresses of whitelisted entities.
     #[clap(long, value_delimiter=',', value_parser = parse_address)]
     pub whitelist: Vec<Address>,
@@ -140,6 +474,12 @@ pub struct UoPoolArgs {
     #[clap(long, default_value = "standard", value_parser=parse_uopool_mode)]
     pub uopool_mode: UoPoolMode,
 
+    /// Entry point version (e.g. `0.7.0`), applied to all entry points.
+    ///
+    /// By default, the version of each entry point is detected from the deployed entry point.
+    #[clap(long, value_parser=parse_entry_point_version)]
+    pub entry_point_version: Option<EntryPointVersion>,
+
     /// P2P configuration
     #[clap(flatten)]
     pub p2p_opts: P2PArgs,
@@ -192,10 +532,11 @@ pub struct RpcArgs {
     pub http_port: u16,
 
     /// Configures the HTTP RPC API modules.
-    #[clap(long = "http.api", value_delimiter=',', default_value = "eth", value_parser = ["eth", "debug", "web3"])]
+    #[clap(long = "http.api", value_delimiter=',', default_value = "eth", value_parser = ["eth", "debug", "web3", "silius"])]
     pub http_api: Vec<String>,
 
-    /// Configures the allowed CORS domains.
+    /// Configures the allowed CORS domains (`scheme://host[:port]`, the host may start with `*.`
+    /// to allow all subdomains). Requests from other origins are rejected.
     ///
     /// By default, this option is set to `*`.
     #[clap(long = "http.corsdomain", value_delimiter = ',', default_value = "*")]
@@ -222,10 +563,10 @@ pub struct RpcArgs {
     pub ws_port: u16,
 
     /// Configures the WS RPC API modules.
-    #[clap(long = "ws.api", value_delimiter=',', default_value = "eth", value_parser = ["eth", "debug", "web3"])]
+    #[clap(long = "ws.api", value_delimiter=',', default_value = "eth", value_parser = ["eth", "debug", "web3", "silius"])]
     pub ws_api: Vec<String>,
 
-    /// Configures the allowed WS origins.
+    /// Configures the allowed WS origins (same format as `--http.corsdomain`).
     ///
     /// By default, this option is set to `*`.
     #[clap(long = "ws.origins", value_delimiter = ',', default_value = "*")]
@@ -234,6 +575,49 @@ pub struct RpcArgs {
     /// Ethereum execution client proxy HTTP RPC endpoint
     #[clap(long)]
     pub eth_client_proxy_address: Option<String>,
+
+    /// Sets the max number of HTTP RPC requests per client IP per minute.
+    ///
+    /// By default, the requests aren't rate limited.
+    #[clap(long = "http.max-requests-per-minute")]
+    pub http_max_requests_per_minute: Option<u64>,
+
+    /// Sets the max number of HTTP RPC requests per client IP per minute for specific methods, in
+    /// the form of `method=limit` (e.g. `eth_sendUserOperation=10`).
+    #[clap(long = "http.method-rate-limits", value_delimiter = ',', value_parser = parse_method_rate_limit)]
+    pub http_method_rate_limits: Vec<(String, u64)>,
+
+    /// Sets the reverse proxies whose `X-Forwarded-For` and `X-Real-IP` headers are used as the
+    /// client IP of the rate limits.
+    ///
+    /// By default, no proxy is trusted and the requests are limited per peer address.
+    #[clap(long = "http.trusted-proxies", value_delimiter = ',')]
+    pub http_trusted_proxies: Vec<IpAddr>,
+}
+
+impl UoPoolArgs {
+    /// Stake requirements of the entity types set by the `--<entity>-min-stake` and
+    /// `--<entity>-min-unstake-delay` options
+    pub fn entity_stake_requirements(&self) -> HashMap<String, StakeRequirements> {
+        [
+            (SENDER, self.account_min_stake, self.account_min_unstake_delay),
+            (FACTORY, self.factory_min_stake, self.factory_min_unstake_delay),
+            (PAYMASTER, self.paymaster_min_stake, self.paymaster_min_unstake_delay),
+            (AGGREGATOR, self.aggregator_min_stake, self.aggregator_min_unstake_delay),
+        ]
+        .into_iter()
+        .filter(|(_, min_stake, min_unstake_delay)| {
+            min_stake.is_some() || min_unstake_delay.is_some()
+        })
+        .map(|(entity, min_stake, min_unstake_delay)| {
+            let requirements = StakeRequirements {
+                min_stake: min_stake.unwrap_or(self.min_stake),
+                min_unstake_delay: min_unstake_delay.map(U256::from),
+            };
+            (entity.to_string(), requirements)
+        })
+        .collect()
+    }
 }
 
 impl RpcArgs {
@@ -316,6 +700,10 @@ pub struct P2PArgs {
     /// If empty, all IPs are allowed.
     #[clap(long = "p2p.whitelist-ips", value_delimiter = ',')]
     pub ips_whitelist: Vec<IpAddr>,
+
+    /// Maximum number of user operations accepted from a single peer per minute.
+    #[clap(long = "p2p.max-uos-per-peer", default_value_t = MAX_USER_OPERATIONS_PER_PEER)]
+    pub max_uos_per_peer: usize,
 }
 
 impl P2PArgs {
@@ -346,6 +734,7 @@ impl P2PArgs {
             .bootnodes(self.bootnodes.clone())
             .peers_whitelist(self.peers_whitelist.clone())
             .ips_whitelist(self.ips_whitelist.clone())
+            .max_uos_per_peer(self.max_uos_per_peer)
             .gs_config(gossipsub_config())
             .discv5_config(discv5::ConfigBuilder::new(listen_addr.to_listen_config()).build());
 
@@ -371,12 +760,41 @@ impl MetricsArgs {
     }
 }
 
+/// Health check HTTP server CLI args
+#[derive(Clone, Debug, Parser, PartialEq)]
+pub struct HealthArgs {
+    /// Disables the health check HTTP server.
+    ///
+    /// By default, the health check HTTP server is started.
+    /// - To disable: `--disable-health`.
+    #[clap(long)]
+    pub disable_health: bool,
+
+    /// Sets the address of the health check HTTP server (`/health/live` and `/health/ready`).
+    ///
+    /// By default, this option is set to `127.0.0.1`
+    #[clap(long = "health.addr", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub health_addr: IpAddr,
+
+    /// Sets the port of the health check HTTP server.
+    ///
+    /// By default, this option is set to `8080`
+    #[clap(long = "health.port", alias = "health-port", default_value_t = HEALTH_PORT)]
+    pub health_port: u16,
+}
+
+impl HealthArgs {
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.health_addr, self.health_port)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use discv5::enr::{CombinedKey, Enr as EnrBuilder};
     use std::{
-        net::{IpAddr, Ipv4Addr},
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
         str::FromStr,
     };
 
@@ -404,6 +822,9 @@ mod tests {
                 )),
                 private_key: None,
                 flashbots_private_key: None,
+                signer_type: SignerType::Mnemonic,
+                aws_kms_key_id: None,
+                ledger_derivation_path: String::from("m/44'/60'/0'/0/0"),
                 beneficiary: Address::from_str("0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990")
                     .unwrap(),
                 min_balance: U256::from(100000000000000000_u64),
@@ -411,9 +832,27 @@ mod tests {
                 bundle_interval: 10,
                 bundle_strategy: BundleStrategy::EthereumClient,
                 eth_client_bundle_address: None,
+                mev_share_endpoint: None,
+                flashbots_relay_endpoints: vec![],
                 bundler_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
                 bundler_port: 3002,
                 enable_access_list: false,
+                use_multicall_bundler: false,
+                bundle_reporter_type: BundleReporterType::None,
+                bundle_reporter_url: None,
+                bundle_reporter_topic: String::from("silius.bundles"),
+                mempool_high_watermark: None,
+                mempool_low_watermark: None,
+                mempool_monitor_interval: Duration::from_millis(10000),
+                enable_profitability_filter: false,
+                min_net_fee: U256::zero(),
+                min_bundle_revenue_multiplier: 1.0,
+                always_submit: false,
+                #[cfg(feature = "sqlite")]
+                submission_log_path: None,
+                resubmit_interval_blocks: 3,
+                max_resubmit_attempts: 5,
+                enable_cancel_on_max_bumps: false,
             },
             BundlerArgs::try_parse_from(args).unwrap()
         );
@@ -446,6 +885,9 @@ mod tests {
                     .unwrap()
                 ),
                 flashbots_private_key: None,
+                signer_type: SignerType::Mnemonic,
+                aws_kms_key_id: None,
+                ledger_derivation_path: String::from("m/44'/60'/0'/0/0"),
                 beneficiary: Address::from_str("0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990")
                     .unwrap(),
                 min_balance: U256::from(100000000000000000_u64),
@@ -453,9 +895,27 @@ mod tests {
                 bundle_interval: 10,
                 bundle_strategy: BundleStrategy::EthereumClient,
                 eth_client_bundle_address: None,
+                mev_share_endpoint: None,
+                flashbots_relay_endpoints: vec![],
                 bundler_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
                 bundler_port: 3002,
                 enable_access_list: false,
+                use_multicall_bundler: false,
+                bundle_reporter_type: BundleReporterType::None,
+                bundle_reporter_url: None,
+                bundle_reporter_topic: String::from("silius.bundles"),
+                mempool_high_watermark: None,
+                mempool_low_watermark: None,
+                mempool_monitor_interval: Duration::from_millis(10000),
+                enable_profitability_filter: false,
+                min_net_fee: U256::zero(),
+                min_bundle_revenue_multiplier: 1.0,
+                always_submit: false,
+                #[cfg(feature = "sqlite")]
+                submission_log_path: None,
+                resubmit_interval_blocks: 3,
+                max_resubmit_attempts: 5,
+                enable_cancel_on_max_bumps: false,
             },
             BundlerArgs::try_parse_from(args).unwrap()
         );
@@ -480,6 +940,7 @@ mod tests {
             "--manual-bundle-mode",
             "--eth-client-bundle-address",
             "http://127.0.0.1:8545",
+            "--enable-cancel-on-max-bumps",
         ];
         assert_eq!(
             BundlerArgs {
@@ -496,6 +957,9 @@ mod tests {
                     )
                     .unwrap()
                 ),
+                signer_type: SignerType::Mnemonic,
+                aws_kms_key_id: None,
+                ledger_derivation_path: String::from("m/44'/60'/0'/0/0"),
                 beneficiary: Address::from_str("0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990")
                     .unwrap(),
                 min_balance: U256::from(100000000000000000_u64),
@@ -503,14 +967,93 @@ mod tests {
                 bundle_interval: 10,
                 bundle_strategy: BundleStrategy::EthereumClient,
                 eth_client_bundle_address: Some(String::from("http://127.0.0.1:8545")),
+                mev_share_endpoint: None,
+                flashbots_relay_endpoints: vec![],
                 bundler_addr: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
                 bundler_port: 3002,
                 enable_access_list: false,
+                use_multicall_bundler: false,
+                bundle_reporter_type: BundleReporterType::None,
+                bundle_reporter_url: None,
+                bundle_reporter_topic: String::from("silius.bundles"),
+                mempool_high_watermark: None,
+                mempool_low_watermark: None,
+                mempool_monitor_interval: Duration::from_millis(10000),
+                enable_profitability_filter: false,
+                min_net_fee: U256::zero(),
+                min_bundle_revenue_multiplier: 1.0,
+                always_submit: false,
+                #[cfg(feature = "sqlite")]
+                submission_log_path: None,
+                resubmit_interval_blocks: 3,
+                max_resubmit_attempts: 5,
+                enable_cancel_on_max_bumps: true,
             },
             BundlerArgs::try_parse_from(args).unwrap()
         );
     }
 
+    #[test]
+    fn bundler_args_min_bundle_profit() {
+        let args = vec![
+            "bundlerargs",
+            "--beneficiary",
+            "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990",
+            "--min-bundle-profit",
+            "1000000000000000",
+        ];
+        let args = BundlerArgs::try_parse_from(args).unwrap();
+        assert!(!args.enable_profitability_filter);
+        assert_eq!(args.min_net_fee, U256::from(1_000_000_000_000_000_u64));
+    }
+
+    #[test]
+    fn bundler_args_signer_type() {
+        let args = vec![
+            "bundlerargs",
+            "--beneficiary",
+            "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990",
+            "--signer-type",
+            "aws-kms",
+            "--aws-kms-key-id",
+            "alias/silius",
+        ];
+        let args = BundlerArgs::try_parse_from(args).unwrap();
+        assert_eq!(args.signer_type, SignerType::AwsKms);
+        assert_eq!(args.aws_kms_key_id, Some(String::from("alias/silius")));
+
+        // the key id is required with the AWS KMS signer
+        let args = vec![
+            "bundlerargs",
+            "--beneficiary",
+            "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990",
+            "--signer-type",
+            "aws-kms",
+        ];
+        assert!(BundlerArgs::try_parse_from(args).is_err());
+    }
+
+    #[test]
+    fn bundler_args_flashbots_relay_endpoints() {
+        let args = vec![
+            "bundlerargs",
+            "--beneficiary",
+            "0x690B9A9E9aa1C9dB991C7721a92d351Db4FaC990",
+            "--bundle-strategy",
+            "flashbots",
+            "--flashbots-relay-endpoints",
+            "https://relay.flashbots.net,https://relay.ultrasound.money/",
+        ];
+        let args = BundlerArgs::try_parse_from(args).unwrap();
+        assert_eq!(
+            args.flashbots_relay_endpoints,
+            vec![
+                String::from("https://relay.flashbots.net"),
+                String::from("https://relay.ultrasound.money/")
+            ]
+        );
+    }
+
     #[test]
     fn bundler_and_uopool_args() {
         let args = vec![
@@ -579,11 +1122,44 @@ mod tests {
                 ws_api: vec![String::from("eth"), String::from("debug"), String::from("web3")],
                 ws_origins: vec![String::from("127.0.0.1:4321")],
                 eth_client_proxy_address: None,
+                http_max_requests_per_minute: None,
+                http_method_rate_limits: vec![],
+                http_trusted_proxies: vec![],
             },
             RpcArgs::try_parse_from(args).unwrap()
         );
     }
 
+    #[test]
+    fn rpc_args_rate_limits() {
+        let args = vec![
+            "rpcargs",
+            "--http",
+            "--http.max-requests-per-minute",
+            "600",
+            "--http.method-rate-limits",
+            "eth_sendUserOperation=10,eth_estimateUserOperationGas=60",
+            "--http.trusted-proxies",
+            "10.0.0.1,::1",
+        ];
+        let args = RpcArgs::try_parse_from(args).unwrap();
+        assert_eq!(args.http_max_requests_per_minute, Some(600));
+        assert_eq!(
+            args.http_method_rate_limits,
+            vec![
+                (String::from("eth_sendUserOperation"), 10),
+                (String::from("eth_estimateUserOperationGas"), 60)
+            ]
+        );
+        assert_eq!(
+            args.http_trusted_proxies,
+            vec![IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), IpAddr::V6(Ipv6Addr::LOCALHOST)]
+        );
+
+        let args = vec!["rpcargs", "--http", "--http.method-rate-limits", "eth_sendUserOperation"];
+        assert!(RpcArgs::try_parse_from(args).is_err());
+    }
+
     #[test]
     fn rpc_args_when_http_is_true_ws_is_false() {
         let args = vec![
@@ -611,6 +1187,9 @@ mod tests {
                 ws_api: vec![String::from("eth"),],
                 ws_origins: vec![String::from("*")],
                 eth_client_proxy_address: None,
+                http_max_requests_per_minute: None,
+                http_method_rate_limits: vec![],
+                http_trusted_proxies: vec![],
             },
             RpcArgs::try_parse_from(args).unwrap()
         );
@@ -643,6 +1222,9 @@ mod tests {
                 ws_api: vec![String::from("eth"), String::from("debug"), String::from("web3")],
                 ws_origins: vec![String::from("127.0.0.1:4321")],
                 eth_client_proxy_address: None,
+                http_max_requests_per_minute: None,
+                http_method_rate_limits: vec![],
+                http_trusted_proxies: vec![],
             },
             RpcArgs::try_parse_from(args).unwrap()
         );
@@ -674,6 +1256,9 @@ mod tests {
                 ws_api: vec![String::from("eth"),],
                 ws_origins: vec![String::from("*")],
                 eth_client_proxy_address: None,
+                http_max_requests_per_minute: None,
+                http_method_rate_limits: vec![],
+                http_trusted_proxies: vec![],
             },
             RpcArgs::try_parse_from(args).unwrap()
         );
@@ -694,6 +1279,9 @@ mod tests {
                 ws_api: vec![String::from("eth"),],
                 ws_origins: vec![String::from("*")],
                 eth_client_proxy_address: None,
+                http_max_requests_per_minute: None,
+                http_method_rate_limits: vec![],
+                http_trusted_proxies: vec![],
             }
             .is_enabled(),
             true
@@ -715,6 +1303,9 @@ mod tests {
                 ws_api: vec![String::from("eth"), String::from("debug"), String::from("web3")],
                 ws_origins: vec![String::from("127.0.0.1:4321")],
                 eth_client_proxy_address: None,
+                http_max_requests_per_minute: None,
+                http_method_rate_limits: vec![],
+                http_trusted_proxies: vec![],
             }
             .is_enabled(),
             true
@@ -736,6 +1327,9 @@ mod tests {
                 ws_api: vec![String::from("eth"), String::from("debug"), String::from("web3")],
                 ws_origins: vec![String::from("127.0.0.1:4321")],
                 eth_client_proxy_address: None,
+                http_max_requests_per_minute: None,
+                http_method_rate_limits: vec![],
+                http_trusted_proxies: vec![],
             }
             .is_enabled(),
             true
@@ -757,6 +1351,9 @@ mod tests {
                 ws_api: vec![String::from("eth"),],
                 ws_origins: vec![String::from("*")],
                 eth_client_proxy_address: None,
+                http_max_requests_per_minute: None,
+                http_method_rate_limits: vec![],
+                http_trusted_proxies: vec![],
             }
             .is_enabled(),
             false
@@ -792,6 +1389,8 @@ mod tests {
             "~/.silius/p2p/node-enr",
             "--p2p.whitelist-enrs",
             &binding,
+            "--p2p.max-uos-per-peer",
+            "64",
         ];
         assert_eq!(
             P2PArgs {
@@ -805,6 +1404,7 @@ mod tests {
                 node_enr: Some(PathBuf::from("~/.silius/p2p/node-enr")),
                 peers_whitelist: vec![enr],
                 ips_whitelist: vec![],
+                max_uos_per_peer: 64,
             },
             P2PArgs::try_parse_from(args).unwrap()
         )
@@ -835,4 +1435,113 @@ mod tests {
             MetricsArgs::try_parse_from(args).unwrap()
         )
     }
+
+    #[test]
+    fn uopool_args_entity_stake_requirements() {
+        let args = vec![
+            "uopoolargs",
+            "--min-stake",
+            "100",
+            "--paymaster-min-stake",
+            "1000",
+            "--paymaster-min-unstake-delay",
+            "172800",
+            "--factory-min-stake",
+            "500",
+        ];
+        let args = UoPoolArgs::try_parse_from(args).unwrap();
+        let requirements = args.entity_stake_requirements();
+        assert_eq!(requirements.len(), 2);
+        assert_eq!(
+            requirements["paymaster"],
+            StakeRequirements {
+                min_stake: U256::from(1000),
+                min_unstake_delay: Some(U256::from(172800))
+            }
+        );
+        assert_eq!(
+            requirements["factory"],
+            // the unstake delay stays unenforced unless it's set explicitly
+            StakeRequirements { min_stake: U256::from(500), min_unstake_delay: None }
+        );
+
+        let args = UoPoolArgs::try_parse_from(vec!["uopoolargs"]).unwrap();
+        assert!(args.entity_stake_requirements().is_empty());
+    }
+
+    #[test]
+    fn uopool_args_bundle_selector() {
+        let args = UoPoolArgs::try_parse_from(vec!["uopoolargs"]).unwrap();
+        assert_eq!(args.bundle_selector, BundleSelectorType::Greedy);
+
+        let args = UoPoolArgs::try_parse_from(vec!["uopoolargs", "--bundle-selector", "knapsack"])
+            .unwrap();
+        assert_eq!(args.bundle_selector, BundleSelectorType::Knapsack);
+
+        assert!(
+            UoPoolArgs::try_parse_from(vec!["uopoolargs", "--bundle-selector", "random"]).is_err()
+        );
+    }
+
+    #[test]
+    fn uopool_args_max_parallel_validations() {
+        let args = UoPoolArgs::try_parse_from(vec!["uopoolargs"]).unwrap();
+        assert_eq!(args.max_parallel_validations, 8);
+
+        let args =
+            UoPoolArgs::try_parse_from(vec!["uopoolargs", "--max-parallel-validations", "16"])
+                .unwrap();
+        assert_eq!(args.max_parallel_validations, 16);
+    }
+
+    #[test]
+    fn uopool_args_opt_in_checks() {
+        let args = UoPoolArgs::try_parse_from(vec!["uopoolargs"]).unwrap();
+        assert_eq!(args.paymaster_deposit_multiplier, None);
+        assert!(!args.enable_sender_balance_check);
+        assert!(!args.enable_call_type_check);
+        assert_eq!(args.min_inner_call_gas_ratio, None);
+        assert!(!args.enable_delegatecall_target_check);
+        assert!(args.trusted_implementations.is_empty());
+
+        let args =
+            UoPoolArgs::try_parse_from(vec!["uopoolargs", "--paymaster-deposit-multiplier", "2"])
+                .unwrap();
+        assert_eq!(args.paymaster_deposit_multiplier, Some(2));
+
+        let args = UoPoolArgs::try_parse_from(vec!["uopoolargs", "--enable-sender-balance-check"])
+            .unwrap();
+        assert!(args.enable_sender_balance_check);
+
+        let args =
+            UoPoolArgs::try_parse_from(vec!["uopoolargs", "--enable-call-type-check"]).unwrap();
+        assert!(args.enable_call_type_check);
+
+        let args =
+            UoPoolArgs::try_parse_from(vec!["uopoolargs", "--min-inner-call-gas-ratio", "50"])
+                .unwrap();
+        assert_eq!(args.min_inner_call_gas_ratio, Some(50));
+
+        let args = UoPoolArgs::try_parse_from(vec![
+            "uopoolargs",
+            "--enable-delegatecall-target-check",
+            "--trusted-implementations",
+            "0x0000000000000000000000000000000000000001",
+        ])
+        .unwrap();
+        assert!(args.enable_delegatecall_target_check);
+        assert_eq!(args.trusted_implementations, vec![Address::from_low_u64_be(1)]);
+    }
+
+    #[test]
+    fn health_args() {
+        let args = vec!["healthargs", "--health.addr", "0.0.0.0", "--health-port", "8081"];
+        let args = HealthArgs::try_parse_from(args).unwrap();
+        assert!(!args.disable_health);
+        assert_eq!(args.listen_addr(), SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8081));
+
+        let args = HealthArgs::try_parse_from(vec!["healthargs", "--disable-health"]).unwrap();
+        assert!(args.disable_health);
+        assert_eq!(args.listen_addr(), SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8080));
+    }
 }
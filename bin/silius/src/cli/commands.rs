@@ -1,9 +1,21 @@
 use super::args::{
-    BundlerAndUoPoolArgs, BundlerArgs, CreateWalletArgs, MetricsArgs, RpcArgs, UoPoolArgs,
+    BundlerAndUoPoolArgs, BundlerArgs, CreateWalletArgs, HealthArgs, MetricsArgs, RpcArgs,
+    UoPoolArgs,
 };
-use crate::bundler::{create_wallet, launch_bundler, launch_bundling, launch_rpc, launch_uopool};
+use crate::bundler::{
+    create_wallet, launch_bundler, launch_bundling, launch_health, launch_rpc, launch_uopool,
+};
+#[cfg(feature = "sqlite")]
+use crate::utils::parse_date;
+#[cfg(feature = "sqlite")]
+use chrono::{DateTime, NaiveDate};
 use clap::{Parser, Subcommand};
-use ethers::types::Address;
+use ethers::{
+    providers::{Http, Provider},
+    types::Address,
+};
+#[cfg(feature = "sqlite")]
+use silius_bundler::SubmissionLog;
 use silius_mempool::{
     init_env, DatabaseTable, UserOperationAddrOp, UserOperationOp, UserOperations,
     UserOperationsByEntity, UserOperationsBySender, WriteMap,
@@ -12,7 +24,7 @@ use silius_metrics::ethers::MetricsMiddleware;
 use silius_primitives::provider::{
     create_http_block_streams, create_http_provider, create_ws_block_streams, create_ws_provider,
 };
-use std::{future::pending, path::PathBuf, sync::Arc};
+use std::{future::pending, net::SocketAddr, path::PathBuf, sync::Arc};
 
 /// Start the bundler with all components (bundling component, user operation mempool, RPC server)
 #[derive(Debug, Parser)]
@@ -32,6 +44,10 @@ pub struct NodeCommand {
     /// All RPC args
     #[clap(flatten)]
     rpc: RpcArgs,
+
+    /// Health check server args
+    #[clap(flatten)]
+    health: HealthArgs,
 }
 
 impl NodeCommand {
@@ -62,6 +78,7 @@ impl NodeCommand {
                 self.uopool,
                 self.common.clone(),
                 self.rpc,
+                self.health,
                 self.common.metrics,
                 eth_client,
                 eth_bundle_client,
@@ -87,6 +104,7 @@ impl NodeCommand {
                     self.uopool,
                     self.common.clone(),
                     self.rpc,
+                    self.health,
                     self.common.metrics,
                     eth_client,
                     eth_client_bundle,
@@ -99,6 +117,7 @@ impl NodeCommand {
                     self.uopool,
                     self.common.clone(),
                     self.rpc,
+                    self.health,
                     self.common.metrics,
                     eth_client.clone(),
                     eth_client,
@@ -126,6 +145,10 @@ pub struct BundlerCommand {
     /// UoPool gRPC listen address
     #[clap(long, default_value = "http://127.0.0.1:3002")]
     pub uopool_grpc_listen_address: String,
+
+    /// Health check server args
+    #[clap(flatten)]
+    health: HealthArgs,
 }
 
 impl BundlerCommand {
@@ -143,6 +166,11 @@ impl BundlerCommand {
             let eth_client = Arc::new(
                 create_http_provider(&eth_client_address, self.common.poll_interval).await?,
             );
+            launch_health(
+                self.health,
+                self.uopool_grpc_listen_address.clone(),
+                Some(eth_client.clone()),
+            );
             launch_bundling(
                 self.bundler,
                 eth_client,
@@ -154,6 +182,11 @@ impl BundlerCommand {
             .await?;
         } else {
             let eth_client = Arc::new(create_ws_provider(&eth_client_address).await?);
+            launch_health(
+                self.health,
+                self.uopool_grpc_listen_address.clone(),
+                Some(eth_client.clone()),
+            );
             launch_bundling(
                 self.bundler,
                 eth_client,
@@ -179,11 +212,17 @@ pub struct UoPoolCommand {
     /// Common Bundler and UoPool args
     #[clap(flatten)]
     common: BundlerAndUoPoolArgs,
+
+    /// Health check server args
+    #[clap(flatten)]
+    health: HealthArgs,
 }
 
 impl UoPoolCommand {
     /// Execute the command
     pub async fn execute(self) -> eyre::Result<()> {
+        let uopool_grpc_listen_address =
+            format!("http://{}", SocketAddr::new(self.uopool.uopool_addr, self.uopool.uopool_port));
         if self.common.eth_client_address.clone().starts_with("http") {
             let eth_client = Arc::new(
                 create_http_provider(&self.common.eth_client_address, self.common.poll_interval)
@@ -191,6 +230,7 @@ impl UoPoolCommand {
             );
             let block_streams =
                 create_http_block_streams(eth_client.clone(), self.common.entry_points.len()).await;
+            launch_health(self.health, uopool_grpc_listen_address, Some(eth_client.clone()));
             launch_uopool(
                 self.uopool,
                 eth_client,
@@ -204,6 +244,7 @@ impl UoPoolCommand {
             let eth_client = Arc::new(create_ws_provider(&self.common.eth_client_address).await?);
             let block_streams =
                 create_ws_block_streams(eth_client.clone(), self.common.entry_points.len()).await;
+            launch_health(self.health, uopool_grpc_listen_address, Some(eth_client.clone()));
             launch_uopool(
                 self.uopool,
                 eth_client,
@@ -237,11 +278,17 @@ pub struct RpcCommand {
     /// All metrics args
     #[clap(flatten)]
     metrics: MetricsArgs,
+
+    /// Health check server args
+    #[clap(flatten)]
+    health: HealthArgs,
 }
 
 impl RpcCommand {
     /// Execute the command
     pub async fn execute(self) -> eyre::Result<()> {
+        // the RPC server doesn't connect to the execution client
+        launch_health::<Provider<Http>>(self.health, self.uopool_grpc_listen_address.clone(), None);
         launch_rpc(
             self.rpc,
             self.uopool_grpc_listen_address,
@@ -268,6 +315,105 @@ impl CreateWalletCommand {
     }
 }
 
+/// Inspect the submitted bundles
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Subcommand)]
+pub enum BundleCommand {
+    /// Print the bundles submitted in the date range
+    #[command(name = "history")]
+    History(BundleHistory),
+}
+
+#[cfg(feature = "sqlite")]
+impl BundleCommand {
+    /// Execute the command
+    pub async fn execute(self) -> eyre::Result<()> {
+        match self {
+            BundleCommand::History(command) => command.execute().await,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+#[derive(Debug, Parser)]
+pub struct BundleHistory {
+    /// Path of the SQLite database the bundles are logged to (`--submission-log-path` of the
+    /// bundler).
+    #[clap(long)]
+    submission_log_path: PathBuf,
+
+    /// First day of the range (YYYY-MM-DD, UTC).
+    #[clap(long, value_parser=parse_date)]
+    from: NaiveDate,
+
+    /// Last day of the range (YYYY-MM-DD, UTC, inclusive).
+    #[clap(long, value_parser=parse_date)]
+    to: NaiveDate,
+}
+
+#[cfg(feature = "sqlite")]
+impl BundleHistory {
+    /// Unix timestamp of the start of the day
+    fn start_of_day(date: NaiveDate) -> u64 {
+        date.and_hms_opt(0, 0, 0).expect("Midnight is valid").and_utc().timestamp() as u64
+    }
+
+    /// Formats the unix timestamp as UTC date and time
+    fn format_timestamp(timestamp: u64) -> String {
+        DateTime::from_timestamp(timestamp as i64, 0)
+            .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    }
+
+    pub async fn execute(self) -> eyre::Result<()> {
+        if !self.submission_log_path.exists() {
+            return Err(eyre::format_err!(
+                "Submission log {:?} doesn't exist",
+                self.submission_log_path
+            ));
+        }
+        if self.from > self.to {
+            return Err(eyre::format_err!("Date range {} - {} is empty", self.from, self.to));
+        }
+
+        let submission_log = SubmissionLog::connect(&self.submission_log_path).await?;
+        let to =
+            self.to.succ_opt().ok_or_else(|| eyre::format_err!("Date {} is too large", self.to))?;
+        let bundles =
+            submission_log.history(Self::start_of_day(self.from), Self::start_of_day(to)).await?;
+
+        println!(
+            "{:<19}  {:<42}  {:<66}  {:>4}  {:>10}  {:>10}  {:>14}  {:<8}  {:<19}",
+            "submitted at",
+            "entry point",
+            "tx hash",
+            "ops",
+            "gas limit",
+            "gas used",
+            "base fee",
+            "status",
+            "confirmed at"
+        );
+        for bundle in bundles.iter() {
+            println!(
+                "{:<19}  {:<42}  {:<66}  {:>4}  {:>10}  {:>10}  {:>14}  {:<8}  {:<19}",
+                Self::format_timestamp(bundle.submitted_at),
+                format!("{:?}", bundle.entry_point),
+                format!("{:?}", bundle.tx_hash),
+                bundle.ops_count,
+                bundle.gas_limit.to_string(),
+                bundle.gas_used.map(|gas| gas.to_string()).unwrap_or_else(|| "-".into()),
+                bundle.base_fee.to_string(),
+                bundle.status.to_string(),
+                bundle.confirmed_at.map(Self::format_timestamp).unwrap_or_else(|| "-".into())
+            );
+        }
+        println!("{} bundles", bundles.len());
+
+        Ok(())
+    }
+}
+
 /// Dump the database
 #[derive(Debug, Subcommand)]
 /// Represents the `Dump` command.
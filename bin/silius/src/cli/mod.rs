@@ -56,6 +56,11 @@ pub enum Commands {
     #[command(name = "create-wallet")]
     CreateWallet(commands::CreateWalletCommand),
 
+    /// Inspect the bundles submitted by the bundling component
+    #[cfg(feature = "sqlite")]
+    #[command(subcommand, name = "bundle")]
+    Bundle(commands::BundleCommand),
+
     /// For debug purposes (dump user operations from database ...)
     #[command(subcommand, name = "debug")]
     Debug(commands::DebugCommand),
@@ -86,6 +91,8 @@ pub fn run() -> eyre::Result<()> {
                     Commands::UoPool(command) => command.execute().await,
                     Commands::Rpc(command) => command.execute().await,
                     Commands::CreateWallet(command) => command.execute(),
+                    #[cfg(feature = "sqlite")]
+                    Commands::Bundle(command) => command.execute().await,
                     Commands::Debug(command) => command.execute(),
                 }
             };
@@ -1,10 +1,16 @@
+#[cfg(feature = "sqlite")]
+use chrono::NaiveDate;
 use dirs::home_dir;
 use discv5::Enr;
 use ethers::types::{Address, U256};
 use expanded_pathbuf::ExpandedPathBuf;
 use pin_utils::pin_mut;
+use silius_contracts::EntryPointVersion;
 use silius_metrics::label::LabelValue;
-use silius_primitives::{bundler::BundleStrategy, UoPoolMode};
+use silius_primitives::{
+    bundler::{BundleReporterType, BundleStrategy, SignerType},
+    BundleSelectorType, UoPoolMode,
+};
 use std::{future::Future, str::FromStr, time::Duration};
 use tracing::info;
 
@@ -35,11 +41,40 @@ pub fn parse_bundle_strategy(s: &str) -> Result<BundleStrategy, String> {
     BundleStrategy::from_str(s).map_err(|_| format!("String {s} is not a valid BundleStrategy"))
 }
 
+/// Parses BundleReporterType from string
+pub fn parse_bundle_reporter_type(s: &str) -> Result<BundleReporterType, String> {
+    BundleReporterType::from_str(s)
+        .map_err(|_| format!("String {s} is not a valid BundleReporterType"))
+}
+
+/// Parses SignerType from string
+pub fn parse_signer_type(s: &str) -> Result<SignerType, String> {
+    SignerType::from_str(s).map_err(|_| format!("String {s} is not a valid SignerType"))
+}
+
 /// Parses UoPoolMode from string
 pub fn parse_uopool_mode(s: &str) -> Result<UoPoolMode, String> {
     UoPoolMode::from_str(s).map_err(|_| format!("String {s} is not a valid UoPoolMode"))
 }
 
+/// Parses BundleSelectorType from string
+pub fn parse_bundle_selector_type(s: &str) -> Result<BundleSelectorType, String> {
+    BundleSelectorType::from_str(s)
+        .map_err(|_| format!("String {s} is not a valid BundleSelectorType"))
+}
+
+/// Parses EntryPointVersion from string
+pub fn parse_entry_point_version(s: &str) -> Result<EntryPointVersion, String> {
+    EntryPointVersion::from_str(s)
+}
+
+/// Parses date (YYYY-MM-DD) from string
+#[cfg(feature = "sqlite")]
+pub fn parse_date(s: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| format!("String {s} is not a valid date (YYYY-MM-DD)"))
+}
+
 /// Parses ENR record
 pub fn parse_enr(enr: &str) -> Result<Enr, String> {
     Enr::from_str(enr).map_err(|_| format!("Enr {enr} is not a valid enr."))
@@ -61,6 +96,14 @@ pub fn parse_label_value(label_value: &str) -> Result<LabelValue, String> {
     Ok(LabelValue::new(label.to_string(), value.to_string()))
 }
 
+/// Parses the rate limit of a JSON-RPC method (method=limit)
+pub fn parse_method_rate_limit(s: &str) -> Result<(String, u64), String> {
+    let (method, limit) =
+        s.split_once('=').ok_or_else(|| format!("String {s} is not a valid method=limit"))?;
+    let limit = limit.parse().map_err(|_| format!("Limit {limit} must be unsigned int"))?;
+    Ok((method.to_string(), limit))
+}
+
 /// Runs the future to completion or until:
 /// - `ctrl-c` is received.
 /// - `SIGTERM` is received (unix only).
@@ -0,0 +1,186 @@
+use crate::metrics::BUNDLE_RECOVERY_OPS_DROPPED;
+use ethers::{
+    contract::EthEvent,
+    providers::Middleware,
+    types::{Address, TransactionReceipt, H256, U64},
+};
+use metrics::counter;
+use silius_contracts::{entry_point::UserOperationEventFilter, EntryPoint};
+use silius_primitives::{
+    constants::{
+        bundler::{INCLUSION_POLL_INTERVAL, SUBMISSION_CONFIRMATION_TIMEOUT},
+        validation::reputation::BAN_SLACK,
+    },
+    EntryPointVersion, UserOperation,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// Removal of the user operations of failed bundles from the mempool (the bundler talks to the
+/// uopool over gRPC)
+#[async_trait::async_trait]
+pub trait RemoveFailedUserOperations: Send + Sync {
+    /// Removes the user operations from the mempool of the entry point without counting them as
+    /// included, and bans the `banned` senders
+    async fn remove_failed_user_operations(
+        &self,
+        ep: &Address,
+        uos: Vec<UserOperation>,
+        banned: Vec<Address>,
+    ) -> eyre::Result<()>;
+}
+
+/// Returns whether the `handleOps` call of the entry point reverted in the bundle transaction:
+/// either the whole transaction reverted, or the entry point emitted no `UserOperationEvent`
+/// (a multicall transaction succeeds even if one of its `handleOps` calls reverts)
+///
+/// # Arguments
+/// * `receipt` - The receipt of the bundle transaction
+/// * `ep` - The entry point of the bundle
+pub fn bundle_reverted(receipt: &TransactionReceipt, ep: &Address) -> bool {
+    receipt.status != Some(U64::from(1)) ||
+        !receipt.logs.iter().any(|log| {
+            log.address == *ep && log.topics.first() == Some(&UserOperationEventFilter::signature())
+        })
+}
+
+/// Recovers the user operations of bundles reverted on-chain. The user operations stay in the
+/// mempool after the bundle is sent, so without the recovery the ones that caused the revert
+/// would be bundled again. Each of them is simulated again and only the ones that pass are kept.
+#[derive(Clone, Debug)]
+pub struct BundleRecovery<M: Middleware> {
+    /// Connection to the execution client
+    pub eth_client: Arc<M>,
+    /// Number of consecutive failures after which the sender is banned
+    pub ban_slack: u64,
+    /// Versions of the entry points the user operations are simulated against (v0.6 if not set)
+    pub entry_point_versions: HashMap<Address, EntryPointVersion>,
+    /// Consecutive failures of the senders
+    failures: Arc<Mutex<HashMap<Address, u64>>>,
+}
+
+impl<M: Middleware + 'static> BundleRecovery<M> {
+    pub fn new(eth_client: Arc<M>) -> Self {
+        Self {
+            eth_client,
+            ban_slack: BAN_SLACK,
+            entry_point_versions: HashMap::new(),
+            failures: Default::default(),
+        }
+    }
+
+    /// Sets the version of the entry point the user operations of its bundles are simulated
+    /// against
+    pub fn with_entry_point_version(mut self, ep: Address, version: EntryPointVersion) -> Self {
+        self.entry_point_versions.insert(ep, version);
+        self
+    }
+
+    /// Number of consecutive failures of the sender
+    pub fn failures(&self, sender: &Address) -> u64 {
+        self.failures.lock().expect("Lock is not poisoned").get(sender).copied().unwrap_or_default()
+    }
+
+    /// Simulates the user operations of the failed bundle again and removes the ones that fail
+    /// from the mempool
+    ///
+    /// # Arguments
+    /// * `mempool` - The mempool the failing user operations are removed from
+    /// * `ep` - The entry point of the bundle
+    /// * `uos` - The user operations of the bundle
+    ///
+    /// # Returns
+    /// The removed user operations
+    pub async fn recover<R: RemoveFailedUserOperations>(
+        &self,
+        mempool: &R,
+        ep: Address,
+        uos: Vec<UserOperation>,
+    ) -> eyre::Result<Vec<UserOperation>> {
+        let version =
+            self.entry_point_versions.get(&ep).copied().unwrap_or(EntryPointVersion::V0_6);
+        let entry_point = EntryPoint::new(self.eth_client.clone(), ep).with_version(version);
+
+        let mut dropped = vec![];
+        for uo in uos {
+            match entry_point.simulate_handle_op(uo.user_operation.clone()).await {
+                Ok(_) => {
+                    self.failures.lock().expect("Lock is not poisoned").remove(&uo.sender);
+                }
+                Err(err) => {
+                    warn!(
+                        uo_hash = ?uo.hash,
+                        reason = ?err,
+                        "Dropping user operation of failed bundle"
+                    );
+                    dropped.push(uo);
+                }
+            }
+        }
+
+        if dropped.is_empty() {
+            return Ok(dropped);
+        }
+
+        let mut banned = vec![];
+        {
+            let mut failures = self.failures.lock().expect("Lock is not poisoned");
+            for uo in dropped.iter() {
+                let count = failures.entry(uo.sender).or_default();
+                *count += 1;
+                if *count >= self.ban_slack {
+                    failures.remove(&uo.sender);
+                    banned.push(uo.sender);
+                }
+            }
+        }
+        banned.sort();
+        banned.dedup();
+
+        mempool.remove_failed_user_operations(&ep, dropped.clone(), banned).await?;
+        counter!(BUNDLE_RECOVERY_OPS_DROPPED).increment(dropped.len() as u64);
+
+        Ok(dropped)
+    }
+
+    /// Waits in the background for the receipt of the bundle transaction and recovers its user
+    /// operations if the `handleOps` call of the entry point reverted (the user operations that
+    /// still pass the simulation are retried with the next bundle of the entry point)
+    pub fn watch<R: RemoveFailedUserOperations + 'static>(
+        &self,
+        mempool: Arc<R>,
+        ep: Address,
+        tx_hash: H256,
+        uos: Vec<UserOperation>,
+    ) {
+        let recovery = self.clone();
+        tokio::spawn(async move {
+            let deadline = Instant::now() + Duration::from_secs(SUBMISSION_CONFIRMATION_TIMEOUT);
+            loop {
+                match recovery.eth_client.get_transaction_receipt(tx_hash).await {
+                    Ok(Some(receipt)) if !bundle_reverted(&receipt, &ep) => return,
+                    Ok(Some(_)) => break,
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to get receipt of bundle {tx_hash:?}: {e:?}"),
+                }
+
+                if Instant::now() >= deadline {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_secs(INCLUSION_POLL_INTERVAL)).await;
+            }
+
+            info!(
+                "Bundle {tx_hash:?} reverted for entry point {ep:?}, simulating its {} user operations again",
+                uos.len()
+            );
+            if let Err(e) = recovery.recover(mempool.as_ref(), ep, uos).await {
+                warn!("Failed to recover user operations of bundle {tx_hash:?}: {e:?}");
+            }
+        });
+    }
+}
@@ -0,0 +1,191 @@
+use ethers::{
+    contract::parse_log,
+    providers::Middleware,
+    types::{Address, TransactionReceipt, H256, I256, U256},
+};
+use serde::Serialize;
+use silius_contracts::entry_point::UserOperationEventFilter;
+use silius_primitives::constants::bundler::{
+    INCLUSION_POLL_INTERVAL, SUBMISSION_CONFIRMATION_TIMEOUT,
+};
+use std::{
+    fmt::Debug,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// Event emitted once the submitted bundle is included (or its inclusion timed out)
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleSubmissionEvent {
+    /// Unix timestamp of the submission in milliseconds
+    pub timestamp_ms: u64,
+    /// Entry point the bundle is sent to
+    pub entry_point: Address,
+    /// Hash returned by the bundle client
+    pub tx_hash: H256,
+    /// Number of user operations in the bundle
+    pub op_count: usize,
+    /// Gas used by the bundle transaction (None if the bundle isn't included before the timeout)
+    pub gas_used: Option<U256>,
+    /// Beneficiary of the bundle
+    pub beneficiary: Address,
+    /// Fees collected by the beneficiary minus the transaction cost (None if the bundle isn't
+    /// included before the timeout)
+    pub net_fee: Option<I256>,
+}
+
+/// Destination of the bundle submission events
+#[async_trait::async_trait]
+pub trait BundleEventSink: Debug + Send + Sync {
+    /// Emits the event to the sink
+    async fn emit(&self, event: &BundleSubmissionEvent) -> eyre::Result<()>;
+}
+
+/// Reports bundle submissions to an external event pipeline
+#[derive(Debug)]
+pub struct BundleReporter {
+    /// Sink the events are emitted to
+    pub sender: Box<dyn BundleEventSink>,
+}
+
+impl BundleReporter {
+    pub fn new(sender: Box<dyn BundleEventSink>) -> Self {
+        Self { sender }
+    }
+
+    /// Emits the event, failures are only logged since reporting must not affect bundling
+    pub async fn report(&self, event: BundleSubmissionEvent) {
+        if let Err(e) = self.sender.emit(&event).await {
+            warn!("Failed to report bundle submission {:?}: {e:?}", event.tx_hash);
+        }
+    }
+
+    /// Waits in the background for the receipt of the bundle transaction, fills the gas used and
+    /// the net fee of the event from it and emits the event (without them if there is no receipt
+    /// before the timeout)
+    pub fn report_on_receipt<M: Middleware + 'static>(
+        self: Arc<Self>,
+        eth_client: Arc<M>,
+        mut event: BundleSubmissionEvent,
+    ) {
+        tokio::spawn(async move {
+            let deadline = Instant::now() + Duration::from_secs(SUBMISSION_CONFIRMATION_TIMEOUT);
+            loop {
+                match eth_client.get_transaction_receipt(event.tx_hash).await {
+                    Ok(Some(receipt)) => {
+                        event.gas_used = receipt.gas_used;
+                        event.net_fee = Some(net_fee(&receipt, event.entry_point));
+                        break;
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to get receipt of bundle {:?}: {e:?}", event.tx_hash),
+                }
+
+                if Instant::now() >= deadline {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_secs(INCLUSION_POLL_INTERVAL)).await;
+            }
+
+            self.report(event).await;
+        });
+    }
+}
+
+/// Returns the fees paid by the user operations of the bundle (`actualGasCost` of their
+/// `UserOperationEvent`s) minus the cost of the bundle transaction
+///
+/// # Arguments
+/// * `receipt` - The receipt of the bundle transaction
+/// * `entry_point` - The entry point the bundle was sent to
+pub fn net_fee(receipt: &TransactionReceipt, entry_point: Address) -> I256 {
+    let fees = receipt
+        .logs
+        .iter()
+        .filter(|log| log.address == entry_point)
+        .filter_map(|log| parse_log::<UserOperationEventFilter>(log.clone()).ok())
+        .fold(U256::zero(), |fees, event| fees.saturating_add(event.actual_gas_cost));
+    let tx_cost = receipt
+        .gas_used
+        .unwrap_or_default()
+        .saturating_mul(receipt.effective_gas_price.unwrap_or_default());
+
+    I256::from_raw(fees) - I256::from_raw(tx_cost)
+}
+
+/// Publishes the events as JSON to a NATS subject
+#[cfg(feature = "nats")]
+#[derive(Debug)]
+pub struct NatsSink {
+    pub client: async_nats::Client,
+    pub subject: String,
+}
+
+#[cfg(feature = "nats")]
+impl NatsSink {
+    /// Connects to the NATS server
+    ///
+    /// # Arguments
+    /// * `url` - The URL of the NATS server
+    /// * `subject` - The subject the events are published to
+    pub async fn connect(url: &str, subject: String) -> eyre::Result<Self> {
+        let client = async_nats::connect(url).await?;
+        Ok(Self { client, subject })
+    }
+}
+
+#[cfg(feature = "nats")]
+#[async_trait::async_trait]
+impl BundleEventSink for NatsSink {
+    async fn emit(&self, event: &BundleSubmissionEvent) -> eyre::Result<()> {
+        let payload = serde_json::to_vec(event)?;
+        self.client.publish(self.subject.clone(), payload.into()).await?;
+        Ok(())
+    }
+}
+
+/// Produces the events as JSON to a Kafka topic, keyed by the entry point
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    pub producer: rdkafka::producer::FutureProducer,
+    pub topic: String,
+}
+
+#[cfg(feature = "kafka")]
+impl Debug for KafkaSink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaSink").field("topic", &self.topic).finish()
+    }
+}
+
+#[cfg(feature = "kafka")]
+impl KafkaSink {
+    /// Creates the Kafka producer
+    ///
+    /// # Arguments
+    /// * `brokers` - Comma separated list of the Kafka brokers
+    /// * `topic` - The topic the events are produced to
+    pub fn new(brokers: &str, topic: String) -> eyre::Result<Self> {
+        let producer = rdkafka::ClientConfig::new().set("bootstrap.servers", brokers).create()?;
+        Ok(Self { producer, topic })
+    }
+}
+
+#[cfg(feature = "kafka")]
+#[async_trait::async_trait]
+impl BundleEventSink for KafkaSink {
+    async fn emit(&self, event: &BundleSubmissionEvent) -> eyre::Result<()> {
+        let payload = serde_json::to_vec(event)?;
+        let key = format!("{:?}", event.entry_point);
+        self.producer
+            .send(
+                rdkafka::producer::FutureRecord::to(&self.topic).payload(&payload).key(&key),
+                std::time::Duration::from_secs(0),
+            )
+            .await
+            .map_err(|(e, _)| eyre::eyre!(e))?;
+        Ok(())
+    }
+}
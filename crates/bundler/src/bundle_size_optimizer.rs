@@ -0,0 +1,48 @@
+use crate::ProfitabilityFilter;
+use ethers::types::U256;
+use silius_primitives::UserOperation;
+
+/// Removes user operations whose fees are too low to be worth the bundle space ("dust")
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BundleSizeOptimizer {
+    /// Minimum fee (in wei) the user operation has to pay to be included in the bundle
+    pub min_op_value: U256,
+}
+
+impl BundleSizeOptimizer {
+    pub fn new(min_op_value: U256) -> Self {
+        Self { min_op_value }
+    }
+
+    /// Fee the bundler collects from the user operation (all of its gas at the effective gas
+    /// price)
+    pub fn op_value(uo: &UserOperation, base_fee: U256) -> U256 {
+        ProfitabilityFilter::effective_gas_price(uo, base_fee)
+            .saturating_mul(ProfitabilityFilter::estimated_gas_used(uo))
+    }
+
+    /// Removes the user operations worth less than the minimum value, keeping the order of the
+    /// rest
+    ///
+    /// # Arguments
+    /// * `uos` - Sorted user operations
+    /// * `base_fee` - Base fee per gas of the latest block
+    ///
+    /// # Returns
+    /// The remaining user operations (all of them if every user operation is dust, so that the
+    /// bundle isn't empty)
+    pub fn optimize(&self, uos: Vec<UserOperation>, base_fee: U256) -> Vec<UserOperation> {
+        if self.min_op_value.is_zero() {
+            return uos;
+        }
+
+        let (valuable, dust): (Vec<_>, Vec<_>) =
+            uos.into_iter().partition(|uo| Self::op_value(uo, base_fee) >= self.min_op_value);
+
+        if valuable.is_empty() {
+            dust
+        } else {
+            valuable
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{
+    cancel_transaction::cancel_transaction, metrics::BUNDLES_RESUBMITTED,
+    nonce_manager::not_broadcast,
+};
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::Middleware,
+    signers::Signer,
+    types::{transaction::eip2718::TypedTransaction, H256, U256, U64},
+};
+use eyre::eyre;
+use metrics::counter;
+use silius_primitives::constants::bundler::{
+    MAX_RESUBMIT_ATTEMPTS, RESUBMIT_FEE_INCREASE_PERC, RESUBMIT_INTERVAL_BLOCKS,
+};
+use std::{sync::Arc, time::Duration};
+use tracing::{info, warn};
+
+/// Time interval for checking whether a submitted bundle transaction is included
+const TRACKER_POLL_INTERVAL: Duration = Duration::from_millis(75);
+
+/// Tracks a submitted bundle transaction until it's included. A transaction pending for too many
+/// blocks is resubmitted with the same nonce and bumped fees, so a fee spike doesn't leave the
+/// bundler stuck behind its own transaction.
+#[derive(Clone, Debug)]
+pub struct BundleTracker {
+    /// Number of blocks a bundle transaction can stay pending before it's resubmitted
+    resubmit_interval_blocks: u64,
+    /// Number of resubmissions after which the bundle is dropped
+    max_resubmit_attempts: u8,
+    /// Whether the dropped bundle transaction is cancelled
+    cancel_on_max_bumps: bool,
+    /// Time interval for polling the execution client
+    poll_interval: Duration,
+}
+
+impl Default for BundleTracker {
+    fn default() -> Self {
+        Self::new(RESUBMIT_INTERVAL_BLOCKS, MAX_RESUBMIT_ATTEMPTS)
+    }
+}
+
+impl BundleTracker {
+    /// Create a new bundle tracker
+    ///
+    /// # Arguments
+    /// * `resubmit_interval_blocks` - Number of blocks after which a pending bundle is resubmitted
+    /// * `max_resubmit_attempts` - Number of resubmissions after which the bundle is dropped
+    ///
+    /// # Returns
+    /// * `BundleTracker` - A new bundle tracker
+    pub fn new(resubmit_interval_blocks: u64, max_resubmit_attempts: u8) -> Self {
+        Self {
+            resubmit_interval_blocks: resubmit_interval_blocks.max(1),
+            max_resubmit_attempts,
+            cancel_on_max_bumps: false,
+            poll_interval: TRACKER_POLL_INTERVAL,
+        }
+    }
+
+    /// Sets whether the bundle transaction dropped after `max_resubmit_attempts` resubmissions is
+    /// cancelled with a zero-value self-transfer at the same nonce
+    pub fn with_cancel_on_max_bumps(mut self, cancel_on_max_bumps: bool) -> Self {
+        self.cancel_on_max_bumps = cancel_on_max_bumps;
+        self
+    }
+
+    /// Sets the time interval for polling the execution client
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Builds the resubmission of a stuck bundle transaction: the same transaction with fees
+    /// increased by 10 % (enough for the execution clients to accept it as a replacement).
+    pub fn resubmission_transaction(tx: &TypedTransaction) -> TypedTransaction {
+        let mut tx = tx.clone();
+        match tx {
+            TypedTransaction::Eip1559(ref mut tx) => {
+                tx.max_fee_per_gas = tx.max_fee_per_gas.map(bump_fee);
+                tx.max_priority_fee_per_gas = tx.max_priority_fee_per_gas.map(bump_fee);
+            }
+            _ => {
+                if let Some(gas_price) = tx.gas_price() {
+                    tx.set_gas_price(bump_fee(gas_price));
+                }
+            }
+        }
+        tx
+    }
+
+    /// Sends the bundle transaction and waits until it's included, resubmitting it with bumped
+    /// fees every `resubmit_interval_blocks` blocks.
+    ///
+    /// When the bundle is dropped after `max_resubmit_attempts` resubmissions, an error is returned
+    /// and, if enabled, the last transaction is cancelled to free the nonce. User operations are
+    /// only removed from the mempool once they are included, so the ones of the dropped bundle are
+    /// picked up again by one of the next bundles.
+    ///
+    /// # Arguments
+    /// * `client` - Signing connection to the Ethereum execution client
+    /// * `tx` - The bundle transaction
+    ///
+    /// # Returns
+    /// * `H256` - The hash of the included transaction
+    pub async fn send_and_track<M: Middleware + 'static, S: Signer + 'static>(
+        &self,
+        client: &SignerMiddleware<Arc<M>, S>,
+        mut tx: TypedTransaction,
+    ) -> eyre::Result<H256> {
+        // every resubmission has to replace the previous transaction, so the nonce is fixed here
+        client.fill_transaction(&mut tx, None).await.map_err(not_broadcast)?;
+
+        let mut tx_hashes = Vec::new();
+        let mut attempts = 0_u8;
+
+        loop {
+            let tx_hash = match client.send_transaction(tx.clone(), None).await {
+                Ok(pending_tx) => pending_tx.tx_hash(),
+                // nothing was broadcast yet, so the nonce is still unused
+                Err(err) if tx_hashes.is_empty() => return Err(not_broadcast(err)),
+                // one of the previous transactions could be included in the meantime
+                Err(err) => match self.included(client, &tx_hashes).await? {
+                    Some(tx_hash) => return Ok(tx_hash),
+                    None => return Err(err.into()),
+                },
+            };
+            tx_hashes.push(tx_hash);
+
+            let submitted_at = self.block_number(client).await?;
+
+            loop {
+                tokio::time::sleep(self.poll_interval).await;
+
+                if let Some(tx_hash) = self.included(client, &tx_hashes).await? {
+                    return Ok(tx_hash);
+                }
+
+                if self.block_number(client).await? >=
+                    submitted_at + U64::from(self.resubmit_interval_blocks)
+                {
+                    break;
+                }
+            }
+
+            if attempts >= self.max_resubmit_attempts {
+                warn!(
+                    "Bundle transaction {tx_hash:?} (nonce {:?}) dropped after {attempts} resubmissions",
+                    tx.nonce()
+                );
+                if self.cancel_on_max_bumps {
+                    if let Err(err) = cancel_transaction(tx_hash, client.signer(), client).await {
+                        warn!(
+                            "Failed to cancel the dropped bundle transaction {tx_hash:?}: {err:?}"
+                        );
+                    }
+                }
+                return Err(eyre!(
+                    "Bundle transaction {tx_hash:?} not included after {attempts} resubmissions"
+                ));
+            }
+
+            attempts += 1;
+            tx = Self::resubmission_transaction(&tx);
+            counter!(BUNDLES_RESUBMITTED).increment(1);
+
+            info!(
+                "Bundle transaction {tx_hash:?} pending for {} blocks, resubmitting with bumped fees (attempt {attempts}/{})",
+                self.resubmit_interval_blocks, self.max_resubmit_attempts
+            );
+        }
+    }
+
+    /// Returns the hash of the transaction that is included (if any)
+    async fn included<M: Middleware + 'static>(
+        &self,
+        client: &M,
+        tx_hashes: &[H256],
+    ) -> eyre::Result<Option<H256>> {
+        for tx_hash in tx_hashes {
+            let tx = client.get_transaction(*tx_hash).await?;
+            if tx.and_then(|tx| tx.block_number).is_some() {
+                return Ok(Some(*tx_hash));
+            }
+        }
+
+        Ok(None)
+    }
+
+    async fn block_number<M: Middleware + 'static>(&self, client: &M) -> eyre::Result<U64> {
+        Ok(client.get_block_number().await?)
+    }
+}
+
+fn bump_fee(fee: U256) -> U256 {
+    fee.saturating_mul(U256::from(100 + RESUBMIT_FEE_INCREASE_PERC)) / U256::from(100)
+}
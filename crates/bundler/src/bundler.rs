@@ -1,15 +1,27 @@
+#[cfg(feature = "sqlite")]
+use crate::submission_log::{unix_timestamp, BundleOp, BundleSubmission, SubmissionLog};
+use crate::{
+    bundle_reporter::{BundleReporter, BundleSubmissionEvent},
+    nonce_manager::BundlerNonceManager,
+    profitability_filter::ProfitabilityFilter,
+};
 use alloy_chains::Chain;
 use ethers::{
     providers::Middleware,
-    signers::Signer,
     types::{
-        transaction::eip2718::TypedTransaction, Address, Eip1559TransactionRequest, H256, U256, U64,
+        transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes,
+        Eip1559TransactionRequest, H256, U256, U64,
     },
 };
-use silius_contracts::entry_point::EntryPointAPI;
-use silius_primitives::{simulation::StorageMap, UserOperation, UserOperationHash, Wallet};
-use std::sync::Arc;
-use tracing::{info, trace};
+use silius_contracts::{entry_point::UserOpsPerAggregator, Aggregator, EntryPoint};
+use silius_primitives::{
+    simulation::StorageMap, EntryPointVersion, RelayStatus, UserOperation, UserOperationHash,
+};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tracing::{info, trace, warn};
 
 /// A trait for sending the bundler of user operations
 #[async_trait::async_trait]
@@ -27,6 +39,12 @@ pub trait SendBundleOp: Send + Sync + 'static {
         bundle: TypedTransaction,
         storage_map: StorageMap,
     ) -> eyre::Result<H256>;
+
+    /// Returns the status of the relays the bundles are sent to (empty if the bundles aren't
+    /// sent to relays)
+    fn relay_status(&self) -> Vec<RelayStatus> {
+        vec![]
+    }
 }
 
 /// The `Bundler` struct is used to represent a bundler with necessary properties
@@ -36,12 +54,14 @@ where
     M: Middleware + 'static,
     S: SendBundleOp,
 {
-    /// Bundler's wallet
-    pub wallet: Wallet,
+    /// Address of the bundler's account the bundles are sent from
+    pub address: Address,
     /// Beneficiary address where the gas is refunded after execution
     pub beneficiary: Address,
     /// Entry point contract address
     pub entry_point: Address,
+    /// Version of the entry point the bundles are encoded for
+    pub entry_point_version: EntryPointVersion,
     /// Chain the bundler is running on
     pub chain: Chain,
     /// Minimum balance required
@@ -52,6 +72,15 @@ where
     pub client: Arc<S>,
     /// Whether add access list into tx
     pub enable_access_list: bool,
+    /// Reporter of bundle submissions (None if not enabled)
+    pub reporter: Option<Arc<BundleReporter>>,
+    /// Filter of unprofitable bundles (None if every bundle is submitted)
+    pub profitability_filter: Option<ProfitabilityFilter>,
+    /// Log of the submitted bundles (None if not enabled)
+    #[cfg(feature = "sqlite")]
+    pub submission_log: Option<Arc<SubmissionLog>>,
+    /// Nonce manager of the bundler's account (shared by the bundlers using the same account)
+    pub nonce_manager: BundlerNonceManager,
 }
 
 impl<M, S> Bundler<M, S>
@@ -65,7 +94,7 @@ where
     /// * `Self` - A new `Bundler` instance
     #[allow(clippy::too_many_arguments)]
     pub fn new(
-        wallet: Wallet,
+        address: Address,
         beneficiary: Address,
         entry_point: Address,
         chain: Chain,
@@ -74,15 +103,169 @@ where
         client: Arc<S>,
         enable_access_list: bool,
     ) -> Self {
+        let nonce_manager = BundlerNonceManager::new(address);
         Self {
-            wallet,
+            address,
             beneficiary,
             entry_point,
+            entry_point_version: EntryPointVersion::V0_6,
             chain,
             min_balance,
             eth_client,
             client,
             enable_access_list,
+            reporter: None,
+            profitability_filter: None,
+            #[cfg(feature = "sqlite")]
+            submission_log: None,
+            nonce_manager,
+        }
+    }
+
+    /// Sets the version of the entry point the bundles are encoded for (default v0.6)
+    pub fn with_entry_point_version(mut self, entry_point_version: EntryPointVersion) -> Self {
+        self.entry_point_version = entry_point_version;
+        self
+    }
+
+    /// Returns the beneficiary of the bundle (bundler's account if its balance is too low)
+    pub(crate) async fn get_beneficiary(&self) -> eyre::Result<Address> {
+        let balance = self.eth_client.get_balance(self.address, None).await?;
+        Ok(if balance < self.min_balance { self.address } else { self.beneficiary })
+    }
+
+    /// Records the submitted bundle in the [SubmissionLog](SubmissionLog), failures are only
+    /// logged since the bundle is already sent
+    #[cfg(feature = "sqlite")]
+    pub(crate) async fn log_submission(
+        &self,
+        tx_hash: H256,
+        gas_limit: U256,
+        uos: &[UserOperation],
+    ) {
+        let submission_log = match self.submission_log.as_ref() {
+            Some(submission_log) => submission_log,
+            None => return,
+        };
+
+        let base_fee = match self.eth_client.get_block(BlockNumber::Latest).await {
+            Ok(block) => block.and_then(|block| block.base_fee_per_gas).unwrap_or_default(),
+            Err(e) => {
+                warn!("Failed to get base fee for bundle {tx_hash:?}: {e:?}");
+                U256::zero()
+            }
+        };
+
+        let bundle = BundleSubmission {
+            entry_point: self.entry_point,
+            tx_hash,
+            gas_limit,
+            base_fee,
+            net_fee: None,
+            submitted_at: unix_timestamp(),
+            ops: uos.iter().map(BundleOp::from).collect(),
+        };
+        if let Err(e) = submission_log.record_submission(&bundle).await {
+            warn!("Failed to log bundle submission {tx_hash:?}: {e:?}");
+        }
+    }
+
+    /// Records the confirmation of the bundle transaction in the [SubmissionLog](SubmissionLog)
+    /// once its receipt is available
+    #[cfg(feature = "sqlite")]
+    pub(crate) fn watch_confirmation(&self, tx_hash: H256) {
+        if let Some(submission_log) = self.submission_log.as_ref() {
+            submission_log.clone().watch_confirmation(self.eth_client.clone(), tx_hash);
+        }
+    }
+
+    /// Returns the `handleOps` transaction of the entry point for the user operations, or the
+    /// `handleAggregatedOps` transaction if some of them use a signature aggregator
+    pub(crate) async fn handle_ops(
+        &self,
+        uos: &[UserOperation],
+        beneficiary: Address,
+    ) -> eyre::Result<TypedTransaction> {
+        let ep = EntryPoint::new(self.eth_client.clone(), self.entry_point)
+            .with_version(self.entry_point_version);
+        if uos.iter().all(|uo| uo.aggregator.is_none()) {
+            return Ok(ep.handle_ops_tx(
+                uos.iter().map(|uo| uo.user_operation.clone()).collect(),
+                beneficiary,
+            ));
+        }
+
+        // the user operations are grouped by the aggregator in the order they were selected (the
+        // ones without an aggregator go under the zero address)
+        let mut groups: Vec<(Option<Address>, Vec<UserOperation>)> = vec![];
+        for uo in uos {
+            match groups.iter_mut().find(|(aggregator, _)| *aggregator == uo.aggregator) {
+                Some((_, group)) => group.push(uo.clone()),
+                None => groups.push((uo.aggregator, vec![uo.clone()])),
+            }
+        }
+
+        let mut uos_per_aggregator = vec![];
+        for (aggregator, uos) in groups {
+            let signature = match aggregator {
+                Some(aggregator) => {
+                    Aggregator::new(self.eth_client.clone(), aggregator)
+                        .with_version(self.entry_point_version)
+                        .aggregate_signatures(
+                            uos.iter().map(|uo| uo.user_operation.clone()).collect(),
+                        )
+                        .await?
+                }
+                None => Bytes::default(),
+            };
+            uos_per_aggregator.push(UserOpsPerAggregator {
+                user_ops: uos.into_iter().map(|uo| uo.user_operation.into()).collect(),
+                aggregator: aggregator.unwrap_or_default(),
+                signature,
+            });
+        }
+
+        Ok(ep.handle_aggregated_ops_tx(uos_per_aggregator, beneficiary))
+    }
+
+    /// Returns whether the bundle transaction should be submitted according to the
+    /// [ProfitabilityFilter](ProfitabilityFilter)
+    pub(crate) async fn should_submit(
+        &self,
+        uos: &[UserOperation],
+        tx: &TypedTransaction,
+    ) -> eyre::Result<bool> {
+        let filter = match self.profitability_filter.as_ref() {
+            Some(filter) => filter,
+            None => return Ok(true),
+        };
+
+        let base_fee = self
+            .eth_client
+            .get_block(BlockNumber::Latest)
+            .await?
+            .and_then(|block| block.base_fee_per_gas)
+            .unwrap_or_default();
+        let submission_gas = tx.gas().cloned().unwrap_or_default();
+
+        if filter.is_profitable(uos, base_fee, submission_gas) {
+            return Ok(true);
+        }
+
+        let gross_fees = ProfitabilityFilter::gross_fees(uos, base_fee);
+        let required = filter.required_revenue(base_fee, submission_gas);
+        if filter.always_submit {
+            warn!(
+                "Submitting unprofitable bundle (always submit is set), fees: {gross_fees}, required: {required}, min net fee: {}",
+                filter.min_net_fee
+            );
+            Ok(true)
+        } else {
+            info!(
+                "Skipping unprofitable bundle, fees: {gross_fees}, required: {required}, min net fee: {}, base fee: {base_fee}, gas: {submission_gas}",
+                filter.min_net_fee
+            );
+            Ok(false)
         }
     }
 
@@ -93,25 +276,15 @@ where
     /// * `uos` - Slice of [UserOperations](UserOperation)
     ///
     /// # Returns
-    /// * `TypedTransaction` - A [TypedTransaction](TypedTransaction)
-    async fn create_bundle(&self, uos: &[UserOperation]) -> eyre::Result<TypedTransaction> {
-        let ep = EntryPointAPI::new(self.entry_point, self.eth_client.clone());
-
-        let nonce =
-            self.eth_client.get_transaction_count(self.wallet.signer.address(), None).await?;
-        let balance = self.eth_client.get_balance(self.wallet.signer.address(), None).await?;
-        let beneficiary = if balance < self.min_balance {
-            self.wallet.signer.address()
-        } else {
-            self.beneficiary
-        };
+    /// * `(TypedTransaction, Address)` - A [TypedTransaction](TypedTransaction) and the beneficiary
+    ///   of the bundle
+    async fn create_bundle(
+        &self,
+        uos: &[UserOperation],
+    ) -> eyre::Result<(TypedTransaction, Address)> {
+        let beneficiary = self.get_beneficiary().await?;
 
-        let mut tx: TypedTransaction = ep
-            .handle_ops(
-                uos.iter().cloned().map(|uo| uo.user_operation.into()).collect(),
-                beneficiary,
-            )
-            .tx;
+        let mut tx = self.handle_ops(uos, beneficiary).await?;
 
         let accesslist = if self.enable_access_list {
             let accesslist = self.eth_client.create_access_list(&tx, None).await?.access_list;
@@ -121,6 +294,8 @@ where
             Default::default()
         };
         let estimated_gas = self.eth_client.estimate_gas(&tx, None).await?;
+        // taken last, so that the nonce isn't lost when the bundle can't be created
+        let nonce = self.nonce_manager.next_nonce(self.eth_client.as_ref()).await?;
 
         let mut max_fee_per_gas: U256 = U256::zero();
         let mut max_priority_fee_per_gas: U256 = U256::zero();
@@ -132,7 +307,7 @@ where
 
         tx = TypedTransaction::Eip1559(Eip1559TransactionRequest {
             to: tx.to().cloned(),
-            from: Some(self.wallet.signer.address()),
+            from: Some(self.address),
             data: tx.data().cloned(),
             chain_id: Some(U64::from(self.chain.id())),
             max_priority_fee_per_gas: Some(max_priority_fee_per_gas / uos.len()),
@@ -143,7 +318,7 @@ where
             access_list: accesslist,
         });
 
-        Ok(tx)
+        Ok((tx, beneficiary))
     }
 
     /// Send a bundle of [UserOperations](UserOperation)
@@ -171,17 +346,68 @@ where
         );
         trace!("Bundle content: {uos:?}");
 
-        let bundle = self.create_bundle(uos).await?;
-        let hash = self.client.send_bundle(bundle, storage_map).await?;
+        let (mut bundle, beneficiary) = self.create_bundle(uos).await?;
+        let nonce = bundle.nonce().cloned().unwrap_or_default();
+        match self.should_submit(uos, &bundle).await {
+            Ok(true) => {}
+            res => {
+                self.nonce_manager.release(nonce);
+                return res.map(|_| None);
+            }
+        }
+
+        #[cfg(feature = "sqlite")]
+        let gas_limit = bundle.gas().cloned().unwrap_or_default();
+        let hash = match self.client.send_bundle(bundle.clone(), storage_map.clone()).await {
+            Ok(hash) => hash,
+            Err(e) if BundlerNonceManager::is_nonce_error(&e) => {
+                warn!("Bundle nonce {nonce} already used, resyncing nonce and retrying: {e:?}");
+                self.nonce_manager.resync(self.eth_client.as_ref()).await?;
+                let nonce = self.nonce_manager.next_nonce(self.eth_client.as_ref()).await?;
+                bundle.set_nonce(nonce);
+                self.client.send_bundle(bundle, storage_map).await.map_err(|e| {
+                    self.nonce_manager.send_failed(nonce, &e);
+                    e
+                })?
+            }
+            Err(e) => {
+                self.nonce_manager.send_failed(nonce, &e);
+                return Err(e);
+            }
+        };
 
         info!(
             "Bundle successfully sent, hash: {:?}, account: {:?}, entry point: {:?}, beneficiary: {:?}",
             hash,
-            self.wallet.signer.address(),
+            self.address,
             self.entry_point,
-            self.beneficiary
+            beneficiary
         );
 
+        if let Some(reporter) = self.reporter.as_ref() {
+            reporter.clone().report_on_receipt(
+                self.eth_client.clone(),
+                BundleSubmissionEvent {
+                    timestamp_ms: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map(|d| d.as_millis() as u64)
+                        .unwrap_or_default(),
+                    entry_point: self.entry_point,
+                    tx_hash: hash,
+                    op_count: uos.len(),
+                    gas_used: None,
+                    beneficiary,
+                    net_fee: None,
+                },
+            );
+        }
+
+        #[cfg(feature = "sqlite")]
+        {
+            self.log_submission(hash, gas_limit, uos).await;
+            self.watch_confirmation(hash);
+        }
+
         Ok(Some(hash))
     }
 }
@@ -0,0 +1,91 @@
+use ethers::{
+    providers::Middleware,
+    signers::Signer,
+    types::{
+        transaction::eip2718::TypedTransaction, Bytes, Eip1559TransactionRequest, Transaction,
+        TxHash, U256,
+    },
+};
+use thiserror::Error;
+use tracing::info;
+
+/// Gas needed for a plain transfer without call data
+const TRANSFER_GAS: u64 = 21000;
+/// Percentage increase of fees for the cancellation transaction
+const CANCEL_FEE_INCREASE_PERC: u64 = 20;
+
+/// Error when cancelling a pending transaction fails
+#[derive(Debug, Error)]
+pub enum CancelError {
+    /// The transaction to be cancelled is unknown to the execution client
+    #[error("transaction {0:?} not found")]
+    NotFound(TxHash),
+    /// Provider error
+    #[error("provider error: {inner}")]
+    Provider { inner: String },
+    /// Signer error
+    #[error("signer error: {inner}")]
+    Signer { inner: String },
+}
+
+fn bump_fee(fee: U256) -> U256 {
+    fee.saturating_mul(U256::from(100 + CANCEL_FEE_INCREASE_PERC)) / U256::from(100)
+}
+
+/// Builds the cancellation transaction for a stuck transaction: a zero-value self-transfer with
+/// the same nonce and fees increased by 20 %.
+pub fn cancellation_transaction<S: Signer>(stuck_tx: &Transaction, signer: &S) -> TypedTransaction {
+    let max_fee_per_gas = stuck_tx.max_fee_per_gas.or(stuck_tx.gas_price).unwrap_or_default();
+    let max_priority_fee_per_gas =
+        stuck_tx.max_priority_fee_per_gas.or(stuck_tx.gas_price).unwrap_or_default();
+
+    Eip1559TransactionRequest::new()
+        .from(signer.address())
+        .to(signer.address())
+        .value(U256::zero())
+        .data(Bytes::default())
+        .nonce(stuck_tx.nonce)
+        .gas(TRANSFER_GAS)
+        .max_fee_per_gas(bump_fee(max_fee_per_gas))
+        .max_priority_fee_per_gas(bump_fee(max_priority_fee_per_gas))
+        .chain_id(signer.chain_id())
+        .into()
+}
+
+/// Cancels a stuck pending transaction by replacing it with a zero-value self-transfer at the
+/// same nonce, which frees the nonce for a new bundle.
+///
+/// # Arguments
+/// * `tx_hash` - Hash of the stuck transaction
+/// * `signer` - Wallet that sent the stuck transaction
+/// * `provider` - Connection to the Ethereum execution client
+///
+/// # Returns
+/// * `TxHash` - The hash of the cancellation transaction
+pub async fn cancel_transaction<M: Middleware, S: Signer>(
+    tx_hash: TxHash,
+    signer: &S,
+    provider: &M,
+) -> Result<TxHash, CancelError> {
+    let stuck_tx = provider
+        .get_transaction(tx_hash)
+        .await
+        .map_err(|err| CancelError::Provider { inner: err.to_string() })?
+        .ok_or(CancelError::NotFound(tx_hash))?;
+
+    let tx = cancellation_transaction(&stuck_tx, signer);
+    let signature = signer
+        .sign_transaction(&tx)
+        .await
+        .map_err(|err| CancelError::Signer { inner: err.to_string() })?;
+
+    let cancel_tx_hash = provider
+        .send_raw_transaction(tx.rlp_signed(&signature))
+        .await
+        .map_err(|err| CancelError::Provider { inner: err.to_string() })?
+        .tx_hash();
+
+    info!("Cancelling transaction {tx_hash:?} (nonce {}) with {cancel_tx_hash:?}", stuck_tx.nonce);
+
+    Ok(cancel_tx_hash)
+}
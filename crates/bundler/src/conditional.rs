@@ -1,4 +1,4 @@
-use crate::bundler::SendBundleOp;
+use crate::{bundler::SendBundleOp, nonce_manager::not_broadcast};
 use alloy_chains::{Chain, NamedChain};
 use ethers::{
     middleware::SignerMiddleware,
@@ -51,11 +51,12 @@ where
             known_accounts.insert(k, AccountStorage::SlotValues(v));
         }
 
-        let signed_tx = self.0.sign_transaction(bundle).await?;
+        let signed_tx = self.0.sign_transaction(bundle).await.map_err(not_broadcast)?;
 
-        let prefix: Option<String> = if self.0.get_chainid().await? ==
+        let chain_id = self.0.get_chainid().await.map_err(not_broadcast)?;
+        let prefix: Option<String> = if chain_id ==
             Chain::from_named(NamedChain::Polygon).id().into() ||
-            self.0.get_chainid().await? == Chain::from_named(NamedChain::PolygonAmoy).id().into()
+            chain_id == Chain::from_named(NamedChain::PolygonAmoy).id().into()
         {
             Some("bor".to_string())
         } else {
@@ -69,7 +70,8 @@ where
                 prefix,
                 ConditionalOptions { known_accounts, ..Default::default() },
             )
-            .await?
+            .await
+            .map_err(not_broadcast)?
             .interval(Duration::from_millis(75));
         let tx_hash = tx.tx_hash();
 
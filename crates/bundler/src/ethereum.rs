@@ -1,17 +1,16 @@
-use crate::bundler::SendBundleOp;
+use crate::{bundle_tracker::BundleTracker, bundler::SendBundleOp, signer::Signer};
 use ethers::{
     middleware::SignerMiddleware,
     providers::Middleware,
-    signers::LocalWallet,
     types::{transaction::eip2718::TypedTransaction, H256},
 };
 use silius_primitives::{simulation::StorageMap, Wallet};
-use std::{sync::Arc, time::Duration};
+use std::sync::Arc;
 use tracing::trace;
 
 /// A type alias for the Ethereum Signer client
 #[derive(Clone)]
-pub struct EthereumClient<M>(pub SignerMiddleware<Arc<M>, LocalWallet>);
+pub struct EthereumClient<M>(pub SignerMiddleware<Arc<M>, Signer>, pub BundleTracker);
 
 #[async_trait::async_trait]
 impl<M> SendBundleOp for EthereumClient<M>
@@ -33,10 +32,9 @@ where
     ) -> eyre::Result<H256> {
         trace!("Sending transaction to the execution client: {bundle:?}");
 
-        let tx = self.0.send_transaction(bundle, None).await?.interval(Duration::from_millis(75));
-        let tx_hash = tx.tx_hash();
+        let tx_hash = self.1.send_and_track(&self.0, bundle).await?;
 
-        let tx_receipt = tx.await?;
+        let tx_receipt = self.0.get_transaction_receipt(tx_hash).await?;
 
         trace!("Transaction receipt: {tx_receipt:?}");
 
@@ -57,7 +55,32 @@ where
     /// # Returns
     /// * `EthereumClient` - A [Ethereum Signer Middleware](EthereumClient)
     pub fn new(eth_client: Arc<M>, wallet: Wallet) -> Self {
-        let signer = SignerMiddleware::new(eth_client, wallet.signer);
-        Self(signer)
+        Self::with_tracker(eth_client, wallet, BundleTracker::default())
+    }
+
+    /// Create an Ethereum client that resubmits stuck bundles as configured by the tracker
+    ///
+    /// # Arguments
+    /// * `eth_client` - Connection to the Ethereum execution client
+    /// * `wallet` - A [Wallet](Wallet) instance
+    /// * `tracker` - A [BundleTracker](BundleTracker) instance
+    ///
+    /// # Returns
+    /// * `EthereumClient` - A [Ethereum Signer Middleware](EthereumClient)
+    pub fn with_tracker(eth_client: Arc<M>, wallet: Wallet, tracker: BundleTracker) -> Self {
+        Self::with_signer(eth_client, wallet.signer.into(), tracker)
+    }
+
+    /// Create an Ethereum client that signs the bundles with the given key backend
+    ///
+    /// # Arguments
+    /// * `eth_client` - Connection to the Ethereum execution client
+    /// * `signer` - A [Signer](Signer) instance
+    /// * `tracker` - A [BundleTracker](BundleTracker) instance
+    ///
+    /// # Returns
+    /// * `EthereumClient` - A [Ethereum Signer Middleware](EthereumClient)
+    pub fn with_signer(eth_client: Arc<M>, signer: Signer, tracker: BundleTracker) -> Self {
+        Self(SignerMiddleware::new(eth_client, signer), tracker)
     }
 }
@@ -1,4 +1,4 @@
-use crate::bundler::SendBundleOp;
+use crate::{bundler::SendBundleOp, nonce_manager::not_broadcast};
 use ethers::{
     middleware::SignerMiddleware,
     providers::{Http, Middleware, Provider},
@@ -63,10 +63,10 @@ where
             known_accounts.insert(k, AccountStorage::SlotValues(v));
         }
 
-        let signed_tx = self.client.sign_transaction(bundle).await?;
+        let signed_tx = self.client.sign_transaction(bundle).await.map_err(not_broadcast)?;
 
         let prefix: Option<String> = Some("pfl".to_string());
-        let block = self.client.get_block(BlockNumber::Latest).await?;
+        let block = self.client.get_block(BlockNumber::Latest).await.map_err(not_broadcast)?;
 
         let mut options = ConditionalOptions { known_accounts, ..Default::default() };
 
@@ -76,20 +76,30 @@ where
         }
 
         // check if the current validator is participating in the Fastlane protocol
-        let fastlane_validators =
-            reqwest::get(FASTLANE_VALIDATORS).await?.json::<FastlaneValidators>().await?;
-        let current_validator: Address =
-            self.polygon_client.request("bor_getCurrentProposer", ()).await?;
+        let fastlane_validators = reqwest::get(FASTLANE_VALIDATORS)
+            .await
+            .map_err(not_broadcast)?
+            .json::<FastlaneValidators>()
+            .await
+            .map_err(not_broadcast)?;
+        let current_validator: Address = self
+            .polygon_client
+            .request("bor_getCurrentProposer", ())
+            .await
+            .map_err(not_broadcast)?;
 
         if !fastlane_validators.validators.contains(&current_validator) {
             trace!("Current validator is not participating in the Fastlane protocol");
-            return Err(eyre::eyre!(
+            return Err(not_broadcast(eyre::eyre!(
                 "Current validator is not participating in the Fastlane protocol"
-            ));
+            )));
         }
 
-        let tx =
-            self.relay_client.send_raw_transaction_conditional(signed_tx, prefix, options).await?;
+        let tx = self
+            .relay_client
+            .send_raw_transaction_conditional(signed_tx, prefix, options)
+            .await
+            .map_err(not_broadcast)?;
         let tx_hash = tx.tx_hash();
 
         let tx_receipt = tx.await?;
@@ -1,4 +1,4 @@
-use crate::bundler::SendBundleOp;
+use crate::{bundler::SendBundleOp, nonce_manager::not_broadcast, relay_router::RelayRouter};
 use ethers::{
     middleware::SignerMiddleware,
     providers::Middleware,
@@ -6,23 +6,32 @@ use ethers::{
     types::{transaction::eip2718::TypedTransaction, H256},
 };
 use ethers_flashbots::{BundleRequest, FlashbotsMiddleware, PendingBundleError, SimulatedBundle};
-use silius_primitives::{simulation::StorageMap, Wallet};
-use std::sync::Arc;
-use tracing::{info, trace};
+use silius_primitives::{simulation::StorageMap, RelayStatus, Wallet};
+use std::{future::Future, sync::Arc};
+use tracing::{info, trace, warn};
 use url::Url;
 
+/// Flashbots Signer Middleware connected to one relay
+pub type FlashbotsRelay<M> =
+    Arc<SignerMiddleware<FlashbotsMiddleware<Arc<M>, LocalWallet>, LocalWallet>>;
+
 /// A struct for the Flashbots Signer client
+///
+/// Bundles are sent to the first healthy relay, the [RelayRouter](RelayRouter) fails over to
+/// the next one when a relay can't be reached.
 #[derive(Clone)]
-pub struct FlashbotsClient<M>(
-    pub Arc<SignerMiddleware<FlashbotsMiddleware<Arc<M>, LocalWallet>, LocalWallet>>,
-);
+pub struct FlashbotsClient<M> {
+    /// One middleware per relay endpoint, in priority order
+    pub relays: Vec<FlashbotsRelay<M>>,
+    /// Health of the relays
+    pub router: Arc<RelayRouter>,
+}
 
 #[async_trait::async_trait]
 impl<M> SendBundleOp for FlashbotsClient<M>
 where
     M: Middleware + 'static,
 {
-    // TODO: add more relay endpoints support
     /// Send a bundle of user operations to the Flashbots relay.
     ///
     /// # Arguments
@@ -36,17 +45,23 @@ where
         bundle: TypedTransaction,
         _storage_map: StorageMap,
     ) -> eyre::Result<H256> {
-        let bundle_req = self.generate_bundle_req(vec![bundle], false).await?;
+        let bundle_req =
+            self.generate_bundle_req(vec![bundle], false).await.map_err(not_broadcast)?;
 
         match self.simulate_flashbots_bundle(&bundle_req).await {
             Ok(_) => {}
-            Err(e) => return Err(eyre::eyre!("Bundle simulation failed: {:?}", e)),
+            Err(e) => return Err(not_broadcast(eyre::eyre!("Bundle simulation failed: {:?}", e))),
         };
 
         let bundle_hash = self.send_flashbots_bundle(bundle_req.clone()).await?;
 
         Ok(bundle_hash)
     }
+
+    /// Returns the status of the Flashbots relays
+    fn relay_status(&self) -> Vec<RelayStatus> {
+        self.router.status()
+    }
 }
 
 impl<M> FlashbotsClient<M>
@@ -57,7 +72,7 @@ where
     ///
     /// # Arguments
     /// * `eth_client` - Connection to the Ethereum execution client
-    /// * `relay_endpoints` - An array of Flashbots relay endpoints
+    /// * `relay_endpoints` - An array of Flashbots relay endpoints in priority order
     /// * `wallet` - A [Wallet](Wallet) instance
     ///
     /// # Returns
@@ -67,31 +82,66 @@ where
         relay_endpoints: Option<Vec<String>>,
         wallet: Wallet,
     ) -> eyre::Result<Self> {
-        // Only support one relay endpoint for now
-        let relay_endpoint: &str = relay_endpoints
-            .as_ref()
-            .expect("No Flashbots relay endpoint provided")
-            .first()
-            .expect("No Flashbots relay endpoint provided");
+        let relay_endpoints = match relay_endpoints {
+            Some(endpoints) if !endpoints.is_empty() => endpoints,
+            _ => return Err(eyre::eyre!("No Flashbots relay endpoint provided")),
+        };
 
         let bundle_signer = match wallet.flashbots_signer {
             Some(ref signer) => signer,
             None => return Err(eyre::eyre!("No Flashbots signer provided")),
         };
 
-        let mut flashbots_middleware = FlashbotsMiddleware::new(
-            eth_client,
-            Url::parse(relay_endpoint)?,
-            bundle_signer.clone(),
-        );
-        flashbots_middleware.set_simulation_relay(
-            Url::parse(relay_endpoint).expect("Failed to parse simulation relay URL"),
-            bundle_signer.clone(),
-        );
+        let relays = relay_endpoints
+            .iter()
+            .map(|relay_endpoint| {
+                let relay_url = Url::parse(relay_endpoint)?;
+                let mut flashbots_middleware = FlashbotsMiddleware::new(
+                    eth_client.clone(),
+                    relay_url.clone(),
+                    bundle_signer.clone(),
+                );
+                flashbots_middleware.set_simulation_relay(relay_url, bundle_signer.clone());
+
+                Ok(Arc::new(SignerMiddleware::new(flashbots_middleware, wallet.signer.clone())))
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+
+        Ok(Self { relays, router: Arc::new(RelayRouter::new(relay_endpoints)) })
+    }
 
-        let client = Arc::new(SignerMiddleware::new(flashbots_middleware, wallet.signer.clone()));
+    /// Sends the request to the available relays in priority order until one of them responds
+    ///
+    /// # Arguments
+    /// * `req` - The request sent to a relay, an error means the relay couldn't be reached
+    ///
+    /// # Returns
+    /// * `T` - The response of the first relay that responded
+    async fn with_failover<T, F, Fut>(&self, req: F) -> eyre::Result<T>
+    where
+        F: Fn(FlashbotsRelay<M>) -> Fut,
+        Fut: Future<Output = eyre::Result<T>>,
+    {
+        let mut last_err = None;
+
+        for idx in self.router.available() {
+            match req(self.relays[idx].clone()).await {
+                Ok(res) => {
+                    self.router.record_success(idx);
+                    return Ok(res);
+                }
+                Err(err) => {
+                    warn!("Flashbots relay {} failed: {err:?}", self.router.relays()[idx]);
+                    self.router.record_failure(idx, format!("{err:?}"));
+                    last_err = Some(err);
+                }
+            }
+        }
 
-        Ok(Self(client))
+        Err(match last_err {
+            Some(err) => eyre::eyre!("All Flashbots relays failed, last error: {err:?}"),
+            None => eyre::eyre!("No Flashbots relay available"),
+        })
     }
 
     /// Generate a Flashbots bundle request
@@ -111,7 +161,7 @@ where
         let mut bundle_req = BundleRequest::new();
         for tx in txs {
             let typed_tx = TypedTransaction::Eip1559(tx.into());
-            let raw_signed_tx = match self.0.signer().sign_transaction(&typed_tx).await {
+            let raw_signed_tx = match self.relays[0].signer().sign_transaction(&typed_tx).await {
                 Ok(tx) => typed_tx.rlp_signed(&tx),
                 Err(e) => return Err(eyre::eyre!("Failed to sign transaction: {:?}", e)),
             };
@@ -124,7 +174,7 @@ where
         }
 
         // Simulate the Flashbots bundle
-        let block_num = self.0.get_block_number().await?;
+        let block_num = self.relays[0].get_block_number().await?;
         bundle_req = bundle_req
             .set_block(block_num + 1)
             .set_simulation_block(block_num)
@@ -141,23 +191,29 @@ where
     /// # Returns
     /// * `H256` - The transaction hash of the bundle
     pub async fn send_flashbots_bundle(&self, bundle: BundleRequest) -> eyre::Result<H256> {
-        // Send the Flashbots bundle and check for status
-        let pending_bundle = match self.0.inner().send_bundle(&bundle).await {
-            Ok(bundle) => bundle,
-            Err(e) => return Err(eyre::eyre!("Failed to send bundle: {:?}", e)),
-        };
-
-        info!("Bundle received at block: {:?}", pending_bundle.block);
+        let bundle = &bundle;
+
+        // Send the Flashbots bundle and check for status (the relay is healthy once it accepts
+        // the bundle, even if the bundle isn't included)
+        self.with_failover(|relay| async move {
+            let pending_bundle = match relay.inner().send_bundle(bundle).await {
+                Ok(bundle) => bundle,
+                Err(e) => return Err(eyre::eyre!("Failed to send bundle: {:?}", e)),
+            };
 
-        match pending_bundle.await {
-            Ok(bundle_hash) => Ok(bundle_hash),
-            Err(err) => match err {
-                PendingBundleError::BundleNotIncluded => {
-                    Err(eyre::eyre!("Bundle not included in the target block"))
-                }
-                _ => Err(eyre::eyre!("Bundle rejected: {:?}", err)),
-            },
-        }
+            info!("Bundle received at block: {:?}", pending_bundle.block);
+
+            Ok(match pending_bundle.await {
+                Ok(bundle_hash) => Ok(bundle_hash),
+                Err(err) => match err {
+                    PendingBundleError::BundleNotIncluded => {
+                        Err(eyre::eyre!("Bundle not included in the target block"))
+                    }
+                    _ => Err(eyre::eyre!("Bundle rejected: {:?}", err)),
+                },
+            })
+        })
+        .await?
     }
 
     /// Simulate a Flashbots bundle
@@ -171,7 +227,9 @@ where
         &self,
         bundle: &BundleRequest,
     ) -> eyre::Result<SimulatedBundle> {
-        let simulated_bundle = self.0.inner().simulate_bundle(bundle).await?;
+        let simulated_bundle = self
+            .with_failover(|relay| async move { Ok(relay.inner().simulate_bundle(bundle).await?) })
+            .await?;
 
         // Currently there's only 1 tx per bundle
         for tx in &simulated_bundle.transactions {
@@ -1,14 +1,54 @@
 //! Bundler is a crate for bundling transactions and sending them to the Ethereum execution client
 #![allow(dead_code)]
 
+mod bundle_recovery;
+mod bundle_reporter;
+mod bundle_size_optimizer;
+mod bundle_tracker;
 mod bundler;
+mod cancel_transaction;
 mod conditional;
 mod ethereum;
 mod fastlane;
 mod flashbots;
+mod mempool_monitor;
+pub mod metrics;
+mod mev_share;
+mod multicall_builder;
+mod nonce_manager;
+mod profitability_filter;
+mod relay_router;
+mod replacement_notifier;
+mod signer;
+#[cfg(feature = "sqlite")]
+mod submission_log;
 
+pub use bundle_recovery::{bundle_reverted, BundleRecovery, RemoveFailedUserOperations};
+#[cfg(feature = "kafka")]
+pub use bundle_reporter::KafkaSink;
+#[cfg(feature = "nats")]
+pub use bundle_reporter::NatsSink;
+pub use bundle_reporter::{net_fee, BundleEventSink, BundleReporter, BundleSubmissionEvent};
+pub use bundle_size_optimizer::BundleSizeOptimizer;
+pub use bundle_tracker::BundleTracker;
 pub use bundler::{Bundler, SendBundleOp};
+pub use cancel_transaction::{cancel_transaction, cancellation_transaction, CancelError};
 pub use conditional::ConditionalClient;
 pub use ethereum::EthereumClient;
 pub use fastlane::FastlaneClient;
 pub use flashbots::FlashbotsClient;
+pub use mempool_monitor::{AlertLevel, MempoolAlert, MempoolMonitor, MempoolSize};
+pub use mev_share::{
+    BundleInclusion, BundlePrivacy, BundleValidity, MevShareBodyItem, MevShareBundle,
+    MevShareClient, MevShareResponse, Refund, RefundConfig,
+};
+pub use multicall_builder::{
+    decode_aggregate3, encode_aggregate3, EntryPointBundle, MulticallBundler,
+};
+pub use nonce_manager::{not_broadcast, BundlerNonceManager, NotBroadcast};
+pub use profitability_filter::ProfitabilityFilter;
+pub use relay_router::RelayRouter;
+pub use replacement_notifier::{OperationIncludedEvent, RemoveUserOperations, ReplacementNotifier};
+pub use signer::{Signer, SignerError};
+#[cfg(feature = "sqlite")]
+pub use submission_log::{BundleOp, BundleRecord, BundleSubmission, SubmissionLog, TxStatus};
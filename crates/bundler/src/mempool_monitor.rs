@@ -0,0 +1,131 @@
+use ethers::types::Address;
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::broadcast;
+use tracing::{error, info, warn};
+
+/// Capacity of the alert channel (slow subscribers miss the oldest alerts)
+const ALERT_CHANNEL_CAPACITY: usize = 64;
+
+/// Congestion level of the mempool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertLevel {
+    OK,
+    WARNING,
+    CRITICAL,
+}
+
+/// Alert emitted when the congestion level of the mempool changes
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolAlert {
+    /// Entry point of the mempool
+    pub entry_point: Address,
+    /// Number of user operations in the mempool
+    pub size: usize,
+    /// Congestion level
+    pub level: AlertLevel,
+}
+
+/// Source of the mempool size (the bundler talks to the uopool over gRPC)
+#[async_trait::async_trait]
+pub trait MempoolSize: Send + Sync {
+    /// Returns the number of user operations in the mempool of the entry point
+    async fn count(&self, ep: &Address) -> eyre::Result<usize>;
+}
+
+/// Polls the mempool size and alerts operators when the mempool gets congested
+#[derive(Clone, Debug)]
+pub struct MempoolMonitor {
+    /// Mempool size above which the mempool is critically congested (warning at 80%)
+    pub high_watermark: usize,
+    /// Mempool size below which the congestion is over
+    pub low_watermark: usize,
+    /// Channel the alerts are broadcast to
+    pub alert_sender: broadcast::Sender<MempoolAlert>,
+}
+
+impl MempoolMonitor {
+    pub fn new(high_watermark: usize, low_watermark: usize) -> Self {
+        let (alert_sender, _) = broadcast::channel(ALERT_CHANNEL_CAPACITY);
+        Self { high_watermark, low_watermark, alert_sender }
+    }
+
+    /// Subscribes to the mempool alerts
+    pub fn subscribe(&self) -> broadcast::Receiver<MempoolAlert> {
+        self.alert_sender.subscribe()
+    }
+
+    /// Returns the new congestion level, or None if the level hasn't changed
+    ///
+    /// Between the low watermark and the warning mark the previous level is kept, so the
+    /// alerts don't flap when the size hovers around one of the marks.
+    pub fn level(&self, size: usize, prev: AlertLevel) -> Option<AlertLevel> {
+        let level = if size > self.high_watermark {
+            AlertLevel::CRITICAL
+        } else if size.saturating_mul(5) > self.high_watermark.saturating_mul(4) {
+            AlertLevel::WARNING
+        } else if size < self.low_watermark {
+            AlertLevel::OK
+        } else {
+            prev
+        };
+
+        (level != prev).then_some(level)
+    }
+
+    /// Checks the mempool sizes of the entry points once and broadcasts the level changes
+    ///
+    /// # Arguments
+    /// * `source` - The source of the mempool size
+    /// * `levels` - The last known level of each entry point
+    pub async fn poll<S: MempoolSize>(
+        &self,
+        source: &S,
+        levels: &mut HashMap<Address, AlertLevel>,
+    ) {
+        for (ep, prev) in levels.iter_mut() {
+            let size = match source.count(ep).await {
+                Ok(size) => size,
+                Err(e) => {
+                    warn!("Failed to get mempool size of entry point {ep:?}: {e:?}");
+                    continue;
+                }
+            };
+
+            if let Some(level) = self.level(size, *prev) {
+                *prev = level;
+                match level {
+                    AlertLevel::OK => info!("Mempool of {ep:?} is no longer congested: {size} uos"),
+                    AlertLevel::WARNING => {
+                        warn!("Mempool of {ep:?} is getting congested: {size} uos")
+                    }
+                    AlertLevel::CRITICAL => error!(
+                        "Mempool of {ep:?} is congested: {size} uos (high watermark {})",
+                        self.high_watermark
+                    ),
+                }
+                // no subscribers isn't an error, the alert is still logged
+                let _ = self.alert_sender.send(MempoolAlert { entry_point: *ep, size, level });
+            }
+        }
+    }
+
+    /// Starts polling the mempool sizes every `poll_interval`
+    pub fn start<S: MempoolSize + 'static>(
+        &self,
+        source: Arc<S>,
+        eps: Vec<Address>,
+        poll_interval: Duration,
+    ) {
+        let monitor = self.clone();
+        tokio::spawn(async move {
+            let mut levels = eps.into_iter().map(|ep| (ep, AlertLevel::OK)).collect();
+            loop {
+                monitor.poll(source.as_ref(), &mut levels).await;
+                tokio::time::sleep(poll_interval).await;
+            }
+        });
+    }
+}
@@ -0,0 +1,6 @@
+//! Metrics recorded by the bundler (described together with the other metrics in silius-metrics)
+
+/// Number of bundle transactions resubmitted with bumped fees
+pub const BUNDLES_RESUBMITTED: &str = "silius_bundles_resubmitted_total";
+/// Number of user operations of reverted bundles dropped after failing the simulation again
+pub const BUNDLE_RECOVERY_OPS_DROPPED: &str = "silius_bundle_recovery_ops_dropped_total";
@@ -0,0 +1,252 @@
+use crate::{bundler::SendBundleOp, nonce_manager::not_broadcast};
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::Middleware,
+    signers::{LocalWallet, Signer},
+    types::{transaction::eip2718::TypedTransaction, Address, Bytes, H256, U64},
+    utils::keccak256,
+};
+use serde::{Deserialize, Serialize};
+use silius_primitives::{simulation::StorageMap, Wallet};
+use std::sync::Arc;
+use tracing::trace;
+
+/// Version of the MEV-Share bundle schema
+const MEV_SHARE_VERSION: &str = "v0.1";
+
+/// Number of blocks (after the next one) the bundle stays valid for
+const MAX_BLOCK_RANGE: u64 = 25;
+
+/// Header carrying the signature of the request body
+const FLASHBOTS_SIGNATURE_HEADER: &str = "X-Flashbots-Signature";
+
+/// Blocks the bundle can be included in
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleInclusion {
+    /// First block the bundle is valid for
+    pub block: U64,
+    /// Last block the bundle is valid for (only the first block if None)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block: Option<U64>,
+}
+
+/// Transaction, transaction hash or nested bundle of the bundle body
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MevShareBodyItem {
+    /// Hash of a transaction from the MEV-Share event stream (backrunning)
+    Hash { hash: H256 },
+    /// Signed transaction
+    #[serde(rename_all = "camelCase")]
+    Tx { tx: Bytes, can_revert: bool },
+    /// Nested bundle
+    Bundle { bundle: Box<MevShareBundle> },
+}
+
+/// Share of the MEV refund paid to the sender of a body transaction
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Refund {
+    /// Index of the transaction in the body
+    pub body_idx: u64,
+    /// Percent of the refund
+    pub percent: u64,
+}
+
+/// Split of the MEV refund among addresses
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefundConfig {
+    pub address: Address,
+    pub percent: u64,
+}
+
+/// Conditions the bundle has to meet to be included
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleValidity {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub refund: Vec<Refund>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub refund_config: Vec<RefundConfig>,
+}
+
+/// What is shared with searchers and which builders receive the bundle
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundlePrivacy {
+    /// Data shared with searchers (e.g. `calldata`, `logs`, `hash`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hints: Option<Vec<String>>,
+    /// Builders the bundle is sent to (only the Flashbots builder if None)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builders: Option<Vec<String>>,
+}
+
+/// Bundle in the format of the `mev_sendBundle` method
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MevShareBundle {
+    pub version: String,
+    pub inclusion: BundleInclusion,
+    pub body: Vec<MevShareBodyItem>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validity: Option<BundleValidity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub privacy: Option<BundlePrivacy>,
+}
+
+impl MevShareBundle {
+    /// Bundle of signed transactions that must not revert
+    ///
+    /// # Arguments
+    /// * `txs` - Signed transactions of the bundle
+    /// * `inclusion` - Blocks the bundle can be included in
+    pub fn new(txs: Vec<Bytes>, inclusion: BundleInclusion) -> Self {
+        Self {
+            version: MEV_SHARE_VERSION.into(),
+            inclusion,
+            body: txs
+                .into_iter()
+                .map(|tx| MevShareBodyItem::Tx { tx, can_revert: false })
+                .collect(),
+            validity: None,
+            privacy: None,
+        }
+    }
+}
+
+/// Result of the `mev_sendBundle` method
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MevShareResponse {
+    pub bundle_hash: H256,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcResponse {
+    result: Option<MevShareResponse>,
+    error: Option<JsonRpcError>,
+}
+
+/// A client for the Flashbots MEV-Share relay
+#[derive(Clone)]
+pub struct MevShareClient<M> {
+    /// Signs the bundle transactions (the bundler wallet)
+    pub eth_client: SignerMiddleware<Arc<M>, LocalWallet>,
+    pub client: reqwest::Client,
+    /// MEV-Share relay endpoint
+    pub endpoint: String,
+    /// Signs the requests to the relay (Flashbots reputation key)
+    pub signing_key: LocalWallet,
+}
+
+#[async_trait::async_trait]
+impl<M> SendBundleOp for MevShareClient<M>
+where
+    M: Middleware + 'static,
+{
+    /// Send a bundle of user operations to the MEV-Share relay.
+    ///
+    /// # Arguments
+    /// * `bundle` - Bundle of user operations as [TypedTransaction](TypedTransaction).
+    /// * 'storage_map' - Storage map
+    ///
+    /// # Returns
+    /// * `H256` - The hash of the MEV-Share bundle
+    async fn send_bundle(
+        &self,
+        bundle: TypedTransaction,
+        _storage_map: StorageMap,
+    ) -> eyre::Result<H256> {
+        let signature =
+            self.eth_client.signer().sign_transaction(&bundle).await.map_err(not_broadcast)?;
+        let tx = bundle.rlp_signed(&signature);
+
+        let block = self.eth_client.get_block_number().await.map_err(not_broadcast)? + 1;
+        let inclusion = BundleInclusion { block, max_block: Some(block + MAX_BLOCK_RANGE) };
+
+        let res =
+            MevShareClient::send_bundle(self, MevShareBundle::new(vec![tx], inclusion)).await?;
+
+        Ok(res.bundle_hash)
+    }
+}
+
+impl<M> MevShareClient<M>
+where
+    M: Middleware + 'static,
+{
+    /// Create a new MEV-Share client
+    ///
+    /// # Arguments
+    /// * `eth_client` - Connection to the Ethereum execution client
+    /// * `endpoint` - MEV-Share relay endpoint
+    /// * `wallet` - A [Wallet](Wallet) instance (with a Flashbots signer)
+    ///
+    /// # Returns
+    /// * `MevShareClient` - A [MEV-Share client](MevShareClient)
+    pub fn new(eth_client: Arc<M>, endpoint: String, wallet: Wallet) -> eyre::Result<Self> {
+        let signing_key = match wallet.flashbots_signer {
+            Some(signer) => signer,
+            None => return Err(eyre::eyre!("No Flashbots signer provided")),
+        };
+
+        Ok(Self {
+            eth_client: SignerMiddleware::new(eth_client, wallet.signer),
+            client: reqwest::Client::new(),
+            endpoint,
+            signing_key,
+        })
+    }
+
+    /// Signature of the request body in the format of the `X-Flashbots-Signature` header
+    async fn sign_body(&self, body: &str) -> eyre::Result<String> {
+        let message = format!("0x{:x}", H256::from(keccak256(body.as_bytes())));
+        let signature = self.signing_key.sign_message(message).await?;
+        Ok(format!("{:?}:0x{signature}", self.signing_key.address()))
+    }
+
+    /// Send a bundle over the `mev_sendBundle` method
+    ///
+    /// # Arguments
+    /// * `bundle` - The [MevShareBundle](MevShareBundle) to send
+    ///
+    /// # Returns
+    /// * `MevShareResponse` - The hash of the bundle
+    pub async fn send_bundle(&self, bundle: MevShareBundle) -> eyre::Result<MevShareResponse> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "mev_sendBundle",
+            "params": [bundle],
+        })
+        .to_string();
+
+        trace!("Sending bundle to the MEV-Share relay: {body}");
+
+        let res = self
+            .client
+            .post(&self.endpoint)
+            .header(FLASHBOTS_SIGNATURE_HEADER, self.sign_body(&body).await?)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JsonRpcResponse>()
+            .await?;
+
+        match (res.result, res.error) {
+            (Some(result), _) => Ok(result),
+            (None, Some(err)) => {
+                Err(eyre::eyre!("MEV-Share relay error {}: {}", err.code, err.message))
+            }
+            (None, None) => Err(eyre::eyre!("Empty response from the MEV-Share relay")),
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use crate::{
+    bundler::{Bundler, SendBundleOp},
+    nonce_manager::BundlerNonceManager,
+};
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    providers::Middleware,
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, Eip1559TransactionRequest, H256,
+        U256, U64,
+    },
+    utils::id,
+};
+use silius_primitives::{simulation::StorageMap, UserOperation, UserOperationHash};
+use tracing::{info, trace, warn};
+
+/// Signature of the Multicall3 `aggregate3` function
+const AGGREGATE3_SIGNATURE: &str = "aggregate3((address,bool,bytes)[])";
+
+/// User operations (and their storage map) to be bundled for one entry point
+pub type EntryPointBundle = (Address, Vec<UserOperation>, StorageMap);
+
+/// Encodes the calls into Multicall3 `aggregate3` call data (failure of each call is allowed)
+///
+/// # Arguments
+/// * `calls` - Targets and call data of the calls
+///
+/// # Returns
+/// * `Bytes` - The `aggregate3` call data
+pub fn encode_aggregate3(calls: &[(Address, Bytes)]) -> Bytes {
+    let calls = calls
+        .iter()
+        .map(|(target, call_data)| {
+            Token::Tuple(vec![
+                Token::Address(*target),
+                Token::Bool(true),
+                Token::Bytes(call_data.to_vec()),
+            ])
+        })
+        .collect();
+
+    [id(AGGREGATE3_SIGNATURE).to_vec(), encode(&[Token::Array(calls)])].concat().into()
+}
+
+/// Decodes the return data of Multicall3 `aggregate3` into success flags of the calls
+pub fn decode_aggregate3(data: &[u8]) -> eyre::Result<Vec<bool>> {
+    let tokens = decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes])))],
+        data,
+    )?;
+
+    tokens
+        .into_iter()
+        .next()
+        .and_then(Token::into_array)
+        .ok_or_else(|| eyre::format_err!("Invalid aggregate3 return data"))?
+        .into_iter()
+        .map(|res| {
+            res.into_tuple()
+                .and_then(|res| res.into_iter().next())
+                .and_then(Token::into_bool)
+                .ok_or_else(|| eyre::format_err!("Invalid aggregate3 result"))
+        })
+        .collect()
+}
+
+/// Bundler that sends bundles of multiple entry points in a single Multicall3 transaction
+#[derive(Clone, Debug)]
+pub struct MulticallBundler<M, S>
+where
+    M: Middleware + 'static,
+    S: SendBundleOp,
+{
+    /// Bundlers of the entry points (the first one sends the multicall transaction)
+    pub bundlers: Vec<Bundler<M, S>>,
+    /// Multicall3 contract address
+    pub multicall: Address,
+}
+
+impl<M, S> MulticallBundler<M, S>
+where
+    M: Middleware + 'static,
+    S: SendBundleOp,
+{
+    /// Create a new multicall bundler
+    ///
+    /// # Arguments
+    /// * `bundlers` - Bundlers of the entry points
+    /// * `multicall` - Multicall3 contract address
+    ///
+    /// # Returns
+    /// * `Self` - A new `MulticallBundler` instance, error if there are no bundlers
+    pub fn new(bundlers: Vec<Bundler<M, S>>, multicall: Address) -> eyre::Result<Self> {
+        if bundlers.is_empty() {
+            return Err(eyre::format_err!("At least one bundler must be present"));
+        }
+        Ok(Self { bundlers, multicall })
+    }
+
+    fn bundler(&self) -> &Bundler<M, S> {
+        &self.bundlers[0]
+    }
+
+    fn bundler_for(&self, ep: &Address) -> Option<&Bundler<M, S>> {
+        self.bundlers.iter().find(|bundler| bundler.entry_point == *ep)
+    }
+
+    /// Encodes `handleOps` (or `handleAggregatedOps`) calls of the entry points into a single
+    /// `aggregate3` call
+    ///
+    /// # Arguments
+    /// * `bundles` - Entry points and their user operations
+    /// * `beneficiary` - Beneficiary of the bundles
+    ///
+    /// # Returns
+    /// * `Bytes` - The `aggregate3` call data
+    pub async fn encode_bundles(
+        &self,
+        bundles: &[(Address, Vec<UserOperation>)],
+        beneficiary: Address,
+    ) -> eyre::Result<Bytes> {
+        let mut calls = Vec::with_capacity(bundles.len());
+        for (ep, uos) in bundles {
+            let bundler = self
+                .bundler_for(ep)
+                .ok_or_else(|| eyre::format_err!("No bundler for entry point {ep:?}"))?;
+            let call_data =
+                bundler.handle_ops(uos, beneficiary).await?.data().cloned().unwrap_or_default();
+            calls.push((*ep, call_data));
+        }
+
+        Ok(encode_aggregate3(&calls))
+    }
+
+    async fn create_bundle(
+        &self,
+        bundles: &[(Address, Vec<UserOperation>)],
+        beneficiary: Address,
+    ) -> eyre::Result<TypedTransaction> {
+        let bundler = self.bundler();
+        let address = bundler.address;
+
+        let mut tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(address)
+            .to(self.multicall)
+            .data(self.encode_bundles(bundles, beneficiary).await?)
+            .chain_id(U64::from(bundler.chain.id()))
+            .into();
+
+        let estimated_gas = bundler.eth_client.estimate_gas(&tx, None).await?;
+        let nonce = bundler.nonce_manager.next_nonce(bundler.eth_client.as_ref()).await?;
+
+        let uos = bundles.iter().flat_map(|(_, uos)| uos.iter()).collect::<Vec<_>>();
+        let max_fee_per_gas =
+            uos.iter().fold(U256::zero(), |acc, uo| acc.saturating_add(uo.max_fee_per_gas));
+        let max_priority_fee_per_gas = uos
+            .iter()
+            .fold(U256::zero(), |acc, uo| acc.saturating_add(uo.max_priority_fee_per_gas));
+
+        tx.set_nonce(nonce);
+        tx.set_gas(estimated_gas);
+        if let TypedTransaction::Eip1559(ref mut tx) = tx {
+            tx.max_fee_per_gas = Some(max_fee_per_gas / uos.len());
+            tx.max_priority_fee_per_gas = Some(max_priority_fee_per_gas / uos.len());
+        }
+
+        Ok(tx)
+    }
+
+    /// Send the bundles of multiple entry points in a single Multicall3 transaction
+    ///
+    /// The `aggregate3` call is simulated first and bundles of the entry points whose `handleOps`
+    /// fails are left out of the multicall transaction and retried separately. The calls of the
+    /// multicall transaction may still revert on-chain without reverting the transaction, which
+    /// is detected from its receipt (see [bundle_reverted](crate::bundle_reverted)).
+    ///
+    /// # Arguments
+    /// * `bundles` - Bundles of the entry points
+    ///
+    /// # Returns
+    /// * `Vec<(Address, H256)>` - The entry points with the hash of the transaction their bundle
+    ///   was sent in (the multicall transaction comes first)
+    pub async fn send_bundles(
+        &self,
+        bundles: Vec<EntryPointBundle>,
+    ) -> eyre::Result<Vec<(Address, H256)>> {
+        let bundles = bundles.into_iter().filter(|(_, uos, _)| !uos.is_empty()).collect::<Vec<_>>();
+        if bundles.is_empty() {
+            info!("Skipping creating a new multicall bundle, no user operations");
+            return Ok(vec![]);
+        }
+
+        info!(
+            "Creating a new multicall bundle for {} entry points: {:?}",
+            bundles.len(),
+            bundles
+                .iter()
+                .map(|(ep, uos, _)| (*ep, uos.iter().map(|uo| uo.hash).collect()))
+                .collect::<Vec<(Address, Vec<UserOperationHash>)>>()
+        );
+
+        let bundler = self.bundler();
+        let beneficiary = bundler.get_beneficiary().await?;
+        let calls = bundles.iter().map(|(ep, uos, _)| (*ep, uos.clone())).collect::<Vec<_>>();
+
+        let sim_tx: TypedTransaction = Eip1559TransactionRequest::new()
+            .from(bundler.address)
+            .to(self.multicall)
+            .data(self.encode_bundles(&calls, beneficiary).await?)
+            .into();
+        let results = decode_aggregate3(&bundler.eth_client.call(&sim_tx, None).await?)?;
+
+        let (valid, failed): (Vec<_>, Vec<_>) = bundles
+            .into_iter()
+            .zip(results.into_iter().chain(std::iter::repeat(false)))
+            .partition(|(_, success)| *success);
+
+        let mut hashes = vec![];
+        if !valid.is_empty() {
+            let mut storage_map = StorageMap::default();
+            let calls = valid
+                .into_iter()
+                .map(|((ep, uos, map), _)| {
+                    storage_map.root_hashes.extend(map.root_hashes);
+                    storage_map.slots.extend(map.slots);
+                    (ep, uos)
+                })
+                .collect::<Vec<_>>();
+
+            let tx = self.create_bundle(&calls, beneficiary).await?;
+            trace!("Multicall bundle transaction: {tx:?}");
+
+            let uos = calls.iter().flat_map(|(_, uos)| uos.iter().cloned()).collect::<Vec<_>>();
+            let nonce = tx.nonce().cloned().unwrap_or_default();
+            match bundler.should_submit(&uos, &tx).await {
+                Ok(true) => {}
+                res => {
+                    bundler.nonce_manager.release(nonce);
+                    return res.map(|_| vec![]);
+                }
+            }
+
+            #[cfg(feature = "sqlite")]
+            let gas_limit = tx.gas().cloned().unwrap_or_default();
+            let tx_hash = match bundler.client.send_bundle(tx, storage_map).await {
+                Ok(tx_hash) => tx_hash,
+                Err(e) => {
+                    if BundlerNonceManager::is_nonce_error(&e) {
+                        bundler.nonce_manager.resync(bundler.eth_client.as_ref()).await?;
+                    } else {
+                        bundler.nonce_manager.send_failed(nonce, &e);
+                    }
+                    return Err(e);
+                }
+            };
+            info!(
+                "Multicall bundle successfully sent, hash: {:?}, account: {:?}, entry points: {:?}, beneficiary: {:?}",
+                tx_hash,
+                bundler.address,
+                calls.iter().map(|(ep, _)| *ep).collect::<Vec<_>>(),
+                beneficiary
+            );
+
+            #[cfg(feature = "sqlite")]
+            {
+                for (ep, uos) in calls.iter() {
+                    if let Some(ep_bundler) = self.bundler_for(ep) {
+                        ep_bundler.log_submission(tx_hash, gas_limit, uos).await;
+                    }
+                }
+                bundler.watch_confirmation(tx_hash);
+            }
+
+            hashes.extend(calls.iter().map(|(ep, _)| (*ep, tx_hash)));
+        }
+
+        let mut error = None;
+        for ((ep, uos, map), _) in failed {
+            warn!("handleOps of entry point {ep:?} failed in multicall, retrying separately");
+            if let Some(bundler) = self.bundler_for(&ep) {
+                // the multicall transaction is already sent, so its hash mustn't be lost
+                match bundler.send_bundle(&uos, map).await {
+                    Ok(Some(tx_hash)) => hashes.push((ep, tx_hash)),
+                    Ok(None) => {}
+                    Err(e) => {
+                        warn!("Failed to send bundle of entry point {ep:?}: {e:?}");
+                        error = error.or(Some(e));
+                    }
+                }
+            }
+        }
+
+        match error {
+            Some(e) if hashes.is_empty() => Err(e),
+            _ => Ok(hashes),
+        }
+    }
+}
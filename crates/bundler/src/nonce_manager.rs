@@ -0,0 +1,130 @@
+use ethers::{
+    providers::Middleware,
+    types::{Address, BlockNumber, U256},
+};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+use thiserror::Error;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Error of sending a bundle that failed before the bundle transaction was broadcast, so the nonce
+/// of the transaction is still unused
+#[derive(Debug, Error)]
+#[error("bundle not broadcast: {0:?}")]
+pub struct NotBroadcast(pub eyre::Report);
+
+/// Marks the error of sending a bundle as raised before the bundle transaction was broadcast
+pub fn not_broadcast<E: Into<eyre::Report>>(err: E) -> eyre::Report {
+    NotBroadcast(err.into()).into()
+}
+
+/// Hands out the nonces of the bundler's account. The pending nonce is fetched from the execution
+/// client once and then incremented locally, so bundles created concurrently (e.g. by the
+/// bundlers of different entry points sharing the same account) never get the same nonce.
+///
+/// Clones share the same counter.
+#[derive(Clone, Debug)]
+pub struct BundlerNonceManager {
+    /// Bundler's account
+    address: Address,
+    /// Next nonce to hand out
+    nonce: Arc<AtomicU64>,
+    /// Whether the nonce was fetched from the execution client
+    synced: Arc<AtomicBool>,
+    /// Serializes the syncs with the execution client
+    sync_lock: Arc<Mutex<()>>,
+}
+
+impl BundlerNonceManager {
+    /// Create a new nonce manager for the account, the nonce is fetched on the first use
+    pub fn new(address: Address) -> Self {
+        Self {
+            address,
+            nonce: Arc::new(AtomicU64::new(0)),
+            synced: Arc::new(AtomicBool::new(false)),
+            sync_lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Returns the account the nonces are handed out for
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sync<M: Middleware + 'static>(&self, eth_client: &M) -> eyre::Result<()> {
+        // pending nonce, so a bundle isn't sent with the nonce of a cancelled bundle transaction
+        let nonce = eth_client
+            .get_transaction_count(self.address, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(|e| eyre::eyre!("Failed to get nonce of {:?}: {e:?}", self.address))?;
+        self.nonce.store(nonce.as_u64(), Ordering::SeqCst);
+        self.synced.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Returns the next nonce of the account, fetching the pending nonce from the execution
+    /// client if it wasn't yet
+    pub async fn next_nonce<M: Middleware + 'static>(&self, eth_client: &M) -> eyre::Result<U256> {
+        if !self.synced.load(Ordering::SeqCst) {
+            let _guard = self.sync_lock.lock().await;
+            if !self.synced.load(Ordering::SeqCst) {
+                self.sync(eth_client).await?;
+            }
+        }
+
+        Ok(self.nonce.fetch_add(1, Ordering::SeqCst).into())
+    }
+
+    /// Fetches the pending nonce from the execution client again (e.g. after the execution client
+    /// rejected a bundle because its nonce was already used)
+    pub async fn resync<M: Middleware + 'static>(&self, eth_client: &M) -> eyre::Result<()> {
+        let _guard = self.sync_lock.lock().await;
+        let stale = self.nonce.load(Ordering::SeqCst);
+        self.sync(eth_client).await?;
+        info!(
+            "Resynced nonce of {:?}: {stale} -> {}",
+            self.address,
+            self.nonce.load(Ordering::SeqCst)
+        );
+        Ok(())
+    }
+
+    /// Hands the nonce back when the bundle it was taken for isn't sent. Only the most recently
+    /// handed out nonce can be given back; otherwise the nonces would have a gap, so the pending
+    /// nonce is fetched again on the next use.
+    pub fn release(&self, nonce: U256) {
+        let nonce = nonce.as_u64();
+        if self
+            .nonce
+            .compare_exchange(nonce + 1, nonce, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            warn!("Nonce {nonce} of {:?} can't be released, a later one is in use", self.address);
+            self.synced.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Handles the failed send of the bundle with the nonce. The nonce is released only if the
+    /// bundle transaction wasn't broadcast; otherwise it may still be included, so the pending
+    /// nonce is fetched again on the next use.
+    pub fn send_failed(&self, nonce: U256, err: &eyre::Report) {
+        if err.downcast_ref::<NotBroadcast>().is_some() {
+            self.release(nonce);
+        } else {
+            warn!("Bundle with nonce {nonce} of {:?} may have been broadcast", self.address);
+            self.synced.store(false, Ordering::SeqCst);
+        }
+    }
+
+    /// Returns whether the error of sending the bundle is caused by an already used nonce (also
+    /// when a pending transaction with the same nonce can't be replaced)
+    pub fn is_nonce_error(err: &eyre::Report) -> bool {
+        let err = format!("{err:?}").to_lowercase();
+        err.contains("nonce too low") ||
+            err.contains("nonce is too low") ||
+            err.contains("replacement transaction underpriced")
+    }
+}
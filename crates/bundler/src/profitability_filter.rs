@@ -0,0 +1,78 @@
+use ethers::types::U256;
+use silius_primitives::UserOperation;
+
+/// Precision of the revenue multiplier (basis points)
+const MULTIPLIER_PRECISION: f64 = 10_000.0;
+
+/// Filter that skips bundles whose collected fees don't cover the cost of the submission
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProfitabilityFilter {
+    /// Minimum fee (in wei) the bundler has to earn on top of the submission cost
+    pub min_net_fee: U256,
+    /// Collected fees have to be at least the submission cost times this multiplier
+    pub min_bundle_revenue_multiplier: f64,
+    /// Whether unprofitable bundles are still submitted (only logged)
+    pub always_submit: bool,
+}
+
+impl Default for ProfitabilityFilter {
+    fn default() -> Self {
+        Self { min_net_fee: U256::zero(), min_bundle_revenue_multiplier: 1.0, always_submit: false }
+    }
+}
+
+impl ProfitabilityFilter {
+    pub fn new(min_net_fee: U256, min_bundle_revenue_multiplier: f64, always_submit: bool) -> Self {
+        Self { min_net_fee, min_bundle_revenue_multiplier, always_submit }
+    }
+
+    /// Gas price the user operation pays at the base fee
+    pub fn effective_gas_price(uo: &UserOperation, base_fee: U256) -> U256 {
+        uo.max_fee_per_gas.min(base_fee.saturating_add(uo.max_priority_fee_per_gas))
+    }
+
+    /// Gas the user operation is expected to use (all of its gas limits)
+    pub fn estimated_gas_used(uo: &UserOperation) -> U256 {
+        uo.call_gas_limit
+            .saturating_add(uo.verification_gas_limit)
+            .saturating_add(uo.pre_verification_gas)
+    }
+
+    /// Fees the bundler collects from the user operations of the bundle
+    pub fn gross_fees(bundle: &[UserOperation], base_fee: U256) -> U256 {
+        bundle.iter().fold(U256::zero(), |acc, uo| {
+            acc.saturating_add(
+                Self::effective_gas_price(uo, base_fee)
+                    .saturating_mul(Self::estimated_gas_used(uo)),
+            )
+        })
+    }
+
+    /// Fees the bundle has to collect (submission cost times the revenue multiplier)
+    pub fn required_revenue(&self, base_fee: U256, submission_gas: U256) -> U256 {
+        let cost = base_fee.saturating_mul(submission_gas);
+        let multiplier = U256::from(
+            (self.min_bundle_revenue_multiplier.max(0.0) * MULTIPLIER_PRECISION).round() as u64,
+        );
+        cost.saturating_mul(multiplier) / U256::from(MULTIPLIER_PRECISION as u64)
+    }
+
+    /// Whether the collected fees of the bundle cover the cost of submitting it
+    ///
+    /// # Arguments
+    /// * `bundle` - User operations of the bundle
+    /// * `base_fee` - Base fee per gas of the latest block
+    /// * `submission_gas` - Estimated gas of the bundle transaction
+    pub fn is_profitable(
+        &self,
+        bundle: &[UserOperation],
+        base_fee: U256,
+        submission_gas: U256,
+    ) -> bool {
+        let gross_fees = Self::gross_fees(bundle, base_fee);
+        let cost = base_fee.saturating_mul(submission_gas);
+
+        gross_fees >= self.required_revenue(base_fee, submission_gas) &&
+            gross_fees.saturating_sub(cost) >= self.min_net_fee
+    }
+}
@@ -0,0 +1,164 @@
+use silius_primitives::{
+    constants::bundler::{RELAY_FAILURE_THRESHOLD, RELAY_RETRY_INTERVAL},
+    RelayHealth, RelayStatus,
+};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// State of the circuit breaker of a relay
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Circuit {
+    /// Requests are sent to the relay
+    Closed,
+    /// The relay is skipped since it failed too many times in a row
+    Open { since: Instant },
+    /// The retry interval passed, the next request decides whether the circuit closes again
+    HalfOpen,
+}
+
+/// Health of one relay
+#[derive(Clone, Debug)]
+struct RelayState {
+    circuit: Circuit,
+    consecutive_failures: u32,
+    last_success: Option<Instant>,
+    last_error: Option<String>,
+}
+
+impl Default for RelayState {
+    fn default() -> Self {
+        Self {
+            circuit: Circuit::Closed,
+            consecutive_failures: 0,
+            last_success: None,
+            last_error: None,
+        }
+    }
+}
+
+/// Picks the relay the bundle is sent to.
+///
+/// The relays are tried in the order they were configured in (priority order). A relay that
+/// fails `failure_threshold` times in a row is skipped (the circuit opens), and after
+/// `retry_interval` one request is sent to it again: the circuit closes if it succeeds, or stays
+/// open for another interval if it fails.
+#[derive(Debug)]
+pub struct RelayRouter {
+    /// Relay endpoints in priority order
+    relays: Vec<String>,
+    states: Mutex<Vec<RelayState>>,
+    failure_threshold: u32,
+    retry_interval: Duration,
+}
+
+impl RelayRouter {
+    /// Create a new relay router with the default failure threshold and retry interval
+    ///
+    /// # Arguments
+    /// * `relays` - Relay endpoints in priority order
+    pub fn new(relays: Vec<String>) -> Self {
+        Self::with_circuit_breaker(
+            relays,
+            RELAY_FAILURE_THRESHOLD,
+            Duration::from_secs(RELAY_RETRY_INTERVAL),
+        )
+    }
+
+    /// Create a new relay router
+    ///
+    /// # Arguments
+    /// * `relays` - Relay endpoints in priority order
+    /// * `failure_threshold` - Number of consecutive failures after which a relay is skipped
+    /// * `retry_interval` - Time after which a skipped relay is tried again
+    pub fn with_circuit_breaker(
+        relays: Vec<String>,
+        failure_threshold: u32,
+        retry_interval: Duration,
+    ) -> Self {
+        let states = Mutex::new(vec![RelayState::default(); relays.len()]);
+        Self { relays, states, failure_threshold: failure_threshold.max(1), retry_interval }
+    }
+
+    /// Relay endpoints in priority order
+    pub fn relays(&self) -> &[String] {
+        &self.relays
+    }
+
+    /// Returns the indexes of the relays that can be tried, in priority order
+    ///
+    /// Relays whose circuit has been open for the retry interval are half-opened and returned as
+    /// well.
+    pub fn available(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let mut states = self.states.lock().expect("Lock is not poisoned");
+
+        states
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(idx, state)| {
+                if let Circuit::Open { since } = state.circuit {
+                    if now.duration_since(since) < self.retry_interval {
+                        return None;
+                    }
+                    state.circuit = Circuit::HalfOpen;
+                }
+                Some(idx)
+            })
+            .collect()
+    }
+
+    /// Records a successful request to the relay (closes its circuit)
+    pub fn record_success(&self, idx: usize) {
+        let mut states = self.states.lock().expect("Lock is not poisoned");
+        if let Some(state) = states.get_mut(idx) {
+            state.circuit = Circuit::Closed;
+            state.consecutive_failures = 0;
+            state.last_success = Some(Instant::now());
+        }
+    }
+
+    /// Records a failed request to the relay (opens its circuit after too many failures)
+    pub fn record_failure(&self, idx: usize, err: String) {
+        let mut states = self.states.lock().expect("Lock is not poisoned");
+        if let Some(state) = states.get_mut(idx) {
+            state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+            state.last_error = Some(err);
+
+            let open = match state.circuit {
+                Circuit::Closed => state.consecutive_failures >= self.failure_threshold,
+                Circuit::HalfOpen => true,
+                Circuit::Open { .. } => false,
+            };
+            if open {
+                warn!(
+                    "Relay {} failed {} times in a row, skipping it for {:?}",
+                    self.relays[idx], state.consecutive_failures, self.retry_interval
+                );
+                state.circuit = Circuit::Open { since: Instant::now() };
+            }
+        }
+    }
+
+    /// Returns the status of every relay, in priority order
+    pub fn status(&self) -> Vec<RelayStatus> {
+        let states = self.states.lock().expect("Lock is not poisoned");
+
+        self.relays
+            .iter()
+            .zip(states.iter())
+            .map(|(relay, state)| RelayStatus {
+                relay: relay.clone(),
+                status: match state.circuit {
+                    Circuit::Open { .. } => RelayHealth::Down,
+                    Circuit::HalfOpen => RelayHealth::Degraded,
+                    Circuit::Closed if state.consecutive_failures > 0 => RelayHealth::Degraded,
+                    Circuit::Closed => RelayHealth::Ok,
+                },
+                last_error: state.last_error.clone(),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,232 @@
+use ethers::{
+    contract::{parse_log, EthEvent},
+    providers::Middleware,
+    types::{Address, Filter, U256},
+};
+use silius_contracts::entry_point::UserOperationEventFilter;
+use silius_primitives::{
+    constants::bundler::INCLUSION_MAX_PENDING_BLOCKS, UserOperation, UserOperationHash,
+};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Capacity of the event channel (slow subscribers miss the oldest events)
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// Emitted when a user operation of a submitted bundle is included on-chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OperationIncludedEvent {
+    /// Entry point that executed the user operation
+    pub entry_point: Address,
+    /// Hash of the user operation
+    pub uo_hash: UserOperationHash,
+    /// Sender of the user operation
+    pub sender: Address,
+    /// Nonce of the user operation
+    pub nonce: U256,
+    /// Whether the execution of the user operation succeeded
+    pub success: bool,
+    /// Block the user operation was included in
+    pub block_number: u64,
+}
+
+/// User operation of a submitted bundle that isn't included yet
+#[derive(Clone, Debug)]
+struct PendingUserOperation {
+    /// Entry point the bundle was submitted to
+    ep: Address,
+    /// The user operation
+    uo: UserOperation,
+    /// Block the user operation was tracked at (None until the first poll)
+    tracked_at: Option<u64>,
+}
+
+/// Removal of the included user operations from the mempool (the bundler talks to the uopool
+/// over gRPC)
+#[async_trait::async_trait]
+pub trait RemoveUserOperations: Send + Sync {
+    /// Removes the user operations from the mempool of the entry point and counts them as
+    /// included for the reputation of their entities
+    async fn remove_user_operations(
+        &self,
+        ep: &Address,
+        uos: Vec<UserOperation>,
+    ) -> eyre::Result<()>;
+}
+
+/// Watches the blocks for the `UserOperationEvent` logs of submitted bundles and removes the
+/// included user operations from the mempool, so that replacements of them can be accepted
+#[derive(Clone, Debug)]
+pub struct ReplacementNotifier<M: Middleware> {
+    /// Connection to the execution client
+    pub eth_client: Arc<M>,
+    /// Number of blocks after which a user operation that isn't included is no longer tracked
+    pub max_pending_blocks: u64,
+    /// User operations of the submitted bundles that aren't included yet
+    pending: Arc<Mutex<HashMap<UserOperationHash, PendingUserOperation>>>,
+    /// Last block the logs were processed for
+    last_block: Arc<Mutex<Option<u64>>>,
+    /// Channel the included user operations are broadcast to
+    event_sender: broadcast::Sender<OperationIncludedEvent>,
+    /// Whether the background task is running
+    started: Arc<AtomicBool>,
+}
+
+impl<M: Middleware + 'static> ReplacementNotifier<M> {
+    pub fn new(eth_client: Arc<M>) -> Self {
+        let (event_sender, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            eth_client,
+            max_pending_blocks: INCLUSION_MAX_PENDING_BLOCKS,
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            last_block: Arc::new(Mutex::new(None)),
+            event_sender,
+            started: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Subscribes to the included user operations
+    pub fn subscribe(&self) -> broadcast::Receiver<OperationIncludedEvent> {
+        self.event_sender.subscribe()
+    }
+
+    /// Tracks the user operations of a bundle submitted to the entry point
+    pub fn track(&self, ep: Address, uos: &[UserOperation]) {
+        let tracked_at = *self.last_block.lock().expect("Lock is not poisoned");
+        let mut pending = self.pending.lock().expect("Lock is not poisoned");
+        for uo in uos {
+            pending.insert(uo.hash, PendingUserOperation { ep, uo: uo.clone(), tracked_at });
+        }
+    }
+
+    /// Number of tracked user operations that aren't included yet
+    pub fn pending(&self) -> usize {
+        self.pending.lock().expect("Lock is not poisoned").len()
+    }
+
+    /// Processes the `UserOperationEvent` logs of the blocks since the last poll
+    ///
+    /// # Arguments
+    /// * `mempool` - The mempool the included user operations are removed from
+    ///
+    /// # Returns
+    /// The tracked user operations included in the processed blocks
+    pub async fn poll<R: RemoveUserOperations>(
+        &self,
+        mempool: &R,
+    ) -> eyre::Result<Vec<OperationIncludedEvent>> {
+        let latest = self.eth_client.get_block_number().await?.as_u64();
+        let from = match *self.last_block.lock().expect("Lock is not poisoned") {
+            Some(last) if last >= latest => return Ok(vec![]),
+            Some(last) => last + 1,
+            None => latest,
+        };
+
+        let eps: Vec<Address> = {
+            let pending = self.pending.lock().expect("Lock is not poisoned");
+            let mut eps: Vec<Address> = pending.values().map(|op| op.ep).collect();
+            eps.sort();
+            eps.dedup();
+            eps
+        };
+
+        let mut events = vec![];
+        if !eps.is_empty() {
+            let filter = Filter::new()
+                .address(eps)
+                .topic0(UserOperationEventFilter::signature())
+                .from_block(from)
+                .to_block(latest);
+            let logs = self.eth_client.get_logs(&filter).await?;
+
+            let mut included: HashMap<Address, Vec<UserOperation>> = HashMap::new();
+            {
+                let mut pending = self.pending.lock().expect("Lock is not poisoned");
+                for log in logs {
+                    let block_number = log.block_number.map(|n| n.as_u64()).unwrap_or(latest);
+                    let event = match parse_log::<UserOperationEventFilter>(log) {
+                        Ok(event) => event,
+                        Err(e) => {
+                            warn!("Failed to parse UserOperationEvent log: {e:?}");
+                            continue;
+                        }
+                    };
+
+                    let uo_hash = UserOperationHash::from(event.user_op_hash);
+                    if let Some(PendingUserOperation { ep, uo, .. }) = pending.remove(&uo_hash) {
+                        events.push(OperationIncludedEvent {
+                            entry_point: ep,
+                            uo_hash,
+                            sender: event.sender,
+                            nonce: event.nonce,
+                            success: event.success,
+                            block_number,
+                        });
+                        included.entry(ep).or_default().push(uo);
+                    }
+                }
+
+                // the user operations of bundles that were dropped, replaced or reverted are
+                // never included
+                let tracked = pending.len();
+                pending.retain(|_, op| {
+                    let tracked_at = *op.tracked_at.get_or_insert(latest);
+                    latest.saturating_sub(tracked_at) <= self.max_pending_blocks
+                });
+                if pending.len() < tracked {
+                    debug!(
+                        "Stopped tracking {} user operations not included within {} blocks",
+                        tracked - pending.len(),
+                        self.max_pending_blocks
+                    );
+                }
+            }
+
+            for (ep, uos) in included {
+                info!("{} user operations of entry point {ep:?} included on-chain", uos.len());
+                if let Err(e) = mempool.remove_user_operations(&ep, uos.clone()).await {
+                    // the blocks are processed again on the next poll
+                    self.track(ep, &uos);
+                    return Err(e);
+                }
+            }
+
+            for event in events.iter() {
+                // no subscribers isn't an error
+                let _ = self.event_sender.send(event.clone());
+            }
+        }
+
+        *self.last_block.lock().expect("Lock is not poisoned") = Some(latest);
+        Ok(events)
+    }
+
+    /// Starts polling the blocks every `poll_interval` (only the first call starts the task)
+    pub fn start<R: RemoveUserOperations + 'static>(
+        &self,
+        mempool: Arc<R>,
+        poll_interval: Duration,
+    ) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let notifier = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = notifier.poll(mempool.as_ref()).await {
+                    warn!("Failed to process included user operations: {e:?}");
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        });
+    }
+}
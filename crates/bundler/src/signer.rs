@@ -0,0 +1,230 @@
+#[cfg(feature = "aws-kms")]
+use ethers::signers::AwsSigner;
+#[cfg(feature = "ledger")]
+use ethers::signers::{HDPath, Ledger};
+use ethers::{
+    signers::{LocalWallet, Signer as EthersSigner},
+    types::{
+        transaction::{eip2718::TypedTransaction, eip712::Eip712},
+        Address, Bytes, Signature,
+    },
+};
+#[cfg(any(feature = "aws-kms", feature = "ledger"))]
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Error when signing a transaction fails
+#[derive(Debug, Error)]
+pub enum SignerError {
+    /// Local wallet error
+    #[error("wallet error: {inner}")]
+    Wallet { inner: String },
+    /// AWS KMS error
+    #[error("aws kms error: {inner}")]
+    AwsKms { inner: String },
+    /// Ledger error
+    #[error("ledger error: {inner}")]
+    Ledger { inner: String },
+}
+
+/// Key backend used by the bundler to sign transactions
+#[derive(Clone, Debug)]
+pub enum Signer {
+    /// Private key held in memory (loaded from a mnemonic file or a private key)
+    LocalWallet(LocalWallet),
+    /// Key stored in AWS KMS
+    #[cfg(feature = "aws-kms")]
+    AwsKms { key_id: String, signer: Arc<AwsSigner> },
+    /// Key stored on a Ledger hardware wallet
+    #[cfg(feature = "ledger")]
+    LedgerHardware { derivation_path: String, signer: Arc<Ledger> },
+}
+
+impl Signer {
+    /// Creates a signer backed by an AWS KMS key
+    ///
+    /// # Arguments
+    /// * `client` - The AWS KMS client
+    /// * `key_id` - The id of the secp256k1 key in AWS KMS
+    /// * `chain_id` - The chain id of the blockchain network to be used
+    ///
+    /// # Returns
+    /// * `Self` - A new `Signer` instance
+    #[cfg(feature = "aws-kms")]
+    pub async fn aws_kms(
+        client: rusoto_kms::KmsClient,
+        key_id: String,
+        chain_id: u64,
+    ) -> Result<Self, SignerError> {
+        let signer = AwsSigner::new(client, key_id.clone(), chain_id)
+            .await
+            .map_err(|e| SignerError::AwsKms { inner: e.to_string() })?;
+        Ok(Self::AwsKms { key_id, signer: Arc::new(signer) })
+    }
+
+    /// Creates a signer backed by an AWS KMS key, the region and the credentials are read from
+    /// the environment (`AWS_REGION`, `AWS_ACCESS_KEY_ID`, ...)
+    ///
+    /// # Arguments
+    /// * `key_id` - The id of the secp256k1 key in AWS KMS
+    /// * `chain_id` - The chain id of the blockchain network to be used
+    ///
+    /// # Returns
+    /// * `Self` - A new `Signer` instance
+    #[cfg(feature = "aws-kms")]
+    pub async fn aws_kms_from_env(key_id: String, chain_id: u64) -> Result<Self, SignerError> {
+        let client = rusoto_kms::KmsClient::new(rusoto_core::Region::default());
+        Self::aws_kms(client, key_id, chain_id).await
+    }
+
+    /// Creates a signer backed by a Ledger hardware wallet
+    ///
+    /// # Arguments
+    /// * `derivation_path` - The derivation path of the account, e.g. `m/44'/60'/0'/0/0`
+    /// * `chain_id` - The chain id of the blockchain network to be used
+    ///
+    /// # Returns
+    /// * `Self` - A new `Signer` instance
+    #[cfg(feature = "ledger")]
+    pub async fn ledger(derivation_path: String, chain_id: u64) -> Result<Self, SignerError> {
+        let signer = Ledger::new(HDPath::Other(derivation_path.clone()), chain_id)
+            .await
+            .map_err(|e| SignerError::Ledger { inner: e.to_string() })?;
+        Ok(Self::LedgerHardware { derivation_path, signer: Arc::new(signer) })
+    }
+
+    /// Signs the transaction and returns it RLP encoded, ready for `eth_sendRawTransaction`
+    ///
+    /// # Arguments
+    /// * `tx` - The transaction to sign, the chain id of the signer is used if not set
+    ///
+    /// # Returns
+    /// * `Bytes` - The signed transaction
+    pub async fn sign_transaction(&self, mut tx: TypedTransaction) -> Result<Bytes, SignerError> {
+        if tx.chain_id().is_none() {
+            tx.set_chain_id(self.chain_id());
+        }
+
+        let signature = EthersSigner::sign_transaction(self, &tx).await?;
+        Ok(tx.rlp_signed(&signature))
+    }
+}
+
+/// Lets the [Signer](Signer) back a `SignerMiddleware`, so the bundles are signed by the
+/// configured backend
+#[async_trait::async_trait]
+impl EthersSigner for Signer {
+    type Error = SignerError;
+
+    async fn sign_message<S: Send + Sync + AsRef<[u8]>>(
+        &self,
+        message: S,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Signer::LocalWallet(wallet) => wallet
+                .sign_message(message)
+                .await
+                .map_err(|e| SignerError::Wallet { inner: e.to_string() }),
+            #[cfg(feature = "aws-kms")]
+            Signer::AwsKms { signer, .. } => signer
+                .sign_message(message)
+                .await
+                .map_err(|e| SignerError::AwsKms { inner: e.to_string() }),
+            #[cfg(feature = "ledger")]
+            Signer::LedgerHardware { signer, .. } => signer
+                .sign_message(message)
+                .await
+                .map_err(|e| SignerError::Ledger { inner: e.to_string() }),
+        }
+    }
+
+    async fn sign_transaction(&self, tx: &TypedTransaction) -> Result<Signature, Self::Error> {
+        match self {
+            Signer::LocalWallet(wallet) => EthersSigner::sign_transaction(wallet, tx)
+                .await
+                .map_err(|e| SignerError::Wallet { inner: e.to_string() }),
+            #[cfg(feature = "aws-kms")]
+            Signer::AwsKms { signer, .. } => EthersSigner::sign_transaction(signer.as_ref(), tx)
+                .await
+                .map_err(|e| SignerError::AwsKms { inner: e.to_string() }),
+            #[cfg(feature = "ledger")]
+            Signer::LedgerHardware { signer, .. } => {
+                EthersSigner::sign_transaction(signer.as_ref(), tx)
+                    .await
+                    .map_err(|e| SignerError::Ledger { inner: e.to_string() })
+            }
+        }
+    }
+
+    async fn sign_typed_data<T: Eip712 + Send + Sync>(
+        &self,
+        payload: &T,
+    ) -> Result<Signature, Self::Error> {
+        match self {
+            Signer::LocalWallet(wallet) => wallet
+                .sign_typed_data(payload)
+                .await
+                .map_err(|e| SignerError::Wallet { inner: e.to_string() }),
+            #[cfg(feature = "aws-kms")]
+            Signer::AwsKms { signer, .. } => signer
+                .sign_typed_data(payload)
+                .await
+                .map_err(|e| SignerError::AwsKms { inner: e.to_string() }),
+            #[cfg(feature = "ledger")]
+            Signer::LedgerHardware { signer, .. } => signer
+                .sign_typed_data(payload)
+                .await
+                .map_err(|e| SignerError::Ledger { inner: e.to_string() }),
+        }
+    }
+
+    /// Address of the signing key
+    fn address(&self) -> Address {
+        match self {
+            Signer::LocalWallet(wallet) => wallet.address(),
+            #[cfg(feature = "aws-kms")]
+            Signer::AwsKms { signer, .. } => signer.address(),
+            #[cfg(feature = "ledger")]
+            Signer::LedgerHardware { signer, .. } => signer.address(),
+        }
+    }
+
+    /// Chain id the signer signs transactions for
+    fn chain_id(&self) -> u64 {
+        match self {
+            Signer::LocalWallet(wallet) => wallet.chain_id(),
+            #[cfg(feature = "aws-kms")]
+            Signer::AwsKms { signer, .. } => signer.chain_id(),
+            #[cfg(feature = "ledger")]
+            Signer::LedgerHardware { signer, .. } => signer.chain_id(),
+        }
+    }
+
+    /// Sets the chain id (the remote backends keep the chain id they were created with while
+    /// the signer is shared)
+    fn with_chain_id<T: Into<u64>>(self, chain_id: T) -> Self {
+        match self {
+            Signer::LocalWallet(wallet) => Signer::LocalWallet(wallet.with_chain_id(chain_id)),
+            #[cfg(feature = "aws-kms")]
+            Signer::AwsKms { key_id, signer } => Signer::AwsKms {
+                key_id,
+                signer: Arc::try_unwrap(signer)
+                    .map(|signer| Arc::new(signer.with_chain_id(chain_id)))
+                    .unwrap_or_else(|signer| signer),
+            },
+            #[cfg(feature = "ledger")]
+            Signer::LedgerHardware { derivation_path, signer } => Signer::LedgerHardware {
+                derivation_path,
+                signer: Arc::try_unwrap(signer)
+                    .map(|signer| Arc::new(signer.with_chain_id(chain_id)))
+                    .unwrap_or_else(|signer| signer),
+            },
+        }
+    }
+}
+
+impl From<LocalWallet> for Signer {
+    fn from(wallet: LocalWallet) -> Self {
+        Signer::LocalWallet(wallet)
+    }
+}
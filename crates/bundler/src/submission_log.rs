@@ -0,0 +1,318 @@
+use ethers::{
+    providers::Middleware,
+    types::{Address, H256, I256, U256, U64},
+};
+use silius_primitives::{
+    constants::bundler::{INCLUSION_POLL_INTERVAL, SUBMISSION_CONFIRMATION_TIMEOUT},
+    UserOperation, UserOperationHash,
+};
+use sqlx::{
+    sqlite::{SqliteConnectOptions, SqlitePool, SqliteRow},
+    Row,
+};
+use std::{
+    fmt::{self, Display},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use tracing::warn;
+
+/// Statements creating the tables of the submission log
+const SCHEMA: [&str; 4] = [
+    "CREATE TABLE IF NOT EXISTS bundles (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        entry_point TEXT NOT NULL,
+        tx_hash TEXT NOT NULL,
+        ops_count INTEGER NOT NULL,
+        gas_limit TEXT NOT NULL,
+        base_fee TEXT NOT NULL,
+        net_fee TEXT,
+        gas_used TEXT,
+        submitted_at INTEGER NOT NULL,
+        confirmed_at INTEGER,
+        status TEXT NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS bundles_tx_hash ON bundles (tx_hash)",
+    "CREATE INDEX IF NOT EXISTS bundles_submitted_at ON bundles (submitted_at)",
+    "CREATE TABLE IF NOT EXISTS bundle_ops (
+        bundle_id INTEGER NOT NULL REFERENCES bundles (id),
+        op_hash TEXT NOT NULL,
+        sender TEXT NOT NULL,
+        nonce TEXT NOT NULL
+    )",
+];
+
+/// Status of a submitted bundle transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Submitted, but not included yet
+    Pending,
+    /// Included and executed successfully
+    Included,
+    /// Included, but the execution reverted
+    Reverted,
+    /// Not included before the confirmation timeout
+    Dropped,
+}
+
+impl Display for TxStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            TxStatus::Pending => "pending",
+            TxStatus::Included => "included",
+            TxStatus::Reverted => "reverted",
+            TxStatus::Dropped => "dropped",
+        };
+        write!(f, "{status}")
+    }
+}
+
+impl FromStr for TxStatus {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(TxStatus::Pending),
+            "included" => Ok(TxStatus::Included),
+            "reverted" => Ok(TxStatus::Reverted),
+            "dropped" => Ok(TxStatus::Dropped),
+            _ => Err(eyre::format_err!("Unknown transaction status {s}")),
+        }
+    }
+}
+
+/// User operation of a submitted bundle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleOp {
+    pub op_hash: UserOperationHash,
+    pub sender: Address,
+    pub nonce: U256,
+}
+
+impl From<&UserOperation> for BundleOp {
+    fn from(uo: &UserOperation) -> Self {
+        Self { op_hash: uo.hash, sender: uo.sender, nonce: uo.nonce }
+    }
+}
+
+/// Bundle handed to the bundle client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleSubmission {
+    /// Entry point the bundle is sent to
+    pub entry_point: Address,
+    /// Hash returned by the bundle client
+    pub tx_hash: H256,
+    /// Gas limit of the bundle transaction
+    pub gas_limit: U256,
+    /// Base fee of the latest block at the time of the submission
+    pub base_fee: U256,
+    /// Fees collected by the beneficiary minus the transaction cost (None if unknown)
+    pub net_fee: Option<I256>,
+    /// Unix timestamp of the submission in seconds
+    pub submitted_at: u64,
+    /// User operations in the bundle
+    pub ops: Vec<BundleOp>,
+}
+
+/// Bundle read back from the submission log
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BundleRecord {
+    pub id: i64,
+    pub entry_point: Address,
+    pub tx_hash: H256,
+    pub ops_count: usize,
+    pub gas_limit: U256,
+    pub base_fee: U256,
+    pub net_fee: Option<I256>,
+    /// Gas used by the bundle transaction (None until the bundle is confirmed)
+    pub gas_used: Option<U256>,
+    pub submitted_at: u64,
+    /// Unix timestamp of the confirmation in seconds (None until the bundle is confirmed)
+    pub confirmed_at: Option<u64>,
+    pub status: TxStatus,
+}
+
+impl TryFrom<SqliteRow> for BundleRecord {
+    type Error = eyre::Error;
+
+    fn try_from(row: SqliteRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            entry_point: row.try_get::<String, _>("entry_point")?.parse()?,
+            tx_hash: row.try_get::<String, _>("tx_hash")?.parse()?,
+            ops_count: row.try_get::<i64, _>("ops_count")? as usize,
+            gas_limit: U256::from_dec_str(&row.try_get::<String, _>("gas_limit")?)?,
+            base_fee: U256::from_dec_str(&row.try_get::<String, _>("base_fee")?)?,
+            net_fee: row
+                .try_get::<Option<String>, _>("net_fee")?
+                .map(|fee| I256::from_dec_str(&fee))
+                .transpose()?,
+            gas_used: row
+                .try_get::<Option<String>, _>("gas_used")?
+                .map(|gas| U256::from_dec_str(&gas))
+                .transpose()?,
+            submitted_at: row.try_get::<i64, _>("submitted_at")? as u64,
+            confirmed_at: row.try_get::<Option<i64>, _>("confirmed_at")?.map(|at| at as u64),
+            status: row.try_get::<String, _>("status")?.parse()?,
+        })
+    }
+}
+
+/// Unix timestamp in seconds
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+/// History of the submitted bundles, persisted to a local SQLite database for post-incident
+/// analysis
+#[derive(Clone, Debug)]
+pub struct SubmissionLog {
+    pub db: SqlitePool,
+}
+
+impl SubmissionLog {
+    /// Opens the database (created if missing) and creates the tables
+    ///
+    /// # Arguments
+    /// * `path` - Path of the SQLite database file
+    pub async fn connect(path: &Path) -> eyre::Result<Self> {
+        let options = SqliteConnectOptions::new().filename(path).create_if_missing(true);
+        let db = SqlitePool::connect_with(options).await?;
+        for statement in SCHEMA {
+            sqlx::query(statement).execute(&db).await?;
+        }
+        Ok(Self { db })
+    }
+
+    /// Records the submitted bundle with the status [Pending](TxStatus::Pending)
+    ///
+    /// # Returns
+    /// * `i64` - The id of the bundle in the log
+    pub async fn record_submission(&self, bundle: &BundleSubmission) -> eyre::Result<i64> {
+        let mut tx = self.db.begin().await?;
+
+        let id = sqlx::query(
+            "INSERT INTO bundles (entry_point, tx_hash, ops_count, gas_limit, base_fee, net_fee, \
+             submitted_at, status) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(format!("{:?}", bundle.entry_point))
+        .bind(format!("{:?}", bundle.tx_hash))
+        .bind(bundle.ops.len() as i64)
+        .bind(bundle.gas_limit.to_string())
+        .bind(bundle.base_fee.to_string())
+        .bind(bundle.net_fee.map(|fee| fee.to_string()))
+        .bind(bundle.submitted_at as i64)
+        .bind(TxStatus::Pending.to_string())
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        for op in bundle.ops.iter() {
+            sqlx::query(
+                "INSERT INTO bundle_ops (bundle_id, op_hash, sender, nonce) VALUES (?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(format!("{:?}", op.op_hash.0))
+            .bind(format!("{:?}", op.sender))
+            .bind(op.nonce.to_string())
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    /// Records the outcome of the bundle transaction (a multicall transaction confirms the
+    /// bundles of all its entry points)
+    pub async fn record_confirmation(
+        &self,
+        tx_hash: H256,
+        gas_used: U256,
+        status: TxStatus,
+    ) -> eyre::Result<()> {
+        sqlx::query(
+            "UPDATE bundles SET gas_used = ?, confirmed_at = ?, status = ? WHERE tx_hash = ?",
+        )
+        .bind(gas_used.to_string())
+        .bind(unix_timestamp() as i64)
+        .bind(status.to_string())
+        .bind(format!("{tx_hash:?}"))
+        .execute(&self.db)
+        .await?;
+        Ok(())
+    }
+
+    /// Returns the bundles submitted in the time range, oldest first
+    ///
+    /// # Arguments
+    /// * `from` - Unix timestamp in seconds (inclusive)
+    /// * `to` - Unix timestamp in seconds (exclusive)
+    pub async fn history(&self, from: u64, to: u64) -> eyre::Result<Vec<BundleRecord>> {
+        sqlx::query(
+            "SELECT * FROM bundles WHERE submitted_at >= ? AND submitted_at < ? ORDER BY \
+             submitted_at, id",
+        )
+        .bind(from as i64)
+        .bind(to as i64)
+        .fetch_all(&self.db)
+        .await?
+        .into_iter()
+        .map(BundleRecord::try_from)
+        .collect()
+    }
+
+    /// Returns the user operations of the bundle
+    pub async fn ops(&self, bundle_id: i64) -> eyre::Result<Vec<BundleOp>> {
+        sqlx::query("SELECT op_hash, sender, nonce FROM bundle_ops WHERE bundle_id = ?")
+            .bind(bundle_id)
+            .fetch_all(&self.db)
+            .await?
+            .into_iter()
+            .map(|row| {
+                Ok(BundleOp {
+                    op_hash: row.try_get::<String, _>("op_hash")?.parse::<H256>()?.into(),
+                    sender: row.try_get::<String, _>("sender")?.parse()?,
+                    nonce: U256::from_dec_str(&row.try_get::<String, _>("nonce")?)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Waits in the background for the receipt of the bundle transaction and records the
+    /// confirmation (the bundle is dropped if there is no receipt before the timeout)
+    pub fn watch_confirmation<M: Middleware + 'static>(
+        self: Arc<Self>,
+        eth_client: Arc<M>,
+        tx_hash: H256,
+    ) {
+        tokio::spawn(async move {
+            let deadline = Instant::now() + Duration::from_secs(SUBMISSION_CONFIRMATION_TIMEOUT);
+            let (gas_used, status) = loop {
+                match eth_client.get_transaction_receipt(tx_hash).await {
+                    Ok(Some(receipt)) => {
+                        let status = if receipt.status == Some(U64::from(1)) {
+                            TxStatus::Included
+                        } else {
+                            TxStatus::Reverted
+                        };
+                        break (receipt.gas_used.unwrap_or_default(), status);
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to get receipt of bundle {tx_hash:?}: {e:?}"),
+                }
+
+                if Instant::now() >= deadline {
+                    break (U256::zero(), TxStatus::Dropped);
+                }
+                tokio::time::sleep(Duration::from_secs(INCLUSION_POLL_INTERVAL)).await;
+            };
+
+            if let Err(e) = self.record_confirmation(tx_hash, gas_used, status).await {
+                warn!("Failed to record confirmation of bundle {tx_hash:?}: {e:?}");
+            }
+        });
+    }
+}
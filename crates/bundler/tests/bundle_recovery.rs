@@ -0,0 +1,81 @@
+use ethers::{
+    contract::EthEvent,
+    providers::{MockProvider, Provider},
+    types::{Address, Log, TransactionReceipt, H256, U64},
+};
+use silius_bundler::{bundle_reverted, BundleRecovery, RemoveFailedUserOperations};
+use silius_contracts::entry_point::UserOperationEventFilter;
+use silius_primitives::{UserOperation, UserOperationSigned};
+use std::sync::{Arc, Mutex};
+
+/// Records the user operations removed from the mempool and the banned senders
+#[derive(Default)]
+struct MockMempool {
+    removed: Mutex<Vec<UserOperation>>,
+    banned: Mutex<Vec<Address>>,
+}
+
+#[async_trait::async_trait]
+impl RemoveFailedUserOperations for MockMempool {
+    async fn remove_failed_user_operations(
+        &self,
+        _ep: &Address,
+        uos: Vec<UserOperation>,
+        banned: Vec<Address>,
+    ) -> eyre::Result<()> {
+        self.removed.lock().unwrap().extend(uos);
+        self.banned.lock().unwrap().extend(banned);
+        Ok(())
+    }
+}
+
+fn uo(sender: Address) -> UserOperation {
+    UserOperation::from_user_operation_signed(
+        H256::random().into(),
+        UserOperationSigned::default().sender(sender),
+    )
+}
+
+#[tokio::test]
+async fn failing_user_operations_are_dropped() -> eyre::Result<()> {
+    // no responses, so the simulation of every user operation fails
+    let (provider, _) = Provider::<MockProvider>::mocked();
+    let mut recovery = BundleRecovery::new(Arc::new(provider));
+    recovery.ban_slack = 2;
+    let mempool = MockMempool::default();
+    let (ep, sender) = (Address::random(), Address::random());
+
+    let dropped = recovery.recover(&mempool, ep, vec![uo(sender), uo(Address::random())]).await?;
+    assert_eq!(dropped.len(), 2);
+    assert_eq!(mempool.removed.lock().unwrap().len(), 2);
+    assert_eq!(recovery.failures(&sender), 1);
+    assert!(mempool.banned.lock().unwrap().is_empty());
+
+    // the sender is banned after failing `ban_slack` times in a row
+    recovery.recover(&mempool, ep, vec![uo(sender)]).await?;
+    assert_eq!(*mempool.banned.lock().unwrap(), vec![sender]);
+    assert_eq!(recovery.failures(&sender), 0);
+
+    Ok(())
+}
+
+#[test]
+fn reverted_handle_ops_of_multicall() {
+    let (ep, other) = (Address::random(), Address::random());
+    let receipt = TransactionReceipt {
+        status: Some(U64::from(1)),
+        logs: vec![Log {
+            address: ep,
+            topics: vec![UserOperationEventFilter::signature(), H256::random()],
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    // the multicall transaction succeeded, but only the first entry point executed its bundle
+    assert!(!bundle_reverted(&receipt, &ep));
+    assert!(bundle_reverted(&receipt, &other));
+
+    let reverted = TransactionReceipt { status: Some(U64::zero()), ..receipt };
+    assert!(bundle_reverted(&reverted, &ep));
+}
@@ -0,0 +1,134 @@
+use ethers::{
+    abi::{encode, Token},
+    contract::EthEvent,
+    types::{Address, Log, TransactionReceipt, H256, I256, U256},
+};
+use silius_bundler::{net_fee, BundleEventSink, BundleReporter, BundleSubmissionEvent};
+use silius_contracts::entry_point::UserOperationEventFilter;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Default)]
+struct RecordingSink {
+    events: Arc<Mutex<Vec<BundleSubmissionEvent>>>,
+    fail: bool,
+}
+
+#[async_trait::async_trait]
+impl BundleEventSink for RecordingSink {
+    async fn emit(&self, event: &BundleSubmissionEvent) -> eyre::Result<()> {
+        if self.fail {
+            return Err(eyre::eyre!("sink unavailable"));
+        }
+        self.events.lock().unwrap().push(event.clone());
+        Ok(())
+    }
+}
+
+fn event() -> BundleSubmissionEvent {
+    BundleSubmissionEvent {
+        timestamp_ms: 1_700_000_000_000,
+        entry_point: Address::random(),
+        tx_hash: H256::random(),
+        op_count: 3,
+        gas_used: None,
+        beneficiary: Address::random(),
+        net_fee: None,
+    }
+}
+
+#[tokio::test]
+async fn report_bundle_submission() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let reporter =
+        BundleReporter::new(Box::new(RecordingSink { events: events.clone(), fail: false }));
+
+    let event = event();
+    reporter.report(event.clone()).await;
+
+    assert_eq!(*events.lock().unwrap(), vec![event]);
+}
+
+#[tokio::test]
+async fn report_ignores_sink_errors() {
+    let reporter =
+        BundleReporter::new(Box::new(RecordingSink { fail: true, ..Default::default() }));
+    reporter.report(event()).await;
+}
+
+fn user_operation_event(entry_point: Address, actual_gas_cost: u64) -> Log {
+    Log {
+        address: entry_point,
+        topics: vec![
+            UserOperationEventFilter::signature(),
+            H256::random(),
+            H256::from(Address::random()),
+            H256::zero(),
+        ],
+        data: encode(&[
+            Token::Uint(U256::zero()),
+            Token::Bool(true),
+            Token::Uint(actual_gas_cost.into()),
+            Token::Uint(100_000.into()),
+        ])
+        .into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn net_fee_from_receipt() {
+    let entry_point = Address::random();
+    let mut receipt = TransactionReceipt {
+        gas_used: Some(100_000.into()),
+        effective_gas_price: Some(10.into()),
+        logs: vec![
+            user_operation_event(entry_point, 600_000),
+            user_operation_event(entry_point, 700_000),
+            // emitted by another contract
+            user_operation_event(Address::random(), 1_000_000),
+        ],
+        ..Default::default()
+    };
+    assert_eq!(net_fee(&receipt, entry_point), I256::from(300_000));
+
+    receipt.logs.pop();
+    receipt.logs.pop();
+    assert_eq!(net_fee(&receipt, entry_point), I256::from(-400_000));
+}
+
+#[test]
+fn event_json() {
+    let event = event();
+    let json = serde_json::to_value(&event).unwrap();
+
+    assert_eq!(json["timestampMs"], 1_700_000_000_000_u64);
+    assert_eq!(json["entryPoint"], format!("{:?}", event.entry_point));
+    assert_eq!(json["txHash"], format!("{:?}", event.tx_hash));
+    assert_eq!(json["opCount"], 3);
+    assert!(json["gasUsed"].is_null());
+    assert!(json["netFee"].is_null());
+}
+
+/// Requires a NATS server at `nats://127.0.0.1:4222`.
+#[cfg(feature = "nats")]
+#[tokio::test]
+#[ignore]
+async fn report_to_nats() -> eyre::Result<()> {
+    use futures_util::StreamExt;
+    use silius_bundler::NatsSink;
+
+    let client = async_nats::connect("nats://127.0.0.1:4222").await?;
+    let mut subscriber = client.subscribe("silius.bundles".to_string()).await?;
+
+    let reporter = BundleReporter::new(Box::new(
+        NatsSink::connect("nats://127.0.0.1:4222", "silius.bundles".into()).await?,
+    ));
+    let event = event();
+    reporter.report(event.clone()).await;
+
+    let message = subscriber.next().await.expect("event is received");
+    let json: serde_json::Value = serde_json::from_slice(&message.payload)?;
+    assert_eq!(json["txHash"], format!("{:?}", event.tx_hash));
+
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use ethers::{
+    types::{H256, U256},
+    utils::parse_units,
+};
+use silius_bundler::BundleSizeOptimizer;
+use silius_primitives::{UserOperation, UserOperationSigned};
+
+fn gwei(amount: &str) -> U256 {
+    U256::from(parse_units(amount, "gwei").unwrap())
+}
+
+/// User operation that uses 100k gas in total
+fn uo(max_priority_fee_per_gas: U256) -> UserOperation {
+    UserOperation::from_user_operation_signed(
+        H256::random().into(),
+        UserOperationSigned::default()
+            .call_gas_limit(50_000.into())
+            .verification_gas_limit(29_000.into())
+            .pre_verification_gas(21_000.into())
+            .max_fee_per_gas(gwei("100"))
+            .max_priority_fee_per_gas(max_priority_fee_per_gas),
+    )
+}
+
+#[test]
+fn dust_operations_removed() {
+    let base_fee = gwei("10");
+    // 100k gas * 12 gwei
+    let optimizer = BundleSizeOptimizer::new(gwei("12") * U256::from(100_000));
+
+    let uos = vec![uo(gwei("5")), uo(gwei("1")), uo(gwei("2")), uo(gwei("0.5")), uo(gwei("1.9"))];
+    let expected = vec![uos[0].hash, uos[2].hash];
+
+    let optimized = optimizer.optimize(uos, base_fee);
+    assert_eq!(optimized.iter().map(|uo| uo.hash).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn bundle_of_dust_kept() {
+    let base_fee = gwei("10");
+    let optimizer = BundleSizeOptimizer::new(gwei("100") * U256::from(100_000));
+
+    let uos = vec![uo(gwei("1")), uo(gwei("2"))];
+    let hashes = uos.iter().map(|uo| uo.hash).collect::<Vec<_>>();
+
+    let optimized = optimizer.optimize(uos.clone(), base_fee);
+    assert_eq!(optimized.iter().map(|uo| uo.hash).collect::<Vec<_>>(), hashes);
+
+    let optimized = BundleSizeOptimizer::default().optimize(uos, base_fee);
+    assert_eq!(optimized.iter().map(|uo| uo.hash).collect::<Vec<_>>(), hashes);
+}
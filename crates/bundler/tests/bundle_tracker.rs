@@ -0,0 +1,112 @@
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{MockProvider, Provider},
+    signers::{LocalWallet, Signer},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Eip1559TransactionRequest, Transaction,
+        TransactionRequest, TxHash, U256, U64,
+    },
+};
+use silius_bundler::BundleTracker;
+use std::{sync::Arc, time::Duration};
+
+const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+fn bundle_transaction(wallet: &LocalWallet) -> TypedTransaction {
+    Eip1559TransactionRequest::new()
+        .from(wallet.address())
+        .to(Address::random())
+        .nonce(7)
+        .gas(1_000_000)
+        .max_fee_per_gas(100_000_000_000_u64)
+        .max_priority_fee_per_gas(2_000_000_000_u64)
+        .chain_id(wallet.chain_id())
+        .into()
+}
+
+fn transaction(hash: TxHash, block_number: Option<u64>) -> Transaction {
+    Transaction { hash, block_number: block_number.map(U64::from), ..Default::default() }
+}
+
+#[test]
+fn resubmission_bumps_fees_by_ten_percent() {
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(Address::random())
+        .nonce(7)
+        .gas(1_000_000)
+        .max_fee_per_gas(100_000_000_000_u64)
+        .max_priority_fee_per_gas(2_000_000_000_u64)
+        .into();
+
+    let resubmitted = BundleTracker::resubmission_transaction(&tx);
+    assert_eq!(resubmitted.nonce(), tx.nonce());
+    assert_eq!(resubmitted.gas(), tx.gas());
+    assert_eq!(resubmitted.to(), tx.to());
+    match resubmitted {
+        TypedTransaction::Eip1559(tx) => {
+            assert_eq!(tx.max_fee_per_gas, Some(U256::from(110_000_000_000_u64)));
+            assert_eq!(tx.max_priority_fee_per_gas, Some(U256::from(2_200_000_000_u64)));
+        }
+        _ => panic!("transaction type changed"),
+    }
+}
+
+#[test]
+fn resubmission_bumps_legacy_gas_price() {
+    let tx: TypedTransaction =
+        TransactionRequest::new().to(Address::random()).nonce(7).gas_price(50).into();
+
+    let resubmitted = BundleTracker::resubmission_transaction(&tx);
+    assert_eq!(resubmitted.nonce(), tx.nonce());
+    assert_eq!(resubmitted.gas_price(), Some(U256::from(55)));
+}
+
+#[tokio::test]
+async fn resubmit_pending_bundle_until_included() -> eyre::Result<()> {
+    let wallet = KEY.parse::<LocalWallet>()?.with_chain_id(1337_u64);
+    let tx = bundle_transaction(&wallet);
+    let resubmitted = BundleTracker::resubmission_transaction(&tx);
+    let (tx_hash, resubmitted_hash) = (TxHash::random(), TxHash::random());
+
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    // responses are returned in reverse order
+    mock.push(transaction(resubmitted_hash, Some(14)))?;
+    mock.push(transaction(tx_hash, None))?;
+    mock.push(U64::from(13))?;
+    mock.push(resubmitted_hash)?;
+    mock.push(U64::from(13))?;
+    mock.push(transaction(tx_hash, None))?;
+    mock.push(U64::from(10))?;
+    mock.push(tx_hash)?;
+
+    let client = SignerMiddleware::new(Arc::new(provider), wallet.clone());
+    let tracker = BundleTracker::new(3, 5).with_poll_interval(Duration::from_millis(1));
+    assert_eq!(tracker.send_and_track(&client, tx.clone()).await?, resubmitted_hash);
+
+    // the bundle transaction is sent as is and replaced at the same nonce
+    let signature = wallet.sign_transaction(&tx).await?;
+    mock.assert_request("eth_sendRawTransaction", [tx.rlp_signed(&signature)])?;
+    assert_eq!(resubmitted.nonce(), tx.nonce());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn drop_bundle_after_max_resubmit_attempts() -> eyre::Result<()> {
+    let wallet = KEY.parse::<LocalWallet>()?.with_chain_id(1337_u64);
+    let tx_hash = TxHash::random();
+
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    // responses are returned in reverse order
+    mock.push(U64::from(13))?;
+    mock.push(transaction(tx_hash, None))?;
+    mock.push(U64::from(10))?;
+    mock.push(tx_hash)?;
+
+    let client = SignerMiddleware::new(Arc::new(provider), wallet.clone());
+    let tracker = BundleTracker::new(3, 0).with_poll_interval(Duration::from_millis(1));
+    let err = tracker.send_and_track(&client, bundle_transaction(&wallet)).await.unwrap_err();
+    assert!(err.to_string().contains("not included after 0 resubmissions"));
+
+    Ok(())
+}
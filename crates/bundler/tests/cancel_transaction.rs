@@ -0,0 +1,67 @@
+use ethers::{
+    providers::{MockProvider, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Transaction, TxHash, H256, U256},
+};
+use silius_bundler::{cancel_transaction, cancellation_transaction, CancelError};
+
+const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+fn stuck_transaction(wallet: &LocalWallet) -> Transaction {
+    Transaction {
+        hash: H256::random(),
+        nonce: U256::from(7),
+        from: wallet.address(),
+        to: Some(wallet.address()),
+        gas: U256::from(1_000_000),
+        max_fee_per_gas: Some(U256::from(100)),
+        max_priority_fee_per_gas: Some(U256::from(10)),
+        transaction_type: Some(2.into()),
+        chain_id: Some(U256::from(wallet.chain_id())),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn cancel_stuck_transaction() -> eyre::Result<()> {
+    let wallet = KEY.parse::<LocalWallet>()?.with_chain_id(1337_u64);
+    let stuck_tx = stuck_transaction(&wallet);
+    let cancel_tx_hash = TxHash::random();
+
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    // responses are returned in reverse order
+    mock.push(cancel_tx_hash)?;
+    mock.push(stuck_tx.clone())?;
+
+    assert_eq!(cancel_transaction(stuck_tx.hash, &wallet, &provider).await?, cancel_tx_hash);
+
+    let tx = cancellation_transaction(&stuck_tx, &wallet);
+    assert_eq!(tx.nonce(), Some(&stuck_tx.nonce));
+    assert_eq!(tx.to_addr(), Some(&wallet.address()));
+    assert_eq!(tx.value(), Some(&U256::zero()));
+    assert_eq!(tx.as_eip1559_ref().unwrap().max_fee_per_gas, Some(U256::from(120)));
+    assert_eq!(tx.as_eip1559_ref().unwrap().max_priority_fee_per_gas, Some(U256::from(12)));
+
+    // the execution client receives the signed cancellation transaction with the stuck nonce
+    let signature = wallet.sign_transaction(&tx).await?;
+    mock.assert_request("eth_getTransactionByHash", [stuck_tx.hash])?;
+    mock.assert_request("eth_sendRawTransaction", [tx.rlp_signed(&signature)])?;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn cancel_unknown_transaction() -> eyre::Result<()> {
+    let wallet = KEY.parse::<LocalWallet>()?;
+    let tx_hash = TxHash::random();
+
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    mock.push(Option::<Transaction>::None)?;
+
+    assert!(matches!(
+        cancel_transaction(tx_hash, &wallet, &provider).await,
+        Err(CancelError::NotFound(hash)) if hash == tx_hash
+    ));
+
+    Ok(())
+}
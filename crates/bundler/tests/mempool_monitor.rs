@@ -0,0 +1,86 @@
+use ethers::types::Address;
+use silius_bundler::{AlertLevel, MempoolAlert, MempoolMonitor, MempoolSize};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[derive(Default)]
+struct MockMempool {
+    size: AtomicUsize,
+}
+
+#[async_trait::async_trait]
+impl MempoolSize for MockMempool {
+    async fn count(&self, _ep: &Address) -> eyre::Result<usize> {
+        Ok(self.size.load(Ordering::SeqCst))
+    }
+}
+
+#[test]
+fn alert_levels() {
+    let monitor = MempoolMonitor::new(100, 50);
+
+    assert_eq!(monitor.level(80, AlertLevel::OK), None);
+    assert_eq!(monitor.level(81, AlertLevel::OK), Some(AlertLevel::WARNING));
+    assert_eq!(monitor.level(101, AlertLevel::WARNING), Some(AlertLevel::CRITICAL));
+    assert_eq!(monitor.level(90, AlertLevel::CRITICAL), Some(AlertLevel::WARNING));
+
+    // level is kept until the size drops below the low watermark
+    assert_eq!(monitor.level(60, AlertLevel::WARNING), None);
+    assert_eq!(monitor.level(50, AlertLevel::CRITICAL), None);
+    assert_eq!(monitor.level(49, AlertLevel::WARNING), Some(AlertLevel::OK));
+}
+
+#[tokio::test]
+async fn alert_on_mempool_congestion() {
+    let ep = Address::random();
+    let mempool = MockMempool::default();
+    let monitor = MempoolMonitor::new(10, 5);
+    let mut alerts = monitor.subscribe();
+    let mut levels = HashMap::from([(ep, AlertLevel::OK)]);
+
+    mempool.size.store(8, Ordering::SeqCst);
+    monitor.poll(&mempool, &mut levels).await;
+    assert!(alerts.try_recv().is_err());
+
+    mempool.size.store(9, Ordering::SeqCst);
+    monitor.poll(&mempool, &mut levels).await;
+    assert_eq!(
+        alerts.try_recv().unwrap(),
+        MempoolAlert { entry_point: ep, size: 9, level: AlertLevel::WARNING }
+    );
+
+    // no new alert while the level stays the same
+    monitor.poll(&mempool, &mut levels).await;
+    assert!(alerts.try_recv().is_err());
+
+    mempool.size.store(11, Ordering::SeqCst);
+    monitor.poll(&mempool, &mut levels).await;
+    assert_eq!(alerts.try_recv().unwrap().level, AlertLevel::CRITICAL);
+
+    mempool.size.store(4, Ordering::SeqCst);
+    monitor.poll(&mempool, &mut levels).await;
+    assert_eq!(
+        alerts.try_recv().unwrap(),
+        MempoolAlert { entry_point: ep, size: 4, level: AlertLevel::OK }
+    );
+}
+
+#[tokio::test]
+async fn monitor_polls_mempool() {
+    let ep = Address::random();
+    let mempool = Arc::new(MockMempool::default());
+    let monitor = MempoolMonitor::new(10, 5);
+    let mut alerts = monitor.subscribe();
+
+    monitor.start(mempool.clone(), vec![ep], Duration::from_millis(10));
+    mempool.size.store(9, Ordering::SeqCst);
+
+    let alert = tokio::time::timeout(Duration::from_secs(1), alerts.recv()).await.unwrap().unwrap();
+    assert_eq!(alert, MempoolAlert { entry_point: ep, size: 9, level: AlertLevel::WARNING });
+}
@@ -0,0 +1,113 @@
+use ethers::{
+    providers::{MockProvider, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, Signature, H256, U64},
+    utils::keccak256,
+};
+use serde_json::json;
+use silius_bundler::{
+    BundleInclusion, BundlePrivacy, MevShareBodyItem, MevShareBundle, MevShareClient,
+};
+use silius_primitives::Wallet;
+use std::sync::Arc;
+use wiremock::{
+    matchers::{body_partial_json, method},
+    Match, Mock, MockServer, Request, ResponseTemplate,
+};
+
+const PRIVATE_KEY: &str = "0x5de4111afa1a4b94908f83103eb1f1706367c2e68ca870fc3fb9a804cdab365a";
+const FLASHBOTS_PRIVATE_KEY: &str =
+    "0x7c852118294e51e653712a81e05800f419141751be58f605c371e15141b007a6";
+
+/// Matches requests whose `X-Flashbots-Signature` header is signed by the address
+struct FlashbotsSignature(Address);
+
+impl Match for FlashbotsSignature {
+    fn matches(&self, request: &Request) -> bool {
+        let Some((address, signature)) = request
+            .headers
+            .get("x-flashbots-signature")
+            .and_then(|header| header.to_str().ok())
+            .and_then(|header| header.split_once(':'))
+        else {
+            return false;
+        };
+
+        let message = format!("0x{:x}", H256::from(keccak256(&request.body)));
+        address.parse::<Address>().ok() == Some(self.0) &&
+            signature
+                .parse::<Signature>()
+                .map(|signature| signature.verify(message, self.0).is_ok())
+                .unwrap_or(false)
+    }
+}
+
+fn client(endpoint: String) -> MevShareClient<Provider<MockProvider>> {
+    let (provider, _) = Provider::<MockProvider>::mocked();
+    let wallet =
+        Wallet::from_private_key(PRIVATE_KEY, 1, true, Some(FLASHBOTS_PRIVATE_KEY)).unwrap();
+    MevShareClient::new(Arc::new(provider), endpoint, wallet).unwrap()
+}
+
+fn bundle() -> MevShareBundle {
+    MevShareBundle::new(
+        vec![Bytes::from(vec![1, 2, 3])],
+        BundleInclusion { block: U64::from(100), max_block: Some(U64::from(125)) },
+    )
+}
+
+#[test]
+fn bundle_schema() {
+    let mut bundle = bundle();
+    bundle.body.push(MevShareBodyItem::Hash { hash: H256::zero() });
+    bundle.privacy = Some(BundlePrivacy { hints: Some(vec!["hash".into()]), builders: None });
+
+    assert_eq!(
+        serde_json::to_value(&bundle).unwrap(),
+        json!({
+            "version": "v0.1",
+            "inclusion": { "block": "0x64", "maxBlock": "0x7d" },
+            "body": [
+                { "tx": "0x010203", "canRevert": false },
+                { "hash": format!("{:?}", H256::zero()) },
+            ],
+            "privacy": { "hints": ["hash"] },
+        })
+    );
+}
+
+#[tokio::test]
+async fn send_signed_bundle() {
+    let server = MockServer::start().await;
+    let signer = FLASHBOTS_PRIVATE_KEY.parse::<LocalWallet>().unwrap().address();
+    let bundle_hash = H256::random();
+
+    Mock::given(method("POST"))
+        .and(body_partial_json(json!({ "method": "mev_sendBundle" })))
+        .and(FlashbotsSignature(signer))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "result": { "bundleHash": bundle_hash },
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let res = client(server.uri()).send_bundle(bundle()).await.unwrap();
+    assert_eq!(res.bundle_hash, bundle_hash);
+}
+
+#[tokio::test]
+async fn reject_wrong_signer() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(FlashbotsSignature(Address::random()))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&server)
+        .await;
+
+    assert!(client(server.uri()).send_bundle(bundle()).await.is_err());
+}
@@ -0,0 +1,213 @@
+use alloy_chains::Chain;
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    providers::{MockProvider, Provider},
+    signers::Signer,
+    types::{Address, Bytes, U256},
+};
+use silius_bundler::{
+    decode_aggregate3, encode_aggregate3, Bundler, EthereumClient, MulticallBundler,
+};
+use silius_primitives::{
+    constants::bundler::MULTICALL3_ADDRESS, UserOperation, UserOperationSigned, Wallet,
+};
+use std::sync::Arc;
+
+const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+// aggregate3((address,bool,bytes)[])
+const AGGREGATE3_SELECTOR: [u8; 4] = [0x82, 0xad, 0x56, 0xcb];
+// handleOps((address,uint256,bytes,bytes,uint256,uint256,uint256,uint256,uint256,bytes,bytes)[],address)
+const HANDLE_OPS_SELECTOR: [u8; 4] = [0x1f, 0xad, 0x94, 0x8c];
+// handleAggregatedOps((UserOperation[],address,bytes)[],address)
+const HANDLE_AGGREGATED_OPS_SELECTOR: [u8; 4] = [0x4b, 0x1d, 0x7c, 0xf5];
+
+fn user_operations(ep: &Address, n: u64) -> Vec<UserOperation> {
+    (0..n)
+        .map(|nonce| {
+            let uo = UserOperationSigned::default().sender(Address::random()).nonce(nonce.into());
+            UserOperation::from_user_operation_signed(uo.hash(ep, Chain::dev().id()), uo)
+        })
+        .collect()
+}
+
+fn user_operation_params() -> ParamType {
+    ParamType::Tuple(vec![
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Bytes,
+        ParamType::Bytes,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Bytes,
+        ParamType::Bytes,
+    ])
+}
+
+#[tokio::test]
+async fn encode_two_entry_point_bundles() -> eyre::Result<()> {
+    let (provider, _) = Provider::<MockProvider>::mocked();
+    let eth_client = Arc::new(provider);
+    let wallet = Wallet::from_private_key(KEY, Chain::dev().id(), false, None)?;
+    let client = Arc::new(EthereumClient::new(eth_client.clone(), wallet.clone()));
+    let beneficiary = Address::random();
+    let eps = [Address::random(), Address::random()];
+
+    let bundlers = eps
+        .iter()
+        .map(|ep| {
+            Bundler::new(
+                wallet.signer.address(),
+                beneficiary,
+                *ep,
+                Chain::dev(),
+                U256::zero(),
+                eth_client.clone(),
+                client.clone(),
+                false,
+            )
+        })
+        .collect();
+    let multicall = MulticallBundler::new(bundlers, MULTICALL3_ADDRESS.parse()?)?;
+
+    let bundles =
+        vec![(eps[0], user_operations(&eps[0], 2)), (eps[1], user_operations(&eps[1], 1))];
+    let data = multicall.encode_bundles(&bundles, beneficiary).await?;
+    assert_eq!(data[..4], AGGREGATE3_SELECTOR);
+
+    let calls = decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))],
+        &data[4..],
+    )?
+    .remove(0)
+    .into_array()
+    .unwrap();
+    assert_eq!(calls.len(), 2);
+
+    for (call, (ep, uos)) in calls.into_iter().zip(bundles.iter()) {
+        let call = call.into_tuple().unwrap();
+        assert_eq!(call[0], Token::Address(*ep));
+        assert_eq!(call[1], Token::Bool(true));
+
+        let call_data = call[2].clone().into_bytes().unwrap();
+        assert_eq!(call_data[..4], HANDLE_OPS_SELECTOR);
+
+        let args = decode(
+            &[ParamType::Array(Box::new(user_operation_params())), ParamType::Address],
+            &call_data[4..],
+        )?;
+        let ops = args[0].clone().into_array().unwrap();
+        assert_eq!(ops.len(), uos.len());
+        for (op, uo) in ops.into_iter().zip(uos.iter()) {
+            let op = op.into_tuple().unwrap();
+            assert_eq!(op[0], Token::Address(uo.sender));
+            assert_eq!(op[1], Token::Uint(uo.nonce));
+        }
+        assert_eq!(args[1], Token::Address(beneficiary));
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn encode_aggregated_bundle() -> eyre::Result<()> {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let eth_client = Arc::new(provider);
+    let wallet = Wallet::from_private_key(KEY, Chain::dev().id(), false, None)?;
+    let client = Arc::new(EthereumClient::new(eth_client.clone(), wallet.clone()));
+    let beneficiary = Address::random();
+    let ep = Address::random();
+    let aggregator = Address::random();
+
+    let bundler = Bundler::new(
+        wallet.signer.address(),
+        beneficiary,
+        ep,
+        Chain::dev(),
+        U256::zero(),
+        eth_client.clone(),
+        client,
+        false,
+    );
+    let multicall = MulticallBundler::new(vec![bundler], MULTICALL3_ADDRESS.parse()?)?;
+
+    // aggregateSignatures of the aggregator
+    let signature = vec![0xaa; 96];
+    mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Bytes(signature.clone())])))?;
+
+    let mut uos = user_operations(&ep, 3);
+    uos[0].aggregator = Some(aggregator);
+    uos[2].aggregator = Some(aggregator);
+    let data = multicall.encode_bundles(&[(ep, uos.clone())], beneficiary).await?;
+
+    let calls = decode(
+        &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+            ParamType::Address,
+            ParamType::Bool,
+            ParamType::Bytes,
+        ])))],
+        &data[4..],
+    )?
+    .remove(0)
+    .into_array()
+    .unwrap();
+    let call_data = calls[0].clone().into_tuple().unwrap()[2].clone().into_bytes().unwrap();
+    assert_eq!(call_data[..4], HANDLE_AGGREGATED_OPS_SELECTOR);
+
+    let args = decode(
+        &[
+            ParamType::Array(Box::new(ParamType::Tuple(vec![
+                ParamType::Array(Box::new(user_operation_params())),
+                ParamType::Address,
+                ParamType::Bytes,
+            ]))),
+            ParamType::Address,
+        ],
+        &call_data[4..],
+    )?;
+    let groups = args[0].clone().into_array().unwrap();
+    assert_eq!(groups.len(), 2);
+
+    // the aggregated user operations come first, the others without a signature
+    let expected = [
+        (aggregator, signature, vec![uos[0].sender, uos[2].sender]),
+        (Address::zero(), vec![], vec![uos[1].sender]),
+    ];
+    for (group, (aggregator, signature, senders)) in groups.into_iter().zip(expected) {
+        let group = group.into_tuple().unwrap();
+        let ops = group[0].clone().into_array().unwrap();
+        assert_eq!(
+            ops.into_iter().map(|op| op.into_tuple().unwrap()[0].clone()).collect::<Vec<_>>(),
+            senders.into_iter().map(Token::Address).collect::<Vec<_>>()
+        );
+        assert_eq!(group[1], Token::Address(aggregator));
+        assert_eq!(group[2], Token::Bytes(signature));
+    }
+    assert_eq!(args[1], Token::Address(beneficiary));
+
+    Ok(())
+}
+
+#[test]
+fn aggregate3_round_trip() -> eyre::Result<()> {
+    let calls = vec![
+        (Address::random(), Bytes::from(vec![1, 2, 3])),
+        (Address::random(), Bytes::default()),
+    ];
+    let data = encode_aggregate3(&calls);
+    assert_eq!(data[..4], AGGREGATE3_SELECTOR);
+
+    let results = encode(&[Token::Array(vec![
+        Token::Tuple(vec![Token::Bool(true), Token::Bytes(vec![])]),
+        Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![0x08, 0xc3, 0x79, 0xa0])]),
+    ])]);
+    assert_eq!(decode_aggregate3(&results)?, vec![true, false]);
+
+    Ok(())
+}
@@ -0,0 +1,89 @@
+use ethers::{
+    providers::{MockProvider, Provider},
+    types::{Address, U256},
+};
+use silius_bundler::{not_broadcast, BundlerNonceManager};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn concurrent_bundles_get_distinct_nonces() -> eyre::Result<()> {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    // the pending nonce is fetched only once
+    mock.push(U256::from(5))?;
+    let provider = Arc::new(provider);
+    let nonce_manager = BundlerNonceManager::new(Address::random());
+
+    let tasks = (0..2)
+        .map(|_| {
+            let (nonce_manager, provider) = (nonce_manager.clone(), provider.clone());
+            tokio::spawn(async move {
+                let mut nonces = vec![];
+                for _ in 0..10 {
+                    nonces.push(nonce_manager.next_nonce(provider.as_ref()).await.unwrap());
+                    tokio::task::yield_now().await;
+                }
+                nonces
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut nonces = vec![];
+    for task in tasks {
+        nonces.extend(task.await?);
+    }
+    nonces.sort();
+
+    assert_eq!(nonces, (5..25).map(U256::from).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn release_and_resync() -> eyre::Result<()> {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    // responses are returned in reverse order
+    mock.push(U256::from(12))?;
+    mock.push(U256::from(3))?;
+    let nonce_manager = BundlerNonceManager::new(Address::random());
+
+    let nonce = nonce_manager.next_nonce(&provider).await?;
+    assert_eq!(nonce, U256::from(3));
+    // the bundle wasn't sent, so the nonce is handed out again
+    nonce_manager.release(nonce);
+    assert_eq!(nonce_manager.next_nonce(&provider).await?, U256::from(3));
+
+    // nonce too low, the pending nonce is fetched again
+    assert!(BundlerNonceManager::is_nonce_error(&eyre::eyre!(
+        "(code: -32000, message: nonce too low, data: None)"
+    )));
+    nonce_manager.resync(&provider).await?;
+    assert_eq!(nonce_manager.next_nonce(&provider).await?, U256::from(12));
+
+    // a pending transaction with the same nonce can't be replaced
+    assert!(BundlerNonceManager::is_nonce_error(&not_broadcast(eyre::eyre!(
+        "(code: -32000, message: replacement transaction underpriced, data: None)"
+    ))));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn send_failed_releases_only_unbroadcast_nonces() -> eyre::Result<()> {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    // responses are returned in reverse order
+    mock.push(U256::from(8))?;
+    mock.push(U256::from(7))?;
+    let nonce_manager = BundlerNonceManager::new(Address::random());
+
+    // the bundle transaction couldn't be signed, so the nonce is handed out again
+    let nonce = nonce_manager.next_nonce(&provider).await?;
+    assert_eq!(nonce, U256::from(7));
+    nonce_manager.send_failed(nonce, &not_broadcast(eyre::eyre!("Failed to sign transaction")));
+    assert_eq!(nonce_manager.next_nonce(&provider).await?, U256::from(7));
+
+    // the bundle transaction may be pending, so the pending nonce is fetched again
+    nonce_manager.send_failed(nonce, &eyre::eyre!("Bundle transaction not included"));
+    assert_eq!(nonce_manager.next_nonce(&provider).await?, U256::from(8));
+
+    Ok(())
+}
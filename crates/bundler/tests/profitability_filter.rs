@@ -0,0 +1,60 @@
+use ethers::{types::U256, utils::parse_units};
+use silius_bundler::ProfitabilityFilter;
+use silius_primitives::{UserOperation, UserOperationHash, UserOperationSigned};
+
+fn gwei(amount: &str) -> U256 {
+    U256::from(parse_units(amount, "gwei").unwrap())
+}
+
+/// User operation that uses 100k gas in total
+fn uo(max_priority_fee_per_gas: U256) -> UserOperation {
+    UserOperation::from_user_operation_signed(
+        UserOperationHash::default(),
+        UserOperationSigned::default()
+            .call_gas_limit(50_000.into())
+            .verification_gas_limit(29_000.into())
+            .pre_verification_gas(21_000.into())
+            .max_fee_per_gas(gwei("100"))
+            .max_priority_fee_per_gas(max_priority_fee_per_gas),
+    )
+}
+
+#[test]
+fn break_even_bundle() {
+    let filter = ProfitabilityFilter::default();
+    let base_fee = gwei("10");
+    // 2 * 100k gas * 11 gwei = 10 gwei * 220k gas
+    let submission_gas = U256::from(220_000);
+
+    let bundle = vec![uo(gwei("1")), uo(gwei("1"))];
+    assert!(filter.is_profitable(&bundle, base_fee, submission_gas));
+
+    let bundle = vec![uo(gwei("1")), uo(gwei("0.99"))];
+    assert!(!filter.is_profitable(&bundle, base_fee, submission_gas));
+}
+
+#[test]
+fn effective_gas_price_capped_by_max_fee() {
+    let uo = uo(gwei("1"));
+    assert_eq!(ProfitabilityFilter::effective_gas_price(&uo, gwei("10")), gwei("11"));
+    assert_eq!(ProfitabilityFilter::effective_gas_price(&uo, gwei("150")), gwei("100"));
+}
+
+#[test]
+fn revenue_multiplier_and_min_net_fee() {
+    let base_fee = gwei("10");
+    let submission_gas = U256::from(100_000);
+    // fees: 100k gas * 15 gwei, cost: 100k gas * 10 gwei
+    let bundle = vec![uo(gwei("5"))];
+
+    let filter = ProfitabilityFilter::new(U256::zero(), 1.5, false);
+    assert!(filter.is_profitable(&bundle, base_fee, submission_gas));
+    let filter = ProfitabilityFilter::new(U256::zero(), 1.51, false);
+    assert!(!filter.is_profitable(&bundle, base_fee, submission_gas));
+
+    let net_fee = gwei("5") * U256::from(100_000);
+    let filter = ProfitabilityFilter::new(net_fee, 1.0, false);
+    assert!(filter.is_profitable(&bundle, base_fee, submission_gas));
+    let filter = ProfitabilityFilter::new(net_fee + 1, 1.0, false);
+    assert!(!filter.is_profitable(&bundle, base_fee, submission_gas));
+}
@@ -0,0 +1,84 @@
+use silius_bundler::RelayRouter;
+use silius_primitives::{RelayHealth, RelayStatus};
+use std::time::Duration;
+
+fn router(retry_interval: Duration) -> RelayRouter {
+    RelayRouter::with_circuit_breaker(
+        vec!["https://relay.flashbots.net".into(), "https://relay.ultrasound.money/".into()],
+        3,
+        retry_interval,
+    )
+}
+
+#[test]
+fn relays_in_priority_order() {
+    let router = router(Duration::from_secs(60));
+    assert_eq!(router.available(), vec![0, 1]);
+    assert!(router.status().iter().all(|s| s.status == RelayHealth::Ok && s.last_error.is_none()));
+}
+
+#[test]
+fn relay_skipped_after_failures() {
+    let router = router(Duration::from_secs(60));
+
+    router.record_failure(0, "connection refused".into());
+    router.record_failure(0, "connection refused".into());
+    assert_eq!(router.available(), vec![0, 1]);
+    assert_eq!(router.status()[0].status, RelayHealth::Degraded);
+
+    router.record_failure(0, "connection refused".into());
+    assert_eq!(router.available(), vec![1]);
+    assert_eq!(
+        router.status(),
+        vec![
+            RelayStatus {
+                relay: "https://relay.flashbots.net".into(),
+                status: RelayHealth::Down,
+                last_error: Some("connection refused".into()),
+            },
+            RelayStatus {
+                relay: "https://relay.ultrasound.money/".into(),
+                status: RelayHealth::Ok,
+                last_error: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn success_resets_failures() {
+    let router = router(Duration::from_secs(60));
+
+    router.record_failure(0, "timeout".into());
+    router.record_failure(0, "timeout".into());
+    router.record_success(0);
+    router.record_failure(0, "timeout".into());
+    router.record_failure(0, "timeout".into());
+
+    assert_eq!(router.available(), vec![0, 1]);
+    assert_eq!(router.status()[0].status, RelayHealth::Degraded);
+}
+
+#[test]
+fn relay_retried_after_interval() {
+    let router = router(Duration::from_millis(100));
+
+    for _ in 0..3 {
+        router.record_failure(0, "timeout".into());
+    }
+    assert_eq!(router.available(), vec![1]);
+
+    std::thread::sleep(Duration::from_millis(150));
+    assert_eq!(router.available(), vec![0, 1]);
+    assert_eq!(router.status()[0].status, RelayHealth::Degraded);
+
+    // a failed retry skips the relay for another interval
+    router.record_failure(0, "timeout".into());
+    assert_eq!(router.available(), vec![1]);
+    assert_eq!(router.status()[0].status, RelayHealth::Down);
+
+    std::thread::sleep(Duration::from_millis(150));
+    assert_eq!(router.available(), vec![0, 1]);
+    router.record_success(0);
+    assert_eq!(router.status()[0].status, RelayHealth::Ok);
+}
@@ -0,0 +1,154 @@
+use ethers::{
+    abi::{encode, Token},
+    contract::EthEvent,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, TransactionRequest, H256, U256},
+    utils::Anvil,
+};
+use silius_bundler::{OperationIncludedEvent, RemoveUserOperations, ReplacementNotifier};
+use silius_contracts::entry_point::UserOperationEventFilter;
+use silius_primitives::{UserOperation, UserOperationHash, UserOperationSigned};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Default)]
+struct MockMempool {
+    uos: Mutex<HashMap<UserOperationHash, UserOperation>>,
+}
+
+#[async_trait::async_trait]
+impl RemoveUserOperations for MockMempool {
+    async fn remove_user_operations(
+        &self,
+        _ep: &Address,
+        uos: Vec<UserOperation>,
+    ) -> eyre::Result<()> {
+        let mut mempool = self.uos.lock().unwrap();
+        for uo in uos {
+            mempool.remove(&uo.hash);
+        }
+        Ok(())
+    }
+}
+
+/// Init code of a contract that emits `UserOperationEvent` with the topics and data of the call
+/// data (userOpHash, sender, paymaster, nonce, success, actualGasCost, actualGasUsed)
+fn event_emitter_code() -> Vec<u8> {
+    let mut runtime = vec![
+        0x36, 0x60, 0x00, 0x60, 0x00, 0x37, // CALLDATACOPY(0, 0, CALLDATASIZE)
+        0x60, 0x40, 0x51, // paymaster
+        0x60, 0x20, 0x51, // sender
+        0x60, 0x00, 0x51, // userOpHash
+        0x7f, // PUSH32 event signature
+    ];
+    runtime.extend_from_slice(UserOperationEventFilter::signature().as_bytes());
+    runtime.extend_from_slice(&[0x60, 0x80, 0x60, 0x60, 0xa4, 0x00]); // LOG4(0x60, 0x80) STOP
+
+    let mut code =
+        vec![0x60, runtime.len() as u8, 0x80, 0x60, 0x0b, 0x60, 0x00, 0x39, 0x60, 0x00, 0xf3];
+    code.extend(runtime);
+    code
+}
+
+#[tokio::test]
+#[ignore]
+async fn included_operation_removed_from_mempool() -> eyre::Result<()> {
+    let anvil = Anvil::new().spawn();
+    let wallet: LocalWallet = anvil.keys()[0].clone().into();
+    let provider = Provider::<Http>::try_from(anvil.endpoint())?;
+    let eth_client =
+        Arc::new(SignerMiddleware::new(provider, wallet.with_chain_id(anvil.chain_id())));
+
+    // stands in for the entry point
+    let receipt = eth_client
+        .send_transaction(TransactionRequest::new().data(event_emitter_code()), None)
+        .await?
+        .await?
+        .expect("Deployment is mined");
+    let ep = receipt.contract_address.expect("Contract is deployed");
+
+    let sender = Address::random();
+    let uo = UserOperation::from_user_operation_signed(
+        H256::random().into(),
+        UserOperationSigned::default().sender(sender).nonce(U256::from(7)),
+    );
+    let mempool = MockMempool::default();
+    mempool.uos.lock().unwrap().insert(uo.hash, uo.clone());
+
+    let notifier = ReplacementNotifier::new(eth_client.clone());
+    let mut events = notifier.subscribe();
+    notifier.track(ep, &[uo.clone()]);
+    assert!(notifier.poll(&mempool).await?.is_empty());
+
+    // the bundle that includes the user operation
+    let call_data = encode(&[
+        Token::FixedBytes(uo.hash.as_bytes().to_vec()),
+        Token::Address(sender),
+        Token::Address(Address::zero()),
+        Token::Uint(U256::from(7)),
+        Token::Bool(true),
+        Token::Uint(U256::from(100_000)),
+        Token::Uint(U256::from(50_000)),
+    ]);
+    let receipt = eth_client
+        .send_transaction(TransactionRequest::new().to(ep).data(call_data), None)
+        .await?
+        .await?
+        .expect("Bundle is mined");
+    assert_eq!(receipt.logs.len(), 1);
+
+    let expected = OperationIncludedEvent {
+        entry_point: ep,
+        uo_hash: uo.hash,
+        sender,
+        nonce: U256::from(7),
+        success: true,
+        block_number: receipt.block_number.expect("Bundle is mined").as_u64(),
+    };
+    assert_eq!(notifier.poll(&mempool).await?, vec![expected.clone()]);
+    assert!(mempool.uos.lock().unwrap().is_empty());
+    assert_eq!(notifier.pending(), 0);
+    assert_eq!(events.try_recv()?, expected);
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn operation_not_included_is_no_longer_tracked() -> eyre::Result<()> {
+    let anvil = Anvil::new().spawn();
+    let wallet: LocalWallet = anvil.keys()[0].clone().into();
+    let provider = Provider::<Http>::try_from(anvil.endpoint())?;
+    let eth_client =
+        Arc::new(SignerMiddleware::new(provider, wallet.with_chain_id(anvil.chain_id())));
+
+    let uo = UserOperation::from_user_operation_signed(
+        H256::random().into(),
+        UserOperationSigned::default().sender(Address::random()),
+    );
+    let mempool = MockMempool::default();
+    mempool.uos.lock().unwrap().insert(uo.hash, uo.clone());
+
+    let mut notifier = ReplacementNotifier::new(eth_client.clone());
+    notifier.max_pending_blocks = 2;
+    notifier.track(Address::random(), &[uo.clone()]);
+    assert!(notifier.poll(&mempool).await?.is_empty());
+
+    // the bundle is never included while the blocks are mined
+    for blocks in 1..=3 {
+        eth_client
+            .send_transaction(TransactionRequest::new().to(Address::random()), None)
+            .await?
+            .await?;
+        assert!(notifier.poll(&mempool).await?.is_empty());
+        assert_eq!(notifier.pending(), if blocks <= 2 { 1 } else { 0 });
+    }
+    // the user operation stays in the mempool
+    assert_eq!(mempool.uos.lock().unwrap().len(), 1);
+
+    Ok(())
+}
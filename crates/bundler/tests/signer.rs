@@ -0,0 +1,33 @@
+use ethers::{
+    signers::{LocalWallet, Signer as _},
+    types::{transaction::eip2718::TypedTransaction, Address, Eip1559TransactionRequest, U256},
+    utils::rlp::Rlp,
+};
+use silius_bundler::Signer;
+
+const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+#[tokio::test]
+async fn local_wallet_signature_is_recoverable() -> eyre::Result<()> {
+    let signer = Signer::from(KEY.parse::<LocalWallet>()?.with_chain_id(1337_u64));
+    assert_eq!(signer.address(), "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266".parse::<Address>()?);
+
+    let tx: TypedTransaction = Eip1559TransactionRequest::new()
+        .to(Address::random())
+        .value(U256::from(1))
+        .nonce(3)
+        .gas(21000)
+        .max_fee_per_gas(100)
+        .max_priority_fee_per_gas(10)
+        .into();
+
+    let raw = signer.sign_transaction(tx.clone()).await?;
+    let (decoded, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw))?;
+
+    assert_eq!(decoded.chain_id(), Some(1337.into()));
+    assert_eq!(decoded.to(), tx.to());
+    assert_eq!(decoded.nonce(), tx.nonce());
+    assert_eq!(signature.recover(decoded.sighash())?, signer.address());
+
+    Ok(())
+}
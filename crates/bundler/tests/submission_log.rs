@@ -0,0 +1,53 @@
+#![cfg(feature = "sqlite")]
+
+use ethers::types::{Address, H256, U256};
+use silius_bundler::{BundleOp, BundleSubmission, SubmissionLog, TxStatus};
+
+#[tokio::test]
+async fn record_confirm_and_query() -> eyre::Result<()> {
+    let dir = tempfile::tempdir()?;
+    let submission_log = SubmissionLog::connect(&dir.path().join("bundles.db")).await?;
+
+    let ops = vec![
+        BundleOp { op_hash: H256::random().into(), sender: Address::random(), nonce: U256::zero() },
+        BundleOp { op_hash: H256::random().into(), sender: Address::random(), nonce: U256::one() },
+    ];
+    let bundle = BundleSubmission {
+        entry_point: Address::random(),
+        tx_hash: H256::random(),
+        gas_limit: U256::from(1_000_000),
+        base_fee: U256::from(30_000_000_000_u64),
+        net_fee: None,
+        submitted_at: 1_700_000_000,
+        ops: ops.clone(),
+    };
+    let id = submission_log.record_submission(&bundle).await?;
+
+    let history = submission_log.history(1_700_000_000, 1_700_000_001).await?;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].id, id);
+    assert_eq!(history[0].entry_point, bundle.entry_point);
+    assert_eq!(history[0].tx_hash, bundle.tx_hash);
+    assert_eq!(history[0].ops_count, 2);
+    assert_eq!(history[0].gas_limit, bundle.gas_limit);
+    assert_eq!(history[0].base_fee, bundle.base_fee);
+    assert_eq!(history[0].status, TxStatus::Pending);
+    assert_eq!(history[0].gas_used, None);
+    assert_eq!(history[0].confirmed_at, None);
+    assert_eq!(submission_log.ops(id).await?, ops);
+
+    submission_log
+        .record_confirmation(bundle.tx_hash, U256::from(600_000), TxStatus::Included)
+        .await?;
+
+    let history = submission_log.history(1_700_000_000, 1_700_000_001).await?;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].status, TxStatus::Included);
+    assert_eq!(history[0].gas_used, Some(U256::from(600_000)));
+    assert!(history[0].confirmed_at.is_some());
+
+    // outside of the range
+    assert!(submission_log.history(1_600_000_000, 1_700_000_000).await?.is_empty());
+
+    Ok(())
+}
@@ -1,17 +1,22 @@
-use ethers_solc::{Project, ProjectPathsConfig};
-use std::{env, path::PathBuf};
+use ethers_solc::{
+    remappings::Remapping, Artifact, ConfigurableContractArtifact, Project, ProjectCompileOutput,
+    ProjectPathsConfig,
+};
+use std::{env, fs, path::PathBuf};
 
 fn compile(
     root: &PathBuf,
     source: &PathBuf,
     build_info: &PathBuf,
     target: &PathBuf,
-) -> eyre::Result<()> {
+    remappings: Vec<Remapping>,
+) -> eyre::Result<ProjectCompileOutput<ConfigurableContractArtifact>> {
     let build_path_config = ProjectPathsConfig::builder()
         .sources(source)
         .artifacts(target)
         .build_infos(build_info)
         .root(root)
+        .remappings(remappings)
         .build()?;
 
     let project = Project::builder().paths(build_path_config).build()?;
@@ -24,7 +29,7 @@ fn compile(
         compiled.output().errors
     );
 
-    Ok(())
+    Ok(compiled)
 }
 
 fn compile_aa_smart_contracts() -> eyre::Result<()> {
@@ -33,7 +38,7 @@ fn compile_aa_smart_contracts() -> eyre::Result<()> {
     let build_info = root.join("contracts").join("build-info");
 
     // compile interfaces
-    compile(&root, &root.join("contracts").join("interfaces"), &build_info, &target)?;
+    compile(&root, &root.join("contracts").join("interfaces"), &build_info, &target, vec![])?;
 
     // compile sender creator smart contract
     compile(
@@ -41,11 +46,42 @@ fn compile_aa_smart_contracts() -> eyre::Result<()> {
         &root.join("contracts").join("core").join("SenderCreator.sol"),
         &build_info,
         &target,
+        vec![],
+    )?;
+
+    Ok(())
+}
+
+/// Compiles the `EntryPointSimulations` smart contract of entry point v0.7 and writes its runtime
+/// bytecode to `$OUT_DIR/v0_7/EntryPointSimulations.bin` (the bindings of v0.7 are generated
+/// from the vendored ABIs, only the bytecode is needed for overriding the code of the entry point
+/// in the simulations)
+fn compile_entry_point_simulations() -> eyre::Result<()> {
+    let thirdparty = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("thirdparty");
+    let root = thirdparty.join("account-abstraction-v0.7");
+    let target = PathBuf::from(env::var("OUT_DIR").expect("OUT_DIR not set")).join("v0_7");
+    let build_info = root.join("contracts").join("build-info");
+    let openzeppelin = thirdparty.join("openzeppelin-contracts").join("contracts");
+
+    let compiled = compile(
+        &root,
+        &root.join("contracts").join("core").join("EntryPointSimulations.sol"),
+        &build_info,
+        &target,
+        vec![format!("@openzeppelin/contracts/={}/", openzeppelin.display()).parse()?],
     )?;
 
+    let bytecode = compiled
+        .find_first("EntryPointSimulations")
+        .and_then(|artifact| artifact.get_deployed_bytecode_bytes())
+        .ok_or_else(|| eyre::eyre!("EntryPointSimulations has no deployed bytecode"))?;
+    fs::write(target.join("EntryPointSimulations.bin"), bytecode.to_string())?;
+
     Ok(())
 }
 
 fn main() {
     compile_aa_smart_contracts().expect("Compiling ERC-4337 smart contracts should pass.");
+    compile_entry_point_simulations()
+        .expect("Compiling entry point v0.7 simulations smart contract should pass.");
 }
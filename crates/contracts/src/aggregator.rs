@@ -0,0 +1,96 @@
+pub use super::gen::AggregatorAPI;
+use super::gen::{aggregator_api::UserOperation, v0_7};
+use crate::error::{decode_revert_string, EntryPointError};
+use ethers::{
+    prelude::ContractError,
+    providers::Middleware,
+    types::{Address, Bytes},
+};
+use silius_primitives::{EntryPointVersion, UserOperationSigned};
+use std::sync::Arc;
+
+/// Signature aggregator of the user operations (the `IAggregator` interface)
+#[derive(Clone)]
+pub struct Aggregator<M: Middleware + 'static> {
+    address: Address,
+    version: EntryPointVersion,
+    aggregator_api: AggregatorAPI<M>,
+    aggregator_api_v0_7: v0_7::AggregatorAPI<M>,
+}
+
+impl<M: Middleware + 'static> Aggregator<M> {
+    pub fn new(eth_client: Arc<M>, address: Address) -> Self {
+        let aggregator_api = AggregatorAPI::new(address, eth_client.clone());
+        let aggregator_api_v0_7 = v0_7::AggregatorAPI::new(address, eth_client);
+        Self { address, version: EntryPointVersion::V0_6, aggregator_api, aggregator_api_v0_7 }
+    }
+
+    /// Sets the version of the entry point the aggregator is used with (default v0.6)
+    pub fn with_version(mut self, version: EntryPointVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    fn deserialize_error_msg(err: ContractError<M>) -> EntryPointError {
+        match err {
+            ContractError::MiddlewareError { e } => {
+                EntryPointError::Provider { inner: e.to_string() }
+            }
+            ContractError::ProviderError { e } => {
+                EntryPointError::Provider { inner: e.to_string() }
+            }
+            ContractError::Revert(data) => EntryPointError::ExecutionReverted(
+                decode_revert_string(data.clone()).unwrap_or_else(|| data.to_string()),
+            ),
+            _ => EntryPointError::Other { inner: err.to_string() },
+        }
+    }
+
+    /// Validates the signature of a single user operation (called when the user operation is
+    /// added to the mempool)
+    ///
+    /// # Returns
+    /// * `Bytes` - The signature that should be used in the user operation (usually empty)
+    pub async fn validate_user_op_signature<U: Into<UserOperationSigned>>(
+        &self,
+        uo: U,
+    ) -> Result<Bytes, EntryPointError> {
+        let uo: UserOperationSigned = uo.into();
+        match self.version {
+            EntryPointVersion::V0_6 => {
+                self.aggregator_api.validate_user_op_signature(UserOperation::from(uo)).call().await
+            }
+            EntryPointVersion::V0_7 => {
+                self.aggregator_api_v0_7.validate_user_op_signature(uo.into()).call().await
+            }
+        }
+        .map_err(Self::deserialize_error_msg)
+    }
+
+    /// Aggregates the signatures of the user operations into a single signature of the bundle
+    pub async fn aggregate_signatures<U: Into<UserOperationSigned>>(
+        &self,
+        uos: Vec<U>,
+    ) -> Result<Bytes, EntryPointError> {
+        let uos = uos.into_iter().map(Into::<UserOperationSigned>::into);
+        match self.version {
+            EntryPointVersion::V0_6 => {
+                self.aggregator_api
+                    .aggregate_signatures(uos.map(UserOperation::from).collect())
+                    .call()
+                    .await
+            }
+            EntryPointVersion::V0_7 => {
+                self.aggregator_api_v0_7
+                    .aggregate_signatures(uos.map(Into::into).collect())
+                    .call()
+                    .await
+            }
+        }
+        .map_err(Self::deserialize_error_msg)
+    }
+}
@@ -1,52 +1,226 @@
 pub use super::{
     error::EntryPointError,
     gen::{
+        entry_point_api::{
+            UserOpsPerAggregator, ValidationResult, ValidationResultWithAggregation,
+        },
+        stake_manager_api::DepositInfo,
         EntryPointAPI, EntryPointAPIEvents, StakeManagerAPI, UserOperationEventFilter,
         ValidatePaymasterUserOpReturn, SELECTORS_INDICES, SELECTORS_NAMES,
     },
 };
 use super::{
-    gen::{
-        entry_point_api::{
-            EntryPointAPIErrors, SenderAddressResult, UserOperation, ValidationResult,
-            ValidationResultWithAggregation,
-        },
-        stake_manager_api::DepositInfo,
-    },
+    gen::entry_point_api::{EntryPointAPIErrors, SenderAddressResult, UserOperation},
     tracer::JS_TRACER,
 };
-use crate::{error::decode_revert_error, executor_tracer::EXECUTOR_TRACER, gen::ExecutionResult};
+use crate::{
+    error::decode_revert_error,
+    executor_tracer::EXECUTOR_TRACER,
+    gen::{v0_7, ExecutionResult},
+};
 use ethers::{
+    abi::AbiDecode,
     prelude::{ContractError, Event},
-    providers::Middleware,
+    providers::{call_raw::RawCall, Middleware},
     types::{
         spoof, transaction::eip2718::TypedTransaction, Address, Bytes, GethDebugTracerType,
-        GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, TransactionRequest, U256,
+        GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, TransactionRequest, H160,
+        U256,
     },
+    utils::id,
 };
+pub use silius_primitives::EntryPointVersion;
+use silius_primitives::UserOperationSigned;
 use std::sync::Arc;
 
 const UINT96_MAX: u128 = 5192296858534827628530496329220095;
 
+const UINT48_MAX: u64 = (1 << 48) - 1;
+
+/// Aggregator address of the validation data that marks a failed signature validation
+const SIG_VALIDATION_FAILED: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x01,
+]);
+
+/// Entry point v0.7 moved the simulation functions into a separate contract, so its runtime
+/// bytecode (~16KB) is noticeably smaller than the one of v0.6 (~23KB).
+const ENTRY_POINT_V07_MAX_CODE_SIZE: usize = 20_000;
+
+/// Detects the version of the entry point deployed at the given address
+///
+/// Calls the `VERSION()` view function first. If the call fails or returns an unknown version,
+/// the version is derived from the size of the deployed bytecode.
+pub async fn detect_entry_point_version<M: Middleware + 'static>(
+    address: Address,
+    eth_client: &M,
+) -> Result<EntryPointVersion, EntryPointError> {
+    let tx: TypedTransaction =
+        TransactionRequest::new().to(address).data(id("VERSION()").to_vec()).into();
+
+    let version = eth_client
+        .call(&tx, None)
+        .await
+        .ok()
+        .and_then(|res| String::decode(res).ok())
+        .and_then(|version| version.parse::<EntryPointVersion>().ok());
+    if let Some(version) = version {
+        return Ok(version);
+    }
+
+    let code = eth_client
+        .get_code(address, None)
+        .await
+        .map_err(|err| EntryPointError::Provider { inner: err.to_string() })?;
+
+    if code.is_empty() {
+        return Err(EntryPointError::Other {
+            inner: format!("no entry point deployed at {address:?}"),
+        });
+    }
+
+    if code.len() > ENTRY_POINT_V07_MAX_CODE_SIZE {
+        Ok(EntryPointVersion::V0_6)
+    } else {
+        Ok(EntryPointVersion::V0_7)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum SimulateValidationResult {
     ValidationResult(ValidationResult),
     ValidationResultWithAggregation(ValidationResultWithAggregation),
 }
 
+/// Splits the validation data of entry point v0.7 into the aggregator (or the signature failure
+/// marker), valid after and valid until (0 means no expiry)
+fn parse_validation_data(validation_data: U256) -> (Address, u64, u64) {
+    let mut word = [0u8; 32];
+    validation_data.to_big_endian(&mut word);
+    let aggregator = Address::from_slice(&word[12..]);
+    let valid_until = ((validation_data >> 160) & U256::from(UINT48_MAX)).as_u64();
+    let valid_after = (validation_data >> 208).as_u64();
+    (aggregator, valid_after, if valid_until == 0 { UINT48_MAX } else { valid_until })
+}
+
+/// Intersects the time ranges of the account and the paymaster validation data, returns whether
+/// either signature validation failed, the aggregator of the account, valid after and valid until
+fn intersect_validation_data(
+    account_validation_data: U256,
+    paymaster_validation_data: U256,
+) -> (bool, Address, u64, u64) {
+    let (aggregator, account_after, account_until) = parse_validation_data(account_validation_data);
+    let (paymaster_sig, paymaster_after, paymaster_until) =
+        parse_validation_data(paymaster_validation_data);
+    let sig_failed = aggregator == SIG_VALIDATION_FAILED || paymaster_sig == SIG_VALIDATION_FAILED;
+    (
+        sig_failed,
+        if sig_failed { Address::zero() } else { aggregator },
+        account_after.max(paymaster_after),
+        account_until.min(paymaster_until),
+    )
+}
+
+/// The v0.7 result of `simulateValidation` converted to the result of v0.6 (the validation data
+/// are unpacked)
+impl From<v0_7::entry_point_simulations_api::ValidationResult> for SimulateValidationResult {
+    fn from(res: v0_7::entry_point_simulations_api::ValidationResult) -> Self {
+        let (sig_failed, aggregator, valid_after, valid_until) = intersect_validation_data(
+            res.return_info.account_validation_data,
+            res.return_info.paymaster_validation_data,
+        );
+        let return_info = (
+            res.return_info.pre_op_gas,
+            res.return_info.prefund,
+            sig_failed,
+            valid_after,
+            valid_until,
+            res.return_info.paymaster_context,
+        );
+        let sender_info = (res.sender_info.stake, res.sender_info.unstake_delay_sec);
+        let factory_info = (res.factory_info.stake, res.factory_info.unstake_delay_sec);
+        let paymaster_info = (res.paymaster_info.stake, res.paymaster_info.unstake_delay_sec);
+
+        if aggregator.is_zero() {
+            SimulateValidationResult::ValidationResult(ValidationResult {
+                return_info,
+                sender_info,
+                factory_info,
+                paymaster_info,
+            })
+        } else {
+            SimulateValidationResult::ValidationResultWithAggregation(
+                ValidationResultWithAggregation {
+                    return_info,
+                    sender_info,
+                    factory_info,
+                    paymaster_info,
+                    aggregator_info: (
+                        res.aggregator_info.aggregator,
+                        (
+                            res.aggregator_info.stake_info.stake,
+                            res.aggregator_info.stake_info.unstake_delay_sec,
+                        ),
+                    ),
+                },
+            )
+        }
+    }
+}
+
+/// The v0.7 result of `simulateHandleOp` converted to the result of v0.6
+impl From<v0_7::entry_point_simulations_api::ExecutionResult> for ExecutionResult {
+    fn from(res: v0_7::entry_point_simulations_api::ExecutionResult) -> Self {
+        let (_, _, valid_after, valid_until) =
+            intersect_validation_data(res.account_validation_data, res.paymaster_validation_data);
+        Self {
+            pre_op_gas: res.pre_op_gas,
+            paid: res.paid,
+            valid_after,
+            valid_until,
+            target_success: res.target_success,
+            target_result: res.target_result,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct EntryPoint<M: Middleware + 'static> {
     eth_client: Arc<M>,
     address: Address,
+    version: EntryPointVersion,
     entry_point_api: EntryPointAPI<M>,
+    entry_point_api_v0_7: v0_7::EntryPointAPI<M>,
+    entry_point_simulations_api: v0_7::EntryPointSimulationsAPI<M>,
     stake_manager_api: StakeManagerAPI<M>,
 }
 
 impl<M: Middleware + 'static> EntryPoint<M> {
     pub fn new(eth_client: Arc<M>, address: Address) -> Self {
         let entry_point_api = EntryPointAPI::new(address, eth_client.clone());
+        let entry_point_api_v0_7 = v0_7::EntryPointAPI::new(address, eth_client.clone());
+        let entry_point_simulations_api =
+            v0_7::EntryPointSimulationsAPI::new(address, eth_client.clone());
         let stake_manager_api = StakeManagerAPI::new(address, eth_client.clone());
-        Self { eth_client, address, entry_point_api, stake_manager_api }
+        Self {
+            eth_client,
+            address,
+            version: EntryPointVersion::V0_6,
+            entry_point_api,
+            entry_point_api_v0_7,
+            entry_point_simulations_api,
+            stake_manager_api,
+        }
+    }
+
+    /// Sets the version of the entry point (default v0.6)
+    pub fn with_version(mut self, version: EntryPointVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn version(&self) -> EntryPointVersion {
+        self.version
     }
 
     pub fn entry_point_api(&self) -> &EntryPointAPI<M> {
@@ -80,10 +254,47 @@ impl<M: Middleware + 'static> EntryPoint<M> {
         }
     }
 
+    /// Converts the user operation into the packed user operation of entry point v0.7
+    fn packed<U: Into<UserOperation>, P: From<UserOperationSigned>>(uo: U) -> P {
+        P::from(UserOperationSigned::from(uo.into()))
+    }
+
+    /// State override replacing the code of the entry point with `EntryPointSimulations` (the
+    /// simulation functions of v0.7 are not part of the deployed entry point)
+    fn simulations_state_override(&self) -> spoof::State {
+        spoof::code(self.address, v0_7::ENTRY_POINT_SIMULATIONS_BYTECODE.clone())
+    }
+
+    /// Calls a function of `EntryPointSimulations` (v0.7) and returns the data it returned (the
+    /// results of the simulations are returned instead of reverted with)
+    async fn call_simulation(
+        &self,
+        tx: TypedTransaction,
+        state: spoof::State,
+        function: &str,
+    ) -> Result<Bytes, EntryPointError> {
+        self.eth_client.provider().call_raw(&tx).state(&state).await.or_else(|e| {
+            EntryPointError::from_provider_error(&e).and_then(|op| match op {
+                EntryPointAPIErrors::FailedOp(err) => Err(EntryPointError::FailedOp(err)),
+                _ => Err(EntryPointError::Other { inner: format!("{function} error: {op:?}") }),
+            })
+        })
+    }
+
     pub async fn simulate_validation<U: Into<UserOperation>>(
         &self,
         uo: U,
     ) -> Result<SimulateValidationResult, EntryPointError> {
+        if self.version == EntryPointVersion::V0_7 {
+            let tx = self.entry_point_simulations_api.simulate_validation(Self::packed(uo)).tx;
+            let res = self
+                .call_simulation(tx, self.simulations_state_override(), "simulate validation")
+                .await?;
+            return v0_7::entry_point_simulations_api::SimulateValidationReturn::decode(res)
+                .map(|res| res.0.into())
+                .map_err(|e| EntryPointError::Decode { inner: e.to_string() });
+        }
+
         let res = self.entry_point_api.simulate_validation(uo.into()).await;
 
         match res {
@@ -107,12 +318,20 @@ impl<M: Middleware + 'static> EntryPoint<M> {
         &self,
         uo: U,
     ) -> Result<GethTrace, EntryPointError> {
-        let call = self.entry_point_api.simulate_validation(uo.into());
+        let (tx, state_overrides) = match self.version {
+            EntryPointVersion::V0_6 => {
+                (self.entry_point_api.simulate_validation(uo.into()).tx, None)
+            }
+            EntryPointVersion::V0_7 => (
+                self.entry_point_simulations_api.simulate_validation(Self::packed(uo)).tx,
+                Some(self.simulations_state_override()),
+            ),
+        };
 
         let res = self
             .eth_client
             .debug_trace_call(
-                call.tx,
+                tx,
                 None,
                 GethDebugTracingCallOptions {
                     tracing_options: GethDebugTracingOptions {
@@ -124,7 +343,7 @@ impl<M: Middleware + 'static> EntryPoint<M> {
                         tracer_config: None,
                         timeout: None,
                     },
-                    state_overrides: None,
+                    state_overrides,
                     block_overrides: None,
                 },
             )
@@ -142,8 +361,20 @@ impl<M: Middleware + 'static> EntryPoint<M> {
     ) -> Result<GethTrace, EntryPointError> {
         let uo = uo.into();
         let max_fee_per_gas = uo.max_fee_per_gas;
-        let call = self.entry_point_api.simulate_handle_op(uo, Address::zero(), Bytes::default());
-        let mut tx: TypedTransaction = call.tx;
+        let mut state_overrides = spoof::balance(Address::zero(), UINT96_MAX.into());
+        let mut tx: TypedTransaction = match self.version {
+            EntryPointVersion::V0_6 => {
+                self.entry_point_api.simulate_handle_op(uo, Address::zero(), Bytes::default()).tx
+            }
+            EntryPointVersion::V0_7 => {
+                state_overrides
+                    .account(self.address)
+                    .code(v0_7::ENTRY_POINT_SIMULATIONS_BYTECODE.clone());
+                self.entry_point_simulations_api
+                    .simulate_handle_op(Self::packed(uo), Address::zero(), Bytes::default())
+                    .tx
+            }
+        };
         tx.set_from(Address::zero());
         tx.set_gas_price(max_fee_per_gas);
         tx.set_gas(u64::MAX);
@@ -162,7 +393,45 @@ impl<M: Middleware + 'static> EntryPoint<M> {
                         tracer_config: None,
                         timeout: None,
                     },
-                    state_overrides: Some(spoof::balance(Address::zero(), UINT96_MAX.into())),
+                    state_overrides: Some(state_overrides),
+                    block_overrides: None,
+                },
+            )
+            .await
+            .map_err(|e| {
+                EntryPointError::from_middleware_error::<M>(e).expect_err("trace err is expected")
+            })?;
+
+        Ok(res)
+    }
+
+    /// Traces `handleOps` with a single user operation with the JavaScript tracer used for the
+    /// validation (the call is not required to succeed, the trace of a reverted call is returned
+    /// as well)
+    pub async fn handle_ops_trace<U: Into<UserOperation>>(
+        &self,
+        uo: U,
+        beneficiary: Address,
+    ) -> Result<GethTrace, EntryPointError> {
+        let mut tx = self.handle_ops_tx(vec![uo], beneficiary);
+        tx.set_from(Address::zero());
+
+        let res = self
+            .eth_client
+            .debug_trace_call(
+                tx,
+                None,
+                GethDebugTracingCallOptions {
+                    tracing_options: GethDebugTracingOptions {
+                        disable_storage: None,
+                        disable_stack: None,
+                        enable_memory: None,
+                        enable_return_data: None,
+                        tracer: Some(GethDebugTracerType::JsTracer(JS_TRACER.into())),
+                        tracer_config: None,
+                        timeout: None,
+                    },
+                    state_overrides: None,
                     block_overrides: None,
                 },
             )
@@ -174,21 +443,85 @@ impl<M: Middleware + 'static> EntryPoint<M> {
         Ok(res)
     }
 
+    /// Returns the `handleOps` transaction of the user operations (in the format of the entry
+    /// point version)
+    pub fn handle_ops_tx<U: Into<UserOperation>>(
+        &self,
+        uos: Vec<U>,
+        beneficiary: Address,
+    ) -> TypedTransaction {
+        match self.version {
+            EntryPointVersion::V0_6 => {
+                self.entry_point_api
+                    .handle_ops(uos.into_iter().map(|u| u.into()).collect(), beneficiary)
+                    .tx
+            }
+            EntryPointVersion::V0_7 => {
+                self.entry_point_api_v0_7
+                    .handle_ops(uos.into_iter().map(Self::packed).collect(), beneficiary)
+                    .tx
+            }
+        }
+    }
+
+    /// Returns the `handleAggregatedOps` transaction of the user operations grouped by the
+    /// aggregator (in the format of the entry point version)
+    pub fn handle_aggregated_ops_tx(
+        &self,
+        uos_per_aggregator: Vec<UserOpsPerAggregator>,
+        beneficiary: Address,
+    ) -> TypedTransaction {
+        match self.version {
+            EntryPointVersion::V0_6 => {
+                self.entry_point_api.handle_aggregated_ops(uos_per_aggregator, beneficiary).tx
+            }
+            EntryPointVersion::V0_7 => {
+                self.entry_point_api_v0_7
+                    .handle_aggregated_ops(
+                        uos_per_aggregator.into_iter().map(Self::packed_per_aggregator).collect(),
+                        beneficiary,
+                    )
+                    .tx
+            }
+        }
+    }
+
+    /// Converts the user operations of an aggregator into the format of entry point v0.7
+    fn packed_per_aggregator(
+        uos: UserOpsPerAggregator,
+    ) -> v0_7::entry_point_api::UserOpsPerAggregator {
+        v0_7::entry_point_api::UserOpsPerAggregator {
+            user_ops: uos.user_ops.into_iter().map(Self::packed).collect(),
+            aggregator: uos.aggregator,
+            signature: uos.signature,
+        }
+    }
+
     pub async fn handle_ops<U: Into<UserOperation>>(
         &self,
         uos: Vec<U>,
         beneficiary: Address,
     ) -> Result<(), EntryPointError> {
-        self.entry_point_api
-            .handle_ops(uos.into_iter().map(|u| u.into()).collect(), beneficiary)
-            .call()
-            .await
-            .or_else(|e| {
-                Self::deserialize_error_msg(e).and_then(|op| match op {
-                    EntryPointAPIErrors::FailedOp(err) => Err(EntryPointError::FailedOp(err)),
-                    _ => Err(EntryPointError::Other { inner: format!("handle ops error: {op:?}") }),
-                })
+        match self.version {
+            EntryPointVersion::V0_6 => {
+                self.entry_point_api
+                    .handle_ops(uos.into_iter().map(|u| u.into()).collect(), beneficiary)
+                    .call()
+                    .await
+            }
+            EntryPointVersion::V0_7 => {
+                self.entry_point_api_v0_7
+                    .handle_ops(uos.into_iter().map(Self::packed).collect(), beneficiary)
+                    .call()
+                    .await
+            }
+        }
+        .or_else(|e| {
+            Self::deserialize_error_msg(e).and_then(|op| match op {
+                EntryPointAPIErrors::FailedOp(err) => Err(EntryPointError::FailedOp(err)),
+                _ => Err(EntryPointError::Other { inner: format!("handle ops error: {op:?}") }),
             })
+        })
     }
 
     pub async fn get_deposit_info(&self, addr: &Address) -> Result<DepositInfo, EntryPointError> {
@@ -261,6 +594,19 @@ impl<M: Middleware + 'static> EntryPoint<M> {
         &self,
         uo: U,
     ) -> Result<ExecutionResult, EntryPointError> {
+        if self.version == EntryPointVersion::V0_7 {
+            let tx = self
+                .entry_point_simulations_api
+                .simulate_handle_op(Self::packed(uo), Address::zero(), Bytes::default())
+                .tx;
+            let res = self
+                .call_simulation(tx, self.simulations_state_override(), "simulate handle op")
+                .await?;
+            return v0_7::entry_point_simulations_api::SimulateHandleOpReturn::decode(res)
+                .map(|res| res.0.into())
+                .map_err(|e| EntryPointError::Decode { inner: e.to_string() });
+        }
+
         let res = self
             .entry_point_api
             .simulate_handle_op(uo.into(), Address::zero(), Bytes::default())
@@ -278,19 +624,119 @@ impl<M: Middleware + 'static> EntryPoint<M> {
         }
     }
 
-    pub async fn handle_aggregated_ops<U: Into<UserOperation>>(
+    pub async fn handle_aggregated_ops(
         &self,
-        _uos_per_aggregator: Vec<U>,
-        _beneficiary: Address,
+        uos_per_aggregator: Vec<UserOpsPerAggregator>,
+        beneficiary: Address,
     ) -> Result<(), EntryPointError> {
-        todo!()
+        match self.version {
+            EntryPointVersion::V0_6 => {
+                self.entry_point_api
+                    .handle_aggregated_ops(uos_per_aggregator, beneficiary)
+                    .call()
+                    .await
+            }
+            EntryPointVersion::V0_7 => {
+                self.entry_point_api_v0_7
+                    .handle_aggregated_ops(
+                        uos_per_aggregator.into_iter().map(Self::packed_per_aggregator).collect(),
+                        beneficiary,
+                    )
+                    .call()
+                    .await
+            }
+        }
+        .or_else(|e| {
+            Self::deserialize_error_msg(e).and_then(|op| match op {
+                EntryPointAPIErrors::FailedOp(err) => Err(EntryPointError::FailedOp(err)),
+                EntryPointAPIErrors::SignatureValidationFailed(err) => {
+                    Err(EntryPointError::Other {
+                        inner: format!(
+                            "signature validation of aggregator {:?} failed",
+                            err.aggregator
+                        ),
+                    })
+                }
+                _ => Err(EntryPointError::Other {
+                    inner: format!("handle aggregated ops error: {op:?}"),
+                }),
+            })
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ethers::providers::{Http, Provider};
+    use ethers::{
+        abi::Token,
+        providers::{Http, Provider},
+    };
+
+    #[tokio::test]
+    async fn detect_entry_point_version_from_version_string() {
+        let (provider, mock) = Provider::mocked();
+        mock.push::<Bytes, _>(Bytes::from(ethers::abi::encode(&[Token::String("0.6.0".into())])))
+            .unwrap();
+
+        let version = detect_entry_point_version(
+            "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".parse().unwrap(),
+            &provider,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(version, EntryPointVersion::V0_6);
+    }
+
+    #[test]
+    fn validation_result_v0_7() {
+        let validation_data = |aggregator: Address, valid_after: u64, valid_until: u64| {
+            (U256::from(valid_after) << 208) |
+                (U256::from(valid_until) << 160) |
+                U256::from_big_endian(aggregator.as_bytes())
+        };
+        let aggregator = Address::repeat_byte(0xaa);
+        let mut res = v0_7::entry_point_simulations_api::ValidationResult::default();
+        res.return_info.pre_op_gas = 100_000.into();
+        res.return_info.account_validation_data = validation_data(Address::zero(), 10, 0);
+        res.return_info.paymaster_validation_data = validation_data(Address::zero(), 20, 1000);
+
+        match SimulateValidationResult::from(res.clone()) {
+            SimulateValidationResult::ValidationResult(res) => {
+                assert_eq!(res.return_info.0, 100_000.into());
+                assert!(!res.return_info.2);
+                assert_eq!((res.return_info.3, res.return_info.4), (20, 1000));
+            }
+            _ => panic!("Validation result without aggregation expected"),
+        }
+
+        res.return_info.paymaster_validation_data = validation_data(SIG_VALIDATION_FAILED, 0, 0);
+        match SimulateValidationResult::from(res.clone()) {
+            SimulateValidationResult::ValidationResult(res) => {
+                assert!(res.return_info.2);
+                assert_eq!((res.return_info.3, res.return_info.4), (10, UINT48_MAX));
+            }
+            _ => panic!("Validation result without aggregation expected"),
+        }
+
+        res.return_info.account_validation_data = validation_data(aggregator, 0, 0);
+        res.return_info.paymaster_validation_data = U256::zero();
+        res.aggregator_info.aggregator = aggregator;
+        match SimulateValidationResult::from(res) {
+            SimulateValidationResult::ValidationResultWithAggregation(res) => {
+                assert_eq!(res.aggregator_info.0, aggregator);
+            }
+            _ => panic!("Validation result with aggregation expected"),
+        }
+    }
+
+    #[test]
+    fn parse_entry_point_version() {
+        assert_eq!("0.6.0".parse::<EntryPointVersion>().unwrap(), EntryPointVersion::V0_6);
+        assert_eq!("v0.7.0".parse::<EntryPointVersion>().unwrap(), EntryPointVersion::V0_7);
+        assert!("0.5.0".parse::<EntryPointVersion>().is_err());
+    }
 
     #[tokio::test]
     #[ignore]
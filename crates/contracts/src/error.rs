@@ -1,4 +1,4 @@
-use crate::gen::{EntryPointAPIErrors, FailedOp};
+use crate::gen::{v0_7, EntryPointAPIErrors, FailedOp};
 use ethers::{
     abi::AbiDecode,
     providers::{JsonRpcError, Middleware, MiddlewareError, ProviderError},
@@ -132,6 +132,15 @@ impl EntryPointError {
 
         Err(Self::Provider { inner: format!("middleware error: {err:?}") })
     }
+
+    /// Returns the [FailedOp](FailedOp) a call to the entry point reverted with (e.g. the
+    /// `eth_estimateGas` of `handleOps`), if the JSON-RPC error contains one
+    pub fn failed_op(err: &JsonRpcError) -> Option<FailedOp> {
+        match Self::from_json_rpc_error(err) {
+            Ok(EntryPointAPIErrors::FailedOp(op)) => Some(op),
+            _ => None,
+        }
+    }
 }
 
 // ethers-rs could not handle `require (true, "reason")` or `revert("test failed")` well in this
@@ -147,6 +156,17 @@ pub fn decode_revert_string(data: Bytes) -> Option<String> {
 }
 
 pub fn decode_revert_error(data: Bytes) -> Result<EntryPointAPIErrors, EntryPointError> {
+    // entry point v0.7 adds the revert data of the account (or paymaster) to some of the failed
+    // user operations
+    if let Ok(v0_7::entry_point_api::EntryPointAPIErrors::FailedOpWithRevert(err)) =
+        v0_7::entry_point_api::EntryPointAPIErrors::decode(data.as_ref())
+    {
+        return Ok(EntryPointAPIErrors::FailedOp(FailedOp {
+            op_index: err.op_index,
+            reason: format!("{} {}", err.reason, err.inner),
+        }));
+    }
+
     let decoded = EntryPointAPIErrors::decode(data.as_ref());
     match decoded {
         Ok(res) => Ok(res),
@@ -167,6 +187,7 @@ pub fn decode_revert_error(data: Bytes) -> Result<EntryPointAPIErrors, EntryPoin
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ethers::abi::AbiEncode;
 
     #[test]
     fn deserialize_error_msg() -> eyre::Result<()> {
@@ -200,4 +221,24 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn deserialize_failed_op_with_revert() -> eyre::Result<()> {
+        let err_msg = v0_7::entry_point_api::EntryPointAPIErrors::FailedOpWithRevert(
+            v0_7::entry_point_api::FailedOpWithRevert {
+                op_index: 1.into(),
+                reason: "AA23 reverted".into(),
+                inner: Bytes::from_str("0xdeadbeef")?,
+            },
+        )
+        .encode();
+        match decode_revert_error(err_msg.into())? {
+            EntryPointAPIErrors::FailedOp(f) => {
+                assert_eq!(f.op_index, 1.into());
+                assert_eq!(f.reason, "AA23 reverted 0xdeadbeef")
+            }
+            _ => panic!("Invalid error message"),
+        }
+        Ok(())
+    }
 }
@@ -6,11 +6,34 @@ use lazy_static::lazy_static;
 use std::collections::HashMap;
 
 abigen!(AccountAPI, "$OUT_DIR/IAccount.sol/IAccount.json");
+abigen!(AggregatorAPI, "$OUT_DIR/IAggregator.sol/IAggregator.json");
 abigen!(EntryPointAPI, "$OUT_DIR/IEntryPoint.sol/IEntryPoint.json");
 abigen!(PaymasterAPI, "$OUT_DIR/IPaymaster.sol/IPaymaster.json");
 abigen!(SenderCreatorAPI, "$OUT_DIR/SenderCreator.sol/SenderCreator.json");
 abigen!(StakeManagerAPI, "$OUT_DIR/IStakeManager.sol/IStakeManager.json");
 
+/// Bindings of the entry point v0.7 smart contracts (generated from the vendored ABIs)
+pub mod v0_7 {
+    use ethers::{contract::abigen, types::Bytes};
+    use lazy_static::lazy_static;
+
+    abigen!(AccountAPI, "$CARGO_MANIFEST_DIR/abi/v0_7/IAccount.json");
+    abigen!(AggregatorAPI, "$CARGO_MANIFEST_DIR/abi/v0_7/IAggregator.json");
+    abigen!(EntryPointAPI, "$CARGO_MANIFEST_DIR/abi/v0_7/IEntryPoint.json");
+    abigen!(EntryPointSimulationsAPI, "$CARGO_MANIFEST_DIR/abi/v0_7/IEntryPointSimulations.json");
+    abigen!(PaymasterAPI, "$CARGO_MANIFEST_DIR/abi/v0_7/IPaymaster.json");
+
+    lazy_static! {
+        /// Runtime bytecode of `EntryPointSimulations`, the code of the entry point is overridden
+        /// with it when simulating (v0.7 doesn't deploy the simulation functions)
+        pub static ref ENTRY_POINT_SIMULATIONS_BYTECODE: Bytes =
+            include_str!(concat!(env!("OUT_DIR"), "/v0_7/EntryPointSimulations.bin"))
+                .trim()
+                .parse()
+                .expect("EntryPointSimulations bytecode is valid hex");
+    }
+}
+
 lazy_static! {
     pub static ref SELECTORS_NAMES: HashMap<Selector, String> = {
         let mut map = HashMap::new();
@@ -28,12 +51,20 @@ lazy_static! {
         map.insert(entry_point_api::UnlockStakeCall::selector(), entry_point_api::UnlockStakeCall::function_name().into());
         map.insert(entry_point_api::WithdrawStakeCall::selector(), entry_point_api::WithdrawStakeCall::function_name().into());
         map.insert(entry_point_api::WithdrawToCall::selector(), entry_point_api::WithdrawToCall::function_name().into());
+        // entry point v0.7 (the functions with the packed user operation)
+        map.insert(v0_7::entry_point_api::GetUserOpHashCall::selector(), v0_7::entry_point_api::GetUserOpHashCall::function_name().into());
+        map.insert(v0_7::entry_point_api::HandleAggregatedOpsCall::selector(), v0_7::entry_point_api::HandleAggregatedOpsCall::function_name().into());
+        map.insert(v0_7::entry_point_api::HandleOpsCall::selector(), v0_7::entry_point_api::HandleOpsCall::function_name().into());
+        map.insert(v0_7::entry_point_simulations_api::SimulateHandleOpCall::selector(), v0_7::entry_point_simulations_api::SimulateHandleOpCall::function_name().into());
+        map.insert(v0_7::entry_point_simulations_api::SimulateValidationCall::selector(), v0_7::entry_point_simulations_api::SimulateValidationCall::function_name().into());
         // sender creator
         map.insert(sender_creator_api::CreateSenderCall::selector(), sender_creator_api::CreateSenderCall::function_name().into());
         // account
         map.insert(account_api::ValidateUserOpCall::selector(), account_api::ValidateUserOpCall::function_name().into());
+        map.insert(v0_7::account_api::ValidateUserOpCall::selector(), v0_7::account_api::ValidateUserOpCall::function_name().into());
         // paymaster
         map.insert(paymaster_api::ValidatePaymasterUserOpCall::selector(), paymaster_api::ValidatePaymasterUserOpCall::function_name().into());
+        map.insert(v0_7::paymaster_api::ValidatePaymasterUserOpCall::selector(), v0_7::paymaster_api::ValidatePaymasterUserOpCall::function_name().into());
         map
     };
     pub static ref SELECTORS_INDICES: HashMap<Selector, usize> = {
@@ -42,8 +73,10 @@ lazy_static! {
         map.insert(sender_creator_api::CreateSenderCall::selector(), 0);
         // sender/account
         map.insert(account_api::ValidateUserOpCall::selector(), 1);
+        map.insert(v0_7::account_api::ValidateUserOpCall::selector(), 1);
         // paymaster
         map.insert(paymaster_api::ValidatePaymasterUserOpCall::selector(), 2);
+        map.insert(v0_7::paymaster_api::ValidatePaymasterUserOpCall::selector(), 2);
         map
     };
 }
@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 
+pub mod aggregator;
 pub mod entry_point;
 mod error;
 pub mod executor_tracer;
@@ -7,7 +8,8 @@ mod gen;
 pub mod tracer;
 pub mod utils;
 
-pub use entry_point::EntryPoint;
+pub use aggregator::Aggregator;
+pub use entry_point::{detect_entry_point_version, EntryPoint, EntryPointVersion};
 pub use error::{decode_revert_string, EntryPointError};
 pub use gen::{
     ExecutionResult, FailedOp, UserOperationEventFilter, UserOperationRevertReasonFilter,
@@ -1,10 +1,10 @@
 use ethers::types::{Address, Bytes, GethTrace, U256};
 use eyre::format_err;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Object (frame) return the JavaScript tracer when simulating validation of user operation
-#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct JsTracerFrame {
     #[serde(rename = "callsFromEntryPoint")]
     pub calls_from_entry_point: Vec<TopLevelCallInfo>,
@@ -25,7 +25,7 @@ impl TryFrom<GethTrace> for JsTracerFrame {
     }
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TopLevelCallInfo {
     #[serde(rename = "topLevelMethodSig")]
     pub top_level_method_sig: Bytes,
@@ -40,26 +40,26 @@ pub struct TopLevelCallInfo {
     pub oog: Option<bool>,
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReadsAndWrites {
     pub reads: HashMap<String, String>,
     pub writes: HashMap<String, u64>,
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ContractSizeInfo {
     pub opcode: String,
     #[serde(rename = "contractSize")]
     pub contract_size: u64,
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Log {
     pub topics: Vec<String>,
     pub data: Bytes,
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Call {
     #[serde(rename = "type")]
     pub typ: String,
@@ -73,7 +73,7 @@ pub struct Call {
     pub value: Option<U256>,
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CallEntry {
     pub typ: String,
     pub from: Option<Address>,
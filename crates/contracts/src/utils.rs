@@ -1,6 +1,13 @@
-use crate::gen::entry_point_api::{self, EntryPointAPICalls};
-use ethers::{abi::AbiDecode, types::Bytes};
-use silius_primitives::UserOperationSigned;
+use crate::gen::{
+    aggregator_api,
+    entry_point_api::{self, EntryPointAPICalls},
+    v0_7,
+};
+use ethers::{
+    abi::AbiDecode,
+    types::{Bytes, H256},
+};
+use silius_primitives::{pack_uint128, unpack_uint128, UserOperationSigned};
 
 impl From<UserOperationSigned> for entry_point_api::UserOperation {
     fn from(uo: UserOperationSigned) -> Self {
@@ -20,6 +27,24 @@ impl From<UserOperationSigned> for entry_point_api::UserOperation {
     }
 }
 
+impl From<UserOperationSigned> for aggregator_api::UserOperation {
+    fn from(uo: UserOperationSigned) -> Self {
+        Self {
+            sender: uo.sender,
+            nonce: uo.nonce,
+            init_code: uo.init_code,
+            call_data: uo.call_data,
+            call_gas_limit: uo.call_gas_limit,
+            verification_gas_limit: uo.verification_gas_limit,
+            pre_verification_gas: uo.pre_verification_gas,
+            max_fee_per_gas: uo.max_fee_per_gas,
+            max_priority_fee_per_gas: uo.max_priority_fee_per_gas,
+            paymaster_and_data: uo.paymaster_and_data,
+            signature: uo.signature,
+        }
+    }
+}
+
 impl From<entry_point_api::UserOperation> for UserOperationSigned {
     fn from(uo: entry_point_api::UserOperation) -> Self {
         Self {
@@ -38,11 +63,82 @@ impl From<entry_point_api::UserOperation> for UserOperationSigned {
     }
 }
 
+/// Implements the conversion of [UserOperationSigned] into the `PackedUserOperation` of the entry
+/// point v0.7 bindings (the gas limits and the fees are packed in pairs)
+macro_rules! impl_packed_user_operation {
+    ($packed:ty) => {
+        impl From<UserOperationSigned> for $packed {
+            fn from(uo: UserOperationSigned) -> Self {
+                Self {
+                    sender: uo.sender,
+                    nonce: uo.nonce,
+                    init_code: uo.init_code,
+                    call_data: uo.call_data,
+                    account_gas_limits: pack_uint128(uo.verification_gas_limit, uo.call_gas_limit)
+                        .0,
+                    pre_verification_gas: uo.pre_verification_gas,
+                    gas_fees: pack_uint128(uo.max_priority_fee_per_gas, uo.max_fee_per_gas).0,
+                    paymaster_and_data: uo.paymaster_and_data,
+                    signature: uo.signature,
+                }
+            }
+        }
+    };
+}
+
+impl_packed_user_operation!(v0_7::entry_point_api::PackedUserOperation);
+impl_packed_user_operation!(v0_7::entry_point_simulations_api::PackedUserOperation);
+impl_packed_user_operation!(v0_7::aggregator_api::PackedUserOperation);
+
+impl From<v0_7::entry_point_api::PackedUserOperation> for UserOperationSigned {
+    fn from(uo: v0_7::entry_point_api::PackedUserOperation) -> Self {
+        let (verification_gas_limit, call_gas_limit) =
+            unpack_uint128(H256::from(uo.account_gas_limits));
+        let (max_priority_fee_per_gas, max_fee_per_gas) = unpack_uint128(H256::from(uo.gas_fees));
+        Self {
+            sender: uo.sender,
+            nonce: uo.nonce,
+            init_code: uo.init_code,
+            call_data: uo.call_data,
+            call_gas_limit,
+            verification_gas_limit,
+            pre_verification_gas: uo.pre_verification_gas,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            paymaster_and_data: uo.paymaster_and_data,
+            signature: uo.signature,
+        }
+    }
+}
+
+/// Parses the user operations from the call data of `handleOps` or `handleAggregatedOps` (of both
+/// entry point versions)
 pub fn parse_from_input_data(data: Bytes) -> Option<Vec<UserOperationSigned>> {
+    if let Ok(call) = v0_7::entry_point_api::EntryPointAPICalls::decode(&data) {
+        return match call {
+            v0_7::entry_point_api::EntryPointAPICalls::HandleOps(ops) => {
+                Some(ops.ops.into_iter().map(|op| op.into()).collect())
+            }
+            v0_7::entry_point_api::EntryPointAPICalls::HandleAggregatedOps(ops) => Some(
+                ops.ops_per_aggregator
+                    .into_iter()
+                    .flat_map(|ops| ops.user_ops.into_iter().map(|op| op.into()))
+                    .collect(),
+            ),
+            _ => None,
+        };
+    }
+
     EntryPointAPICalls::decode(data).ok().and_then(|call| match call {
         EntryPointAPICalls::HandleOps(ops) => {
             Some(ops.ops.into_iter().map(|op| op.into()).collect())
         }
+        EntryPointAPICalls::HandleAggregatedOps(ops) => Some(
+            ops.ops_per_aggregator
+                .into_iter()
+                .flat_map(|ops| ops.user_ops.into_iter().map(|op| op.into()))
+                .collect(),
+        ),
         _ => None,
     })
 }
@@ -50,7 +146,7 @@ pub fn parse_from_input_data(data: Bytes) -> Option<Vec<UserOperationSigned>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ethers::types::Bytes;
+    use ethers::{abi::AbiEncode, types::Bytes};
     use std::str::FromStr;
 
     #[test]
@@ -60,4 +156,21 @@ mod tests {
         let res = parse_from_input_data(data);
         assert!(matches!(res, Some(..)), "No user operation found")
     }
+
+    #[test]
+    fn parse_input_data_v0_7() {
+        let uo = UserOperationSigned::default()
+            .call_gas_limit(33_100.into())
+            .verification_gas_limit(60_624.into())
+            .pre_verification_gas(44_056.into())
+            .max_fee_per_gas(1_695_000_030_u64.into())
+            .max_priority_fee_per_gas(1_695_000_000.into());
+        let data = v0_7::entry_point_api::HandleOpsCall {
+            ops: vec![uo.clone().into()],
+            beneficiary: Default::default(),
+        }
+        .encode();
+
+        assert_eq!(parse_from_input_data(data.into()), Some(vec![uo]));
+    }
 }
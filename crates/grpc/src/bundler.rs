@@ -1,21 +1,36 @@
 use crate::{
     proto::{
         bundler::*,
-        uopool::{GetSortedRequest, RemoveRequest},
+        uopool::{GetCountRequest, GetSortedRequest, RemoveFailedRequest, RemoveRequest},
     },
     uo_pool_client::UoPoolClient,
 };
 use alloy_chains::Chain;
 use async_trait::async_trait;
 use ethers::{
-    providers::Middleware,
+    providers::{Middleware, MiddlewareError},
     types::{Address, H256, U256},
 };
 use parking_lot::Mutex;
-use silius_bundler::{Bundler, SendBundleOp};
-use silius_metrics::grpc::MetricsLayer;
-use silius_primitives::{simulation::StorageMap, UserOperation, Wallet};
-use std::{net::SocketAddr, sync::Arc, time::Duration};
+#[cfg(feature = "sqlite")]
+use silius_bundler::SubmissionLog;
+use silius_bundler::{
+    bundle_reverted, BundleRecovery, BundleReporter, Bundler, BundlerNonceManager, MempoolMonitor,
+    MempoolSize, MulticallBundler, ProfitabilityFilter, RemoveFailedUserOperations,
+    RemoveUserOperations, ReplacementNotifier, SendBundleOp,
+};
+use silius_contracts::EntryPointError;
+use silius_metrics::{bundler::record_bundle_submission, grpc::MetricsLayer};
+use silius_primitives::{
+    constants::bundler::{INCLUSION_POLL_INTERVAL, MULTICALL3_ADDRESS},
+    simulation::StorageMap,
+    EntryPointVersion, UserOperation,
+};
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 use tonic::{Request, Response, Status};
 use tracing::{error, info};
 
@@ -25,8 +40,67 @@ where
     S: SendBundleOp + Clone + 'static,
 {
     pub bundlers: Vec<Bundler<M, S>>,
+    pub multicall_bundler: Option<MulticallBundler<M, S>>,
     pub running: Arc<Mutex<bool>>,
     pub uopool_grpc_client: UoPoolClient<tonic::transport::Channel>,
+    pub replacement_notifier: Option<ReplacementNotifier<M>>,
+    pub bundle_recovery: Option<BundleRecovery<M>>,
+}
+
+#[async_trait]
+impl MempoolSize for UoPoolClient<tonic::transport::Channel> {
+    async fn count(&self, ep: &Address) -> eyre::Result<usize> {
+        let req = Request::new(GetCountRequest { ep: Some((*ep).into()) });
+        let res = self.clone().get_count(req).await?;
+        Ok(res.into_inner().count as usize)
+    }
+}
+
+#[async_trait]
+impl RemoveUserOperations for UoPoolClient<tonic::transport::Channel> {
+    async fn remove_user_operations(
+        &self,
+        ep: &Address,
+        uos: Vec<UserOperation>,
+    ) -> eyre::Result<()> {
+        let req = Request::new(RemoveRequest {
+            uos: uos.into_iter().map(|uo| uo.into()).collect(),
+            ep: Some((*ep).into()),
+        });
+        self.clone().remove(req).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RemoveFailedUserOperations for UoPoolClient<tonic::transport::Channel> {
+    async fn remove_failed_user_operations(
+        &self,
+        ep: &Address,
+        uos: Vec<UserOperation>,
+        banned: Vec<Address>,
+    ) -> eyre::Result<()> {
+        let req = Request::new(RemoveFailedRequest {
+            uos: uos.into_iter().map(|uo| uo.into()).collect(),
+            ep: Some((*ep).into()),
+            banned: banned.into_iter().map(|addr| addr.into()).collect(),
+        });
+        self.clone().remove_failed(req).await?;
+        Ok(())
+    }
+}
+
+/// Returns the reason the bundle couldn't be sent: the `FailedOp` reason if a user operation
+/// failed in `handleOps` (while estimating the gas of the bundle), otherwise the error itself
+fn send_bundle_error<M: Middleware + 'static>(err: &eyre::Report) -> String {
+    match err
+        .downcast_ref::<M::Error>()
+        .and_then(|e| e.as_error_response())
+        .and_then(EntryPointError::failed_op)
+    {
+        Some(op) => format!("FailedOp(opIndex: {}, reason: {})", op.op_index, op.reason),
+        None => format!("{err:?}"),
+    }
 }
 
 fn is_running(running: Arc<Mutex<bool>>) -> bool {
@@ -41,9 +115,56 @@ where
 {
     pub fn new(
         bundlers: Vec<Bundler<M, S>>,
+        multicall_bundler: Option<MulticallBundler<M, S>>,
         uopool_grpc_client: UoPoolClient<tonic::transport::Channel>,
     ) -> Self {
-        Self { bundlers, running: Arc::new(Mutex::new(false)), uopool_grpc_client }
+        let replacement_notifier =
+            bundlers.first().map(|bundler| ReplacementNotifier::new(bundler.eth_client.clone()));
+        // the user operations are simulated again against the entry point version of the bundle
+        let bundle_recovery = bundlers.first().map(|first| {
+            bundlers.iter().fold(BundleRecovery::new(first.eth_client.clone()), |recovery, b| {
+                recovery.with_entry_point_version(b.entry_point, b.entry_point_version)
+            })
+        });
+        Self {
+            bundlers,
+            multicall_bundler,
+            running: Arc::new(Mutex::new(false)),
+            uopool_grpc_client,
+            replacement_notifier,
+            bundle_recovery,
+        }
+    }
+
+    /// Tracks the user operations of a submitted bundle until they are included on-chain (the
+    /// notifier starts with the first submitted bundle)
+    fn notify_inclusion(
+        replacement_notifier: Option<&ReplacementNotifier<M>>,
+        uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
+        ep: Address,
+        uos: &[UserOperation],
+    ) {
+        if let Some(replacement_notifier) = replacement_notifier {
+            replacement_notifier.track(ep, uos);
+            replacement_notifier.start(
+                Arc::new(uopool_grpc_client.clone()),
+                Duration::from_secs(INCLUSION_POLL_INTERVAL),
+            );
+        }
+    }
+
+    /// Simulates the user operations of a submitted bundle again if the bundle reverts on-chain,
+    /// the failing ones are removed from the mempool instead of being bundled again
+    fn recover_on_failure(
+        bundle_recovery: Option<&BundleRecovery<M>>,
+        uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
+        ep: Address,
+        tx_hash: H256,
+        uos: &[UserOperation],
+    ) {
+        if let Some(bundle_recovery) = bundle_recovery {
+            bundle_recovery.watch(Arc::new(uopool_grpc_client.clone()), ep, tx_hash, uos.to_vec());
+        }
     }
 
     async fn get_user_operations(
@@ -64,23 +185,96 @@ where
         Ok((uos, map))
     }
 
-    pub async fn send_bundles(&self) -> eyre::Result<(Vec<UserOperation>, Option<H256>)> {
+    async fn send_multicall_bundle(
+        multicall_bundler: &MulticallBundler<M, S>,
+        uopool_grpc_client: &UoPoolClient<tonic::transport::Channel>,
+        replacement_notifier: Option<&ReplacementNotifier<M>>,
+        bundle_recovery: Option<&BundleRecovery<M>>,
+    ) -> eyre::Result<(Vec<(Address, Vec<UserOperation>)>, Option<H256>)> {
+        let mut bundles = vec![];
+
+        for bundler in multicall_bundler.bundlers.iter() {
+            let (uos, map) =
+                Self::get_user_operations(uopool_grpc_client, &bundler.entry_point).await?;
+            bundles.push((bundler.entry_point, uos, map));
+        }
+
+        let start = Instant::now();
+        let submitted: Vec<(Address, Vec<UserOperation>)> =
+            bundles.iter().map(|(ep, uos, _)| (*ep, uos.clone())).collect();
+        let hashes = multicall_bundler.send_bundles(bundles).await?;
+        let latency = start.elapsed();
+
+        // entry points retried separately are watched with the hash of their own transaction
+        for (ep, hash) in hashes.iter() {
+            if let Some((_, uos)) = submitted.iter().find(|(submitted_ep, _)| submitted_ep == ep) {
+                record_bundle_submission(*ep, latency);
+                Self::notify_inclusion(replacement_notifier, uopool_grpc_client, *ep, uos);
+                Self::recover_on_failure(bundle_recovery, uopool_grpc_client, *ep, *hash, uos);
+            }
+        }
+
+        // the user operations of the first transaction (the multicall one, if it was sent)
+        let tx_hash = hashes.first().map(|(_, hash)| *hash);
+        let submitted = submitted
+            .into_iter()
+            .filter(|(ep, _)| {
+                hashes.iter().any(|(sent_ep, hash)| sent_ep == ep && Some(*hash) == tx_hash)
+            })
+            .collect();
+
+        Ok((submitted, tx_hash))
+    }
+
+    /// Sends the bundle of the entry point and records the submission metrics
+    async fn send_bundle(
+        bundler: &Bundler<M, S>,
+        uos: &[UserOperation],
+        map: StorageMap,
+    ) -> eyre::Result<Option<H256>> {
+        let start = Instant::now();
+        let tx_hash = bundler.send_bundle(uos, map).await?;
+        if tx_hash.is_some() {
+            record_bundle_submission(bundler.entry_point, start.elapsed());
+        }
+        Ok(tx_hash)
+    }
+
+    /// Sends the bundles of the entry points
+    ///
+    /// # Returns
+    /// * The entry points and their user operations in the bundle, with the hash of the bundle
+    ///   transaction
+    pub async fn send_bundles(
+        &self,
+    ) -> eyre::Result<(Vec<(Address, Vec<UserOperation>)>, Option<H256>)> {
+        if let Some(multicall_bundler) = self.multicall_bundler.as_ref() {
+            return Self::send_multicall_bundle(
+                multicall_bundler,
+                &self.uopool_grpc_client,
+                self.replacement_notifier.as_ref(),
+                self.bundle_recovery.as_ref(),
+            )
+            .await;
+        }
+
         let mut tx_hashes: Vec<Option<H256>> = vec![];
-        let mut user_operations: Vec<Vec<UserOperation>> = vec![];
+        let mut user_operations: Vec<(Address, Vec<UserOperation>)> = vec![];
 
         for bundler in self.bundlers.iter() {
             let (uos, map) =
                 Self::get_user_operations(&self.uopool_grpc_client, &bundler.entry_point).await?;
-            let tx_hash = bundler.send_bundle(&uos, map).await?;
+            let tx_hash = Self::send_bundle(bundler, &uos, map).await?;
 
             tx_hashes.push(tx_hash);
-            user_operations.push(uos);
+            user_operations.push((bundler.entry_point, uos));
         }
 
         // FIXME: Because currently the bundler support multiple bundler and
         // we don't have a way to know which bundler is the one that is
+        user_operations.truncate(1);
         Ok((
-            user_operations.first().expect("At least one bundler must be present").to_vec(),
+            user_operations,
             tx_hashes.into_iter().next().expect("At least one bundler must be present"),
         ))
     }
@@ -104,10 +298,43 @@ where
                 *r = true;
             }
 
+            if let Some(multicall_bundler) = self.multicall_bundler.clone() {
+                let running_lock = self.running.clone();
+                let uopool_grpc_client = self.uopool_grpc_client.clone();
+                let replacement_notifier = self.replacement_notifier.clone();
+                let bundle_recovery = self.bundle_recovery.clone();
+
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(Duration::from_secs(int));
+                    loop {
+                        interval.tick().await;
+
+                        if !is_running(running_lock.clone()) {
+                            break;
+                        }
+
+                        if let Err(e) = Self::send_multicall_bundle(
+                            &multicall_bundler,
+                            &uopool_grpc_client,
+                            replacement_notifier.as_ref(),
+                            bundle_recovery.as_ref(),
+                        )
+                        .await
+                        {
+                            error!("Error while sending multicall bundle: {e:?}");
+                        }
+                    }
+                });
+
+                return;
+            }
+
             for bundler in self.bundlers.iter() {
                 let bundler_own = bundler.clone();
                 let running_lock = self.running.clone();
                 let uopool_grpc_client = self.uopool_grpc_client.clone();
+                let replacement_notifier = self.replacement_notifier.clone();
+                let bundle_recovery = self.bundle_recovery.clone();
 
                 tokio::spawn(async move {
                     let mut interval = tokio::time::interval(Duration::from_secs(int));
@@ -125,8 +352,26 @@ where
                         .await
                         {
                             Ok((bundle, map)) => {
-                                if let Err(e) = bundler_own.send_bundle(&bundle, map).await {
-                                    error!("Error while sending bundle: {e:?}");
+                                match Self::send_bundle(&bundler_own, &bundle, map).await {
+                                    Ok(Some(tx_hash)) => {
+                                        Self::notify_inclusion(
+                                            replacement_notifier.as_ref(),
+                                            &uopool_grpc_client,
+                                            bundler_own.entry_point,
+                                            &bundle,
+                                        );
+                                        Self::recover_on_failure(
+                                            bundle_recovery.as_ref(),
+                                            &uopool_grpc_client,
+                                            bundler_own.entry_point,
+                                            tx_hash,
+                                            &bundle,
+                                        );
+                                    }
+                                    Ok(None) => {}
+                                    Err(e) => {
+                                        error!("Error while sending bundle: {e:?}");
+                                    }
                                 }
                             }
                             Err(e) => {
@@ -169,10 +414,16 @@ where
         &self,
         _req: Request<()>,
     ) -> Result<Response<SendBundleNowResponse>, Status> {
-        let (uos, tx_hash) = self
-            .send_bundles()
-            .await
-            .map_err(|e| tonic::Status::internal(format!("Send bundle now with error: {e:?}")))?;
+        let (uos, tx_hash) = match self.send_bundles().await {
+            Ok(res) => res,
+            Err(e) => {
+                error!("Send bundle now with error: {e:?}");
+                return Ok(Response::new(SendBundleNowResponse {
+                    transaction_hash: None,
+                    error: send_bundle_error::<M>(&e),
+                }));
+            }
+        };
 
         if let Some(tx_hash) = tx_hash {
             // wait for the tx to be mined
@@ -184,37 +435,52 @@ where
                     .eth_client
                     .get_transaction_receipt(tx_hash)
                     .await;
-                if let Ok(tx_receipt) = tx_receipt {
-                    if tx_receipt.is_some() {
+                if let Ok(Some(tx_receipt)) = tx_receipt {
+                    // a reverted `handleOps` call of a multicall transaction leaves its user
+                    // operations in the mempool (the bundle recovery takes care of them)
+                    for (ep, uos) in uos
+                        .into_iter()
+                        .filter(|(ep, uos)| !uos.is_empty() && !bundle_reverted(&tx_receipt, ep))
+                    {
                         self.uopool_grpc_client
                             .clone()
                             .remove(Request::new(RemoveRequest {
                                 uos: uos.into_iter().map(|uo| uo.into()).collect(),
-                                ep: Some(
-                                    self.bundlers
-                                        .first()
-                                        .expect("Must have at least one bundler")
-                                        .entry_point
-                                        .into(),
-                                ),
+                                ep: Some(ep.into()),
                             }))
                             .await?;
-                        break;
                     }
+                    break;
                 }
                 tokio::time::sleep(Duration::from_millis(50)).await;
             }
         }
 
-        Ok(Response::new(SendBundleNowResponse { res: Some(tx_hash.unwrap_or_default().into()) }))
+        Ok(Response::new(SendBundleNowResponse {
+            transaction_hash: tx_hash.map(|tx_hash| tx_hash.into()),
+            error: String::new(),
+        }))
+    }
+
+    async fn get_relay_status(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetRelayStatusResponse>, Status> {
+        // all bundlers share the client
+        let relays =
+            self.bundlers.first().map(|bundler| bundler.client.relay_status()).unwrap_or_default();
+
+        Ok(Response::new(GetRelayStatusResponse {
+            relays: relays.into_iter().map(|relay| relay.into()).collect(),
+        }))
     }
 }
 
 #[allow(clippy::too_many_arguments)]
 pub fn bundler_service_run<M, S>(
     addr: SocketAddr,
-    wallet: Wallet,
-    eps: Vec<Address>,
+    address: Address,
+    eps: Vec<(Address, EntryPointVersion)>,
     chain: Chain,
     beneficiary: Address,
     min_balance: U256,
@@ -224,15 +490,31 @@ pub fn bundler_service_run<M, S>(
     uopool_grpc_client: UoPoolClient<tonic::transport::Channel>,
     enable_metrics: bool,
     enable_access_list: bool,
+    use_multicall_bundler: bool,
+    bundle_reporter: Option<Arc<BundleReporter>>,
+    mempool_monitor: Option<MempoolMonitor>,
+    mempool_monitor_interval: Duration,
+    profitability_filter: Option<ProfitabilityFilter>,
+    #[cfg(feature = "sqlite")] submission_log: Option<Arc<SubmissionLog>>,
 ) where
     M: Middleware + Clone + 'static,
     S: SendBundleOp + Clone + 'static,
 {
+    if let Some(mempool_monitor) = mempool_monitor {
+        mempool_monitor.start(
+            Arc::new(uopool_grpc_client.clone()),
+            eps.iter().map(|(ep, _)| *ep).collect(),
+            mempool_monitor_interval,
+        );
+    }
+
+    // the bundlers of all entry points send from the same account
+    let nonce_manager = BundlerNonceManager::new(address);
     let bundlers: Vec<Bundler<M, S>> = eps
         .into_iter()
-        .map(|ep| {
-            Bundler::new(
-                wallet.clone(),
+        .map(|(ep, ep_version)| {
+            let mut bundler = Bundler::new(
+                address,
                 beneficiary,
                 ep,
                 chain,
@@ -241,10 +523,30 @@ pub fn bundler_service_run<M, S>(
                 client.clone(),
                 enable_access_list,
             )
+            .with_entry_point_version(ep_version);
+            bundler.reporter = bundle_reporter.clone();
+            bundler.profitability_filter = profitability_filter;
+            #[cfg(feature = "sqlite")]
+            {
+                bundler.submission_log = submission_log.clone();
+            }
+            bundler.nonce_manager = nonce_manager.clone();
+            bundler
         })
         .collect();
 
-    let bundler_service = BundlerService::new(bundlers, uopool_grpc_client);
+    let multicall_bundler = if use_multicall_bundler {
+        MulticallBundler::new(
+            bundlers.clone(),
+            MULTICALL3_ADDRESS.parse().expect("Multicall3 address is valid"),
+        )
+        .map_err(|e| error!("Multicall bundler disabled: {e:?}"))
+        .ok()
+    } else {
+        None
+    };
+
+    let bundler_service = BundlerService::new(bundlers, multicall_bundler, uopool_grpc_client);
     if let Some(bundle_interval) = bundle_interval {
         bundler_service.start_bundling(bundle_interval);
     }
@@ -7,4 +7,4 @@ mod utils;
 
 pub use bundler::{bundler_service_run, BundlerService};
 pub use proto::{bundler::*, types::*, uopool::*};
-pub use uopool::{uopool_service_run, UoPoolService};
+pub use uopool::{uopool_service_run, UoPoolServe, UoPoolService};
@@ -116,6 +116,7 @@ pub mod types {
                         user_operation.signature.as_ref(),
                     ),
                 }),
+                aggregator: user_operation.aggregator.map(|aggregator| aggregator.into()),
             }
         }
     }
@@ -137,6 +138,7 @@ pub mod types {
                         silius_primitives::UserOperationSigned::default()
                     }
                 },
+                aggregator: user_operation.aggregator.map(|aggregator| aggregator.into()),
             }
         }
     }
@@ -426,6 +428,79 @@ pub mod types {
 
 pub mod uopool {
     tonic::include_proto!("uopool");
+
+    impl From<silius_primitives::UserOperationStatus> for UserOperationStatus {
+        fn from(value: silius_primitives::UserOperationStatus) -> Self {
+            match value {
+                silius_primitives::UserOperationStatus::Pending => Self::Pending,
+                silius_primitives::UserOperationStatus::Included => Self::Included,
+                silius_primitives::UserOperationStatus::Dropped => Self::Dropped,
+            }
+        }
+    }
+
+    impl From<UserOperationStatus> for silius_primitives::UserOperationStatus {
+        fn from(value: UserOperationStatus) -> Self {
+            match value {
+                UserOperationStatus::Pending => Self::Pending,
+                UserOperationStatus::Included => Self::Included,
+                UserOperationStatus::Dropped => Self::Dropped,
+            }
+        }
+    }
+
+    impl From<silius_primitives::MempoolStats> for GetMempoolStatsResponse {
+        fn from(value: silius_primitives::MempoolStats) -> Self {
+            Self {
+                total_ops: value.total_ops as u64,
+                unique_senders: value.unique_senders as u64,
+                unique_paymasters: value.unique_paymasters as u64,
+                oldest_op_age_secs: value.oldest_op_age_secs,
+            }
+        }
+    }
+
+    impl From<GetMempoolStatsResponse> for silius_primitives::MempoolStats {
+        fn from(value: GetMempoolStatsResponse) -> Self {
+            Self {
+                total_ops: value.total_ops as usize,
+                unique_senders: value.unique_senders as usize,
+                unique_paymasters: value.unique_paymasters as usize,
+                oldest_op_age_secs: value.oldest_op_age_secs,
+            }
+        }
+    }
+
+    impl From<silius_primitives::reputation::ReputationEntryWithReason> for ReputationEntryWithReason {
+        fn from(value: silius_primitives::reputation::ReputationEntryWithReason) -> Self {
+            Self {
+                entry: Some(value.entry.into()),
+                inclusion_rate: value.inclusion_rate,
+                threshold: value.threshold,
+                reason: value.reason.unwrap_or_default(),
+            }
+        }
+    }
+
+    impl From<ReputationEntryWithReason> for silius_primitives::reputation::ReputationEntryWithReason {
+        fn from(value: ReputationEntryWithReason) -> Self {
+            Self {
+                entry: value.entry.map(Into::into).unwrap_or_default(),
+                inclusion_rate: value.inclusion_rate,
+                threshold: value.threshold,
+                reason: Some(value.reason).filter(|reason| !reason.is_empty()),
+            }
+        }
+    }
+
+    impl From<silius_primitives::UserOperationEvent> for UserOperationEventResponse {
+        fn from(value: silius_primitives::UserOperationEvent) -> Self {
+            Self {
+                hash: Some(value.hash.into()),
+                status: UserOperationStatus::from(value.status).into(),
+            }
+        }
+    }
 }
 
 pub mod bundler {
@@ -450,4 +525,44 @@ pub mod bundler {
             }
         }
     }
+
+    impl From<silius_primitives::RelayHealth> for RelayHealth {
+        fn from(value: silius_primitives::RelayHealth) -> Self {
+            match value {
+                silius_primitives::RelayHealth::Ok => Self::Healthy,
+                silius_primitives::RelayHealth::Degraded => Self::Degraded,
+                silius_primitives::RelayHealth::Down => Self::Down,
+            }
+        }
+    }
+
+    impl From<RelayHealth> for silius_primitives::RelayHealth {
+        fn from(value: RelayHealth) -> Self {
+            match value {
+                RelayHealth::Healthy => Self::Ok,
+                RelayHealth::Degraded => Self::Degraded,
+                RelayHealth::Down => Self::Down,
+            }
+        }
+    }
+
+    impl From<silius_primitives::RelayStatus> for RelayStatus {
+        fn from(value: silius_primitives::RelayStatus) -> Self {
+            Self {
+                relay: value.relay,
+                status: RelayHealth::from(value.status).into(),
+                last_error: value.last_error.unwrap_or_default(),
+            }
+        }
+    }
+
+    impl From<RelayStatus> for silius_primitives::RelayStatus {
+        fn from(value: RelayStatus) -> Self {
+            Self {
+                status: value.status().into(),
+                relay: value.relay,
+                last_error: (!value.last_error.is_empty()).then_some(value.last_error),
+            }
+        }
+    }
 }
@@ -12,22 +12,27 @@ use ethers::{
     types::{Address, U256},
 };
 use eyre::Result;
-use futures::{channel::mpsc::unbounded, StreamExt};
+use futures::{channel::mpsc::unbounded, Stream, StreamExt};
 use parking_lot::RwLock;
+use silius_bundler::BundleSizeOptimizer;
 use silius_mempool::{
-    mempool_id, validate::validator::StandardUserOperationValidator, Mempool, MempoolErrorKind,
-    MempoolId, Reputation, SanityCheck, SimulationCheck, SimulationTraceCheck,
-    UoPool as UserOperationPool, UoPoolBuilder,
+    mempool_id, validate::validator::StandardUserOperationValidator, EntryPointOverhead, Mempool,
+    MempoolError, MempoolErrorKind, MempoolId, Reputation, SanityCheck, SimulationCheck,
+    SimulationTraceCheck, StakeRequirements, UoPool as UserOperationPool, UoPoolBuilder,
 };
-use silius_metrics::grpc::MetricsLayer;
+use silius_metrics::{grpc::MetricsLayer, mempool::record_validation_error};
 use silius_p2p::{
     config::Config,
     service::{MempoolChannel, Network},
 };
-use silius_primitives::{p2p::NetworkMessage, provider::BlockStream, UoPoolMode};
-use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
-use tonic::{Code, Request, Response, Status};
-use tracing::{error, info};
+use silius_primitives::{
+    p2p::NetworkMessage, provider::BlockStream, BundleSelectorType, EntryPointVersion, UoPoolMode,
+    UserOperationHash,
+};
+use std::{collections::HashMap, net::SocketAddr, pin::Pin, sync::Arc, time::Duration};
+use tokio::{sync::broadcast::error::RecvError, task::JoinHandle};
+use tonic::{transport::server::TcpIncoming, Code, Request, Response, Status};
+use tracing::{error, info, warn};
 
 type StandardUserPool<M, SanCk, SimCk, SimTrCk> =
     UserOperationPool<M, StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>>;
@@ -35,6 +40,49 @@ type StandardUserPool<M, SanCk, SimCk, SimTrCk> =
 type UoPoolMaps<M, SanCk, SimCk, SimTrCk> =
     Arc<RwLock<HashMap<MempoolId, UoPoolBuilder<M, SanCk, SimCk, SimTrCk>>>>;
 
+type UserOperationEventStream =
+    Pin<Box<dyn Stream<Item = Result<UserOperationEventResponse, Status>> + Send>>;
+
+/// Converts the outcome of adding a user operation into the response (errors other than the
+/// invalid user operation and the full mempool are internal errors)
+fn add_response(res: Result<UserOperationHash, MempoolError>) -> Result<AddResponse, Status> {
+    if let Err(ref err) = res {
+        if !matches!(
+            err.kind,
+            MempoolErrorKind::InvalidUserOperation(_) |
+                MempoolErrorKind::MempoolFull |
+                MempoolErrorKind::TooManyOpsForSender { .. }
+        ) {
+            return Err(Status::internal(format!("Internal error: {err}")));
+        }
+    }
+    add_result(res)
+}
+
+/// Converts the outcome of adding a user operation of a batch into its result (any error is
+/// returned in the result, so the hashes of the other user operations aren't lost)
+fn add_result(res: Result<UserOperationHash, MempoolError>) -> Result<AddResponse, Status> {
+    match res {
+        Ok(uo_hash) => Ok(AddResponse {
+            res: AddResult::Added as i32,
+            data: serde_json::to_string(&uo_hash)
+                .map_err(|err| Status::internal(format!("Failed to serialize hash: {err}")))?,
+            results: vec![],
+        }),
+        Err(err) => {
+            if let MempoolErrorKind::InvalidUserOperation(ref e) = err.kind {
+                record_validation_error(e);
+            }
+            Ok(AddResponse {
+                res: AddResult::NotAdded as i32,
+                data: serde_json::to_string(&err)
+                    .map_err(|err| Status::internal(format!("Failed to serialize error: {err}")))?,
+                results: vec![],
+            })
+        }
+    }
+}
+
 pub struct UoPoolService<M, SanCk, SimCk, SimTrCk>
 where
     M: Middleware + Clone + 'static,
@@ -44,6 +92,7 @@ where
 {
     pub uopools: UoPoolMaps<M, SanCk, SimCk, SimTrCk>,
     pub chain: Chain,
+    pub bundle_size_optimizer: Option<BundleSizeOptimizer>,
 }
 
 impl<M, SanCk, SimCk, SimTrCk> UoPoolService<M, SanCk, SimCk, SimTrCk>
@@ -53,8 +102,12 @@ where
     SimCk: SimulationCheck + Clone + 'static,
     SimTrCk: SimulationTraceCheck<M> + Clone + 'static,
 {
-    pub fn new(uopools: UoPoolMaps<M, SanCk, SimCk, SimTrCk>, chain: Chain) -> Self {
-        Self { uopools, chain }
+    pub fn new(
+        uopools: UoPoolMaps<M, SanCk, SimCk, SimTrCk>,
+        chain: Chain,
+        bundle_size_optimizer: Option<BundleSizeOptimizer>,
+    ) -> Self {
+        Self { uopools, chain, bundle_size_optimizer }
     }
 
     #[allow(clippy::type_complexity)]
@@ -79,35 +132,39 @@ where
     SimCk: SimulationCheck + Clone + 'static,
     SimTrCk: SimulationTraceCheck<M> + Clone + 'static,
 {
+    type SubscribeUserOperationEventsStream = UserOperationEventStream;
+
     async fn add(&self, req: Request<AddRequest>) -> Result<Response<AddResponse>, Status> {
         let req = req.into_inner();
 
-        let uo = parse_uo(req.uo)?;
         let ep = parse_addr(req.ep)?;
 
-        let res = {
-            let uopool = self.get_uopool(&ep)?;
-            uopool.validate_user_operation(&uo, None).await
-        };
+        if !req.uos.is_empty() {
+            let uos = req.uos.into_iter().map(|uo| uo.into()).collect();
+            let mut uopool = self.get_uopool(&ep)?;
+            let results = uopool
+                .add_user_operations_batch(uos)
+                .await
+                .into_iter()
+                .map(add_result)
+                .collect::<Result<Vec<_>, _>>()?;
+            let res = if results.iter().all(|res| res.res == AddResult::Added as i32) {
+                AddResult::Added
+            } else {
+                AddResult::NotAdded
+            };
+            return Ok(Response::new(AddResponse {
+                res: res as i32,
+                data: String::new(),
+                results,
+            }));
+        }
+
+        let uo = parse_uo(req.uo)?;
 
         let mut uopool = self.get_uopool(&ep)?;
 
-        match uopool.add_user_operation(uo, res).await {
-            Ok(uo_hash) => Ok(Response::new(AddResponse {
-                res: AddResult::Added as i32,
-                data: serde_json::to_string(&uo_hash)
-                    .map_err(|err| Status::internal(format!("Failed to serialize hash: {err}")))?,
-            })),
-            Err(err) => match err.kind {
-                MempoolErrorKind::InvalidUserOperation(_) => Ok(Response::new(AddResponse {
-                    res: AddResult::NotAdded as i32,
-                    data: serde_json::to_string(&err).map_err(|err| {
-                        Status::internal(format!("Failed to serialize error: {err}"))
-                    })?,
-                })),
-                _ => Err(Status::internal(format!("Internal error: {err}"))),
-            },
-        }
+        Ok(Response::new(add_response(uopool.validate_and_add_user_operation(uo, None).await)?))
     }
 
     async fn remove(&self, req: Request<RemoveRequest>) -> Result<Response<()>, Status> {
@@ -121,6 +178,23 @@ where
         Ok(Response::new(()))
     }
 
+    async fn remove_failed(
+        &self,
+        req: Request<RemoveFailedRequest>,
+    ) -> Result<Response<()>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let mut uopool = self.get_uopool(&ep)?;
+
+        uopool.remove_failed_user_operations(
+            req.uos.into_iter().map(|uo| uo.into()).collect(),
+            &req.banned.into_iter().map(|addr| addr.into()).collect::<Vec<Address>>(),
+        );
+
+        Ok(Response::new(()))
+    }
+
     async fn get_chain_id(
         &self,
         _req: Request<()>,
@@ -177,9 +251,18 @@ where
 
         let uos = {
             let uopool = self.get_uopool(&ep)?;
-            uopool.get_sorted_user_operations().map_err(|e| {
+            let base_fee = uopool.base_fee_per_gas().await.map_err(|e| {
+                tonic::Status::internal(format!("Get base fee internal error: {e:?}"))
+            })?;
+            let uos = uopool.get_sorted_user_operations(base_fee).map_err(|e| {
                 tonic::Status::internal(format!("Get sorted uos internal error: {e:?}"))
-            })?
+            })?;
+            let uos = uopool.select_bundle_candidates(uos, base_fee).await;
+
+            match self.bundle_size_optimizer {
+                Some(optimizer) => optimizer.optimize(uos, base_fee),
+                None => uos,
+            }
         };
 
         let (uos_valid, storage_map) = {
@@ -215,9 +298,9 @@ where
                 return Ok(Response::new(GetUserOperationByHashResponse {
                     user_operation: Some(uo_by_hash.user_operation.into()),
                     entry_point: Some(uo_by_hash.entry_point.into()),
-                    transaction_hash: Some(uo_by_hash.transaction_hash.into()),
-                    block_hash: Some(uo_by_hash.block_hash.into()),
-                    block_number: uo_by_hash.block_number.as_u64(),
+                    transaction_hash: uo_by_hash.transaction_hash.map(Into::into),
+                    block_hash: uo_by_hash.block_hash.map(Into::into),
+                    block_number: uo_by_hash.block_number.map(|n| n.as_u64()).unwrap_or_default(),
                 }));
             }
         }
@@ -274,6 +357,130 @@ where
         }
     }
 
+    async fn get_count(
+        &self,
+        req: Request<GetCountRequest>,
+    ) -> Result<Response<GetCountResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uopool = self.get_uopool(&ep)?;
+        match uopool.count() {
+            Ok(count) => Ok(Response::new(GetCountResponse { count: count as u64 })),
+            Err(err) => Err(Status::unknown(format!("Internal error: {err:?}"))),
+        }
+    }
+
+    async fn get_mempool_paged(
+        &self,
+        req: Request<GetMempoolPagedRequest>,
+    ) -> Result<Response<GetMempoolPagedResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uopool = self.get_uopool(&ep)?;
+        match uopool.get_page(req.offset as usize, req.limit as usize) {
+            Ok((uos, total)) => Ok(Response::new(GetMempoolPagedResponse {
+                uos: uos.into_iter().map(Into::into).collect(),
+                total: total as u64,
+            })),
+            Err(err) => Err(Status::unknown(format!("Internal error: {err:?}"))),
+        }
+    }
+
+    async fn get_all_by_entity(
+        &self,
+        req: Request<GetAllByEntityRequest>,
+    ) -> Result<Response<GetAllResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let entity = parse_addr(req.entity)?;
+        let uopool = self.get_uopool(&ep)?;
+        let uos = uopool.get_all_by_entity(&entity);
+        Ok(Response::new(GetAllResponse { uos: uos.into_iter().map(Into::into).collect() }))
+    }
+
+    async fn get_user_operations_by_paymaster(
+        &self,
+        req: Request<GetUserOperationsByPaymasterRequest>,
+    ) -> Result<Response<GetUserOperationsByPaymasterResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let paymaster = parse_addr(req.paymaster)?;
+        let uopool = self.get_uopool(&ep)?;
+        let (uos, total_committed_gas) = uopool.get_all_by_paymaster(&paymaster);
+        Ok(Response::new(GetUserOperationsByPaymasterResponse {
+            uos: uos.into_iter().map(Into::into).collect(),
+            total_committed_gas,
+        }))
+    }
+
+    async fn get_entity_relationships(
+        &self,
+        req: Request<GetEntityRelationshipsRequest>,
+    ) -> Result<Response<GetEntityRelationshipsResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let addr = parse_addr(req.addr)?;
+        let uopool = self.get_uopool(&ep)?;
+        Ok(Response::new(GetEntityRelationshipsResponse {
+            entities: uopool.get_entities_for_sender(&addr).into_iter().map(Into::into).collect(),
+            senders: uopool.get_senders_for_entity(&addr).into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn get_mempool_stats(
+        &self,
+        req: Request<GetMempoolStatsRequest>,
+    ) -> Result<Response<GetMempoolStatsResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uopool = self.get_uopool(&ep)?;
+        Ok(Response::new(uopool.get_mempool_stats().into()))
+    }
+
+    async fn trace_user_operation(
+        &self,
+        req: Request<TraceUserOperationRequest>,
+    ) -> Result<Response<TraceUserOperationResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uopool = self.get_uopool(&ep)?;
+
+        let uo = match req.uo {
+            Some(uo) => uo.into(),
+            None => {
+                let uo_hash = parse_hash(req.hash)?;
+                uopool
+                    .get_user_operation_by_hash(&uo_hash.into())
+                    .await
+                    .map_err(|_| Status::not_found("User operation not found"))?
+                    .user_operation
+            }
+        };
+
+        let trace = uopool
+            .trace_user_operation(&uo)
+            .await
+            .map_err(|err| Status::internal(format!("Failed to trace user operation: {err}")))?;
+
+        Ok(Response::new(TraceUserOperationResponse {
+            trace: serde_json::to_string(&trace)
+                .map_err(|err| Status::internal(format!("Failed to serialize trace: {err}")))?,
+            note: match uopool.mode {
+                UoPoolMode::Standard => String::new(),
+                UoPoolMode::Unsafe => "The mempool runs in unsafe mode, the opcode and storage \
+                                       access rules are not checked on the validation trace"
+                    .into(),
+            },
+        }))
+    }
+
     async fn clear_mempool(&self, _req: Request<()>) -> Result<Response<()>, Status> {
         self.uopools.read().values().for_each(|uopool| {
             uopool.uopool().clear_mempool();
@@ -288,6 +495,13 @@ where
         Ok(Response::new(()))
     }
 
+    async fn clear_receipts(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        self.uopools.read().values().for_each(|uopool| {
+            uopool.uopool().clear_receipts();
+        });
+        Ok(Response::new(()))
+    }
+
     async fn clear(&self, _req: Request<()>) -> Result<Response<()>, Status> {
         self.uopools.read().values().for_each(|uopool| {
             uopool.uopool().clear();
@@ -305,7 +519,7 @@ where
         let uopool = self.get_uopool(&ep)?;
 
         Ok(Response::new(GetAllReputationResponse {
-            rep: uopool.get_reputation().into_iter().map(Into::into).collect(),
+            rep: uopool.get_reputation_with_reason().into_iter().map(Into::into).collect(),
         }))
     }
 
@@ -320,7 +534,13 @@ where
 
         let res = Response::new(SetReputationResponse {
             res: match uopool.set_reputation(req.rep.iter().map(|re| re.clone().into()).collect()) {
-                Ok(_) => SetReputationResult::Set as i32,
+                Ok(_) => {
+                    uopool.set_entity_lists(
+                        req.whitelist.into_iter().map(Into::into).collect(),
+                        req.blacklist.into_iter().map(Into::into).collect(),
+                    );
+                    SetReputationResult::Set as i32
+                }
                 Err(_) => SetReputationResult::NotSet as i32,
             },
         });
@@ -369,20 +589,184 @@ where
             is_staked: res.is_staked,
         }))
     }
+
+    async fn subscribe_user_operation_events(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<Self::SubscribeUserOperationEventsStream>, Status> {
+        let receivers: Vec<_> = self.uopools.read().values().map(|b| b.subscribe()).collect();
+        let (tx, rx) = unbounded::<Result<UserOperationEventResponse, Status>>();
+
+        // forward the changes of all mempools until the client disconnects
+        for mut receiver in receivers {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => {
+                            if tx.unbounded_send(Ok(event.into())).is_err() {
+                                break;
+                            }
+                        }
+                        Err(RecvError::Lagged(n)) => {
+                            warn!("User operation events subscriber lagged behind by {n} events")
+                        }
+                        Err(RecvError::Closed) => break,
+                    }
+                }
+            });
+        }
+
+        Ok(Response::new(Box::pin(rx)))
+    }
+
+    async fn validate_user_operation(
+        &self,
+        req: Request<ValidateUserOperationRequest>,
+    ) -> Result<Response<ValidateUserOperationResponse>, Status> {
+        let req = req.into_inner();
+
+        let uo = parse_uo(req.uo)?;
+        let ep = parse_addr(req.ep)?;
+
+        let uopool = self.get_uopool(&ep)?;
+
+        Ok(Response::new(match uopool.validate_user_operation(&uo, None).await {
+            Ok(res) => ValidateUserOperationResponse {
+                res: ValidateUserOperationResult::Valid as i32,
+                pre_fund: Some(res.pre_fund.into()),
+                verification_gas_limit: Some(res.verification_gas_limit.into()),
+                valid_after: res.valid_after.map(|v| v.into()),
+                data: String::new(),
+                valid_until: Some(res.valid_until.into()),
+                // pre-op gas includes the pre-verification gas
+                validation_gas_used: Some(
+                    res.verification_gas_limit.saturating_sub(uo.pre_verification_gas).into(),
+                ),
+                paymaster_gas_used: res.paymaster_gas_used.map(|v| v.into()),
+            },
+            Err(err) => ValidateUserOperationResponse {
+                res: ValidateUserOperationResult::NotValid as i32,
+                pre_fund: None,
+                verification_gas_limit: None,
+                valid_after: None,
+                valid_until: None,
+                validation_gas_used: None,
+                paymaster_gas_used: None,
+                data: serde_json::to_string(&MempoolError { hash: uo.hash, kind: err.into() })
+                    .map_err(|err| Status::internal(format!("Failed to serialize error: {err}")))?,
+            },
+        }))
+    }
+
+    async fn simulate_bundle(
+        &self,
+        req: Request<SimulateBundleRequest>,
+    ) -> Result<Response<SimulateBundleResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uopool = self.get_uopool(&ep)?;
+
+        let mut valid = vec![];
+        let mut failed = vec![];
+        let mut gas_total = U256::zero();
+
+        for uo in req.uos.into_iter().map(silius_primitives::UserOperation::from) {
+            match uopool.simulate_user_operation(&uo).await {
+                Ok(res) => {
+                    gas_total = gas_total
+                        .saturating_add(res.verification_gas_limit)
+                        .saturating_add(uo.call_gas_limit);
+                    valid.push(uo.hash.into());
+                }
+                Err(err) => failed.push(SimulateBundleFailure {
+                    hash: Some(uo.hash.into()),
+                    data: serde_json::to_string(&MempoolError { hash: uo.hash, kind: err.into() })
+                        .map_err(|err| {
+                            Status::internal(format!("Failed to serialize error: {err}"))
+                        })?,
+                }),
+            }
+        }
+
+        Ok(Response::new(SimulateBundleResponse {
+            valid,
+            failed,
+            gas_total: Some(gas_total.into()),
+        }))
+    }
+
+    async fn suggest_max_priority_fee_per_gas(
+        &self,
+        req: Request<SuggestMaxPriorityFeePerGasRequest>,
+    ) -> Result<Response<SuggestMaxPriorityFeePerGasResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let uopool = self.get_uopool(&ep)?;
+
+        let max_priority_fee_per_gas =
+            uopool.get_max_priority_fee_per_gas_estimate().await.map_err(|err| {
+                Status::internal(format!("Failed to suggest max priority fee per gas: {err:?}"))
+            })?;
+
+        Ok(Response::new(SuggestMaxPriorityFeePerGasResponse {
+            max_priority_fee_per_gas: Some(max_priority_fee_per_gas.into()),
+        }))
+    }
+
+    async fn get_deposit_info(
+        &self,
+        req: Request<GetDepositInfoRequest>,
+    ) -> Result<Response<GetDepositInfoResponse>, Status> {
+        let req = req.into_inner();
+
+        let ep = parse_addr(req.ep)?;
+        let addr = parse_addr(req.addr)?;
+        let uopool = self.get_uopool(&ep)?;
+
+        let res = uopool
+            .get_deposit_info(&addr)
+            .await
+            .map_err(|e| Status::internal(format!("Get deposit info internal error: {e}")))?;
+        Ok(Response::new(GetDepositInfoResponse {
+            deposit: Some(U256::from(res.deposit).into()),
+            staked: res.staked,
+            stake: Some(U256::from(res.stake).into()),
+            unstake_delay: res.unstake_delay_sec,
+            withdraw_time: res.withdraw_time,
+        }))
+    }
 }
 
 #[allow(clippy::too_many_arguments)]
 pub async fn uopool_service_run<M, SanCk, SimCk, SimTrCk>(
     addr: SocketAddr,
     mode: UoPoolMode,
-    eps: Vec<Address>,
+    eps: Vec<(Address, EntryPointVersion)>,
     eth_client: Arc<M>,
     block_streams: Vec<BlockStream>,
     chain: Chain,
     max_verification_gas: U256,
+    entry_point_overhead: EntryPointOverhead,
     mempool: Mempool,
     reputation: Reputation,
     validator: StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>,
+    min_priority_fee_per_gas: U256,
+    priority_fee_tip_perc: u64,
+    default_sig_size: usize,
+    estimation_binary_search_rounds: u64,
+    max_mempool_age: Duration,
+    max_mempool_size: usize,
+    max_bundle_gas: U256,
+    bundle_selector: BundleSelectorType,
+    max_ops_per_unstaked_sender: usize,
+    max_ops_per_staked_sender: usize,
+    entity_stake_requirements: HashMap<String, StakeRequirements>,
+    max_parallel_validations: usize,
+    reorg_depth_limit: u64,
+    bundle_size_optimizer: Option<BundleSizeOptimizer>,
     p2p_config: Option<Config>,
     enable_metrics: bool,
 ) -> Result<()>
@@ -401,7 +785,7 @@ where
         if let Some(config) = p2p_config {
             let mut mempool_channels: Vec<MempoolChannel> = Vec::new();
 
-            for (ep, block_stream) in eps.into_iter().zip(block_streams.into_iter()) {
+            for ((ep, ep_version), block_stream) in eps.into_iter().zip(block_streams.into_iter()) {
                 let id = mempool_id(&ep, chain.id());
 
                 let (mempool_sender, mempool_receiver) = unbounded::<NetworkMessage>();
@@ -410,15 +794,33 @@ where
                     mode,
                     eth_client.clone(),
                     ep,
+                    ep_version,
                     chain,
                     max_verification_gas,
+                    entry_point_overhead,
                     mempool.clone(),
                     reputation.clone(),
                     validator.clone(),
                     Some(mempool_sender),
-                );
+                )
+                .min_priority_fee_per_gas(min_priority_fee_per_gas)
+                .priority_fee_tip_percent(priority_fee_tip_perc)
+                .default_sig_size(default_sig_size)
+                .estimation_binary_search_rounds(estimation_binary_search_rounds)
+                .max_mempool_age(max_mempool_age)
+                .max_mempool_size(max_mempool_size)
+                .max_bundle_gas(max_bundle_gas)
+                .bundle_selector_type(bundle_selector)
+                .max_ops_per_unstaked_sender(max_ops_per_unstaked_sender)
+                .max_ops_per_staked_sender(max_ops_per_staked_sender)
+                .with_entity_stake_requirements(entity_stake_requirements.clone())
+                .max_parallel_validations(max_parallel_validations)
+                .reorg_depth_limit(reorg_depth_limit)
+                .with_latest_calldata_pricing()
+                .await;
                 uo_builder.register_block_updates(block_stream);
                 uo_builder.register_reputation_updates();
+                uo_builder.register_expiry_sweeps();
 
                 let (network_sender, mut network_receiver) = unbounded::<NetworkMessage>();
                 let mut uo_pool = uo_builder.uopool();
@@ -428,10 +830,13 @@ where
                     while let Some(msg) = network_receiver.next().await {
                         if let NetworkMessage::Validate { user_operation, validation_config } = msg
                         {
-                            let res = uo_pool
-                                .validate_user_operation(&user_operation, Some(validation_config))
-                                .await;
-                            match uo_pool.add_user_operation(user_operation, res).await {
+                            match uo_pool
+                                .validate_and_add_user_operation(
+                                    user_operation,
+                                    Some(validation_config),
+                                )
+                                .await
+                            {
                                 Ok(_) => {}
                                 Err(e) => {
                                     error!("Failed to add user operation: {:?} from p2p", e)
@@ -474,29 +879,50 @@ where
                 }
             });
         } else {
-            for (ep, block_stream) in eps.into_iter().zip(block_streams.into_iter()) {
+            for ((ep, ep_version), block_stream) in eps.into_iter().zip(block_streams.into_iter()) {
                 let id = mempool_id(&ep, chain.id());
                 let uo_builder = UoPoolBuilder::new(
                     mode,
                     eth_client.clone(),
                     ep,
+                    ep_version,
                     chain,
                     max_verification_gas,
+                    entry_point_overhead,
                     mempool.clone(),
                     reputation.clone(),
                     validator.clone(),
                     None,
-                );
+                )
+                .min_priority_fee_per_gas(min_priority_fee_per_gas)
+                .priority_fee_tip_percent(priority_fee_tip_perc)
+                .default_sig_size(default_sig_size)
+                .estimation_binary_search_rounds(estimation_binary_search_rounds)
+                .max_mempool_age(max_mempool_age)
+                .max_mempool_size(max_mempool_size)
+                .max_bundle_gas(max_bundle_gas)
+                .bundle_selector_type(bundle_selector)
+                .max_ops_per_unstaked_sender(max_ops_per_unstaked_sender)
+                .max_ops_per_staked_sender(max_ops_per_staked_sender)
+                .with_entity_stake_requirements(entity_stake_requirements.clone())
+                .max_parallel_validations(max_parallel_validations)
+                .reorg_depth_limit(reorg_depth_limit)
+                .with_latest_calldata_pricing()
+                .await;
                 uo_builder.register_block_updates(block_stream);
                 uo_builder.register_reputation_updates();
+                uo_builder.register_expiry_sweeps();
                 m_map.insert(id, uo_builder);
             }
         };
 
         let uopool_map = Arc::new(RwLock::new(m_map));
-        let svc = uo_pool_server::UoPoolServer::new(
-            UoPoolService::<M, SanCk, SimCk, SimTrCk>::new(uopool_map, chain),
-        );
+        let svc =
+            uo_pool_server::UoPoolServer::new(UoPoolService::<M, SanCk, SimCk, SimTrCk>::new(
+                uopool_map,
+                chain,
+                bundle_size_optimizer,
+            ));
 
         if enable_metrics {
             builder.layer(MetricsLayer).add_service(svc).serve(addr).await
@@ -509,3 +935,65 @@ where
 
     Ok(())
 }
+
+/// Serves a user operation pool over gRPC, for embedding the mempool in other applications
+#[async_trait]
+pub trait UoPoolServe {
+    /// Builds the user operation pool and starts the gRPC server on the given address.
+    ///
+    /// # Arguments
+    /// * `grpc_addr` - Address the gRPC server listens on
+    /// * `block_stream` - Stream of new blocks, block updates are not registered if None
+    ///
+    /// # Returns
+    /// The handle of the server task, it resolves when the server stops.
+    async fn build_and_serve(
+        self,
+        grpc_addr: SocketAddr,
+        block_stream: Option<BlockStream>,
+    ) -> Result<JoinHandle<()>>;
+}
+
+#[async_trait]
+impl<M, SanCk, SimCk, SimTrCk> UoPoolServe for UoPoolBuilder<M, SanCk, SimCk, SimTrCk>
+where
+    M: Middleware + Clone + 'static,
+    SanCk: SanityCheck<M> + Clone + 'static,
+    SimCk: SimulationCheck + Clone + 'static,
+    SimTrCk: SimulationTraceCheck<M> + Clone + 'static,
+{
+    async fn build_and_serve(
+        self,
+        grpc_addr: SocketAddr,
+        block_stream: Option<BlockStream>,
+    ) -> Result<JoinHandle<()>> {
+        let builder = self.with_latest_calldata_pricing().await;
+        if let Some(block_stream) = block_stream {
+            builder.register_block_updates(block_stream);
+        }
+        builder.register_reputation_updates();
+        builder.register_expiry_sweeps();
+
+        let (id, chain) = {
+            let uopool = builder.uopool();
+            (uopool.id, uopool.chain)
+        };
+        let uopool_map = Arc::new(RwLock::new(HashMap::from([(id, builder)])));
+        let svc = uo_pool_server::UoPoolServer::new(
+            UoPoolService::<M, SanCk, SimCk, SimTrCk>::new(uopool_map, chain, None),
+        );
+
+        // bind before spawning, so the port accepts connections once this returns
+        let incoming = TcpIncoming::new(grpc_addr, true, None).map_err(|e| eyre::eyre!(e))?;
+
+        Ok(tokio::spawn(async move {
+            if let Err(e) = tonic::transport::Server::builder()
+                .add_service(svc)
+                .serve_with_incoming(incoming)
+                .await
+            {
+                error!("uopool gRPC server stopped: {e:?}");
+            }
+        }))
+    }
+}
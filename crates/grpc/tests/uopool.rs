@@ -0,0 +1,195 @@
+use alloy_chains::Chain;
+use ethers::{
+    providers::{MockProvider, Provider},
+    types::{Address, Block, H256, U256},
+};
+use parking_lot::RwLock;
+use silius_contracts::EntryPoint;
+use silius_grpc::{uo_pool_client::UoPoolClient, GetEntityRelationshipsRequest, UoPoolServe};
+use silius_mempool::{
+    validate::{validator::new_canonical, UserOperationValidationOutcome},
+    EntryPointOverhead, Mempool, MempoolErrorKind, Reputation, SanityCheck, SimulationCheck,
+    SimulationTraceCheck, UoPoolBuilder,
+};
+use silius_primitives::{
+    constants::validation::reputation::{
+        BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, MIN_UNSTAKE_DELAY, THROTTLING_SLACK,
+    },
+    reputation::ReputationEntry,
+    simulation::CodeHash,
+    EntryPointVersion, UoPoolMode, UserOperation, UserOperationHash, UserOperationReceipt,
+    UserOperationSigned,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    net::{SocketAddr, TcpListener},
+    sync::Arc,
+};
+use tonic::Request;
+
+/// Address with a port that is currently free
+fn free_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+/// Builder of a user operation pool with the in-memory mempool (shared by its user operation
+/// pools) and reputation
+fn builder(
+    provider: Arc<Provider<MockProvider>>,
+    ep: Address,
+    chain: Chain,
+) -> UoPoolBuilder<
+    Provider<MockProvider>,
+    impl SanityCheck<Provider<MockProvider>> + Clone,
+    impl SimulationCheck + Clone,
+    impl SimulationTraceCheck<Provider<MockProvider>> + Clone,
+> {
+    let mempool = Mempool::new(
+        Box::new(Arc::new(RwLock::new(HashMap::<UserOperationHash, UserOperation>::default()))),
+        Box::new(Arc::new(RwLock::new(HashMap::<Address, HashSet<UserOperationHash>>::default()))),
+        Box::new(Arc::new(RwLock::new(HashMap::<Address, HashSet<UserOperationHash>>::default()))),
+        Box::new(Arc::new(RwLock::new(HashMap::<Address, HashSet<UserOperationHash>>::default()))),
+        Box::new(Arc::new(RwLock::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()))),
+        Box::new(Arc::new(RwLock::new(HashMap::<(Address, U256), UserOperationHash>::default()))),
+        Box::new(Arc::new(RwLock::new(
+            HashMap::<UserOperationHash, UserOperationReceipt>::default(),
+        ))),
+    );
+    let reputation = Reputation::new(
+        MIN_INCLUSION_RATE_DENOMINATOR,
+        THROTTLING_SLACK,
+        BAN_SLACK,
+        U256::from(1),
+        MIN_UNSTAKE_DELAY.into(),
+        Arc::new(RwLock::new(HashSet::<Address>::default())),
+        Arc::new(RwLock::new(HashSet::<Address>::default())),
+        Box::new(HashMap::<Address, ReputationEntry>::default()),
+    );
+    UoPoolBuilder::new(
+        UoPoolMode::Standard,
+        provider.clone(),
+        ep,
+        EntryPointVersion::V0_6,
+        chain,
+        U256::from(5000000),
+        EntryPointOverhead::default(),
+        mempool,
+        reputation,
+        new_canonical(
+            EntryPoint::new(provider, ep),
+            chain,
+            U256::from(5000000),
+            U256::from(1),
+            U256::from(100000),
+            3500,
+        ),
+        None,
+    )
+}
+
+#[tokio::test]
+async fn build_and_serve() {
+    let (provider, _) = Provider::<MockProvider>::mocked();
+    let ep = Address::random();
+    let chain = Chain::dev();
+    let builder = builder(Arc::new(provider), ep, chain);
+
+    let addr = free_addr();
+    let handle = builder.build_and_serve(addr, None).await.unwrap();
+
+    let mut client = UoPoolClient::connect(format!("http://{addr}")).await.unwrap();
+    let res = client.get_chain_id(Request::new(())).await.unwrap().into_inner();
+    assert_eq!(res.chain_id, chain.id());
+
+    let res = client.get_supported_entry_points(Request::new(())).await.unwrap().into_inner();
+    assert_eq!(res.eps.len(), 1);
+
+    assert!(!handle.is_finished());
+    handle.abort();
+}
+
+#[tokio::test]
+async fn get_entity_relationships() {
+    let (provider, _) = Provider::<MockProvider>::mocked();
+    let ep = Address::random();
+    let builder = builder(Arc::new(provider), ep, Chain::dev());
+
+    let (sender, paymaster) = (Address::random(), Address::random());
+    let uo = UserOperation::from_user_operation_signed(
+        H256::random().into(),
+        UserOperationSigned::default()
+            .sender(sender)
+            .paymaster_and_data(paymaster.as_bytes().to_vec().into()),
+    );
+    builder.uopool().mempool.add(uo).unwrap();
+
+    let addr = free_addr();
+    let handle = builder.build_and_serve(addr, None).await.unwrap();
+    let mut client = UoPoolClient::connect(format!("http://{addr}")).await.unwrap();
+
+    let req = |addr: Address| {
+        Request::new(GetEntityRelationshipsRequest { ep: Some(ep.into()), addr: Some(addr.into()) })
+    };
+    let res = client.get_entity_relationships(req(sender)).await.unwrap().into_inner();
+    assert_eq!(res.entities.into_iter().map(Into::into).collect::<Vec<Address>>(), vec![paymaster]);
+    assert!(res.senders.is_empty());
+
+    let res = client.get_entity_relationships(req(paymaster)).await.unwrap().into_inner();
+    assert!(res.entities.is_empty());
+    assert_eq!(res.senders.into_iter().map(Into::into).collect::<Vec<Address>>(), vec![sender]);
+
+    handle.abort();
+}
+
+#[tokio::test]
+async fn included_user_operation_counted_once() {
+    let (provider, _) = Provider::<MockProvider>::mocked();
+    let builder = builder(Arc::new(provider), Address::random(), Chain::dev());
+    let mut uopool = builder.uopool();
+
+    let paymaster = Address::random();
+    let uo = UserOperation::from_user_operation_signed(
+        H256::random().into(),
+        UserOperationSigned::default()
+            .sender(Address::random())
+            .paymaster_and_data(paymaster.as_bytes().to_vec().into()),
+    );
+    uopool.mempool.add(uo.clone()).unwrap();
+
+    // the inclusion is reported by the block updates and the bundler
+    uopool.remove_user_operations(vec![uo.clone()]);
+    uopool.remove_user_operations(vec![uo.clone()]);
+
+    assert_eq!(uopool.reputation.get(&uo.sender).unwrap().uo_included, 1);
+    assert_eq!(uopool.reputation.get(&paymaster).unwrap().uo_included, 1);
+}
+
+#[tokio::test]
+async fn replacement_of_removed_user_operation_makes_room() {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    let builder = builder(Arc::new(provider), Address::random(), Chain::dev()).max_mempool_size(1);
+    let mut uopool = builder.uopool();
+
+    let uo = |fee: u64| {
+        UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::default()
+                .sender(Address::random())
+                .max_fee_per_gas(fee.into())
+                .max_priority_fee_per_gas(fee.into()),
+        )
+    };
+    uopool.mempool.add(uo(10)).unwrap();
+
+    // the replaced user operation left the mempool during the validation, so the user operation
+    // has to make room like a new one
+    mock.push(Block::<H256> { base_fee_per_gas: Some(U256::one()), ..Default::default() }).unwrap();
+    let res = UserOperationValidationOutcome {
+        prev_hash: Some(H256::random().into()),
+        ..Default::default()
+    };
+    let err = uopool.add_user_operation(uo(5), Ok(res)).await.unwrap_err();
+
+    assert!(matches!(err.kind, MempoolErrorKind::MempoolFull));
+    assert_eq!(uopool.mempool.len().unwrap(), 1);
+}
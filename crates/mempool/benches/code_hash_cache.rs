@@ -0,0 +1,65 @@
+//! Validation of 100 user operations that visit the same sender and paymaster contracts, with
+//! code hashes fetched for every user operation (200 `eth_getCode` calls) and cached for the
+//! block (2 calls).
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use ethers::{
+    providers::{MockProvider, Provider},
+    types::{Address, Bytes},
+};
+use silius_mempool::validate::{
+    simulation::code_hash_cache::CodeHashCache, simulation_trace::code_hashes::CodeHashes,
+};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+
+const USER_OPERATIONS: usize = 100;
+
+fn provider(responses: usize) -> Arc<Provider<MockProvider>> {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    for _ in 0..responses {
+        mock.push::<Bytes, _>(Bytes::from(vec![0x60, 0x80])).unwrap();
+    }
+    Arc::new(provider)
+}
+
+async fn validate(eth_client: Arc<Provider<MockProvider>>, addrs: &[Address], cached: bool) {
+    let cache = CodeHashCache::default();
+    for _ in 0..USER_OPERATIONS {
+        let fresh = CodeHashCache::default();
+        CodeHashes
+            .get_code_hashes(
+                addrs.to_vec(),
+                &mut vec![],
+                &eth_client,
+                if cached { &cache } else { &fresh },
+            )
+            .await
+            .unwrap();
+    }
+}
+
+fn code_hash_cache(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+    let addrs = [Address::random(), Address::random()];
+
+    let mut group = c.benchmark_group("code_hash_cache");
+    group.bench_function("uncached", |b| {
+        b.iter_batched(
+            || provider(USER_OPERATIONS * addrs.len()),
+            |eth_client| rt.block_on(validate(eth_client, &addrs, false)),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("cached", |b| {
+        b.iter_batched(
+            || provider(addrs.len()),
+            |eth_client| rt.block_on(validate(eth_client, &addrs, true)),
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, code_hash_cache);
+criterion_main!(benches);
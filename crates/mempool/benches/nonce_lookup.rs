@@ -0,0 +1,73 @@
+//! Compares the lookup of a user operation by sender and nonce through the sender index (scan
+//! over all user operations of the sender) with the lookup through the nonce index.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ethers::types::{Address, U256};
+use silius_mempool::{
+    init_env, CodeHashes, DatabaseTable, Mempool, UserOperationReceipts, UserOperations,
+    UserOperationsByAggregator, UserOperationsByEntity, UserOperationsByNonce,
+    UserOperationsBySender, WriteMap,
+};
+use silius_primitives::{UserOperation, UserOperationSigned};
+use std::sync::Arc;
+use tempfile::TempDir;
+
+const USER_OPERATIONS: usize = 10_000;
+const SENDERS: usize = 100;
+
+fn mempool(dir: &TempDir) -> (Mempool, Vec<Address>) {
+    let env = init_env::<WriteMap>(dir.path().to_path_buf()).expect("Init mdbx failed");
+    env.create_tables().expect("Create mdbx database tables failed");
+    let env = Arc::new(env);
+    let mut mempool = Mempool::new(
+        Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env)),
+    );
+
+    let ep = Address::random();
+    let senders = (0..SENDERS).map(|_| Address::random()).collect::<Vec<_>>();
+    for i in 0..USER_OPERATIONS {
+        let uo = UserOperationSigned {
+            sender: senders[i % SENDERS],
+            nonce: U256::from(i / SENDERS),
+            ..UserOperationSigned::random()
+        };
+        let uo_hash = uo.hash(&ep, 5);
+        mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo)).unwrap();
+    }
+
+    (mempool, senders)
+}
+
+fn nonce_lookup(c: &mut Criterion) {
+    let dir = TempDir::new().unwrap();
+    let (mempool, senders) = mempool(&dir);
+    let nonce = U256::from(USER_OPERATIONS / SENDERS / 2);
+
+    let mut group = c.benchmark_group("nonce_lookup");
+    group.bench_function("sender_scan", |b| {
+        b.iter(|| {
+            for sender in senders.iter() {
+                black_box(
+                    mempool.get_all_by_sender(sender).into_iter().find(|uo| uo.nonce == nonce),
+                );
+            }
+        })
+    });
+    group.bench_function("nonce_index", |b| {
+        b.iter(|| {
+            for sender in senders.iter() {
+                black_box(mempool.get_by_sender_and_nonce(sender, nonce));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, nonce_lookup);
+criterion_main!(benches);
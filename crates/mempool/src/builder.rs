@@ -1,23 +1,60 @@
 use crate::{
+    bundle_selector::{new_bundle_selector, BundleSelector, GreedyBundleSelector},
+    utils::{CalldataPricing, ChainPreVerificationGas, EntryPointOverhead},
     validate::{
         validator::StandardUserOperationValidator, SanityCheck, SimulationCheck,
         SimulationTraceCheck,
     },
-    Mempool, Reputation, UoPool,
+    Mempool, ReplacementPolicy, Reputation, StakeRequirements, UoPool,
 };
 use alloy_chains::Chain;
 use ethers::{
+    abi::{decode, ParamType, Token},
     providers::Middleware,
-    types::{Address, H256, U256},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Block, BlockNumber, Bytes, Transaction,
+        TransactionRequest, H160, H256, U256,
+    },
+    utils::id,
 };
+use eyre::format_err;
 use futures::channel::mpsc::UnboundedSender;
 use futures_util::StreamExt;
-use silius_contracts::EntryPoint;
+use parking_lot::RwLock;
+use silius_contracts::{utils::parse_from_input_data, EntryPoint};
 use silius_primitives::{
-    p2p::NetworkMessage, provider::BlockStream, UoPoolMode, UserOperation, UserOperationSigned,
+    constants::{
+        bundler::MULTICALL3_ADDRESS,
+        mempool::{
+            DEFAULT_SIGNATURE_SIZE, ESTIMATION_BINARY_SEARCH_ROUNDS, MAX_BUNDLE_GAS,
+            MAX_MEMPOOL_AGE, MAX_MEMPOOL_SIZE, MAX_OPS_PER_STAKED_SENDER,
+            MAX_OPS_PER_UNSTAKED_SENDER, MAX_PARALLEL_VALIDATIONS, MEMPOOL_EXPIRY_INTERVAL,
+            PRIORITY_FEE_TIP_PERC, REORG_DEPTH_LIMIT, USER_OPERATION_EVENTS_CAPACITY,
+        },
+    },
+    p2p::NetworkMessage,
+    provider::BlockStream,
+    BundleSelectorType, EntryPointVersion, UoPoolMode, UserOperation, UserOperationEvent,
 };
-use std::{sync::Arc, time::Duration};
-use tracing::warn;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{sync::broadcast, try_join};
+use tracing::{info, warn};
+
+/// Address of the OP Stack `GasPriceOracle` predeploy
+const OP_GAS_PRICE_ORACLE: Address = H160([
+    0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x0f,
+]);
+
+/// Address of the Arbitrum `ArbGasInfo` precompile
+const ARB_GAS_INFO: Address = H160([
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x6c,
+]);
 
 type StandardUoPool<M, SanCk, SimCk, SimTrCk> =
     UoPool<M, StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>>;
@@ -32,11 +69,43 @@ where
     mode: UoPoolMode,
     eth_client: Arc<M>,
     entrypoint: Address,
+    entry_point_version: EntryPointVersion,
     chain: Chain,
     max_verification_gas: U256,
+    entry_point_overhead: EntryPointOverhead,
     mempool: Mempool,
     reputation: Reputation,
     validator: StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>,
+    // Calldata pricing detected from the latest block
+    calldata_pricing: Arc<RwLock<CalldataPricing>>,
+    // L1 calldata pricing of the chain (the OP Stack L1 gas price is updated on new blocks)
+    chain_pre_verification_gas: Arc<RwLock<ChainPreVerificationGas>>,
+    // Lowest max priority fee per gas suggested to wallets
+    min_priority_fee_per_gas: U256,
+    // Percentage added on top of the suggested max priority fee per gas
+    priority_fee_tip_perc: U256,
+    // Minimum size of the dummy signature the pre-verification gas is estimated with
+    default_sig_size: usize,
+    // Maximum number of rounds of the verification gas limit binary search
+    estimation_binary_search_rounds: u64,
+    // Time a user operation can stay in the mempool before it's evicted
+    max_mempool_age: Duration,
+    // Maximum number of user operations in the mempool
+    max_mempool_size: usize,
+    // Maximum total gas of a bundle
+    max_bundle_gas: U256,
+    // Maximum number of user operations of an unstaked sender in the mempool
+    max_ops_per_unstaked_sender: usize,
+    // Maximum number of user operations of a staked sender in the mempool
+    max_ops_per_staked_sender: usize,
+    // Maximum number of user operations of a batch validated concurrently
+    max_parallel_validations: usize,
+    // Strategy picking the bundle candidates out of the mempool
+    bundle_selector: Box<dyn BundleSelector>,
+    // Maximum number of blocks a reorg is followed back
+    reorg_depth_limit: u64,
+    // Channel the changes of the mempool are broadcast to (shared by all the uopool instances)
+    events: broadcast::Sender<UserOperationEvent>,
     // Channel to publish to p2p network (None if not enabled)
     network: Option<UnboundedSender<NetworkMessage>>,
 }
@@ -53,51 +122,475 @@ where
         mode: UoPoolMode,
         eth_client: Arc<M>,
         entrypoint: Address,
+        entry_point_version: EntryPointVersion,
         chain: Chain,
         max_verification_gas: U256,
+        entry_point_overhead: EntryPointOverhead,
         mempool: Mempool,
         reputation: Reputation,
         validator: StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>,
         network: Option<UnboundedSender<NetworkMessage>>,
     ) -> Self {
+        // the sanity checks of the validator see the L1 prices updated by the mempool
+        let chain_pre_verification_gas = validator.chain_pre_verification_gas();
+        // the validator simulates against the entry point of this mempool
+        let validator = validator.with_entry_point(
+            EntryPoint::new(eth_client.clone(), entrypoint).with_version(entry_point_version),
+        );
+
         Self {
             mode,
             eth_client,
             entrypoint,
+            entry_point_version,
             chain,
             max_verification_gas,
+            entry_point_overhead,
             mempool,
             reputation,
             validator,
+            calldata_pricing: Arc::new(RwLock::new(CalldataPricing::default())),
+            chain_pre_verification_gas,
+            min_priority_fee_per_gas: U256::zero(),
+            priority_fee_tip_perc: PRIORITY_FEE_TIP_PERC.into(),
+            default_sig_size: DEFAULT_SIGNATURE_SIZE,
+            estimation_binary_search_rounds: ESTIMATION_BINARY_SEARCH_ROUNDS,
+            max_mempool_age: Duration::from_secs(MAX_MEMPOOL_AGE),
+            max_mempool_size: MAX_MEMPOOL_SIZE,
+            max_bundle_gas: MAX_BUNDLE_GAS.into(),
+            max_ops_per_unstaked_sender: MAX_OPS_PER_UNSTAKED_SENDER,
+            max_ops_per_staked_sender: MAX_OPS_PER_STAKED_SENDER,
+            max_parallel_validations: MAX_PARALLEL_VALIDATIONS,
+            bundle_selector: Box::new(GreedyBundleSelector),
+            reorg_depth_limit: REORG_DEPTH_LIMIT,
+            events: broadcast::channel(USER_OPERATION_EVENTS_CAPACITY).0,
             network,
         }
     }
 
+    /// Subscribes to the changes of the mempool (user operations added, included or dropped).
+    pub fn subscribe(&self) -> broadcast::Receiver<UserOperationEvent> {
+        self.events.subscribe()
+    }
+
+    /// Sets the calldata pricing the pre-verification gas is priced with until the next block
+    /// update (default legacy).
+    pub fn calldata_pricing(self, calldata_pricing: CalldataPricing) -> Self {
+        *self.calldata_pricing.write() = calldata_pricing;
+        self
+    }
+
+    /// Sets the calldata pricing of the latest block, so the pre-verification gas is priced right
+    /// before the first block update (stays legacy if the block can't be fetched).
+    pub async fn with_latest_calldata_pricing(self) -> Self {
+        match self.eth_client.get_block(BlockNumber::Latest).await {
+            Ok(Some(block)) => {
+                let calldata_pricing = CalldataPricing::from_block(&block);
+                info!("Using {calldata_pricing:?} calldata pricing for pre-verification gas");
+                self.calldata_pricing(calldata_pricing)
+            }
+            Ok(None) => self,
+            Err(e) => {
+                warn!("Failed to get the latest block for the calldata pricing: {e:?}");
+                self
+            }
+        }
+    }
+
+    /// Sets the minimum fee increase (in percent) for replacing a user operation (default 10%).
+    pub fn min_fee_bump_percent(mut self, min_fee_bump_percent: u8) -> Self {
+        self.validator =
+            self.validator.with_replacement_policy(ReplacementPolicy::new(min_fee_bump_percent));
+        self
+    }
+
+    /// Sets the lowest max priority fee per gas suggested to wallets (default 0).
+    pub fn min_priority_fee_per_gas(mut self, min_priority_fee_per_gas: U256) -> Self {
+        self.min_priority_fee_per_gas = min_priority_fee_per_gas;
+        self
+    }
+
+    /// Sets the percentage added on top of the suggested max priority fee per gas (default 10%).
+    pub fn priority_fee_tip_percent(mut self, priority_fee_tip_perc: u64) -> Self {
+        self.priority_fee_tip_perc = priority_fee_tip_perc.into();
+        self
+    }
+
+    /// Sets the minimum size of the dummy signature the pre-verification gas is estimated with
+    /// (default 65 bytes, the size of an ECDSA signature).
+    pub fn default_sig_size(mut self, default_sig_size: usize) -> Self {
+        self.default_sig_size = default_sig_size;
+        self
+    }
+
+    /// Sets the maximum number of rounds of the verification gas limit binary search (default 16).
+    pub fn estimation_binary_search_rounds(mut self, estimation_binary_search_rounds: u64) -> Self {
+        self.estimation_binary_search_rounds = estimation_binary_search_rounds;
+        self
+    }
+
+    /// Sets the time a user operation can stay in the mempool before it's evicted (default 1
+    /// hour).
+    pub fn max_mempool_age(mut self, max_mempool_age: Duration) -> Self {
+        self.max_mempool_age = max_mempool_age;
+        self
+    }
+
+    /// Sets the maximum number of user operations in the mempool (default 10 000).
+    pub fn max_mempool_size(mut self, max_mempool_size: usize) -> Self {
+        self.max_mempool_size = max_mempool_size;
+        self
+    }
+
+    /// Sets the maximum number of user operations of an unstaked sender in the mempool (default
+    /// 1).
+    pub fn max_ops_per_unstaked_sender(mut self, max_ops_per_unstaked_sender: usize) -> Self {
+        self.max_ops_per_unstaked_sender = max_ops_per_unstaked_sender;
+        self
+    }
+
+    /// Sets the maximum number of user operations of a staked sender in the mempool (default 4).
+    pub fn max_ops_per_staked_sender(mut self, max_ops_per_staked_sender: usize) -> Self {
+        self.max_ops_per_staked_sender = max_ops_per_staked_sender;
+        self
+    }
+
+    /// Sets the maximum number of user operations of a batch validated concurrently (default 8).
+    pub fn max_parallel_validations(mut self, max_parallel_validations: usize) -> Self {
+        self.max_parallel_validations = max_parallel_validations;
+        self
+    }
+
+    /// Sets the minimum stake and unstake delay of the entity types (`account`, `factory`,
+    /// `paymaster` or `aggregator`) that differ from the default ones of the reputation.
+    pub fn with_entity_stake_requirements(
+        mut self,
+        requirements: HashMap<String, StakeRequirements>,
+    ) -> Self {
+        self.reputation = self.reputation.with_entity_stake_requirements(requirements);
+        self
+    }
+
+    /// Sets the strategy picking the bundle candidates out of the mempool (default
+    /// [GreedyBundleSelector]).
+    pub fn with_bundle_selector(mut self, bundle_selector: Box<dyn BundleSelector>) -> Self {
+        self.bundle_selector = bundle_selector;
+        self
+    }
+
+    /// Sets the strategy picking the bundle candidates out of the mempool by its type (the
+    /// simulation selector simulates against the entry point of the mempool).
+    pub fn bundle_selector_type(self, selector_type: BundleSelectorType) -> Self {
+        let entry_point = EntryPoint::<M>::new(self.eth_client.clone(), self.entrypoint)
+            .with_version(self.entry_point_version);
+        self.with_bundle_selector(new_bundle_selector(selector_type, entry_point))
+    }
+
+    /// Sets how the L1 calldata is priced in the pre-verification gas (default selected by the
+    /// chain).
+    pub fn chain_pre_verification_gas(
+        self,
+        chain_pre_verification_gas: ChainPreVerificationGas,
+    ) -> Self {
+        *self.chain_pre_verification_gas.write() = chain_pre_verification_gas;
+        self
+    }
+
+    /// Sets the maximum total gas of a bundle (default 10 000 000).
+    pub fn max_bundle_gas(mut self, max_bundle_gas: U256) -> Self {
+        self.max_bundle_gas = max_bundle_gas;
+        self
+    }
+
+    /// Stores the mempool and the reputation entries in Redis instead of the ones passed to
+    /// [new](Self::new), so the bundler instances connected to the same server share them. The
+    /// keys are prefixed with the mempool ID.
+    #[cfg(feature = "redis")]
+    pub fn with_redis_backend(mut self, client: redis::Client) -> eyre::Result<Self> {
+        use crate::{
+            mempool_id,
+            redis::{tables, RedisEnv, RedisTable},
+        };
+
+        let id = mempool_id(&self.entrypoint, self.chain.id());
+        let env = Arc::new(RedisEnv::connect(client, &format!("{id:?}"))?);
+
+        self.mempool = Mempool::new(
+            Box::new(RedisTable::<tables::UserOperations>::new(env.clone())),
+            Box::new(RedisTable::<tables::UserOperationsBySender>::new(env.clone())),
+            Box::new(RedisTable::<tables::UserOperationsByEntity>::new(env.clone())),
+            Box::new(RedisTable::<tables::UserOperationsByAggregator>::new(env.clone())),
+            Box::new(RedisTable::<tables::CodeHashes>::new(env.clone())),
+            Box::new(RedisTable::<tables::UserOperationsByNonce>::new(env.clone())),
+            Box::new(RedisTable::<tables::UserOperationReceipts>::new(env.clone())),
+        )
+        .with_shared_storage();
+        self.reputation = self
+            .reputation
+            .with_entities(Box::new(RedisTable::<tables::EntitiesReputation>::new(env)));
+        Ok(self)
+    }
+
+    /// Sets the maximum number of blocks a chain reorganization is followed back (default 64).
+    pub fn reorg_depth_limit(mut self, reorg_depth_limit: u64) -> Self {
+        self.reorg_depth_limit = reorg_depth_limit;
+        self
+    }
+
+    /// Reads the `index`-th word returned by a view function of an L2 system contract
+    async fn call_system_contract(
+        eth_client: &M,
+        to: Address,
+        signature: &str,
+        index: usize,
+    ) -> eyre::Result<U256> {
+        let tx: TypedTransaction =
+            TransactionRequest::new().to(to).data(id(signature).to_vec()).into();
+        let res = eth_client.call(&tx, None).await?;
+        let word = res
+            .get(index * 32..(index + 1) * 32)
+            .ok_or(format_err!("Unexpected return data of {signature}"))?;
+        Ok(U256::from_big_endian(word))
+    }
+
+    /// Reads the current L1 prices of the chain from its system contracts
+    async fn l1_prices(
+        eth_client: &M,
+        chain_pre_verification_gas: ChainPreVerificationGas,
+    ) -> eyre::Result<ChainPreVerificationGas> {
+        Ok(match chain_pre_verification_gas {
+            ChainPreVerificationGas::OptimismStack { .. } => {
+                let oracle = |signature: &'static str| {
+                    Self::call_system_contract(eth_client, OP_GAS_PRICE_ORACLE, signature, 0)
+                };
+                let (l1_base_fee, base_fee_scalar, blob_base_fee, blob_base_fee_scalar) = try_join!(
+                    oracle("l1BaseFee()"),
+                    oracle("baseFeeScalar()"),
+                    oracle("blobBaseFee()"),
+                    oracle("blobBaseFeeScalar()"),
+                )?;
+                ChainPreVerificationGas::OptimismStack {
+                    l1_base_fee,
+                    base_fee_scalar,
+                    blob_base_fee,
+                    blob_base_fee_scalar,
+                }
+            }
+            // `perL1CalldataByte` is the second price returned by `getPricesInWei()`
+            ChainPreVerificationGas::Arbitrum { .. } => ChainPreVerificationGas::Arbitrum {
+                l1_price_per_byte: Self::call_system_contract(
+                    eth_client,
+                    ARB_GAS_INFO,
+                    "getPricesInWei()",
+                    1,
+                )
+                .await?,
+            },
+            mainnet @ ChainPreVerificationGas::Mainnet { .. } => mainnet,
+        })
+    }
+
+    /// Decodes the call data of the Multicall3 `aggregate3` transaction into the calls sent to
+    /// the entry point
+    fn aggregate3_calls(input: &Bytes, ep: &Address) -> Vec<Bytes> {
+        let selector = id("aggregate3((address,bool,bytes)[])");
+        if input.len() < 4 || input[..4] != selector {
+            return vec![];
+        }
+
+        let calls = decode(
+            &[ParamType::Array(Box::new(ParamType::Tuple(vec![
+                ParamType::Address,
+                ParamType::Bool,
+                ParamType::Bytes,
+            ])))],
+            &input[4..],
+        )
+        .ok()
+        .and_then(|tokens| tokens.into_iter().next())
+        .and_then(Token::into_array)
+        .unwrap_or_default();
+
+        calls
+            .into_iter()
+            .filter_map(|call| {
+                let mut call = call.into_tuple()?.into_iter();
+                let target = call.next()?.into_address()?;
+                let call_data = call.nth(1)?.into_bytes()?;
+                (target == *ep).then(|| call_data.into())
+            })
+            .collect()
+    }
+
+    /// Decodes the user operations of the `handleOps` and `handleAggregatedOps` transactions sent
+    /// to the entry point, either directly or through Multicall3
+    fn included_user_operations(
+        block: &Block<Transaction>,
+        uopool: &StandardUoPool<M, SanCk, SimCk, SimTrCk>,
+    ) -> Vec<UserOperation> {
+        let ep = uopool.entry_point.address();
+        let version = uopool.entry_point.version();
+        let multicall: Address = MULTICALL3_ADDRESS.parse().expect("Multicall3 address is valid");
+        let mut included = vec![];
+
+        for tx in block.transactions.iter() {
+            let calls = if tx.to == Some(ep) {
+                vec![tx.input.clone()]
+            } else if tx.to == Some(multicall) {
+                Self::aggregate3_calls(&tx.input, &ep)
+            } else {
+                continue;
+            };
+
+            for uos in calls.into_iter().filter_map(parse_from_input_data) {
+                included.extend(uos.into_iter().map(|uo| {
+                    UserOperation::from_user_operation_signed(
+                        uo.hash_for(&ep, uopool.chain.id(), version),
+                        uo,
+                    )
+                }));
+            }
+        }
+
+        included
+    }
+
+    /// Detects a chain reorganization (the parent of the new block isn't the previous head) and
+    /// reinstates the user operations included in the reorged out blocks, while removing the
+    /// ones included in the blocks that replaced them.
+    ///
+    /// # Arguments
+    /// * `block` - The new head block
+    /// * `uopool` - The uopool the block is handled for
+    /// * `recent_blocks` - Numbers and hashes of the recently handled blocks (oldest first),
+    ///   updated to the new chain
+    ///
+    /// # Returns
+    /// * `eyre::Result<HashSet<Address>>` - The senders of the reinstated user operations
+    async fn handle_reorg(
+        block: &Block<Transaction>,
+        uopool: &mut StandardUoPool<M, SanCk, SimCk, SimTrCk>,
+        recent_blocks: &mut VecDeque<(u64, H256)>,
+    ) -> eyre::Result<HashSet<Address>> {
+        let number = block.number.map(|n| n.as_u64()).unwrap_or_default();
+        let eth_client = uopool.entry_point.eth_client();
+
+        // walk back the handled blocks until the one that is still canonical
+        let mut reorged = vec![];
+        let mut replacements = vec![];
+        while let Some(&(n, hash)) = recent_blocks.back() {
+            let canonical = if n > number {
+                None
+            } else if n == number {
+                block.hash
+            } else if n + 1 == number {
+                Some(block.parent_hash)
+            } else {
+                eth_client.get_block(n).await?.and_then(|b| b.hash)
+            };
+
+            if canonical == Some(hash) {
+                break;
+            }
+
+            reorged.push(hash);
+            // the new block itself is handled by the caller
+            if n < number {
+                replacements.extend(canonical);
+            }
+            recent_blocks.pop_back();
+        }
+
+        let mut senders = HashSet::new();
+        if reorged.is_empty() {
+            return Ok(senders);
+        }
+
+        warn!(
+            "Chain reorganization detected at block {number}, {} blocks reorged out",
+            reorged.len()
+        );
+
+        for hash in reorged {
+            match eth_client.get_block_with_txs(hash).await? {
+                Some(block) => {
+                    let uos = Self::included_user_operations(&block, uopool);
+                    senders.extend(uos.iter().map(|uo| uo.sender));
+                    uopool.reinstate_user_operations(uos);
+                }
+                None => warn!("Reorged out block {hash:?} is no longer available"),
+            }
+        }
+
+        for hash in replacements {
+            if let Some(block) = eth_client.get_block_with_txs(hash).await? {
+                uopool.remove_user_operations(Self::included_user_operations(&block, uopool));
+                // the rest of the reorg still has to be handled
+                if let Err(e) = uopool.store_user_operation_receipts(hash).await {
+                    warn!("Failed to store user operation receipts of block {hash:?}: {e:?}");
+                }
+            }
+        }
+
+        Ok(senders)
+    }
+
     async fn handle_block_update(
         hash: H256,
         uopool: &mut StandardUoPool<M, SanCk, SimCk, SimTrCk>,
+        recent_blocks: &mut VecDeque<(u64, H256)>,
+        reorg_depth_limit: u64,
     ) -> eyre::Result<()> {
-        let txs =
-            uopool.entry_point.eth_client().get_block_with_txs(hash).await?.map(|b| b.transactions);
-
-        if let Some(txs) = txs {
-            for tx in txs {
-                if tx.to == Some(uopool.entry_point.address()) {
-                    let dec: Result<(Vec<UserOperationSigned>, Address), _> =
-                        uopool.entry_point.entry_point_api().decode("handleOps", tx.input);
-
-                    if let Ok((uos, _)) = dec {
-                        uopool.remove_user_operations(
-                            uos.iter()
-                                .map(|uo| {
-                                    UserOperation::from_user_operation_signed(
-                                        uo.hash(&uopool.entry_point.address(), uopool.chain.id()),
-                                        uo.clone(),
-                                    )
-                                })
-                                .collect(),
-                        );
-                    }
+        let block = uopool.entry_point.eth_client().get_block_with_txs(hash).await?;
+
+        if let Some(block) = block {
+            if let Some(number) = block.number {
+                uopool.validator.code_hash_cache().invalidate_for_block(number.as_u64());
+                uopool.validator.simulation_cache().invalidate_for_block(number.as_u64());
+            }
+
+            let calldata_pricing = CalldataPricing::from_block(&block);
+            if *uopool.calldata_pricing.read() != calldata_pricing {
+                info!("Using {calldata_pricing:?} calldata pricing for pre-verification gas");
+                *uopool.calldata_pricing.write() = calldata_pricing;
+            }
+
+            let chain_pre_verification_gas = *uopool.chain_pre_verification_gas.read();
+            if !matches!(chain_pre_verification_gas, ChainPreVerificationGas::Mainnet { .. }) {
+                match Self::l1_prices(&uopool.entry_point.eth_client(), chain_pre_verification_gas)
+                    .await
+                {
+                    Ok(l1_prices) => *uopool.chain_pre_verification_gas.write() = l1_prices,
+                    Err(e) => warn!("Failed to get the L1 prices: {:?}", e),
+                }
+            }
+
+            let senders =
+                Self::handle_reorg(&block, uopool, recent_blocks).await.unwrap_or_else(|e| {
+                    warn!("Failed to handle chain reorganization: {:?}", e);
+                    HashSet::new()
+                });
+
+            uopool.remove_user_operations(Self::included_user_operations(&block, uopool));
+            let _ = uopool
+                .store_user_operation_receipts(hash)
+                .await
+                .map_err(|e| warn!("Failed to store user operation receipts: {:?}", e));
+
+            if !senders.is_empty() {
+                let evicted = uopool.remove_stale_user_operations(&senders).await?;
+                if evicted > 0 {
+                    info!("Evicted {evicted} user operations invalidated by the reorg");
+                }
+            }
+
+            if let Some(number) = block.number.map(|n| n.as_u64()) {
+                // the same block may be received again
+                if recent_blocks.back() != Some(&(number, hash)) {
+                    recent_blocks.push_back((number, hash));
+                }
+                while recent_blocks.len() as u64 > reorg_depth_limit {
+                    recent_blocks.pop_front();
                 }
             }
         }
@@ -108,13 +601,20 @@ where
     pub fn register_block_updates(&self, mut block_stream: BlockStream) {
         let mut uopool = self.uopool();
         let network = self.network.clone();
+        let reorg_depth_limit = self.reorg_depth_limit;
         tokio::spawn(async move {
+            let mut recent_blocks = VecDeque::new();
             while let Some(hash) = block_stream.next().await {
                 if let Ok(hash) = hash {
                     let h: H256 = hash;
-                    let _ = Self::handle_block_update(h, &mut uopool)
-                        .await
-                        .map_err(|e| warn!("Failed to handle block update: {:?}", e));
+                    let _ = Self::handle_block_update(
+                        h,
+                        &mut uopool,
+                        &mut recent_blocks,
+                        reorg_depth_limit,
+                    )
+                    .await
+                    .map_err(|e| warn!("Failed to handle block update: {:?}", e));
 
                     // update p2p latest block info
                     if let Some(ref network) = network {
@@ -150,18 +650,49 @@ where
         });
     }
 
+    pub fn register_expiry_sweeps(&self) {
+        let mut uopool = self.uopool();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(Duration::from_secs(MEMPOOL_EXPIRY_INTERVAL)).await;
+                let evicted = uopool.remove_expired_user_operations();
+                if evicted > 0 {
+                    info!("Evicted {evicted} expired user operations from the mempool");
+                }
+            }
+        });
+    }
+
     pub fn uopool(&self) -> StandardUoPool<M, SanCk, SimCk, SimTrCk> {
-        let entry_point = EntryPoint::<M>::new(self.eth_client.clone(), self.entrypoint);
+        let entry_point = EntryPoint::<M>::new(self.eth_client.clone(), self.entrypoint)
+            .with_version(self.entry_point_version);
 
-        UoPool::<M, StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>>::new(
+        let mut uopool = UoPool::<M, StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>>::new(
             self.mode,
             entry_point,
             self.validator.clone(),
             self.mempool.clone(),
             self.reputation.clone(),
             self.max_verification_gas,
+            self.entry_point_overhead,
             self.chain,
             self.network.as_ref().cloned(),
-        )
+        );
+        uopool.calldata_pricing = self.calldata_pricing.clone();
+        uopool.chain_pre_verification_gas = self.chain_pre_verification_gas.clone();
+        uopool.replacement_policy = self.validator.replacement_policy();
+        uopool.min_priority_fee_per_gas = self.min_priority_fee_per_gas;
+        uopool.priority_fee_tip_perc = self.priority_fee_tip_perc;
+        uopool.default_sig_size = self.default_sig_size;
+        uopool.estimation_binary_search_rounds = self.estimation_binary_search_rounds;
+        uopool.max_mempool_age = self.max_mempool_age;
+        uopool.max_mempool_size = self.max_mempool_size;
+        uopool.max_bundle_gas = self.max_bundle_gas;
+        uopool.max_ops_per_unstaked_sender = self.max_ops_per_unstaked_sender;
+        uopool.max_ops_per_staked_sender = self.max_ops_per_staked_sender;
+        uopool.max_parallel_validations = self.max_parallel_validations;
+        uopool.bundle_selector = self.bundle_selector.clone();
+        uopool.events = self.events.clone();
+        uopool
     }
 }
@@ -0,0 +1,352 @@
+use crate::utils::{effective_gas_price, sort_by_effective_gas_price};
+use dyn_clone::DynClone;
+use ethers::{
+    providers::Middleware,
+    types::{Address, U256},
+};
+use silius_contracts::EntryPoint;
+use silius_primitives::{BundleSelectorType, UserOperation};
+use std::collections::HashMap;
+use tracing::debug;
+
+/// Number of gas buckets the [KnapsackSelector] splits the gas budget into
+pub const KNAPSACK_GAS_BUCKETS: u64 = 1000;
+
+/// Gas the bundle selection accounts for a user operation (the same as the bundle gas limit)
+fn gas_of(uo: &UserOperation) -> U256 {
+    uo.verification_gas_limit
+        .saturating_add(uo.call_gas_limit)
+        .saturating_add(uo.pre_verification_gas)
+}
+
+/// Picks and orders the bundle candidates out of the user operations in the mempool, before
+/// they are validated for the second time and packed into a bundle by
+/// [UoPool::bundle_user_operations](crate::UoPool::bundle_user_operations).
+#[async_trait::async_trait]
+pub trait BundleSelector: Send + Sync + DynClone {
+    /// Selects the bundle candidates
+    ///
+    /// # Arguments
+    /// * `uos` - The user operations in the mempool, sorted by the effective gas price
+    /// * `max_gas` - The gas budget of the bundle
+    /// * `base_fee` - Base fee per gas of the latest block
+    ///
+    /// # Returns
+    /// The candidates in the order they should be bundled
+    async fn select(
+        &self,
+        uos: Vec<UserOperation>,
+        max_gas: U256,
+        base_fee: U256,
+    ) -> Vec<UserOperation>;
+}
+
+dyn_clone::clone_trait_object!(BundleSelector);
+
+/// Creates the [BundleSelector] of the type
+///
+/// # Arguments
+/// * `selector_type` - The [BundleSelectorType] to create
+/// * `entry_point` - The [EntryPoint](EntryPoint) the bundles are sent to (the simulation selector
+///   simulates the user operations against it)
+pub fn new_bundle_selector<M: Middleware + 'static>(
+    selector_type: BundleSelectorType,
+    entry_point: EntryPoint<M>,
+) -> Box<dyn BundleSelector> {
+    match selector_type {
+        BundleSelectorType::Greedy => Box::new(GreedyBundleSelector),
+        BundleSelectorType::Simulation => Box::new(SimulationValidatedSelector::new(entry_point)),
+        BundleSelectorType::Knapsack => Box::new(KnapsackSelector),
+    }
+}
+
+/// Puts the user operations of each sender in the order of the nonces, keeping the positions the
+/// user operations of the sender take
+fn order_by_nonce(uos: &mut [UserOperation]) {
+    let mut positions: HashMap<Address, Vec<usize>> = HashMap::new();
+    for (i, uo) in uos.iter().enumerate() {
+        positions.entry(uo.sender).or_default().push(i);
+    }
+
+    for positions in positions.into_values().filter(|positions| positions.len() > 1) {
+        let mut sender_uos = positions.iter().map(|i| uos[*i].clone()).collect::<Vec<_>>();
+        sender_uos.sort_by_key(|uo| uo.nonce);
+        for (i, uo) in positions.into_iter().zip(sender_uos) {
+            uos[i] = uo;
+        }
+    }
+}
+
+/// Takes the user operations with the highest effective gas price first. The gas budget is
+/// enforced while bundling, so that the user operations failing the second validation don't
+/// take up room in the bundle.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GreedyBundleSelector;
+
+#[async_trait::async_trait]
+impl BundleSelector for GreedyBundleSelector {
+    async fn select(
+        &self,
+        mut uos: Vec<UserOperation>,
+        _max_gas: U256,
+        base_fee: U256,
+    ) -> Vec<UserOperation> {
+        sort_by_effective_gas_price(&mut uos, base_fee);
+        uos
+    }
+}
+
+/// Runs every candidate through `simulateHandleOp` and drops the ones that fail, keeping the
+/// order of the rest.
+#[derive(Clone)]
+pub struct SimulationValidatedSelector<M: Middleware + 'static> {
+    /// The [EntryPoint](EntryPoint) the user operations are simulated against
+    pub entry_point: EntryPoint<M>,
+}
+
+impl<M: Middleware + 'static> SimulationValidatedSelector<M> {
+    pub fn new(entry_point: EntryPoint<M>) -> Self {
+        Self { entry_point }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware + 'static> BundleSelector for SimulationValidatedSelector<M> {
+    async fn select(
+        &self,
+        uos: Vec<UserOperation>,
+        _max_gas: U256,
+        _base_fee: U256,
+    ) -> Vec<UserOperation> {
+        let mut selected = Vec::with_capacity(uos.len());
+
+        for uo in uos {
+            match self.entry_point.simulate_handle_op(uo.user_operation.clone()).await {
+                Ok(_) => selected.push(uo),
+                Err(err) => {
+                    debug!("Dropping bundle candidate {:?} failing simulation: {err:?}", uo.hash)
+                }
+            }
+        }
+
+        selected
+    }
+}
+
+/// Maximizes the total fee of the bundle under the gas budget. The gas is rounded up to
+/// [KNAPSACK_GAS_BUCKETS] buckets of the budget, which keeps the dynamic programming table small
+/// at the price of slightly underusing the budget. A user operation is only selected together
+/// with the user operations of the sender with lower nonces, and they are bundled in the order of
+/// the nonces.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct KnapsackSelector;
+
+impl KnapsackSelector {
+    /// Fee the bundler collects from the user operation (all of its gas at the effective gas
+    /// price)
+    pub fn op_value(uo: &UserOperation, base_fee: U256) -> U256 {
+        effective_gas_price(uo, base_fee).saturating_mul(gas_of(uo))
+    }
+}
+
+#[async_trait::async_trait]
+impl BundleSelector for KnapsackSelector {
+    async fn select(
+        &self,
+        uos: Vec<UserOperation>,
+        max_gas: U256,
+        base_fee: U256,
+    ) -> Vec<UserOperation> {
+        if max_gas.is_zero() {
+            return vec![];
+        }
+
+        let unit = max_gas
+            .checked_add(U256::from(KNAPSACK_GAS_BUCKETS - 1))
+            .unwrap_or(U256::MAX)
+            .checked_div(KNAPSACK_GAS_BUCKETS.into())
+            .unwrap_or_default()
+            .max(U256::one());
+        let capacity = max_gas.checked_div(unit).unwrap_or_default().as_usize();
+
+        // the user operations of a sender are grouped in the order of the nonces
+        let mut groups: Vec<Vec<UserOperation>> = vec![];
+        let mut group_of: HashMap<Address, usize> = HashMap::new();
+        for uo in uos {
+            let g = *group_of.entry(uo.sender).or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+            groups[g].push(uo);
+        }
+
+        // a group is taken as a prefix of its user operations, the prefixes that don't fit into
+        // the budget are never selected
+        let groups = groups
+            .into_iter()
+            .map(|mut uos| {
+                uos.sort_by_key(|uo| uo.nonce);
+                let mut prefixes = vec![];
+                let (mut weight, mut value) = (U256::zero(), U256::zero());
+                for uo in uos.iter() {
+                    weight = weight.saturating_add(gas_of(uo).saturating_add(unit - 1) / unit);
+                    if weight > capacity.into() {
+                        break;
+                    }
+                    value = value.saturating_add(Self::op_value(uo, base_fee));
+                    prefixes.push((weight.as_usize(), value));
+                }
+                (uos, prefixes)
+            })
+            .collect::<Vec<_>>();
+
+        // best[w] - the highest total fee with at most w buckets used
+        let mut best = vec![U256::zero(); capacity + 1];
+        // taken[g][w] - the number of user operations of the group taken at w buckets
+        let mut taken = vec![vec![0; capacity + 1]; groups.len()];
+        for (g, (_, prefixes)) in groups.iter().enumerate() {
+            for w in (0..=capacity).rev() {
+                for (n, (weight, value)) in prefixes.iter().enumerate() {
+                    if *weight > w {
+                        break;
+                    }
+                    let with = best[w - weight].saturating_add(*value);
+                    if with > best[w] {
+                        best[w] = with;
+                        taken[g][w] = n + 1;
+                    }
+                }
+            }
+        }
+
+        let mut selected = vec![];
+        let mut w = capacity;
+        for (g, (uos, prefixes)) in groups.into_iter().enumerate().rev() {
+            let n = taken[g][w];
+            if n > 0 {
+                w -= prefixes[n - 1].0;
+                selected.extend(uos.into_iter().take(n));
+            }
+        }
+
+        sort_by_effective_gas_price(&mut selected, base_fee);
+        order_by_nonce(&mut selected);
+        selected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        abi::AbiEncode,
+        providers::{JsonRpcError, MockProvider, MockResponse, Provider},
+        types::H256,
+        utils::hex,
+    };
+    use silius_contracts::{ExecutionResult, FailedOp};
+    use silius_primitives::UserOperationSigned;
+    use std::sync::Arc;
+
+    fn uo(gas: u64, max_priority_fee_per_gas: u64) -> UserOperation {
+        sender_uo(Address::random(), 0, gas, max_priority_fee_per_gas)
+    }
+
+    fn sender_uo(
+        sender: Address,
+        nonce: u64,
+        gas: u64,
+        max_priority_fee_per_gas: u64,
+    ) -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::default()
+                .sender(sender)
+                .nonce(nonce.into())
+                .call_gas_limit(gas.into())
+                .max_fee_per_gas(U256::MAX)
+                .max_priority_fee_per_gas(max_priority_fee_per_gas.into()),
+        )
+    }
+
+    /// Response of `eth_call` reverting with the data
+    fn revert(data: Vec<u8>) -> MockResponse {
+        MockResponse::Error(JsonRpcError {
+            code: 3,
+            message: "execution reverted".into(),
+            data: Some(format!("0x{}", hex::encode(data)).into()),
+        })
+    }
+
+    fn hashes(uos: &[UserOperation]) -> Vec<H256> {
+        uos.iter().map(|uo| uo.hash.0).collect()
+    }
+
+    #[tokio::test]
+    async fn greedy_selection() {
+        let uos = vec![uo(100, 1), uo(100, 3), uo(100, 2)];
+        let expected = vec![uos[1].hash.0, uos[2].hash.0, uos[0].hash.0];
+
+        let selected = GreedyBundleSelector.select(uos, 100.into(), U256::zero()).await;
+        assert_eq!(hashes(&selected), expected);
+    }
+
+    #[tokio::test]
+    async fn simulation_selection() {
+        let (provider, mock) = Provider::<MockProvider>::mocked();
+        let selector = SimulationValidatedSelector::new(EntryPoint::new(
+            Arc::new(provider),
+            Address::random(),
+        ));
+        let uos = vec![uo(100, 3), uo(100, 2), uo(100, 1)];
+        let expected = vec![uos[0].hash.0, uos[2].hash.0];
+
+        // the mocked responses are returned in the reverse order
+        mock.push_response(revert(ExecutionResult::default().encode()));
+        mock.push_response(revert(
+            FailedOp { op_index: U256::zero(), reason: "AA23 reverted".into() }.encode(),
+        ));
+        mock.push_response(revert(ExecutionResult::default().encode()));
+
+        let selected = selector.select(uos, 100.into(), U256::zero()).await;
+        assert_eq!(hashes(&selected), expected);
+    }
+
+    #[tokio::test]
+    async fn knapsack_selection() {
+        // the greedy selection would take the first one and nothing else would fit
+        let uos = vec![uo(600, 10), uo(500, 9), uo(500, 9)];
+        let mut expected = vec![uos[1].hash.0, uos[2].hash.0];
+        expected.sort();
+
+        let selected = KnapsackSelector.select(uos, 1000.into(), U256::zero()).await;
+        let mut selected = hashes(&selected);
+        selected.sort();
+        assert_eq!(selected, expected);
+    }
+
+    #[tokio::test]
+    async fn knapsack_nonce_order() {
+        // the second user operation of the sender pays the most, but it can't be bundled without
+        // the first one, which doesn't leave room for the other sender
+        let sender = Address::random();
+        let uos = vec![sender_uo(sender, 1, 100, 100), uo(500, 9), sender_uo(sender, 0, 600, 1)];
+        let expected = vec![uos[2].hash.0, uos[0].hash.0];
+
+        let selected = KnapsackSelector.select(uos, 1000.into(), U256::zero()).await;
+        assert_eq!(hashes(&selected), expected);
+    }
+
+    #[tokio::test]
+    async fn knapsack_oversized() {
+        let uos = vec![uo(2000, 10), uo(100, 1)];
+        let expected = vec![uos[1].hash.0];
+
+        let selected = KnapsackSelector.select(uos, 1000.into(), U256::zero()).await;
+        assert_eq!(hashes(&selected), expected);
+        assert!(KnapsackSelector
+            .select(vec![uo(1, 1)], U256::zero(), U256::zero())
+            .await
+            .is_empty());
+    }
+}
@@ -47,6 +47,9 @@ pub enum DatabaseError {
     /// Databse not found
     #[error("Database not found")]
     NotFound,
+    /// Database was created by a newer version of the bundler
+    #[error("Database schema version {version} is newer than the supported version {supported}")]
+    UnsupportedSchemaVersion { version: u32, supported: u32 },
 }
 
 impl From<RethDatabaseError> for DatabaseError {
@@ -1,33 +1,56 @@
 use super::{
     env::DatabaseError,
-    tables::{CodeHashes, UserOperations, UserOperationsByEntity, UserOperationsBySender},
+    tables::{
+        CodeHashes, UserOperationAggregators, UserOperationReceipts, UserOperations,
+        UserOperationsByAggregator, UserOperationsByEntity, UserOperationsByNonce,
+        UserOperationsBySender,
+    },
     utils::{
-        WrapAddress, WrapCodeHash, WrapCodeHashVec, WrapUserOpSet, WrapUserOperationHash,
-        WrapUserOperationSigned,
+        WrapAddress, WrapCodeHash, WrapCodeHashVec, WrapSenderNonce, WrapUserOpSet,
+        WrapUserOperationHash, WrapUserOperationReceipt, WrapUserOperationSigned,
     },
     DatabaseTable,
 };
 use crate::{
     mempool::{
         AddRemoveUserOp, AddRemoveUserOpHash, ClearOp, UserOperationAddrOp,
-        UserOperationCodeHashOp, UserOperationOp,
+        UserOperationCodeHashOp, UserOperationNonceOp, UserOperationOp, UserOperationReceiptOp,
     },
+    utils::sort_by_effective_gas_price,
     MempoolErrorKind,
 };
-use ethers::types::Address;
+use ethers::types::{Address, U256};
 use reth_db::{
     cursor::DbCursorRO,
     database::Database,
     mdbx::EnvironmentKind,
     transaction::{DbTx, DbTxMut},
+    Error as RethDatabaseError,
+};
+use silius_primitives::{
+    simulation::CodeHash, UserOperation, UserOperationHash, UserOperationReceipt,
 };
-use silius_primitives::{simulation::CodeHash, UserOperation, UserOperationHash};
+
+/// Builds the user operation from the stored one and its signature aggregator (if any)
+fn user_operation_with_aggregator<'a, TX: DbTx<'a>>(
+    tx: &TX,
+    hash: WrapUserOperationHash,
+    uo: WrapUserOperationSigned,
+) -> Result<UserOperation, RethDatabaseError> {
+    let aggregator = tx.get::<UserOperationAggregators>(hash.clone())?;
+    let mut uo = UserOperation::from_user_operation_signed(hash.into(), uo.into());
+    uo.aggregator = aggregator.map(Into::into);
+    Ok(uo)
+}
 
 impl<E: EnvironmentKind> AddRemoveUserOp for DatabaseTable<E, UserOperations> {
     fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
         let tx = self.env.tx_mut()?;
         let uo_hash_wrap: WrapUserOperationHash = uo.hash.into();
         let uo_wrap: WrapUserOperationSigned = uo.user_operation.into();
+        if let Some(aggregator) = uo.aggregator {
+            tx.put::<UserOperationAggregators>(uo_hash_wrap.clone(), aggregator.into())?;
+        }
         tx.put::<UserOperations>(uo_hash_wrap, uo_wrap)?;
         tx.commit()?;
         Ok(uo.hash)
@@ -39,9 +62,30 @@ impl<E: EnvironmentKind> AddRemoveUserOp for DatabaseTable<E, UserOperations> {
         let tx = self.env.tx_mut()?;
         let original_value = tx.get::<UserOperations>(uo_hash_wrap.clone())?;
         tx.delete::<UserOperations>(uo_hash_wrap.clone(), None)?;
+        tx.delete::<UserOperationAggregators>(uo_hash_wrap, None)?;
         tx.commit()?;
         Ok(original_value.is_some())
     }
+
+    fn replace(
+        &mut self,
+        prev_hash: &UserOperationHash,
+        uo: UserOperation,
+    ) -> Result<UserOperationHash, MempoolErrorKind> {
+        // readers don't see the table without either of the user operations
+        let tx = self.env.tx_mut()?;
+        let prev_hash_wrap: WrapUserOperationHash = (*prev_hash).into();
+        let uo_hash_wrap: WrapUserOperationHash = uo.hash.into();
+        let uo_wrap: WrapUserOperationSigned = uo.user_operation.into();
+        tx.delete::<UserOperations>(prev_hash_wrap.clone(), None)?;
+        tx.delete::<UserOperationAggregators>(prev_hash_wrap, None)?;
+        if let Some(aggregator) = uo.aggregator {
+            tx.put::<UserOperationAggregators>(uo_hash_wrap.clone(), aggregator.into())?;
+        }
+        tx.put::<UserOperations>(uo_hash_wrap, uo_wrap)?;
+        tx.commit()?;
+        Ok(uo.hash)
+    }
 }
 
 macro_rules! impl_add_remove_user_op_hash {
@@ -72,14 +116,12 @@ macro_rules! impl_add_remove_user_op_hash {
                 uo_hash: &UserOperationHash,
             ) -> Result<bool, MempoolErrorKind> {
                 let tx = self.env.tx_mut()?;
-                if let Some(mut uo_hash_set) =
-                    tx.get::<UserOperationsBySender>(address.clone().into())?
-                {
+                if let Some(mut uo_hash_set) = tx.get::<$table>(address.clone().into())? {
                     uo_hash_set.remove(&uo_hash.clone().into());
                     if uo_hash_set.is_empty() {
-                        tx.delete::<UserOperationsBySender>(address.clone().into(), None)?;
+                        tx.delete::<$table>(address.clone().into(), None)?;
                     } else {
-                        tx.put::<UserOperationsBySender>(address.clone().into(), uo_hash_set)?;
+                        tx.put::<$table>(address.clone().into(), uo_hash_set)?;
                     }
                     tx.commit()?;
                     Ok(true)
@@ -93,6 +135,7 @@ macro_rules! impl_add_remove_user_op_hash {
 
 impl_add_remove_user_op_hash!(UserOperationsBySender);
 impl_add_remove_user_op_hash!(UserOperationsByEntity);
+impl_add_remove_user_op_hash!(UserOperationsByAggregator);
 
 impl<E: EnvironmentKind> UserOperationOp for DatabaseTable<E, UserOperations> {
     fn get_by_uo_hash(
@@ -102,32 +145,25 @@ impl<E: EnvironmentKind> UserOperationOp for DatabaseTable<E, UserOperations> {
         let uo_hash_wrap: WrapUserOperationHash = (*uo_hash).into();
 
         let tx = self.env.tx()?;
-        let res = tx.get::<UserOperations>(uo_hash_wrap)?;
+        let res = tx
+            .get::<UserOperations>(uo_hash_wrap.clone())?
+            .map(|uo| user_operation_with_aggregator(&tx, uo_hash_wrap, uo))
+            .transpose()?;
         tx.commit()?;
 
-        Ok(res.map(|uo| UserOperation::from_user_operation_signed(*uo_hash, uo.into())))
+        Ok(res)
     }
 
-    fn get_sorted(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+    fn get_sorted(&self, base_fee_per_gas: U256) -> Result<Vec<UserOperation>, MempoolErrorKind> {
         self.env
             .tx()
             .and_then(|tx| {
                 let mut cursor = tx.cursor_read::<UserOperations>()?;
                 let mut uos: Vec<UserOperation> = cursor
                     .walk(Some(WrapUserOperationHash::default()))?
-                    .map(|a| {
-                        a.map(|(hash, uo)| {
-                            UserOperation::from_user_operation_signed(hash.into(), uo.into())
-                        })
-                    })
+                    .map(|a| a.and_then(|(hash, uo)| user_operation_with_aggregator(&tx, hash, uo)))
                     .collect::<Result<Vec<_>, _>>()?;
-                uos.sort_by(|a, b| {
-                    if a.max_priority_fee_per_gas != b.max_priority_fee_per_gas {
-                        b.max_priority_fee_per_gas.cmp(&a.max_priority_fee_per_gas)
-                    } else {
-                        a.nonce.cmp(&b.nonce)
-                    }
-                });
+                sort_by_effective_gas_price(&mut uos, base_fee_per_gas);
                 Ok(uos)
             })
             .map_err(|e| MempoolErrorKind::Database(DatabaseError::Internal(e)))
@@ -138,11 +174,43 @@ impl<E: EnvironmentKind> UserOperationOp for DatabaseTable<E, UserOperations> {
         let mut c = tx.cursor_read::<UserOperations>()?;
         let mut res = Vec::new();
         while let Some((hash, uo)) = c.next()? {
-            res.push(UserOperation::from_user_operation_signed(hash.into(), uo.into()))
+            res.push(user_operation_with_aggregator(&tx, hash, uo)?)
         }
 
         Ok(res)
     }
+
+    fn get_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        // the keys are ordered by the hash, so the cursor is only advanced to the offset
+        self.env
+            .tx()
+            .and_then(|tx| {
+                let mut cursor = tx.cursor_read::<UserOperations>()?;
+                let uos = cursor
+                    .walk(Some(WrapUserOperationHash::default()))?
+                    .skip(offset)
+                    .take(limit)
+                    .map(|a| a.and_then(|(hash, uo)| user_operation_with_aggregator(&tx, hash, uo)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(uos)
+            })
+            .map_err(|e| MempoolErrorKind::Database(DatabaseError::Internal(e)))
+    }
+
+    fn len(&self) -> Result<usize, MempoolErrorKind> {
+        let tx = self.env.tx()?;
+        let mut c = tx.cursor_read::<UserOperations>()?;
+        let mut len = 0;
+        while c.next()?.is_some() {
+            len += 1;
+        }
+
+        Ok(len)
+    }
 }
 macro_rules! impl_user_op_addr_op {
     ($table:ident) => {
@@ -165,6 +233,52 @@ macro_rules! impl_user_op_addr_op {
 }
 impl_user_op_addr_op!(UserOperationsBySender);
 impl_user_op_addr_op!(UserOperationsByEntity);
+impl_user_op_addr_op!(UserOperationsByAggregator);
+
+impl<E: EnvironmentKind> UserOperationNonceOp for DatabaseTable<E, UserOperationsByNonce> {
+    fn add(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        let key: WrapSenderNonce = (*sender, nonce).into();
+        let tx = self.env.tx_mut()?;
+        tx.put::<UserOperationsByNonce>(key, uo_hash.into())?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let key: WrapSenderNonce = (*sender, nonce).into();
+        let tx = self.env.tx_mut()?;
+        match tx.get::<UserOperationsByNonce>(key.clone())? {
+            Some(hash) if UserOperationHash::from(hash) == *uo_hash => {
+                tx.delete::<UserOperationsByNonce>(key, None)?;
+                tx.commit()?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    fn get_by_sender_and_nonce(
+        &self,
+        sender: &Address,
+        nonce: U256,
+    ) -> Result<Option<UserOperationHash>, MempoolErrorKind> {
+        let key: WrapSenderNonce = (*sender, nonce).into();
+        let tx = self.env.tx()?;
+        let res = tx.get::<UserOperationsByNonce>(key)?;
+        tx.commit()?;
+        Ok(res.map(Into::into))
+    }
+}
 
 impl<E: EnvironmentKind> UserOperationCodeHashOp for DatabaseTable<E, CodeHashes> {
     fn has_code_hashes(&self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
@@ -220,6 +334,49 @@ impl<E: EnvironmentKind> UserOperationCodeHashOp for DatabaseTable<E, CodeHashes
     }
 }
 
+impl<E: EnvironmentKind> UserOperationReceiptOp for DatabaseTable<E, UserOperationReceipts> {
+    fn add_receipt(&mut self, receipt: UserOperationReceipt) -> Result<(), MempoolErrorKind> {
+        let uo_hash_wrap: WrapUserOperationHash = receipt.user_operation_hash.into();
+        let receipt_wrap: WrapUserOperationReceipt = receipt.into();
+        let tx = self.env.tx_mut()?;
+        tx.put::<UserOperationReceipts>(uo_hash_wrap, receipt_wrap)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_receipt(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperationReceipt>, MempoolErrorKind> {
+        let uo_hash_wrap: WrapUserOperationHash = (*uo_hash).into();
+        let tx = self.env.tx()?;
+        let res = tx.get::<UserOperationReceipts>(uo_hash_wrap)?;
+        tx.commit()?;
+        Ok(res.map(Into::into))
+    }
+
+    fn remove_receipt(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let uo_hash_wrap: WrapUserOperationHash = (*uo_hash).into();
+        let tx = self.env.tx_mut()?;
+        let removed = tx.delete::<UserOperationReceipts>(uo_hash_wrap, None)?;
+        tx.commit()?;
+        Ok(removed)
+    }
+}
+
+impl<E: EnvironmentKind> ClearOp for DatabaseTable<E, UserOperations> {
+    fn clear(&mut self) {
+        self.env
+            .tx_mut()
+            .and_then(|tx| {
+                tx.clear::<UserOperations>()?;
+                tx.clear::<UserOperationAggregators>()?;
+                tx.commit()
+            })
+            .expect("Clear database failed");
+    }
+}
+
 macro_rules! impl_clear {
     ($table: ident) => {
         impl<E: EnvironmentKind> ClearOp for DatabaseTable<E, $table> {
@@ -235,20 +392,30 @@ macro_rules! impl_clear {
         }
     };
 }
-impl_clear!(UserOperations);
 impl_clear!(UserOperationsBySender);
 impl_clear!(UserOperationsByEntity);
+impl_clear!(UserOperationsByAggregator);
+impl_clear!(UserOperationsByNonce);
 impl_clear!(CodeHashes);
+impl_clear!(UserOperationReceipts);
 
 #[cfg(test)]
 mod tests {
     use crate::{
         database::{
             init_env,
-            tables::{CodeHashes, UserOperations, UserOperationsByEntity, UserOperationsBySender},
+            tables::{
+                CodeHashes, UserOperationReceipts, UserOperations, UserOperationsByAggregator,
+                UserOperationsByEntity, UserOperationsByNonce, UserOperationsBySender,
+            },
             DatabaseTable,
         },
-        utils::tests::mempool_test_case,
+        utils::tests::{
+            aggregator_test_case, capacity_tracker_test_case,
+            concurrent_update_user_operation_test_case, expiry_test_case, mempool_test_case,
+            receipts_test_case, sort_by_effective_gas_price_test_case,
+            update_user_operation_test_case,
+        },
         Mempool,
     };
     use reth_libmdbx::WriteMap;
@@ -268,15 +435,158 @@ mod tests {
             DatabaseTable::new(env.clone());
         let uo_ops_entity: DatabaseTable<WriteMap, UserOperationsByEntity> =
             DatabaseTable::new(env.clone());
+        let uo_ops_aggregator: DatabaseTable<WriteMap, UserOperationsByAggregator> =
+            DatabaseTable::new(env.clone());
         let uo_ops_codehashes: DatabaseTable<WriteMap, CodeHashes> =
             DatabaseTable::new(env.clone());
+        let uo_ops_nonce: DatabaseTable<WriteMap, UserOperationsByNonce> =
+            DatabaseTable::new(env.clone());
+        let uo_receipts: DatabaseTable<WriteMap, UserOperationReceipts> =
+            DatabaseTable::new(env.clone());
         let mempool = Mempool::new(
             Box::new(uo_ops),
             Box::new(uo_ops_sender),
             Box::new(uo_ops_entity),
+            Box::new(uo_ops_aggregator),
             Box::new(uo_ops_codehashes),
+            Box::new(uo_ops_nonce),
+            Box::new(uo_receipts),
         );
 
         mempool_test_case(mempool);
+
+        // reopen the mempool on the same database, the capacity tracker starts from the stored
+        // user operations
+        let mempool = Mempool::new(
+            Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env)),
+        );
+
+        capacity_tracker_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn database_mempool_sort_by_effective_gas_price() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.into_path()).unwrap();
+        env.create_tables().expect("Create mdbx database tables failed");
+        let env = Arc::new(env);
+        let mempool = Mempool::new(
+            Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env)),
+        );
+
+        sort_by_effective_gas_price_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn database_mempool_expiry() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.into_path()).unwrap();
+        env.create_tables().expect("Create mdbx database tables failed");
+        let env = Arc::new(env);
+        let mempool = Mempool::new(
+            Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env)),
+        );
+
+        expiry_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn database_mempool_receipts() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.into_path()).unwrap();
+        env.create_tables().expect("Create mdbx database tables failed");
+        let env = Arc::new(env);
+        let mempool = Mempool::new(
+            Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env)),
+        );
+
+        receipts_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn database_mempool_update_user_operation() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.into_path()).unwrap();
+        env.create_tables().expect("Create mdbx database tables failed");
+        let env = Arc::new(env);
+        let mempool = Mempool::new(
+            Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env)),
+        );
+
+        update_user_operation_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn database_mempool_aggregator() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.into_path()).unwrap();
+        env.create_tables().expect("Create mdbx database tables failed");
+        let env = Arc::new(env);
+        let mempool = Mempool::new(
+            Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env)),
+        );
+
+        aggregator_test_case(mempool);
+    }
+
+    #[test]
+    fn database_mempool_concurrent_update_user_operation() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.into_path()).unwrap();
+        env.create_tables().expect("Create mdbx database tables failed");
+        let env = Arc::new(env);
+        let mempool = Mempool::new(
+            Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env)),
+        );
+
+        concurrent_update_user_operation_test_case(mempool);
     }
 }
@@ -0,0 +1,148 @@
+//! Versioning of the database schema. The version is stored in the [Meta] table and the databases
+//! created by older versions of the bundler are migrated on startup.
+
+use super::{
+    env::{DatabaseError, Env},
+    tables::Meta,
+    utils::WrapMetaKey,
+};
+use reth_db::{
+    database::Database,
+    mdbx::{tx::Tx, EnvironmentKind, RW},
+    transaction::{DbTx, DbTxMut},
+};
+use tracing::info;
+
+/// Version of the database schema supported by this version of the bundler
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Key of the schema version in the [Meta] table
+pub const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Migration of the database to the next schema version (runs in the same transaction as the
+/// update of the stored version)
+pub type Migration<E> = for<'a> fn(&Tx<'a, RW, E>) -> Result<(), DatabaseError>;
+
+/// Migrations of the database schema in order, the first one migrates the database from version 1
+/// to version 2 and so on
+pub fn migrations<E: EnvironmentKind>() -> Vec<Migration<E>> {
+    vec![]
+}
+
+impl<E: EnvironmentKind> Env<E> {
+    /// Returns the schema version of the database (`None` if the database predates the versioning)
+    pub fn schema_version(&self) -> Result<Option<u32>, DatabaseError> {
+        let tx = self.tx()?;
+        let version = tx.get::<Meta>(WrapMetaKey::from(SCHEMA_VERSION_KEY))?;
+        tx.commit()?;
+        Ok(version.map(Into::into))
+    }
+
+    /// Migrates the database to the current [SCHEMA_VERSION]
+    pub fn migrate(&self) -> Result<(), DatabaseError> {
+        self.migrate_to(SCHEMA_VERSION, &migrations())
+    }
+
+    /// Migrates the database to the `target` schema version
+    ///
+    /// # Arguments
+    /// * `target` - The schema version to migrate to
+    /// * `migrations` - The migrations in order, the i-th one migrates from version `i + 1`
+    ///
+    /// # Returns
+    /// * `Ok(())` if the database is at the `target` version, otherwise
+    ///   [DatabaseError::UnsupportedSchemaVersion] if the database is newer than `target`
+    pub fn migrate_to(
+        &self,
+        target: u32,
+        migrations: &[Migration<E>],
+    ) -> Result<(), DatabaseError> {
+        debug_assert_eq!(migrations.len() as u32 + 1, target, "Missing database migrations");
+
+        // databases without the stored version were created with the initial schema
+        let version = self.schema_version()?.unwrap_or(1);
+        if version > target {
+            return Err(DatabaseError::UnsupportedSchemaVersion { version, supported: target });
+        }
+
+        // all the migrations are applied at once, so the database is never left in between
+        let tx = self.tx_mut()?;
+        for (from, migrate) in (version..target).zip(&migrations[(version - 1) as usize..]) {
+            info!("Migrating database schema from version {from} to {}", from + 1);
+            migrate(&tx)?;
+        }
+        tx.put::<Meta>(SCHEMA_VERSION_KEY.into(), target.into())?;
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Migration, SCHEMA_VERSION, SCHEMA_VERSION_KEY};
+    use crate::database::{
+        env::{DatabaseError, Env},
+        init_env,
+        tables::Meta,
+        utils::WrapMetaKey,
+    };
+    use reth_db::{
+        database::Database,
+        mdbx::{tx::Tx, RW},
+        transaction::{DbTx, DbTxMut},
+    };
+    use reth_libmdbx::WriteMap;
+    use tempfile::TempDir;
+
+    const COLUMN_KEY: &str = "column";
+
+    /// Hypothetical migration to version 2 that adds a column with the default value
+    fn add_column(tx: &Tx<'_, RW, WriteMap>) -> Result<(), DatabaseError> {
+        tx.put::<Meta>(COLUMN_KEY.into(), 0u32.into())?;
+        Ok(())
+    }
+
+    #[test]
+    fn database_schema_migration() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.path().to_path_buf()).unwrap();
+        assert_eq!(env.schema_version().unwrap(), Some(SCHEMA_VERSION));
+        drop(env);
+
+        let env = Env::<WriteMap>::open(dir.path().to_path_buf()).unwrap();
+        let migrations: [Migration<WriteMap>; 1] = [add_column];
+        env.migrate_to(2, &migrations).unwrap();
+        assert_eq!(env.schema_version().unwrap(), Some(2));
+        let tx = env.tx().unwrap();
+        assert_eq!(tx.get::<Meta>(WrapMetaKey::from(COLUMN_KEY)).unwrap(), Some(0u32.into()));
+        tx.commit().unwrap();
+
+        // already migrated
+        env.migrate_to(2, &migrations).unwrap();
+        assert_eq!(env.schema_version().unwrap(), Some(2));
+        drop(env);
+
+        // the database is newer than the binary
+        let res = init_env::<WriteMap>(dir.path().to_path_buf());
+        assert!(matches!(
+            res.unwrap_err().downcast::<DatabaseError>(),
+            Ok(DatabaseError::UnsupportedSchemaVersion { version: 2, supported: SCHEMA_VERSION })
+        ));
+    }
+
+    #[test]
+    fn database_without_schema_version() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.path().to_path_buf()).unwrap();
+        let tx = env.tx_mut().unwrap();
+        tx.delete::<Meta>(SCHEMA_VERSION_KEY.into(), None).unwrap();
+        tx.commit().unwrap();
+        assert_eq!(env.schema_version().unwrap(), None);
+
+        env.migrate().unwrap();
+        assert_eq!(env.schema_version().unwrap(), Some(SCHEMA_VERSION));
+    }
+}
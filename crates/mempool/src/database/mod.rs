@@ -9,6 +9,7 @@ use std::{path::PathBuf, sync::Arc};
 
 mod env;
 pub mod mempool;
+pub mod migrations;
 pub mod reputation;
 pub mod tables;
 mod utils;
@@ -16,6 +17,7 @@ mod utils;
 pub fn init_env<E: EnvironmentKind>(path: PathBuf) -> eyre::Result<Env<E>> {
     let env = Env::open(path)?;
     env.create_tables()?;
+    env.migrate()?;
     Ok(env)
 }
 /// The database-based implementation of the [Mempool](crate::mempool::Mempool) trait.
@@ -1,10 +1,19 @@
-use super::{tables::EntitiesReputation, utils::WrapAddress, DatabaseTable};
-use crate::{mempool::ClearOp, reputation::ReputationEntryOp, ReputationError};
+use super::{
+    tables::EntitiesReputation,
+    utils::{WrapAddress, WrapEmpty},
+    DatabaseTable,
+};
+use crate::{
+    mempool::ClearOp,
+    reputation::{EntityListOp, ReputationEntryOp},
+    ReputationError,
+};
 use ethers::types::Address;
 use reth_db::{
     cursor::DbCursorRO,
     database::Database,
     mdbx::EnvironmentKind,
+    table::Table,
     transaction::{DbTx, DbTxMut},
 };
 use silius_primitives::reputation::ReputationEntry;
@@ -58,10 +67,44 @@ impl<E: EnvironmentKind> ReputationEntryOp for DatabaseTable<E, EntitiesReputati
     }
 }
 
+impl<E: EnvironmentKind, T> EntityListOp for DatabaseTable<E, T>
+where
+    T: Table<Key = WrapAddress, Value = WrapEmpty>,
+{
+    fn add(&mut self, addr: &Address) -> Result<(), ReputationError> {
+        let tx = self.env.tx_mut()?;
+        tx.put::<T>((*addr).into(), WrapEmpty)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn remove(&mut self, addr: &Address) -> Result<(), ReputationError> {
+        let tx = self.env.tx_mut()?;
+        tx.delete::<T>((*addr).into(), None)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn get_all(&self) -> Result<Vec<Address>, ReputationError> {
+        let tx = self.env.tx()?;
+        let mut c = tx.cursor_read::<T>()?;
+        let res: Vec<Address> = c
+            .walk(Some(WrapAddress::default()))?
+            .map(|a| a.map(|(k, _)| k.into()))
+            .collect::<Result<Vec<_>, _>>()?;
+        tx.commit()?;
+        Ok(res)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        database::{init_env, tables::EntitiesReputation, DatabaseTable},
+        database::{
+            init_env,
+            tables::{BlacklistedEntities, EntitiesReputation, WhitelistedEntities},
+            DatabaseTable,
+        },
         utils::tests::reputation_test_case,
         Reputation,
     };
@@ -95,4 +138,45 @@ mod tests {
         );
         reputation_test_case(reputation);
     }
+
+    #[tokio::test]
+    async fn database_entity_lists() {
+        let dir = TempDir::new().unwrap();
+
+        let env = init_env::<WriteMap>(dir.into_path()).unwrap();
+        env.create_tables().expect("Create mdbx database tables failed");
+        let env = Arc::new(env);
+        let reputation = || {
+            Reputation::new(
+                MIN_INCLUSION_RATE_DENOMINATOR,
+                THROTTLING_SLACK,
+                BAN_SLACK,
+                U256::from(1),
+                U256::from(0),
+                Arc::new(RwLock::new(HashSet::<Address>::default())),
+                Arc::new(RwLock::new(HashSet::<Address>::default())),
+                Box::new(DatabaseTable::<WriteMap, EntitiesReputation>::new(env.clone())),
+            )
+            .with_entity_lists(
+                Box::new(DatabaseTable::<WriteMap, WhitelistedEntities>::new(env.clone())),
+                Box::new(DatabaseTable::<WriteMap, BlacklistedEntities>::new(env.clone())),
+            )
+            .unwrap()
+        };
+
+        let addrs: Vec<Address> = (0..3).map(|_| Address::random()).collect();
+
+        let mut first = reputation();
+        assert!(first.add_whitelist(&addrs[0]));
+        assert!(first.add_blacklist(&addrs[1]));
+        assert!(first.add_blacklist(&addrs[2]));
+        assert!(first.remove_blacklist(&addrs[2]));
+
+        // the lists are loaded from the database
+        let second = reputation();
+        assert!(second.is_whitelist(&addrs[0]));
+        assert!(second.is_blacklist(&addrs[1]));
+        assert!(!second.is_blacklist(&addrs[2]));
+        assert!(!second.is_whitelist(&addrs[1]));
+    }
 }
@@ -1,5 +1,6 @@
 use super::utils::{
-    WrapAddress, WrapCodeHashVec, WrapReputationEntry, WrapUserOpSet, WrapUserOperationHash,
+    WrapAddress, WrapCodeHashVec, WrapEmpty, WrapMetaKey, WrapReputationEntry, WrapSenderNonce,
+    WrapU32, WrapUserOpSet, WrapUserOperationHash, WrapUserOperationReceipt,
     WrapUserOperationSigned,
 };
 use reth_db::{table, TableType};
@@ -20,6 +21,22 @@ table!(
     ( UserOperationsByEntity ) WrapAddress | WrapUserOpSet
 );
 
+table!(
+    /// Stores the hashes of user operations by signature aggregator
+    ( UserOperationsByAggregator ) WrapAddress | WrapUserOpSet
+);
+
+table!(
+    /// Stores the signature aggregator of the user operation (if any)
+    ( UserOperationAggregators ) WrapUserOperationHash | WrapAddress
+);
+
+table!(
+    /// Stores the hash of user operation by sender and nonce
+    /// Nonce is encoded as big endian, so user operations of the sender are sorted by nonce.
+    ( UserOperationsByNonce ) WrapSenderNonce | WrapUserOperationHash
+);
+
 table!(
     /// Stores the code hashes (needed during simulation)
     ( CodeHashes ) WrapUserOperationHash | WrapCodeHashVec
@@ -30,11 +47,38 @@ table!(
     ( EntitiesReputation ) WrapAddress | WrapReputationEntry
 );
 
+table!(
+    /// Stores the whitelisted entities
+    ( WhitelistedEntities ) WrapAddress | WrapEmpty
+);
+
+table!(
+    /// Stores the blacklisted entities
+    ( BlacklistedEntities ) WrapAddress | WrapEmpty
+);
+
+table!(
+    /// Stores the receipts of the user operations included on-chain
+    ( UserOperationReceipts ) WrapUserOperationHash | WrapUserOperationReceipt
+);
+
+table!(
+    /// Stores the metadata of the database (e.g. the schema version)
+    ( Meta ) WrapMetaKey | WrapU32
+);
+
 /// Tables that should be present inside database
-pub const TABLES: [(TableType, &str); 5] = [
+pub const TABLES: [(TableType, &str); 12] = [
     (TableType::Table, UserOperations::const_name()),
     (TableType::Table, UserOperationsBySender::const_name()),
     (TableType::Table, UserOperationsByEntity::const_name()),
+    (TableType::Table, UserOperationsByAggregator::const_name()),
+    (TableType::Table, UserOperationAggregators::const_name()),
+    (TableType::Table, UserOperationsByNonce::const_name()),
     (TableType::Table, CodeHashes::const_name()),
     (TableType::Table, EntitiesReputation::const_name()),
+    (TableType::Table, WhitelistedEntities::const_name()),
+    (TableType::Table, BlacklistedEntities::const_name()),
+    (TableType::Table, UserOperationReceipts::const_name()),
+    (TableType::Table, Meta::const_name()),
 ];
@@ -2,12 +2,13 @@ use bin_layout::{Decoder, Encoder};
 use ethers::{
     abi::{AbiDecode, AbiEncode},
     prelude::{EthAbiCodec, EthAbiType},
-    types::{Address, Bytes},
+    types::{Address, Bytes, U256},
 };
 use reth_db::table::{Compress, Decode, Decompress, Encode};
 use serde::{Deserialize, Serialize};
 use silius_primitives::{
-    reputation::ReputationEntry, simulation::CodeHash, UserOperationHash, UserOperationSigned,
+    reputation::ReputationEntry, simulation::CodeHash, UserOperationHash, UserOperationReceipt,
+    UserOperationSigned,
 };
 use std::{collections::HashSet, fmt::Debug};
 
@@ -111,6 +112,108 @@ construct_wrap_struct!(CodeHash, WrapCodeHash);
 construct_wrap_struct!(UserOperationSigned, WrapUserOperationSigned);
 construct_wrap_struct!(ReputationEntry, WrapReputationEntry);
 
+/// Empty value of the tables that only store a set of keys
+#[derive(Default, Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct WrapEmpty;
+
+impl Compress for WrapEmpty {
+    type Compressed = Vec<u8>;
+    fn compress(self) -> Self::Compressed {
+        vec![]
+    }
+}
+
+impl Decompress for WrapEmpty {
+    fn decompress<B: Into<prost::bytes::Bytes>>(_value: B) -> Result<Self, reth_db::Error> {
+        Ok(Self)
+    }
+}
+
+/// Key of the user operation by its sender and nonce (20 bytes of the address followed by 32 bytes
+/// of the big endian nonce).
+#[derive(Default, Hash, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct WrapSenderNonce(Address, U256);
+
+impl Decode for WrapSenderNonce {
+    fn decode<B: Into<prost::bytes::Bytes>>(value: B) -> Result<Self, reth_db::Error> {
+        let value = value.into();
+        if value.len() != 52 {
+            return Err(reth_db::Error::DecodeError);
+        }
+        Ok(Self(Address::from_slice(&value[..20]), U256::from_big_endian(&value[20..])))
+    }
+}
+
+impl Encode for WrapSenderNonce {
+    type Encoded = [u8; 52];
+    fn encode(self) -> Self::Encoded {
+        let mut encoded = [0u8; 52];
+        encoded[..20].copy_from_slice(self.0.as_bytes());
+        self.1.to_big_endian(&mut encoded[20..]);
+        encoded
+    }
+}
+
+impl From<(Address, U256)> for WrapSenderNonce {
+    fn from((sender, nonce): (Address, U256)) -> Self {
+        Self(sender, nonce)
+    }
+}
+
+/// Key of the table with the metadata of the database (e.g. the schema version)
+#[derive(Default, Hash, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct WrapMetaKey(String);
+
+impl Decode for WrapMetaKey {
+    fn decode<B: Into<prost::bytes::Bytes>>(value: B) -> Result<Self, reth_db::Error> {
+        String::from_utf8(value.into().to_vec()).map(Self).map_err(|_e| reth_db::Error::DecodeError)
+    }
+}
+
+impl Encode for WrapMetaKey {
+    type Encoded = Vec<u8>;
+    fn encode(self) -> Self::Encoded {
+        self.0.into_bytes()
+    }
+}
+
+impl From<&str> for WrapMetaKey {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+/// Unsigned integer value encoded as big endian
+#[derive(Default, Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub struct WrapU32(u32);
+
+impl Compress for WrapU32 {
+    type Compressed = Vec<u8>;
+    fn compress(self) -> Self::Compressed {
+        self.0.to_be_bytes().to_vec()
+    }
+}
+
+impl Decompress for WrapU32 {
+    fn decompress<B: Into<prost::bytes::Bytes>>(value: B) -> Result<Self, reth_db::Error> {
+        let bytes: [u8; 4] =
+            value.into().as_ref().try_into().map_err(|_e| reth_db::Error::DecodeError)?;
+        Ok(Self(u32::from_be_bytes(bytes)))
+    }
+}
+
+impl From<u32> for WrapU32 {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<WrapU32> for u32 {
+    fn from(value: WrapU32) -> Self {
+        value.0
+    }
+}
+
 impl<'de> Decoder<'de> for WrapUserOperationHash {
     fn decoder(data: &mut &'de [u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let data: [u8; 32] = <[u8; 32]>::decoder(data)?;
@@ -213,3 +316,35 @@ impl Decompress for WrapCodeHashVec {
         Ok(decoded.into())
     }
 }
+
+/// The receipt of the user operation, stored as JSON (it contains the logs and the transaction
+/// receipt, which aren't ABI encodable).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapUserOperationReceipt(UserOperationReceipt);
+
+impl From<UserOperationReceipt> for WrapUserOperationReceipt {
+    fn from(value: UserOperationReceipt) -> Self {
+        Self(value)
+    }
+}
+
+impl From<WrapUserOperationReceipt> for UserOperationReceipt {
+    fn from(value: WrapUserOperationReceipt) -> Self {
+        value.0
+    }
+}
+
+impl Compress for WrapUserOperationReceipt {
+    type Compressed = Vec<u8>;
+    fn compress(self) -> Self::Compressed {
+        serde_json::to_vec(&self.0).expect("Receipt is serializable")
+    }
+}
+
+impl Decompress for WrapUserOperationReceipt {
+    fn decompress<B: Into<prost::bytes::Bytes>>(value: B) -> Result<Self, reth_db::Error> {
+        serde_json::from_slice(value.into().as_ref())
+            .map(Self)
+            .map_err(|_| reth_db::Error::DecodeError)
+    }
+}
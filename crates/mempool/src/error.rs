@@ -36,6 +36,19 @@ pub enum MempoolErrorKind {
     /// User operation rejected because validation failed
     #[error(transparent)]
     InvalidUserOperation(#[from] InvalidMempoolUserOperationError),
+    /// The mempool is full and the user operation doesn't pay more than the least profitable one
+    #[error("mempool is full")]
+    MempoolFull,
+    /// The sender already has the maximum number of user operations in the mempool
+    #[error("sender {sender:?} has {count} user operations in the mempool (max {max})")]
+    TooManyOpsForSender {
+        /// The address of the sender
+        sender: Address,
+        /// Number of user operations of the sender in the mempool
+        count: usize,
+        /// Maximum number of user operations of the sender
+        max: usize,
+    },
     /// Provider error
     #[error("provider error: {inner}")]
     Provider {
@@ -46,6 +59,20 @@ pub enum MempoolErrorKind {
     #[cfg(feature = "mdbx")]
     #[error(transparent)]
     Database(DatabaseError),
+    /// SQLite database error
+    #[cfg(feature = "sqlite")]
+    #[error("sqlite error: {inner}")]
+    Sqlite {
+        /// The inner error message
+        inner: String,
+    },
+    /// Redis error
+    #[cfg(feature = "redis")]
+    #[error("redis error: {inner}")]
+    Redis {
+        /// The inner error message
+        inner: String,
+    },
     /// Any other error
     #[error("other error: {inner}")]
     Other {
@@ -79,6 +106,20 @@ impl From<reth_db::Error> for MempoolErrorKind {
     }
 }
 
+#[cfg(feature = "sqlite")]
+impl From<sqlx::Error> for MempoolErrorKind {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Sqlite { inner: e.to_string() }
+    }
+}
+
+#[cfg(feature = "redis")]
+impl From<redis::RedisError> for MempoolErrorKind {
+    fn from(e: redis::RedisError) -> Self {
+        Self::Redis { inner: e.to_string() }
+    }
+}
+
 /// Error when validating user operation failed
 #[derive(Debug, Error, Serialize, Deserialize)]
 pub enum InvalidMempoolUserOperationError {
@@ -120,6 +161,30 @@ pub enum ReputationError {
     #[cfg(feature = "mdbx")]
     #[error(transparent)]
     Database(DatabaseError),
+    /// Redis error
+    #[cfg(feature = "redis")]
+    #[error("redis error: {inner}")]
+    Redis {
+        /// The inner error message
+        inner: String,
+    },
+}
+
+impl ReputationError {
+    /// Name of the error variant (e.g. used as the label of the metrics)
+    pub fn variant(&self) -> &'static str {
+        match self {
+            ReputationError::BannedEntity { .. } => "BannedEntity",
+            ReputationError::ThrottledEntity { .. } => "ThrottledEntity",
+            ReputationError::StakeTooLow { .. } => "StakeTooLow",
+            ReputationError::UnstakeDelayTooLow { .. } => "UnstakeDelayTooLow",
+            ReputationError::UnstakedEntity { .. } => "UnstakedEntity",
+            #[cfg(feature = "mdbx")]
+            ReputationError::Database(_) => "Database",
+            #[cfg(feature = "redis")]
+            ReputationError::Redis { .. } => "Redis",
+        }
+    }
 }
 
 #[cfg(feature = "mdbx")]
@@ -129,21 +194,43 @@ impl From<reth_db::Error> for ReputationError {
     }
 }
 
+#[cfg(feature = "redis")]
+impl From<redis::RedisError> for ReputationError {
+    fn from(e: redis::RedisError) -> Self {
+        Self::Redis { inner: e.to_string() }
+    }
+}
+
 /// Error when sanity check fails
 #[derive(Debug, Error, Serialize, Deserialize)]
 pub enum SanityError {
+    /// User operation is already in the mempool
+    #[error("user operation {hash:?} already known")]
+    AlreadyKnown { hash: UserOperationHash },
     /// Verification gas limit is too high
     #[error("verificationGasLimit too high: expected at most {verification_gas_limit_expected}")]
     VerificationGasLimitTooHigh {
         verification_gas_limit: U256,
         verification_gas_limit_expected: U256,
     },
+    /// Verification gas limit is too low
+    #[error("verificationGasLimit too low: expected at least {verification_gas_limit_expected}")]
+    VerificationGasLimitTooLow {
+        verification_gas_limit: U256,
+        verification_gas_limit_expected: U256,
+    },
+    /// Sum of verification gas limit and call gas limit is too high
+    #[error("verificationGasLimit + callGasLimit too high: {combined}, expected at most {max}")]
+    CombinedGasLimitTooHigh { combined: U256, max: U256 },
     /// Pre verification gas is too low
     #[error("preVerificationGas too low: expected at least {pre_verification_gas_expected}")]
     PreVerificationGasTooLow { pre_verification_gas: U256, pre_verification_gas_expected: U256 },
     /// Call gas limit is too low
     #[error("callGasLimit too low: expected at least {call_gas_limit_expected}")]
     CallGasLimitTooLow { call_gas_limit: U256, call_gas_limit_expected: U256 },
+    /// Call gas limit is below the absolute minimum (base transaction cost + CALL)
+    #[error("callGasLimit {provided} below minimum {minimum}")]
+    CallGasTooLow { provided: U256, minimum: U256 },
     /// Max fee per gas is too low (lower than current base fee per gas)
     #[error("maxFeePerGas too low: expected at least {base_fee_per_gas}")]
     MaxFeePerGasTooLow { max_fee_per_gas: U256, base_fee_per_gas: U256 },
@@ -156,12 +243,46 @@ pub enum SanityError {
         max_priority_fee_per_gas: U256,
         max_priority_fee_per_gas_expected: U256,
     },
+    /// Paymaster deposit doesn't cover the worst-case cost of the user operation
+    #[error("paymaster {paymaster:?} deposit {deposit} too low: expected at least {required}")]
+    PaymasterDepositTooLow { paymaster: Address, deposit: U256, required: U256 },
+    /// Sender deposit (or its balance if the deposit can't be read) doesn't cover the prefund of
+    /// the user operation
+    #[error("sender {sender:?} deposit {deposit} too low: expected at least {required}")]
+    SenderInsufficientDeposit { sender: Address, deposit: U256, required: U256 },
+    /// Init code is too short to contain the factory address
+    #[error("initCode length {len} too short: expected at least 20 bytes (factory address)")]
+    InitCodeTooShort { len: usize },
+    /// Factory in the init code isn't deployed
+    #[error("factory {factory:?} in initCode has no code")]
+    FactoryHasNoCode { factory: Address },
+    /// Verification gas limit doesn't leave enough gas for the paymaster postOp
+    #[error("verificationGasLimit {provided} too low for paymaster postOp: expected {required}")]
+    InsufficientPostOpGasReserve { provided: U256, required: U256 },
+    /// Call data costs more calldata gas than allowed
+    #[error("callData gas {calldata_gas} too high: expected at most {max_calldata_gas}")]
+    CallDataGasTooHigh { calldata_gas: U256, max_calldata_gas: U256 },
+    /// Call data is larger than allowed
+    #[error("callData size {actual} too large: expected at most {max} bytes")]
+    CallDataTooLarge { actual: usize, max: usize },
+    /// Init code is larger than allowed
+    #[error("initCode size {actual} too large: expected at most {max} bytes")]
+    InitCodeTooLarge { actual: usize, max: usize },
     /// Paymaster validation failed
     #[error("{inner}")]
     Paymaster { inner: String },
     /// Sender validation failed
     #[error("{inner}")]
     Sender { inner: String },
+    /// Nonce is too far ahead of the on-chain nonce of the sender
+    #[error("nonce {nonce} too high: on-chain nonce is {on_chain_nonce}")]
+    NonceTooHigh { nonce: U256, on_chain_nonce: U256 },
+    /// Signature can't pass the validation of the account
+    #[error("invalid signature: {inner}")]
+    Signature { inner: String },
+    /// User operation can't replace the one with the same sender and nonce
+    #[error(transparent)]
+    Replacement(ReplacementError),
     /// Entity role validation
     #[error("A {entity} at {address:?} in this user operation is used as a {entity_other} entity in another useroperation currently in mempool")]
     EntityRoles { entity: String, address: Address, entity_other: String },
@@ -186,12 +307,55 @@ pub enum SanityError {
     },
 }
 
+impl SanityError {
+    /// Name of the error variant (e.g. used as the label of the metrics)
+    pub fn variant(&self) -> &'static str {
+        match self {
+            SanityError::AlreadyKnown { .. } => "AlreadyKnown",
+            SanityError::VerificationGasLimitTooHigh { .. } => "VerificationGasLimitTooHigh",
+            SanityError::VerificationGasLimitTooLow { .. } => "VerificationGasLimitTooLow",
+            SanityError::CombinedGasLimitTooHigh { .. } => "CombinedGasLimitTooHigh",
+            SanityError::PreVerificationGasTooLow { .. } => "PreVerificationGasTooLow",
+            SanityError::CallGasLimitTooLow { .. } => "CallGasLimitTooLow",
+            SanityError::CallGasTooLow { .. } => "CallGasTooLow",
+            SanityError::MaxFeePerGasTooLow { .. } => "MaxFeePerGasTooLow",
+            SanityError::MaxPriorityFeePerGasTooHigh { .. } => "MaxPriorityFeePerGasTooHigh",
+            SanityError::MaxPriorityFeePerGasTooLow { .. } => "MaxPriorityFeePerGasTooLow",
+            SanityError::PaymasterDepositTooLow { .. } => "PaymasterDepositTooLow",
+            SanityError::SenderInsufficientDeposit { .. } => "SenderInsufficientDeposit",
+            SanityError::InitCodeTooShort { .. } => "InitCodeTooShort",
+            SanityError::FactoryHasNoCode { .. } => "FactoryHasNoCode",
+            SanityError::InsufficientPostOpGasReserve { .. } => "InsufficientPostOpGasReserve",
+            SanityError::CallDataGasTooHigh { .. } => "CallDataGasTooHigh",
+            SanityError::CallDataTooLarge { .. } => "CallDataTooLarge",
+            SanityError::InitCodeTooLarge { .. } => "InitCodeTooLarge",
+            SanityError::Paymaster { .. } => "Paymaster",
+            SanityError::Sender { .. } => "Sender",
+            SanityError::NonceTooHigh { .. } => "NonceTooHigh",
+            SanityError::Signature { .. } => "Signature",
+            SanityError::Replacement(_) => "Replacement",
+            SanityError::EntityRoles { .. } => "EntityRoles",
+            SanityError::Reputation(_) => "Reputation",
+            SanityError::Provider { .. } => "Provider",
+            #[cfg(feature = "mdbx")]
+            SanityError::Database(_) => "Database",
+            SanityError::Other { .. } => "Other",
+        }
+    }
+}
+
 impl From<ReputationError> for SanityError {
     fn from(err: ReputationError) -> Self {
         SanityError::Reputation(err)
     }
 }
 
+impl From<ReplacementError> for SanityError {
+    fn from(err: ReplacementError) -> Self {
+        SanityError::Replacement(err)
+    }
+}
+
 impl From<EntryPointError> for SanityError {
     fn from(err: EntryPointError) -> Self {
         match err {
@@ -201,6 +365,20 @@ impl From<EntryPointError> for SanityError {
     }
 }
 
+/// Error when a user operation can't replace another one in the mempool
+#[derive(Debug, Error, Serialize, Deserialize)]
+pub enum ReplacementError {
+    /// User operations have different senders
+    #[error("sender {new:?} doesn't match sender {existing:?} of the replaced user operation")]
+    SenderMismatch { existing: Address, new: Address },
+    /// User operations have different nonces
+    #[error("nonce {new} doesn't match nonce {existing} of the replaced user operation")]
+    NonceMismatch { existing: U256, new: U256 },
+    /// Fee isn't increased enough
+    #[error("couldn't replace user operation: {field} {new_fee} too low, expected at least {required_fee} (was {existing_fee})")]
+    FeeBumpTooLow { field: String, existing_fee: U256, new_fee: U256, required_fee: U256 },
+}
+
 /// Error when simulation fails
 #[derive(Debug, Error, Serialize, Deserialize)]
 pub enum SimulationError {
@@ -228,6 +406,30 @@ pub enum SimulationError {
     /// Errors related to calls
     #[error("Illegal call into {inner}")]
     CallStack { inner: String },
+    /// Entity made too many calls to external contracts
+    #[error("{entity} made {count} external calls, expected at most {max}")]
+    TooManyExternalCalls { count: usize, max: usize, entity: String },
+    /// TIMESTAMP opcode used during validation
+    #[error("{entity} uses banned opcode: TIMESTAMP ({hint})")]
+    TimestampForbidden { entity: String, hint: String },
+    /// Call type that isn't allowed during validation
+    #[error("{entity} used forbidden {call_type} to {to:?}")]
+    ForbiddenCallType { call_type: String, to: Address, entity: String },
+    /// DELEGATECALL to an address that isn't a trusted implementation
+    #[error("{entity} at {from:?} used DELEGATECALL to untrusted {to:?}")]
+    UntrustedDelegatecall { from: Address, to: Address, entity: String },
+    /// Call during validation forwarded too little of the available gas
+    #[error("{entity} forwarded {forwarded} of {available} available gas to a call")]
+    InsufficientGasForwardedToCall { available: u64, forwarded: u64, entity: String },
+    /// Sender transferred ETH (other than the prefund) during validation
+    #[error("sender transferred {amount} wei during validation")]
+    SenderTransfersDuringValidation { amount: U256 },
+    /// Entity called a banned address during validation
+    #[error("{caller_entity} called banned entity {banned_address:?}")]
+    CallToBannedEntity { caller_entity: String, banned_address: Address },
+    /// Signature aggregator rejected the signature of the user operation
+    #[error("signature aggregator {aggregator:?} rejected the signature: {inner}")]
+    AggregatorSignature { aggregator: Address, inner: String },
     /// Codes hashes changed between the first and the second simulations
     #[error("Code hashes changed between the first and the second simulations")]
     CodeHashes,
@@ -255,6 +457,41 @@ pub enum SimulationError {
     },
 }
 
+impl SimulationError {
+    /// Name of the error variant (e.g. used as the label of the metrics)
+    pub fn variant(&self) -> &'static str {
+        match self {
+            SimulationError::Signature => "Signature",
+            SimulationError::Timestamp { .. } => "Timestamp",
+            SimulationError::Validation { .. } => "Validation",
+            SimulationError::Execution { .. } => "Execution",
+            SimulationError::Opcode { .. } => "Opcode",
+            SimulationError::StorageAccess { .. } => "StorageAccess",
+            SimulationError::Unstaked { .. } => "Unstaked",
+            SimulationError::CallStack { .. } => "CallStack",
+            SimulationError::TooManyExternalCalls { .. } => "TooManyExternalCalls",
+            SimulationError::TimestampForbidden { .. } => "TimestampForbidden",
+            SimulationError::ForbiddenCallType { .. } => "ForbiddenCallType",
+            SimulationError::UntrustedDelegatecall { .. } => "UntrustedDelegatecall",
+            SimulationError::InsufficientGasForwardedToCall { .. } => {
+                "InsufficientGasForwardedToCall"
+            }
+            SimulationError::SenderTransfersDuringValidation { .. } => {
+                "SenderTransfersDuringValidation"
+            }
+            SimulationError::CallToBannedEntity { .. } => "CallToBannedEntity",
+            SimulationError::AggregatorSignature { .. } => "AggregatorSignature",
+            SimulationError::CodeHashes => "CodeHashes",
+            SimulationError::OutOfGas => "OutOfGas",
+            SimulationError::Reputation(_) => "Reputation",
+            SimulationError::Provider { .. } => "Provider",
+            #[cfg(feature = "mdbx")]
+            SimulationError::Database(_) => "Database",
+            SimulationError::Other { .. } => "Other",
+        }
+    }
+}
+
 impl From<ReputationError> for SimulationError {
     fn from(err: ReputationError) -> Self {
         SimulationError::Reputation(err)
@@ -12,11 +12,11 @@ use silius_contracts::{
     EntryPoint, EntryPointError, ExecutionResult, FailedOp, UserOperationEventFilter,
     UserOperationRevertReasonFilter,
 };
-use silius_primitives::UserOperationSigned;
-use std::str::FromStr;
+use silius_primitives::{constants::validation::sanity::MIN_VERIFICATION_GAS, UserOperationSigned};
+use std::{future::Future, str::FromStr};
 
 const FALL_BACK_BINARY_SEARCH_CUT_OFF: u128 = 30000;
-const BASE_VGL_BUFFER: u128 = 25;
+const BASE_VGL_BUFFER: u128 = 10;
 const MAX_CALL_GAS_LIMIT: u64 = 18_000_000;
 const MAX_RETRY: u64 = 7;
 const NON_ZERO_GAS: u64 = 12100; // should be different based on diferrent chain
@@ -50,6 +50,76 @@ fn is_execution_revert<T: ToString>(err: T) -> bool {
     err.to_string().contains(EXECUTION_REVERTED)
 }
 
+/// Outcome of simulating the user operation with the gas limit probed by the binary search
+#[derive(Debug)]
+enum GasProbe {
+    /// The simulation succeeded
+    Success,
+    /// The simulation ran out of gas
+    TooLow(EntryPointError),
+    /// The prefund for the gas limit can't be paid
+    TooHigh(EntryPointError),
+}
+
+/// Binary search for the lowest gas limit in `[low, high]` the user operation succeeds with
+///
+/// # Arguments
+/// * `low` - Lower bound of the search
+/// * `high` - Upper bound of the search
+/// * `rounds` - Maximum number of search rounds
+/// * `probe` - Simulates the user operation with the given gas limit
+///
+/// # Returns
+/// The lowest successful gas limit found within `rounds` rounds, otherwise the error of the last
+/// rejected simulation
+async fn binary_search_gas<F, Fut>(
+    mut low: u128,
+    high: u128,
+    rounds: u64,
+    mut probe: F,
+) -> Result<u128, EntryPointError>
+where
+    F: FnMut(u128) -> Fut,
+    Fut: Future<Output = Result<GasProbe, EntryPointError>>,
+{
+    let mut found = None;
+    let mut err = EntryPointError::Other {
+        inner: "Could not find a valid verification gas limit".to_string(),
+    };
+
+    // the limits left to search are `[low, end)`
+    let mut end = high + 1;
+    let mut round = 0;
+    while low < end && round < rounds {
+        round += 1;
+        let mid = low + (end - low) / 2;
+        match probe(mid).await? {
+            GasProbe::Success => {
+                end = mid;
+                found = Some(mid);
+            }
+            GasProbe::TooLow(e) => {
+                low = mid + 1;
+                err = e;
+            }
+            GasProbe::TooHigh(e) => {
+                end = mid;
+                err = e;
+            }
+        }
+    }
+
+    // the rounds ran out before any limit succeeded, the upper bound is the last resort
+    if found.is_none() && low < end {
+        match probe(high).await? {
+            GasProbe::Success => found = Some(high),
+            GasProbe::TooLow(e) | GasProbe::TooHigh(e) => err = e,
+        }
+    }
+
+    found.ok_or(err)
+}
+
 #[derive(Debug, Default)]
 struct TraceOutput {
     tracer_result: ExecutorTracerResult,
@@ -149,9 +219,15 @@ async fn trace_simulate_handle_op<M: Middleware>(
     Ok(TraceOutput { tracer_result, execution_result, user_op_event, user_op_revert_event })
 }
 
+/// Estimates the `verification_gas_limit` and `call_gas_limit` of the user operation
+///
+/// The `verification_gas_limit` is the lowest limit the validation succeeds with (binary search
+/// between the minimum and `max_verification_gas`, in at most `rounds` rounds) plus a buffer.
 pub async fn estimate_user_op_gas<M: Middleware>(
     user_operation_original: &UserOperationSigned,
     entry_point: &EntryPoint<M>,
+    max_verification_gas: U256,
+    rounds: u64,
 ) -> Result<(U256, U256), EntryPointError> {
     let mut iter: u64 = 0;
 
@@ -160,43 +236,24 @@ pub async fn estimate_user_op_gas<M: Middleware>(
     user_operation.call_gas_limit = 0.into();
     user_operation.max_priority_fee_per_gas = user_operation_original.max_fee_per_gas;
 
-    // Binary search
-    let mut l: u128 = 0;
-    let mut r: u128 = u64::MAX.into();
-    let mut f: u128 = 0;
-
-    let mut err = EntryPointError::Other {
-        inner: "Could not find a valid verification gas limit".to_string(),
-    };
-
-    while r - l >= FALL_BACK_BINARY_SEARCH_CUT_OFF {
-        let m = (l + r) / 2;
-        user_operation.verification_gas_limit = m.into();
-        match entry_point.simulate_handle_op(user_operation.clone()).await {
-            // VGL too high
-            Ok(_) => {
-                r = m - 1;
-                f = m;
-                continue;
-            }
-            Err(e) => {
-                err = e.clone();
-                if is_prefund_not_paid(&e) {
-                    r = m - 1;
-                    continue;
-                } else if is_validation_oog(&e) {
-                    l = m + 1;
-                    continue;
-                } else {
-                    return Err(e);
+    let mut f = binary_search_gas(
+        MIN_VERIFICATION_GAS.into(),
+        max_verification_gas.min(u64::MAX.into()).as_u128(),
+        rounds,
+        |verification_gas_limit| {
+            let mut uo = user_operation.clone();
+            uo.verification_gas_limit = verification_gas_limit.into();
+            async move {
+                match entry_point.simulate_handle_op(uo).await {
+                    Ok(_) => Ok(GasProbe::Success),
+                    Err(e) if is_prefund_not_paid(&e) => Ok(GasProbe::TooHigh(e)),
+                    Err(e) if is_validation_oog(&e) => Ok(GasProbe::TooLow(e)),
+                    Err(e) => Err(e),
                 }
             }
-        }
-    }
-
-    if f == 0 {
-        return Err(err);
-    }
+        },
+    )
+    .await?;
 
     let out: TraceOutput;
     let mut res: Result<(U256, U256), EntryPointError> = Ok((0u64.into(), 0u64.into()));
@@ -287,8 +344,62 @@ pub async fn estimate_user_op_gas<M: Middleware>(
 mod tests {
     use super::*;
     use ethers::providers::{Http, Provider};
+    use silius_primitives::constants::mempool::ESTIMATION_BINARY_SEARCH_ROUNDS;
     use std::sync::Arc;
 
+    /// Runs the binary search against a user operation that needs `required` gas
+    async fn search(low: u128, high: u128, required: u128) -> (Result<u128, EntryPointError>, u64) {
+        let mut probes = 0;
+        let res = binary_search_gas(low, high, ESTIMATION_BINARY_SEARCH_ROUNDS, |gas| {
+            probes += 1;
+            async move {
+                if gas >= required {
+                    Ok(GasProbe::Success)
+                } else {
+                    Ok(GasProbe::TooLow(EntryPointError::Other { inner: "OOG".into() }))
+                }
+            }
+        })
+        .await;
+        (res, probes)
+    }
+
+    #[tokio::test]
+    async fn binary_search_converges() {
+        let low = u128::from(MIN_VERIFICATION_GAS);
+        // 2^16 - 1 limits to search
+        let high = low + (1 << ESTIMATION_BINARY_SEARCH_ROUNDS) - 2;
+
+        for required in [low, low + 1, low + 12_345, low + 40_000, high] {
+            let (res, probes) = search(low, high, required).await;
+            assert_eq!(res.unwrap(), required);
+            assert!(probes <= ESTIMATION_BINARY_SEARCH_ROUNDS);
+        }
+    }
+
+    #[tokio::test]
+    async fn binary_search_capped_rounds() {
+        let low = u128::from(MIN_VERIFICATION_GAS);
+        let high = 10_000_000;
+        let required = 1_234_567;
+
+        let (res, probes) = search(low, high, required).await;
+        let res = res.unwrap();
+        assert_eq!(probes, ESTIMATION_BINARY_SEARCH_ROUNDS);
+        // after the last round the lowest successful limit is within one step of the minimum
+        assert!(
+            res >= required && res - required <= (high - low) >> ESTIMATION_BINARY_SEARCH_ROUNDS
+        );
+
+        // the upper bound is simulated once the rounds run out
+        let (res, probes) = search(low, high, high).await;
+        assert_eq!(res.unwrap(), high);
+        assert_eq!(probes, ESTIMATION_BINARY_SEARCH_ROUNDS + 1);
+
+        let (res, _) = search(low, high, high + 1).await;
+        assert!(matches!(res, Err(EntryPointError::Other { inner }) if inner == "OOG"));
+    }
+
     #[tokio::test]
     #[ignore]
     async fn estimate_user_operation_gas() {
@@ -312,7 +423,9 @@ mod tests {
             signature: "0xcbe8b7855dc1481374c37579f953876b778a4ee16f5408b18894d2306977651498b79128e5fedab6855d6b16f8466e8247e4ba601989d1c5fd24194b01b5e8514d".parse().unwrap(),
         };
 
-        let res = estimate_user_op_gas(&uo, &ep).await;
+        let res =
+            estimate_user_op_gas(&uo, &ep, U256::from(5_000_000), ESTIMATION_BINARY_SEARCH_ROUNDS)
+                .await;
         assert!(res.is_err());
     }
 }
@@ -2,6 +2,7 @@
 #![allow(dead_code)]
 
 mod builder;
+mod bundle_selector;
 #[cfg(feature = "mdbx")]
 mod database;
 pub mod error;
@@ -9,31 +10,50 @@ mod estimate;
 mod memory;
 mod mempool;
 pub mod metrics;
+#[cfg(feature = "redis")]
+pub mod redis;
+mod replacement;
 mod reputation;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
 mod uopool;
 mod utils;
 pub mod validate;
 
+#[cfg(feature = "redis")]
+pub use self::redis::{RedisEnv, RedisTable};
 pub use builder::UoPoolBuilder;
+pub use bundle_selector::{
+    new_bundle_selector, BundleSelector, GreedyBundleSelector, KnapsackSelector,
+    SimulationValidatedSelector, KNAPSACK_GAS_BUCKETS,
+};
 #[cfg(feature = "mdbx")]
 pub use database::{
     init_env,
     tables::{
-        CodeHashes, EntitiesReputation, UserOperations, UserOperationsByEntity,
-        UserOperationsBySender,
+        BlacklistedEntities, CodeHashes, EntitiesReputation, UserOperationAggregators,
+        UserOperationReceipts, UserOperations, UserOperationsByAggregator, UserOperationsByEntity,
+        UserOperationsByNonce, UserOperationsBySender, WhitelistedEntities,
     },
     DatabaseError, DatabaseTable, WriteMap,
 };
 pub use error::{
-    InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, ReputationError, SanityError,
-    SimulationError,
+    InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, ReplacementError,
+    ReputationError, SanityError, SimulationError,
 };
 pub use mempool::{
-    mempool_id, AddRemoveUserOp, AddRemoveUserOpHash, ClearOp, Mempool, MempoolId,
-    UserOperationAct, UserOperationAddrAct, UserOperationAddrOp, UserOperationCodeHashAct,
-    UserOperationCodeHashOp, UserOperationOp,
+    mempool_id, AddRemoveUserOp, AddRemoveUserOpHash, CapacityTracker, ClearOp, EntityGraph,
+    Mempool, MempoolId, UserOperationAct, UserOperationAddrAct, UserOperationAddrOp,
+    UserOperationCodeHashAct, UserOperationCodeHashOp, UserOperationNonceAct, UserOperationNonceOp,
+    UserOperationOp, UserOperationReceiptAct, UserOperationReceiptOp,
 };
-pub use reputation::{HashSetOp, Reputation, ReputationEntryOp};
+pub use replacement::ReplacementPolicy;
+pub use reputation::{EntityListOp, HashSetOp, Reputation, ReputationEntryOp, StakeRequirements};
+#[cfg(feature = "sqlite")]
+pub use sqlite::{SqliteEnv, SqliteTable};
 pub use uopool::UoPool;
-pub use utils::Overhead;
+pub use utils::{
+    calculate_pre_verification_gas_pectra, CalldataPricing, ChainPreVerificationGas,
+    EntryPointOverhead, Overhead, PectraOverhead,
+};
 pub use validate::{SanityCheck, SimulationCheck, SimulationTraceCheck};
@@ -1,20 +1,22 @@
 use crate::{
     mempool::{
         AddRemoveUserOp, AddRemoveUserOpHash, ClearOp, UserOperationAddrOp,
-        UserOperationCodeHashOp, UserOperationOp,
+        UserOperationCodeHashOp, UserOperationNonceOp, UserOperationOp, UserOperationReceiptOp,
     },
+    utils::sort_by_effective_gas_price,
     MempoolErrorKind,
 };
-use ethers::types::Address;
+use ethers::types::{Address, U256};
 use silius_primitives::{
-    simulation::CodeHash, UserOperation, UserOperationHash, UserOperationSigned,
+    simulation::CodeHash, UserOperation, UserOperationHash, UserOperationReceipt,
 };
 use std::collections::{HashMap, HashSet};
 
-impl AddRemoveUserOp for HashMap<UserOperationHash, UserOperationSigned> {
+impl AddRemoveUserOp for HashMap<UserOperationHash, UserOperation> {
     fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
-        self.insert(uo.hash, uo.user_operation);
-        Ok(uo.hash)
+        let uo_hash = uo.hash;
+        self.insert(uo_hash, uo);
+        Ok(uo_hash)
     }
 
     fn remove_by_uo_hash(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
@@ -26,40 +28,39 @@ impl AddRemoveUserOp for HashMap<UserOperationHash, UserOperationSigned> {
         self.remove(uo_hash);
         Ok(true)
     }
+
+    fn replace(
+        &mut self,
+        prev_hash: &UserOperationHash,
+        uo: UserOperation,
+    ) -> Result<UserOperationHash, MempoolErrorKind> {
+        self.remove(prev_hash);
+        let uo_hash = uo.hash;
+        self.insert(uo_hash, uo);
+        Ok(uo_hash)
+    }
 }
 
-impl UserOperationOp for HashMap<UserOperationHash, UserOperationSigned> {
+impl UserOperationOp for HashMap<UserOperationHash, UserOperation> {
     fn get_by_uo_hash(
         &self,
         uo_hash: &UserOperationHash,
     ) -> Result<Option<UserOperation>, MempoolErrorKind> {
-        if let Some(uo) = self.get(uo_hash) {
-            Ok(Some(UserOperation::from_user_operation_signed(*uo_hash, uo.clone())))
-        } else {
-            Ok(None)
-        }
+        Ok(self.get(uo_hash).cloned())
     }
 
-    fn get_sorted(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
-        let mut uos: Vec<UserOperation> = self
-            .iter()
-            .map(|(hash, uo)| UserOperation::from_user_operation_signed(*hash, uo.clone()))
-            .collect();
-        uos.sort_by(|a, b| {
-            if a.max_priority_fee_per_gas != b.max_priority_fee_per_gas {
-                b.max_priority_fee_per_gas.cmp(&a.max_priority_fee_per_gas)
-            } else {
-                a.nonce.cmp(&b.nonce)
-            }
-        });
+    fn get_sorted(&self, base_fee_per_gas: U256) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let mut uos: Vec<UserOperation> = self.values().cloned().collect();
+        sort_by_effective_gas_price(&mut uos, base_fee_per_gas);
         Ok(uos)
     }
 
     fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
-        Ok(self
-            .iter()
-            .map(|(hash, uo)| UserOperation::from_user_operation_signed(*hash, uo.clone()))
-            .collect())
+        Ok(self.values().cloned().collect())
+    }
+
+    fn len(&self) -> Result<usize, MempoolErrorKind> {
+        Ok(HashMap::len(self))
     }
 }
 
@@ -135,13 +136,78 @@ impl UserOperationCodeHashOp for HashMap<UserOperationHash, Vec<CodeHash>> {
     }
 }
 
+impl UserOperationNonceOp for HashMap<(Address, U256), UserOperationHash> {
+    fn add(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        self.insert((*sender, nonce), uo_hash);
+        Ok(())
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let key = (*sender, nonce);
+        if self.get(&key) == Some(uo_hash) {
+            self.remove(&key);
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn get_by_sender_and_nonce(
+        &self,
+        sender: &Address,
+        nonce: U256,
+    ) -> Result<Option<UserOperationHash>, MempoolErrorKind> {
+        Ok(self.get(&(*sender, nonce)).cloned())
+    }
+}
+
+impl UserOperationReceiptOp for HashMap<UserOperationHash, UserOperationReceipt> {
+    fn add_receipt(&mut self, receipt: UserOperationReceipt) -> Result<(), MempoolErrorKind> {
+        self.insert(receipt.user_operation_hash, receipt);
+        Ok(())
+    }
+
+    fn get_receipt(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperationReceipt>, MempoolErrorKind> {
+        Ok(self.get(uo_hash).cloned())
+    }
+
+    fn remove_receipt(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        Ok(self.remove(uo_hash).is_some())
+    }
+}
+
+impl ClearOp for HashMap<UserOperationHash, UserOperationReceipt> {
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
+impl ClearOp for HashMap<(Address, U256), UserOperationHash> {
+    fn clear(&mut self) {
+        self.clear()
+    }
+}
+
 impl ClearOp for HashMap<UserOperationHash, Vec<CodeHash>> {
     fn clear(&mut self) {
         self.clear()
     }
 }
 
-impl ClearOp for HashMap<UserOperationHash, UserOperationSigned> {
+impl ClearOp for HashMap<UserOperationHash, UserOperation> {
     fn clear(&mut self) {
         self.clear()
     }
@@ -156,17 +222,261 @@ impl ClearOp for HashMap<Address, HashSet<UserOperationHash>> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{utils::tests::mempool_test_case, Mempool};
+    use crate::{
+        utils::tests::{
+            aggregator_test_case, capacity_tracker_test_case,
+            concurrent_update_user_operation_test_case, expiry_test_case, mempool_test_case,
+            receipts_test_case, sort_by_effective_gas_price_test_case,
+            update_user_operation_test_case,
+        },
+        Mempool,
+    };
+    use ethers::types::H256;
+    use parking_lot::RwLock;
+    use silius_primitives::UserOperationSigned;
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
 
     #[allow(clippy::unit_cmp)]
     #[tokio::test]
     async fn memory_mempool() {
         let mempool = Mempool::new(
-            Box::new(HashMap::<UserOperationHash, UserOperationSigned>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperation>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
             Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
             Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
             Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
         );
         mempool_test_case(mempool);
     }
+
+    #[tokio::test]
+    async fn memory_mempool_capacity_tracker() {
+        let mempool = Mempool::new(
+            Box::new(HashMap::<UserOperationHash, UserOperation>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
+        );
+        capacity_tracker_test_case(mempool);
+    }
+
+    /// Memory table of the user operations that fails the writes once `reject` is set
+    #[derive(Clone, Default)]
+    struct RejectingUserOperations {
+        uos: HashMap<UserOperationHash, UserOperation>,
+        reject: Arc<AtomicBool>,
+    }
+
+    impl RejectingUserOperations {
+        fn check(&self) -> Result<(), MempoolErrorKind> {
+            if self.reject.load(Ordering::SeqCst) {
+                return Err(MempoolErrorKind::Other { inner: "write rejected".into() });
+            }
+            Ok(())
+        }
+    }
+
+    impl AddRemoveUserOp for RejectingUserOperations {
+        fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
+            self.check()?;
+            self.uos.add(uo)
+        }
+
+        fn remove_by_uo_hash(
+            &mut self,
+            uo_hash: &UserOperationHash,
+        ) -> Result<bool, MempoolErrorKind> {
+            self.uos.remove_by_uo_hash(uo_hash)
+        }
+
+        fn replace(
+            &mut self,
+            prev_hash: &UserOperationHash,
+            uo: UserOperation,
+        ) -> Result<UserOperationHash, MempoolErrorKind> {
+            self.check()?;
+            self.uos.replace(prev_hash, uo)
+        }
+    }
+
+    impl UserOperationOp for RejectingUserOperations {
+        fn get_by_uo_hash(
+            &self,
+            uo_hash: &UserOperationHash,
+        ) -> Result<Option<UserOperation>, MempoolErrorKind> {
+            self.uos.get_by_uo_hash(uo_hash)
+        }
+
+        fn get_sorted(
+            &self,
+            base_fee_per_gas: U256,
+        ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+            self.uos.get_sorted(base_fee_per_gas)
+        }
+
+        fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+            self.uos.get_all()
+        }
+
+        fn len(&self) -> Result<usize, MempoolErrorKind> {
+            self.uos.len()
+        }
+    }
+
+    impl ClearOp for RejectingUserOperations {
+        fn clear(&mut self) {
+            self.uos.clear()
+        }
+    }
+
+    #[tokio::test]
+    async fn memory_mempool_capacity_failed_write() {
+        let reject = Arc::new(AtomicBool::new(false));
+        let mut mempool = Mempool::new(
+            Box::new(RejectingUserOperations { reject: reject.clone(), ..Default::default() }),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
+        );
+
+        let sender = Address::random();
+        let paymaster = Address::random();
+        let uo = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::default()
+                .sender(sender)
+                .paymaster_and_data(paymaster.as_bytes().to_vec().into()),
+        );
+        mempool.add(uo.clone()).unwrap();
+
+        reject.store(true, Ordering::SeqCst);
+
+        // the failed add isn't counted and adds no edge
+        let other = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::default()
+                .sender(Address::random())
+                .paymaster_and_data(paymaster.as_bytes().to_vec().into()),
+        );
+        assert!(mempool.add(other.clone()).is_err());
+        assert_eq!(mempool.get_sender_count(&other.sender), 0);
+        assert!(mempool.get_entities_for_sender(&other.sender).is_empty());
+        assert_eq!(mempool.get_senders_for_entity(&paymaster), vec![sender]);
+
+        // the failed replacement keeps the counts and the edges of the previous user operation
+        let replacement = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::default().sender(sender).max_fee_per_gas(1.into()),
+        );
+        assert!(mempool.update_user_operation(&uo.hash, replacement).is_err());
+        assert_eq!(mempool.get_sender_count(&sender), 1);
+        assert_eq!(mempool.get_entity_count(&paymaster), 1);
+        assert_eq!(mempool.get_entities_for_sender(&sender), vec![paymaster]);
+    }
+
+    #[tokio::test]
+    async fn memory_mempool_sort_by_effective_gas_price() {
+        let mempool = Mempool::new(
+            Box::new(HashMap::<UserOperationHash, UserOperation>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
+        );
+        sort_by_effective_gas_price_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn memory_mempool_expiry() {
+        let mempool = Mempool::new(
+            Box::new(HashMap::<UserOperationHash, UserOperation>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
+        );
+        expiry_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn memory_mempool_receipts() {
+        let mempool = Mempool::new(
+            Box::new(HashMap::<UserOperationHash, UserOperation>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
+        );
+        receipts_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn memory_mempool_update_user_operation() {
+        let mempool = Mempool::new(
+            Box::new(HashMap::<UserOperationHash, UserOperation>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
+        );
+        update_user_operation_test_case(mempool);
+    }
+
+    #[tokio::test]
+    async fn memory_mempool_aggregator() {
+        let mempool = Mempool::new(
+            Box::new(HashMap::<UserOperationHash, UserOperation>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
+        );
+        aggregator_test_case(mempool);
+    }
+
+    #[test]
+    fn memory_mempool_concurrent_update_user_operation() {
+        // the tables are shared by the clones of the mempool, like in the bundler
+        let mempool = Mempool::new(
+            Box::new(Arc::new(RwLock::new(HashMap::<UserOperationHash, UserOperation>::default()))),
+            Box::new(Arc::new(RwLock::new(
+                HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            ))),
+            Box::new(Arc::new(RwLock::new(
+                HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            ))),
+            Box::new(Arc::new(RwLock::new(
+                HashMap::<Address, HashSet<UserOperationHash>>::default(),
+            ))),
+            Box::new(Arc::new(RwLock::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()))),
+            Box::new(Arc::new(RwLock::new(
+                HashMap::<(Address, U256), UserOperationHash>::default(),
+            ))),
+            Box::new(Arc::new(RwLock::new(
+                HashMap::<UserOperationHash, UserOperationReceipt>::default(),
+            ))),
+        );
+        concurrent_update_user_operation_test_case(mempool);
+    }
 }
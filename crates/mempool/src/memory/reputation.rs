@@ -49,14 +49,17 @@ impl ReputationEntryOp for HashMap<Address, ReputationEntry> {
 }
 #[cfg(test)]
 mod tests {
-    use crate::{utils::tests::reputation_test_case, Reputation};
+    use crate::{
+        utils::tests::reputation_test_case, Reputation, ReputationError, StakeRequirements,
+    };
     use ethers::types::{Address, U256};
     use parking_lot::RwLock;
     use silius_primitives::{
-        constants::validation::reputation::{
-            BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK,
+        constants::validation::{
+            entities::{FACTORY, PAYMASTER, SENDER},
+            reputation::{BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK},
         },
-        reputation::ReputationEntry,
+        reputation::{ReputationEntry, StakeInfo, Status},
     };
     use std::{
         collections::{HashMap, HashSet},
@@ -78,4 +81,110 @@ mod tests {
         );
         reputation_test_case(reputation);
     }
+
+    #[tokio::test]
+    async fn memory_reputation_status_with_reason() {
+        let entry: Box<HashMap<Address, ReputationEntry>> = Box::new(HashMap::default());
+        let mut reputation = Reputation::new(
+            MIN_INCLUSION_RATE_DENOMINATOR,
+            THROTTLING_SLACK,
+            BAN_SLACK,
+            U256::from(1),
+            U256::from(0),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            entry,
+        );
+        let (paymaster, factory) = (Address::random(), Address::random());
+
+        let status = reputation.get_status_with_reason(&paymaster).unwrap();
+        assert_eq!((status.status, status.inclusion_rate, status.reason), (Status::OK, 1.0, None));
+
+        // of 1000 seen user operations, at least 99 have to be included to avoid throttling and
+        // at least 95 to avoid the ban
+        reputation
+            .set_entities(vec![
+                ReputationEntry { address: paymaster, uo_seen: 1000, uo_included: 10, status: 0 },
+                ReputationEntry { address: factory, uo_seen: 1000, uo_included: 96, status: 0 },
+            ])
+            .unwrap();
+        let status = reputation.get_status_with_reason(&paymaster).unwrap();
+        assert_eq!(status.status, Status::BANNED);
+        assert_eq!(status.status, Status::from(reputation.get_status(&paymaster).unwrap()));
+        assert_eq!((status.inclusion_rate, status.threshold), (0.01, 0.095));
+        assert!(status.reason.unwrap().contains("below the ban threshold"));
+
+        let status = reputation.get_status_with_reason(&factory).unwrap();
+        assert_eq!(status.status, Status::THROTTLED);
+        assert_eq!((status.inclusion_rate, status.threshold), (0.096, 0.099));
+        assert_eq!(
+            status.reason.unwrap(),
+            "inclusion rate 9.6% (96 of 1000 user operations included) is below the throttling \
+             threshold 9.9%"
+        );
+
+        reputation.add_blacklist(&factory);
+        let status = reputation.get_status_with_reason(&factory).unwrap();
+        assert_eq!((status.status, status.reason), (Status::BANNED, Some("blacklisted".into())));
+    }
+
+    #[tokio::test]
+    async fn memory_reputation_entity_stake_requirements() {
+        let entry: Box<HashMap<Address, ReputationEntry>> = Box::new(HashMap::default());
+        let reputation = Reputation::new(
+            MIN_INCLUSION_RATE_DENOMINATOR,
+            THROTTLING_SLACK,
+            BAN_SLACK,
+            U256::from(1),
+            U256::from(0),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            entry,
+        )
+        .with_entity_stake_requirements(HashMap::from([
+            (
+                PAYMASTER.to_string(),
+                StakeRequirements {
+                    min_stake: U256::from(100),
+                    min_unstake_delay: Some(U256::from(10)),
+                },
+            ),
+            (
+                SENDER.to_string(),
+                StakeRequirements { min_stake: U256::from(10), min_unstake_delay: None },
+            ),
+            ("unknown".to_string(), StakeRequirements::default()),
+        ]));
+        let info = StakeInfo {
+            address: Address::random(),
+            stake: U256::from(50),
+            unstake_delay: 5.into(),
+        };
+
+        // the factory has the default requirements
+        assert!(reputation.verify_stake(FACTORY, Some(info), None, None).is_ok());
+        assert_eq!(reputation.stake_requirements(FACTORY).min_stake, U256::from(1));
+
+        assert!(matches!(
+            reputation.verify_stake(PAYMASTER, Some(info), None, None),
+            Err(ReputationError::StakeTooLow { min_stake, .. }) if min_stake == U256::from(100)
+        ));
+        let info = StakeInfo { stake: U256::from(100), ..info };
+        assert!(matches!(
+            reputation.verify_stake(PAYMASTER, Some(info), None, None),
+            Err(ReputationError::UnstakeDelayTooLow { .. })
+        ));
+        let info = StakeInfo { unstake_delay: 10.into(), ..info };
+        assert!(reputation.verify_stake(PAYMASTER, Some(info), None, None).is_ok());
+
+        // only the minimum stake is set for the account, the unstake delay isn't enforced
+        let info = StakeInfo { stake: U256::from(10), unstake_delay: 5.into(), ..info };
+        assert!(reputation.verify_stake(SENDER, Some(info), None, None).is_ok());
+        assert_eq!(reputation.stake_requirements(SENDER).min_unstake_delay, None);
+
+        // the minimum stake of the mempool (p2p) takes precedence
+        assert!(reputation
+            .verify_stake(PAYMASTER, Some(info), Some(U256::from(1000)), None)
+            .is_err());
+    }
 }
@@ -0,0 +1,89 @@
+use dashmap::DashMap;
+use ethers::types::Address;
+use silius_primitives::UserOperation;
+
+/// In-memory number of user operations per sender and per entity (factory and paymaster) in the
+/// mempool, so that sanity checks don't have to query the mempool storage (which is expensive
+/// for the database mempool)
+#[derive(Debug, Default)]
+pub struct CapacityTracker {
+    sender_counts: DashMap<Address, u32>,
+    entity_counts: DashMap<Address, u32>,
+    paymaster_counts: DashMap<Address, u32>,
+}
+
+impl CapacityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the tracker from the user operations already in the mempool
+    pub fn from_user_operations(uos: &[UserOperation]) -> Self {
+        let tracker = Self::new();
+        for uo in uos {
+            tracker.add(uo);
+        }
+        tracker
+    }
+
+    fn increment(counts: &DashMap<Address, u32>, addr: Address) {
+        *counts.entry(addr).or_default() += 1;
+    }
+
+    fn decrement(counts: &DashMap<Address, u32>, addr: &Address) {
+        counts.remove_if_mut(addr, |_, count| {
+            *count = count.saturating_sub(1);
+            *count == 0
+        });
+    }
+
+    /// Counts the user operation added to the mempool
+    pub fn add(&self, uo: &UserOperation) {
+        let (sender, factory, paymaster) = uo.get_entities();
+        Self::increment(&self.sender_counts, sender);
+        for entity in [factory, paymaster].into_iter().flatten() {
+            Self::increment(&self.entity_counts, entity);
+        }
+        if let Some(paymaster) = paymaster {
+            Self::increment(&self.paymaster_counts, paymaster);
+        }
+    }
+
+    /// Stops counting the user operation removed from the mempool
+    pub fn remove(&self, uo: &UserOperation) {
+        let (sender, factory, paymaster) = uo.get_entities();
+        Self::decrement(&self.sender_counts, &sender);
+        for entity in [factory, paymaster].into_iter().flatten() {
+            Self::decrement(&self.entity_counts, &entity);
+        }
+        if let Some(paymaster) = paymaster {
+            Self::decrement(&self.paymaster_counts, &paymaster);
+        }
+    }
+
+    /// Number of user operations of the sender
+    pub fn get_sender_count(&self, addr: &Address) -> u32 {
+        self.sender_counts.get(addr).map(|count| *count).unwrap_or_default()
+    }
+
+    /// Number of user operations using the address as a factory or paymaster
+    pub fn get_entity_count(&self, addr: &Address) -> u32 {
+        self.entity_counts.get(addr).map(|count| *count).unwrap_or_default()
+    }
+
+    /// Number of distinct senders of the user operations
+    pub fn num_senders(&self) -> usize {
+        self.sender_counts.len()
+    }
+
+    /// Number of distinct paymasters used by the user operations
+    pub fn num_paymasters(&self) -> usize {
+        self.paymaster_counts.len()
+    }
+
+    pub fn clear(&self) {
+        self.sender_counts.clear();
+        self.entity_counts.clear();
+        self.paymaster_counts.clear();
+    }
+}
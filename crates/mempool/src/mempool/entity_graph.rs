@@ -0,0 +1,101 @@
+use ethers::types::Address;
+use parking_lot::RwLock;
+use silius_primitives::UserOperation;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Default)]
+struct Edges {
+    // sender -> factories and paymasters of its user operations
+    entities_by_sender: HashMap<Address, HashSet<Address>>,
+    // factory or paymaster -> senders of the user operations using it
+    senders_by_entity: HashMap<Address, HashSet<Address>>,
+    // number of user operations behind each (sender, entity) edge
+    counts: HashMap<(Address, Address), u32>,
+}
+
+/// In-memory relationships between the senders and the entities (factory and paymaster) of the
+/// user operations in the mempool. An edge is removed with the last user operation behind it.
+#[derive(Debug, Default)]
+pub struct EntityGraph {
+    edges: RwLock<Edges>,
+}
+
+impl EntityGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates the graph from the user operations already in the mempool
+    pub fn from_user_operations(uos: &[UserOperation]) -> Self {
+        let graph = Self::new();
+        for uo in uos {
+            graph.add(uo);
+        }
+        graph
+    }
+
+    /// Adds the edges of the user operation added to the mempool
+    pub fn add(&self, uo: &UserOperation) {
+        let (sender, factory, paymaster) = uo.get_entities();
+        let mut edges = self.edges.write();
+        for entity in [factory, paymaster].into_iter().flatten() {
+            *edges.counts.entry((sender, entity)).or_default() += 1;
+            edges.entities_by_sender.entry(sender).or_default().insert(entity);
+            edges.senders_by_entity.entry(entity).or_default().insert(sender);
+        }
+    }
+
+    /// Removes the edges of the user operation removed from the mempool (if no other user
+    /// operation is behind them)
+    pub fn remove(&self, uo: &UserOperation) {
+        let (sender, factory, paymaster) = uo.get_entities();
+        let mut edges = self.edges.write();
+        for entity in [factory, paymaster].into_iter().flatten() {
+            let Some(count) = edges.counts.get_mut(&(sender, entity)) else {
+                continue;
+            };
+            *count = count.saturating_sub(1);
+            if *count > 0 {
+                continue;
+            }
+
+            edges.counts.remove(&(sender, entity));
+            if let Some(entities) = edges.entities_by_sender.get_mut(&sender) {
+                entities.remove(&entity);
+                if entities.is_empty() {
+                    edges.entities_by_sender.remove(&sender);
+                }
+            }
+            if let Some(senders) = edges.senders_by_entity.get_mut(&entity) {
+                senders.remove(&sender);
+                if senders.is_empty() {
+                    edges.senders_by_entity.remove(&entity);
+                }
+            }
+        }
+    }
+
+    /// Factories and paymasters used by the user operations of the sender
+    pub fn get_entities_for_sender(&self, addr: &Address) -> Vec<Address> {
+        self.edges
+            .read()
+            .entities_by_sender
+            .get(addr)
+            .map(|entities| entities.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Senders of the user operations using the address as a factory or paymaster
+    pub fn get_senders_for_entity(&self, addr: &Address) -> Vec<Address> {
+        self.edges
+            .read()
+            .senders_by_entity
+            .get(addr)
+            .map(|senders| senders.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&self) {
+        *self.edges.write() = Edges::default();
+    }
+}
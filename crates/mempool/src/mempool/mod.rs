@@ -0,0 +1,1087 @@
+use crate::MempoolErrorKind;
+use dashmap::DashMap;
+use dyn_clone::DynClone;
+use ethers::{
+    abi::AbiEncode,
+    types::{Address, H256, U256},
+    utils::{keccak256, to_checksum},
+};
+use parking_lot::RwLock;
+use silius_primitives::{
+    simulation::CodeHash, MempoolStats, UserOperation, UserOperationHash, UserOperationReceipt,
+};
+use std::{
+    collections::HashSet,
+    sync::Arc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+mod capacity_tracker;
+mod entity_graph;
+
+pub use capacity_tracker::CapacityTracker;
+pub use entity_graph::EntityGraph;
+
+pub type MempoolId = H256;
+
+pub fn mempool_id(ep: &Address, chain_id: u64) -> MempoolId {
+    H256::from_slice(
+        keccak256([to_checksum(ep, None).encode(), U256::from(chain_id).encode()].concat())
+            .as_slice(),
+    )
+}
+
+/// AddRemoveUserOp describe the ability to add and remove user operation
+pub trait AddRemoveUserOp {
+    /// Adds a [UserOperation](UserOperation) to the mempool
+    ///
+    /// # Arguments
+    /// * `uo` - The [UserOperation](UserOperation) to add
+    ///
+    /// # Returns
+    /// * `Ok(UserOperationHash)` - The hash of the [UserOperation](UserOperation) that was added
+    /// * `Err(MempoolErrorKind)` - If the [UserOperation](UserOperation) could not be added
+    fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind>;
+    /// Removes a [UserOperation](UserOperation) by its hash
+    ///
+    /// # Arguments
+    /// * `uo_hash` - The hash of the [UserOperation](UserOperation) to remove
+    ///
+    /// # Returns
+    /// * `Ok(bool)` - true if the [UserOperation](UserOperation) was removed, false means it was
+    ///   not found
+    /// * `Err(MempoolErrorKind)` - If there are some  internal errors
+    fn remove_by_uo_hash(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind>;
+    /// Replaces a [UserOperation](UserOperation) with another one (with the same sender and
+    /// nonce). Backends that can do it atomically override it, so the table never misses or
+    /// holds both of them.
+    ///
+    /// # Arguments
+    /// * `prev_hash` - The hash of the [UserOperation](UserOperation) being replaced
+    /// * `uo` - The replacing [UserOperation](UserOperation)
+    ///
+    /// # Returns
+    /// * `Ok(UserOperationHash)` - The hash of the replacing [UserOperation](UserOperation)
+    /// * `Err(MempoolErrorKind)` - If the [UserOperation](UserOperation) could not be replaced
+    fn replace(
+        &mut self,
+        prev_hash: &UserOperationHash,
+        uo: UserOperation,
+    ) -> Result<UserOperationHash, MempoolErrorKind> {
+        let uo_hash = self.add(uo)?;
+        if uo_hash != *prev_hash {
+            self.remove_by_uo_hash(prev_hash)?;
+        }
+        Ok(uo_hash)
+    }
+}
+
+impl<T: AddRemoveUserOp> AddRemoveUserOp for Arc<RwLock<T>> {
+    fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
+        self.write().add(uo)
+    }
+
+    fn remove_by_uo_hash(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        self.write().remove_by_uo_hash(uo_hash)
+    }
+
+    fn replace(
+        &mut self,
+        prev_hash: &UserOperationHash,
+        uo: UserOperation,
+    ) -> Result<UserOperationHash, MempoolErrorKind> {
+        self.write().replace(prev_hash, uo)
+    }
+}
+
+/// AddRemoveUserOpHash describe the ability to add and remove user operation hash set
+/// associated with an address
+pub trait AddRemoveUserOpHash {
+    ///
+    /// Adds a user operation hash to the an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address associated with the user operation.
+    /// * `uo_hash` - The hash of the user operation.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the user operation hash was successfully added to the mempool,
+    /// otherwise returns an error of type `MempoolErrorKind`.
+    fn add(
+        &mut self,
+        address: &Address,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind>;
+
+    /// Removes a user operation hash from an address.
+    ///
+    /// This function removes the specified user operation hash from the mempool
+    /// associated with the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - The address associated with the mempool.
+    /// * `uo_hash` - The user operation hash to be removed.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating whether the removal was successful or not.
+    /// - If the user operation hash was successfully removed, `Ok(true)` is returned.
+    /// - If the user operation hash was not found in the mempool, `Ok(false)` is returned.
+    /// - If an error occurred during the removal process, an `Err` variant is returned.
+    fn remove_uo_hash(
+        &mut self,
+        address: &Address,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind>;
+}
+
+impl<T: AddRemoveUserOpHash> AddRemoveUserOpHash for Arc<RwLock<T>> {
+    fn add(
+        &mut self,
+        address: &Address,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        self.write().add(address, uo_hash)
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        address: &Address,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        self.write().remove_uo_hash(address, uo_hash)
+    }
+}
+
+/// Trait representing operations on user operations.
+pub trait UserOperationOp {
+    /// Retrieves a user operation by its hash.
+    ///
+    /// # Arguments
+    ///
+    /// * `uo_hash` - The hash of the user operation.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(UserOperation))` if the user operation is found,
+    /// `Ok(None)` if the user operation is not found, or an `Err(MempoolErrorKind)` if an error
+    /// occurs.
+    fn get_by_uo_hash(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperation>, MempoolErrorKind>;
+
+    /// Retrieves all user operations sorted by the effective gas price
+    /// (`min(max_fee_per_gas, max_priority_fee_per_gas + base_fee_per_gas)`).
+    ///
+    /// # Arguments
+    ///
+    /// * `base_fee_per_gas` - The current base fee per gas.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<UserOperation>)` containing all user operations sorted in the specified
+    /// order, or an `Err(MempoolErrorKind)` if an error occurs.
+    fn get_sorted(&self, base_fee_per_gas: U256) -> Result<Vec<UserOperation>, MempoolErrorKind>;
+
+    /// Retrieves all user operations.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<UserOperation>)` containing all user operations,
+    /// or an `Err(MempoolErrorKind)` if an error occurs.
+    fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind>;
+
+    /// Retrieves a page of user operations ordered by their hashes.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The number of user operations skipped.
+    /// * `limit` - The maximum number of user operations returned.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Vec<UserOperation>)` containing the user operations of the page,
+    /// or an `Err(MempoolErrorKind)` if an error occurs.
+    fn get_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let mut uos = self.get_all()?;
+        uos.sort_by_key(|uo| uo.hash);
+        Ok(uos.into_iter().skip(offset).take(limit).collect())
+    }
+
+    /// Retrieves the number of user operations.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(usize)` with the number of user operations,
+    /// or an `Err(MempoolErrorKind)` if an error occurs.
+    fn len(&self) -> Result<usize, MempoolErrorKind>;
+
+    /// Checks whether there are no user operations.
+    fn is_empty(&self) -> Result<bool, MempoolErrorKind> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Retrieves the unix times (in milliseconds) when the user operations were added.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(Vec<(UserOperationHash, u64)>))` with the insertion times, `Ok(None)` if
+    /// the storage doesn't keep them, or an `Err(MempoolErrorKind)` if an error occurs.
+    fn get_inserted_at(&self) -> Result<Option<Vec<(UserOperationHash, u64)>>, MempoolErrorKind> {
+        Ok(None)
+    }
+}
+
+impl<T: UserOperationOp> UserOperationOp for Arc<RwLock<T>> {
+    fn get_by_uo_hash(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperation>, MempoolErrorKind> {
+        self.read().get_by_uo_hash(uo_hash)
+    }
+
+    fn get_sorted(&self, base_fee_per_gas: U256) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        self.read().get_sorted(base_fee_per_gas)
+    }
+
+    fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        self.read().get_all()
+    }
+
+    fn get_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        self.read().get_page(offset, limit)
+    }
+
+    fn len(&self) -> Result<usize, MempoolErrorKind> {
+        self.read().len()
+    }
+
+    fn get_inserted_at(&self) -> Result<Option<Vec<(UserOperationHash, u64)>>, MempoolErrorKind> {
+        self.read().get_inserted_at()
+    }
+}
+
+/// Trait for operations related to user operation addresses.
+pub trait UserOperationAddrOp {
+    /// Retrieves all user operation hashes associated with the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to retrieve user operation hashes for.
+    ///
+    /// # Returns
+    ///
+    /// A vector containing all user operation hashes associated with the given address.
+    fn get_all_by_address(&self, addr: &Address) -> Vec<UserOperationHash>;
+
+    /// Retrieves the number of user operation hashes associated with the given address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to retrieve the number of user operation hashes for.
+    ///
+    /// # Returns
+    ///
+    /// The number of user operation hashes associated with the given address.
+    fn get_number_by_address(&self, addr: &Address) -> usize {
+        self.get_all_by_address(addr).len()
+    }
+}
+
+impl<T: UserOperationAddrOp> UserOperationAddrOp for Arc<RwLock<T>> {
+    fn get_all_by_address(&self, addr: &Address) -> Vec<UserOperationHash> {
+        self.read().get_all_by_address(addr)
+    }
+}
+
+/// Trait for managing user operation code hashes in a memory pool.
+pub trait UserOperationCodeHashOp {
+    /// Checks if the given user operation hash has associated code hashes in the memory pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `uo_hash` - The user operation hash to check.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating whether the user operation hash has associated code hashes.
+    ///
+    /// - If the user operation hash has associated code hashes, `Ok(true)` is returned.
+    /// - If the user operation hash does not have associated code hashes, `Ok(false)` is returned.
+    /// - If an error occurs during the operation, an `Err` variant is returned with a
+    ///   `MempoolErrorKind`.
+    fn has_code_hashes(&self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind>;
+
+    /// Sets the code hashes for the given user operation hash in the memory pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `uo_hash` - The user operation hash to set the code hashes for.
+    /// * `hashes` - The code hashes to associate with the user operation hash.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating the success or failure of the operation.
+    ///
+    /// - If the code hashes are successfully set, `Ok(())` is returned.
+    /// - If an error occurs during the operation, an `Err` variant is returned with a
+    ///   `MempoolErrorKind`.
+    fn set_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+        hashes: Vec<CodeHash>,
+    ) -> Result<(), MempoolErrorKind>;
+
+    /// Retrieves the code hashes associated with the given user operation hash from the memory
+    /// pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `uo_hash` - The user operation hash to retrieve the code hashes for.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` containing the code hashes associated with the user operation hash.
+    ///
+    /// - If the code hashes are successfully retrieved, `Ok(Vec<CodeHash>)` is returned with the
+    ///   code hashes.
+    /// - If an error occurs during the operation, an `Err` variant is returned with a
+    ///   `MempoolErrorKind`.
+    fn get_code_hashes(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Vec<CodeHash>, MempoolErrorKind>;
+
+    /// Removes the code hashes associated with the given user operation hash from the memory pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `uo_hash` - The user operation hash to remove the code hashes for.
+    ///
+    /// # Returns
+    ///
+    /// Returns a `Result` indicating whether the code hashes were successfully removed.
+    ///
+    /// - If the code hashes are successfully removed, `Ok(true)` is returned.
+    /// - If the user operation hash does not have associated code hashes, `Ok(false)` is returned.
+    /// - If an error occurs during the operation, an `Err` variant is returned with a
+    ///   `MempoolErrorKind`.
+    fn remove_code_hashes(&mut self, uo_hash: &UserOperationHash)
+        -> Result<bool, MempoolErrorKind>;
+}
+
+impl<T: UserOperationCodeHashOp> UserOperationCodeHashOp for Arc<RwLock<T>> {
+    fn has_code_hashes(&self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        self.read().has_code_hashes(uo_hash)
+    }
+
+    fn set_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+        hashes: Vec<CodeHash>,
+    ) -> Result<(), MempoolErrorKind> {
+        self.write().set_code_hashes(uo_hash, hashes)
+    }
+
+    fn get_code_hashes(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Vec<CodeHash>, MempoolErrorKind> {
+        self.read().get_code_hashes(uo_hash)
+    }
+
+    fn remove_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        self.write().remove_code_hashes(uo_hash)
+    }
+}
+
+/// Trait for the index of user operations by sender and nonce.
+pub trait UserOperationNonceOp {
+    /// Indexes the user operation hash by the sender and nonce.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender of the user operation.
+    /// * `nonce` - The nonce of the user operation.
+    /// * `uo_hash` - The hash of the user operation.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the user operation hash was indexed, otherwise returns an error of type
+    /// `MempoolErrorKind`.
+    fn add(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind>;
+
+    /// Removes the user operation hash from the index (only if the sender and nonce are still
+    /// indexed to this hash).
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender of the user operation.
+    /// * `nonce` - The nonce of the user operation.
+    /// * `uo_hash` - The hash of the user operation.
+    ///
+    /// # Returns
+    ///
+    /// - If the user operation hash was removed, `Ok(true)` is returned.
+    /// - If the user operation hash was not indexed, `Ok(false)` is returned.
+    /// - If an error occurred during the removal process, an `Err` variant is returned.
+    fn remove_uo_hash(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind>;
+
+    /// Retrieves the hash of the user operation with the given sender and nonce.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender` - The sender of the user operation.
+    /// * `nonce` - The nonce of the user operation.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(UserOperationHash))` if the user operation is indexed, `Ok(None)` if it is
+    /// not, or an `Err(MempoolErrorKind)` if an error occurs.
+    fn get_by_sender_and_nonce(
+        &self,
+        sender: &Address,
+        nonce: U256,
+    ) -> Result<Option<UserOperationHash>, MempoolErrorKind>;
+}
+
+impl<T: UserOperationNonceOp> UserOperationNonceOp for Arc<RwLock<T>> {
+    fn add(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        self.write().add(sender, nonce, uo_hash)
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        self.write().remove_uo_hash(sender, nonce, uo_hash)
+    }
+
+    fn get_by_sender_and_nonce(
+        &self,
+        sender: &Address,
+        nonce: U256,
+    ) -> Result<Option<UserOperationHash>, MempoolErrorKind> {
+        self.read().get_by_sender_and_nonce(sender, nonce)
+    }
+}
+
+/// Trait for the receipts of the user operations included on-chain.
+pub trait UserOperationReceiptOp {
+    /// Stores the receipt of the included user operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `receipt` - The receipt of the user operation.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the receipt was stored, otherwise returns an error of type
+    /// `MempoolErrorKind`.
+    fn add_receipt(&mut self, receipt: UserOperationReceipt) -> Result<(), MempoolErrorKind>;
+
+    /// Retrieves the receipt of the user operation.
+    ///
+    /// # Arguments
+    ///
+    /// * `uo_hash` - The hash of the user operation.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(Some(UserOperationReceipt))` if the receipt is stored, `Ok(None)` if it is not,
+    /// or an `Err(MempoolErrorKind)` if an error occurs.
+    fn get_receipt(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperationReceipt>, MempoolErrorKind>;
+
+    /// Removes the receipt of the user operation (e.g. its block was reorged out).
+    ///
+    /// # Arguments
+    ///
+    /// * `uo_hash` - The hash of the user operation.
+    ///
+    /// # Returns
+    ///
+    /// - If the receipt was removed, `Ok(true)` is returned.
+    /// - If the receipt was not stored, `Ok(false)` is returned.
+    /// - If an error occurred during the removal process, an `Err` variant is returned.
+    fn remove_receipt(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind>;
+}
+
+impl<T: UserOperationReceiptOp> UserOperationReceiptOp for Arc<RwLock<T>> {
+    fn add_receipt(&mut self, receipt: UserOperationReceipt) -> Result<(), MempoolErrorKind> {
+        self.write().add_receipt(receipt)
+    }
+
+    fn get_receipt(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperationReceipt>, MempoolErrorKind> {
+        self.read().get_receipt(uo_hash)
+    }
+
+    fn remove_receipt(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        self.write().remove_receipt(uo_hash)
+    }
+}
+
+/// A trait for clearing operation.
+pub trait ClearOp {
+    /// Clears the operation.
+    fn clear(&mut self);
+}
+
+pub trait UserOperationAct:
+    AddRemoveUserOp + UserOperationOp + ClearOp + Send + Sync + DynClone
+{
+}
+
+dyn_clone::clone_trait_object!(UserOperationAct);
+impl<T> UserOperationAct for T where
+    T: AddRemoveUserOp + UserOperationOp + ClearOp + Send + Sync + Clone
+{
+}
+
+impl<T: ClearOp> ClearOp for Arc<RwLock<T>> {
+    fn clear(&mut self) {
+        self.write().clear()
+    }
+}
+
+pub trait UserOperationAddrAct:
+    AddRemoveUserOpHash + UserOperationAddrOp + ClearOp + Send + Sync + DynClone
+{
+}
+
+dyn_clone::clone_trait_object!(UserOperationAddrAct);
+impl<T> UserOperationAddrAct for T where
+    T: AddRemoveUserOpHash + UserOperationAddrOp + ClearOp + Send + Sync + Clone
+{
+}
+
+pub trait UserOperationCodeHashAct:
+    UserOperationCodeHashOp + ClearOp + Send + Sync + DynClone
+{
+}
+
+dyn_clone::clone_trait_object!(UserOperationCodeHashAct);
+impl<T> UserOperationCodeHashAct for T where
+    T: UserOperationCodeHashOp + ClearOp + Send + Sync + Clone
+{
+}
+
+pub trait UserOperationNonceAct: UserOperationNonceOp + ClearOp + Send + Sync + DynClone {}
+
+dyn_clone::clone_trait_object!(UserOperationNonceAct);
+impl<T> UserOperationNonceAct for T where T: UserOperationNonceOp + ClearOp + Send + Sync + Clone {}
+
+pub trait UserOperationReceiptAct:
+    UserOperationReceiptOp + ClearOp + Send + Sync + DynClone
+{
+}
+
+dyn_clone::clone_trait_object!(UserOperationReceiptAct);
+impl<T> UserOperationReceiptAct for T where T: UserOperationReceiptOp + ClearOp + Send + Sync + Clone
+{}
+
+#[derive(Clone)]
+pub struct Mempool {
+    user_operations: Box<dyn UserOperationAct>,
+    user_operations_by_sender: Box<dyn UserOperationAddrAct>,
+    user_operations_by_entity: Box<dyn UserOperationAddrAct>,
+    user_operations_by_aggregator: Box<dyn UserOperationAddrAct>,
+    user_operations_code_hashes: Box<dyn UserOperationCodeHashAct>,
+    user_operations_by_nonce: Box<dyn UserOperationNonceAct>,
+    // Receipts of the included user operations (kept after they leave the mempool)
+    user_operation_receipts: Box<dyn UserOperationReceiptAct>,
+    capacity: Arc<CapacityTracker>,
+    entity_graph: Arc<EntityGraph>,
+    // In-memory insertion times of the user operations (for the expiry)
+    inserted_at: Arc<DashMap<UserOperationHash, Instant>>,
+    // The storage is shared with other bundler instances, so the counts and the insertion times
+    // are read from the storage instead of the in-memory indexes
+    shared: bool,
+}
+
+/// Current unix time in milliseconds
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or_default()
+}
+
+impl Mempool {
+    pub fn new(
+        user_operations: Box<dyn UserOperationAct>,
+        user_operations_by_sender: Box<dyn UserOperationAddrAct>,
+        user_operations_by_entity: Box<dyn UserOperationAddrAct>,
+        user_operations_by_aggregator: Box<dyn UserOperationAddrAct>,
+        user_operations_code_hashes: Box<dyn UserOperationCodeHashAct>,
+        user_operations_by_nonce: Box<dyn UserOperationNonceAct>,
+        user_operation_receipts: Box<dyn UserOperationReceiptAct>,
+    ) -> Self {
+        // the database mempool can already contain user operations from the previous run, their
+        // age is counted from the restart
+        let uos = user_operations.get_all().unwrap_or_default();
+        let capacity = Arc::new(CapacityTracker::from_user_operations(&uos));
+        let entity_graph = Arc::new(EntityGraph::from_user_operations(&uos));
+        let now = Instant::now();
+        let inserted_at = Arc::new(uos.iter().map(|uo| (uo.hash, now)).collect());
+        Self {
+            user_operations,
+            user_operations_by_sender,
+            user_operations_by_entity,
+            user_operations_by_aggregator,
+            user_operations_code_hashes,
+            user_operations_by_nonce,
+            user_operation_receipts,
+            capacity,
+            entity_graph,
+            inserted_at,
+            shared: false,
+        }
+    }
+
+    /// Marks the storage as shared with other bundler instances (e.g. Redis). The numbers of user
+    /// operations per sender and entity, the statistics and the ages of the user operations are
+    /// then read from the storage, as the in-memory indexes only see the changes of this instance.
+    pub fn with_shared_storage(mut self) -> Self {
+        self.shared = true;
+        self
+    }
+
+    pub fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
+        let (sender, factory, paymaster) = uo.get_entities();
+        let (uo_hash, nonce) = (uo.hash, uo.nonce);
+        let exists = self.user_operations.get_by_uo_hash(&uo_hash)?.is_some();
+        self.user_operations.add(uo.clone())?;
+        // counted only once the user operation is stored
+        if !exists {
+            self.capacity.add(&uo);
+            self.entity_graph.add(&uo);
+        }
+        self.user_operations_by_sender.add(&sender, uo_hash)?;
+        self.user_operations_by_nonce.add(&sender, nonce, uo_hash)?;
+        if let Some(factory) = factory {
+            self.user_operations_by_entity.add(&factory, uo_hash)?;
+        }
+        if let Some(paymaster) = paymaster {
+            self.user_operations_by_entity.add(&paymaster, uo_hash)?;
+        }
+        if let Some(aggregator) = uo.aggregator {
+            self.user_operations_by_aggregator.add(&aggregator, uo_hash)?;
+        }
+        self.inserted_at.entry(uo_hash).or_insert_with(Instant::now);
+        Ok(uo_hash)
+    }
+
+    pub fn get(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperation>, MempoolErrorKind> {
+        self.user_operations.get_by_uo_hash(uo_hash)
+    }
+
+    pub fn get_all_by_sender(&self, addr: &Address) -> Vec<UserOperation> {
+        let uos_by_sender = self.user_operations_by_sender.get_all_by_address(addr);
+        uos_by_sender
+            .iter()
+            .flat_map(|uo_hash| self.user_operations.get_by_uo_hash(uo_hash))
+            .flatten()
+            .collect()
+    }
+
+    pub fn get_number_by_sender(&self, addr: &Address) -> usize {
+        self.user_operations_by_sender.get_number_by_address(addr)
+    }
+
+    pub fn get_all_by_entity(&self, addr: &Address) -> Vec<UserOperation> {
+        let uos_by_entity = self.user_operations_by_entity.get_all_by_address(addr);
+        uos_by_entity
+            .iter()
+            .flat_map(|uo_hash| self.user_operations.get_by_uo_hash(uo_hash))
+            .flatten()
+            .collect()
+    }
+
+    pub fn get_number_by_entity(&self, addr: &Address) -> usize {
+        self.user_operations_by_entity.get_number_by_address(addr)
+    }
+
+    /// Gets the user operations whose signatures are validated by the aggregator
+    pub fn get_all_by_aggregator(&self, addr: &Address) -> Vec<UserOperation> {
+        let uos_by_aggregator = self.user_operations_by_aggregator.get_all_by_address(addr);
+        uos_by_aggregator
+            .iter()
+            .flat_map(|uo_hash| self.user_operations.get_by_uo_hash(uo_hash))
+            .flatten()
+            .collect()
+    }
+
+    pub fn get_number_by_aggregator(&self, addr: &Address) -> usize {
+        self.user_operations_by_aggregator.get_number_by_address(addr)
+    }
+
+    /// Gets the user operations sponsored by the paymaster (the entity index also holds the user
+    /// operations that use the address as the factory, which are skipped)
+    pub fn get_all_by_paymaster(&self, paymaster: &Address) -> Vec<UserOperation> {
+        self.get_all_by_entity(paymaster)
+            .into_iter()
+            .filter(|uo| uo.get_entities().2.as_ref() == Some(paymaster))
+            .collect()
+    }
+
+    /// Number of user operations in the mempool
+    pub fn len(&self) -> Result<usize, MempoolErrorKind> {
+        self.user_operations.len()
+    }
+
+    pub fn is_empty(&self) -> Result<bool, MempoolErrorKind> {
+        self.user_operations.is_empty()
+    }
+
+    /// Number of user operations of the sender (from the in-memory index unless the storage is
+    /// shared)
+    pub fn get_sender_count(&self, addr: &Address) -> usize {
+        if self.shared {
+            self.get_number_by_sender(addr)
+        } else {
+            self.capacity.get_sender_count(addr) as usize
+        }
+    }
+
+    /// Number of user operations using the address as a factory or paymaster (from the in-memory
+    /// index unless the storage is shared)
+    pub fn get_entity_count(&self, addr: &Address) -> usize {
+        if self.shared {
+            self.get_number_by_entity(addr)
+        } else {
+            self.capacity.get_entity_count(addr) as usize
+        }
+    }
+
+    /// In-memory relationships between the senders and the entities of the user operations
+    pub fn entity_graph(&self) -> Arc<EntityGraph> {
+        self.entity_graph.clone()
+    }
+
+    /// Factories and paymasters used by the user operations of the sender (from the entity graph
+    /// unless the storage is shared)
+    pub fn get_entities_for_sender(&self, addr: &Address) -> Vec<Address> {
+        if !self.shared {
+            return self.entity_graph.get_entities_for_sender(addr);
+        }
+
+        let mut entities = HashSet::new();
+        for uo in self.get_all_by_sender(addr) {
+            let (_, factory, paymaster) = uo.get_entities();
+            entities.extend(factory.into_iter().chain(paymaster));
+        }
+        entities.into_iter().collect()
+    }
+
+    /// Senders of the user operations using the address as a factory or paymaster (from the
+    /// entity graph unless the storage is shared)
+    pub fn get_senders_for_entity(&self, addr: &Address) -> Vec<Address> {
+        if !self.shared {
+            return self.entity_graph.get_senders_for_entity(addr);
+        }
+
+        self.get_all_by_entity(addr)
+            .into_iter()
+            .map(|uo| uo.sender)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Statistics of the mempool, computed from the in-memory indexes (without reading the
+    /// storage, which is expensive for the database mempool). The statistics of a shared storage
+    /// are computed from the storage.
+    pub fn stats(&self) -> MempoolStats {
+        if let Some(inserted_at) = self.shared_inserted_at() {
+            let uos = self.user_operations.get_all().unwrap_or_default();
+            let counts = CapacityTracker::from_user_operations(&uos);
+            let oldest = inserted_at.iter().map(|(_, t)| *t).min();
+            return MempoolStats {
+                total_ops: uos.len(),
+                unique_senders: counts.num_senders(),
+                unique_paymasters: counts.num_paymasters(),
+                oldest_op_age_secs: oldest
+                    .map(|t| unix_millis().saturating_sub(t) / 1000)
+                    .unwrap_or_default(),
+            };
+        }
+
+        let oldest = self.inserted_at.iter().map(|entry| *entry.value()).min();
+        MempoolStats {
+            total_ops: self.inserted_at.len(),
+            unique_senders: self.capacity.num_senders(),
+            unique_paymasters: self.capacity.num_paymasters(),
+            oldest_op_age_secs: oldest.map(|t| t.elapsed().as_secs()).unwrap_or_default(),
+        }
+    }
+
+    /// Insertion times kept by the shared storage (`None` if the storage isn't shared or doesn't
+    /// keep them)
+    fn shared_inserted_at(&self) -> Option<Vec<(UserOperationHash, u64)>> {
+        if !self.shared {
+            return None;
+        }
+        self.user_operations.get_inserted_at().ok().flatten()
+    }
+
+    /// Gets the user operation with the given sender and nonce (without scanning all user
+    /// operations of the sender)
+    pub fn get_by_sender_and_nonce(&self, sender: &Address, nonce: U256) -> Option<UserOperation> {
+        self.user_operations_by_nonce
+            .get_by_sender_and_nonce(sender, nonce)
+            .ok()
+            .flatten()
+            .and_then(|uo_hash| self.get(&uo_hash).ok().flatten())
+    }
+
+    pub fn get_prev_by_sender(&self, uo: &UserOperation) -> Option<UserOperation> {
+        self.get_by_sender_and_nonce(&uo.sender, uo.nonce)
+    }
+
+    pub fn has_code_hashes(&self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        self.user_operations_code_hashes.has_code_hashes(uo_hash)
+    }
+
+    pub fn set_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+        hashes: Vec<CodeHash>,
+    ) -> Result<(), MempoolErrorKind> {
+        self.user_operations_code_hashes.set_code_hashes(uo_hash, hashes)
+    }
+
+    pub fn get_code_hashes(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Vec<CodeHash>, MempoolErrorKind> {
+        self.user_operations_code_hashes.get_code_hashes(uo_hash)
+    }
+
+    pub fn remove(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let uo = if let Some(user_op) = self.user_operations.get_by_uo_hash(uo_hash)? {
+            user_op
+        } else {
+            return Ok(false);
+        };
+
+        let (sender, factory, paymaster) = uo.get_entities();
+
+        self.user_operations.remove_by_uo_hash(uo_hash)?;
+
+        self.user_operations_by_sender.remove_uo_hash(&sender, uo_hash)?;
+
+        self.user_operations_by_nonce.remove_uo_hash(&sender, uo.nonce, uo_hash)?;
+
+        if let Some(factory) = factory {
+            self.user_operations_by_entity.remove_uo_hash(&factory, uo_hash)?;
+        }
+
+        if let Some(paymaster) = paymaster {
+            self.user_operations_by_entity.remove_uo_hash(&paymaster, uo_hash)?;
+        }
+
+        if let Some(aggregator) = uo.aggregator {
+            self.user_operations_by_aggregator.remove_uo_hash(&aggregator, uo_hash)?;
+        }
+
+        self.user_operations_code_hashes.remove_code_hashes(uo_hash)?;
+
+        self.capacity.remove(&uo);
+
+        self.entity_graph.remove(&uo);
+
+        self.inserted_at.remove(uo_hash);
+
+        Ok(true)
+    }
+
+    /// Replaces the user operation with the same sender and nonce (replace-by-fee) without
+    /// removing it first, so the mempool doesn't miss the user operation of the sender in between.
+    /// The indexes of the replacing user operation are added before the swap and the ones of the
+    /// replaced user operation are removed after it. If the previous user operation is already
+    /// gone, `uo` is just added.
+    pub fn update_user_operation(
+        &mut self,
+        prev_hash: &UserOperationHash,
+        uo: UserOperation,
+    ) -> Result<UserOperationHash, MempoolErrorKind> {
+        let uo_prev = match self.user_operations.get_by_uo_hash(prev_hash)? {
+            Some(uo_prev) if uo_prev.hash != uo.hash => uo_prev,
+            _ => return self.add(uo),
+        };
+        if uo_prev.sender != uo.sender || uo_prev.nonce != uo.nonce {
+            return Err(MempoolErrorKind::Other {
+                inner: format!(
+                    "User operation {:?} doesn't have the sender and nonce of {prev_hash:?}",
+                    uo.hash
+                ),
+            });
+        }
+
+        let (sender, factory, paymaster) = uo.get_entities();
+        let (_, factory_prev, paymaster_prev) = uo_prev.get_entities();
+        let (uo_hash, nonce) = (uo.hash, uo.nonce);
+
+        self.user_operations_by_sender.add(&sender, uo_hash)?;
+        if let Some(factory) = factory {
+            self.user_operations_by_entity.add(&factory, uo_hash)?;
+        }
+        if let Some(paymaster) = paymaster {
+            self.user_operations_by_entity.add(&paymaster, uo_hash)?;
+        }
+        if let Some(aggregator) = uo.aggregator {
+            self.user_operations_by_aggregator.add(&aggregator, uo_hash)?;
+        }
+
+        self.user_operations.replace(prev_hash, uo.clone())?;
+        self.capacity.remove(&uo_prev);
+        self.capacity.add(&uo);
+        self.entity_graph.remove(&uo_prev);
+        self.entity_graph.add(&uo);
+        self.user_operations_by_nonce.add(&sender, nonce, uo_hash)?;
+
+        self.user_operations_by_sender.remove_uo_hash(&sender, prev_hash)?;
+        if let Some(factory) = factory_prev {
+            self.user_operations_by_entity.remove_uo_hash(&factory, prev_hash)?;
+        }
+        if let Some(paymaster) = paymaster_prev {
+            self.user_operations_by_entity.remove_uo_hash(&paymaster, prev_hash)?;
+        }
+        if let Some(aggregator) = uo_prev.aggregator {
+            self.user_operations_by_aggregator.remove_uo_hash(&aggregator, prev_hash)?;
+        }
+        self.user_operations_code_hashes.remove_code_hashes(prev_hash)?;
+
+        self.inserted_at.remove(prev_hash);
+        self.inserted_at.insert(uo_hash, Instant::now());
+
+        Ok(uo_hash)
+    }
+
+    pub fn add_receipt(&mut self, receipt: UserOperationReceipt) -> Result<(), MempoolErrorKind> {
+        self.user_operation_receipts.add_receipt(receipt)
+    }
+
+    pub fn get_receipt(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperationReceipt>, MempoolErrorKind> {
+        self.user_operation_receipts.get_receipt(uo_hash)
+    }
+
+    pub fn remove_receipt(
+        &mut self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        self.user_operation_receipts.remove_receipt(uo_hash)
+    }
+
+    /// Removes all receipts (they aren't removed by [clear](Mempool::clear))
+    pub fn clear_receipts(&mut self) {
+        self.user_operation_receipts.clear();
+    }
+
+    /// Removes the user operations using the address as a factory, paymaster or aggregator, and
+    /// the ones of the address as the sender (the entity index doesn't hold the senders)
+    pub fn remove_by_entity(&mut self, entity: &Address) -> Result<(), MempoolErrorKind> {
+        let mut uos = self.user_operations_by_entity.get_all_by_address(entity);
+        uos.extend(self.user_operations_by_sender.get_all_by_address(entity));
+        uos.extend(self.user_operations_by_aggregator.get_all_by_address(entity));
+
+        for uo_hash in uos {
+            self.remove(&uo_hash)?;
+        }
+
+        Ok(())
+    }
+
+    // Get UserOperations sorted by the effective gas price at the base fee
+    pub fn get_sorted(
+        &self,
+        base_fee_per_gas: U256,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        self.user_operations.get_sorted(base_fee_per_gas)
+    }
+
+    pub fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        self.user_operations.get_all()
+    }
+
+    /// Gets a page of the UserOperations ordered by their hashes
+    pub fn get_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        self.user_operations.get_page(offset, limit)
+    }
+
+    /// Gets the hashes of the user operations added at or before the cutoff
+    pub fn get_all_older_than(&self, cutoff: Instant) -> Vec<UserOperationHash> {
+        if let Some(inserted_at) = self.shared_inserted_at() {
+            let age = Instant::now().saturating_duration_since(cutoff).as_millis() as u64;
+            let cutoff = unix_millis().saturating_sub(age);
+            return inserted_at
+                .into_iter()
+                .filter(|(_, t)| *t <= cutoff)
+                .map(|(uo_hash, _)| uo_hash)
+                .collect();
+        }
+
+        self.inserted_at
+            .iter()
+            .filter(|entry| *entry.value() <= cutoff)
+            .map(|entry| *entry.key())
+            .collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.user_operations.clear();
+        self.user_operations_by_sender.clear();
+        self.user_operations_by_entity.clear();
+        self.user_operations_by_aggregator.clear();
+        self.user_operations_code_hashes.clear();
+        self.user_operations_by_nonce.clear();
+        self.capacity.clear();
+        self.entity_graph.clear();
+        self.inserted_at.clear();
+    }
+}
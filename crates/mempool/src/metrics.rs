@@ -1 +1,4 @@
+//! Metrics recorded by the mempool (described together with the other metrics in silius-metrics)
 
+/// Number of user operations evicted from a full mempool
+pub const MEMPOOL_EVICTIONS: &str = "silius_mempool_evictions_total";
@@ -0,0 +1,443 @@
+use super::{
+    tables::{
+        CodeHashes, UserOperationReceipts, UserOperations, UserOperationsByAggregator,
+        UserOperationsByEntity, UserOperationsByNonce, UserOperationsBySender,
+    },
+    RedisTable,
+};
+use crate::{
+    mempool::{
+        unix_millis, AddRemoveUserOp, AddRemoveUserOpHash, ClearOp, UserOperationAddrOp,
+        UserOperationCodeHashOp, UserOperationNonceOp, UserOperationOp, UserOperationReceiptOp,
+    },
+    utils::sort_by_effective_gas_price,
+    MempoolErrorKind,
+};
+use ethers::types::{Address, U256};
+use redis::{Commands, Script};
+use silius_primitives::{
+    simulation::CodeHash, UserOperation, UserOperationHash, UserOperationReceipt,
+};
+use std::str::FromStr;
+
+/// Deletes the field of the hash only if it still points to the user operation (a newer user
+/// operation with the same sender and nonce could have replaced it)
+const REMOVE_NONCE_SCRIPT: &str = r#"
+if redis.call('HGET', KEYS[1], ARGV[1]) == ARGV[2] then
+    return redis.call('HDEL', KEYS[1], ARGV[1])
+end
+return 0
+"#;
+
+/// Addresses are stored as lowercase hex strings (the same as the hashes)
+fn addr_key(addr: &Address) -> String {
+    format!("{addr:?}")
+}
+
+fn decode_err(e: impl std::fmt::Display) -> MempoolErrorKind {
+    MempoolErrorKind::Redis { inner: format!("decode error: {e}") }
+}
+
+fn parse_uo_hashes(hashes: Vec<String>) -> Vec<UserOperationHash> {
+    hashes.iter().filter_map(|hash| UserOperationHash::from_str(hash).ok()).collect()
+}
+
+impl AddRemoveUserOp for RedisTable<UserOperations> {
+    fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
+        let uo_hash = uo.hash;
+        let data = serde_json::to_string(&uo).map_err(decode_err)?;
+        let (key, all) = (self.env.key("uo", uo_hash), self.env.single_key("uos"));
+        let inserted_at = self.env.single_key("uos_inserted_at");
+
+        self.env.run(|con| {
+            // the insertion time of a user operation added again is kept
+            redis::pipe()
+                .atomic()
+                .set(key, data)
+                .ignore()
+                .sadd(all, uo_hash.to_string())
+                .ignore()
+                .cmd("ZADD")
+                .arg(inserted_at)
+                .arg("NX")
+                .arg(unix_millis())
+                .arg(uo_hash.to_string())
+                .ignore()
+                .query::<()>(con)
+        })?;
+        Ok(uo_hash)
+    }
+
+    fn remove_by_uo_hash(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let (key, all) = (self.env.key("uo", uo_hash), self.env.single_key("uos"));
+        let inserted_at = self.env.single_key("uos_inserted_at");
+
+        let (deleted, _, _): (u64, u64, u64) = self.env.run(|con| {
+            redis::pipe()
+                .atomic()
+                .del(key)
+                .srem(all, uo_hash.to_string())
+                .zrem(inserted_at, uo_hash.to_string())
+                .query(con)
+        })?;
+        Ok(deleted > 0)
+    }
+
+    fn replace(
+        &mut self,
+        prev_hash: &UserOperationHash,
+        uo: UserOperation,
+    ) -> Result<UserOperationHash, MempoolErrorKind> {
+        let uo_hash = uo.hash;
+        let data = serde_json::to_string(&uo).map_err(decode_err)?;
+        let (key_prev, key) = (self.env.key("uo", prev_hash), self.env.key("uo", uo_hash));
+        let (all, inserted_at) =
+            (self.env.single_key("uos"), self.env.single_key("uos_inserted_at"));
+
+        self.env.run(|con| {
+            redis::pipe()
+                .atomic()
+                .del(key_prev)
+                .ignore()
+                .srem(all.clone(), prev_hash.to_string())
+                .ignore()
+                .zrem(inserted_at.clone(), prev_hash.to_string())
+                .ignore()
+                .set(key, data)
+                .ignore()
+                .sadd(all, uo_hash.to_string())
+                .ignore()
+                .zadd(inserted_at, uo_hash.to_string(), unix_millis())
+                .ignore()
+                .query::<()>(con)
+        })?;
+        Ok(uo_hash)
+    }
+}
+
+impl UserOperationOp for RedisTable<UserOperations> {
+    fn get_by_uo_hash(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperation>, MempoolErrorKind> {
+        let key = self.env.key("uo", uo_hash);
+        self.env
+            .run::<Option<String>, MempoolErrorKind, _>(|con| con.get(key))?
+            .map(|data| serde_json::from_str(&data).map_err(decode_err))
+            .transpose()
+    }
+
+    fn get_sorted(&self, base_fee_per_gas: U256) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let mut uos = self.get_all()?;
+        sort_by_effective_gas_price(&mut uos, base_fee_per_gas);
+        Ok(uos)
+    }
+
+    fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let all = self.env.single_key("uos");
+        let hashes: Vec<String> = self.env.run(|con| con.smembers(all))?;
+        if hashes.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let keys: Vec<String> = hashes.iter().map(|hash| self.env.key("uo", hash)).collect();
+        let data: Vec<Option<String>> =
+            self.env.run(|con| redis::cmd("MGET").arg(keys).query(con))?;
+        // user operations removed in between are skipped
+        data.into_iter()
+            .flatten()
+            .map(|data| serde_json::from_str(&data).map_err(decode_err))
+            .collect()
+    }
+
+    fn len(&self) -> Result<usize, MempoolErrorKind> {
+        let all = self.env.single_key("uos");
+        self.env.run(|con| con.scard(all))
+    }
+
+    fn get_inserted_at(&self) -> Result<Option<Vec<(UserOperationHash, u64)>>, MempoolErrorKind> {
+        let inserted_at = self.env.single_key("uos_inserted_at");
+        // the scores are doubles, exact for the unix times in milliseconds
+        let times: Vec<(String, f64)> =
+            self.env.run(|con| con.zrange_withscores(inserted_at, 0, -1))?;
+        Ok(Some(
+            times
+                .into_iter()
+                .filter_map(|(hash, t)| {
+                    UserOperationHash::from_str(&hash).ok().map(|h| (h, t as u64))
+                })
+                .collect(),
+        ))
+    }
+}
+
+macro_rules! impl_addr_table {
+    ($table: ident, $name: literal) => {
+        impl AddRemoveUserOpHash for RedisTable<$table> {
+            fn add(
+                &mut self,
+                address: &Address,
+                uo_hash: UserOperationHash,
+            ) -> Result<(), MempoolErrorKind> {
+                let key = self.env.key($name, addr_key(address));
+                self.env.run(|con| con.sadd(key, uo_hash.to_string()))
+            }
+
+            fn remove_uo_hash(
+                &mut self,
+                address: &Address,
+                uo_hash: &UserOperationHash,
+            ) -> Result<bool, MempoolErrorKind> {
+                let key = self.env.key($name, addr_key(address));
+                let removed: u64 = self.env.run(|con| con.srem(key, uo_hash.to_string()))?;
+                Ok(removed > 0)
+            }
+        }
+
+        impl UserOperationAddrOp for RedisTable<$table> {
+            fn get_all_by_address(&self, address: &Address) -> Vec<UserOperationHash> {
+                let key = self.env.key($name, addr_key(address));
+                self.env
+                    .run::<_, MempoolErrorKind, _>(|con| con.smembers(key))
+                    .map(parse_uo_hashes)
+                    .unwrap_or_else(|_| vec![])
+            }
+        }
+    };
+}
+impl_addr_table!(UserOperationsBySender, "sender");
+impl_addr_table!(UserOperationsByEntity, "entity");
+impl_addr_table!(UserOperationsByAggregator, "aggregator");
+
+impl UserOperationNonceOp for RedisTable<UserOperationsByNonce> {
+    fn add(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        let key = self.env.key("nonce", addr_key(sender));
+        self.env.run(|con| con.hset(key, nonce.to_string(), uo_hash.to_string()))
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let key = self.env.key("nonce", addr_key(sender));
+        let removed: u64 = self.env.run(|con| {
+            Script::new(REMOVE_NONCE_SCRIPT)
+                .key(key)
+                .arg(nonce.to_string())
+                .arg(uo_hash.to_string())
+                .invoke(con)
+        })?;
+        Ok(removed > 0)
+    }
+
+    fn get_by_sender_and_nonce(
+        &self,
+        sender: &Address,
+        nonce: U256,
+    ) -> Result<Option<UserOperationHash>, MempoolErrorKind> {
+        let key = self.env.key("nonce", addr_key(sender));
+        self.env
+            .run::<Option<String>, MempoolErrorKind, _>(|con| con.hget(key, nonce.to_string()))?
+            .map(|hash| UserOperationHash::from_str(&hash).map_err(decode_err))
+            .transpose()
+    }
+}
+
+impl UserOperationCodeHashOp for RedisTable<CodeHashes> {
+    fn has_code_hashes(&self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let key = self.env.key("code_hashes", uo_hash);
+        self.env.run(|con| con.exists(key))
+    }
+
+    fn set_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+        hashes: Vec<CodeHash>,
+    ) -> Result<(), MempoolErrorKind> {
+        let key = self.env.key("code_hashes", uo_hash);
+        let data = serde_json::to_string(&hashes).map_err(decode_err)?;
+        self.env.run(|con| con.set(key, data))
+    }
+
+    fn get_code_hashes(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Vec<CodeHash>, MempoolErrorKind> {
+        let key = self.env.key("code_hashes", uo_hash);
+        match self.env.run::<Option<String>, MempoolErrorKind, _>(|con| con.get(key))? {
+            Some(data) => serde_json::from_str(&data).map_err(decode_err),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn remove_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let key = self.env.key("code_hashes", uo_hash);
+        let deleted: u64 = self.env.run(|con| con.del(key))?;
+        Ok(deleted > 0)
+    }
+}
+
+impl UserOperationReceiptOp for RedisTable<UserOperationReceipts> {
+    fn add_receipt(&mut self, receipt: UserOperationReceipt) -> Result<(), MempoolErrorKind> {
+        let key = self.env.key("receipt", receipt.user_operation_hash);
+        let data = serde_json::to_string(&receipt).map_err(decode_err)?;
+        self.env.run(|con| con.set(key, data))
+    }
+
+    fn get_receipt(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperationReceipt>, MempoolErrorKind> {
+        let key = self.env.key("receipt", uo_hash);
+        self.env
+            .run::<Option<String>, MempoolErrorKind, _>(|con| con.get(key))?
+            .map(|data| serde_json::from_str(&data).map_err(decode_err))
+            .transpose()
+    }
+
+    fn remove_receipt(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let key = self.env.key("receipt", uo_hash);
+        let deleted: u64 = self.env.run(|con| con.del(key))?;
+        Ok(deleted > 0)
+    }
+}
+
+macro_rules! impl_clear {
+    ($table: ident, $name: literal) => {
+        impl ClearOp for RedisTable<$table> {
+            fn clear(&mut self) {
+                self.env.clear_table($name).expect("Clear redis table failed");
+            }
+        }
+    };
+}
+impl_clear!(UserOperationsBySender, "sender");
+impl_clear!(UserOperationsByEntity, "entity");
+impl_clear!(UserOperationsByAggregator, "aggregator");
+impl_clear!(UserOperationsByNonce, "nonce");
+impl_clear!(CodeHashes, "code_hashes");
+impl_clear!(UserOperationReceipts, "receipt");
+
+impl ClearOp for RedisTable<UserOperations> {
+    fn clear(&mut self) {
+        let keys = vec![self.env.single_key("uos"), self.env.single_key("uos_inserted_at")];
+        self.env.run::<(), MempoolErrorKind, _>(|con| con.del(&keys)).expect("Clear redis failed");
+        self.env.clear_table("uo").expect("Clear redis table failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        redis::{
+            tables::{
+                CodeHashes, UserOperationReceipts, UserOperations, UserOperationsByAggregator,
+                UserOperationsByEntity, UserOperationsByNonce, UserOperationsBySender,
+            },
+            RedisEnv, RedisTable,
+        },
+        utils::tests::{
+            capacity_tracker_test_case, mempool_test_case, receipts_test_case,
+            sort_by_effective_gas_price_test_case,
+        },
+        Mempool,
+    };
+    use ethers::types::{Address, H256};
+    use silius_primitives::{UserOperation, UserOperationSigned};
+    use std::{
+        sync::Arc,
+        time::{Duration, Instant},
+    };
+    use testcontainers::clients::Cli;
+    use testcontainers_modules::redis::Redis;
+
+    fn redis_mempool(port: u16) -> Mempool {
+        let client = redis::Client::open(format!("redis://127.0.0.1:{port}")).unwrap();
+        let env = Arc::new(RedisEnv::connect(client, "test").unwrap());
+        Mempool::new(
+            Box::new(RedisTable::<UserOperations>::new(env.clone())),
+            Box::new(RedisTable::<UserOperationsBySender>::new(env.clone())),
+            Box::new(RedisTable::<UserOperationsByEntity>::new(env.clone())),
+            Box::new(RedisTable::<UserOperationsByAggregator>::new(env.clone())),
+            Box::new(RedisTable::<CodeHashes>::new(env.clone())),
+            Box::new(RedisTable::<UserOperationsByNonce>::new(env.clone())),
+            Box::new(RedisTable::<UserOperationReceipts>::new(env)),
+        )
+        .with_shared_storage()
+    }
+
+    #[allow(clippy::unit_cmp)]
+    #[tokio::test]
+    async fn redis_mempool_basic() {
+        let docker = Cli::default();
+        let node = docker.run(Redis::default());
+        mempool_test_case(redis_mempool(node.get_host_port_ipv4(6379)));
+    }
+
+    #[tokio::test]
+    async fn redis_mempool_shared() {
+        let docker = Cli::default();
+        let node = docker.run(Redis::default());
+        let port = node.get_host_port_ipv4(6379);
+
+        // the capacity tracker of a second instance starts from the user operations added by the
+        // first one
+        capacity_tracker_test_case(redis_mempool(port));
+        capacity_tracker_test_case(redis_mempool(port));
+    }
+
+    #[tokio::test]
+    async fn redis_mempool_shared_counts() {
+        let docker = Cli::default();
+        let node = docker.run(Redis::default());
+        let port = node.get_host_port_ipv4(6379);
+
+        // both instances are running, the second one sees the changes of the first one
+        let (mut first, second) = (redis_mempool(port), redis_mempool(port));
+        let before = Instant::now() - Duration::from_secs(1);
+        let (sender, paymaster) = (Address::random(), Address::random());
+        let uo = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::default()
+                .sender(sender)
+                .paymaster_and_data(paymaster.as_bytes().to_vec().into()),
+        );
+        first.add(uo.clone()).unwrap();
+
+        assert_eq!(second.get_sender_count(&sender), 1);
+        assert_eq!(second.get_entity_count(&paymaster), 1);
+        let stats = second.stats();
+        assert_eq!((stats.total_ops, stats.unique_senders, stats.unique_paymasters), (1, 1, 1));
+        assert!(second.get_all_older_than(before).is_empty());
+        assert_eq!(second.get_all_older_than(Instant::now()), vec![uo.hash]);
+
+        assert!(first.remove(&uo.hash).unwrap());
+        assert_eq!(second.get_sender_count(&sender), 0);
+        assert_eq!(second.stats().total_ops, 0);
+        assert!(second.get_all_older_than(Instant::now()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn redis_mempool_sort_by_effective_gas_price() {
+        let docker = Cli::default();
+        let node = docker.run(Redis::default());
+        sort_by_effective_gas_price_test_case(redis_mempool(node.get_host_port_ipv4(6379)));
+    }
+
+    #[tokio::test]
+    async fn redis_mempool_receipts() {
+        let docker = Cli::default();
+        let node = docker.run(Redis::default());
+        receipts_test_case(redis_mempool(node.get_host_port_ipv4(6379)));
+    }
+}
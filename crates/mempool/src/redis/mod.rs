@@ -0,0 +1,105 @@
+//! The Redis implementation of the [Mempool](crate::mempool::Mempool) and
+//! [Reputation](crate::Reputation) tables. Bundler instances connected to the same Redis server
+//! share the user operations and the reputation of the entities.
+
+use parking_lot::Mutex;
+use redis::{Client, Commands, Connection, RedisError, RedisResult};
+use std::{fmt, sync::Arc};
+
+pub mod mempool;
+pub mod reputation;
+pub mod tables;
+
+/// Connection to the Redis server the tables are stored in
+///
+/// The table traits are synchronous, so one blocking connection is shared by the tables (the
+/// same as with the MDBX transactions). The connection is opened again if it drops.
+pub struct RedisEnv {
+    client: Client,
+    connection: Mutex<Connection>,
+    /// Prefix of all the keys (empty or ending with `:`)
+    prefix: String,
+}
+
+impl fmt::Debug for RedisEnv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedisEnv")
+            .field("client", &self.client)
+            .field("prefix", &self.prefix)
+            .finish()
+    }
+}
+
+impl RedisEnv {
+    /// Connects to the Redis server
+    ///
+    /// # Arguments
+    /// * `client` - Client of the Redis server
+    /// * `prefix` - Prefix of the keys, to keep the tables of multiple mempools apart (can be
+    ///   empty)
+    pub fn connect(client: Client, prefix: &str) -> Result<Self, RedisError> {
+        let connection = client.get_connection()?;
+        let prefix = if prefix.is_empty() { String::new() } else { format!("{prefix}:") };
+        Ok(Self { client, connection: Mutex::new(connection), prefix })
+    }
+
+    /// Returns the key of the item in the table (`{prefix}{table}:{id}`)
+    pub fn key(&self, table: &str, id: impl fmt::Display) -> String {
+        format!("{}{table}:{id}", self.prefix)
+    }
+
+    /// Returns the key of a single item (`{prefix}{name}`)
+    pub fn single_key(&self, name: &str) -> String {
+        format!("{}{name}", self.prefix)
+    }
+
+    /// Executes the commands on the connection
+    pub fn run<T, E, F>(&self, f: F) -> Result<T, E>
+    where
+        E: From<RedisError>,
+        F: FnOnce(&mut Connection) -> RedisResult<T>,
+    {
+        let mut connection = self.connection.lock();
+        let res = f(&mut connection);
+        if let Err(err) = &res {
+            if err.is_connection_dropped() || err.is_io_error() {
+                if let Ok(reconnected) = self.client.get_connection() {
+                    *connection = reconnected;
+                }
+            }
+        }
+        res.map_err(E::from)
+    }
+
+    /// Deletes all the items of the table
+    pub fn clear_table(&self, table: &str) -> Result<(), RedisError> {
+        let pattern = format!("{}{table}:*", self.prefix);
+        self.run(|con| {
+            let keys: Vec<String> = con.scan_match(&pattern)?.collect();
+            if !keys.is_empty() {
+                con.del::<_, ()>(keys)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// The Redis-based implementation of the [Mempool](crate::mempool::Mempool) and
+/// [ReputationEntryOp](crate::ReputationEntryOp) traits.
+#[derive(Debug)]
+pub struct RedisTable<Table> {
+    pub env: Arc<RedisEnv>,
+    _table: std::marker::PhantomData<Table>,
+}
+
+impl<Table> Clone for RedisTable<Table> {
+    fn clone(&self) -> Self {
+        Self { env: self.env.clone(), _table: std::marker::PhantomData }
+    }
+}
+
+impl<Table> RedisTable<Table> {
+    pub fn new(env: Arc<RedisEnv>) -> Self {
+        Self { env, _table: std::marker::PhantomData }
+    }
+}
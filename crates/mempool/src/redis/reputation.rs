@@ -0,0 +1,124 @@
+use super::{tables::EntitiesReputation, RedisTable};
+use crate::{mempool::ClearOp, reputation::ReputationEntryOp, ReputationError};
+use ethers::types::Address;
+use redis::Commands;
+use silius_primitives::reputation::ReputationEntry;
+use std::{collections::HashMap, str::FromStr};
+
+fn parse_entry(
+    address: Address,
+    fields: HashMap<String, u64>,
+) -> Result<Option<ReputationEntry>, ReputationError> {
+    if fields.is_empty() {
+        return Ok(None);
+    }
+
+    let field = |name: &str| {
+        fields.get(name).copied().ok_or_else(|| ReputationError::Redis {
+            inner: format!("reputation entry of {address:?} is missing {name}"),
+        })
+    };
+    Ok(Some(ReputationEntry {
+        address,
+        uo_seen: field("uo_seen")?,
+        uo_included: field("uo_included")?,
+        status: field("status")?,
+    }))
+}
+
+impl ClearOp for RedisTable<EntitiesReputation> {
+    fn clear(&mut self) {
+        let all = self.env.single_key("reputations");
+        self.env.run::<(), ReputationError, _>(|con| con.del(all)).expect("Clear redis failed");
+        self.env.clear_table("reputation").expect("Clear redis table failed");
+    }
+}
+
+impl ReputationEntryOp for RedisTable<EntitiesReputation> {
+    fn get_entry(&self, addr: &Address) -> Result<Option<ReputationEntry>, ReputationError> {
+        let key = self.env.key("reputation", format!("{addr:?}"));
+        let fields: HashMap<String, u64> = self.env.run(|con| con.hgetall(key))?;
+        parse_entry(*addr, fields)
+    }
+
+    fn set_entry(
+        &mut self,
+        entry: ReputationEntry,
+    ) -> Result<Option<ReputationEntry>, ReputationError> {
+        let addr = format!("{:?}", entry.address);
+        let (key, all) = (self.env.key("reputation", &addr), self.env.single_key("reputations"));
+
+        let (original, _, _): (HashMap<String, u64>, (), ()) = self.env.run(|con| {
+            redis::pipe()
+                .atomic()
+                .hgetall(&key)
+                .hset_multiple(
+                    &key,
+                    &[
+                        ("uo_seen", entry.uo_seen),
+                        ("uo_included", entry.uo_included),
+                        ("status", entry.status),
+                    ],
+                )
+                .sadd(all, addr)
+                .query(con)
+        })?;
+        parse_entry(entry.address, original)
+    }
+
+    fn contains_entry(&self, addr: &Address) -> Result<bool, ReputationError> {
+        let key = self.env.key("reputation", format!("{addr:?}"));
+        self.env.run(|con| con.exists(key))
+    }
+
+    fn get_all(&self) -> Vec<ReputationEntry> {
+        let all = self.env.single_key("reputations");
+        self.env
+            .run::<Vec<String>, ReputationError, _>(|con| con.smembers(all))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|addr| Address::from_str(addr).ok())
+            .filter_map(|addr| self.get_entry(&addr).ok().flatten())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        redis::{tables::EntitiesReputation, RedisEnv, RedisTable},
+        utils::tests::reputation_test_case,
+        Reputation,
+    };
+    use ethers::types::{Address, U256};
+    use parking_lot::RwLock;
+    use silius_primitives::constants::validation::reputation::{
+        BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK,
+    };
+    use std::{collections::HashSet, sync::Arc};
+    use testcontainers::clients::Cli;
+    use testcontainers_modules::redis::Redis;
+
+    #[tokio::test]
+    async fn redis_reputation() {
+        let docker = Cli::default();
+        let node = docker.run(Redis::default());
+        let client =
+            redis::Client::open(format!("redis://127.0.0.1:{}", node.get_host_port_ipv4(6379)))
+                .unwrap();
+
+        let env = Arc::new(RedisEnv::connect(client, "test").unwrap());
+        let entry: Box<RedisTable<EntitiesReputation>> = Box::new(RedisTable::new(env));
+        let reputation = Reputation::new(
+            MIN_INCLUSION_RATE_DENOMINATOR,
+            THROTTLING_SLACK,
+            BAN_SLACK,
+            U256::from(1),
+            U256::from(0),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            entry,
+        );
+        reputation_test_case(reputation);
+    }
+}
@@ -0,0 +1,36 @@
+//! Tables of the Redis mempool (the types only select the implementation of the
+//! [RedisTable](super::RedisTable))
+
+/// Stores the user operations as JSON (`uo:{hash}`, hashes in the `uos` set and their insertion
+/// times in the `uos_inserted_at` sorted set)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperations;
+
+/// Stores the hashes of user operations by sender (`sender:{addr}` sets)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationsBySender;
+
+/// Stores the hashes of user operations by involved entities (`entity:{addr}` sets)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationsByEntity;
+
+/// Stores the hashes of user operations by signature aggregator (`aggregator:{addr}` sets)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationsByAggregator;
+
+/// Stores the hash of user operation by sender and nonce (`nonce:{sender}` hashes)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationsByNonce;
+
+/// Stores the code hashes as JSON (`code_hashes:{hash}`)
+#[derive(Clone, Copy, Debug)]
+pub struct CodeHashes;
+
+/// Stores the receipts of the user operations included on-chain as JSON (`receipt:{hash}`)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationReceipts;
+
+/// Stores the reputation of the entities (`reputation:{addr}` hashes, addresses in the
+/// `reputations` set)
+#[derive(Clone, Copy, Debug)]
+pub struct EntitiesReputation;
@@ -0,0 +1,163 @@
+use crate::{error::ReplacementError, utils::calculate_valid_gas};
+use ethers::types::U256;
+use silius_primitives::{constants::mempool::GAS_INCREASE_PERC, UserOperation};
+
+/// Rules a [UserOperation](UserOperation) has to satisfy to replace another one with the same
+/// sender and nonce that is already in the mempool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplacementPolicy {
+    /// Minimum increase (in percent) of both `maxFeePerGas` and `maxPriorityFeePerGas`
+    pub min_fee_bump_percent: u8,
+}
+
+impl Default for ReplacementPolicy {
+    fn default() -> Self {
+        Self { min_fee_bump_percent: GAS_INCREASE_PERC as u8 }
+    }
+}
+
+impl ReplacementPolicy {
+    pub fn new(min_fee_bump_percent: u8) -> Self {
+        Self { min_fee_bump_percent }
+    }
+
+    /// Returns the lowest fee that is accepted in place of `existing_fee`.
+    pub fn required_fee(&self, existing_fee: U256) -> U256 {
+        calculate_valid_gas(existing_fee, self.min_fee_bump_percent.into())
+    }
+
+    /// Checks whether `new` can replace `existing` in the mempool.
+    ///
+    /// # Arguments
+    /// `existing` - The [UserOperation](UserOperation) that is already in the mempool
+    /// `new` - The [UserOperation](UserOperation) replacing it
+    ///
+    /// # Returns
+    /// Nothing if the replacement is allowed, otherwise a [ReplacementError](ReplacementError)
+    pub fn check_replacement(
+        &self,
+        existing: &UserOperation,
+        new: &UserOperation,
+    ) -> Result<(), ReplacementError> {
+        if existing.sender != new.sender {
+            return Err(ReplacementError::SenderMismatch {
+                existing: existing.sender,
+                new: new.sender,
+            });
+        }
+
+        if existing.nonce != new.nonce {
+            return Err(ReplacementError::NonceMismatch {
+                existing: existing.nonce,
+                new: new.nonce,
+            });
+        }
+
+        for (field, existing_fee, new_fee) in [
+            ("maxFeePerGas", existing.max_fee_per_gas, new.max_fee_per_gas),
+            (
+                "maxPriorityFeePerGas",
+                existing.max_priority_fee_per_gas,
+                new.max_priority_fee_per_gas,
+            ),
+        ] {
+            let required_fee = self.required_fee(existing_fee);
+            if new_fee < required_fee {
+                return Err(ReplacementError::FeeBumpTooLow {
+                    field: field.into(),
+                    existing_fee,
+                    new_fee,
+                    required_fee,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Address;
+    use proptest::prelude::*;
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    fn uo(sender: Address, nonce: u64, max_fee: u64, max_priority_fee: u64) -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default()
+                .sender(sender)
+                .nonce(nonce.into())
+                .max_fee_per_gas(max_fee.into())
+                .max_priority_fee_per_gas(max_priority_fee.into()),
+        )
+    }
+
+    #[test]
+    fn different_sender_or_nonce() {
+        let policy = ReplacementPolicy::default();
+        let existing = uo(Address::random(), 1, 100, 10);
+
+        assert!(matches!(
+            policy.check_replacement(&existing, &uo(Address::random(), 1, 200, 20)),
+            Err(ReplacementError::SenderMismatch { .. })
+        ));
+        assert!(matches!(
+            policy.check_replacement(&existing, &uo(existing.sender, 2, 200, 20)),
+            Err(ReplacementError::NonceMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn priority_fee_not_bumped() {
+        let policy = ReplacementPolicy::default();
+        let existing = uo(Address::random(), 1, 100, 10);
+
+        assert!(matches!(
+            policy.check_replacement(&existing, &uo(existing.sender, 1, 110, 10)),
+            Err(ReplacementError::FeeBumpTooLow { field, existing_fee, new_fee, required_fee })
+                if field == "maxPriorityFeePerGas" &&
+                    existing_fee == 10.into() &&
+                    new_fee == 10.into() &&
+                    required_fee == 11.into()
+        ));
+    }
+
+    proptest! {
+        #[test]
+        fn boundary_at_configured_percent(
+            percent in 0u8..=100,
+            max_fee in 1u64..1_000_000_000_000,
+            max_priority_fee in 1u64..1_000_000_000_000,
+        ) {
+            let policy = ReplacementPolicy::new(percent);
+            let existing = uo(Address::random(), 1, max_fee, max_priority_fee);
+            let required_max_fee = policy.required_fee(max_fee.into()).as_u64();
+            let required_max_priority_fee = policy.required_fee(max_priority_fee.into()).as_u64();
+
+            // exactly the configured bump (rounded up) is accepted
+            prop_assert_eq!(required_max_fee, (max_fee * (100 + percent as u64)).div_ceil(100));
+            prop_assert!(policy
+                .check_replacement(
+                    &existing,
+                    &uo(existing.sender, 1, required_max_fee, required_max_priority_fee)
+                )
+                .is_ok());
+
+            // one wei below on either fee is rejected
+            prop_assert!(policy
+                .check_replacement(
+                    &existing,
+                    &uo(existing.sender, 1, required_max_fee - 1, required_max_priority_fee)
+                )
+                .is_err());
+            prop_assert!(policy
+                .check_replacement(
+                    &existing,
+                    &uo(existing.sender, 1, required_max_fee, required_max_priority_fee - 1)
+                )
+                .is_err());
+        }
+    }
+}
@@ -3,10 +3,17 @@ use dyn_clone::DynClone;
 use ethers::types::{Address, Bytes, U256};
 use parking_lot::RwLock;
 use silius_primitives::{
+    constants::validation::entities::{AGGREGATOR, FACTORY, PAYMASTER, SENDER},
     get_address,
-    reputation::{ReputationEntry, ReputationStatus, StakeInfo, Status},
+    reputation::{ReputationEntry, ReputationStatus, StakeInfo, Status, StatusWithReason},
 };
-use std::{collections::HashSet, fmt::Debug, ops::Deref, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+    ops::Deref,
+    sync::Arc,
+};
+use tracing::{error, warn};
 
 /// Trait representing operations on a HashSet.
 pub trait HashSetOp: Default + Sync + Send {
@@ -57,6 +64,39 @@ impl<T: HashSetOp> HashSetOp for Arc<RwLock<T>> {
         self.read().is_in_list(addr)
     }
 }
+/// Trait representing the storage the whitelist or blacklist is persisted in.
+pub trait EntityListOp: Sync + Send + Debug + DynClone {
+    /// Adds the given address to the stored list.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to be added.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the address was stored, or an `Err` if an error occurred.
+    fn add(&mut self, addr: &Address) -> Result<(), ReputationError>;
+
+    /// Removes the given address from the stored list.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to be removed.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the address isn't stored anymore, or an `Err` if an error occurred.
+    fn remove(&mut self, addr: &Address) -> Result<(), ReputationError>;
+
+    /// Retrieves all addresses of the stored list.
+    ///
+    /// # Returns
+    ///
+    /// Returns a vector containing all stored addresses, or an `Err` if an error occurred.
+    fn get_all(&self) -> Result<Vec<Address>, ReputationError>;
+}
+dyn_clone::clone_trait_object!(EntityListOp);
+
 /// Trait representing operations on a reputation entry.
 pub trait ReputationEntryOp: ClearOp + Sync + Send + Debug + DynClone {
     /// Retrieves the reputation entry associated with the given address.
@@ -150,6 +190,14 @@ impl<T: ReputationEntryOp> ReputationEntryOp for Arc<RwLock<T>> {
     }
 }
 
+/// Minimum stake and unstake delay of an entity type
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakeRequirements {
+    pub min_stake: U256,
+    /// The unstake delay of the entity type isn't enforced if it's not set
+    pub min_unstake_delay: Option<U256>,
+}
+
 #[derive(Debug)]
 pub struct Reputation {
     /// Minimum denominator for calculating the minimum expected inclusions
@@ -162,10 +210,16 @@ pub struct Reputation {
     min_stake: U256,
     /// Minimum time requuired to unstake
     min_unstake_delay: U256,
+    /// Stake requirements of the entity types that differ from the ones above
+    per_entity_requirements: HashMap<&'static str, StakeRequirements>,
     /// Whitelisted addresses
     whitelist: Arc<RwLock<HashSet<Address>>>,
     /// Blacklisted addreses
     blacklist: Arc<RwLock<HashSet<Address>>>,
+    /// Storage the whitelist is persisted in (None if it's only kept in memory)
+    whitelist_store: Option<Box<dyn EntityListOp>>,
+    /// Storage the blacklist is persisted in (None if it's only kept in memory)
+    blacklist_store: Option<Box<dyn EntityListOp>>,
     /// Entities' repuation registry
     entities: Box<dyn ReputationEntryOp>,
 }
@@ -178,8 +232,11 @@ impl Clone for Reputation {
             ban_slack: self.ban_slack,
             min_stake: self.min_stake,
             min_unstake_delay: self.min_unstake_delay,
+            per_entity_requirements: self.per_entity_requirements.clone(),
             whitelist: self.whitelist.clone(),
             blacklist: self.blacklist.clone(),
+            whitelist_store: self.whitelist_store.clone(),
+            blacklist_store: self.blacklist_store.clone(),
             entities: self.entities.clone(),
         }
     }
@@ -203,12 +260,69 @@ impl Reputation {
             ban_slack,
             min_stake,
             min_unstake_delay,
+            per_entity_requirements: HashMap::new(),
             whitelist,
             blacklist,
+            whitelist_store: None,
+            blacklist_store: None,
             entities,
         }
     }
 
+    /// Persists the whitelist and blacklist in the given storages. The addresses stored by
+    /// earlier runs are added to the lists.
+    ///
+    /// # Arguments
+    /// * `whitelist` - The storage of the whitelist
+    /// * `blacklist` - The storage of the blacklist
+    ///
+    /// # Returns
+    /// * `Ok(Self)` if the stored addresses were loaded
+    pub fn with_entity_lists(
+        mut self,
+        whitelist: Box<dyn EntityListOp>,
+        blacklist: Box<dyn EntityListOp>,
+    ) -> Result<Self, ReputationError> {
+        for addr in whitelist.get_all()? {
+            self.whitelist.add_into_list(&addr);
+        }
+        for addr in blacklist.get_all()? {
+            self.blacklist.add_into_list(&addr);
+        }
+        self.whitelist_store = Some(whitelist);
+        self.blacklist_store = Some(blacklist);
+        Ok(self)
+    }
+
+    /// Sets the stake requirements of the entity types (`account`, `factory`, `paymaster` or
+    /// `aggregator`). The other entity types keep the default minimum stake and unstake delay.
+    ///
+    /// # Arguments
+    /// * `requirements` - The stake requirements by entity type
+    pub fn with_entity_stake_requirements(
+        mut self,
+        requirements: HashMap<String, StakeRequirements>,
+    ) -> Self {
+        for (entity, requirement) in requirements {
+            match [SENDER, FACTORY, PAYMASTER, AGGREGATOR].into_iter().find(|e| *e == entity) {
+                Some(entity) => {
+                    self.per_entity_requirements.insert(entity, requirement);
+                }
+                None => warn!("Ignoring stake requirements of unknown entity type {entity}"),
+            }
+        }
+        self
+    }
+
+    /// Replaces the registry the reputation entries are stored in
+    ///
+    /// # Arguments
+    /// * `entities` - The new registry
+    pub fn with_entities(mut self, entities: Box<dyn ReputationEntryOp>) -> Self {
+        self.entities = entities;
+        self
+    }
+
     /// Set the default reputation entry for an address.
     /// It would do nothing if the address already exists.
     ///
@@ -295,6 +409,7 @@ impl Reputation {
     ///
     /// * `true` if the address was added successfully. Otherwise, `false`
     pub fn add_whitelist(&mut self, addr: &Address) -> bool {
+        persist_list_change(&mut self.whitelist_store, addr, |store, addr| store.add(addr));
         self.whitelist.add_into_list(addr)
     }
 
@@ -305,6 +420,7 @@ impl Reputation {
     ///
     /// * `true` if the address was removed successfully. Otherwise, `false
     pub fn remove_whitelist(&mut self, addr: &Address) -> bool {
+        persist_list_change(&mut self.whitelist_store, addr, |store, addr| store.remove(addr));
         self.whitelist.remove_from_list(addr)
     }
 
@@ -327,6 +443,7 @@ impl Reputation {
     /// # Returns
     /// * `true` if the address was added successfully. Otherwise, `false
     pub fn add_blacklist(&mut self, addr: &Address) -> bool {
+        persist_list_change(&mut self.blacklist_store, addr, |store, addr| store.add(addr));
         self.blacklist.add_into_list(addr)
     }
 
@@ -338,6 +455,7 @@ impl Reputation {
     /// # Returns
     /// * `true` if the address was removed successfully. Otherwise, `false
     pub fn remove_blacklist(&mut self, addr: &Address) -> bool {
+        persist_list_change(&mut self.blacklist_store, addr, |store, addr| store.remove(addr));
         self.blacklist.remove_from_list(addr)
     }
 
@@ -360,6 +478,14 @@ impl Reputation {
         self.min_unstake_delay
     }
 
+    /// Stake requirements of the entity type (the default ones if none are set for it)
+    pub fn stake_requirements(&self, entity: &str) -> StakeRequirements {
+        self.per_entity_requirements
+            .get(entity)
+            .copied()
+            .unwrap_or(StakeRequirements { min_stake: self.min_stake, min_unstake_delay: None })
+    }
+
     /// Get an entity's reputation status
     ///
     /// # Arguments
@@ -378,10 +504,10 @@ impl Reputation {
 
         Ok(match self.entities.get_entry(addr)? {
             Some(ent) => {
-                let max_seen = ent.uo_seen / self.min_inclusion_denominator;
-                if max_seen > ent.uo_included + self.ban_slack {
+                let min_seen = ent.uo_included * self.min_inclusion_denominator;
+                if ent.uo_seen > min_seen + self.ban_slack {
                     Status::BANNED.into()
-                } else if max_seen > ent.uo_included + self.throttling_slack {
+                } else if ent.uo_seen > min_seen + self.throttling_slack {
                     Status::THROTTLED.into()
                 } else {
                     Status::OK.into()
@@ -391,6 +517,79 @@ impl Reputation {
         })
     }
 
+    /// Get an entity's reputation status together with the inclusion rate and the threshold it's
+    /// compared against
+    ///
+    /// # Arguments
+    /// * `addr` - The address to get the status of
+    ///
+    /// # Returns
+    /// * `Ok(StatusWithReason)` - The status (OK if the address isn't known) and why it isn't OK
+    pub fn get_status_with_reason(
+        &self,
+        addr: &Address,
+    ) -> Result<StatusWithReason, ReputationError> {
+        let ent = self
+            .entities
+            .get_entry(addr)?
+            .unwrap_or_else(|| ReputationEntry::default_with_addr(*addr));
+        // the entity is throttled (banned) if fewer than `(uo_seen - slack) / denominator` of the
+        // seen user operations are included
+        let threshold = |slack: u64| {
+            if ent.uo_seen == 0 {
+                0.0
+            } else {
+                ent.uo_seen.saturating_sub(slack) as f64 /
+                    self.min_inclusion_denominator as f64 /
+                    ent.uo_seen as f64
+            }
+        };
+        let min_seen = ent.uo_included * self.min_inclusion_denominator;
+        let mut res = StatusWithReason {
+            status: Status::OK,
+            uo_seen: ent.uo_seen,
+            uo_included: ent.uo_included,
+            inclusion_rate: if ent.uo_seen == 0 {
+                1.0
+            } else {
+                ent.uo_included as f64 / ent.uo_seen as f64
+            },
+            threshold: threshold(self.throttling_slack),
+            reason: None,
+        };
+
+        if self.whitelist.is_in_list(addr) {
+            return Ok(res);
+        }
+
+        if self.blacklist.is_in_list(addr) {
+            res.status = Status::BANNED;
+            res.reason = Some("blacklisted".into());
+            return Ok(res);
+        }
+
+        let kind = if ent.uo_seen > min_seen + self.ban_slack {
+            res.status = Status::BANNED;
+            res.threshold = threshold(self.ban_slack);
+            "ban"
+        } else if ent.uo_seen > min_seen + self.throttling_slack {
+            res.status = Status::THROTTLED;
+            "throttling"
+        } else {
+            return Ok(res);
+        };
+        res.reason = Some(format!(
+            "inclusion rate {:.1}% ({} of {} user operations included) is below the {kind} \
+             threshold {:.1}%",
+            res.inclusion_rate * 100.0,
+            ent.uo_included,
+            ent.uo_seen,
+            res.threshold * 100.0
+        ));
+
+        Ok(res)
+    }
+
     /// Update an entity's status when the user operation is reverted.
     ///
     /// # Arguments
@@ -415,8 +614,8 @@ impl Reputation {
     /// # Arguments
     /// * `entity` - The entity type
     /// * `info` - The entity's [stake information](StakeInfo)
-    /// * `min_stake` - Min stake required. If set, this value has higher priority than the struct's
-    ///   value.
+    /// * `min_stake` - Min stake required. If set, this value has higher priority than the stake
+    ///   requirements of the entity type.
     /// * `min_unstake_delay` - Min unstake delay required. If set, this value has higher priority
     ///   than the struct's value.
     ///
@@ -437,8 +636,10 @@ impl Reputation {
                 return Ok(());
             }
 
-            let min_stake =
-                if let Some(min_stake) = min_stake { min_stake } else { self.min_stake };
+            let requirements = self.per_entity_requirements.get(entity);
+            let min_stake = min_stake
+                .or(requirements.map(|requirements| requirements.min_stake))
+                .unwrap_or(self.min_stake);
 
             // TODO: use this value below
             let _min_unstake_delay = if let Some(min_unstake_delay) = min_unstake_delay {
@@ -446,15 +647,20 @@ impl Reputation {
             } else {
                 self.min_unstake_delay
             };
+            // the default minimum unstake delay isn't enforced until the spec tests are updated,
+            // only the one set for the entity type
+            let required_unstake_delay = requirements
+                .and_then(|requirements| requirements.min_unstake_delay)
+                .unwrap_or(U256::from(2));
 
             let err = if info.stake < min_stake {
                 ReputationError::StakeTooLow {
                     entity: entity.into(),
                     address: info.address,
                     stake: info.stake,
-                    min_stake: self.min_stake,
+                    min_stake,
                 }
-            } else if info.unstake_delay < U256::from(2)
+            } else if info.unstake_delay < required_unstake_delay
             // TODO: remove this when spec tests are updated!!!!
             /* min_unstake_delay */
             {
@@ -462,7 +668,9 @@ impl Reputation {
                     address: info.address,
                     entity: entity.into(),
                     unstake_delay: info.unstake_delay,
-                    min_unstake_delay: self.min_unstake_delay,
+                    min_unstake_delay: requirements
+                        .and_then(|requirements| requirements.min_unstake_delay)
+                        .unwrap_or(self.min_unstake_delay),
                 }
             } else {
                 return Ok(());
@@ -526,6 +734,19 @@ impl Reputation {
     }
 }
 
+/// Applies a change of the whitelist or blacklist to its storage (if it's persisted). A failed
+/// write is only logged, the list in memory is changed anyway.
+fn persist_list_change<F>(store: &mut Option<Box<dyn EntityListOp>>, addr: &Address, change: F)
+where
+    F: FnOnce(&mut dyn EntityListOp, &Address) -> Result<(), ReputationError>,
+{
+    if let Some(store) = store.as_mut() {
+        if let Err(err) = change(store.as_mut(), addr) {
+            error!("Failed to persist the list change of {addr:?}: {err:?}");
+        }
+    }
+}
+
 // impl<H, R> Reputation<H, R>
 // where
 //     H: HashSetOp + Default,
@@ -0,0 +1,567 @@
+use super::{
+    tables::{
+        CodeHashes, UserOperationReceipts, UserOperations, UserOperationsByAggregator,
+        UserOperationsByEntity, UserOperationsByNonce, UserOperationsBySender,
+    },
+    SqliteTable,
+};
+use crate::{
+    mempool::{
+        AddRemoveUserOp, AddRemoveUserOpHash, ClearOp, UserOperationAddrOp,
+        UserOperationCodeHashOp, UserOperationNonceOp, UserOperationOp, UserOperationReceiptOp,
+    },
+    utils::sort_by_effective_gas_price,
+    MempoolErrorKind,
+};
+use ethers::{
+    abi::{AbiDecode, AbiEncode},
+    types::{Address, U256},
+};
+use silius_primitives::{
+    simulation::CodeHash, UserOperation, UserOperationHash, UserOperationReceipt,
+    UserOperationSigned,
+};
+use sqlx::{sqlite::SqliteRow, Row};
+use std::{
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Addresses are stored as lowercase hex strings (the same as the hashes)
+fn addr_key(addr: &Address) -> String {
+    format!("{addr:?}")
+}
+
+fn decode_err(e: impl std::fmt::Display) -> MempoolErrorKind {
+    MempoolErrorKind::Sqlite { inner: format!("decode error: {e}") }
+}
+
+fn uo_hash_from_row(row: &SqliteRow) -> Result<UserOperationHash, MempoolErrorKind> {
+    UserOperationHash::from_str(row.try_get("hash")?).map_err(decode_err)
+}
+
+/// Selects the user operations together with their signature aggregators
+const SELECT_USER_OPERATIONS: &str = "SELECT u.hash, u.data, a.aggregator FROM user_operations u \
+                                      LEFT JOIN aggregator_index a ON a.hash = u.hash";
+
+fn uo_from_row(row: &SqliteRow) -> Result<UserOperation, MempoolErrorKind> {
+    let uo = UserOperationSigned::decode(row.try_get::<Vec<u8>, _>("data")?).map_err(decode_err)?;
+    let mut uo = UserOperation::from_user_operation_signed(uo_hash_from_row(row)?, uo);
+    uo.aggregator = row
+        .try_get::<Option<String>, _>("aggregator")?
+        .map(|aggregator| Address::from_str(&aggregator).map_err(decode_err))
+        .transpose()?;
+    Ok(uo)
+}
+
+impl AddRemoveUserOp for SqliteTable<UserOperations> {
+    fn add(&mut self, uo: UserOperation) -> Result<UserOperationHash, MempoolErrorKind> {
+        let uo_hash = uo.hash;
+        let sender = addr_key(&uo.sender);
+        let data = uo.user_operation.encode();
+        let inserted_at =
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+
+        self.env.run(|pool| async move {
+            // the insertion time of a replaced user operation is kept
+            sqlx::query(
+                "INSERT INTO user_operations (hash, data, sender, inserted_at) VALUES (?, ?, ?, ?) \
+                 ON CONFLICT (hash) DO UPDATE SET data = excluded.data, sender = excluded.sender",
+            )
+            .bind(uo_hash.to_string())
+            .bind(data)
+            .bind(sender)
+            .bind(inserted_at as i64)
+            .execute(&pool)
+            .await?;
+            Ok(uo_hash)
+        })
+    }
+
+    fn remove_by_uo_hash(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let uo_hash = uo_hash.to_string();
+        self.env.run(|pool| async move {
+            let res = sqlx::query("DELETE FROM user_operations WHERE hash = ?")
+                .bind(uo_hash)
+                .execute(&pool)
+                .await?;
+            Ok(res.rows_affected() > 0)
+        })
+    }
+}
+
+impl UserOperationOp for SqliteTable<UserOperations> {
+    fn get_by_uo_hash(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperation>, MempoolErrorKind> {
+        let uo_hash = uo_hash.to_string();
+        self.env.run(|pool| async move {
+            let query = format!("{SELECT_USER_OPERATIONS} WHERE u.hash = ?");
+            sqlx::query(&query)
+                .bind(uo_hash)
+                .fetch_optional(&pool)
+                .await?
+                .map(|row| uo_from_row(&row))
+                .transpose()
+        })
+    }
+
+    fn get_sorted(&self, base_fee_per_gas: U256) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        let mut uos = self.get_all()?;
+        sort_by_effective_gas_price(&mut uos, base_fee_per_gas);
+        Ok(uos)
+    }
+
+    fn get_all(&self) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        self.env.run(|pool| async move {
+            sqlx::query(SELECT_USER_OPERATIONS)
+                .fetch_all(&pool)
+                .await?
+                .iter()
+                .map(uo_from_row)
+                .collect::<Result<Vec<_>, _>>()
+        })
+    }
+
+    fn get_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<UserOperation>, MempoolErrorKind> {
+        // the hashes are lowercase hex strings of the same length, so they sort as the bytes
+        self.env.run(|pool| async move {
+            let query = format!("{SELECT_USER_OPERATIONS} ORDER BY u.hash LIMIT ? OFFSET ?");
+            sqlx::query(&query)
+                .bind(limit as i64)
+                .bind(offset as i64)
+                .fetch_all(&pool)
+                .await?
+                .iter()
+                .map(uo_from_row)
+                .collect::<Result<Vec<_>, _>>()
+        })
+    }
+
+    fn len(&self) -> Result<usize, MempoolErrorKind> {
+        self.env.run(|pool| async move {
+            let len: i64 =
+                sqlx::query("SELECT COUNT(*) FROM user_operations").fetch_one(&pool).await?.get(0);
+            Ok(len as usize)
+        })
+    }
+}
+
+// The sender is stored with the user operation, so the index only has to be read.
+impl AddRemoveUserOpHash for SqliteTable<UserOperationsBySender> {
+    fn add(
+        &mut self,
+        _address: &Address,
+        _uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        Ok(())
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        address: &Address,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        Ok(self.get_all_by_address(address).contains(uo_hash))
+    }
+}
+
+impl UserOperationAddrOp for SqliteTable<UserOperationsBySender> {
+    fn get_all_by_address(&self, address: &Address) -> Vec<UserOperationHash> {
+        let sender = addr_key(address);
+        self.env
+            .run(|pool| async move {
+                sqlx::query("SELECT hash FROM user_operations WHERE sender = ?")
+                    .bind(sender)
+                    .fetch_all(&pool)
+                    .await?
+                    .iter()
+                    .map(uo_hash_from_row)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .unwrap_or_else(|_| vec![])
+    }
+}
+
+impl AddRemoveUserOpHash for SqliteTable<UserOperationsByEntity> {
+    fn add(
+        &mut self,
+        address: &Address,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        let entity = addr_key(address);
+        self.env.run(|pool| async move {
+            sqlx::query("INSERT OR IGNORE INTO entity_index (entity_addr, hash) VALUES (?, ?)")
+                .bind(entity)
+                .bind(uo_hash.to_string())
+                .execute(&pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        address: &Address,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let (entity, uo_hash) = (addr_key(address), uo_hash.to_string());
+        self.env.run(|pool| async move {
+            let res = sqlx::query("DELETE FROM entity_index WHERE entity_addr = ? AND hash = ?")
+                .bind(entity)
+                .bind(uo_hash)
+                .execute(&pool)
+                .await?;
+            Ok(res.rows_affected() > 0)
+        })
+    }
+}
+
+impl UserOperationAddrOp for SqliteTable<UserOperationsByEntity> {
+    fn get_all_by_address(&self, address: &Address) -> Vec<UserOperationHash> {
+        let entity = addr_key(address);
+        self.env
+            .run(|pool| async move {
+                sqlx::query("SELECT hash FROM entity_index WHERE entity_addr = ?")
+                    .bind(entity)
+                    .fetch_all(&pool)
+                    .await?
+                    .iter()
+                    .map(uo_hash_from_row)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .unwrap_or_else(|_| vec![])
+    }
+}
+
+// A user operation has at most one aggregator, so the index is keyed by the hash.
+impl AddRemoveUserOpHash for SqliteTable<UserOperationsByAggregator> {
+    fn add(
+        &mut self,
+        address: &Address,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        let aggregator = addr_key(address);
+        self.env.run(|pool| async move {
+            sqlx::query("INSERT OR REPLACE INTO aggregator_index (aggregator, hash) VALUES (?, ?)")
+                .bind(aggregator)
+                .bind(uo_hash.to_string())
+                .execute(&pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        address: &Address,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let (aggregator, uo_hash) = (addr_key(address), uo_hash.to_string());
+        self.env.run(|pool| async move {
+            let res = sqlx::query("DELETE FROM aggregator_index WHERE aggregator = ? AND hash = ?")
+                .bind(aggregator)
+                .bind(uo_hash)
+                .execute(&pool)
+                .await?;
+            Ok(res.rows_affected() > 0)
+        })
+    }
+}
+
+impl UserOperationAddrOp for SqliteTable<UserOperationsByAggregator> {
+    fn get_all_by_address(&self, address: &Address) -> Vec<UserOperationHash> {
+        let aggregator = addr_key(address);
+        self.env
+            .run(|pool| async move {
+                sqlx::query("SELECT hash FROM aggregator_index WHERE aggregator = ?")
+                    .bind(aggregator)
+                    .fetch_all(&pool)
+                    .await?
+                    .iter()
+                    .map(uo_hash_from_row)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .unwrap_or_else(|_| vec![])
+    }
+}
+
+impl UserOperationNonceOp for SqliteTable<UserOperationsByNonce> {
+    fn add(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: UserOperationHash,
+    ) -> Result<(), MempoolErrorKind> {
+        let sender = addr_key(sender);
+        self.env.run(|pool| async move {
+            sqlx::query(
+                "INSERT OR REPLACE INTO nonce_index (sender, nonce, hash) VALUES (?, ?, ?)",
+            )
+            .bind(sender)
+            .bind(nonce.to_string())
+            .bind(uo_hash.to_string())
+            .execute(&pool)
+            .await?;
+            Ok(())
+        })
+    }
+
+    fn remove_uo_hash(
+        &mut self,
+        sender: &Address,
+        nonce: U256,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let (sender, uo_hash) = (addr_key(sender), uo_hash.to_string());
+        self.env.run(|pool| async move {
+            let res =
+                sqlx::query("DELETE FROM nonce_index WHERE sender = ? AND nonce = ? AND hash = ?")
+                    .bind(sender)
+                    .bind(nonce.to_string())
+                    .bind(uo_hash)
+                    .execute(&pool)
+                    .await?;
+            Ok(res.rows_affected() > 0)
+        })
+    }
+
+    fn get_by_sender_and_nonce(
+        &self,
+        sender: &Address,
+        nonce: U256,
+    ) -> Result<Option<UserOperationHash>, MempoolErrorKind> {
+        let sender = addr_key(sender);
+        self.env.run(|pool| async move {
+            sqlx::query("SELECT hash FROM nonce_index WHERE sender = ? AND nonce = ?")
+                .bind(sender)
+                .bind(nonce.to_string())
+                .fetch_optional(&pool)
+                .await?
+                .map(|row| uo_hash_from_row(&row))
+                .transpose()
+        })
+    }
+}
+
+impl UserOperationCodeHashOp for SqliteTable<CodeHashes> {
+    fn has_code_hashes(&self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let uo_hash = uo_hash.to_string();
+        self.env.run(|pool| async move {
+            let row = sqlx::query("SELECT 1 FROM code_hashes WHERE hash = ?")
+                .bind(uo_hash)
+                .fetch_optional(&pool)
+                .await?;
+            Ok(row.is_some())
+        })
+    }
+
+    fn set_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+        hashes: Vec<CodeHash>,
+    ) -> Result<(), MempoolErrorKind> {
+        let uo_hash = uo_hash.to_string();
+        let data = serde_json::to_vec(&hashes).map_err(decode_err)?;
+        self.env.run(|pool| async move {
+            sqlx::query("INSERT OR REPLACE INTO code_hashes (hash, data) VALUES (?, ?)")
+                .bind(uo_hash)
+                .bind(data)
+                .execute(&pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn get_code_hashes(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Vec<CodeHash>, MempoolErrorKind> {
+        let uo_hash = uo_hash.to_string();
+        self.env.run(|pool| async move {
+            match sqlx::query("SELECT data FROM code_hashes WHERE hash = ?")
+                .bind(uo_hash)
+                .fetch_optional(&pool)
+                .await?
+            {
+                Some(row) => serde_json::from_slice::<Vec<CodeHash>>(row.try_get("data")?)
+                    .map_err(decode_err),
+                None => Ok(vec![]),
+            }
+        })
+    }
+
+    fn remove_code_hashes(
+        &mut self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<bool, MempoolErrorKind> {
+        let uo_hash = uo_hash.to_string();
+        self.env.run(|pool| async move {
+            let res = sqlx::query("DELETE FROM code_hashes WHERE hash = ?")
+                .bind(uo_hash)
+                .execute(&pool)
+                .await?;
+            Ok(res.rows_affected() > 0)
+        })
+    }
+}
+
+impl UserOperationReceiptOp for SqliteTable<UserOperationReceipts> {
+    fn add_receipt(&mut self, receipt: UserOperationReceipt) -> Result<(), MempoolErrorKind> {
+        let uo_hash = receipt.user_operation_hash.to_string();
+        let data = serde_json::to_vec(&receipt).map_err(decode_err)?;
+        self.env.run(|pool| async move {
+            sqlx::query("INSERT OR REPLACE INTO receipts (hash, data) VALUES (?, ?)")
+                .bind(uo_hash)
+                .bind(data)
+                .execute(&pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn get_receipt(
+        &self,
+        uo_hash: &UserOperationHash,
+    ) -> Result<Option<UserOperationReceipt>, MempoolErrorKind> {
+        let uo_hash = uo_hash.to_string();
+        self.env.run(|pool| async move {
+            sqlx::query("SELECT data FROM receipts WHERE hash = ?")
+                .bind(uo_hash)
+                .fetch_optional(&pool)
+                .await?
+                .map(|row| {
+                    serde_json::from_slice::<UserOperationReceipt>(row.try_get("data")?)
+                        .map_err(decode_err)
+                })
+                .transpose()
+        })
+    }
+
+    fn remove_receipt(&mut self, uo_hash: &UserOperationHash) -> Result<bool, MempoolErrorKind> {
+        let uo_hash = uo_hash.to_string();
+        self.env.run(|pool| async move {
+            let res = sqlx::query("DELETE FROM receipts WHERE hash = ?")
+                .bind(uo_hash)
+                .execute(&pool)
+                .await?;
+            Ok(res.rows_affected() > 0)
+        })
+    }
+}
+
+macro_rules! impl_clear {
+    ($table: ident, $statement: literal) => {
+        impl ClearOp for SqliteTable<$table> {
+            fn clear(&mut self) {
+                self.env
+                    .run(|pool| async move {
+                        sqlx::query($statement).execute(&pool).await?;
+                        Ok(())
+                    })
+                    .expect("Clear database failed");
+            }
+        }
+    };
+}
+impl_clear!(UserOperations, "DELETE FROM user_operations");
+impl_clear!(UserOperationsByEntity, "DELETE FROM entity_index");
+impl_clear!(UserOperationsByAggregator, "DELETE FROM aggregator_index");
+impl_clear!(UserOperationsByNonce, "DELETE FROM nonce_index");
+impl_clear!(CodeHashes, "DELETE FROM code_hashes");
+impl_clear!(UserOperationReceipts, "DELETE FROM receipts");
+
+impl ClearOp for SqliteTable<UserOperationsBySender> {
+    // the senders are cleared with the user operations
+    fn clear(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        sqlite::{
+            tables::{
+                CodeHashes, UserOperationReceipts, UserOperations, UserOperationsByAggregator,
+                UserOperationsByEntity, UserOperationsByNonce, UserOperationsBySender,
+            },
+            SqliteEnv, SqliteTable,
+        },
+        utils::tests::{
+            aggregator_test_case, capacity_tracker_test_case, expiry_test_case, mempool_test_case,
+            receipts_test_case, sort_by_effective_gas_price_test_case,
+        },
+        Mempool,
+    };
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn sqlite_mempool(env: Arc<SqliteEnv>) -> Mempool {
+        Mempool::new(
+            Box::new(SqliteTable::<UserOperations>::new(env.clone())),
+            Box::new(SqliteTable::<UserOperationsBySender>::new(env.clone())),
+            Box::new(SqliteTable::<UserOperationsByEntity>::new(env.clone())),
+            Box::new(SqliteTable::<UserOperationsByAggregator>::new(env.clone())),
+            Box::new(SqliteTable::<CodeHashes>::new(env.clone())),
+            Box::new(SqliteTable::<UserOperationsByNonce>::new(env.clone())),
+            Box::new(SqliteTable::<UserOperationReceipts>::new(env)),
+        )
+    }
+
+    #[allow(clippy::unit_cmp)]
+    #[tokio::test]
+    async fn sqlite_mempool_memory() {
+        let env = Arc::new(SqliteEnv::connect(":memory:").unwrap());
+        mempool_test_case(sqlite_mempool(env));
+    }
+
+    #[allow(clippy::unit_cmp)]
+    #[tokio::test]
+    async fn sqlite_mempool_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("mempool.db");
+
+        let env = Arc::new(SqliteEnv::connect(path.to_str().unwrap()).unwrap());
+        mempool_test_case(sqlite_mempool(env.clone()));
+
+        // reopen the mempool on the same database, the capacity tracker starts from the stored
+        // user operations
+        drop(env);
+        let env = Arc::new(SqliteEnv::connect(path.to_str().unwrap()).unwrap());
+        capacity_tracker_test_case(sqlite_mempool(env));
+    }
+
+    #[tokio::test]
+    async fn sqlite_mempool_capacity_tracker() {
+        let env = Arc::new(SqliteEnv::connect(":memory:").unwrap());
+        capacity_tracker_test_case(sqlite_mempool(env));
+    }
+
+    #[tokio::test]
+    async fn sqlite_mempool_sort_by_effective_gas_price() {
+        let env = Arc::new(SqliteEnv::connect(":memory:").unwrap());
+        sort_by_effective_gas_price_test_case(sqlite_mempool(env));
+    }
+
+    #[tokio::test]
+    async fn sqlite_mempool_expiry() {
+        let env = Arc::new(SqliteEnv::connect(":memory:").unwrap());
+        expiry_test_case(sqlite_mempool(env));
+    }
+
+    #[tokio::test]
+    async fn sqlite_mempool_receipts() {
+        let env = Arc::new(SqliteEnv::connect(":memory:").unwrap());
+        receipts_test_case(sqlite_mempool(env));
+    }
+
+    #[tokio::test]
+    async fn sqlite_mempool_aggregator() {
+        let env = Arc::new(SqliteEnv::connect(":memory:").unwrap());
+        aggregator_test_case(sqlite_mempool(env));
+    }
+}
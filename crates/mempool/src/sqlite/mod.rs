@@ -0,0 +1,132 @@
+//! The SQLite implementation of the [Mempool](crate::mempool::Mempool) trait. A portable
+//! alternative to the MDBX database (no C library with platform-specific build requirements).
+
+use crate::MempoolErrorKind;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use std::{future::Future, str::FromStr, sync::Arc};
+use tokio::runtime::{Builder, Runtime};
+
+pub mod mempool;
+pub mod tables;
+
+/// Statements creating the tables of the mempool
+const SCHEMA: [&str; 7] = [
+    "CREATE TABLE IF NOT EXISTS user_operations (
+        hash TEXT PRIMARY KEY,
+        data BLOB NOT NULL,
+        sender TEXT NOT NULL,
+        inserted_at INTEGER NOT NULL
+    )",
+    "CREATE INDEX IF NOT EXISTS user_operations_sender ON user_operations (sender, hash)",
+    "CREATE TABLE IF NOT EXISTS entity_index (
+        entity_addr TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        PRIMARY KEY (entity_addr, hash)
+    )",
+    "CREATE TABLE IF NOT EXISTS aggregator_index (
+        aggregator TEXT NOT NULL,
+        hash TEXT PRIMARY KEY
+    )",
+    "CREATE TABLE IF NOT EXISTS nonce_index (
+        sender TEXT NOT NULL,
+        nonce TEXT NOT NULL,
+        hash TEXT NOT NULL,
+        PRIMARY KEY (sender, nonce)
+    )",
+    "CREATE TABLE IF NOT EXISTS code_hashes (
+        hash TEXT PRIMARY KEY,
+        data BLOB NOT NULL
+    )",
+    "CREATE TABLE IF NOT EXISTS receipts (
+        hash TEXT PRIMARY KEY,
+        data BLOB NOT NULL
+    )",
+];
+
+/// Runtime the queries are executed on (shut down in the background when dropped, since the
+/// mempool is usually dropped inside of an async context)
+#[derive(Debug)]
+struct SqliteRuntime(Option<Runtime>);
+
+impl Drop for SqliteRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
+/// SQLite database the mempool tables are stored in
+///
+/// The mempool traits are synchronous, so the queries are executed on a dedicated runtime and
+/// the caller blocks until they finish (the same as with the MDBX transactions).
+#[derive(Debug)]
+pub struct SqliteEnv {
+    pub pool: SqlitePool,
+    runtime: SqliteRuntime,
+}
+
+impl SqliteEnv {
+    /// Opens the database (created if missing) and creates the tables
+    ///
+    /// # Arguments
+    /// * `url` - Path of the SQLite database file or `:memory:` for an in-memory database
+    pub fn connect(url: &str) -> eyre::Result<Self> {
+        let runtime = Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("silius-sqlite")
+            .enable_all()
+            .build()?;
+
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        // every connection to the in-memory database shares it, so it's kept open
+        let pool_options = if url.trim_start_matches("sqlite:") == ":memory:" {
+            SqlitePoolOptions::new().max_connections(1)
+        } else {
+            SqlitePoolOptions::new()
+        }
+        .idle_timeout(None)
+        .max_lifetime(None);
+
+        let pool = futures::executor::block_on(runtime.spawn(async move {
+            let pool = pool_options.connect_with(options).await?;
+            for statement in SCHEMA {
+                sqlx::query(statement).execute(&pool).await?;
+            }
+            Ok::<_, sqlx::Error>(pool)
+        }))??;
+
+        Ok(Self { pool, runtime: SqliteRuntime(Some(runtime)) })
+    }
+
+    /// Executes the queries on the runtime of the database and waits for the result
+    pub fn run<T, F, Fut>(&self, f: F) -> Result<T, MempoolErrorKind>
+    where
+        T: Send + 'static,
+        F: FnOnce(SqlitePool) -> Fut,
+        Fut: Future<Output = Result<T, MempoolErrorKind>> + Send + 'static,
+    {
+        let runtime = self.runtime.0.as_ref().expect("Runtime is running");
+        futures::executor::block_on(runtime.spawn(f(self.pool.clone())))
+            .map_err(|e| MempoolErrorKind::Sqlite { inner: e.to_string() })?
+    }
+}
+
+/// The SQLite-based implementation of the [Mempool](crate::mempool::Mempool) trait.
+#[derive(Debug)]
+pub struct SqliteTable<Table> {
+    pub env: Arc<SqliteEnv>,
+    _table: std::marker::PhantomData<Table>,
+}
+
+impl<Table> Clone for SqliteTable<Table> {
+    fn clone(&self) -> Self {
+        Self { env: self.env.clone(), _table: std::marker::PhantomData }
+    }
+}
+
+impl<Table> SqliteTable<Table> {
+    pub fn new(env: Arc<SqliteEnv>) -> Self {
+        Self { env, _table: std::marker::PhantomData }
+    }
+}
@@ -0,0 +1,30 @@
+//! Tables of the SQLite mempool (the types only select the implementation of the
+//! [SqliteTable](super::SqliteTable))
+
+/// Stores the user operations (`user_operations`)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperations;
+
+/// Hashes of user operations by sender, read from the `sender` column of `user_operations`
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationsBySender;
+
+/// Stores the hashes of user operations by involved entities (`entity_index`)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationsByEntity;
+
+/// Stores the signature aggregators of user operations (`aggregator_index`)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationsByAggregator;
+
+/// Stores the hash of user operation by sender and nonce (`nonce_index`)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationsByNonce;
+
+/// Stores the code hashes (`code_hashes`)
+#[derive(Clone, Copy, Debug)]
+pub struct CodeHashes;
+
+/// Stores the receipts of the user operations included on-chain (`receipts`)
+#[derive(Clone, Copy, Debug)]
+pub struct UserOperationReceipts;
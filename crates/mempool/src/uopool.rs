@@ -1,38 +1,63 @@
 use crate::{
+    bundle_selector::{BundleSelector, GreedyBundleSelector},
     estimate::estimate_user_op_gas,
     mempool::Mempool,
     mempool_id,
-    utils::div_ceil,
+    metrics::MEMPOOL_EVICTIONS,
+    utils::{
+        div_ceil, effective_gas_price, estimate_max_priority_fee_per_gas, user_operation_logs,
+        CalldataPricing, ChainPreVerificationGas, EntryPointOverhead,
+    },
     validate::{
         utils::merge_storage_maps, UserOperationValidationOutcome, UserOperationValidator,
         UserOperationValidatorMode,
     },
-    InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, MempoolId, Overhead,
+    InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, MempoolId, ReplacementPolicy,
     Reputation, ReputationError, SanityError, SimulationError,
 };
 use alloy_chains::Chain;
 use ethers::{
     prelude::LogMeta,
     providers::Middleware,
-    types::{Address, BlockNumber, U256},
+    types::{Address, BlockNumber, TransactionReceipt, H256, U256},
 };
 use eyre::format_err;
-use futures::channel::mpsc::UnboundedSender;
+use futures::{channel::mpsc::UnboundedSender, stream, StreamExt};
+use metrics::counter;
+use parking_lot::RwLock;
 use silius_contracts::{
-    entry_point::UserOperationEventFilter, utils::parse_from_input_data, EntryPoint,
-    EntryPointError,
+    entry_point::{DepositInfo, UserOperationEventFilter},
+    tracer::JsTracerFrame,
+    utils::parse_from_input_data,
+    EntryPoint, EntryPointError,
 };
 use silius_primitives::{
-    constants::validation::reputation::THROTTLED_ENTITY_BUNDLE_COUNT,
+    constants::{
+        mempool::{
+            DEFAULT_SIGNATURE_SIZE, ESTIMATION_BINARY_SEARCH_ROUNDS, MAX_BUNDLE_GAS,
+            MAX_MEMPOOL_AGE, MAX_MEMPOOL_SIZE, MAX_OPS_PER_STAKED_SENDER,
+            MAX_OPS_PER_UNSTAKED_SENDER, MAX_PARALLEL_VALIDATIONS, PRIORITY_FEE_TIP_PERC,
+            USER_OPERATION_EVENTS_CAPACITY,
+        },
+        validation::{entities::SENDER, reputation::THROTTLED_ENTITY_BUNDLE_COUNT},
+    },
     get_address,
     p2p::NetworkMessage,
-    reputation::{ReputationEntry, StakeInfo, StakeInfoResponse, Status},
+    reputation::{
+        ReputationEntry, ReputationEntryWithReason, StakeInfo, StakeInfoResponse, Status,
+    },
     simulation::{StorageMap, ValidationConfig},
-    UoPoolMode, UserOperation, UserOperationByHash, UserOperationGasEstimation, UserOperationHash,
-    UserOperationReceipt,
+    EntryPointVersion, MempoolStats, UoPoolMode, UserOperation, UserOperationByHash,
+    UserOperationEvent, UserOperationGasEstimation, UserOperationHash, UserOperationReceipt,
+    UserOperationSigned, UserOperationStatus,
 };
-use std::collections::{HashMap, HashSet};
-use tracing::{debug, error, info, trace};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::broadcast;
+use tracing::{debug, error, info, trace, warn};
 
 const FILTER_MAX_DEPTH: u64 = 10;
 const PRE_VERIFICATION_SAFE_RESERVE_PERC: u64 = 10; // percentage how higher pre verification gas we return
@@ -56,8 +81,41 @@ pub struct UoPool<M: Middleware + 'static, V: UserOperationValidator> {
     pub reputation: Reputation,
     // The maximum gas limit for [UserOperation](UserOperation) gas verification.
     pub max_verification_gas: U256,
+    // Gas consumed by the entry point itself (reserved out of the bundle gas limit)
+    pub entry_point_overhead: EntryPointOverhead,
     // The [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain ID
     pub chain: Chain,
+    // Calldata pricing used for the pre-verification gas (updated on new blocks)
+    pub calldata_pricing: Arc<RwLock<CalldataPricing>>,
+    // L1 calldata pricing of the chain used for the pre-verification gas (updated on new blocks)
+    pub chain_pre_verification_gas: Arc<RwLock<ChainPreVerificationGas>>,
+    // Rules for replacing a user operation with the same sender and nonce
+    pub replacement_policy: ReplacementPolicy,
+    // Lowest max priority fee per gas suggested to wallets
+    pub min_priority_fee_per_gas: U256,
+    // Percentage added on top of the suggested max priority fee per gas
+    pub priority_fee_tip_perc: U256,
+    // Minimum size of the dummy signature the pre-verification gas is estimated with (a longer
+    // signature of the user operation is kept)
+    pub default_sig_size: usize,
+    // Maximum number of rounds of the verification gas limit binary search
+    pub estimation_binary_search_rounds: u64,
+    // Time a user operation can stay in the mempool before it's evicted
+    pub max_mempool_age: Duration,
+    // Maximum number of user operations in the mempool
+    pub max_mempool_size: usize,
+    // Maximum total gas of a bundle
+    pub max_bundle_gas: U256,
+    // Maximum number of user operations of an unstaked sender in the mempool
+    pub max_ops_per_unstaked_sender: usize,
+    // Maximum number of user operations of a staked sender in the mempool
+    pub max_ops_per_staked_sender: usize,
+    // Maximum number of user operations of a batch validated concurrently
+    pub max_parallel_validations: usize,
+    // Strategy picking the bundle candidates out of the mempool
+    pub bundle_selector: Box<dyn BundleSelector>,
+    // Channel the changes of the mempool are broadcast to
+    pub events: broadcast::Sender<UserOperationEvent>,
     // Connection to the p2p network (None if not enabled)
     network: Option<UnboundedSender<NetworkMessage>>,
 }
@@ -73,6 +131,7 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
     /// `reputation` - The [Reputation](Reputation) object
     /// `max_verification_gas` - The maximum gas limit for [UserOperation](UserOperation) gas
     /// verification.
+    /// `entry_point_overhead` - Gas consumed by the entry point itself
     /// `chain` - The [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain ID
     /// `network` - Connection to the p2p network (None if not enabled)
     ///
@@ -86,6 +145,7 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         mempool: Mempool,
         reputation: Reputation,
         max_verification_gas: U256,
+        entry_point_overhead: EntryPointOverhead,
         chain: Chain,
         network: Option<UnboundedSender<NetworkMessage>>,
     ) -> Self {
@@ -97,11 +157,35 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
             mempool,
             reputation,
             max_verification_gas,
+            entry_point_overhead,
             chain,
+            calldata_pricing: Arc::new(RwLock::new(CalldataPricing::default())),
+            chain_pre_verification_gas: Arc::new(RwLock::new(ChainPreVerificationGas::for_chain(
+                chain,
+            ))),
+            replacement_policy: ReplacementPolicy::default(),
+            min_priority_fee_per_gas: U256::zero(),
+            priority_fee_tip_perc: PRIORITY_FEE_TIP_PERC.into(),
+            default_sig_size: DEFAULT_SIGNATURE_SIZE,
+            estimation_binary_search_rounds: ESTIMATION_BINARY_SEARCH_ROUNDS,
+            max_mempool_age: Duration::from_secs(MAX_MEMPOOL_AGE),
+            max_mempool_size: MAX_MEMPOOL_SIZE,
+            max_bundle_gas: MAX_BUNDLE_GAS.into(),
+            max_ops_per_unstaked_sender: MAX_OPS_PER_UNSTAKED_SENDER,
+            max_ops_per_staked_sender: MAX_OPS_PER_STAKED_SENDER,
+            max_parallel_validations: MAX_PARALLEL_VALIDATIONS,
+            bundle_selector: Box::new(GreedyBundleSelector),
+            events: broadcast::channel(USER_OPERATION_EVENTS_CAPACITY).0,
             network,
         }
     }
 
+    /// Broadcasts the change of the [UserOperation](UserOperation) to the subscribers
+    fn notify(&self, hash: UserOperationHash, status: UserOperationStatus) {
+        // no subscribers isn't an error
+        let _ = self.events.send(UserOperationEvent { hash, status });
+    }
+
     /// Returns all of the [UserOperations](UserOperation) in the mempool
     ///
     /// # Returns
@@ -112,6 +196,89 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         })
     }
 
+    /// Returns the number of [UserOperations](UserOperation) in the mempool
+    ///
+    /// # Returns
+    /// `Result<usize, eyre::Error>` - The number of user operations
+    pub fn count(&self) -> eyre::Result<usize> {
+        self.mempool.len().map_err(|err| {
+            format_err!(
+                "Getting the number of user operations in mempool failed with error: {err:?}",
+            )
+        })
+    }
+
+    /// Returns a page of the [UserOperations](UserOperation) in the mempool ordered by their
+    /// hashes, together with the total number of user operations in the mempool
+    ///
+    /// # Arguments
+    /// * `offset` - The number of user operations skipped
+    /// * `limit` - The maximum number of user operations returned
+    ///
+    /// # Returns
+    /// `Result<(Vec<UserOperation>, usize), eyre::Error>` - The page and the total
+    pub fn get_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> eyre::Result<(Vec<UserOperation>, usize)> {
+        let err = |err| {
+            format_err!("Getting a page of user operations from mempool failed with error: {err:?}",)
+        };
+        let uos = self.mempool.get_page(offset, limit).map_err(err)?;
+        let total = self.mempool.len().map_err(err)?;
+        Ok((uos, total))
+    }
+
+    /// Returns the [UserOperations](UserOperation) in the mempool that reference the entity
+    /// (as the factory or paymaster)
+    ///
+    /// # Arguments
+    /// * `addr` - The address of the entity
+    pub fn get_all_by_entity(&self, addr: &Address) -> Vec<UserOperation> {
+        self.mempool.get_all_by_entity(addr)
+    }
+
+    /// Returns the [UserOperations](UserOperation) in the mempool sponsored by the paymaster,
+    /// together with the gas they commit the paymaster to
+    ///
+    /// # Arguments
+    /// * `paymaster` - The address of the paymaster
+    ///
+    /// # Returns
+    /// `(Vec<UserOperation>, u64)` - An array of [UserOperations](UserOperation) and the sum of
+    /// their gas limits, with the verification gas limit counted three times as the entry point
+    /// also reserves it for `postOp`
+    pub fn get_all_by_paymaster(&self, paymaster: &Address) -> (Vec<UserOperation>, u64) {
+        let uos = self.mempool.get_all_by_paymaster(paymaster);
+        let total_committed_gas = uos
+            .iter()
+            .map(|uo| {
+                uo.call_gas_limit
+                    .saturating_add(uo.verification_gas_limit.saturating_mul(3.into()))
+                    .saturating_add(uo.pre_verification_gas)
+            })
+            .fold(U256::zero(), |total, gas| total.saturating_add(gas));
+        (uos, total_committed_gas.min(U256::from(u64::MAX)).as_u64())
+    }
+
+    /// Returns the factories and paymasters used by the [UserOperations](UserOperation) of the
+    /// sender in the mempool
+    pub fn get_entities_for_sender(&self, addr: &Address) -> Vec<Address> {
+        self.mempool.get_entities_for_sender(addr)
+    }
+
+    /// Returns the senders of the [UserOperations](UserOperation) in the mempool that use the
+    /// address as the factory or paymaster
+    pub fn get_senders_for_entity(&self, addr: &Address) -> Vec<Address> {
+        self.mempool.get_senders_for_entity(addr)
+    }
+
+    /// Returns the [statistics](MempoolStats) of the mempool
+    pub fn get_mempool_stats(&self) -> MempoolStats {
+        self.mempool.stats()
+    }
+
     /// Returns an array of [ReputationEntry](ReputationEntry) for entities.
     ///
     /// # Returns
@@ -120,6 +287,22 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         self.reputation.get_all().unwrap_or_default()
     }
 
+    /// Gets the [ReputationEntry](ReputationEntry) of all entities with the reason of their
+    /// status
+    ///
+    /// # Returns
+    /// `Vec<ReputationEntryWithReason>` - An array of
+    /// [ReputationEntryWithReason](ReputationEntryWithReason)
+    pub fn get_reputation_with_reason(&self) -> Vec<ReputationEntryWithReason> {
+        self.get_reputation()
+            .into_iter()
+            .flat_map(|entry| {
+                let status = self.reputation.get_status_with_reason(&entry.address)?;
+                Ok::<_, ReputationError>(ReputationEntryWithReason::new(entry.address, status))
+            })
+            .collect()
+    }
+
     /// Sets the [ReputationEntry](ReputationEntry) for entities
     ///
     /// # Arguments
@@ -134,6 +317,23 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         self.reputation.set_entities(reputation)
     }
 
+    /// Whitelists and blacklists the addresses. An address is moved off the other list, so the
+    /// whitelist and blacklist don't overlap.
+    ///
+    /// # Arguments
+    /// `whitelist` - The addresses to be whitelisted
+    /// `blacklist` - The addresses to be blacklisted
+    pub fn set_entity_lists(&mut self, whitelist: Vec<Address>, blacklist: Vec<Address>) {
+        for addr in whitelist {
+            self.reputation.remove_blacklist(&addr);
+            self.reputation.add_whitelist(&addr);
+        }
+        for addr in blacklist {
+            self.reputation.remove_whitelist(&addr);
+            self.reputation.add_blacklist(&addr);
+        }
+    }
+
     /// Batch clears the [Mempool](Mempool).
     ///
     /// # Returns
@@ -150,6 +350,14 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         self.reputation.clear();
     }
 
+    /// Clears the stored [UserOperationReceipts](UserOperationReceipt).
+    ///
+    /// # Returns
+    /// `()` - Returns nothing
+    pub fn clear_receipts(&mut self) {
+        self.mempool.clear_receipts();
+    }
+
     /// Batch clears the [Mempool](Mempool) and [Reputation](Reputation).
     ///
     /// # Returns
@@ -175,13 +383,100 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         val_config: Option<ValidationConfig>,
     ) -> Result<(), MempoolError> {
         for uo in user_operations {
-            let res = self.validate_user_operation(&uo, val_config.clone()).await;
-            self.add_user_operation(uo, res).await?;
+            self.validate_and_add_user_operation(uo, val_config.clone()).await?;
         }
 
         Ok(())
     }
 
+    /// Validates a single [UserOperation](UserOperation) and adds it into the mempool. The sender
+    /// limit is checked before the validation, so the user operations of a sender that is
+    /// already over the limit aren't simulated.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to add
+    /// `val_config` - The optional [ValidationConfig](ValidationConfig) object
+    ///
+    /// # Returns
+    /// `Result<UserOperationHash, MempoolError>` - The hash of the added
+    /// [UserOperation](UserOperation)
+    pub async fn validate_and_add_user_operation(
+        &mut self,
+        uo: UserOperation,
+        val_config: Option<ValidationConfig>,
+    ) -> Result<UserOperationHash, MempoolError> {
+        let uo = self.rehash_user_operation(uo);
+        self.check_sender_limit(&uo, val_config.as_ref()).await?;
+        let res = self.validate_user_operation(&uo, val_config).await;
+        self.add_user_operation(uo, res).await
+    }
+
+    /// Replaces the hash of the [UserOperation](UserOperation) with the hash the entry point
+    /// computes (the hash of the RPC is the one of entry point v0.6)
+    fn rehash_user_operation(&self, uo: UserOperation) -> UserOperation {
+        match self.entry_point.version() {
+            EntryPointVersion::V0_6 => uo,
+            version => UserOperation::from_user_operation_signed(
+                uo.user_operation.hash_for(&self.entry_point.address(), self.chain.id(), version),
+                uo.user_operation,
+            ),
+        }
+    }
+
+    /// Validates a batch of [UserOperations](UserOperation) concurrently (up to
+    /// `max_parallel_validations` at a time) and adds the valid ones into the mempool.
+    /// The user operations are validated against the mempool before the batch, so the ones of a
+    /// sender that already has a user operation earlier in the batch are validated only after it
+    /// is added.
+    ///
+    /// # Arguments
+    /// `user_operations` - The array of [UserOperations](UserOperation) to add
+    ///
+    /// # Returns
+    /// `Vec<Result<UserOperationHash, MempoolError>>` - The outcome of each of the
+    /// [UserOperations](UserOperation), in the order of the batch
+    pub async fn add_user_operations_batch(
+        &mut self,
+        user_operations: Vec<UserOperation>,
+    ) -> Vec<Result<UserOperationHash, MempoolError>> {
+        let mut senders = HashSet::new();
+        let (first, rest): (Vec<_>, Vec<_>) = user_operations
+            .into_iter()
+            .map(|uo| self.rehash_user_operation(uo))
+            .enumerate()
+            .partition(|(_, uo)| senders.insert(uo.sender));
+
+        let validated = {
+            let uopool = &*self;
+            stream::iter(first)
+                .map(|(i, uo)| async move {
+                    let res = match uopool.check_sender_limit(&uo, None).await {
+                        Ok(()) => Ok(uopool.validate_user_operation(&uo, None).await),
+                        Err(err) => Err(err),
+                    };
+                    (i, uo, res)
+                })
+                .buffered(self.max_parallel_validations.max(1))
+                .collect::<Vec<_>>()
+                .await
+        };
+
+        let mut results = Vec::with_capacity(validated.len() + rest.len());
+        for (i, uo, res) in validated {
+            let res = match res {
+                Ok(res) => self.add_user_operation(uo, res).await,
+                Err(err) => Err(err),
+            };
+            results.push((i, res));
+        }
+        for (i, uo) in rest {
+            results.push((i, self.validate_and_add_user_operation(uo, None).await));
+        }
+
+        results.sort_by_key(|(i, _)| *i);
+        results.into_iter().map(|(_, res)| res).collect()
+    }
+
     /// Validates a single [UserOperation](UserOperation) and returns the validation outcome by
     /// calling [UserOperationValidator::validate_user_operation](UserOperationValidator::validate_user_operation)
     ///
@@ -210,12 +505,55 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
             .await
     }
 
+    /// Simulates a single [UserOperation](UserOperation) without the sanity checks and without
+    /// touching the mempool
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to simulate
+    ///
+    /// # Returns
+    /// `Result<UserOperationValidationOutcome, InvalidMempoolUserOperationError>` - The validation
+    /// outcome
+    pub async fn simulate_user_operation(
+        &self,
+        uo: &UserOperation,
+    ) -> Result<UserOperationValidationOutcome, InvalidMempoolUserOperationError> {
+        self.validator
+            .validate_user_operation(
+                uo,
+                &self.mempool,
+                &self.reputation,
+                None,
+                UserOperationValidatorMode::Simulation |
+                    UserOperationValidatorMode::SimulationTrace,
+            )
+            .await
+    }
+
+    /// Traces `handleOps` with the single [UserOperation](UserOperationSigned) via
+    /// `debug_traceCall`, to find out why the user operation fails (e.g. which call reverts with
+    /// `FailedOp`)
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperationSigned) to trace
+    ///
+    /// # Returns
+    /// `Result<JsTracerFrame, eyre::Error>` - The trace returned by the tracer
+    pub async fn trace_user_operation(
+        &self,
+        uo: &UserOperationSigned,
+    ) -> eyre::Result<JsTracerFrame> {
+        let geth_trace = self.entry_point.handle_ops_trace(uo.clone(), Address::zero()).await?;
+        JsTracerFrame::try_from(geth_trace)
+    }
+
     /// Adds a single validated user operation into the pool
     /// Indirectly invoked by RPC API via gRPC sevice to add a [UserOperation](UserOperation) into
     /// the mempool The function first validates the [UserOperation](UserOperation) by calling
     /// [UoPool::validate_user_operation](UoPool::validate_user_operation). If
     /// [UserOperation](UserOperation) passes the validation, then adds it into the mempool by
-    /// calling [Mempool::add](Mempool::add).
+    /// calling [Mempool::add](Mempool::add), or [Mempool::update_user_operation] if it replaces
+    /// a previous user operation.
     ///
     /// # Arguments
     /// `uo` - The [UserOperation](UserOperation) to add
@@ -227,7 +565,7 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
     /// [UserOperation](UserOperation)
     pub async fn add_user_operation(
         &mut self,
-        uo: UserOperation,
+        mut uo: UserOperation,
         res: Result<UserOperationValidationOutcome, InvalidMempoolUserOperationError>,
     ) -> Result<UserOperationHash, MempoolError> {
         let res = match res {
@@ -243,8 +581,25 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
             }
         };
 
-        if let Some(uo_hash) = res.prev_hash {
-            self.remove_user_operation(&uo_hash);
+        uo.aggregator = res.aggregator;
+
+        // a replacement doesn't grow the mempool, unless the replaced user operation is already
+        // gone (e.g. included or evicted during the validation)
+        let prev_hash = match res.prev_hash {
+            Some(prev_hash) => self
+                .mempool
+                .get(&prev_hash)
+                .map_err(|kind| MempoolError { hash: uo.hash, kind })?
+                .map(|_| prev_hash),
+            None => None,
+        };
+        if let Some(uo_hash) = prev_hash {
+            self.check_replacement(&uo_hash, &uo, res.val_config.ignore_prev)?;
+        } else {
+            // checked again, other user operations of the sender could be added during the
+            // validation
+            self.check_sender_limit(&uo, Some(&res.val_config)).await?;
+            self.make_room_for_user_operation(&uo).await?;
         }
 
         if let Some(ref sender) = self.network {
@@ -257,8 +612,16 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                 .expect("Failed to send user operation to publish channel")
         };
 
-        match self.mempool.add(uo.clone()) {
+        let added = match prev_hash {
+            Some(ref prev_hash) => self.mempool.update_user_operation(prev_hash, uo.clone()),
+            None => self.mempool.add(uo.clone()),
+        };
+
+        match added {
             Ok(uo_hash) => {
+                if let Some(prev_hash) = prev_hash.filter(|prev_hash| *prev_hash != uo_hash) {
+                    self.notify(prev_hash, UserOperationStatus::Dropped);
+                }
                 // TODO: find better way to do it atomically
                 if let Some(code_hashes) = res.code_hashes {
                     match self.mempool.set_code_hashes(&uo_hash, code_hashes){
@@ -268,6 +631,7 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                 }
                 info!("{uo_hash:?} added to the mempool {:?}", self.id);
                 trace!("{uo:?} added to the mempool {:?}", self.id);
+                self.notify(uo_hash, UserOperationStatus::Pending);
 
                 // update reputation
                 self.reputation
@@ -283,6 +647,11 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                         .increment_seen(&p_addr)
                         .map_err(|e| MempoolError { hash: uo_hash, kind: e.into() })?;
                 }
+                if let Some(a_addr) = uo.aggregator {
+                    self.reputation
+                        .increment_seen(&a_addr)
+                        .map_err(|e| MempoolError { hash: uo_hash, kind: e.into() })?;
+                }
 
                 Ok(uo_hash)
             }
@@ -290,17 +659,179 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         }
     }
 
+    /// Checks that `uo` can replace the previous [UserOperation](UserOperation) of the sender
+    /// against the [ReplacementPolicy](ReplacementPolicy). The swap itself is done by
+    /// [Mempool::update_user_operation](Mempool::update_user_operation).
+    ///
+    /// # Arguments
+    /// `prev_hash` - The hash of the [UserOperation](UserOperation) being replaced
+    /// `uo` - The replacing [UserOperation](UserOperation)
+    /// `ignore_prev` - Skip the fee bump check (user operations received from the p2p network)
+    ///
+    /// # Returns
+    /// `Result<(), MempoolError>` - Error if `uo` can't replace the previous user operation
+    pub fn check_replacement(
+        &mut self,
+        prev_hash: &UserOperationHash,
+        uo: &UserOperation,
+        ignore_prev: bool,
+    ) -> Result<(), MempoolError> {
+        if !ignore_prev {
+            let uo_prev =
+                self.mempool.get(prev_hash).map_err(|kind| MempoolError { hash: uo.hash, kind })?;
+
+            if let Some(uo_prev) = uo_prev {
+                self.replacement_policy.check_replacement(&uo_prev, uo).map_err(|err| {
+                    MempoolError { hash: uo.hash, kind: SanityError::from(err).into() }
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Rejects the [UserOperation](UserOperation) if its sender already has the maximum number of
+    /// user operations in the mempool. The sanity checks limit unstaked senders too, but they
+    /// can be skipped, so the limit is enforced here as well. A replacement (same sender and
+    /// nonce) doesn't grow the mempool, so it isn't limited.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) about to be added
+    /// `val_config` - The optional [ValidationConfig](ValidationConfig) the stake of the sender is
+    /// checked against
+    ///
+    /// # Returns
+    /// `Result<(), MempoolError>` -
+    /// [TooManyOpsForSender](MempoolErrorKind::TooManyOpsForSender) if the limit is reached
+    pub async fn check_sender_limit(
+        &self,
+        uo: &UserOperation,
+        val_config: Option<&ValidationConfig>,
+    ) -> Result<(), MempoolError> {
+        let count = self.mempool.get_number_by_sender(&uo.sender);
+        if count < self.max_ops_per_unstaked_sender.min(self.max_ops_per_staked_sender) ||
+            self.mempool.get_prev_by_sender(uo).is_some()
+        {
+            return Ok(());
+        }
+        let val_config = val_config.cloned().unwrap_or_default();
+
+        // the stake is only looked up once the sender is over the lower limit
+        let info =
+            self.entry_point.get_deposit_info(&uo.sender).await.map_err(|err| MempoolError {
+                hash: uo.hash,
+                kind: MempoolErrorKind::Provider { inner: format!("{err:?}") },
+            })?;
+        let stake_info = StakeInfo {
+            address: uo.sender,
+            stake: U256::from(info.stake),
+            unstake_delay: U256::from(info.unstake_delay_sec),
+        };
+        let max = if self
+            .reputation
+            .verify_stake(
+                SENDER,
+                Some(stake_info),
+                val_config.min_stake,
+                val_config.min_unstake_delay,
+            )
+            .is_ok()
+        {
+            self.max_ops_per_staked_sender
+        } else {
+            self.max_ops_per_unstaked_sender
+        };
+
+        if count >= max {
+            return Err(MempoolError {
+                hash: uo.hash,
+                kind: MempoolErrorKind::TooManyOpsForSender { sender: uo.sender, count, max },
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Evicts the least profitable [UserOperation](UserOperation) if the mempool is full
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) about to be added
+    ///
+    /// # Returns
+    /// `Result<(), MempoolError>` - [MempoolFull](MempoolErrorKind::MempoolFull) if `uo` doesn't
+    /// pay a higher effective gas price than the least profitable user operation
+    pub async fn make_room_for_user_operation(
+        &mut self,
+        uo: &UserOperation,
+    ) -> Result<(), MempoolError> {
+        let len = self.mempool.len().map_err(|kind| MempoolError { hash: uo.hash, kind })?;
+        if len < self.max_mempool_size {
+            return Ok(());
+        }
+
+        let base_fee_per_gas = self.base_fee_per_gas().await.map_err(|e| MempoolError {
+            hash: uo.hash,
+            kind: MempoolErrorKind::Provider { inner: e.to_string() },
+        })?;
+        let uos = self
+            .mempool
+            .get_sorted(base_fee_per_gas)
+            .map_err(|kind| MempoolError { hash: uo.hash, kind })?;
+
+        let least_profitable = match uos.last() {
+            Some(uo_least) => uo_least,
+            None => return Ok(()),
+        };
+        if effective_gas_price(uo, base_fee_per_gas) <=
+            effective_gas_price(least_profitable, base_fee_per_gas)
+        {
+            return Err(MempoolError { hash: uo.hash, kind: MempoolErrorKind::MempoolFull });
+        }
+
+        info!(
+            "Mempool {:?} is full, evicting {:?} for {:?}",
+            self.id, least_profitable.hash, uo.hash
+        );
+        self.remove_user_operation(&least_profitable.hash);
+        counter!(MEMPOOL_EVICTIONS).increment(1);
+
+        Ok(())
+    }
+
     /// Sorts the [UserOperations](UserOperation) in the mempool by calling the
     /// [Mempool::get_sorted](Mempool::get_sorted) function
     ///
+    /// # Arguments
+    /// `base_fee_per_gas` - The base fee per gas the effective gas prices are computed with
+    ///
     /// # Returns
     /// `Result<Vec<UserOperation>, eyre::Error>` - The sorted [UserOperations](UserOperation)
-    pub fn get_sorted_user_operations(&self) -> eyre::Result<Vec<UserOperation>> {
-        self.mempool.get_sorted().map_err(|err| {
+    pub fn get_sorted_user_operations(
+        &self,
+        base_fee_per_gas: U256,
+    ) -> eyre::Result<Vec<UserOperation>> {
+        self.mempool.get_sorted(base_fee_per_gas).map_err(|err| {
             format_err!("Getting sorted user operations from mempool failed with error: {err:?}",)
         })
     }
 
+    /// Picks the bundle candidates out of the sorted [UserOperations](UserOperation) with the
+    /// configured [BundleSelector](BundleSelector) and the maximum bundle gas as the budget
+    ///
+    /// # Arguments
+    /// `uos` - The sorted [UserOperations](UserOperation)
+    /// `base_fee_per_gas` - Base fee per gas of the latest block
+    ///
+    /// # Returns
+    /// `Vec<UserOperation>` - The bundle candidates in the order they should be bundled
+    pub async fn select_bundle_candidates(
+        &self,
+        uos: Vec<UserOperation>,
+        base_fee_per_gas: U256,
+    ) -> Vec<UserOperation> {
+        self.bundle_selector.select(uos, self.max_bundle_gas, base_fee_per_gas).await
+    }
+
     /// Bundles an array of [UserOperations](UserOperation)
     /// The function first checks the reputations of the entities, then validate each
     /// [UserOperation](UserOperation) by calling
@@ -320,14 +851,15 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
     ) -> eyre::Result<(Vec<UserOperation>, StorageMap)> {
         let mut uos_valid = vec![];
         let mut senders = HashSet::new();
-        let mut gas_total = U256::zero();
+        // the entry point overhead of the bundle comes out of the gas budget first
+        let mut gas_total = self.entry_point_overhead.fixed_overhead;
         let mut paymaster_dep = HashMap::new();
         let mut staked_entity_c = HashMap::new();
         let mut storage_maps: Vec<StorageMap> = Vec::new();
 
         let senders_all = uos.iter().map(|uo| uo.sender).collect::<HashSet<_>>();
 
-        'uos: for uo in uos {
+        'uos: for mut uo in uos {
             if senders.contains(&uo.sender) {
                 continue;
             }
@@ -386,6 +918,30 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                         continue;
                     }
 
+                    // the signature aggregator is known only after the simulation
+                    if let Some(a) = val_out.aggregator {
+                        let a_st = Status::from(self.reputation.get_status(&a).map_err(|err| {
+                            format_err!("Error getting reputation status with error: {err:?}")
+                        })?);
+                        let a_c = staked_entity_c.get(&a).cloned().unwrap_or(0);
+                        match a_st {
+                            Status::BANNED => {
+                                self.mempool.remove(&uo.hash).map_err(|err| {
+                                    format_err!(
+                                        "Removing a banned user operation {:?} failed with error: {err:?}",
+                                        uo.hash,
+                                    )
+                                })?;
+                                continue;
+                            }
+                            Status::THROTTLED if a_c > THROTTLED_ENTITY_BUNDLE_COUNT => {
+                                continue;
+                            }
+                            _ => (),
+                        }
+                    }
+                    uo.aggregator = val_out.aggregator;
+
                     for addr in val_out.storage_map.root_hashes.keys() {
                         if *addr != uo.sender && senders_all.contains(addr) {
                             continue 'uos;
@@ -404,9 +960,12 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                     // it would be better to use estimate_gas instead of call_gas_limit
                     // The result of call_gas_limit is usesally higher and less user op would be
                     // included
-                    let gas_cost = val_out.verification_gas_limit.saturating_add(uo.call_gas_limit);
+                    let gas_cost = val_out
+                        .verification_gas_limit
+                        .saturating_add(uo.call_gas_limit)
+                        .saturating_add(uo.pre_verification_gas);
                     let gas_total_new = gas_total.saturating_add(gas_cost);
-                    if gas_total_new.gt(&self.max_verification_gas) {
+                    if gas_total_new.gt(&self.max_bundle_gas) {
                         break;
                     }
 
@@ -432,6 +991,10 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                         staked_entity_c.entry(f).and_modify(|c| *c += 1).or_insert(1);
                     }
 
+                    if let Some(a) = uo.aggregator {
+                        staked_entity_c.entry(a).and_modify(|c| *c += 1).or_insert(1);
+                    }
+
                     gas_total = gas_total_new;
                 }
                 Err(_) => {
@@ -465,6 +1028,22 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         block.base_fee_per_gas.ok_or(format_err!("No base fee found"))
     }
 
+    /// Suggests the max priority fee per gas for a new user operation, so that wallets don't have
+    /// to guess a fee that gets the user operation bundled.
+    ///
+    /// # Returns
+    /// `Result<U256, eyre::Error>` - The suggested max priority fee per gas.
+    pub async fn get_max_priority_fee_per_gas_estimate(&self) -> eyre::Result<U256> {
+        let base_fee_per_gas = self.base_fee_per_gas().await?;
+        let uos = self.get_all()?;
+        Ok(estimate_max_priority_fee_per_gas(
+            &uos,
+            base_fee_per_gas,
+            self.priority_fee_tip_perc,
+            self.min_priority_fee_per_gas,
+        ))
+    }
+
     /// Estimates the `verification_gas_limit`, `call_gas_limit` and `pre_verification_gas` for a
     /// user operation. The function is indirectly invoked by the `estimate_user_operation_gas`
     /// JSON RPC method.
@@ -479,41 +1058,41 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         &self,
         uo: &UserOperation,
     ) -> Result<UserOperationGasEstimation, MempoolError> {
-        let pre_verification_gas = div_ceil(
-            Overhead::default().calculate_pre_verification_gas(uo).saturating_mul(
-                U256::from(100).saturating_add(PRE_VERIFICATION_SAFE_RESERVE_PERC.into()),
-            ),
-            U256::from(100),
-        );
+        let pre_verification_gas = self.estimate_pre_verification_gas(uo).await?;
 
         let (verification_gas_limit, call_gas_limit) = match self.mode {
-            UoPoolMode::Standard => estimate_user_op_gas(&uo.user_operation, &self.entry_point)
-                .await
-                .map_err(|e| match e {
-                    EntryPointError::FailedOp(op) => MempoolError {
-                        hash: uo.hash,
-                        kind: MempoolErrorKind::InvalidUserOperation(
-                            InvalidMempoolUserOperationError::Simulation(
-                                SimulationError::Validation { inner: op.reason },
-                            ),
-                        ),
-                    },
-                    EntryPointError::ExecutionReverted(e) => MempoolError {
-                        hash: uo.hash,
-                        kind: MempoolErrorKind::InvalidUserOperation(
-                            InvalidMempoolUserOperationError::Simulation(
-                                SimulationError::Execution { inner: e },
-                            ),
-                        ),
-                    },
-                    EntryPointError::Provider { inner } => {
-                        MempoolError { hash: uo.hash, kind: MempoolErrorKind::Provider { inner } }
-                    }
-                    _ => MempoolError {
-                        hash: uo.hash,
-                        kind: MempoolErrorKind::Other { inner: e.to_string() },
-                    },
-                })?,
+            UoPoolMode::Standard => estimate_user_op_gas(
+                &uo.user_operation,
+                &self.entry_point,
+                self.max_verification_gas,
+                self.estimation_binary_search_rounds,
+            )
+            .await
+            .map_err(|e| match e {
+                EntryPointError::FailedOp(op) => MempoolError {
+                    hash: uo.hash,
+                    kind: MempoolErrorKind::InvalidUserOperation(
+                        InvalidMempoolUserOperationError::Simulation(SimulationError::Validation {
+                            inner: op.reason,
+                        }),
+                    ),
+                },
+                EntryPointError::ExecutionReverted(e) => MempoolError {
+                    hash: uo.hash,
+                    kind: MempoolErrorKind::InvalidUserOperation(
+                        InvalidMempoolUserOperationError::Simulation(SimulationError::Execution {
+                            inner: e,
+                        }),
+                    ),
+                },
+                EntryPointError::Provider { inner } => {
+                    MempoolError { hash: uo.hash, kind: MempoolErrorKind::Provider { inner } }
+                }
+                _ => MempoolError {
+                    hash: uo.hash,
+                    kind: MempoolErrorKind::Other { inner: e.to_string() },
+                },
+            })?,
             UoPoolMode::Unsafe => {
                 let ret =
                     self.entry_point.simulate_handle_op(uo.clone().user_operation).await.map_err(
@@ -549,7 +1128,13 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                     ret.pre_op_gas.saturating_sub(pre_verification_gas).saturating_mul(3.into()),
                     2.into(),
                 );
-                let call_gas_limit = div_ceil(ret.paid, uo.user_operation.max_fee_per_gas)
+                // the entry point charges the effective gas price, which is below the max fee
+                // whenever the base fee leaves room for it
+                let base_fee_per_gas = self.base_fee_per_gas().await.map_err(|e| MempoolError {
+                    hash: uo.hash,
+                    kind: MempoolErrorKind::Provider { inner: e.to_string() },
+                })?;
+                let call_gas_limit = div_ceil(ret.paid, effective_gas_price(uo, base_fee_per_gas))
                     .saturating_sub(ret.pre_op_gas)
                     .saturating_add(35000.into());
 
@@ -564,6 +1149,41 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         })
     }
 
+    /// Estimates the pre-verification gas of the [UserOperation](UserOperation) with a reserve on
+    /// top. The fixed and per user operation overhead of the entry point are already part of the
+    /// [Overhead](crate::Overhead) of the calculation.
+    async fn estimate_pre_verification_gas(
+        &self,
+        uo: &UserOperation,
+    ) -> Result<U256, MempoolError> {
+        let calldata_pricing = *self.calldata_pricing.read();
+        let chain_pre_verification_gas = *self.chain_pre_verification_gas.read();
+        // wallets usually estimate before setting the fees, the L1 fee is then priced at the
+        // base fee
+        let base_fee_per_gas = match chain_pre_verification_gas {
+            ChainPreVerificationGas::Mainnet { .. } => U256::zero(),
+            _ if !uo.max_fee_per_gas.is_zero() => U256::zero(),
+            _ => self.base_fee_per_gas().await.map_err(|e| MempoolError {
+                hash: uo.hash,
+                kind: MempoolErrorKind::Provider { inner: e.to_string() },
+            })?,
+        };
+        Ok(div_ceil(
+            chain_pre_verification_gas
+                .calculate_pre_verification_gas_with_sig_size(
+                    uo,
+                    self.entry_point.version(),
+                    calldata_pricing,
+                    base_fee_per_gas,
+                    uo.signature.len().max(self.default_sig_size),
+                )
+                .saturating_mul(
+                    U256::from(100).saturating_add(PRE_VERIFICATION_SAFE_RESERVE_PERC.into()),
+                ),
+            U256::from(100),
+        ))
+    }
+
     /// Filters the events logged from the [EntryPoint](EntryPoint) contract for a given user
     /// operation hash.
     ///
@@ -596,6 +1216,8 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
 
     /// Gets the user operation by hash.
     /// The function is indirectly invoked by the `get_user_operation_by_hash` JSON RPC method.
+    /// User operations still pending in the mempool are returned without the transaction and
+    /// block, otherwise the user operation is searched for in the entry point logs.
     ///
     /// # Arguments
     /// * `uo_hash` - The [UserOperationHash](UserOperationHash) to get the user operation for.
@@ -606,6 +1228,16 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         &self,
         uo_hash: &UserOperationHash,
     ) -> eyre::Result<UserOperationByHash> {
+        if let Some(uo) = self.mempool.get(uo_hash)? {
+            return Ok(UserOperationByHash {
+                user_operation: uo.user_operation,
+                entry_point: self.entry_point.address(),
+                transaction_hash: None,
+                block_hash: None,
+                block_number: None,
+            });
+        }
+
         let event = self.get_user_operation_event_meta(uo_hash).await?;
 
         if let Some((event, log_meta)) = event {
@@ -625,9 +1257,9 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                 return Ok(UserOperationByHash {
                     user_operation: uo,
                     entry_point: ep,
-                    transaction_hash: log_meta.transaction_hash,
-                    block_hash: log_meta.block_hash,
-                    block_number: log_meta.block_number,
+                    transaction_hash: Some(log_meta.transaction_hash),
+                    block_hash: Some(log_meta.block_hash),
+                    block_number: Some(log_meta.block_number),
                 });
             }
         }
@@ -648,6 +1280,10 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
         &self,
         uo_hash: &UserOperationHash,
     ) -> eyre::Result<UserOperationReceipt> {
+        if let Some(receipt) = self.mempool.get_receipt(uo_hash)? {
+            return Ok(receipt);
+        }
+
         let event = self.get_user_operation_event_meta(uo_hash).await?;
 
         if let Some((event, log_meta)) = event {
@@ -657,25 +1293,85 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
                 .get_transaction_receipt(log_meta.transaction_hash)
                 .await?
             {
-                let uo = self.get_user_operation_by_hash(uo_hash).await?;
-                return Ok(UserOperationReceipt {
-                    user_operation_hash: *uo_hash,
-                    sender: event.sender,
-                    nonce: event.nonce,
-                    actual_gas_cost: event.actual_gas_cost,
-                    actual_gas_used: event.actual_gas_used,
-                    success: event.success,
-                    tx_receipt: tx_receipt.clone(),
-                    logs: tx_receipt.logs.into_iter().collect(),
-                    paymaster: get_address(&uo.user_operation.paymaster_and_data),
-                    reason: String::new(), // TODO: this must be set to revert reason
-                });
+                return Ok(self.user_operation_receipt(event, &log_meta, tx_receipt));
             }
         }
 
         Err(format_err!("No user operation found"))
     }
 
+    /// Builds the [UserOperationReceipt](UserOperationReceipt) from the `UserOperationEvent` log
+    /// and the receipt of the transaction that emitted it.
+    fn user_operation_receipt(
+        &self,
+        event: UserOperationEventFilter,
+        log_meta: &LogMeta,
+        tx_receipt: TransactionReceipt,
+    ) -> UserOperationReceipt {
+        UserOperationReceipt {
+            user_operation_hash: event.user_op_hash.into(),
+            sender: event.sender,
+            nonce: event.nonce,
+            actual_gas_cost: event.actual_gas_cost,
+            actual_gas_used: event.actual_gas_used,
+            success: event.success,
+            logs: user_operation_logs(
+                &tx_receipt.logs,
+                self.entry_point.address(),
+                log_meta.log_index,
+            ),
+            tx_receipt,
+            paymaster: Some(event.paymaster).filter(|paymaster| !paymaster.is_zero()),
+            reason: String::new(), // TODO: this must be set to revert reason
+        }
+    }
+
+    /// Stores the [UserOperationReceipts](UserOperationReceipt) of the user operations included
+    /// in the block, so that `eth_getUserOperationReceipt` doesn't have to query the logs.
+    ///
+    /// # Arguments
+    /// * `block_hash` - The hash of the block.
+    ///
+    /// # Returns
+    /// `Result<usize, eyre::Error>` - The number of stored receipts
+    pub async fn store_user_operation_receipts(&mut self, block_hash: H256) -> eyre::Result<usize> {
+        let events: Vec<(UserOperationEventFilter, LogMeta)> = self
+            .entry_point
+            .entry_point_api()
+            .event::<UserOperationEventFilter>()
+            .at_block_hash(block_hash)
+            .query_with_meta()
+            .await?;
+
+        // bundles contain multiple user operations
+        let mut tx_receipts: HashMap<H256, TransactionReceipt> = HashMap::new();
+        let mut stored = 0;
+
+        for (event, log_meta) in events {
+            let tx_receipt = match tx_receipts.get(&log_meta.transaction_hash) {
+                Some(tx_receipt) => tx_receipt.clone(),
+                None => match self
+                    .entry_point
+                    .eth_client()
+                    .get_transaction_receipt(log_meta.transaction_hash)
+                    .await?
+                {
+                    Some(tx_receipt) => {
+                        tx_receipts.insert(log_meta.transaction_hash, tx_receipt.clone());
+                        tx_receipt
+                    }
+                    None => continue,
+                },
+            };
+
+            let receipt = self.user_operation_receipt(event, &log_meta, tx_receipt);
+            self.mempool.add_receipt(receipt)?;
+            stored += 1;
+        }
+
+        Ok(stored)
+    }
+
     /// Removes the [UserOperation](UserOperation) from the user operation mempool
     /// given the [UserOperationHash](UserOperationHash).
     ///
@@ -685,18 +1381,50 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
     /// # Returns
     /// `Option<()>` - None if the user operation was successfully removed.
     pub fn remove_user_operation(&mut self, uo_hash: &UserOperationHash) -> Option<()> {
-        self.mempool.remove(uo_hash).ok();
+        if let Ok(true) = self.mempool.remove(uo_hash) {
+            self.notify(*uo_hash, UserOperationStatus::Dropped);
+        }
         None
     }
 
+    /// Removes the [UserOperations](UserOperation) that have been in the mempool for longer than
+    /// `max_mempool_age` (their nonce or gas prices are likely stale by then)
+    ///
+    /// # Returns
+    /// `usize` - The number of evicted [UserOperations](UserOperation)
+    pub fn remove_expired_user_operations(&mut self) -> usize {
+        let cutoff = match Instant::now().checked_sub(self.max_mempool_age) {
+            Some(cutoff) => cutoff,
+            None => return 0,
+        };
+
+        let expired = self.mempool.get_all_older_than(cutoff);
+        for uo_hash in expired.iter() {
+            debug!(
+                "Evicting user operation {uo_hash:?} older than {:?} from the mempool",
+                self.max_mempool_age
+            );
+            self.remove_user_operation(uo_hash);
+        }
+
+        expired.len()
+    }
+
+    /// Removes the [UserOperations](UserOperation) of the entity, whether it's their factory,
+    /// paymaster or sender
     pub fn remove_user_operation_by_entity(&mut self, entity: &Address) -> Option<()> {
-        self.mempool.remove_by_entity(entity).ok();
+        let mut uos = self.mempool.get_all_by_entity(entity);
+        uos.extend(self.mempool.get_all_by_sender(entity));
+        for uo in uos {
+            self.remove_user_operation(&uo.hash);
+        }
         None
     }
 
     /// Removes multiple [UserOperations](UserOperation) from the
     /// user operation mempool given an array of
-    /// [UserOperation](UserOperation).
+    /// [UserOperation](UserOperation). The entities of the removed user operations are counted
+    /// as included (once, even if the inclusion is reported more than once).
     ///
     /// # Arguments
     /// * `uos` - The array of [UserOperation](UserOperation).
@@ -704,8 +1432,15 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
     /// # Returns
     /// `Option<()>` - None
     pub fn remove_user_operations(&mut self, uos: Vec<UserOperation>) -> Option<()> {
-        for uo in uos {
-            self.remove_user_operation(&uo.hash);
+        for mut uo in uos {
+            // the user operations decoded from the bundle transaction don't have the aggregator
+            if let Ok(Some(stored)) = self.mempool.get(&uo.hash) {
+                uo.aggregator = stored.aggregator;
+            }
+            if !matches!(self.mempool.remove(&uo.hash), Ok(true)) {
+                continue;
+            }
+            self.notify(uo.hash, UserOperationStatus::Included);
 
             // update reputations
             self.reputation.increment_included(&uo.sender).ok();
@@ -717,11 +1452,94 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
             if let Some(addr) = get_address(&uo.init_code) {
                 self.reputation.increment_included(&addr).ok();
             }
+
+            if let Some(addr) = uo.aggregator {
+                self.reputation.increment_included(&addr).ok();
+            }
         }
 
         None
     }
 
+    /// Removes the [UserOperations](UserOperation) of a failed bundle that also fail the
+    /// simulation afterwards. Their senders are counted as seen, but not as included, which
+    /// lowers their reputation, and the `banned` senders (the ones failing repeatedly) are banned.
+    ///
+    /// # Arguments
+    /// * `uos` - The array of failed [UserOperation](UserOperation).
+    /// * `banned` - The senders to ban.
+    pub fn remove_failed_user_operations(&mut self, uos: Vec<UserOperation>, banned: &[Address]) {
+        for uo in uos {
+            self.remove_user_operation(&uo.hash);
+            self.reputation.increment_seen(&uo.sender).ok();
+        }
+
+        for sender in banned {
+            warn!("Banning sender {sender:?}, its user operations repeatedly failed bundles");
+            self.reputation.update_handle_ops_reverted(sender).ok();
+        }
+    }
+
+    /// Adds the [UserOperations](UserOperation) included in reorged out blocks back to the
+    /// mempool (they aren't validated again, the ones with invalid nonces are evicted by
+    /// [remove_stale_user_operations](UoPool::remove_stale_user_operations)).
+    ///
+    /// # Arguments
+    /// * `uos` - The array of [UserOperation](UserOperation).
+    ///
+    /// # Returns
+    /// `usize` - The number of reinstated [UserOperations](UserOperation)
+    pub fn reinstate_user_operations(&mut self, uos: Vec<UserOperation>) -> usize {
+        let mut reinstated = 0;
+
+        for uo in uos {
+            let uo_hash = uo.hash;
+            // the receipt was stored when the reorged out block was handled
+            self.mempool.remove_receipt(&uo_hash).ok();
+            match self.mempool.add(uo) {
+                Ok(_) => {
+                    self.notify(uo_hash, UserOperationStatus::Pending);
+                    reinstated += 1;
+                }
+                Err(err) => debug!("Failed to reinstate user operation {uo_hash:?}: {err:?}"),
+            }
+        }
+
+        reinstated
+    }
+
+    /// Removes the [UserOperations](UserOperation) of the senders whose nonce is lower than the
+    /// nonce in the entry point (e.g. after a reorg included another user operation with the same
+    /// nonce).
+    ///
+    /// # Arguments
+    /// * `senders` - The senders whose user operations are checked.
+    ///
+    /// # Returns
+    /// `Result<usize, eyre::Error>` - The number of evicted [UserOperations](UserOperation)
+    pub async fn remove_stale_user_operations(
+        &mut self,
+        senders: &HashSet<Address>,
+    ) -> eyre::Result<usize> {
+        let mut evicted = 0;
+
+        for sender in senders {
+            for uo in self.mempool.get_all_by_sender(sender) {
+                let on_chain_nonce = self.entry_point.get_nonce(sender, uo.nonce >> 64).await?;
+                if uo.nonce < on_chain_nonce {
+                    debug!(
+                        "Evicting user operation {:?} with nonce {} below the on-chain nonce {}",
+                        uo.hash, uo.nonce, on_chain_nonce
+                    );
+                    self.remove_user_operation(&uo.hash);
+                    evicted += 1;
+                }
+            }
+        }
+
+        Ok(evicted)
+    }
+
     /// Gets the [StakeInfoResponse](StakeInfoResponse) for entity
     ///
     /// # Arguments
@@ -741,4 +1559,124 @@ impl<M: Middleware + 'static, V: UserOperationValidator> UoPool<M, V> {
             is_staked: self.reputation.verify_stake("", Some(stake_info), None, None).is_ok(),
         })
     }
+
+    /// Gets the [DepositInfo](DepositInfo) of the address in the entry point
+    ///
+    /// # Arguments
+    /// * `addr` - The address of the account, paymaster or any other entity.
+    ///
+    /// # Returns
+    /// `Result<DepositInfo, eyre::Error>` - Deposit and stake of the address.
+    pub async fn get_deposit_info(&self, addr: &Address) -> eyre::Result<DepositInfo> {
+        Ok(self.entry_point.get_deposit_info(addr).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{memory_mempool, memory_reputation};
+    use enumset::EnumSet;
+    use ethers::providers::{MockProvider, Provider};
+
+    /// Validator that only accepts the next nonce of the sender in the mempool (a nonce of 99 is
+    /// always rejected)
+    struct NonceValidator;
+
+    #[async_trait::async_trait]
+    impl UserOperationValidator for NonceValidator {
+        async fn validate_user_operation(
+            &self,
+            uo: &UserOperation,
+            mempool: &Mempool,
+            _reputation: &Reputation,
+            _val_config: Option<ValidationConfig>,
+            _mode: EnumSet<UserOperationValidatorMode>,
+        ) -> Result<UserOperationValidationOutcome, InvalidMempoolUserOperationError> {
+            let on_chain_nonce = U256::from(mempool.get_number_by_sender(&uo.sender));
+            if uo.nonce != on_chain_nonce {
+                return Err(SanityError::NonceTooHigh { nonce: uo.nonce, on_chain_nonce }.into());
+            }
+            Ok(UserOperationValidationOutcome::default())
+        }
+    }
+
+    fn uo(sender: Address, nonce: u64) -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::default().sender(sender).nonce(nonce.into()),
+        )
+    }
+
+    #[tokio::test]
+    async fn add_user_operations_batch() {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let mut uopool = UoPool::new(
+            UoPoolMode::Standard,
+            EntryPoint::new(Arc::new(provider), Address::random()),
+            NonceValidator,
+            memory_mempool(),
+            memory_reputation(),
+            U256::from(5000000),
+            EntryPointOverhead::default(),
+            Chain::dev(),
+            None,
+        );
+
+        let (sender, sender_other) = (Address::random(), Address::random());
+        let uos = vec![
+            uo(sender, 0),
+            uo(sender_other, 99),
+            // validated after the first user operation of the sender is added
+            uo(sender, 1),
+            uo(sender_other, 0),
+            uo(sender, 2),
+        ];
+        let hashes: Vec<_> = uos.iter().map(|uo| uo.hash).collect();
+
+        let results = uopool.add_user_operations_batch(uos).await;
+        assert_eq!(results.len(), 5);
+
+        // the results are in the order of the batch and the invalid user operation doesn't fail
+        // the others
+        for (i, res) in results.iter().enumerate() {
+            if i == 1 {
+                assert!(matches!(
+                    res,
+                    Err(MempoolError { kind: MempoolErrorKind::InvalidUserOperation(_), hash })
+                        if *hash == hashes[1]
+                ));
+            } else {
+                assert_eq!(*res.as_ref().unwrap(), hashes[i]);
+            }
+        }
+
+        assert_eq!(uopool.mempool.get_number_by_sender(&sender), 3);
+        assert_eq!(uopool.mempool.get_number_by_sender(&sender_other), 1);
+        assert_eq!(uopool.mempool.len().unwrap(), 4);
+    }
+
+    #[tokio::test]
+    async fn estimate_pre_verification_gas() {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let uopool = UoPool::new(
+            UoPoolMode::Standard,
+            EntryPoint::new(Arc::new(provider), Address::random()),
+            NonceValidator,
+            memory_mempool(),
+            memory_reputation(),
+            U256::from(5000000),
+            EntryPointOverhead { fixed_overhead: 21000.into(), per_op_overhead: 18000.into() },
+            Chain::dev(),
+            None,
+        );
+
+        // the 576 bytes of the packed user operation with a dummy 65 bytes signature cost 42568
+        // gas, the entry point overhead isn't added on top of it again
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default(),
+        );
+        assert_eq!(uopool.estimate_pre_verification_gas(&uo).await.unwrap(), 46825.into());
+    }
 }
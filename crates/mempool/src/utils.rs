@@ -1,6 +1,19 @@
-use ethers::types::{Address, H256, U256};
-use silius_primitives::{simulation::CodeHash, UserOperationSigned};
-use std::{collections::HashMap, ops::Deref};
+use alloy_chains::{Chain, NamedChain};
+use ethers::{
+    contract::EthEvent,
+    types::{Address, Block, Log, H256, U256},
+};
+use silius_contracts::entry_point::UserOperationEventFilter;
+use silius_primitives::{
+    constants::mempool::PRIORITY_FEE_PERCENTILE, simulation::CodeHash, EntryPointVersion,
+    UserOperation, UserOperationSigned,
+};
+use std::{collections::HashMap, ops::Deref, str::FromStr};
+
+/// Topic of the event the entry point emits before executing the user operations:
+/// keccak256("BeforeExecution()")
+const BEFORE_EXECUTION_TOPIC: &str =
+    "0xbb47ee3e183a558b1a2ff0874b079f3fc5478b7454eacf2bfc5af2ff5878f972";
 
 pub fn equal_code_hashes(hashes: &[CodeHash], hashes_prev: &Vec<CodeHash>) -> bool {
     if hashes_prev.len() != hashes.len() {
@@ -33,6 +46,8 @@ pub struct Overhead {
     pub non_zero_byte: U256,
     pub bundle_size: U256,
     pub sig_size: U256,
+    /// Version of the entry point the user operation is packed for
+    pub entry_point_version: EntryPointVersion,
 }
 
 impl Default for Overhead {
@@ -45,11 +60,17 @@ impl Default for Overhead {
             non_zero_byte: U256::from(16),
             bundle_size: U256::from(1),
             sig_size: U256::from(65),
+            entry_point_version: EntryPointVersion::V0_6,
         }
     }
 }
 
 impl Overhead {
+    /// Creates the default [Overhead](Overhead) for the user operations of the entry point version
+    pub fn for_entry_point(entry_point_version: EntryPointVersion) -> Self {
+        Self { entry_point_version, ..Default::default() }
+    }
+
     /// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned)
     /// The function first packs the [UserOperation](UserOperationSigned), then extracts the call
     /// data for gas calculation.
@@ -60,7 +81,7 @@ impl Overhead {
     /// # Returns
     /// The pre-verification gas of the [UserOperation](UserOperationSigned)
     pub fn calculate_pre_verification_gas(&self, uo: &UserOperationSigned) -> U256 {
-        let uo_pack = uo.pack();
+        let uo_pack = uo.pack_for(self.entry_point_version);
 
         let call_data = uo_pack.deref().iter().fold(U256::zero(), |acc, &x| {
             let byte_cost = if x == 0 { &self.zero_byte } else { &self.non_zero_byte };
@@ -84,6 +105,350 @@ impl Overhead {
             .saturating_add(self.per_user_op)
             .saturating_add(word_cost)
     }
+
+    /// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned) as if it was
+    /// signed with a signature of `sig_size` bytes
+    ///
+    /// The pre-verification gas is part of the signed data, so it has to be estimated before the
+    /// signature is known. ECDSA signatures are 65 bytes, ERC-1271 accounts can use any size.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperationSigned) to calculate the pre-verification gas for
+    /// `sig_size` - The expected size of the signature in bytes
+    ///
+    /// # Returns
+    /// The pre-verification gas of the [UserOperation](UserOperationSigned)
+    pub fn calculate_pre_verification_gas_with_sig_size(
+        &self,
+        uo: &UserOperationSigned,
+        sig_size: usize,
+    ) -> U256 {
+        self.calculate_pre_verification_gas(&with_dummy_signature(uo, sig_size))
+    }
+}
+
+/// Replaces the signature of the [UserOperation](UserOperationSigned) with `sig_size` non-zero
+/// bytes (the most expensive signature of that size)
+fn with_dummy_signature(uo: &UserOperationSigned, sig_size: usize) -> UserOperationSigned {
+    UserOperationSigned { signature: vec![0xff; sig_size].into(), ..uo.clone() }
+}
+
+/// Struct to calculate the pre-verification gas of a user operation after the Pectra upgrade
+///
+/// [EIP-7623](https://eips.ethereum.org/EIPS/eip-7623) introduces a calldata floor price: a zero
+/// byte of calldata is one token, a non-zero byte is `tokens_per_non_zero_byte` tokens, and each
+/// token costs `floor_per_token` gas. The user operation is charged the maximum of the standard
+/// and the floor pre-verification gas.
+pub struct PectraOverhead {
+    pub standard: Overhead,
+    pub tokens_per_non_zero_byte: U256,
+    pub floor_per_token: U256,
+}
+
+impl Default for PectraOverhead {
+    fn default() -> Self {
+        Self {
+            standard: Overhead::default(),
+            tokens_per_non_zero_byte: U256::from(4),
+            floor_per_token: U256::from(10),
+        }
+    }
+}
+
+impl PectraOverhead {
+    /// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned) using the
+    /// EIP-7623 calldata pricing
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperationSigned) to calculate the pre-verification gas for
+    ///
+    /// # Returns
+    /// The pre-verification gas of the [UserOperation](UserOperationSigned)
+    pub fn calculate_pre_verification_gas(&self, uo: &UserOperationSigned) -> U256 {
+        let standard = self.standard.calculate_pre_verification_gas(uo);
+        standard.max(self.calculate_floor_pre_verification_gas(uo))
+    }
+
+    /// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned) with the
+    /// calldata priced at the EIP-7623 floor
+    pub fn calculate_floor_pre_verification_gas(&self, uo: &UserOperationSigned) -> U256 {
+        let uo_pack = uo.pack_for(self.standard.entry_point_version);
+
+        let tokens = uo_pack.deref().iter().fold(U256::zero(), |acc, &x| {
+            acc.saturating_add(if x == 0 { U256::one() } else { self.tokens_per_non_zero_byte })
+        });
+        let call_data = tokens.saturating_mul(self.floor_per_token);
+
+        let word_cost = div_ceil(
+            self.standard.per_user_op_word.saturating_mul(U256::from(uo_pack.len() + 31)),
+            U256::from(32),
+        );
+
+        let fixed_divided_by_bundle_size = div_ceil(self.standard.fixed, self.standard.bundle_size);
+
+        fixed_divided_by_bundle_size
+            .saturating_add(call_data)
+            .saturating_add(self.standard.per_user_op)
+            .saturating_add(word_cost)
+    }
+}
+
+/// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned) with the default
+/// [PectraOverhead](PectraOverhead)
+pub fn calculate_pre_verification_gas_pectra(uo: &UserOperationSigned) -> U256 {
+    PectraOverhead::default().calculate_pre_verification_gas(uo)
+}
+
+/// Calldata pricing rules that are active on the connected chain
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CalldataPricing {
+    /// Calldata pricing before the Pectra upgrade
+    #[default]
+    Legacy,
+    /// Calldata pricing with the EIP-7623 floor
+    Pectra,
+}
+
+impl CalldataPricing {
+    /// Detects the calldata pricing from a block
+    ///
+    /// Blocks produced after the Pectra upgrade contain the `requestsHash` field (named
+    /// `requestsRoot` in the earlier drafts of EIP-7685).
+    pub fn from_block<TX>(block: &Block<TX>) -> Self {
+        if block.other.contains_key("requestsHash") || block.other.contains_key("requestsRoot") {
+            CalldataPricing::Pectra
+        } else {
+            CalldataPricing::Legacy
+        }
+    }
+
+    /// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned) of the entry
+    /// point version according to the calldata pricing
+    pub fn calculate_pre_verification_gas(
+        &self,
+        uo: &UserOperationSigned,
+        entry_point_version: EntryPointVersion,
+    ) -> U256 {
+        let standard = Overhead::for_entry_point(entry_point_version);
+        match self {
+            CalldataPricing::Legacy => standard.calculate_pre_verification_gas(uo),
+            CalldataPricing::Pectra => {
+                PectraOverhead { standard, ..Default::default() }.calculate_pre_verification_gas(uo)
+            }
+        }
+    }
+
+    /// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned) with a dummy
+    /// signature of `sig_size` bytes according to the calldata pricing
+    pub fn calculate_pre_verification_gas_with_sig_size(
+        &self,
+        uo: &UserOperationSigned,
+        entry_point_version: EntryPointVersion,
+        sig_size: usize,
+    ) -> U256 {
+        self.calculate_pre_verification_gas(
+            &with_dummy_signature(uo, sig_size),
+            entry_point_version,
+        )
+    }
+}
+
+/// Denominator of the OP Stack fee scalars
+const OP_FEE_SCALAR_DECIMALS: u64 = 1_000_000;
+
+/// Default OP Stack (Ecotone) base fee scalar, as set on OP Mainnet
+const OP_BASE_FEE_SCALAR: u64 = 5_227;
+
+/// Default OP Stack (Ecotone) blob base fee scalar, as set on OP Mainnet
+const OP_BLOB_BASE_FEE_SCALAR: u64 = 1_014_213;
+
+/// How the pre-verification gas is priced on the connected chain
+///
+/// On L2s the dominant cost of a user operation is posting its calldata to L1, which is charged
+/// on top of the L2 execution gas. The L1 part is converted to L2 gas with the max fee per gas of
+/// the user operation (or the base fee if it isn't set yet), since the pre-verification gas is
+/// paid at that price.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainPreVerificationGas {
+    /// The calldata is paid with the L1 gas of the bundle transaction
+    Mainnet {
+        /// Intrinsic gas of the bundle transaction
+        fixed_cost: U256,
+    },
+    /// OP Stack chains charge the (Ecotone) L1 data fee for the calldata gas of the transaction
+    OptimismStack {
+        /// L1 base fee reported by the `GasPriceOracle` predeploy
+        l1_base_fee: U256,
+        /// Scalar of the L1 base fee (in millionths)
+        base_fee_scalar: U256,
+        /// L1 blob base fee reported by the `GasPriceOracle` predeploy
+        blob_base_fee: U256,
+        /// Scalar of the L1 blob base fee (in millionths)
+        blob_base_fee_scalar: U256,
+    },
+    /// Arbitrum charges the calldata posted to L1 in wei per byte
+    Arbitrum {
+        /// Price of one calldata byte reported by the `ArbGasInfo` precompile
+        l1_price_per_byte: U256,
+    },
+}
+
+impl Default for ChainPreVerificationGas {
+    fn default() -> Self {
+        Self::Mainnet { fixed_cost: Overhead::default().fixed }
+    }
+}
+
+impl ChainPreVerificationGas {
+    /// Selects the pricing of the chain (the L1 prices of OP Stack and Arbitrum chains are
+    /// updated with every block)
+    pub fn for_chain(chain: Chain) -> Self {
+        match chain.named() {
+            Some(
+                NamedChain::Optimism |
+                NamedChain::OptimismSepolia |
+                NamedChain::Base |
+                NamedChain::BaseSepolia,
+            ) => Self::OptimismStack {
+                l1_base_fee: U256::zero(),
+                base_fee_scalar: U256::from(OP_BASE_FEE_SCALAR),
+                blob_base_fee: U256::zero(),
+                blob_base_fee_scalar: U256::from(OP_BLOB_BASE_FEE_SCALAR),
+            },
+            Some(NamedChain::Arbitrum | NamedChain::ArbitrumNova | NamedChain::ArbitrumSepolia) => {
+                Self::Arbitrum { l1_price_per_byte: U256::zero() }
+            }
+            _ => Self::default(),
+        }
+    }
+
+    /// Calculates the L2 gas paying for the L1 calldata of a [UserOperation](UserOperationSigned)
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperationSigned) to calculate the L1 gas for
+    /// `entry_point_version` - The version of the entry point the user operation is packed for
+    /// `base_fee_per_gas` - The gas price used when the max fee per gas of the user operation is 0
+    ///
+    /// # Returns
+    /// The L1 part of the pre-verification gas (0 on L1 chains)
+    pub fn l1_gas(
+        &self,
+        uo: &UserOperationSigned,
+        entry_point_version: EntryPointVersion,
+        base_fee_per_gas: U256,
+    ) -> U256 {
+        let gas_price =
+            if uo.max_fee_per_gas.is_zero() { base_fee_per_gas } else { uo.max_fee_per_gas };
+
+        let l1_fee = match self {
+            Self::Mainnet { .. } => return U256::zero(),
+            Self::OptimismStack {
+                l1_base_fee,
+                base_fee_scalar,
+                blob_base_fee,
+                blob_base_fee_scalar,
+            } => {
+                let overhead = Overhead::default();
+                let uo_pack = uo.pack_for(entry_point_version);
+                let calldata_gas = uo_pack.iter().fold(U256::zero(), |acc, &x| {
+                    let byte_cost =
+                        if x == 0 { overhead.zero_byte } else { overhead.non_zero_byte };
+                    acc.saturating_add(byte_cost)
+                });
+                // Ecotone: (calldata_gas / 16) * (16 * base_fee_scalar * l1_base_fee +
+                // blob_base_fee_scalar * blob_base_fee) / 1e6
+                let weighted_gas_price = U256::from(16)
+                    .saturating_mul(*base_fee_scalar)
+                    .saturating_mul(*l1_base_fee)
+                    .saturating_add(blob_base_fee_scalar.saturating_mul(*blob_base_fee));
+                div_ceil(
+                    calldata_gas.saturating_mul(weighted_gas_price),
+                    U256::from(16 * OP_FEE_SCALAR_DECIMALS),
+                )
+            }
+            Self::Arbitrum { l1_price_per_byte } => {
+                l1_price_per_byte.saturating_mul(U256::from(uo.pack_for(entry_point_version).len()))
+            }
+        };
+
+        div_ceil(l1_fee, gas_price)
+    }
+
+    /// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned): the L2
+    /// execution part according to the calldata pricing and the L1 calldata part of the chain
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperationSigned) to calculate the pre-verification gas for
+    /// `entry_point_version` - The version of the entry point the user operation is packed for
+    /// `calldata_pricing` - The calldata pricing active on the chain
+    /// `base_fee_per_gas` - The gas price used when the max fee per gas of the user operation is 0
+    ///
+    /// # Returns
+    /// The pre-verification gas of the [UserOperation](UserOperationSigned)
+    pub fn calculate_pre_verification_gas(
+        &self,
+        uo: &UserOperationSigned,
+        entry_point_version: EntryPointVersion,
+        calldata_pricing: CalldataPricing,
+        base_fee_per_gas: U256,
+    ) -> U256 {
+        let base = calldata_pricing.calculate_pre_verification_gas(uo, entry_point_version);
+
+        match self {
+            Self::Mainnet { fixed_cost } => {
+                base.saturating_sub(Overhead::default().fixed).saturating_add(*fixed_cost)
+            }
+            _ => base.saturating_add(self.l1_gas(uo, entry_point_version, base_fee_per_gas)),
+        }
+    }
+
+    /// Calculates the pre-verification gas of a [UserOperation](UserOperationSigned) with a dummy
+    /// signature of `sig_size` bytes
+    pub fn calculate_pre_verification_gas_with_sig_size(
+        &self,
+        uo: &UserOperationSigned,
+        entry_point_version: EntryPointVersion,
+        calldata_pricing: CalldataPricing,
+        base_fee_per_gas: U256,
+        sig_size: usize,
+    ) -> U256 {
+        self.calculate_pre_verification_gas(
+            &with_dummy_signature(uo, sig_size),
+            entry_point_version,
+            calldata_pricing,
+            base_fee_per_gas,
+        )
+    }
+}
+
+/// Gas consumed by the entry point itself before and after calling the account (and paymaster)
+///
+/// Default values correspond to the `handleOps` execution of the entry point v0.6.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EntryPointOverhead {
+    /// Overhead charged once per bundle
+    pub fixed_overhead: U256,
+    /// Overhead charged for each user operation in the bundle
+    pub per_op_overhead: U256,
+}
+
+impl Default for EntryPointOverhead {
+    fn default() -> Self {
+        Self { fixed_overhead: U256::from(10000), per_op_overhead: U256::from(8000) }
+    }
+}
+
+impl EntryPointOverhead {
+    /// Calculates the gas the entry point consumes for a bundle of `num_ops` user operations
+    ///
+    /// # Arguments
+    /// `num_ops` - The number of user operations in the bundle
+    ///
+    /// # Returns
+    /// `fixed_overhead + per_op_overhead * num_ops`
+    pub fn calculate_entry_point_overhead(&self, num_ops: usize) -> U256 {
+        self.fixed_overhead.saturating_add(self.per_op_overhead.saturating_mul(U256::from(num_ops)))
+    }
 }
 
 /// Helper function to calculate the valid gas of a [UserOperation](UserOperation)
@@ -106,6 +471,88 @@ pub fn calculate_valid_gas(gas_price: U256, gas_incr_perc: U256) -> U256 {
     div_ceil(numerator, denominator)
 }
 
+/// Gas price the bundler is paid by the [UserOperation](UserOperation) at the given base fee
+pub fn effective_gas_price(uo: &UserOperation, base_fee_per_gas: U256) -> U256 {
+    uo.max_fee_per_gas.min(uo.max_priority_fee_per_gas.saturating_add(base_fee_per_gas))
+}
+
+/// Sorts the [UserOperations](UserOperation) by the effective gas price in descending order, the
+/// nonce breaks the ties
+pub fn sort_by_effective_gas_price(uos: &mut [UserOperation], base_fee_per_gas: U256) {
+    uos.sort_by(|a, b| {
+        effective_gas_price(b, base_fee_per_gas)
+            .cmp(&effective_gas_price(a, base_fee_per_gas))
+            .then_with(|| a.nonce.cmp(&b.nonce))
+    });
+}
+
+/// Suggests the max priority fee per gas for new [UserOperations](UserOperation) based on the
+/// user operations currently in the mempool
+///
+/// # Arguments
+/// `uos` - The user operations in the mempool
+/// `base_fee_per_gas` - The current base fee per gas
+/// `tip_perc` - The percentage added on top of the percentile priority fee
+/// `min_priority_fee_per_gas` - The lowest priority fee the bundler accepts
+///
+/// # Returns
+/// The [PRIORITY_FEE_PERCENTILE](PRIORITY_FEE_PERCENTILE)th percentile of the effective priority
+/// fees increased by `tip_perc`, but never lower than `min_priority_fee_per_gas`
+pub fn estimate_max_priority_fee_per_gas(
+    uos: &[UserOperation],
+    base_fee_per_gas: U256,
+    tip_perc: U256,
+    min_priority_fee_per_gas: U256,
+) -> U256 {
+    // user operations that can't pay the base fee aren't going to be bundled anyway
+    let mut fees = uos
+        .iter()
+        .filter(|uo| uo.max_fee_per_gas >= base_fee_per_gas)
+        .map(|uo| uo.max_priority_fee_per_gas.min(uo.max_fee_per_gas - base_fee_per_gas))
+        .collect::<Vec<U256>>();
+
+    if fees.is_empty() {
+        return min_priority_fee_per_gas;
+    }
+
+    fees.sort();
+    // nearest-rank percentile
+    let rank = (fees.len() * PRIORITY_FEE_PERCENTILE).div_ceil(100);
+    let fee = fees[rank.saturating_sub(1)];
+
+    calculate_valid_gas(fee, tip_perc).max(min_priority_fee_per_gas)
+}
+
+/// Picks the logs emitted during the execution of a single [UserOperation](UserOperation) out of the
+/// logs of the bundle transaction
+///
+/// # Arguments
+/// `logs` - The logs of the bundle transaction
+/// `entry_point` - The address of the entry point
+/// `log_index` - The log index of the `UserOperationEvent` of the user operation
+///
+/// # Returns
+/// The logs between the preceding `BeforeExecution` or `UserOperationEvent` of the entry point and
+/// the `UserOperationEvent` of the user operation (both excluded)
+pub fn user_operation_logs(logs: &[Log], entry_point: Address, log_index: U256) -> Vec<Log> {
+    let Some(end) = logs.iter().position(|log| log.log_index == Some(log_index)) else {
+        return vec![];
+    };
+
+    let before_execution = H256::from_str(BEFORE_EXECUTION_TOPIC).expect("Topic is valid");
+    let start = logs[..end]
+        .iter()
+        .rposition(|log| {
+            log.address == entry_point &&
+                log.topics.first().map_or(false, |topic| {
+                    *topic == before_execution || *topic == UserOperationEventFilter::signature()
+                })
+        })
+        .map_or(0, |i| i + 1);
+
+    logs[start..end].to_vec()
+}
+
 /// Helper function to calculate the call gas limit of a [UserOperation](UserOperation)
 /// The function is invoked by the
 /// [estimate_user_operation_gas](crates::uopool::estimate::estimate_user_operation_gas) method.
@@ -150,10 +597,22 @@ pub fn div_ceil(numerator: U256, denominator: U256) -> U256 {
 pub mod tests {
     use super::*;
     use crate::{mempool::Mempool, Reputation};
-    use ethers::types::{Address, Bytes, H256, U256};
+    use ethers::types::{Address, Block, Bytes, H256, U256};
     use silius_primitives::{
+        constants::validation::reputation::{
+            BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK,
+        },
         reputation::{ReputationEntry, Status},
-        UserOperation, UserOperationHash, UserOperationSigned,
+        UserOperation, UserOperationHash, UserOperationReceipt, UserOperationSigned,
+    };
+    use std::{
+        collections::HashSet,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::{Duration, Instant},
     };
 
     #[test]
@@ -206,6 +665,7 @@ pub mod tests {
             non_zero_byte: U256::from(16),
             bundle_size: U256::from(1),
             sig_size: U256::from(65),
+            entry_point_version: EntryPointVersion::V0_6,
         };
         let uo = UserOperationSigned {
             sender: "0xAB7e2cbFcFb6A5F33A75aD745C3E5fB48d689B54".parse().unwrap(),
@@ -224,6 +684,36 @@ pub mod tests {
         assert_eq!(gas_oh.calculate_pre_verification_gas(&uo), 1549132.into());
     }
 
+    #[test]
+    fn pre_verification_gas_calculation_with_sig_size() {
+        let gas_oh = Overhead::default();
+        let uo = UserOperationSigned {
+            signature: Bytes::from(vec![255; 1024]),
+            ..UserOperationSigned::random()
+        };
+
+        let pvg_ecdsa = gas_oh.calculate_pre_verification_gas_with_sig_size(&uo, 65);
+        let pvg_erc1271 = gas_oh.calculate_pre_verification_gas_with_sig_size(&uo, 100);
+        assert!(pvg_erc1271 > pvg_ecdsa);
+
+        // the actual signature of the user operation is ignored
+        assert_eq!(
+            pvg_ecdsa,
+            gas_oh.calculate_pre_verification_gas(&UserOperationSigned {
+                signature: Bytes::from(vec![255; 65]),
+                ..uo.clone()
+            })
+        );
+        assert_eq!(
+            CalldataPricing::Legacy.calculate_pre_verification_gas_with_sig_size(
+                &uo,
+                EntryPointVersion::V0_6,
+                65
+            ),
+            pvg_ecdsa
+        );
+    }
+
     /// This test occurred overflow when previous `calculate_pre_verification_gas` is used.
     /// previous `calculate_pre_verification_gas` is https://github.com/silius-rs/silius/blob/bd79ea0e610adff8d77ba128f53befa8401a4d77/crates/uopool/src/utils.rs#L63-L84
     #[test]
@@ -257,6 +747,161 @@ pub mod tests {
         let _ = gas_oh.calculate_pre_verification_gas(&uo);
     }
 
+    #[test]
+    fn pre_verification_gas_calculation_pectra() {
+        let uo = UserOperationSigned {
+            sender: "0xAB7e2cbFcFb6A5F33A75aD745C3E5fB48d689B54".parse().unwrap(),
+            nonce: U256::zero(),
+            init_code: "0xe19e9755942bb0bd0cccce25b1742596b8a8250b3bf2c3e70000000000000000000000001d9a2cb3638c2fc8bf9c01d088b79e75cd188b17000000000000000000000000789d9058feecf1948af429793e7f1eb4a75db2220000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_data: "0x80c5c7d0000000000000000000000000ab7e2cbfcfb6a5f33a75ad745c3e5fb48d689b5400000000000000000000000000000000000000000000000002c68af0bb14000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_gas_limit: 21900.into(),
+            verification_gas_limit: 1218343.into(),
+            pre_verification_gas: 50780.into(),
+            max_fee_per_gas: 10064120791_u64.into(),
+            max_priority_fee_per_gas: 1620899097.into(),
+            paymaster_and_data: Bytes::default(),
+            signature: "0x4e69eb5e02d47ba28878655d61c59c20c3e9a2e6905381305626f6a5a2892ec12bd8dd59179f0642731e0e853af54a71ce422a1a234548c9dd1c559bd07df4461c".parse().unwrap(),
+        };
+
+        let pvg = Overhead::default().calculate_pre_verification_gas(&uo);
+        let pvg_pectra = calculate_pre_verification_gas_pectra(&uo);
+        assert_eq!(pvg, 45340.into());
+        // the 800 bytes of the packed user operation are 572 zero and 228 non-zero bytes, 1484
+        // tokens priced at the floor are above the standard cost of the calldata
+        let pectra = PectraOverhead::default();
+        assert_eq!(pectra.calculate_floor_pre_verification_gas(&uo), 54244.into());
+        assert_eq!(pvg_pectra, 54244.into());
+        assert!(pvg_pectra > pvg);
+
+        // the standard cost is charged when it is above the floor
+        let pectra = PectraOverhead { floor_per_token: U256::from(1), ..Default::default() };
+        assert_eq!(pectra.calculate_pre_verification_gas(&uo), pvg);
+
+        let version = EntryPointVersion::V0_6;
+        assert_eq!(CalldataPricing::Legacy.calculate_pre_verification_gas(&uo, version), pvg);
+        assert_eq!(
+            CalldataPricing::Pectra.calculate_pre_verification_gas(&uo, version),
+            pvg_pectra
+        );
+    }
+
+    #[test]
+    fn pre_verification_gas_calculation_v0_7() {
+        let uo = UserOperationSigned::random();
+
+        // the packed user operation of entry point v0.7 is 2 words shorter
+        let overhead = Overhead::for_entry_point(EntryPointVersion::V0_7);
+        let pvg = Overhead::default().calculate_pre_verification_gas(&uo);
+        let pvg_v0_7 = overhead.calculate_pre_verification_gas(&uo);
+        assert!(pvg_v0_7 < pvg);
+        assert_eq!(
+            CalldataPricing::Legacy.calculate_pre_verification_gas(&uo, EntryPointVersion::V0_7),
+            pvg_v0_7
+        );
+
+        let arbitrum =
+            ChainPreVerificationGas::Arbitrum { l1_price_per_byte: U256::from(1_000_000_000_u64) };
+        assert_eq!(
+            arbitrum.l1_gas(&uo, EntryPointVersion::V0_6, U256::from(1_000_000_000_u64)) -
+                arbitrum.l1_gas(&uo, EntryPointVersion::V0_7, U256::from(1_000_000_000_u64)),
+            U256::from(64)
+        );
+    }
+
+    #[test]
+    fn chain_pre_verification_gas_calculation() {
+        let uo = UserOperationSigned {
+            max_fee_per_gas: U256::from(1_000_000_000),
+            ..UserOperationSigned::random()
+        };
+        let base_fee = U256::from(500_000_000);
+        let version = EntryPointVersion::V0_6;
+        let pvg = CalldataPricing::Legacy.calculate_pre_verification_gas(&uo, version);
+
+        let mainnet = ChainPreVerificationGas::for_chain(Chain::from(NamedChain::Mainnet));
+        assert_eq!(mainnet, ChainPreVerificationGas::default());
+        assert_eq!(
+            mainnet.calculate_pre_verification_gas(&uo, version, CalldataPricing::Legacy, base_fee),
+            pvg
+        );
+        let mainnet = ChainPreVerificationGas::Mainnet { fixed_cost: U256::from(25000) };
+        assert_eq!(
+            mainnet.calculate_pre_verification_gas(&uo, version, CalldataPricing::Legacy, base_fee),
+            pvg + U256::from(4000)
+        );
+
+        // the L1 fees aren't known before the first block
+        let optimism = ChainPreVerificationGas::for_chain(Chain::from(NamedChain::Optimism));
+        assert_eq!(
+            optimism.calculate_pre_verification_gas(
+                &uo,
+                version,
+                CalldataPricing::Legacy,
+                base_fee
+            ),
+            pvg
+        );
+        let optimism = ChainPreVerificationGas::OptimismStack {
+            l1_base_fee: U256::from(10_000_000_000_u64),
+            base_fee_scalar: U256::from(1_000_000),
+            blob_base_fee: U256::from(20_000_000_000_u64),
+            blob_base_fee_scalar: U256::from(8_000_000),
+        };
+        let calldata_gas = |uo: &UserOperationSigned| {
+            uo.pack()
+                .iter()
+                .map(|&x| if x == 0 { U256::from(4) } else { U256::from(16) })
+                .fold(U256::zero(), |acc, x| acc + x)
+        };
+        // (16 * 10 gwei + 8 * 20 gwei) / 16 = 20 gwei per calldata gas, paid at 1 gwei
+        assert_eq!(
+            optimism.calculate_pre_verification_gas(
+                &uo,
+                version,
+                CalldataPricing::Legacy,
+                base_fee
+            ),
+            pvg + calldata_gas(&uo) * U256::from(20)
+        );
+        // the base fee prices the L1 fee when the max fee per gas isn't set yet
+        let unpriced = UserOperationSigned { max_fee_per_gas: U256::zero(), ..uo.clone() };
+        assert_eq!(
+            optimism.l1_gas(&unpriced, version, base_fee),
+            calldata_gas(&unpriced) * U256::from(40)
+        );
+
+        let arbitrum = ChainPreVerificationGas::for_chain(Chain::from(NamedChain::Arbitrum));
+        assert_eq!(
+            arbitrum.calculate_pre_verification_gas(
+                &uo,
+                version,
+                CalldataPricing::Legacy,
+                base_fee
+            ),
+            pvg
+        );
+        let arbitrum =
+            ChainPreVerificationGas::Arbitrum { l1_price_per_byte: U256::from(3_000_000_000_u64) };
+        assert_eq!(
+            arbitrum.calculate_pre_verification_gas(
+                &uo,
+                version,
+                CalldataPricing::Legacy,
+                base_fee
+            ),
+            pvg + U256::from(3 * uo.pack().len())
+        );
+    }
+
+    #[test]
+    fn calldata_pricing_from_block() {
+        let mut block = Block::<H256>::default();
+        assert_eq!(CalldataPricing::from_block(&block), CalldataPricing::Legacy);
+
+        block.other.insert("requestsHash".into(), format!("{:?}", H256::zero()).into());
+        assert_eq!(CalldataPricing::from_block(&block), CalldataPricing::Pectra);
+    }
+
     #[test]
     fn valid_gas_calculation_when_no_round_up_case() {
         let gas_price = U256::from(100);
@@ -272,6 +917,78 @@ pub mod tests {
         assert_eq!(calculate_valid_gas(gas_price, gas_incr_perc), 12.into());
     }
 
+    #[test]
+    fn max_priority_fee_per_gas_estimation() {
+        let base_fee_per_gas = U256::from(100);
+        let uos = (1..=10)
+            .map(|i| {
+                UserOperation::from_user_operation_signed(
+                    H256::from_low_u64_be(i).into(),
+                    UserOperationSigned::default()
+                        .nonce(i.into())
+                        .max_priority_fee_per_gas(U256::from(i * 10))
+                        .max_fee_per_gas(U256::from(1000)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // 75th percentile of 10, 20, ..., 100 is 80, plus 10%
+        assert_eq!(
+            estimate_max_priority_fee_per_gas(&uos, base_fee_per_gas, 10.into(), U256::zero()),
+            88.into()
+        );
+        assert_eq!(
+            estimate_max_priority_fee_per_gas(&uos, base_fee_per_gas, 10.into(), 200.into()),
+            200.into()
+        );
+
+        // effective priority fee is capped by the max fee minus the base fee
+        let uos = uos
+            .into_iter()
+            .map(|uo| {
+                UserOperation::from_user_operation_signed(
+                    uo.hash,
+                    uo.user_operation.clone().max_fee_per_gas(U256::from(150)),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            estimate_max_priority_fee_per_gas(&uos, base_fee_per_gas, 10.into(), U256::zero()),
+            55.into()
+        );
+
+        // user operations that can't pay the base fee are ignored
+        assert_eq!(
+            estimate_max_priority_fee_per_gas(&uos, 200.into(), 10.into(), 1.into()),
+            1.into()
+        );
+        assert_eq!(
+            estimate_max_priority_fee_per_gas(&[], base_fee_per_gas, 10.into(), 5.into()),
+            5.into()
+        );
+    }
+
+    #[test]
+    fn entry_point_overhead_scales_linearly() {
+        let overhead = EntryPointOverhead {
+            fixed_overhead: U256::from(10000),
+            per_op_overhead: U256::from(8000),
+        };
+        assert_eq!(overhead.calculate_entry_point_overhead(0), 10000.into());
+        assert_eq!(overhead.calculate_entry_point_overhead(1), 18000.into());
+        assert_eq!(overhead.calculate_entry_point_overhead(10), 90000.into());
+
+        let per_op = overhead.calculate_entry_point_overhead(2) -
+            overhead.calculate_entry_point_overhead(1);
+        for num_ops in 1..20 {
+            assert_eq!(
+                overhead.calculate_entry_point_overhead(num_ops + 1) -
+                    overhead.calculate_entry_point_overhead(num_ops),
+                per_op
+            );
+        }
+    }
+
     #[test]
     fn call_gas_limit_calculation() {
         let paid = U256::from(100);
@@ -298,6 +1015,36 @@ pub mod tests {
         assert_eq!(div_ceil(U256::from(10), U256::from(3)), 4.into());
     }
 
+    #[test]
+    fn user_operation_logs_filtering() {
+        let ep = Address::random();
+        let account = Address::random();
+        let log = |address: Address, topic: H256, log_index: u64| Log {
+            address,
+            topics: vec![topic],
+            log_index: Some(log_index.into()),
+            ..Default::default()
+        };
+
+        let before_execution = H256::from_str(BEFORE_EXECUTION_TOPIC).unwrap();
+        let uo_event = UserOperationEventFilter::signature();
+        let logs = vec![
+            log(ep, H256::random(), 0),
+            log(ep, before_execution, 1),
+            log(account, H256::random(), 2),
+            log(ep, uo_event, 3),
+            log(account, H256::random(), 4),
+            log(account, H256::random(), 5),
+            log(ep, uo_event, 6),
+        ];
+
+        assert_eq!(user_operation_logs(&logs, ep, 3.into()), logs[2..3].to_vec());
+        assert_eq!(user_operation_logs(&logs, ep, 6.into()), logs[4..6].to_vec());
+        // events of other entry points don't delimit the user operations
+        assert_eq!(user_operation_logs(&logs, Address::random(), 6.into()), logs[0..6].to_vec());
+        assert!(user_operation_logs(&logs, ep, 7.into()).is_empty());
+    }
+
     pub fn mempool_test_case(mut mempool: Mempool) {
         let ep = Address::random();
         let chain_id = 5_u64;
@@ -355,26 +1102,38 @@ pub mod tests {
         }
 
         assert_eq!(mempool.get_all().unwrap().len(), 7);
+        assert_eq!(mempool.len().unwrap(), 7);
         assert_eq!(mempool.get_all_by_sender(&senders[0]).len(), 2);
         assert_eq!(mempool.get_all_by_sender(&senders[1]).len(), 2);
         assert_eq!(mempool.get_all_by_sender(&senders[2]).len(), 3);
+        assert_eq!(
+            mempool.get_by_sender_and_nonce(&senders[2], U256::from(2)).unwrap().hash,
+            uo_hash
+        );
+        assert!(mempool.get_by_sender_and_nonce(&senders[0], U256::from(2)).is_none());
 
         assert_eq!(mempool.remove(&uo_hash).unwrap(), true);
         assert_eq!(mempool.remove(&H256::random().into()).unwrap(), false);
+        assert!(mempool.get_by_sender_and_nonce(&senders[2], U256::from(2)).is_none());
+        assert!(mempool.get_by_sender_and_nonce(&senders[2], U256::from(1)).is_some());
 
         assert_eq!(mempool.get_all().unwrap().len(), 6);
+        assert_eq!(mempool.len().unwrap(), 6);
         assert_eq!(mempool.get_all_by_sender(&senders[0]).len(), 2);
         assert_eq!(mempool.get_all_by_sender(&senders[2]).len(), 2);
 
         assert_eq!(mempool.clear(), ());
 
         assert_eq!(mempool.get_all().unwrap().len(), 0);
+        assert!(mempool.is_empty().unwrap());
         assert_eq!(mempool.get_all_by_sender(&senders[0]).len(), 0);
+        assert!(mempool.get_by_sender_and_nonce(&senders[1], U256::from(0)).is_none());
 
         for i in 0..3 {
             uo = UserOperationSigned {
                 sender: senders[2],
                 nonce: U256::from(i),
+                max_fee_per_gas: U256::from(i + 1),
                 max_priority_fee_per_gas: U256::from(i + 1),
                 ..UserOperationSigned::random()
             };
@@ -388,7 +1147,7 @@ pub mod tests {
             );
         }
 
-        let sorted = mempool.get_sorted().unwrap();
+        let sorted = mempool.get_sorted(U256::zero()).unwrap();
         assert_eq!(sorted[0].max_priority_fee_per_gas, U256::from(3));
         assert_eq!(sorted[1].max_priority_fee_per_gas, U256::from(2));
         assert_eq!(sorted[2].max_priority_fee_per_gas, U256::from(1));
@@ -415,6 +1174,404 @@ pub mod tests {
         assert_eq!(code_hashes, code_hashes_get);
     }
 
+    pub fn sort_by_effective_gas_price_test_case(mut mempool: Mempool) {
+        let ep = Address::random();
+        let chain_id = 5_u64;
+
+        // high priority fee, but low cap
+        let capped = UserOperationSigned {
+            sender: Address::random(),
+            max_fee_per_gas: U256::from(12),
+            max_priority_fee_per_gas: U256::from(10),
+            ..UserOperationSigned::random()
+        };
+        let capped_hash = capped.hash(&ep, chain_id);
+        let uncapped = UserOperationSigned {
+            sender: Address::random(),
+            max_fee_per_gas: U256::from(100),
+            max_priority_fee_per_gas: U256::from(5),
+            ..UserOperationSigned::random()
+        };
+        let uncapped_hash = uncapped.hash(&ep, chain_id);
+
+        mempool.add(UserOperation::from_user_operation_signed(capped_hash, capped)).unwrap();
+        mempool.add(UserOperation::from_user_operation_signed(uncapped_hash, uncapped)).unwrap();
+
+        // without the base fee, the effective gas prices are the priority fees (10 and 5)
+        let sorted = mempool.get_sorted(U256::zero()).unwrap();
+        assert_eq!(
+            sorted.iter().map(|uo| uo.hash).collect::<Vec<_>>(),
+            [capped_hash, uncapped_hash]
+        );
+
+        // base fee 10 caps the first user operation at 12, the second one pays 15
+        let sorted = mempool.get_sorted(U256::from(10)).unwrap();
+        assert_eq!(
+            sorted.iter().map(|uo| uo.hash).collect::<Vec<_>>(),
+            [uncapped_hash, capped_hash]
+        );
+    }
+
+    pub fn expiry_test_case(mut mempool: Mempool) {
+        let ep = Address::random();
+        let chain_id = 5_u64;
+
+        let before = Instant::now() - Duration::from_secs(1);
+        let uo = UserOperationSigned::random();
+        let uo_hash = uo.hash(&ep, chain_id);
+        mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo.clone())).unwrap();
+
+        assert!(mempool.get_all_older_than(before).is_empty());
+        assert_eq!(mempool.get_all_older_than(Instant::now()), vec![uo_hash]);
+
+        // adding the user operation again doesn't reset its age
+        mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo)).unwrap();
+        assert_eq!(mempool.get_all_older_than(Instant::now()), vec![uo_hash]);
+
+        assert!(mempool.remove(&uo_hash).unwrap());
+        assert!(mempool.get_all_older_than(Instant::now()).is_empty());
+    }
+
+    pub fn update_user_operation_test_case(mut mempool: Mempool) {
+        let ep = Address::random();
+        let chain_id = 5_u64;
+        let (sender, paymaster) = (Address::random(), Address::random());
+
+        let uo_prev = UserOperationSigned {
+            sender,
+            nonce: U256::from(1),
+            paymaster_and_data: paymaster.as_bytes().to_vec().into(),
+            ..UserOperationSigned::random()
+        };
+        let prev_hash = uo_prev.hash(&ep, chain_id);
+        mempool.add(UserOperation::from_user_operation_signed(prev_hash, uo_prev.clone())).unwrap();
+
+        let uo = UserOperationSigned {
+            max_priority_fee_per_gas: uo_prev.max_priority_fee_per_gas * U256::from(2),
+            paymaster_and_data: Default::default(),
+            ..uo_prev.clone()
+        };
+        let uo_hash = uo.hash(&ep, chain_id);
+        assert_eq!(
+            mempool
+                .update_user_operation(
+                    &prev_hash,
+                    UserOperation::from_user_operation_signed(uo_hash, uo.clone())
+                )
+                .unwrap(),
+            uo_hash
+        );
+
+        assert_eq!(mempool.len().unwrap(), 1);
+        assert!(mempool.get(&prev_hash).unwrap().is_none());
+        assert_eq!(mempool.get_by_sender_and_nonce(&sender, U256::from(1)).unwrap().hash, uo_hash);
+        assert_eq!(
+            mempool.get_all_by_sender(&sender).iter().map(|uo| uo.hash).collect::<Vec<_>>(),
+            vec![uo_hash]
+        );
+        assert!(mempool.get_all_by_entity(&paymaster).is_empty());
+        assert_eq!(mempool.get_sender_count(&sender), 1);
+        assert_eq!(mempool.get_entity_count(&paymaster), 0);
+
+        // the replaced user operation is already gone, the user operation is just added
+        let uo_other = UserOperationSigned { nonce: U256::from(2), ..uo.clone() };
+        let other_hash = uo_other.hash(&ep, chain_id);
+        mempool
+            .update_user_operation(
+                &prev_hash,
+                UserOperation::from_user_operation_signed(other_hash, uo_other),
+            )
+            .unwrap();
+        assert_eq!(mempool.len().unwrap(), 2);
+
+        // the sender and the nonce must match
+        let uo_foreign = UserOperationSigned::random();
+        let foreign_hash = uo_foreign.hash(&ep, chain_id);
+        assert!(mempool
+            .update_user_operation(
+                &uo_hash,
+                UserOperation::from_user_operation_signed(foreign_hash, uo_foreign)
+            )
+            .is_err());
+        assert_eq!(mempool.len().unwrap(), 2);
+    }
+
+    /// Replaces the user operations of several senders from concurrent threads (with clones of the
+    /// mempool sharing its tables) while another thread checks that none of them is ever missing
+    /// or present twice
+    pub fn concurrent_update_user_operation_test_case(mut mempool: Mempool) {
+        let senders = (0..4).map(|_| Address::random()).collect::<Vec<_>>();
+        for sender in senders.iter() {
+            let uo = UserOperationSigned::random().sender(*sender);
+            mempool
+                .add(UserOperation::from_user_operation_signed(H256::random().into(), uo))
+                .unwrap();
+        }
+
+        let done = Arc::new(AtomicBool::new(false));
+        let reader = {
+            let (mempool, senders, done) = (mempool.clone(), senders.clone(), done.clone());
+            thread::spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    assert_eq!(mempool.len().unwrap(), 4);
+                    let uos = mempool.get_all().unwrap();
+                    for sender in senders.iter() {
+                        assert_eq!(uos.iter().filter(|uo| uo.sender == *sender).count(), 1);
+                    }
+                }
+            })
+        };
+        let writers = senders
+            .iter()
+            .map(|sender| {
+                let (mut mempool, sender) = (mempool.clone(), *sender);
+                thread::spawn(move || {
+                    for _ in 0..200 {
+                        let uo_prev =
+                            mempool.get_by_sender_and_nonce(&sender, U256::zero()).unwrap();
+                        let uo = UserOperationSigned {
+                            max_priority_fee_per_gas: uo_prev.max_priority_fee_per_gas + 1,
+                            ..uo_prev.user_operation.clone()
+                        };
+                        mempool
+                            .update_user_operation(
+                                &uo_prev.hash,
+                                UserOperation::from_user_operation_signed(
+                                    H256::random().into(),
+                                    uo,
+                                ),
+                            )
+                            .unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for writer in writers {
+            writer.join().unwrap();
+        }
+        done.store(true, Ordering::SeqCst);
+        reader.join().unwrap();
+
+        assert_eq!(mempool.len().unwrap(), 4);
+        assert_eq!(mempool.stats().total_ops, 4);
+        assert_eq!(mempool.stats().unique_senders, 4);
+        for sender in senders.iter() {
+            assert_eq!(mempool.get_sender_count(sender), 1);
+        }
+    }
+
+    pub fn aggregator_test_case(mut mempool: Mempool) {
+        let ep = Address::random();
+        let chain_id = 5_u64;
+        let (sender, aggregator) = (Address::random(), Address::random());
+
+        let uo_prev =
+            UserOperationSigned { sender, nonce: U256::from(1), ..UserOperationSigned::random() };
+        let prev_hash = uo_prev.hash(&ep, chain_id);
+        let mut uo = UserOperation::from_user_operation_signed(prev_hash, uo_prev.clone());
+        uo.aggregator = Some(aggregator);
+        mempool.add(uo).unwrap();
+
+        // the aggregator is stored with the user operation
+        assert_eq!(mempool.get(&prev_hash).unwrap().unwrap().aggregator, Some(aggregator));
+        assert_eq!(mempool.get_all().unwrap()[0].aggregator, Some(aggregator));
+        assert_eq!(
+            mempool.get_all_by_aggregator(&aggregator).iter().map(|uo| uo.hash).collect::<Vec<_>>(),
+            vec![prev_hash]
+        );
+        assert_eq!(mempool.get_number_by_aggregator(&aggregator), 1);
+
+        // the replacing user operation doesn't use the aggregator
+        let uo_new = UserOperationSigned {
+            max_priority_fee_per_gas: uo_prev.max_priority_fee_per_gas * U256::from(2),
+            ..uo_prev
+        };
+        let uo_hash = uo_new.hash(&ep, chain_id);
+        mempool
+            .update_user_operation(
+                &prev_hash,
+                UserOperation::from_user_operation_signed(uo_hash, uo_new.clone()),
+            )
+            .unwrap();
+        assert_eq!(mempool.get(&uo_hash).unwrap().unwrap().aggregator, None);
+        assert!(mempool.get_all_by_aggregator(&aggregator).is_empty());
+
+        let mut uo = UserOperation::from_user_operation_signed(uo_hash, uo_new);
+        uo.aggregator = Some(aggregator);
+        mempool.update_user_operation(&uo_hash, uo).unwrap();
+        assert_eq!(mempool.get_number_by_aggregator(&aggregator), 1);
+
+        // banning the aggregator removes its user operations
+        mempool.remove_by_entity(&aggregator).unwrap();
+        assert_eq!(mempool.len().unwrap(), 0);
+        assert!(mempool.get_all_by_aggregator(&aggregator).is_empty());
+    }
+
+    pub fn receipts_test_case(mut mempool: Mempool) {
+        let receipt = |uo_hash: UserOperationHash| UserOperationReceipt {
+            user_operation_hash: uo_hash,
+            sender: Address::random(),
+            nonce: U256::from(1),
+            paymaster: Some(Address::random()),
+            actual_gas_cost: U256::from(100_000),
+            actual_gas_used: U256::from(50_000),
+            success: true,
+            reason: String::new(),
+            logs: vec![],
+            tx_receipt: Default::default(),
+        };
+
+        let uo_hashes: Vec<UserOperationHash> = (0..2).map(|_| H256::random().into()).collect();
+        for uo_hash in uo_hashes.iter() {
+            mempool.add_receipt(receipt(*uo_hash)).unwrap();
+        }
+
+        let stored = mempool.get_receipt(&uo_hashes[0]).unwrap().unwrap();
+        assert_eq!(stored.user_operation_hash, uo_hashes[0]);
+        assert_eq!(stored.actual_gas_used, U256::from(50_000));
+        assert!(stored.paymaster.is_some());
+        assert!(mempool.get_receipt(&H256::random().into()).unwrap().is_none());
+
+        // receipts outlive the user operations in the mempool
+        mempool.clear();
+        assert!(mempool.get_receipt(&uo_hashes[1]).unwrap().is_some());
+
+        assert!(mempool.remove_receipt(&uo_hashes[0]).unwrap());
+        assert!(!mempool.remove_receipt(&uo_hashes[0]).unwrap());
+        assert!(mempool.get_receipt(&uo_hashes[0]).unwrap().is_none());
+
+        mempool.clear_receipts();
+        assert!(mempool.get_receipt(&uo_hashes[1]).unwrap().is_none());
+    }
+
+    pub fn capacity_tracker_test_case(mut mempool: Mempool) {
+        let ep = Address::random();
+        let chain_id = 5_u64;
+        let senders = [Address::random(), Address::random(), Address::random()];
+        let paymasters = [Address::random(), Address::random()];
+
+        let assert_counts = |mempool: &Mempool| {
+            for addr in senders.iter().chain(paymasters.iter()) {
+                assert_eq!(mempool.get_sender_count(addr), mempool.get_number_by_sender(addr));
+                assert_eq!(mempool.get_entity_count(addr), mempool.get_number_by_entity(addr));
+                assert_eq!(
+                    mempool.get_all_by_entity(addr).len(),
+                    mempool.get_number_by_entity(addr)
+                );
+            }
+
+            for paymaster in paymasters.iter() {
+                let uos = mempool.get_all_by_paymaster(paymaster);
+                assert!(uos.iter().all(|uo| uo.get_entities().2 == Some(*paymaster)));
+                assert_eq!(uos.len(), mempool.get_number_by_entity(paymaster));
+            }
+
+            // the entity graph matches the user operations in the mempool
+            for sender in senders.iter() {
+                let entities = mempool
+                    .get_all_by_sender(sender)
+                    .iter()
+                    .flat_map(|uo| {
+                        let (_, factory, paymaster) = uo.get_entities();
+                        [factory, paymaster].into_iter().flatten()
+                    })
+                    .collect::<HashSet<_>>();
+                assert_eq!(
+                    mempool.get_entities_for_sender(sender).into_iter().collect::<HashSet<_>>(),
+                    entities
+                );
+            }
+            for paymaster in paymasters.iter() {
+                let sponsored = mempool
+                    .get_all_by_entity(paymaster)
+                    .iter()
+                    .map(|uo| uo.sender)
+                    .collect::<HashSet<_>>();
+                assert_eq!(
+                    mempool.get_senders_for_entity(paymaster).into_iter().collect::<HashSet<_>>(),
+                    sponsored
+                );
+            }
+
+            let uos = mempool.get_all().unwrap();
+
+            // the pages cover the whole mempool in the order of the hashes
+            let mut hashes = uos.iter().map(|uo| uo.hash).collect::<Vec<_>>();
+            hashes.sort();
+            let mut paged = vec![];
+            loop {
+                let page = mempool.get_page(paged.len(), 7).unwrap();
+                assert!(page.len() <= 7);
+                if page.is_empty() {
+                    break;
+                }
+                paged.extend(page.into_iter().map(|uo| uo.hash));
+            }
+            assert_eq!(paged, hashes);
+
+            let stats = mempool.stats();
+            assert_eq!(stats.total_ops, uos.len());
+            assert_eq!(
+                stats.unique_senders,
+                uos.iter().map(|uo| uo.sender).collect::<HashSet<_>>().len()
+            );
+            assert_eq!(
+                stats.unique_paymasters,
+                uos.iter().filter_map(|uo| uo.get_entities().2).collect::<HashSet<_>>().len()
+            );
+        };
+
+        // counts of the user operations already in the storage
+        for uo in mempool.get_all().unwrap() {
+            assert_eq!(
+                mempool.get_sender_count(&uo.sender),
+                mempool.get_number_by_sender(&uo.sender)
+            );
+        }
+
+        let mut uo_hashes: Vec<UserOperationHash> = vec![];
+        for i in 0..100 {
+            let rand = H256::random();
+            if uo_hashes.is_empty() || rand[0] % 3 != 0 {
+                let paymaster_and_data = match rand[1] % 3 {
+                    0 => Bytes::default(),
+                    n => paymasters[n as usize - 1].as_bytes().to_vec().into(),
+                };
+                let uo = UserOperationSigned {
+                    sender: senders[rand[2] as usize % senders.len()],
+                    nonce: U256::from(i),
+                    paymaster_and_data,
+                    ..UserOperationSigned::random()
+                };
+                let uo_hash = uo.hash(&ep, chain_id);
+                mempool.add(UserOperation::from_user_operation_signed(uo_hash, uo)).unwrap();
+                uo_hashes.push(uo_hash);
+            } else {
+                let uo_hash = uo_hashes.swap_remove(rand[1] as usize % uo_hashes.len());
+                assert!(mempool.remove(&uo_hash).unwrap());
+            }
+
+            assert_counts(&mempool);
+        }
+
+        // re-adding the same user operation doesn't count it twice
+        if let Some(uo_hash) = uo_hashes.first() {
+            let uo = mempool.get(uo_hash).unwrap().unwrap();
+            mempool.add(uo).unwrap();
+            assert_counts(&mempool);
+        }
+
+        // removing the user operations of a sender also updates its paymasters
+        mempool.remove_by_entity(&senders[0]).unwrap();
+        assert_eq!(mempool.get_number_by_sender(&senders[0]), 0);
+        assert!(mempool.get_entities_for_sender(&senders[0]).is_empty());
+        assert_counts(&mempool);
+
+        mempool.clear();
+        assert_counts(&mempool);
+    }
+
     pub fn reputation_test_case(mut reputation: Reputation) {
         let mut addrs: Vec<Address> = vec![];
 
@@ -463,8 +1620,8 @@ pub mod tests {
 
         assert_eq!(reputation.update_handle_ops_reverted(&addrs[3]).unwrap(), ());
 
-        for _ in 0..250 {
-            assert_eq!(reputation.increment_seen(&addrs[3]).unwrap(), ());
+        for _ in 0..6 {
+            assert_eq!(reputation.increment_included(&addrs[3]).unwrap(), ());
         }
         assert_eq!(Status::from(reputation.get_status(&addrs[3]).unwrap()), Status::THROTTLED);
 
@@ -472,5 +1629,164 @@ pub mod tests {
             assert_eq!(reputation.increment_seen(&addrs[3]).unwrap(), ());
         }
         assert_eq!(Status::from(reputation.get_status(&addrs[3]).unwrap()), Status::BANNED);
+
+        // the harness expects the default reputation parameters
+        assert_eq!(MIN_INCLUSION_RATE_DENOMINATOR, 10);
+        assert_eq!(THROTTLING_SLACK, 10);
+        assert_eq!(BAN_SLACK, 50);
+
+        // status = THROTTLED if uo_seen > MIN_INCLUSION_RATE_DENOMINATOR * uo_included +
+        // THROTTLING_SLACK, BANNED if uo_seen > MIN_INCLUSION_RATE_DENOMINATOR * uo_included +
+        // BAN_SLACK
+        let addr = Address::random();
+        for _ in 0..10 {
+            reputation.increment_seen(&addr).unwrap();
+        }
+        assert_eq!(Status::from(reputation.get_status(&addr).unwrap()), Status::OK);
+        reputation.increment_seen(&addr).unwrap();
+        assert_eq!(Status::from(reputation.get_status(&addr).unwrap()), Status::THROTTLED);
+        for _ in 11..50 {
+            reputation.increment_seen(&addr).unwrap();
+        }
+        assert_eq!(Status::from(reputation.get_status(&addr).unwrap()), Status::THROTTLED);
+        reputation.increment_seen(&addr).unwrap();
+        assert_eq!(Status::from(reputation.get_status(&addr).unwrap()), Status::BANNED);
+        assert_eq!(
+            reputation.get(&addr).unwrap(),
+            ReputationEntry {
+                address: addr,
+                uo_seen: 51,
+                uo_included: 0,
+                status: Status::BANNED.into(),
+            }
+        );
+
+        // inclusions raise the thresholds
+        let addr_included = Address::random();
+        let entry = |uo_seen: u64, uo_included: u64| ReputationEntry {
+            address: addr_included,
+            uo_seen,
+            uo_included,
+            status: Status::OK.into(),
+        };
+        reputation.set_entities(vec![entry(20, 1)]).unwrap();
+        assert_eq!(Status::from(reputation.get_status(&addr_included).unwrap()), Status::OK);
+        reputation.set_entities(vec![entry(21, 1)]).unwrap();
+        assert_eq!(Status::from(reputation.get_status(&addr_included).unwrap()), Status::THROTTLED);
+        reputation.set_entities(vec![entry(60, 1)]).unwrap();
+        assert_eq!(Status::from(reputation.get_status(&addr_included).unwrap()), Status::THROTTLED);
+        reputation.set_entities(vec![entry(61, 1)]).unwrap();
+        assert_eq!(Status::from(reputation.get_status(&addr_included).unwrap()), Status::BANNED);
+        reputation.increment_included(&addr_included).unwrap();
+        assert_eq!(Status::from(reputation.get_status(&addr_included).unwrap()), Status::THROTTLED);
+
+        // force-set status is ignored, status is always derived from the counters
+        reputation
+            .set_entities(vec![ReputationEntry {
+                address: addr_included,
+                uo_seen: 0,
+                uo_included: 0,
+                status: Status::BANNED.into(),
+            }])
+            .unwrap();
+        assert_eq!(Status::from(reputation.get_status(&addr_included).unwrap()), Status::OK);
+
+        // whitelist and blacklist take precedence over the counters
+        assert!(reputation.add_whitelist(&addr));
+        assert_eq!(Status::from(reputation.get_status(&addr).unwrap()), Status::OK);
+        assert!(reputation.remove_whitelist(&addr));
+        assert!(reputation.add_blacklist(&addr_included));
+        assert_eq!(Status::from(reputation.get_status(&addr_included).unwrap()), Status::BANNED);
+        assert!(reputation.remove_blacklist(&addr_included));
+
+        // reverted handle ops bans the entity until enough of its user operations are included
+        let addr_reverted = Address::random();
+        reputation.update_handle_ops_reverted(&addr_reverted).unwrap();
+        assert_eq!(
+            reputation.get(&addr_reverted).unwrap(),
+            ReputationEntry {
+                address: addr_reverted,
+                uo_seen: 100,
+                uo_included: 0,
+                status: Status::BANNED.into(),
+            }
+        );
+        for _ in 0..5 {
+            reputation.increment_included(&addr_reverted).unwrap();
+        }
+        assert_eq!(Status::from(reputation.get_status(&addr_reverted).unwrap()), Status::THROTTLED);
+        for _ in 5..9 {
+            reputation.increment_included(&addr_reverted).unwrap();
+        }
+        assert_eq!(Status::from(reputation.get_status(&addr_reverted).unwrap()), Status::OK);
+
+        // hourly update decays the counters by 23/24
+        let addr_decay = Address::random();
+        reputation
+            .set_entities(vec![
+                ReputationEntry {
+                    address: addr_decay,
+                    uo_seen: 240,
+                    uo_included: 24,
+                    status: Status::OK.into(),
+                },
+                ReputationEntry {
+                    address: addr_included,
+                    uo_seen: 1,
+                    uo_included: 1,
+                    status: Status::OK.into(),
+                },
+            ])
+            .unwrap();
+        reputation.update_hourly().unwrap();
+        let decayed = reputation.get(&addr_decay).unwrap();
+        assert_eq!((decayed.uo_seen, decayed.uo_included), (230, 23));
+        let decayed = reputation.get(&addr_included).unwrap();
+        assert_eq!((decayed.uo_seen, decayed.uo_included), (0, 0));
+        let decayed = reputation.get(&addr).unwrap();
+        assert_eq!((decayed.uo_seen, decayed.uo_included), (48, 0));
+        assert_eq!(Status::from(decayed.status), Status::THROTTLED);
+
+        // every status is the one the formula gives for the counters
+        let addr_formula = Address::random();
+        for (uo_seen, uo_included) in
+            [(0, 0), (10, 0), (11, 0), (50, 0), (51, 0), (110, 10), (111, 10), (150, 10), (151, 10)]
+        {
+            reputation
+                .set_entities(vec![ReputationEntry {
+                    address: addr_formula,
+                    uo_seen,
+                    uo_included,
+                    status: Status::OK.into(),
+                }])
+                .unwrap();
+            let expected = if uo_seen > MIN_INCLUSION_RATE_DENOMINATOR * uo_included + BAN_SLACK {
+                Status::BANNED
+            } else if uo_seen > MIN_INCLUSION_RATE_DENOMINATOR * uo_included + THROTTLING_SLACK {
+                Status::THROTTLED
+            } else {
+                Status::OK
+            };
+            assert_eq!(Status::from(reputation.get_status(&addr_formula).unwrap()), expected);
+        }
+
+        // get all returns every entity that has an entry, with the derived status
+        let all = reputation.get_all().unwrap();
+        assert_eq!(all.len(), 7);
+        for addr in
+            [addrs[2], addrs[3], addr, addr_included, addr_reverted, addr_decay, addr_formula]
+        {
+            let entry = all.iter().find(|entry| entry.address == addr).unwrap();
+            assert_eq!(*entry, reputation.get(&addr).unwrap());
+        }
+        assert!(!all.iter().any(|entry| entry.address == addrs[0]));
+
+        // clear removes the entries but keeps the lists
+        reputation.clear();
+        assert!(reputation.get_all().unwrap().is_empty());
+        assert_eq!(reputation.get(&addr).unwrap(), ReputationEntry::default_with_addr(addr));
+        assert_eq!(Status::from(reputation.get_status(&addrs[3]).unwrap()), Status::OK);
+        assert_eq!(Status::from(reputation.get_status(&addrs[1]).unwrap()), Status::BANNED);
+        assert!(reputation.is_whitelist(&addrs[2]));
     }
 }
@@ -1,16 +1,20 @@
 //! User operation validator module provides all the necessary traits and types for validations.
 use crate::{
-    mempool::Mempool, InvalidMempoolUserOperationError, Reputation, SanityError, SimulationError,
+    mempool::Mempool,
+    utils::ChainPreVerificationGas,
+    validate::simulation::{code_hash_cache::CodeHashCache, context::SimulationContext},
+    InvalidMempoolUserOperationError, ReplacementPolicy, Reputation, SanityError, SimulationError,
 };
 use alloy_chains::Chain;
 use enumset::{EnumSet, EnumSetType};
-use ethers::{providers::Middleware, types::U256};
-use silius_contracts::{entry_point::SimulateValidationResult, tracer::JsTracerFrame, EntryPoint};
+use ethers::{
+    providers::Middleware,
+    types::{Address, U256},
+};
+use silius_contracts::{tracer::JsTracerFrame, EntryPoint};
 use silius_primitives::{
-    constants::validation::entities::NUMBER_OF_LEVELS,
-    reputation::StakeInfo,
     simulation::{CodeHash, StorageMap, ValidationConfig},
-    UserOperation, UserOperationHash,
+    EntryPointVersion, UserOperation, UserOperationHash,
 };
 
 pub mod sanity;
@@ -29,8 +33,11 @@ pub struct UserOperationValidationOutcome {
     pub verification_gas_limit: U256,
     // Simulation
     pub valid_after: Option<U256>,
+    pub valid_until: U256,
+    pub aggregator: Option<Address>,
     // Simulation trace
     pub code_hashes: Option<Vec<CodeHash>>,
+    pub paymaster_gas_used: Option<U256>,
     pub storage_map: StorageMap,
     // the block which the user operation is verified on
     pub verified_block: U256,
@@ -63,8 +70,11 @@ pub trait UserOperationValidator: Send + Sync {
 /// The [UserOperation] sanity check helper trait.
 pub struct SanityHelper<'a, M: Middleware + 'static> {
     entry_point: &'a EntryPoint<M>,
+    entry_point_version: EntryPointVersion,
     chain: Chain,
     val_config: ValidationConfig,
+    replacement_policy: ReplacementPolicy,
+    chain_pre_verification_gas: ChainPreVerificationGas,
 }
 
 #[async_trait::async_trait]
@@ -138,6 +148,23 @@ impl<M: Middleware> SanityCheck<M> for () {
     }
 }
 
+/// An optional check, `None` passes every user operation (the check is disabled).
+#[async_trait::async_trait]
+impl<M: Middleware, T: SanityCheck<M>> SanityCheck<M> for Option<T> {
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        reputation: &Reputation,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        match self {
+            Some(check) => check.check_user_operation(uo, mempool, reputation, helper).await,
+            None => Ok(()),
+        }
+    }
+}
+
 // These macro enable people to chain sanity check implementations:
 // `(SanityCheck1, SanityCheck2, SanityCheck3, ...).check_user_operation(uo, mempool, reputation,
 // helper)`` SanityCheck1,2,3 could be any data type which implement SanityCheck trait.
@@ -151,10 +178,20 @@ sanity_check_impls! { A B C D F G I }
 sanity_check_impls! { A B C D F G I J }
 sanity_check_impls! { A B C D F G I J K }
 sanity_check_impls! { A B C D F G I J K L }
+sanity_check_impls! { A B C D F G I J K L N }
+sanity_check_impls! { A B C D F G I J K L N O }
+sanity_check_impls! { A B C D F G I J K L N O P }
+sanity_check_impls! { A B C D F G I J K L N O P Q }
+sanity_check_impls! { A B C D F G I J K L N O P Q R }
+sanity_check_impls! { A B C D F G I J K L N O P Q R S }
+sanity_check_impls! { A B C D F G I J K L N O P Q R S T }
+sanity_check_impls! { A B C D F G I J K L N O P Q R S T U }
+sanity_check_impls! { A B C D F G I J K L N O P Q R S T U V }
 
 /// The [UserOperation] simulation check helper trait.
 pub struct SimulationHelper<'a> {
-    simulate_validation_result: &'a SimulateValidationResult,
+    context: &'a SimulationContext,
+    reputation: &'a Reputation,
     val_config: ValidationConfig,
     valid_after: Option<U256>,
 }
@@ -216,12 +253,11 @@ simulation_check_impls! { A B C D F G I J K L }
 /// The [UserOperation] simulation trace check helper trait.
 pub struct SimulationTraceHelper<'a, M: Middleware + Send + Sync + 'static> {
     entry_point: &'a EntryPoint<M>,
-    chain: Chain,
-    simulate_validation_result: &'a SimulateValidationResult,
+    context: &'a SimulationContext,
     js_trace: &'a JsTracerFrame,
     val_config: ValidationConfig,
-    stake_info: Option<[StakeInfo; NUMBER_OF_LEVELS]>,
     code_hashes: Option<Vec<CodeHash>>,
+    code_hash_cache: &'a CodeHashCache,
 }
 
 #[async_trait::async_trait]
@@ -293,6 +329,23 @@ impl<M: Middleware> SimulationTraceCheck<M> for () {
     }
 }
 
+/// An optional check, `None` passes every user operation (the check is disabled).
+#[async_trait::async_trait]
+impl<M: Middleware, T: SimulationTraceCheck<M>> SimulationTraceCheck<M> for Option<T> {
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        match self {
+            Some(check) => check.check_user_operation(uo, mempool, reputation, helper).await,
+            None => Ok(()),
+        }
+    }
+}
+
 // These macro enable people to chain simulation check implementations:
 // `(SimulationTraceCheck1, SimulationTraceCheck2, SimulationTraceCheck3,
 // ...).check_user_operation(uo, mempool, reputeation helper)`` SimulationTraceCheck1,2,3 could be
@@ -307,3 +360,6 @@ simulation_trace_check_impls! { A B C D F G I }
 simulation_trace_check_impls! { A B C D F G I J }
 simulation_trace_check_impls! { A B C D F G I J K }
 simulation_trace_check_impls! { A B C D F G I J K L }
+simulation_trace_check_impls! { A B C D F G I J K L N }
+simulation_trace_check_impls! { A B C D F G I J K L N O }
+simulation_trace_check_impls! { A B C D F G I J K L N O P }
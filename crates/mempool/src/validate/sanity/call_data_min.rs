@@ -0,0 +1,83 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{providers::Middleware, types::U256};
+use silius_primitives::{constants::validation::sanity::MIN_CALL_GAS, UserOperation};
+
+#[derive(Clone)]
+pub struct CallGasMinimum {
+    /// Absolute lower bound for call gas limit of one user operation
+    pub min_call_gas: U256,
+}
+
+impl Default for CallGasMinimum {
+    fn default() -> Self {
+        Self { min_call_gas: U256::from(MIN_CALL_GAS) }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for CallGasMinimum {
+    /// The `check_user_operation` method implementation for the `CallGasMinimum` sanity check.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to check.
+    /// `helper` - The helper struct that contains the entry point and the Ethereum client.
+    ///
+    /// # Returns
+    /// None if the sanity check passes, otherwise [SanityError].
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        _helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        if uo.call_gas_limit < self.min_call_gas {
+            return Err(SanityError::CallGasTooLow {
+                provided: uo.call_gas_limit,
+                minimum: self.min_call_gas,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{check_sanity, memory_mempool};
+    use ethers::providers::{MockProvider, Provider};
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    async fn check(call_gas_limit: u64) -> Result<(), SanityError> {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default().call_gas_limit(call_gas_limit.into()),
+        );
+        check_sanity(&CallGasMinimum::default(), &uo, &memory_mempool(), provider).await
+    }
+
+    #[tokio::test]
+    async fn call_gas_limit_zero() {
+        assert!(matches!(
+            check(0).await,
+            Err(SanityError::CallGasTooLow { provided, minimum })
+                if provided == U256::zero() && minimum == U256::from(MIN_CALL_GAS)
+        ));
+    }
+
+    #[tokio::test]
+    async fn call_gas_limit_below_minimum() {
+        assert!(matches!(check(100).await, Err(SanityError::CallGasTooLow { .. })));
+    }
+
+    #[tokio::test]
+    async fn call_gas_limit_at_minimum() {
+        assert!(check(21_100).await.is_ok());
+    }
+}
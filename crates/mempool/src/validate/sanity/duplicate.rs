@@ -0,0 +1,72 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::UserOperation;
+
+#[derive(Clone)]
+pub struct DuplicateCheck;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for DuplicateCheck {
+    /// The method implementation that rejects user operations already in the mempool.
+    ///
+    /// The hash carried by the [UserOperation](UserOperation) is used as is, so this check doesn't
+    /// make any RPC calls and should run before all other checks.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to be checked.
+    /// `mempool` - The [Mempool](Mempool) to look up the user operation hash in.
+    ///
+    /// # Returns
+    /// Nothing if the sanity check is successful, otherwise a [SanityError](SanityError)
+    /// is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        _reputation: &Reputation,
+        _helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        let existing =
+            mempool.get(&uo.hash).map_err(|e| SanityError::Other { inner: e.to_string() })?;
+
+        if existing.is_some() {
+            return Err(SanityError::AlreadyKnown { hash: uo.hash });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{check_sanity, memory_mempool};
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::{Address, H256},
+    };
+    use silius_primitives::UserOperationSigned;
+
+    #[tokio::test]
+    async fn reject_same_user_operation_twice() {
+        let mut mempool = memory_mempool();
+        let uo = UserOperation::from_user_operation_signed(
+            H256::random().into(),
+            UserOperationSigned::default().sender(Address::random()),
+        );
+
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        assert!(check_sanity(&DuplicateCheck, &uo, &mempool, provider).await.is_ok());
+        mempool.add(uo.clone()).unwrap();
+
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        assert!(matches!(
+            check_sanity(&DuplicateCheck, &uo, &mempool, provider).await,
+            Err(SanityError::AlreadyKnown { hash }) if hash == uo.hash
+        ));
+    }
+}
@@ -7,7 +7,7 @@ use crate::{
 use ethers::{providers::Middleware, types::Address};
 use silius_primitives::{
     constants::validation::{
-        entities::{FACTORY, PAYMASTER, SENDER},
+        entities::{AGGREGATOR, FACTORY, PAYMASTER, SENDER},
         reputation::THROTTLED_ENTITY_MEMPOOL_COUNT,
     },
     reputation::Status,
@@ -56,7 +56,9 @@ impl Entities {
         _reputation: &Reputation,
     ) -> Result<(), SanityError> {
         if *status == Status::THROTTLED &&
-            (mempool.get_number_by_sender(addr) + mempool.get_number_by_entity(addr)) >=
+            mempool.get_sender_count(addr) +
+                mempool.get_entity_count(addr) +
+                mempool.get_number_by_aggregator(addr) >=
                 THROTTLED_ENTITY_MEMPOOL_COUNT
         {
             return Err(
@@ -109,6 +111,13 @@ impl<M: Middleware> SanityCheck<M> for Entities {
             self.check_throttled(PAYMASTER, &paymaster, &status, helper, mempool, reputation)?;
         }
 
+        // aggregator (known only for the user operations that were already simulated)
+        if let Some(aggregator) = uo.aggregator {
+            let status = self.get_status(&aggregator, helper, reputation)?;
+            self.check_banned(AGGREGATOR, &aggregator, &status)?;
+            self.check_throttled(AGGREGATOR, &aggregator, &status, helper, mempool, reputation)?;
+        }
+
         Ok(())
     }
 }
@@ -0,0 +1,72 @@
+use super::unstaked_entities::{calculate_allowed_user_operations, get_stake};
+use crate::{
+    mempool::Mempool,
+    reputation::Reputation,
+    validate::{SanityCheck, SanityHelper},
+    ReputationError, SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::{constants::validation::entities::FACTORY, get_address, UserOperation};
+
+#[derive(Clone)]
+pub struct FactoryStake;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for FactoryStake {
+    /// The method implementation that performs the sanity check on the stake of the factory
+    /// (only if the user operation has an init code).
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        reputation: &Reputation,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        let factory = match get_address(&uo.init_code) {
+            Some(factory) => factory,
+            None => return Ok(()),
+        };
+
+        // [STO-040] - UserOperation may not use an entity address (factory/paymaster/aggregator)
+        // that is used as an "account" in another UserOperation in the mempool
+        if mempool.get_number_by_sender(&factory) > 0 {
+            return Err(SanityError::EntityRoles {
+                entity: FACTORY.into(),
+                address: uo.sender,
+                entity_other: "sender".into(),
+            });
+        }
+
+        let factory_stake = get_stake(&factory, helper).await?;
+        if reputation
+            .verify_stake(
+                FACTORY,
+                Some(factory_stake),
+                helper.val_config.min_stake,
+                helper.val_config.min_unstake_delay,
+            )
+            .is_err()
+        {
+            // [UREP-020] - for other entities
+            let entity = reputation.get(&factory)?;
+            let uos_allowed = calculate_allowed_user_operations(entity);
+            if mempool.get_number_by_entity(&factory) as u64 >= uos_allowed {
+                return Err(ReputationError::UnstakedEntity {
+                    entity: FACTORY.into(),
+                    address: factory,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
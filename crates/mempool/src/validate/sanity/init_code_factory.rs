@@ -0,0 +1,105 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::{get_address, UserOperation};
+
+#[derive(Clone)]
+pub struct InitCodeFactory;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for InitCodeFactory {
+    /// The method implementation that performs the sanity check on the factory in the init code.
+    /// Reputation of the factory is checked by the [Entities](super::entities::Entities) check.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        if uo.init_code.is_empty() {
+            return Ok(());
+        }
+
+        let factory = get_address(&uo.init_code)
+            .ok_or(SanityError::InitCodeTooShort { len: uo.init_code.len() })?;
+
+        let code = helper
+            .entry_point
+            .eth_client()
+            .get_code(factory, None)
+            .await
+            .map_err(|e| SanityError::Provider { inner: e.to_string() })?;
+
+        if code.is_empty() {
+            return Err(SanityError::FactoryHasNoCode { factory });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{check_sanity, memory_mempool};
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::{Address, Bytes},
+    };
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    async fn check(init_code: Bytes, factory_code: Bytes) -> Result<(), SanityError> {
+        let (provider, mock) = Provider::<MockProvider>::mocked();
+        mock.push(factory_code).unwrap();
+
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default().sender(Address::random()).init_code(init_code),
+        );
+
+        check_sanity(&InitCodeFactory, &uo, &memory_mempool(), provider).await
+    }
+
+    fn init_code(factory: Address) -> Bytes {
+        [factory.as_bytes(), &[0x01, 0x02, 0x03, 0x04]].concat().into()
+    }
+
+    #[tokio::test]
+    async fn deployed_account() {
+        assert!(check(Bytes::default(), Bytes::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn factory_with_code() {
+        assert!(check(init_code(Address::random()), vec![0x60, 0x80].into()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn factory_without_code() {
+        let factory = Address::random();
+        assert!(matches!(
+            check(init_code(factory), Bytes::default()).await,
+            Err(SanityError::FactoryHasNoCode { factory: f }) if f == factory
+        ));
+    }
+
+    #[tokio::test]
+    async fn init_code_too_short() {
+        assert!(matches!(
+            check(vec![0x01; 19].into(), vec![0x60, 0x80].into()).await,
+            Err(SanityError::InitCodeTooShort { len: 19 })
+        ));
+    }
+}
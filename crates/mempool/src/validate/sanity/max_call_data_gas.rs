@@ -0,0 +1,121 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{providers::Middleware, types::U256};
+use silius_primitives::{constants::validation::sanity::MAX_CALLDATA_GAS, UserOperation};
+
+/// Calldata gas cost of a zero byte
+const ZERO_BYTE_GAS: u64 = 4;
+/// Calldata gas cost of a non-zero byte
+const NON_ZERO_BYTE_GAS: u64 = 16;
+
+/// Rejects user operations whose `callData` alone costs more calldata gas than allowed, so that a
+/// single user operation can't take up most of the bundle transaction
+#[derive(Clone)]
+pub struct MaxCallDataGas {
+    /// Maximum calldata gas of the `callData` of one user operation
+    pub max_calldata_gas: U256,
+}
+
+impl Default for MaxCallDataGas {
+    fn default() -> Self {
+        Self { max_calldata_gas: MAX_CALLDATA_GAS.into() }
+    }
+}
+
+impl MaxCallDataGas {
+    pub fn new(max_calldata_gas: U256) -> Self {
+        Self { max_calldata_gas }
+    }
+
+    /// Calldata gas cost of the bytes
+    pub fn calldata_gas(data: &[u8]) -> U256 {
+        let zero_bytes = data.iter().filter(|b| **b == 0).count() as u64;
+        let non_zero_bytes = data.len() as u64 - zero_bytes;
+        U256::from(zero_bytes * ZERO_BYTE_GAS) + U256::from(non_zero_bytes * NON_ZERO_BYTE_GAS)
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for MaxCallDataGas {
+    /// The method implementation that checks the calldata gas cost of the `callData`.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        _helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        let calldata_gas = Self::calldata_gas(&uo.call_data);
+        if calldata_gas > self.max_calldata_gas {
+            return Err(SanityError::CallDataGasTooHigh {
+                calldata_gas,
+                max_calldata_gas: self.max_calldata_gas,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{check_sanity, memory_mempool};
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::Bytes,
+    };
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    const MAX_GAS: u64 = 1600;
+
+    async fn check(call_data: Vec<u8>) -> Result<(), SanityError> {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default().call_data(Bytes::from(call_data)),
+        );
+        check_sanity(&MaxCallDataGas::new(MAX_GAS.into()), &uo, &memory_mempool(), provider).await
+    }
+
+    fn is_too_high(res: Result<(), SanityError>, calldata_gas: u64) -> bool {
+        matches!(res, Err(SanityError::CallDataGasTooHigh { calldata_gas: gas, max_calldata_gas })
+            if gas == U256::from(calldata_gas) && max_calldata_gas == U256::from(MAX_GAS))
+    }
+
+    #[tokio::test]
+    async fn all_zero_calldata() {
+        assert_eq!(MaxCallDataGas::calldata_gas(&[0; 400]), U256::from(1600));
+        assert!(check(vec![0; 400]).await.is_ok());
+        assert!(is_too_high(check(vec![0; 401]).await, 1604));
+    }
+
+    #[tokio::test]
+    async fn all_non_zero_calldata() {
+        assert_eq!(MaxCallDataGas::calldata_gas(&[0xff; 100]), U256::from(1600));
+        assert!(check(vec![0xff; 100]).await.is_ok());
+        assert!(is_too_high(check(vec![0xff; 101]).await, 1616));
+    }
+
+    #[tokio::test]
+    async fn mixed_calldata() {
+        // 50 non-zero bytes (800 gas) and 200 zero bytes (800 gas)
+        let call_data = [[0x01, 0, 0, 0, 0]; 50].concat();
+        assert_eq!(MaxCallDataGas::calldata_gas(&call_data), U256::from(1600));
+        assert!(check(call_data.clone()).await.is_ok());
+
+        let call_data = [call_data, vec![0x01]].concat();
+        assert!(is_too_high(check(call_data).await, 1616));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::{
+    constants::validation::sanity::{MAX_CALL_DATA_SIZE, MAX_INIT_CODE_SIZE},
+    UserOperation,
+};
+
+/// Rejects user operations with an oversized `callData` or `initCode`, which waste bandwidth and
+/// slow down the simulation regardless of the gas they pay for
+#[derive(Clone)]
+pub struct MaxCallDataSize {
+    /// Maximum size of the `callData` in bytes
+    pub max_call_data_size: usize,
+    /// Maximum size of the `initCode` in bytes
+    pub max_init_code_size: usize,
+}
+
+impl Default for MaxCallDataSize {
+    fn default() -> Self {
+        Self { max_call_data_size: MAX_CALL_DATA_SIZE, max_init_code_size: MAX_INIT_CODE_SIZE }
+    }
+}
+
+impl MaxCallDataSize {
+    pub fn new(max_call_data_size: usize, max_init_code_size: usize) -> Self {
+        Self { max_call_data_size, max_init_code_size }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for MaxCallDataSize {
+    /// The method implementation that checks the sizes of the `callData` and the `initCode`.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        _helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        if uo.call_data.len() > self.max_call_data_size {
+            return Err(SanityError::CallDataTooLarge {
+                actual: uo.call_data.len(),
+                max: self.max_call_data_size,
+            });
+        }
+
+        if uo.init_code.len() > self.max_init_code_size {
+            return Err(SanityError::InitCodeTooLarge {
+                actual: uo.init_code.len(),
+                max: self.max_init_code_size,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{check_sanity, memory_mempool};
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::Bytes,
+    };
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    async fn check(call_data: Vec<u8>, init_code: Vec<u8>) -> Result<(), SanityError> {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default()
+                .call_data(Bytes::from(call_data))
+                .init_code(Bytes::from(init_code)),
+        );
+        check_sanity(&MaxCallDataSize::default(), &uo, &memory_mempool(), provider).await
+    }
+
+    #[tokio::test]
+    async fn within_limits() {
+        assert!(check(vec![], vec![]).await.is_ok());
+        assert!(check(vec![1; MAX_CALL_DATA_SIZE], vec![1; MAX_INIT_CODE_SIZE]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn call_data_too_large() {
+        assert!(matches!(
+            check(vec![1; MAX_CALL_DATA_SIZE + 1], vec![]).await,
+            Err(SanityError::CallDataTooLarge { actual, max })
+                if actual == MAX_CALL_DATA_SIZE + 1 && max == MAX_CALL_DATA_SIZE
+        ));
+    }
+
+    #[tokio::test]
+    async fn init_code_too_large() {
+        assert!(matches!(
+            check(vec![], vec![1; MAX_INIT_CODE_SIZE + 1]).await,
+            Err(SanityError::InitCodeTooLarge { actual, max })
+                if actual == MAX_INIT_CODE_SIZE + 1 && max == MAX_INIT_CODE_SIZE
+        ));
+    }
+}
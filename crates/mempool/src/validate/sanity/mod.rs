@@ -1,9 +1,22 @@
 //! Sanity module performs call gas limit, verification gas limit, max priority fee, paymaster
-//! verification, sender vericiation, and UserOperation type checks
+//! verification, sender vericiation, nonce gap, signature format, and UserOperation type checks
+pub mod call_data_min;
 pub mod call_gas;
+pub mod duplicate;
 pub mod entities;
+pub mod factory_stake;
+pub mod init_code_factory;
+pub mod max_call_data_gas;
+pub mod max_call_data_size;
 pub mod max_fee;
+pub mod nonce_gap;
 pub mod paymaster;
+pub mod paymaster_balance;
+pub mod paymaster_postop;
+pub mod paymaster_stake;
 pub mod sender;
+pub mod sender_balance;
+pub mod sender_stake;
+pub mod signature;
 pub mod unstaked_entities;
 pub mod verification_gas;
@@ -0,0 +1,95 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{providers::Middleware, types::U256};
+use silius_primitives::{constants::validation::sanity::MAX_NONCE_GAP, UserOperation};
+
+#[derive(Clone)]
+pub struct NonceGap {
+    /// Max number of nonces a user operation may be ahead of the on-chain nonce
+    pub max_nonce_gap: u64,
+}
+
+impl Default for NonceGap {
+    fn default() -> Self {
+        Self { max_nonce_gap: MAX_NONCE_GAP }
+    }
+}
+
+impl NonceGap {
+    /// The helper method that compares the sequence of the nonce with the on-chain one.
+    ///
+    /// The upper 192 bits of the nonce are the key and the lower 64 bits the sequence, and the
+    /// entry point returns the on-chain nonce of the same key.
+    ///
+    /// # Arguments
+    /// `nonce` - The nonce of the user operation
+    /// `on_chain_nonce` - The nonce of the sender in the entry point (for the same key)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SanityError] error.
+    fn check_nonce(&self, nonce: U256, on_chain_nonce: U256) -> Result<(), SanityError> {
+        if nonce.low_u64() > on_chain_nonce.low_u64().saturating_add(self.max_nonce_gap) {
+            return Err(SanityError::NonceTooHigh { nonce, on_chain_nonce });
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for NonceGap {
+    /// The method implementation that rejects the user operations too far ahead of the on-chain
+    /// nonce of the sender (they can't be included before the gap is filled and only take up
+    /// the capacity of the mempool).
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to check.
+    /// `helper` - The helper struct that contains the entry point and the Ethereum client.
+    ///
+    /// # Returns
+    /// None if the sanity check passes, otherwise [SanityError].
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        let on_chain_nonce = helper.entry_point.get_nonce(&uo.sender, uo.nonce >> 64).await?;
+        self.check_nonce(uo.nonce, on_chain_nonce)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nonce_within_gap() {
+        let check = NonceGap::default();
+        assert!(check.check_nonce(U256::zero(), U256::zero()).is_ok());
+        assert!(check.check_nonce(U256::from(MAX_NONCE_GAP), U256::zero()).is_ok());
+        assert!(check.check_nonce(U256::from(105), U256::from(100)).is_ok());
+    }
+
+    #[test]
+    fn nonce_too_high() {
+        let check = NonceGap::default();
+        assert!(matches!(
+            check.check_nonce(U256::from(100), U256::zero()),
+            Err(SanityError::NonceTooHigh { nonce, on_chain_nonce })
+                if nonce == U256::from(100) && on_chain_nonce == U256::zero()
+        ));
+    }
+
+    #[test]
+    fn nonce_with_key() {
+        let check = NonceGap::default();
+        let key = U256::from(7) << 64;
+        assert!(check.check_nonce(key + 3, key + 1).is_ok());
+        assert!(check.check_nonce(key + 100, key).is_err());
+    }
+}
@@ -4,7 +4,7 @@ use crate::{
     Reputation, SanityError,
 };
 use ethers::{providers::Middleware, types::U256};
-use silius_primitives::{get_address, UserOperation};
+use silius_primitives::{get_address, EntryPointVersion, UserOperation};
 
 #[derive(Clone)]
 pub struct Paymaster;
@@ -28,6 +28,15 @@ impl<M: Middleware> SanityCheck<M> for Paymaster {
         helper: &SanityHelper<M>,
     ) -> Result<(), SanityError> {
         if !uo.paymaster_and_data.is_empty() {
+            // entry point v0.7 reads the paymaster gas limits from the paymaster and data
+            if helper.entry_point_version == EntryPointVersion::V0_7 &&
+                uo.paymaster_gas_limits().is_none()
+            {
+                return Err(SanityError::Paymaster {
+                    inner: "Paymaster and data is missing the paymaster gas limits".into(),
+                });
+            }
+
             if let Some(addr) = get_address(&uo.paymaster_and_data) {
                 let code = helper
                     .entry_point
@@ -0,0 +1,128 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{providers::Middleware, types::U256};
+use silius_primitives::{get_address, UserOperation};
+
+/// Requires the paymaster deposit to cover the worst-case gas cost of the user operation.
+///
+/// Stricter than [Paymaster](super::paymaster::Paymaster), which only requires a non-zero
+/// deposit. When both checks are enabled, the first failing one rejects the user operation.
+#[derive(Clone)]
+pub struct PaymasterBalance {
+    /// Multiplier of the worst-case cost the deposit must cover (e.g., 2 for a 2x buffer)
+    pub min_deposit_multiplier: u32,
+}
+
+impl Default for PaymasterBalance {
+    fn default() -> Self {
+        Self { min_deposit_multiplier: 1 }
+    }
+}
+
+impl PaymasterBalance {
+    /// Deposit the paymaster must have in the entry point to sponsor the user operation
+    pub fn required_deposit(&self, uo: &UserOperation) -> U256 {
+        uo.max_fee_per_gas
+            .saturating_mul(uo.call_gas_limit.saturating_add(uo.verification_gas_limit))
+            .saturating_mul(U256::from(self.min_deposit_multiplier))
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for PaymasterBalance {
+    /// The method implementation that checks the paymaster deposit.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        let Some(paymaster) = get_address(&uo.paymaster_and_data) else {
+            return Ok(());
+        };
+
+        let deposit = helper.entry_point.balance_of(&paymaster).await?;
+        let required = self.required_deposit(uo);
+
+        if deposit < required {
+            return Err(SanityError::PaymasterDepositTooLow { paymaster, deposit, required });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{check_sanity, memory_mempool};
+    use ethers::{
+        abi::{encode, Token},
+        providers::{MockProvider, Provider},
+        types::{Address, Bytes},
+    };
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    async fn check(check: PaymasterBalance, deposit: u64) -> Result<(), SanityError> {
+        let (provider, mock) = Provider::<MockProvider>::mocked();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[Token::Uint(U256::from(deposit))]))).unwrap();
+
+        // worst-case cost: 10 * (100_000 + 100_000) = 2_000_000
+        let uo = UserOperationSigned::default()
+            .paymaster_and_data(Address::random().as_bytes().to_vec().into())
+            .max_fee_per_gas(10.into())
+            .call_gas_limit(100_000.into())
+            .verification_gas_limit(100_000.into());
+        let uo = UserOperation::from_user_operation_signed(UserOperationHash::default(), uo);
+
+        check_sanity(&check, &uo, &memory_mempool(), provider).await
+    }
+
+    #[tokio::test]
+    async fn deposit_covers_worst_case_cost() {
+        assert!(check(PaymasterBalance::default(), 2_000_000).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn deposit_half_of_required() {
+        assert!(matches!(
+            check(PaymasterBalance::default(), 1_000_000).await,
+            Err(SanityError::PaymasterDepositTooLow { deposit, required, .. })
+                if deposit == U256::from(1_000_000) && required == U256::from(2_000_000)
+        ));
+    }
+
+    #[tokio::test]
+    async fn deposit_below_multiplied_cost() {
+        assert!(matches!(
+            check(PaymasterBalance { min_deposit_multiplier: 2 }, 2_000_000).await,
+            Err(SanityError::PaymasterDepositTooLow { required, .. })
+                if required == U256::from(4_000_000)
+        ));
+    }
+
+    #[tokio::test]
+    async fn disabled_check_skips_deposit() {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let uo = UserOperationSigned::default()
+            .paymaster_and_data(Address::random().as_bytes().to_vec().into())
+            .max_fee_per_gas(10.into());
+        let uo = UserOperation::from_user_operation_signed(UserOperationHash::default(), uo);
+
+        assert!(check_sanity(&None::<PaymasterBalance>, &uo, &memory_mempool(), provider)
+            .await
+            .is_ok());
+    }
+}
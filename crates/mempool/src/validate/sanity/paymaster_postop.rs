@@ -0,0 +1,116 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{providers::Middleware, types::U256};
+use silius_primitives::{
+    constants::validation::sanity::PAYMASTER_VERIFICATION_GAS_OVERHEAD, UserOperation,
+};
+
+/// Entry point v0.6 has no separate paymaster gas limits, the paymaster validation and postOp are
+/// paid from the verification gas limit of the user operation
+#[derive(Clone, Default)]
+pub struct PaymasterPostOp {
+    /// Gas the verification gas limit has to reserve for the paymaster postOp
+    pub min_post_op_gas: U256,
+}
+
+impl PaymasterPostOp {
+    pub fn new(min_post_op_gas: U256) -> Self {
+        Self { min_post_op_gas }
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for PaymasterPostOp {
+    /// The method implementation that checks whether the verification gas limit reserves enough
+    /// gas for the paymaster postOp.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        _helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        if uo.paymaster_and_data.is_empty() {
+            return Ok(());
+        }
+
+        let required =
+            U256::from(PAYMASTER_VERIFICATION_GAS_OVERHEAD).saturating_add(self.min_post_op_gas);
+        if uo.verification_gas_limit < required {
+            return Err(SanityError::InsufficientPostOpGasReserve {
+                provided: uo.verification_gas_limit,
+                required,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{check_sanity, memory_mempool};
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::{Address, Bytes},
+    };
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    const MIN_POST_OP_GAS: u64 = 20_000;
+    const THRESHOLD: u64 = PAYMASTER_VERIFICATION_GAS_OVERHEAD + MIN_POST_OP_GAS;
+
+    async fn check(
+        verification_gas_limit: u64,
+        paymaster_and_data: Bytes,
+    ) -> Result<(), SanityError> {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default()
+                .verification_gas_limit(verification_gas_limit.into())
+                .paymaster_and_data(paymaster_and_data),
+        );
+        let check = PaymasterPostOp::new(MIN_POST_OP_GAS.into());
+        check_sanity(&check, &uo, &memory_mempool(), provider).await
+    }
+
+    fn paymaster_and_data() -> Bytes {
+        Address::random().as_bytes().to_vec().into()
+    }
+
+    #[tokio::test]
+    async fn verification_gas_at_threshold() {
+        assert!(check(THRESHOLD, paymaster_and_data()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn verification_gas_below_threshold() {
+        assert!(matches!(
+            check(THRESHOLD - 1, paymaster_and_data()).await,
+            Err(SanityError::InsufficientPostOpGasReserve { provided, required })
+                if provided == U256::from(THRESHOLD - 1) && required == U256::from(THRESHOLD)
+        ));
+    }
+
+    #[tokio::test]
+    async fn verification_gas_above_threshold() {
+        assert!(check(THRESHOLD + 1, paymaster_and_data()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn no_paymaster() {
+        assert!(check(0, Bytes::default()).await.is_ok());
+    }
+}
@@ -0,0 +1,72 @@
+use super::unstaked_entities::{calculate_allowed_user_operations, get_stake};
+use crate::{
+    mempool::Mempool,
+    reputation::Reputation,
+    validate::{SanityCheck, SanityHelper},
+    ReputationError, SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::{constants::validation::entities::PAYMASTER, get_address, UserOperation};
+
+#[derive(Clone)]
+pub struct PaymasterStake;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for PaymasterStake {
+    /// The method implementation that performs the sanity check on the stake of the paymaster
+    /// (only if the user operation has a paymaster).
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        reputation: &Reputation,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        let paymaster = match get_address(&uo.paymaster_and_data) {
+            Some(paymaster) => paymaster,
+            None => return Ok(()),
+        };
+
+        // [STO-040] - UserOperation may not use an entity address (factory/paymaster/aggregator)
+        // that is used as an "account" in another UserOperation in the mempool
+        if mempool.get_number_by_sender(&paymaster) > 0 {
+            return Err(SanityError::EntityRoles {
+                entity: PAYMASTER.into(),
+                address: uo.sender,
+                entity_other: "sender".into(),
+            });
+        }
+
+        let paymaster_stake = get_stake(&paymaster, helper).await?;
+        if reputation
+            .verify_stake(
+                PAYMASTER,
+                Some(paymaster_stake),
+                helper.val_config.min_stake,
+                helper.val_config.min_unstake_delay,
+            )
+            .is_err()
+        {
+            // [UREP-020] - for other entities
+            let entity = reputation.get(&paymaster)?;
+            let uos_allowed = calculate_allowed_user_operations(entity);
+            if mempool.get_number_by_entity(&paymaster) as u64 >= uos_allowed {
+                return Err(ReputationError::UnstakedEntity {
+                    entity: PAYMASTER.into(),
+                    address: paymaster,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,11 +1,10 @@
 use crate::{
     mempool::Mempool,
-    utils::calculate_valid_gas,
     validate::{SanityCheck, SanityHelper},
     Reputation, SanityError,
 };
 use ethers::providers::Middleware;
-use silius_primitives::{constants::mempool::GAS_INCREASE_PERC, UserOperation};
+use silius_primitives::UserOperation;
 
 #[derive(Clone)]
 pub struct Sender;
@@ -54,29 +53,11 @@ impl<M: Middleware> SanityCheck<M> for Sender {
         let mut uo_prev: Option<UserOperation> = None;
 
         if !helper.val_config.ignore_prev {
-            uo_prev = mempool
-                .get_all_by_sender(&uo.sender)
-                .iter()
-                .find(|uo_prev| uo_prev.nonce == uo.nonce)
-                .cloned();
+            uo_prev = mempool.get_by_sender_and_nonce(&uo.sender, uo.nonce);
         }
 
         if let Some(uo_prev) = uo_prev {
-            if uo.max_fee_per_gas <
-                calculate_valid_gas(uo_prev.max_fee_per_gas, GAS_INCREASE_PERC.into()) ||
-                uo.max_priority_fee_per_gas <
-                    calculate_valid_gas(
-                        uo_prev.max_priority_fee_per_gas,
-                        GAS_INCREASE_PERC.into(),
-                    )
-            {
-                return Err(SanityError::Sender {
-                    inner: format!(
-                        "{0} couldn't replace user operation (gas increase too low)",
-                        uo.sender
-                    ),
-                });
-            }
+            helper.replacement_policy.check_replacement(&uo_prev, uo)?;
         }
 
         Ok(())
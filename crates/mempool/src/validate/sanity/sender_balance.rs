@@ -0,0 +1,168 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{providers::Middleware, types::U256};
+use silius_primitives::UserOperation;
+use tracing::warn;
+
+/// Requires the entry point deposit of the sender of a user operation without a paymaster to cover
+/// the prefund.
+///
+/// The sender's balance is only used as a fallback if the deposit can't be read from the entry
+/// point.
+#[derive(Clone, Default)]
+pub struct SenderBalance;
+
+impl SenderBalance {
+    /// Prefund the entry point requires for the user operation
+    pub fn required_prefund(uo: &UserOperation) -> U256 {
+        uo.max_fee_per_gas.saturating_mul(
+            uo.call_gas_limit
+                .saturating_add(uo.verification_gas_limit)
+                .saturating_add(uo.pre_verification_gas),
+        )
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for SenderBalance {
+    /// The method implementation that checks the sender deposit and balance.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        if !uo.paymaster_and_data.is_empty() {
+            return Ok(());
+        }
+
+        let required = Self::required_prefund(uo);
+
+        let deposit = match helper.entry_point.get_deposit_info(&uo.sender).await {
+            Ok(info) => U256::from(info.deposit),
+            Err(e) => {
+                warn!(
+                    "Failed to get deposit of sender {:?}, checking its balance: {e:?}",
+                    uo.sender
+                );
+                helper
+                    .entry_point
+                    .eth_client()
+                    .get_balance(uo.sender, None)
+                    .await
+                    .map_err(|e| SanityError::Provider { inner: e.to_string() })?
+            }
+        };
+
+        if deposit < required {
+            return Err(SanityError::SenderInsufficientDeposit {
+                sender: uo.sender,
+                deposit,
+                required,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::{check_sanity, memory_mempool};
+    use ethers::{
+        abi::{encode, Token},
+        providers::{MockProvider, Provider},
+        types::{Address, Bytes},
+    };
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    // prefund: 10 * (50_000 + 100_000 + 50_000) = 2_000_000
+    const PREFUND: u64 = 2_000_000;
+
+    /// Checks the user operation against the deposit info returned by the entry point (`None`
+    /// if the call fails) and the sender's balance
+    async fn check(deposit: Option<u64>, balance: u64) -> Result<(), SanityError> {
+        let (provider, mock) = Provider::<MockProvider>::mocked();
+        // responses are returned in reverse order
+        mock.push(U256::from(balance)).unwrap();
+        let deposit_info = match deposit {
+            Some(deposit) => Bytes::from(encode(&[
+                Token::Uint(U256::from(deposit)),
+                Token::Bool(false),
+                Token::Uint(U256::zero()),
+                Token::Uint(U256::zero()),
+                Token::Uint(U256::zero()),
+            ])),
+            // can't be decoded
+            None => Bytes::default(),
+        };
+        mock.push::<Bytes, _>(deposit_info).unwrap();
+
+        let uo = UserOperationSigned::default()
+            .sender(Address::random())
+            .max_fee_per_gas(10.into())
+            .call_gas_limit(50_000.into())
+            .verification_gas_limit(100_000.into())
+            .pre_verification_gas(50_000.into());
+        let uo = UserOperation::from_user_operation_signed(UserOperationHash::default(), uo);
+
+        check_sanity(&SenderBalance, &uo, &memory_mempool(), provider).await
+    }
+
+    #[tokio::test]
+    async fn zero_deposit() {
+        assert!(matches!(
+            check(Some(0), PREFUND).await,
+            Err(SanityError::SenderInsufficientDeposit { deposit, required, .. })
+                if deposit.is_zero() && required == U256::from(PREFUND)
+        ));
+    }
+
+    #[tokio::test]
+    async fn exact_deposit() {
+        assert!(check(Some(PREFUND), 0).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn deposit_one_wei_below() {
+        assert!(matches!(
+            check(Some(PREFUND - 1), PREFUND).await,
+            Err(SanityError::SenderInsufficientDeposit { deposit, .. })
+                if deposit == U256::from(PREFUND - 1)
+        ));
+    }
+
+    #[tokio::test]
+    async fn balance_fallback() {
+        assert!(check(None, PREFUND).await.is_ok());
+        assert!(matches!(
+            check(None, PREFUND - 1).await,
+            Err(SanityError::SenderInsufficientDeposit { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn disabled_check_skips_deposit() {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let uo =
+            UserOperationSigned::default().sender(Address::random()).max_fee_per_gas(10.into());
+        let uo = UserOperation::from_user_operation_signed(UserOperationHash::default(), uo);
+
+        assert!(check_sanity(&None::<SenderBalance>, &uo, &memory_mempool(), provider)
+            .await
+            .is_ok());
+    }
+}
@@ -0,0 +1,67 @@
+use super::unstaked_entities::get_stake;
+use crate::{
+    mempool::Mempool,
+    reputation::Reputation,
+    validate::{SanityCheck, SanityHelper},
+    ReputationError, SanityError,
+};
+use ethers::providers::Middleware;
+use silius_primitives::{
+    constants::validation::{entities::SENDER, reputation::SAME_SENDER_MEMPOOL_COUNT},
+    UserOperation,
+};
+
+#[derive(Clone)]
+pub struct SenderStake;
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for SenderStake {
+    /// The method implementation that performs the sanity check on the stake of the sender.
+    ///
+    /// # Arguments
+    /// `uo` - The user operation to be checked.
+    /// `helper` - The [sanity check helper](SanityHelper) that contains the necessary data to
+    /// perform the sanity check.
+    ///
+    /// # Returns
+    /// None if the sanity check is successful, otherwise a [SanityError] is returned.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        reputation: &Reputation,
+        helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        // [STO-040] - UserOperation may not use an entity address (factory/paymaster/aggregator)
+        // that is used as an "account" in another UserOperation in the mempool
+        if mempool.get_number_by_entity(&uo.sender) > 0 {
+            return Err(SanityError::EntityRoles {
+                entity: SENDER.into(),
+                address: uo.sender,
+                entity_other: "different".into(),
+            });
+        }
+
+        // [UREP-010] - UserOperation with unstaked sender are only allowed up to
+        // SAME_SENDER_MEMPOOL_COUNT times in the mempool
+        let sender_stake = get_stake(&uo.sender, helper).await?;
+        if reputation
+            .verify_stake(
+                SENDER,
+                Some(sender_stake),
+                helper.val_config.min_stake,
+                helper.val_config.min_unstake_delay,
+            )
+            .is_err() &&
+            mempool.get_number_by_sender(&uo.sender) >= SAME_SENDER_MEMPOOL_COUNT
+        {
+            return Err(ReputationError::UnstakedEntity {
+                entity: SENDER.into(),
+                address: uo.sender,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
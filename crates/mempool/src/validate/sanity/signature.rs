@@ -0,0 +1,125 @@
+use crate::{
+    mempool::Mempool,
+    validate::{SanityCheck, SanityHelper},
+    Reputation, SanityError,
+};
+use ethers::{providers::Middleware, types::Signature};
+use silius_primitives::UserOperation;
+
+/// Length of an ECDSA signature (r, s, v)
+const ECDSA_SIGNATURE_LENGTH: usize = 65;
+
+#[derive(Clone)]
+pub struct SignatureFormat;
+
+impl SignatureFormat {
+    /// The helper method that checks the format of the signature.
+    ///
+    /// The account decides how the signature is verified, so only the signatures that can't pass
+    /// any verification are rejected: empty signatures and malformed ECDSA signatures of deployed
+    /// accounts (the signature of an undeployed account is only checked during the simulation).
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SanityError] error.
+    fn check_signature(uo: &UserOperation) -> Result<(), SanityError> {
+        if uo.signature.is_empty() {
+            return Err(SanityError::Signature { inner: "signature is empty".into() });
+        }
+
+        let is_ecdsa =
+            uo.signature.len() == ECDSA_SIGNATURE_LENGTH && matches!(uo.signature[64], 27 | 28);
+        if !uo.init_code.is_empty() || !is_ecdsa {
+            return Ok(());
+        }
+
+        Signature::try_from(uo.signature.as_ref())
+            .and_then(|sig| sig.recover(uo.hash.0))
+            .map(|_| ())
+            .map_err(|e| SanityError::Signature {
+                inner: format!("malformed ECDSA signature: {e}"),
+            })
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SanityCheck<M> for SignatureFormat {
+    /// The method implementation that rejects the signatures that can't be valid before the
+    /// simulation.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `helper` - The [sanity check helper](SanityHelper)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SanityError] error.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        _helper: &SanityHelper<M>,
+    ) -> Result<(), SanityError> {
+        Self::check_signature(uo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        signers::LocalWallet,
+        types::{Bytes, H256},
+    };
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    fn user_operation(signature: Bytes, init_code: Bytes) -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::from(H256::random()),
+            UserOperationSigned::default().signature(signature).init_code(init_code),
+        )
+    }
+
+    #[test]
+    fn empty_signature() {
+        let uo = user_operation(Bytes::default(), Bytes::default());
+        assert!(matches!(
+            SignatureFormat::check_signature(&uo),
+            Err(SanityError::Signature { .. })
+        ));
+    }
+
+    #[test]
+    fn ecdsa_signature() {
+        let mut uo = user_operation(Bytes::default(), Bytes::default());
+        let wallet = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+            .parse::<LocalWallet>()
+            .unwrap();
+        uo.signature = wallet.sign_hash(uo.hash.0).unwrap().to_vec().into();
+        assert!(SignatureFormat::check_signature(&uo).is_ok());
+    }
+
+    #[test]
+    fn malformed_ecdsa_signature() {
+        let mut signature = vec![0_u8; ECDSA_SIGNATURE_LENGTH];
+        signature[64] = 27;
+
+        let uo = user_operation(signature.clone().into(), Bytes::default());
+        assert!(matches!(
+            SignatureFormat::check_signature(&uo),
+            Err(SanityError::Signature { .. })
+        ));
+
+        // checked during the simulation
+        let uo = user_operation(signature.into(), vec![1_u8; 24].into());
+        assert!(SignatureFormat::check_signature(&uo).is_ok());
+    }
+
+    #[test]
+    fn other_signature() {
+        let uo = user_operation(vec![1_u8; 96].into(), Bytes::default());
+        assert!(SignatureFormat::check_signature(&uo).is_ok());
+    }
+}
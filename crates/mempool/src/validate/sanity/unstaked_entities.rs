@@ -1,67 +1,56 @@
+use super::{
+    factory_stake::FactoryStake, paymaster_stake::PaymasterStake, sender_stake::SenderStake,
+};
 use crate::{
     mempool::Mempool,
     reputation::Reputation,
     validate::{SanityCheck, SanityHelper},
-    ReputationError, SanityError,
+    SanityError,
 };
 use ethers::{
     providers::Middleware,
     types::{Address, U256},
 };
 use silius_primitives::{
-    constants::validation::{
-        entities::{FACTORY, PAYMASTER, SENDER},
-        reputation::{
-            INCLUSION_RATE_FACTOR, SAME_SENDER_MEMPOOL_COUNT, SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT,
-        },
+    constants::validation::reputation::{
+        INCLUSION_RATE_FACTOR, SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT,
     },
     reputation::{ReputationEntry, StakeInfo},
     UserOperation,
 };
 use std::cmp;
 
-#[derive(Clone)]
-pub struct UnstakedEntities;
+/// Gets the deposit info for entity.
+pub(crate) async fn get_stake<M: Middleware>(
+    addr: &Address,
+    helper: &SanityHelper<'_, M>,
+) -> Result<StakeInfo, SanityError> {
+    let info = helper.entry_point.get_deposit_info(addr).await?;
 
-impl UnstakedEntities {
-    /// Gets the deposit info for entity.
-    async fn get_stake<'a, M: Middleware>(
-        &self,
-        addr: &Address,
-        helper: &SanityHelper<'a, M>,
-    ) -> Result<StakeInfo, SanityError> {
-        let info = helper.entry_point.get_deposit_info(addr).await?;
-
-        Ok(StakeInfo {
-            address: *addr,
-            stake: U256::from(info.stake),
-            unstake_delay: U256::from(info.unstake_delay_sec),
-        })
-    }
-
-    /// Gets the reputation entry for entity.
-    fn get_entity<M: Middleware>(
-        &self,
-        addr: &Address,
-        _helper: &SanityHelper<M>,
-        reputation: &Reputation,
-    ) -> Result<ReputationEntry, SanityError> {
-        reputation.get(addr).map_err(|e| e.into())
-    }
+    Ok(StakeInfo {
+        address: *addr,
+        stake: U256::from(info.stake),
+        unstake_delay: U256::from(info.unstake_delay_sec),
+    })
+}
 
-    /// Calculates allowed number of user operations
-    fn calculate_allowed_user_operations(entity: ReputationEntry) -> u64 {
-        if entity.uo_seen == 0 {
-            SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT as u64
-        } else {
-            SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT as u64 +
-                ((entity.uo_included as f64 / entity.uo_seen as f64) * INCLUSION_RATE_FACTOR as f64)
-                    as u64 +
-                cmp::min(entity.uo_included, 10000)
-        }
+/// Calculates allowed number of user operations
+pub(crate) fn calculate_allowed_user_operations(entity: ReputationEntry) -> u64 {
+    if entity.uo_seen == 0 {
+        SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT as u64
+    } else {
+        SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT as u64 +
+            ((entity.uo_included as f64 / entity.uo_seen as f64) * INCLUSION_RATE_FACTOR as f64)
+                as u64 +
+            cmp::min(entity.uo_included, 10000)
     }
 }
 
+/// Stake checks of all entities: [SenderStake], [FactoryStake] and [PaymasterStake] (in this
+/// order)
+#[derive(Clone)]
+pub struct UnstakedEntities;
+
 #[async_trait::async_trait]
 impl<M: Middleware> SanityCheck<M> for UnstakedEntities {
     /// The method implementation that performs the sanity check for the unstaked entities.
@@ -80,114 +69,134 @@ impl<M: Middleware> SanityCheck<M> for UnstakedEntities {
         reputation: &Reputation,
         helper: &SanityHelper<M>,
     ) -> Result<(), SanityError> {
-        let (sender, factory, paymaster) = uo.get_entities();
-
         // [SREP-010] - the "canonical mempool" defines a staked entity if it has MIN_STAKE_VALUE
         // and unstake delay of MIN_UNSTAKE_DELAY
+        (SenderStake, FactoryStake, PaymasterStake)
+            .check_user_operation(uo, mempool, reputation, helper)
+            .await
+    }
+}
 
-        // sender
-        // [STO-040] - UserOperation may not use an entity address (factory/paymaster/aggregator)
-        // that is used as an "account" in another UserOperation in the mempool
-        if mempool.get_number_by_entity(&sender) > 0 {
-            return Err(SanityError::EntityRoles {
-                entity: SENDER.into(),
-                address: sender,
-                entity_other: "different".into(),
-            });
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        validate::utils::tests::{check_sanity, memory_mempool},
+        ReputationError,
+    };
+    use ethers::{
+        abi::{encode, Token},
+        providers::{MockProvider, Provider},
+        types::{Bytes, H256},
+    };
+    use silius_primitives::{
+        constants::validation::{
+            entities::{FACTORY, PAYMASTER, SENDER},
+            reputation::SAME_SENDER_MEMPOOL_COUNT,
+        },
+        UserOperationSigned,
+    };
+
+    fn user_operation(uo: UserOperationSigned) -> UserOperation {
+        UserOperation::from_user_operation_signed(H256::random().into(), uo)
+    }
+
+    /// Runs the check against unstaked entities
+    async fn check<C: SanityCheck<Provider<MockProvider>>>(
+        check: C,
+        uo: &UserOperation,
+        mempool: &Mempool,
+    ) -> Result<(), SanityError> {
+        let (provider, mock) = Provider::<MockProvider>::mocked();
+        mock.push::<Bytes, _>(Bytes::from(encode(&[
+            Token::Uint(U256::zero()),
+            Token::Bool(false),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+        ])))
+        .unwrap();
 
-        // [UREP-010] - UserOperation with unstaked sender are only allowed up to
-        // SAME_SENDER_MEMPOOL_COUNT times in the mempool
-        let sender_stake = self.get_stake(&sender, helper).await?;
-        if reputation
-            .verify_stake(
-                SENDER,
-                Some(sender_stake),
-                helper.val_config.min_stake,
-                helper.val_config.min_unstake_delay,
-            )
-            .is_err() &&
-            mempool.get_number_by_sender(&uo.sender) >= SAME_SENDER_MEMPOOL_COUNT
-        {
-            return Err(ReputationError::UnstakedEntity {
-                entity: SENDER.into(),
-                address: uo.sender,
-            }
-            .into());
+        check_sanity(&check, uo, mempool, provider).await
+    }
+
+    /// User operation with the sender, factory and paymaster
+    fn entities_user_operation() -> (UserOperation, Address, Address, Address) {
+        let (sender, factory, paymaster) =
+            (Address::random(), Address::random(), Address::random());
+        let uo = user_operation(
+            UserOperationSigned::default()
+                .sender(sender)
+                .init_code(factory.as_bytes().to_vec().into())
+                .paymaster_and_data(paymaster.as_bytes().to_vec().into()),
+        );
+        (uo, sender, factory, paymaster)
+    }
+
+    #[tokio::test]
+    async fn sender_stake_rejects_only_sender() {
+        let (uo, sender, _, _) = entities_user_operation();
+        let mut mempool = memory_mempool();
+        for nonce in 0..SAME_SENDER_MEMPOOL_COUNT {
+            mempool
+                .add(user_operation(
+                    UserOperationSigned::default().sender(sender).nonce(nonce.into()),
+                ))
+                .unwrap();
         }
 
-        // factory
-        if let Some(factory) = factory {
-            // [STO-040] - UserOperation may not use an entity address
-            // (factory/paymaster/aggregator) that is used as an "account" in another UserOperation
-            // in the mempool
-            if mempool.get_number_by_sender(&factory) > 0 {
-                return Err(SanityError::EntityRoles {
-                    entity: FACTORY.into(),
-                    address: sender,
-                    entity_other: "sender".into(),
-                });
-            }
-
-            let factory_stake = self.get_stake(&factory, helper).await?;
-            if reputation
-                .verify_stake(
-                    FACTORY,
-                    Some(factory_stake),
-                    helper.val_config.min_stake,
-                    helper.val_config.min_unstake_delay,
-                )
-                .is_err()
-            {
-                // [UREP-020] - for other entities
-                let entity = self.get_entity(&factory, helper, reputation)?;
-                let uos_allowed = Self::calculate_allowed_user_operations(entity);
-                if mempool.get_number_by_entity(&factory) as u64 >= uos_allowed {
-                    return Err(ReputationError::UnstakedEntity {
-                        entity: FACTORY.into(),
-                        address: factory,
-                    }
-                    .into());
-                }
-            }
+        assert!(matches!(
+            check(SenderStake, &uo, &mempool).await,
+            Err(SanityError::Reputation(ReputationError::UnstakedEntity { entity, address }))
+                if entity == SENDER && address == sender
+        ));
+        assert!(check(FactoryStake, &uo, &mempool).await.is_ok());
+        assert!(check(PaymasterStake, &uo, &mempool).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn factory_stake_rejects_only_factory() {
+        let (uo, _, factory, _) = entities_user_operation();
+        let mut mempool = memory_mempool();
+        for _ in 0..SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT {
+            mempool
+                .add(user_operation(
+                    UserOperationSigned::default()
+                        .sender(Address::random())
+                        .init_code(factory.as_bytes().to_vec().into()),
+                ))
+                .unwrap();
         }
 
-        // paymaster
-        if let Some(paymaster) = paymaster {
-            // [STO-040] - UserOperation may not use an entity address
-            // (factory/paymaster/aggregator) that is used as an "account" in another UserOperation
-            // in the mempool
-            if mempool.get_number_by_sender(&paymaster) > 0 {
-                return Err(SanityError::EntityRoles {
-                    entity: PAYMASTER.into(),
-                    address: sender,
-                    entity_other: "sender".into(),
-                });
-            }
-
-            let paymaster_stake = self.get_stake(&paymaster, helper).await?;
-            if reputation
-                .verify_stake(
-                    PAYMASTER,
-                    Some(paymaster_stake),
-                    helper.val_config.min_stake,
-                    helper.val_config.min_unstake_delay,
-                )
-                .is_err()
-            {
-                // [UREP-020] - for other entities
-                let entity = self.get_entity(&paymaster, helper, reputation)?;
-                let uos_allowed = Self::calculate_allowed_user_operations(entity);
-                if mempool.get_number_by_entity(&paymaster) as u64 >= uos_allowed {
-                    return Err(ReputationError::UnstakedEntity {
-                        entity: PAYMASTER.into(),
-                        address: paymaster,
-                    }
-                    .into());
-                }
-            }
+        assert!(check(SenderStake, &uo, &mempool).await.is_ok());
+        assert!(matches!(
+            check(FactoryStake, &uo, &mempool).await,
+            Err(SanityError::Reputation(ReputationError::UnstakedEntity { entity, address }))
+                if entity == FACTORY && address == factory
+        ));
+        assert!(check(PaymasterStake, &uo, &mempool).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn paymaster_stake_rejects_only_paymaster() {
+        let (uo, _, _, paymaster) = entities_user_operation();
+        let mut mempool = memory_mempool();
+        for _ in 0..SAME_UNSTAKED_ENTITY_MEMPOOL_COUNT {
+            mempool
+                .add(user_operation(
+                    UserOperationSigned::default()
+                        .sender(Address::random())
+                        .paymaster_and_data(paymaster.as_bytes().to_vec().into()),
+                ))
+                .unwrap();
         }
 
-        Ok(())
+        assert!(check(SenderStake, &uo, &mempool).await.is_ok());
+        assert!(check(FactoryStake, &uo, &mempool).await.is_ok());
+        assert!(matches!(
+            check(PaymasterStake, &uo, &mempool).await,
+            Err(SanityError::Reputation(ReputationError::UnstakedEntity { entity, address }))
+                if entity == PAYMASTER && address == paymaster
+        ));
     }
 }
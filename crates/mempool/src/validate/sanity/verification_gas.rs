@@ -5,11 +5,24 @@ use crate::{
     Overhead, Reputation, SanityError,
 };
 use ethers::{providers::Middleware, types::U256};
-use silius_primitives::UserOperation;
+use silius_primitives::{
+    constants::validation::sanity::{MAX_COMBINED_GAS, MIN_VERIFICATION_GAS},
+    EntryPointVersion, UserOperation,
+};
 
 #[derive(Clone)]
 pub struct VerificationGas {
-    pub max_verification_gas: U256,
+    /// Maximum verification gas limit of one user operation
+    pub max_verification_gas_limit: U256,
+    /// Maximum sum of verification gas limit and call gas limit of one user operation
+    pub max_combined_gas: U256,
+}
+
+impl VerificationGas {
+    /// Creates a new [VerificationGas](VerificationGas) check with the default combined gas cap.
+    pub fn new(max_verification_gas_limit: U256) -> Self {
+        Self { max_verification_gas_limit, max_combined_gas: U256::from(MAX_COMBINED_GAS) }
+    }
 }
 
 #[async_trait::async_trait]
@@ -29,18 +42,54 @@ impl<M: Middleware> SanityCheck<M> for VerificationGas {
         uo: &UserOperation,
         _mempool: &Mempool,
         _reputation: &Reputation,
-        _helper: &SanityHelper<M>,
+        helper: &SanityHelper<M>,
     ) -> Result<(), SanityError> {
-        if uo.verification_gas_limit > self.max_verification_gas {
+        if uo.verification_gas_limit > self.max_verification_gas_limit {
             return Err(SanityError::VerificationGasLimitTooHigh {
                 verification_gas_limit: uo.verification_gas_limit,
-                verification_gas_limit_expected: self.max_verification_gas,
+                verification_gas_limit_expected: self.max_verification_gas_limit,
+            });
+        }
+
+        // entry point always charges at least this much for verification
+        if uo.verification_gas_limit < U256::from(MIN_VERIFICATION_GAS) {
+            return Err(SanityError::VerificationGasLimitTooLow {
+                verification_gas_limit: uo.verification_gas_limit,
+                verification_gas_limit_expected: U256::from(MIN_VERIFICATION_GAS),
+            });
+        }
+
+        // entry point v0.7 runs the paymaster validation with its own gas limit
+        if helper.entry_point_version == EntryPointVersion::V0_7 {
+            if let Some((paymaster_verification_gas_limit, _)) = uo.paymaster_gas_limits() {
+                if paymaster_verification_gas_limit > self.max_verification_gas_limit {
+                    return Err(SanityError::VerificationGasLimitTooHigh {
+                        verification_gas_limit: paymaster_verification_gas_limit,
+                        verification_gas_limit_expected: self.max_verification_gas_limit,
+                    });
+                }
+            }
+        }
+
+        let combined = uo.verification_gas_limit.saturating_add(uo.call_gas_limit);
+        if combined > self.max_combined_gas {
+            return Err(SanityError::CombinedGasLimitTooHigh {
+                combined,
+                max: self.max_combined_gas,
             });
         }
 
-        // calculate the pvg and allow 10 % deviation
+        // calculate the pvg (including the L1 calldata cost on L2s, priced at the max fee per gas
+        // of the user operation) and allow 10 % deviation
         let pre_gas = div_ceil(
-            Overhead::default().calculate_pre_verification_gas(uo).saturating_mul(U256::from(90)),
+            Overhead::for_entry_point(helper.entry_point_version)
+                .calculate_pre_verification_gas(uo)
+                .saturating_add(helper.chain_pre_verification_gas.l1_gas(
+                    uo,
+                    helper.entry_point_version,
+                    U256::zero(),
+                ))
+                .saturating_mul(U256::from(90)),
             U256::from(100),
         );
         if uo.pre_verification_gas < pre_gas {
@@ -53,3 +102,126 @@ impl<M: Middleware> SanityCheck<M> for VerificationGas {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::ChainPreVerificationGas,
+        validate::utils::tests::{
+            check_sanity, check_sanity_on_chain, check_sanity_on_entry_point, memory_mempool,
+        },
+    };
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::{Address, Bytes},
+    };
+    use silius_primitives::{pack_uint128, UserOperationHash, UserOperationSigned};
+
+    async fn check(uo: UserOperationSigned) -> Result<(), SanityError> {
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        let uo = UserOperation::from_user_operation_signed(UserOperationHash::default(), uo);
+        let check = VerificationGas::new(U256::from(5_000_000));
+        check_sanity(&check, &uo, &memory_mempool(), provider).await
+    }
+
+    fn user_operation(verification_gas_limit: u64, call_gas_limit: u64) -> UserOperationSigned {
+        UserOperationSigned::default()
+            .verification_gas_limit(verification_gas_limit.into())
+            .call_gas_limit(call_gas_limit.into())
+            .pre_verification_gas(100_000.into())
+    }
+
+    #[tokio::test]
+    async fn verification_gas_limit_in_bounds() {
+        assert!(check(user_operation(100_000, 200_000)).await.is_ok());
+        assert!(check(user_operation(1500, 0)).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn verification_gas_limit_too_high() {
+        assert!(matches!(
+            check(user_operation(5_000_001, 0)).await,
+            Err(SanityError::VerificationGasLimitTooHigh { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn verification_gas_limit_too_low() {
+        assert!(matches!(
+            check(user_operation(1499, 100_000)).await,
+            Err(SanityError::VerificationGasLimitTooLow { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn combined_gas_limit_too_high() {
+        let res = check(user_operation(5_000_000, 5_000_001)).await;
+        assert!(matches!(
+            res,
+            Err(SanityError::CombinedGasLimitTooHigh { combined, max })
+                if combined == U256::from(10_000_001) && max == U256::from(MAX_COMBINED_GAS)
+        ));
+    }
+
+    #[tokio::test]
+    async fn pre_verification_gas_includes_l1_gas() {
+        let uo = user_operation(100_000, 200_000).max_fee_per_gas(1_000_000_000.into());
+        let uo = UserOperation::from_user_operation_signed(UserOperationHash::default(), uo);
+        let check = VerificationGas::new(U256::from(5_000_000));
+
+        // 500 gwei per calldata byte posted to L1 is 500 gas per byte at 1 gwei
+        let arbitrum = ChainPreVerificationGas::Arbitrum {
+            l1_price_per_byte: U256::from(500_000_000_000_u64),
+        };
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        assert!(matches!(
+            check_sanity_on_chain(&check, &uo, &memory_mempool(), provider, arbitrum).await,
+            Err(SanityError::PreVerificationGasTooLow { .. })
+        ));
+
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            uo.user_operation.clone().pre_verification_gas(
+                U256::from(100_000) +
+                    arbitrum.l1_gas(&uo.user_operation, EntryPointVersion::V0_6, U256::zero()),
+            ),
+        );
+        let (provider, _) = Provider::<MockProvider>::mocked();
+        assert!(check_sanity_on_chain(&check, &uo, &memory_mempool(), provider, arbitrum)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn paymaster_verification_gas_limit_too_high_v0_7() {
+        let check = VerificationGas::new(U256::from(5_000_000));
+        let paymaster_and_data = |paymaster_verification_gas_limit: u64| -> Bytes {
+            [
+                Address::random().as_bytes(),
+                pack_uint128(paymaster_verification_gas_limit.into(), 50_000.into()).as_bytes(),
+            ]
+            .concat()
+            .into()
+        };
+
+        for (paymaster_verification_gas_limit, valid) in [(5_000_000, true), (5_000_001, false)] {
+            let uo = UserOperation::from_user_operation_signed(
+                UserOperationHash::default(),
+                user_operation(100_000, 200_000)
+                    .paymaster_and_data(paymaster_and_data(paymaster_verification_gas_limit)),
+            );
+            let (provider, _) = Provider::<MockProvider>::mocked();
+            let res = check_sanity_on_entry_point(
+                &check,
+                &uo,
+                &memory_mempool(),
+                provider,
+                ChainPreVerificationGas::default(),
+                EntryPointVersion::V0_7,
+            )
+            .await;
+            assert_eq!(res.is_ok(), valid);
+        }
+    }
+}
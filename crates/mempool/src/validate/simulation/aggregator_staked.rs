@@ -0,0 +1,239 @@
+use crate::{
+    validate::{SimulationCheck, SimulationHelper},
+    Reputation, ReputationError, SimulationError,
+};
+use ethers::types::U256;
+use silius_contracts::entry_point::SimulateValidationResult;
+use silius_primitives::{
+    constants::validation::entities::AGGREGATOR,
+    reputation::{StakeInfo, Status},
+    UserOperation,
+};
+
+#[derive(Clone)]
+pub struct AggregatorStaked;
+
+impl AggregatorStaked {
+    /// The helper method that checks the stake and reputation of the signature aggregator.
+    ///
+    /// # Arguments
+    /// `info` - The stake info of the aggregator (zero address if no aggregator is used)
+    /// `min_stake` - The minimum stake of the aggregator
+    /// `min_unstake_delay` - The minimum unstake delay of the aggregator
+    /// `reputation` - The [Reputation](Reputation) of the entities
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] is returned.
+    fn check_aggregator(
+        info: StakeInfo,
+        min_stake: U256,
+        min_unstake_delay: U256,
+        reputation: &Reputation,
+    ) -> Result<(), SimulationError> {
+        let aggregator = info.address;
+        if aggregator.is_zero() {
+            return Ok(());
+        }
+
+        if !reputation.is_whitelist(&aggregator) {
+            if info.stake < min_stake {
+                return Err(ReputationError::StakeTooLow {
+                    entity: AGGREGATOR.into(),
+                    address: aggregator,
+                    stake: info.stake,
+                    min_stake,
+                }
+                .into());
+            }
+
+            if info.unstake_delay < min_unstake_delay {
+                return Err(ReputationError::UnstakeDelayTooLow {
+                    address: aggregator,
+                    entity: AGGREGATOR.into(),
+                    unstake_delay: info.unstake_delay,
+                    min_unstake_delay,
+                }
+                .into());
+            }
+        }
+
+        match Status::from(reputation.get_status(&aggregator)?) {
+            Status::BANNED => Err(ReputationError::BannedEntity {
+                entity: AGGREGATOR.into(),
+                address: aggregator,
+            }
+            .into()),
+            Status::THROTTLED => Err(ReputationError::ThrottledEntity {
+                entity: AGGREGATOR.into(),
+                address: aggregator,
+            }
+            .into()),
+            Status::OK => Ok(()),
+        }
+    }
+}
+
+impl SimulationCheck for AggregatorStaked {
+    /// The method implementation that checks the stake and reputation of the signature aggregator
+    /// reported by the simulation.
+    ///
+    /// # Arguments
+    /// `_uo` - Not used in this check
+    /// `helper` - The [SimulationHelper]
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    fn check_user_operation(
+        &self,
+        _uo: &UserOperation,
+        helper: &mut SimulationHelper,
+    ) -> Result<(), SimulationError> {
+        let (address, (stake, unstake_delay)) = match &helper.context.validation_result {
+            SimulateValidationResult::ValidationResultWithAggregation(res) => res.aggregator_info,
+            SimulateValidationResult::ValidationResult(_) => return Ok(()),
+        };
+        let requirements = helper.reputation.stake_requirements(AGGREGATOR);
+
+        Self::check_aggregator(
+            StakeInfo { address, stake, unstake_delay },
+            helper.val_config.min_stake.unwrap_or(requirements.min_stake),
+            helper
+                .val_config
+                .min_unstake_delay
+                .or(requirements.min_unstake_delay)
+                .unwrap_or(helper.reputation.min_unstake_delay()),
+            helper.reputation,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::simulation::simulation_helper::MockEntryPoint;
+    use ethers::types::Address;
+    use parking_lot::RwLock;
+    use silius_contracts::entry_point::{ValidationResult, ValidationResultWithAggregation};
+    use silius_primitives::{
+        constants::validation::reputation::{
+            BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK,
+        },
+        reputation::ReputationEntry,
+        simulation::ValidationConfig,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    };
+
+    const MIN_STAKE: u64 = 100;
+    const MIN_UNSTAKE_DELAY: u64 = 86400;
+
+    fn reputation(entries: Vec<ReputationEntry>) -> Reputation {
+        let mut reputation = Reputation::new(
+            MIN_INCLUSION_RATE_DENOMINATOR,
+            THROTTLING_SLACK,
+            BAN_SLACK,
+            U256::from(MIN_STAKE),
+            U256::from(MIN_UNSTAKE_DELAY),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            Box::new(HashMap::<Address, ReputationEntry>::default()),
+        );
+        reputation.set_entities(entries).unwrap();
+        reputation
+    }
+
+    /// Runs the check on the simulation result (without an aggregator if `info` is None)
+    fn check(info: Option<StakeInfo>, reputation: &Reputation) -> Result<(), SimulationError> {
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default(),
+        );
+        let return_info = (0.into(), 0.into(), false, 0, u64::MAX, Default::default());
+        let validation_result = match info {
+            Some(info) => SimulateValidationResult::ValidationResultWithAggregation(
+                ValidationResultWithAggregation {
+                    return_info,
+                    aggregator_info: (info.address, (info.stake, info.unstake_delay)),
+                    ..Default::default()
+                },
+            ),
+            None => SimulateValidationResult::ValidationResult(ValidationResult {
+                return_info,
+                ..Default::default()
+            }),
+        };
+        let context = MockEntryPoint::new()
+            .with_validation_result(validation_result)
+            .simulation_context(&uo)?;
+        let mut helper = SimulationHelper::new(&context, reputation, ValidationConfig::default());
+
+        AggregatorStaked.check_user_operation(&uo, &mut helper)
+    }
+
+    fn staked(address: Address) -> StakeInfo {
+        StakeInfo { address, stake: MIN_STAKE.into(), unstake_delay: MIN_UNSTAKE_DELAY.into() }
+    }
+
+    #[test]
+    fn no_aggregator() {
+        assert!(check(None, &reputation(vec![])).is_ok());
+        assert!(check(Some(StakeInfo::default()), &reputation(vec![])).is_ok());
+    }
+
+    #[test]
+    fn staked_aggregator() {
+        assert!(check(Some(staked(Address::random())), &reputation(vec![])).is_ok());
+    }
+
+    #[test]
+    fn stake_too_low() {
+        let aggregator = Address::random();
+        let info = StakeInfo { stake: U256::from(MIN_STAKE - 1), ..staked(aggregator) };
+        assert!(matches!(
+            check(Some(info), &reputation(vec![])),
+            Err(SimulationError::Reputation(ReputationError::StakeTooLow {
+                entity,
+                address,
+                stake,
+                min_stake
+            })) if entity == AGGREGATOR && address == aggregator &&
+                stake == U256::from(MIN_STAKE - 1) && min_stake == U256::from(MIN_STAKE)
+        ));
+    }
+
+    #[test]
+    fn unstake_delay_too_low() {
+        let info = StakeInfo { unstake_delay: U256::from(1), ..staked(Address::random()) };
+        assert!(matches!(
+            check(Some(info), &reputation(vec![])),
+            Err(SimulationError::Reputation(ReputationError::UnstakeDelayTooLow { entity, .. }))
+                if entity == AGGREGATOR
+        ));
+    }
+
+    #[test]
+    fn banned_aggregator() {
+        let aggregator = Address::random();
+        let entry =
+            ReputationEntry { address: aggregator, uo_seen: 10000, uo_included: 0, status: 0 };
+        assert!(matches!(
+            check(Some(staked(aggregator)), &reputation(vec![entry])),
+            Err(SimulationError::Reputation(ReputationError::BannedEntity { entity, address }))
+                if entity == AGGREGATOR && address == aggregator
+        ));
+    }
+
+    #[test]
+    fn throttled_aggregator() {
+        let aggregator = Address::random();
+        let entry = ReputationEntry { address: aggregator, uo_seen: 20, uo_included: 0, status: 0 };
+        assert!(matches!(
+            check(Some(staked(aggregator)), &reputation(vec![entry])),
+            Err(SimulationError::Reputation(ReputationError::ThrottledEntity { entity, address }))
+                if entity == AGGREGATOR && address == aggregator
+        ));
+    }
+}
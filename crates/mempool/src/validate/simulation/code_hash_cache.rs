@@ -0,0 +1,158 @@
+use ethers::types::{Address, H256};
+use lru::LruCache;
+use parking_lot::Mutex;
+use silius_primitives::constants::validation::simulation::{
+    CODE_HASH_CACHE_SIZE, CODE_HASH_CACHE_TTL,
+};
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Code hash of a contract, the block number and the time it was fetched at
+#[derive(Clone, Copy, Debug)]
+struct Entry {
+    hash: H256,
+    block_number: u64,
+    fetched_at: Instant,
+}
+
+/// Code hashes of the contracts visited during validation, cached for the block they were
+/// fetched in
+///
+/// User operations of the same sender, factory or paymaster reference the same contracts, so
+/// within one block their code hashes are fetched (`eth_getCode`) only once. The cache holds at
+/// most `size` contracts (least recently used are evicted first) and an entry expires after `ttl`
+/// even if the block subscription stalls.
+#[derive(Debug)]
+pub struct CodeHashCache {
+    /// Cached code hashes (None if the cache is disabled)
+    inner: Option<Mutex<LruCache<Address, Entry>>>,
+    /// Time a code hash is served for
+    ttl: Duration,
+    /// Latest block received from the block subscription
+    block_number: AtomicU64,
+}
+
+impl Default for CodeHashCache {
+    fn default() -> Self {
+        Self::new(CODE_HASH_CACHE_SIZE, Duration::from_secs(CODE_HASH_CACHE_TTL))
+    }
+}
+
+impl CodeHashCache {
+    /// Creates the cache
+    ///
+    /// # Arguments
+    /// * `size` - Max number of cached contracts (0 disables the cache)
+    /// * `ttl` - Time a code hash is served for
+    pub fn new(size: usize, ttl: Duration) -> Self {
+        Self {
+            inner: NonZeroUsize::new(size).map(|size| Mutex::new(LruCache::new(size))),
+            ttl,
+            block_number: AtomicU64::new(0),
+        }
+    }
+
+    /// Block number the cached code hashes are valid for
+    pub fn block_number(&self) -> u64 {
+        self.block_number.load(Ordering::SeqCst)
+    }
+
+    /// Code hash of the contract, None if it wasn't fetched in the current block or expired
+    pub fn get(&self, addr: &Address) -> Option<H256> {
+        let block_number = self.block_number();
+        let mut inner = self.inner.as_ref()?.lock();
+        inner
+            .get(addr)
+            .filter(|entry| {
+                entry.block_number == block_number && entry.fetched_at.elapsed() < self.ttl
+            })
+            .map(|entry| entry.hash)
+    }
+
+    /// Caches the code hash of the contract
+    ///
+    /// # Arguments
+    /// * `addr` - Address of the contract
+    /// * `hash` - Code hash of the contract
+    /// * `block_number` - Block number read before fetching the code (a hash fetched while a new
+    ///   block arrived is never served for the new block)
+    pub fn insert(&self, addr: Address, hash: H256, block_number: u64) {
+        if let Some(inner) = &self.inner {
+            inner.lock().put(addr, Entry { hash, block_number, fetched_at: Instant::now() });
+        }
+    }
+
+    /// Drops all cached code hashes when a new block arrives
+    pub fn invalidate_for_block(&self, block_number: u64) {
+        self.block_number.store(block_number, Ordering::SeqCst);
+        if let Some(inner) = &self.inner {
+            inner.lock().clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.as_ref().map(|inner| inner.lock().len()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidated_by_new_block() {
+        let cache = CodeHashCache::default();
+        let (addr, hash) = (Address::random(), H256::random());
+
+        cache.invalidate_for_block(10);
+        cache.insert(addr, hash, cache.block_number());
+        assert_eq!(cache.get(&addr), Some(hash));
+
+        cache.invalidate_for_block(11);
+        assert_eq!(cache.get(&addr), None);
+        assert!(cache.is_empty());
+
+        // fetched before the new block arrived
+        cache.insert(addr, hash, 10);
+        assert_eq!(cache.get(&addr), None);
+    }
+
+    #[test]
+    fn bounded_size() {
+        let cache = CodeHashCache::new(2, Duration::from_secs(60));
+        let addrs = [Address::random(), Address::random(), Address::random()];
+        for addr in addrs {
+            cache.insert(addr, H256::random(), cache.block_number());
+        }
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&addrs[0]), None);
+        assert!(cache.get(&addrs[2]).is_some());
+    }
+
+    #[test]
+    fn expired() {
+        let cache = CodeHashCache::new(10, Duration::ZERO);
+        let addr = Address::random();
+        cache.insert(addr, H256::random(), cache.block_number());
+
+        assert_eq!(cache.get(&addr), None);
+    }
+
+    #[test]
+    fn disabled() {
+        let cache = CodeHashCache::new(0, Duration::from_secs(60));
+        let addr = Address::random();
+        cache.insert(addr, H256::random(), cache.block_number());
+
+        assert_eq!(cache.get(&addr), None);
+        assert!(cache.is_empty());
+    }
+}
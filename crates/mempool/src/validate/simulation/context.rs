@@ -0,0 +1,172 @@
+use crate::{
+    validate::{
+        simulation_trace::depth_tracker::{CallFrame, DepthTracker},
+        utils::extract_stake_info,
+    },
+    SimulationError,
+};
+use alloy_chains::Chain;
+use ethers::{
+    providers::Middleware,
+    types::{Address, Block, BlockNumber, H256, U256},
+};
+use silius_contracts::{entry_point::SimulateValidationResult, tracer::JsTracerFrame, EntryPoint};
+use silius_primitives::{
+    constants::validation::entities::NUMBER_OF_LEVELS, get_address, reputation::StakeInfo,
+    UserOperation,
+};
+
+/// State shared by the simulation and simulation trace checks of a [UserOperation]
+///
+/// The context is populated once, right after the `simulateValidation` call, so the checks don't
+/// have to query the Ethereum execution client or decode the simulation result on their own.
+#[derive(Clone, Debug)]
+pub struct SimulationContext {
+    /// The user operation being validated
+    pub user_op: UserOperation,
+    /// Address of the entry point
+    pub entry_point: Address,
+    /// The [EIP-155](https://eips.ethereum.org/EIPS/eip-155) chain ID
+    pub chain_id: U256,
+    /// The latest block the user operation is simulated on
+    pub block: Block<H256>,
+    /// Base fee per gas of the latest block
+    pub base_fee: U256,
+    /// Stake info of the sender
+    pub sender_info: StakeInfo,
+    /// Stake info of the factory (None if the account is already deployed)
+    pub factory_info: Option<StakeInfo>,
+    /// Stake info of the paymaster (None if the user operation has no paymaster)
+    pub paymaster_info: Option<StakeInfo>,
+    /// Result of the `simulateValidation` call
+    pub validation_result: SimulateValidationResult,
+    /// Call frames of the simulation trace (empty until the trace is set)
+    pub call_frames: Vec<CallFrame>,
+}
+
+impl SimulationContext {
+    /// Creates the context from the simulation result and the latest block
+    ///
+    /// # Arguments
+    /// `entry_point` - The [EntryPoint](EntryPoint) contract object
+    /// `chain` - The chain the user operation is validated on
+    /// `uo` - The [UserOperation] being validated
+    /// `validation_result` - The result of the `simulateValidation` call
+    ///
+    /// # Returns
+    /// The [SimulationContext] or a [SimulationError] if the latest block couldn't be fetched
+    pub async fn new<M: Middleware + 'static>(
+        entry_point: &EntryPoint<M>,
+        chain: Chain,
+        uo: &UserOperation,
+        validation_result: SimulateValidationResult,
+    ) -> Result<Self, SimulationError> {
+        let block = entry_point
+            .eth_client()
+            .get_block(BlockNumber::Latest)
+            .await
+            .map_err(|err| SimulationError::Provider { inner: err.to_string() })?
+            .ok_or(SimulationError::Other { inner: "latest block not found".into() })?;
+
+        Ok(Self::from_block(entry_point.address(), chain, uo, block, validation_result))
+    }
+
+    /// Creates the context from an already fetched block
+    pub fn from_block(
+        entry_point: Address,
+        chain: Chain,
+        uo: &UserOperation,
+        block: Block<H256>,
+        validation_result: SimulateValidationResult,
+    ) -> Self {
+        let [factory_info, sender_info, paymaster_info] =
+            extract_stake_info(uo, &validation_result);
+
+        Self {
+            user_op: uo.clone(),
+            entry_point,
+            chain_id: chain.id().into(),
+            base_fee: block.base_fee_per_gas.unwrap_or_default(),
+            block,
+            sender_info,
+            factory_info: get_address(&uo.init_code).map(|_| factory_info),
+            paymaster_info: get_address(&uo.paymaster_and_data).map(|_| paymaster_info),
+            validation_result,
+            call_frames: vec![],
+        }
+    }
+
+    /// Sets the call frames from the simulation trace of the user operation
+    pub fn set_trace(&mut self, js_trace: &JsTracerFrame) {
+        self.call_frames = DepthTracker::call_frames(&js_trace.calls);
+    }
+
+    /// Stake info of the factory, account and paymaster (zero address for missing entities)
+    pub fn stake_info(&self) -> [StakeInfo; NUMBER_OF_LEVELS] {
+        [
+            self.factory_info.unwrap_or_default(),
+            self.sender_info,
+            self.paymaster_info.unwrap_or_default(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::providers::{MockProvider, Provider};
+    use silius_contracts::entry_point::ValidationResult;
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn context_from_mock_provider() {
+        let (provider, mock) = Provider::<MockProvider>::mocked();
+        let block = Block::<H256> {
+            number: Some(100.into()),
+            hash: Some(H256::random()),
+            base_fee_per_gas: Some(7.into()),
+            ..Default::default()
+        };
+        mock.push(block.clone()).unwrap();
+
+        let ep_addr = Address::random();
+        let entry_point = EntryPoint::new(Arc::new(provider), ep_addr);
+
+        let sender = Address::random();
+        let paymaster = Address::random();
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default()
+                .sender(sender)
+                .paymaster_and_data(paymaster.as_bytes().to_vec().into()),
+        );
+        let validation_result = SimulateValidationResult::ValidationResult(ValidationResult {
+            sender_info: (1.into(), 2.into()),
+            factory_info: (3.into(), 4.into()),
+            paymaster_info: (5.into(), 6.into()),
+            ..Default::default()
+        });
+
+        let ctx = SimulationContext::new(&entry_point, Chain::dev(), &uo, validation_result)
+            .await
+            .unwrap();
+
+        assert_eq!(ctx.user_op.hash, uo.hash);
+        assert_eq!(ctx.entry_point, ep_addr);
+        assert_eq!(ctx.chain_id, Chain::dev().id().into());
+        assert_eq!(ctx.block.hash, block.hash);
+        assert_eq!(ctx.base_fee, 7.into());
+        assert_eq!(
+            ctx.sender_info,
+            StakeInfo { address: sender, stake: 1.into(), unstake_delay: 2.into() }
+        );
+        assert_eq!(ctx.factory_info, None);
+        assert_eq!(
+            ctx.paymaster_info,
+            Some(StakeInfo { address: paymaster, stake: 5.into(), unstake_delay: 6.into() })
+        );
+        assert!(matches!(ctx.validation_result, SimulateValidationResult::ValidationResult(_)));
+        assert_eq!(ctx.stake_info()[0], StakeInfo::default());
+    }
+}
@@ -1,5 +1,11 @@
 //! `simulation` module performs checks against a user operation's signature and
 //! timestamp via a `eth_call` to the Ethereum execution client.
+pub mod aggregator_staked;
+pub mod code_hash_cache;
+pub mod context;
 pub mod signature;
+pub mod simulation_cache;
+#[cfg(test)]
+pub mod simulation_helper;
 pub mod timestamp;
 pub mod verification_extra_gas;
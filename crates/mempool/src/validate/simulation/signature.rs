@@ -22,7 +22,7 @@ impl SimulationCheck for Signature {
         _uo: &UserOperation,
         helper: &mut SimulationHelper,
     ) -> Result<(), SimulationError> {
-        let sig_check = match helper.simulate_validation_result {
+        let sig_check = match &helper.context.validation_result {
             SimulateValidationResult::ValidationResult(res) => res.return_info.2,
             SimulateValidationResult::ValidationResultWithAggregation(res) => res.return_info.2,
         };
@@ -34,3 +34,41 @@ impl SimulationCheck for Signature {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::{
+        simulation::simulation_helper::MockEntryPoint, utils::tests::memory_reputation,
+    };
+    use silius_contracts::entry_point::ValidationResult;
+    use silius_primitives::{simulation::ValidationConfig, UserOperationHash, UserOperationSigned};
+
+    fn check(sig_failed: bool) -> Result<(), SimulationError> {
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default(),
+        );
+        let entry_point = MockEntryPoint::new().with_validation_result(
+            SimulateValidationResult::ValidationResult(ValidationResult {
+                return_info: (0.into(), 0.into(), sig_failed, 0, u64::MAX, Default::default()),
+                ..Default::default()
+            }),
+        );
+        let context = entry_point.simulation_context(&uo)?;
+        let reputation = memory_reputation();
+        let mut helper = SimulationHelper::new(&context, &reputation, ValidationConfig::default());
+
+        Signature.check_user_operation(&uo, &mut helper)
+    }
+
+    #[test]
+    fn valid_signature() {
+        assert!(check(false).is_ok());
+    }
+
+    #[test]
+    fn invalid_signature() {
+        assert!(matches!(check(true), Err(SimulationError::Signature)));
+    }
+}
@@ -0,0 +1,198 @@
+use ethers::{
+    types::{GethTrace, H256},
+    utils::keccak256,
+};
+use lru::LruCache;
+use parking_lot::Mutex;
+use silius_contracts::entry_point::SimulateValidationResult;
+use silius_primitives::UserOperationSigned;
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Outcome of a simulation: the result, or the revert reason of the entry point (`FailedOp`)
+pub type CachedSimulation<T> = Result<T, String>;
+
+/// Simulations of one user operation in the block they were made in
+#[derive(Clone, Debug, Default)]
+struct Entry {
+    block_number: u64,
+    validation: Option<CachedSimulation<SimulateValidationResult>>,
+    trace: Option<CachedSimulation<GethTrace>>,
+}
+
+/// Results of `simulateValidation` (and its trace), cached for the block they were made in
+///
+/// Resubmitting an unchanged user operation (e.g. a wallet retrying a rejected one) within the
+/// same block returns the cached results instead of calling the execution client again. Provider
+/// errors aren't cached. The simulations are keyed by the [hash of the whole user
+/// operation](SimulationCache::key), since the user operation hash doesn't cover the signature.
+#[derive(Debug)]
+pub struct SimulationCache {
+    /// Least recently used simulations are evicted first (None if the cache is disabled)
+    inner: Option<Mutex<LruCache<H256, Entry>>>,
+    /// Latest block received from the block subscription
+    block_number: AtomicU64,
+}
+
+impl Default for SimulationCache {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl SimulationCache {
+    /// Creates the cache
+    ///
+    /// # Arguments
+    /// * `size` - Max number of cached user operations (0 disables the cache)
+    pub fn new(size: usize) -> Self {
+        Self {
+            inner: NonZeroUsize::new(size).map(|size| Mutex::new(LruCache::new(size))),
+            block_number: AtomicU64::new(0),
+        }
+    }
+
+    /// Key of the user operation: the hash of the packed user operation including the signature
+    pub fn key(uo: &UserOperationSigned) -> H256 {
+        keccak256(uo.pack()).into()
+    }
+
+    /// Block number the cached simulations are valid for
+    pub fn block_number(&self) -> u64 {
+        self.block_number.load(Ordering::SeqCst)
+    }
+
+    /// Cached `simulateValidation` result of the user operation in the current block
+    pub fn get_validation(&self, key: &H256) -> Option<CachedSimulation<SimulateValidationResult>> {
+        self.get(key, |entry| entry.validation.clone())
+    }
+
+    /// Cached `simulateValidation` trace of the user operation in the current block
+    pub fn get_trace(&self, key: &H256) -> Option<CachedSimulation<GethTrace>> {
+        self.get(key, |entry| entry.trace.clone())
+    }
+
+    /// Caches the `simulateValidation` result of the user operation
+    ///
+    /// # Arguments
+    /// * `key` - [Key](SimulationCache::key) of the user operation
+    /// * `res` - Result of the simulation
+    /// * `block_number` - Block number read before the simulation
+    pub fn insert_validation(
+        &self,
+        key: H256,
+        res: CachedSimulation<SimulateValidationResult>,
+        block_number: u64,
+    ) {
+        self.insert(key, block_number, |entry| entry.validation = Some(res));
+    }
+
+    /// Caches the `simulateValidation` trace of the user operation
+    ///
+    /// # Arguments
+    /// * `key` - [Key](SimulationCache::key) of the user operation
+    /// * `trace` - Trace of the simulation
+    /// * `block_number` - Block number read before the simulation
+    pub fn insert_trace(&self, key: H256, trace: CachedSimulation<GethTrace>, block_number: u64) {
+        self.insert(key, block_number, |entry| entry.trace = Some(trace));
+    }
+
+    /// Drops all cached simulations when a new block arrives
+    pub fn invalidate_for_block(&self, block_number: u64) {
+        self.block_number.store(block_number, Ordering::SeqCst);
+        if let Some(inner) = &self.inner {
+            inner.lock().clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.as_ref().map(|inner| inner.lock().len()).unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn get<T>(&self, key: &H256, f: impl Fn(&Entry) -> Option<T>) -> Option<T> {
+        let block_number = self.block_number();
+        let mut inner = self.inner.as_ref()?.lock();
+        inner.get(key).filter(|entry| entry.block_number == block_number).and_then(f)
+    }
+
+    fn insert(&self, key: H256, block_number: u64, f: impl FnOnce(&mut Entry)) {
+        // simulated while a new block arrived
+        if block_number != self.block_number() {
+            return;
+        }
+
+        if let Some(inner) = &self.inner {
+            let mut inner = inner.lock();
+            let entry =
+                inner.get_or_insert_mut(key, || Entry { block_number, ..Default::default() });
+            if entry.block_number != block_number {
+                *entry = Entry { block_number, ..Default::default() };
+            }
+            f(entry);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidated_by_new_block() {
+        let cache = SimulationCache::new(10);
+        let key = H256::random();
+
+        cache.invalidate_for_block(10);
+        cache.insert_validation(key, Err("AA23 reverted".into()), cache.block_number());
+        assert_eq!(cache.get_validation(&key), Some(Err("AA23 reverted".into())));
+        assert_eq!(cache.get_trace(&key), None);
+
+        cache.invalidate_for_block(11);
+        assert_eq!(cache.get_validation(&key), None);
+        assert!(cache.is_empty());
+
+        // simulated before the new block arrived
+        cache.insert_validation(key, Err("AA23 reverted".into()), 10);
+        assert_eq!(cache.get_validation(&key), None);
+    }
+
+    #[test]
+    fn least_recently_used_evicted() {
+        let cache = SimulationCache::new(2);
+        let keys: Vec<H256> = (0..3).map(|_| H256::random()).collect();
+
+        cache.insert_validation(keyes[0], Err("0".into()), 0);
+        cache.insert_validation(keyes[1], Err("1".into()), 0);
+        assert!(cache.get_validation(&keyes[0]).is_some());
+        cache.insert_validation(keyes[2], Err("2".into()), 0);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_validation(&keyes[0]).is_some());
+        assert!(cache.get_validation(&keyes[1]).is_none());
+        assert!(cache.get_validation(&keyes[2]).is_some());
+    }
+
+    #[test]
+    fn disabled() {
+        let cache = SimulationCache::new(0);
+        let key = H256::random();
+
+        cache.insert_validation(key, Err("AA23 reverted".into()), 0);
+        assert_eq!(cache.get_validation(&key), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn key_covers_signature() {
+        let uo = UserOperationSigned::default();
+        let signed = UserOperationSigned::default().signature(vec![1; 65].into());
+        assert_ne!(SimulationCache::key(&uo), SimulationCache::key(&signed));
+        assert_eq!(SimulationCache::key(&uo), SimulationCache::key(&uo.clone()));
+    }
+}
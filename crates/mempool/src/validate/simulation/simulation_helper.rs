@@ -0,0 +1,159 @@
+//! Mocked entry point for testing simulation checks without an Ethereum execution client.
+use crate::{
+    validate::{simulation::context::SimulationContext, SimulationHelper},
+    Reputation, SimulationError,
+};
+use alloy_chains::Chain;
+use ethers::types::{Address, Block, U256};
+use silius_contracts::{
+    entry_point::{SimulateValidationResult, ValidationResult},
+    EntryPointError, ExecutionResult, FailedOp,
+};
+use silius_primitives::{simulation::ValidationConfig, UserOperation};
+
+/// `validUntil` the entry point reports when the account doesn't set an expiration
+const UINT48_MAX: u64 = (1 << 48) - 1;
+
+/// Entry point returning preconfigured simulation results instead of calling the contract.
+#[derive(Clone, Debug)]
+pub struct MockEntryPoint {
+    validation_result: SimulateValidationResult,
+    execution_result: Option<ExecutionResult>,
+    error: Option<String>,
+}
+
+impl Default for MockEntryPoint {
+    fn default() -> Self {
+        Self {
+            validation_result: SimulateValidationResult::ValidationResult(ValidationResult {
+                return_info: (U256::zero(), U256::zero(), false, 0, UINT48_MAX, Default::default()),
+                ..Default::default()
+            }),
+            execution_result: None,
+            error: None,
+        }
+    }
+}
+
+impl MockEntryPoint {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the result returned by `simulateValidation`.
+    pub fn with_validation_result(mut self, validation_result: SimulateValidationResult) -> Self {
+        self.validation_result = validation_result;
+        self
+    }
+
+    /// Sets the result returned by `simulateHandleOp`.
+    pub fn with_simulate_handle_op_result(mut self, execution_result: ExecutionResult) -> Self {
+        self.execution_result = Some(execution_result);
+        self
+    }
+
+    /// Makes every simulation revert with `FailedOp(0, revert_reason)`.
+    pub fn with_error(mut self, revert_reason: impl Into<String>) -> Self {
+        self.error = Some(revert_reason.into());
+        self
+    }
+
+    fn failed_op(&self) -> Option<EntryPointError> {
+        self.error.as_ref().map(|reason| {
+            EntryPointError::FailedOp(FailedOp { op_index: U256::zero(), reason: reason.clone() })
+        })
+    }
+
+    pub fn simulate_validation(
+        &self,
+        _uo: &UserOperation,
+    ) -> Result<&SimulateValidationResult, EntryPointError> {
+        match self.failed_op() {
+            Some(err) => Err(err),
+            None => Ok(&self.validation_result),
+        }
+    }
+
+    pub fn simulate_handle_op(
+        &self,
+        _uo: &UserOperation,
+    ) -> Result<ExecutionResult, EntryPointError> {
+        if let Some(err) = self.failed_op() {
+            return Err(err);
+        }
+
+        self.execution_result
+            .clone()
+            .ok_or(EntryPointError::NoRevert { function: "simulate_handle_op".into() })
+    }
+
+    /// Creates the [SimulationContext] from the mocked `simulateValidation` result, mapping errors
+    /// the same way as the [validator](crate::validate::validator::StandardUserOperationValidator).
+    pub fn simulation_context(
+        &self,
+        uo: &UserOperation,
+    ) -> Result<SimulationContext, SimulationError> {
+        let validation_result = self.simulate_validation(uo).map_err(|err| match err {
+            EntryPointError::FailedOp(op) => SimulationError::Validation { inner: op.reason },
+            _ => SimulationError::from(err),
+        })?;
+
+        Ok(SimulationContext::from_block(
+            Address::zero(),
+            Chain::dev(),
+            uo,
+            Block::default(),
+            validation_result.clone(),
+        ))
+    }
+}
+
+impl<'a> SimulationHelper<'a> {
+    pub fn new(
+        context: &'a SimulationContext,
+        reputation: &'a Reputation,
+        val_config: ValidationConfig,
+    ) -> Self {
+        Self { context, reputation, val_config, valid_after: None }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    fn uo() -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default(),
+        )
+    }
+
+    #[test]
+    fn simulate_handle_op_result() {
+        let execution_result = ExecutionResult { pre_op_gas: 100_000.into(), ..Default::default() };
+        let entry_point =
+            MockEntryPoint::new().with_simulate_handle_op_result(execution_result.clone());
+
+        assert_eq!(entry_point.simulate_handle_op(&uo()).unwrap(), execution_result);
+        assert!(matches!(
+            MockEntryPoint::new().simulate_handle_op(&uo()),
+            Err(EntryPointError::NoRevert { .. })
+        ));
+    }
+
+    #[test]
+    fn failed_op() {
+        let entry_point = MockEntryPoint::new().with_error("AA23 reverted");
+
+        assert!(matches!(
+            entry_point.simulation_context(&uo()),
+            Err(SimulationError::Validation { inner }) if inner == "AA23 reverted"
+        ));
+        assert!(matches!(
+            entry_point.simulate_handle_op(&uo()),
+            Err(EntryPointError::FailedOp(op)) if op.reason == "AA23 reverted"
+        ));
+    }
+}
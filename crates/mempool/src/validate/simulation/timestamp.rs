@@ -3,14 +3,29 @@ use crate::{
     SimulationError,
 };
 use ethers::types::U256;
-use silius_primitives::{simulation::EXPIRATION_TIMESTAMP_DIFF, UserOperation};
+use silius_primitives::{
+    constants::mempool::MAX_MEMPOOL_AGE, simulation::EXPIRATION_TIMESTAMP_DIFF, UserOperation,
+};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Clone)]
-pub struct Timestamp;
+pub struct Timestamp {
+    /// User operations that expire within this number of seconds are rejected
+    pub expiry_buffer: u64,
+    /// User operations that become valid later than this number of seconds from now are rejected
+    /// (they would be evicted from the mempool before they can be included)
+    pub max_valid_after: u64,
+}
+
+impl Default for Timestamp {
+    fn default() -> Self {
+        Self { expiry_buffer: EXPIRATION_TIMESTAMP_DIFF, max_valid_after: MAX_MEMPOOL_AGE }
+    }
+}
 
 impl SimulationCheck for Timestamp {
-    /// The method implementation that checks the timestamp of the user operation.
+    /// The method implementation that checks the validity window (`validAfter` and `validUntil`)
+    /// of the user operation.
     ///
     /// # Arguments
     /// `_uo` - Not used in this check
@@ -23,7 +38,7 @@ impl SimulationCheck for Timestamp {
         _uo: &UserOperation,
         helper: &mut SimulationHelper,
     ) -> Result<(), SimulationError> {
-        let (valid_after, valid_until) = extract_timestamps(helper.simulate_validation_result);
+        let (valid_after, valid_until) = extract_timestamps(&helper.context.validation_result);
 
         let now = U256::from(
             SystemTime::now()
@@ -36,10 +51,16 @@ impl SimulationCheck for Timestamp {
             return Err(SimulationError::Timestamp { inner: "already expired".into() });
         }
 
-        if valid_until <= now + EXPIRATION_TIMESTAMP_DIFF {
+        if valid_until <= now + self.expiry_buffer {
             return Err(SimulationError::Timestamp { inner: "expires too soon".into() });
         }
 
+        if valid_after > now + self.max_valid_after {
+            return Err(SimulationError::Timestamp {
+                inner: format!("not valid until {valid_after} (too far in the future)"),
+            });
+        }
+
         if valid_after > now {
             helper.valid_after = Some(valid_after);
         }
@@ -47,3 +68,71 @@ impl SimulationCheck for Timestamp {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::{
+        simulation::simulation_helper::MockEntryPoint, utils::tests::memory_reputation,
+    };
+    use silius_contracts::entry_point::{SimulateValidationResult, ValidationResult};
+    use silius_primitives::{simulation::ValidationConfig, UserOperationHash, UserOperationSigned};
+
+    fn now() -> u64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+    }
+
+    fn check(after: u64, until: u64) -> Result<Option<U256>, SimulationError> {
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default(),
+        );
+        let entry_point = MockEntryPoint::new().with_validation_result(
+            SimulateValidationResult::ValidationResult(ValidationResult {
+                return_info: (0.into(), 0.into(), false, after, until, Default::default()),
+                ..Default::default()
+            }),
+        );
+        let context = entry_point.simulation_context(&uo)?;
+        let reputation = memory_reputation();
+        let mut helper = SimulationHelper::new(&context, &reputation, ValidationConfig::default());
+
+        Timestamp::default().check_user_operation(&uo, &mut helper)?;
+        Ok(helper.valid_after)
+    }
+
+    #[test]
+    fn valid_timestamps() {
+        assert_eq!(check(0, now() + 3600).unwrap(), None);
+    }
+
+    #[test]
+    fn valid_after_in_future() {
+        let valid_after = now() + 600;
+        assert_eq!(check(valid_after, now() + 3600).unwrap(), Some(valid_after.into()));
+    }
+
+    #[test]
+    fn valid_after_too_far_in_future() {
+        assert!(matches!(
+            check(now() + MAX_MEMPOOL_AGE + 600, now() + 2 * MAX_MEMPOOL_AGE),
+            Err(SimulationError::Timestamp { .. })
+        ));
+    }
+
+    #[test]
+    fn already_expired() {
+        assert!(matches!(
+            check(0, now() - 1),
+            Err(SimulationError::Timestamp { inner }) if inner == "already expired"
+        ));
+    }
+
+    #[test]
+    fn expires_too_soon() {
+        assert!(matches!(
+            check(0, now() + 1),
+            Err(SimulationError::Timestamp { inner }) if inner == "expires too soon"
+        ));
+    }
+}
@@ -22,7 +22,7 @@ impl SimulationCheck for VerificationExtraGas {
         uo: &UserOperation,
         helper: &mut SimulationHelper,
     ) -> Result<(), SimulationError> {
-        let pre_op_gas = match helper.simulate_validation_result {
+        let pre_op_gas = match &helper.context.validation_result {
             SimulateValidationResult::ValidationResult(res) => res.return_info.0,
             SimulateValidationResult::ValidationResultWithAggregation(res) => res.return_info.0,
         };
@@ -44,3 +44,45 @@ impl SimulationCheck for VerificationExtraGas {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::{
+        simulation::simulation_helper::MockEntryPoint, utils::tests::memory_reputation,
+    };
+    use silius_contracts::entry_point::ValidationResult;
+    use silius_primitives::{simulation::ValidationConfig, UserOperationHash, UserOperationSigned};
+
+    fn check(pre_op_gas: u64) -> Result<(), SimulationError> {
+        let uo = UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default()
+                .verification_gas_limit(100_000.into())
+                .pre_verification_gas(50_000.into()),
+        );
+        let entry_point = MockEntryPoint::new().with_validation_result(
+            SimulateValidationResult::ValidationResult(ValidationResult {
+                return_info: (pre_op_gas.into(), 0.into(), false, 0, u64::MAX, Default::default()),
+                ..Default::default()
+            }),
+        );
+        let context = entry_point.simulation_context(&uo)?;
+        let reputation = memory_reputation();
+        let mut helper = SimulationHelper::new(&context, &reputation, ValidationConfig::default());
+
+        VerificationExtraGas.check_user_operation(&uo, &mut helper)
+    }
+
+    #[test]
+    fn enough_extra_gas() {
+        // extra gas: 100_000 - (140_000 - 50_000) = 10_000
+        assert!(check(140_000).is_ok());
+    }
+
+    #[test]
+    fn not_enough_extra_gas() {
+        // extra gas: 100_000 - (149_500 - 50_000) = 500, account is deployed so 1_000 is needed
+        assert!(matches!(check(149_500), Err(SimulationError::Validation { .. })));
+    }
+}
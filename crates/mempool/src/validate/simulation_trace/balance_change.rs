@@ -0,0 +1,165 @@
+use crate::{
+    mempool::Mempool,
+    validate::{
+        simulation_trace::depth_tracker::DepthTracker, SimulationTraceCheck, SimulationTraceHelper,
+    },
+    Reputation, SimulationError,
+};
+use ethers::{
+    providers::Middleware,
+    types::{Address, U256},
+};
+use silius_contracts::tracer::Call;
+use silius_primitives::UserOperation;
+
+const CALL_OPCODE: &str = "CALL";
+
+/// Rejects user operations whose sender transfers ETH during validation.
+///
+/// A sender that drains itself in `validateUserOp` makes the user operation fail to pay for its
+/// execution. Transfers to the entry point are allowed, since that is how the sender pays the
+/// missing prefund.
+#[derive(Clone)]
+pub struct BalanceChange;
+
+impl BalanceChange {
+    /// The helper method that sums the value the sender transfers during validation.
+    ///
+    /// Only the calls nested in the `validateUserOp` frame (the entry point calling the sender)
+    /// are counted, calls made from a delegated frame are attributed to the sender.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `entry_point` - The address of the entry point
+    /// `calls` - The calls from the simulation trace
+    ///
+    /// # Returns
+    /// The total value transferred out of the sender.
+    fn transferred_value(uo: &UserOperation, entry_point: Address, calls: &[Call]) -> U256 {
+        let mut tracker = DepthTracker::new();
+        let mut validation_depth = None;
+        let mut amount = U256::zero();
+
+        for call in calls.iter() {
+            let Some(frame) = tracker.process(call) else {
+                continue;
+            };
+
+            match validation_depth {
+                Some(depth) if frame.depth > depth => {
+                    if frame.from == uo.sender &&
+                        frame.call_type == CALL_OPCODE &&
+                        frame.to != entry_point
+                    {
+                        amount = amount.saturating_add(call.value.unwrap_or_default());
+                    }
+                }
+                _ => {
+                    validation_depth =
+                        (frame.from == entry_point && frame.to == uo.sender).then_some(frame.depth);
+                }
+            }
+        }
+
+        amount
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for BalanceChange {
+    /// The method implementation that checks the sender doesn't transfer ETH during validation.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `helper` - The [SimulationTraceHelper](crate::validate::SimulationTraceHelper)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        let amount =
+            Self::transferred_value(uo, helper.entry_point.address(), &helper.js_trace.calls);
+        if !amount.is_zero() {
+            return Err(SimulationError::SenderTransfersDuringValidation { amount });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use silius_primitives::{simulation::RETURN_OPCODE, UserOperationHash, UserOperationSigned};
+
+    fn call(typ: &str, from: Address, to: Address, value: u64) -> Call {
+        Call {
+            typ: typ.into(),
+            from: Some(from),
+            to: Some(to),
+            value: Some(value.into()),
+            ..Default::default()
+        }
+    }
+
+    fn ret() -> Call {
+        Call { typ: RETURN_OPCODE.to_string(), ..Default::default() }
+    }
+
+    fn user_operation() -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default().sender(Address::random()),
+        )
+    }
+
+    #[test]
+    fn sender_transfers_during_validation() {
+        let uo = user_operation();
+        let (ep, implementation, receiver) =
+            (Address::random(), Address::random(), Address::random());
+
+        let calls = [
+            call(CALL_OPCODE, ep, uo.sender, 0),
+            call("DELEGATECALL", uo.sender, implementation, 0),
+            call(CALL_OPCODE, implementation, receiver, 100),
+            ret(),
+            ret(),
+            call(CALL_OPCODE, uo.sender, receiver, 20),
+            ret(),
+            ret(),
+        ];
+        assert_eq!(BalanceChange::transferred_value(&uo, ep, &calls), U256::from(120));
+    }
+
+    #[test]
+    fn prefund_to_entry_point() {
+        let uo = user_operation();
+        let ep = Address::random();
+
+        let calls = [call(CALL_OPCODE, ep, uo.sender, 0), call(CALL_OPCODE, uo.sender, ep, 1000)];
+        assert!(BalanceChange::transferred_value(&uo, ep, &calls).is_zero());
+    }
+
+    #[test]
+    fn transfers_outside_of_validation() {
+        let uo = user_operation();
+        let (ep, paymaster) = (Address::random(), Address::random());
+
+        // the paymaster frame opens after validateUserOp returned
+        let calls = [
+            call(CALL_OPCODE, ep, uo.sender, 0),
+            ret(),
+            call(CALL_OPCODE, ep, paymaster, 0),
+            call(CALL_OPCODE, paymaster, Address::random(), 100),
+            ret(),
+            ret(),
+        ];
+        assert!(BalanceChange::transferred_value(&uo, ep, &calls).is_zero());
+    }
+}
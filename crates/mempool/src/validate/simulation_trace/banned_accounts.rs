@@ -0,0 +1,196 @@
+use crate::{
+    mempool::Mempool,
+    validate::{
+        simulation_trace::depth_tracker::CallFrame, SimulationTraceCheck, SimulationTraceHelper,
+    },
+    Reputation, SimulationError,
+};
+use ethers::{providers::Middleware, types::Address};
+use silius_primitives::{
+    constants::validation::entities::{FACTORY, PAYMASTER, SENDER},
+    get_address,
+    reputation::Status,
+    UserOperation,
+};
+use std::collections::HashMap;
+
+const CALL_OPCODE: &str = "CALL";
+const STATICCALL_OPCODE: &str = "STATICCALL";
+
+/// Rejects user operations whose entities call a banned address during validation.
+///
+/// The sanity checks only look at the entities referenced in the user operation, so a banned
+/// paymaster or factory called internally by the account would otherwise go unnoticed.
+#[derive(Clone)]
+pub struct BannedAccountCallCheck;
+
+impl BannedAccountCallCheck {
+    /// The helper method that checks the call targets against the reputation.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) whose entities make the calls
+    /// `entry_point` - The entry point address
+    /// `call_frames` - The call frames from the simulation trace
+    /// `reputation` - The [Reputation](Reputation) of the entities
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    fn check_calls(
+        uo: &UserOperation,
+        entry_point: Address,
+        call_frames: &[CallFrame],
+        reputation: &Reputation,
+    ) -> Result<(), SimulationError> {
+        let mut entities: HashMap<Address, &str> = HashMap::from([(uo.sender, SENDER)]);
+        if let Some(factory) = get_address(&uo.init_code) {
+            entities.insert(factory, FACTORY);
+        }
+        if let Some(paymaster) = get_address(&uo.paymaster_and_data) {
+            entities.insert(paymaster, PAYMASTER);
+        }
+
+        // entity on whose behalf each open call frame executes
+        let mut frames: Vec<Option<&str>> = vec![];
+
+        for frame in call_frames.iter() {
+            frames.truncate(frame.depth);
+
+            let caller = entities.get(&frame.from).copied();
+            let caller = caller.or(frames.last().copied().flatten());
+            let callee = entities.get(&frame.to).copied();
+            frames.push(callee.or(caller));
+
+            if frame.from == entry_point || frame.to == entry_point {
+                continue;
+            }
+
+            if frame.call_type == CALL_OPCODE || frame.call_type == STATICCALL_OPCODE {
+                let status = Status::from(reputation.get_status(&frame.to)?);
+                if status == Status::BANNED {
+                    return Err(SimulationError::CallToBannedEntity {
+                        caller_entity: caller.unwrap_or(SENDER).to_string(),
+                        banned_address: frame.to,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for BannedAccountCallCheck {
+    /// The method implementation that checks the calls to banned addresses.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `reputation` - The [Reputation](Reputation) of the entities
+    /// `helper` - The [SimulationTraceHelper](crate::validate::SimulationTraceHelper)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        Self::check_calls(uo, helper.entry_point.address(), &helper.context.call_frames, reputation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::simulation_trace::depth_tracker::DepthTracker;
+    use ethers::types::U256;
+    use parking_lot::RwLock;
+    use silius_contracts::tracer::Call;
+    use silius_primitives::{
+        constants::validation::reputation::{
+            BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK,
+        },
+        reputation::ReputationEntry,
+        simulation::RETURN_OPCODE,
+        UserOperationHash, UserOperationSigned,
+    };
+    use std::{collections::HashSet, sync::Arc};
+
+    fn call(typ: &str, from: Address, to: Address) -> Call {
+        Call { typ: typ.into(), from: Some(from), to: Some(to), ..Default::default() }
+    }
+
+    fn ret() -> Call {
+        Call { typ: RETURN_OPCODE.to_string(), ..Default::default() }
+    }
+
+    fn reputation(banned: &[Address]) -> Reputation {
+        Reputation::new(
+            MIN_INCLUSION_RATE_DENOMINATOR,
+            THROTTLING_SLACK,
+            BAN_SLACK,
+            U256::from(1),
+            U256::from(0),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            Arc::new(RwLock::new(banned.iter().cloned().collect())),
+            Box::new(HashMap::<Address, ReputationEntry>::default()),
+        )
+    }
+
+    fn uo() -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default().sender(Address::random()),
+        )
+    }
+
+    #[test]
+    fn account_calls_banned_paymaster() {
+        let (uo, ep, paymaster) = (uo(), Address::random(), Address::random());
+        let calls = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, uo.sender),
+            call(STATICCALL_OPCODE, uo.sender, paymaster),
+            ret(),
+            ret(),
+        ]);
+
+        assert!(matches!(
+            BannedAccountCallCheck::check_calls(&uo, ep, &calls, &reputation(&[paymaster])),
+            Err(SimulationError::CallToBannedEntity { caller_entity, banned_address })
+                if caller_entity == SENDER && banned_address == paymaster
+        ));
+        assert!(BannedAccountCallCheck::check_calls(&uo, ep, &calls, &reputation(&[])).is_ok());
+    }
+
+    #[test]
+    fn banned_address_called_through_library() {
+        let (uo, ep, library, banned) =
+            (uo(), Address::random(), Address::random(), Address::random());
+        let calls = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, uo.sender),
+            call(CALL_OPCODE, uo.sender, library),
+            call(CALL_OPCODE, library, banned),
+            ret(),
+            ret(),
+            ret(),
+        ]);
+
+        assert!(matches!(
+            BannedAccountCallCheck::check_calls(&uo, ep, &calls, &reputation(&[banned])),
+            Err(SimulationError::CallToBannedEntity { caller_entity, banned_address })
+                if caller_entity == SENDER && banned_address == banned
+        ));
+    }
+
+    #[test]
+    fn entry_point_calls_are_ignored() {
+        let (uo, ep) = (uo(), Address::random());
+        let calls = DepthTracker::call_frames(&[call(CALL_OPCODE, ep, uo.sender), ret()]);
+
+        assert!(
+            BannedAccountCallCheck::check_calls(&uo, ep, &calls, &reputation(&[uo.sender])).is_ok()
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     mempool::Mempool,
-    validate::{utils::extract_stake_info, SimulationTraceCheck, SimulationTraceHelper},
+    validate::{SimulationTraceCheck, SimulationTraceHelper},
     Reputation, SimulationError,
 };
 use ethers::{abi::AbiDecode, providers::Middleware};
@@ -103,14 +103,12 @@ impl<M: Middleware> SimulationTraceCheck<M> for CallStack {
     /// None if the check passes, otherwise a [SimulationError] error.
     async fn check_user_operation(
         &self,
-        uo: &UserOperation,
+        _uo: &UserOperation,
         _mempool: &Mempool,
         reputation: &Reputation,
         helper: &mut SimulationTraceHelper<M>,
     ) -> Result<(), SimulationError> {
-        if helper.stake_info.is_none() {
-            helper.stake_info = Some(extract_stake_info(uo, helper.simulate_validation_result));
-        }
+        let stake_info = helper.context.stake_info();
 
         let mut calls: Vec<CallEntry> = vec![];
         self.parse_call_stack(helper.js_trace, &mut calls)?;
@@ -139,7 +137,7 @@ impl<M: Middleware> SimulationTraceCheck<M> for CallStack {
             }
 
             // paymaster
-            for (i, stake_info) in helper.stake_info.unwrap_or_default().iter().enumerate() {
+            for (i, stake_info) in stake_info.iter().enumerate() {
                 if LEVEL_TO_ENTITY[i] == PAYMASTER &&
                     call.method == Some(VALIDATE_PAYMASTER_USER_OP_FUNCTION.clone()) &&
                     call.to == Some(stake_info.address)
@@ -0,0 +1,208 @@
+use crate::{
+    mempool::Mempool,
+    validate::{
+        simulation_trace::depth_tracker::CallFrame, SimulationTraceCheck, SimulationTraceHelper,
+    },
+    Reputation, SimulationError,
+};
+use ethers::{
+    providers::Middleware,
+    types::{Address, H256},
+};
+use silius_contracts::tracer::TopLevelCallInfo;
+use silius_primitives::{
+    constants::validation::entities::{FACTORY, PAYMASTER, SENDER},
+    get_address, UserOperation,
+};
+use std::{collections::HashSet, str::FromStr};
+
+const CALLCODE_OPCODE: &str = "CALLCODE";
+const DELEGATECALL_OPCODE: &str = "DELEGATECALL";
+
+/// EIP-1967 implementation slot: `bytes32(uint256(keccak256('eip1967.proxy.implementation')) - 1)`
+pub(crate) const IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a9e3a8be6d0c7e3f9f";
+
+/// Restricts the call types used during validation.
+///
+/// `CALLCODE` is rejected everywhere. `DELEGATECALL` is only allowed to the implementation of the
+/// sender, i.e. an address the sender read from its EIP-1967 implementation slot during
+/// validation. `CALL` and `STATICCALL` are not restricted by this check.
+#[derive(Clone)]
+pub struct CallTypeCheck;
+
+impl CallTypeCheck {
+    /// The helper method that collects the implementation addresses of the sender.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) whose sender is a proxy
+    /// `calls_from_entry_point` - The top level calls from the simulation trace
+    ///
+    /// # Returns
+    /// The addresses read from the implementation slot of the sender.
+    fn implementations(
+        uo: &UserOperation,
+        calls_from_entry_point: &[TopLevelCallInfo],
+    ) -> HashSet<Address> {
+        calls_from_entry_point
+            .iter()
+            .filter_map(|level| level.access.get(&uo.sender))
+            .filter_map(|access| {
+                access.reads.iter().find_map(|(slot, value)| {
+                    if slot.eq_ignore_ascii_case(IMPLEMENTATION_SLOT) {
+                        H256::from_str(value).ok().map(Address::from)
+                    } else {
+                        None
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// The helper method that checks the call types of the simulation trace.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `frames` - The call frames from the simulation trace
+    /// `implementations` - The implementation addresses of the sender
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    fn check_calls(
+        uo: &UserOperation,
+        frames: &[CallFrame],
+        implementations: &HashSet<Address>,
+    ) -> Result<(), SimulationError> {
+        let entity = |from: Address| match from {
+            addr if addr == uo.sender => SENDER.to_string(),
+            addr if Some(addr) == get_address(&uo.init_code) => FACTORY.to_string(),
+            addr if Some(addr) == get_address(&uo.paymaster_and_data) => PAYMASTER.to_string(),
+            addr => format!("{addr:?}"),
+        };
+
+        for frame in frames.iter() {
+            let forbidden = match frame.call_type.as_str() {
+                CALLCODE_OPCODE => true,
+                DELEGATECALL_OPCODE => {
+                    frame.from != uo.sender || !implementations.contains(&frame.to)
+                }
+                _ => false,
+            };
+
+            if forbidden {
+                return Err(SimulationError::ForbiddenCallType {
+                    call_type: frame.call_type.clone(),
+                    to: frame.to,
+                    entity: entity(frame.from),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for CallTypeCheck {
+    /// The method implementation that performs the call type check.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `helper` - The [SimulationTraceHelper](crate::validate::SimulationTraceHelper)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        let implementations = Self::implementations(uo, &helper.js_trace.calls_from_entry_point);
+        Self::check_calls(uo, &helper.context.call_frames, &implementations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::simulation_trace::depth_tracker::DepthTracker;
+    use silius_contracts::tracer::{Call, ReadsAndWrites};
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+    use std::collections::HashMap;
+
+    fn call(typ: &str, from: Address, to: Address) -> Call {
+        Call { typ: typ.into(), from: Some(from), to: Some(to), ..Default::default() }
+    }
+
+    fn user_operation() -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default().sender(Address::random()),
+        )
+    }
+
+    fn proxy_level(uo: &UserOperation, implementation: Address) -> TopLevelCallInfo {
+        let access = ReadsAndWrites {
+            reads: HashMap::from([(
+                IMPLEMENTATION_SLOT.to_string(),
+                format!("{:?}", H256::from(implementation)),
+            )]),
+            writes: HashMap::default(),
+        };
+        TopLevelCallInfo { access: HashMap::from([(uo.sender, access)]), ..Default::default() }
+    }
+
+    #[test]
+    fn callcode_rejected() {
+        let uo = user_operation();
+        let implementation = Address::random();
+        let implementations = HashSet::from([implementation]);
+
+        let calls = DepthTracker::call_frames(&[call(CALLCODE_OPCODE, uo.sender, implementation)]);
+        assert!(matches!(
+            CallTypeCheck::check_calls(&uo, &calls, &implementations),
+            Err(SimulationError::ForbiddenCallType { call_type, to, entity })
+                if call_type == CALLCODE_OPCODE && to == implementation && entity == SENDER
+        ));
+
+        let (from, to) = (Address::random(), Address::random());
+        let calls = DepthTracker::call_frames(&[call(CALLCODE_OPCODE, from, to)]);
+        assert!(matches!(
+            CallTypeCheck::check_calls(&uo, &calls, &HashSet::default()),
+            Err(SimulationError::ForbiddenCallType { .. })
+        ));
+    }
+
+    #[test]
+    fn delegatecall_to_implementation() {
+        let uo = user_operation();
+        let implementation = Address::random();
+        let implementations =
+            CallTypeCheck::implementations(&uo, &[proxy_level(&uo, implementation)]);
+        assert_eq!(implementations, HashSet::from([implementation]));
+
+        let calls = DepthTracker::call_frames(&[
+            call("CALL", Address::random(), uo.sender),
+            call(DELEGATECALL_OPCODE, uo.sender, implementation),
+            call("STATICCALL", uo.sender, Address::random()),
+        ]);
+        assert!(CallTypeCheck::check_calls(&uo, &calls, &implementations).is_ok());
+    }
+
+    #[test]
+    fn delegatecall_to_random_address() {
+        let uo = user_operation();
+        let implementations =
+            CallTypeCheck::implementations(&uo, &[proxy_level(&uo, Address::random())]);
+
+        let to = Address::random();
+        let calls = DepthTracker::call_frames(&[call(DELEGATECALL_OPCODE, uo.sender, to)]);
+        assert!(matches!(
+            CallTypeCheck::check_calls(&uo, &calls, &implementations),
+            Err(SimulationError::ForbiddenCallType { call_type, to: addr, .. })
+                if call_type == DELEGATECALL_OPCODE && addr == to
+        ));
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
     mempool::Mempool,
     utils::equal_code_hashes,
-    validate::{SimulationTraceCheck, SimulationTraceHelper},
+    validate::{
+        simulation::code_hash_cache::CodeHashCache, SimulationTraceCheck, SimulationTraceHelper,
+    },
     Reputation, SimulationError,
 };
 use ethers::{
@@ -24,18 +26,26 @@ impl CodeHashes {
     /// `addrs` - The list of addresses
     /// `hashes` - The list of code hashes
     /// `eth_client` - The Ethereum client
+    /// `cache` - Code hashes already fetched in the current block
     ///
     /// # Returns
     /// None if code hash is available, otherwise [SimulationError](SimulationError).
-    async fn get_code_hashes<M: Middleware + 'static>(
+    pub async fn get_code_hashes<M: Middleware + 'static>(
         &self,
         addrs: Vec<Address>,
         hashes: &mut Vec<CodeHash>,
         eth_client: &Arc<M>,
+        cache: &CodeHashCache,
     ) -> Result<(), SimulationError> {
+        let block_number = cache.block_number();
         let mut ts: JoinSet<Option<(Address, H256)>> = JoinSet::new();
 
         for addr in addrs {
+            if let Some(hash) = cache.get(&addr) {
+                hashes.push(CodeHash { address: addr, hash });
+                continue;
+            }
+
             let eth_client = eth_client.clone();
 
             ts.spawn(async move {
@@ -48,7 +58,10 @@ impl CodeHashes {
 
         while let Some(res) = ts.join_next().await {
             match res {
-                Ok(Some(h)) => hashes.push(CodeHash { address: h.0, hash: h.1 }),
+                Ok(Some(h)) => {
+                    cache.insert(h.0, h.1, block_number);
+                    hashes.push(CodeHash { address: h.0, hash: h.1 });
+                }
                 Ok(None) | Err(_) => {
                     return Err(SimulationError::Other {
                         inner: "Failed to retrieve code hashes".into(),
@@ -89,7 +102,13 @@ impl<M: Middleware> SimulationTraceCheck<M> for CodeHashes {
             .collect::<Vec<Address>>();
 
         let hashes: &mut Vec<CodeHash> = &mut vec![];
-        self.get_code_hashes(addrs, hashes, &helper.entry_point.eth_client()).await?;
+        self.get_code_hashes(
+            addrs,
+            hashes,
+            &helper.entry_point.eth_client(),
+            helper.code_hash_cache,
+        )
+        .await?;
 
         match mempool.has_code_hashes(&uo.hash) {
             Ok(true) => {
@@ -118,3 +137,40 @@ impl<M: Middleware> SimulationTraceCheck<M> for CodeHashes {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::Bytes,
+    };
+
+    #[tokio::test]
+    async fn code_hashes_cached_per_block() {
+        let (provider, mock) = Provider::<MockProvider>::mocked();
+        let eth_client = Arc::new(provider);
+        let cache = CodeHashCache::default();
+        let (sender, paymaster) = (Address::random(), Address::random());
+
+        // only the first user operation fetches the code, the provider fails on any other request
+        for _ in 0..2 {
+            mock.push::<Bytes, _>(Bytes::from(vec![0x60, 0x80])).unwrap();
+        }
+        for _ in 0..100 {
+            let hashes = &mut vec![];
+            CodeHashes
+                .get_code_hashes(vec![sender, paymaster], hashes, &eth_client, &cache)
+                .await
+                .unwrap();
+            assert_eq!(hashes.len(), 2);
+        }
+        assert_eq!(cache.len(), 2);
+
+        cache.invalidate_for_block(1);
+        assert!(CodeHashes
+            .get_code_hashes(vec![sender], &mut vec![], &eth_client, &cache)
+            .await
+            .is_err());
+    }
+}
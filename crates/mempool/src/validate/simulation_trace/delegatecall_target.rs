@@ -0,0 +1,206 @@
+use crate::{
+    mempool::Mempool,
+    validate::{
+        simulation_trace::{call_type_check::IMPLEMENTATION_SLOT, depth_tracker::CallFrame},
+        SimulationTraceCheck, SimulationTraceHelper,
+    },
+    Reputation, SimulationError,
+};
+use ethers::{
+    providers::Middleware,
+    types::{Address, H256},
+};
+use silius_primitives::{
+    constants::validation::entities::{FACTORY, PAYMASTER, SENDER},
+    get_address, UserOperation,
+};
+use std::{collections::HashSet, str::FromStr};
+
+const DELEGATECALL_OPCODE: &str = "DELEGATECALL";
+
+/// Restricts the targets of `DELEGATECALL` during validation.
+///
+/// A `DELEGATECALL` runs the code of the target in the context (storage and balance) of the
+/// caller, so it is only allowed to the sender itself (upgradeable proxies), to the EIP-1967
+/// implementation of the sender and to the configured trusted implementations. User operations
+/// deploying the sender (with `initCode`) are skipped.
+#[derive(Clone, Default)]
+pub struct DelegatecallTargetCheck {
+    /// Implementations every account may delegate to
+    pub trusted_implementations: HashSet<Address>,
+}
+
+impl DelegatecallTargetCheck {
+    pub fn new(trusted_implementations: HashSet<Address>) -> Self {
+        Self { trusted_implementations }
+    }
+
+    /// The helper method that reads the implementation from the EIP-1967 slot of the sender.
+    ///
+    /// # Returns
+    /// The implementation address, None if the slot is empty (e.g. the sender isn't deployed)
+    pub(crate) async fn sender_implementation<M: Middleware>(
+        eth_client: &M,
+        sender: Address,
+    ) -> Result<Option<Address>, SimulationError> {
+        let slot = H256::from_str(IMPLEMENTATION_SLOT).expect("Implementation slot is valid");
+        let value = eth_client
+            .get_storage_at(sender, slot, None)
+            .await
+            .map_err(|e| SimulationError::Provider { inner: e.to_string() })?;
+
+        let implementation = Address::from(value);
+        Ok((!implementation.is_zero()).then_some(implementation))
+    }
+
+    /// The helper method that checks the targets of the `DELEGATECALL`s in the simulation trace.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `frames` - The call frames from the simulation trace
+    /// `trusted` - The addresses the user operation may delegate to
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    fn check_calls(
+        uo: &UserOperation,
+        frames: &[CallFrame],
+        trusted: &HashSet<Address>,
+    ) -> Result<(), SimulationError> {
+        let entity = |from: Address| match from {
+            addr if addr == uo.sender => SENDER.to_string(),
+            addr if Some(addr) == get_address(&uo.init_code) => FACTORY.to_string(),
+            addr if Some(addr) == get_address(&uo.paymaster_and_data) => PAYMASTER.to_string(),
+            addr => format!("{addr:?}"),
+        };
+
+        for frame in frames.iter().filter(|frame| frame.call_type == DELEGATECALL_OPCODE) {
+            if frame.to == uo.sender || trusted.contains(&frame.to) {
+                continue;
+            }
+
+            return Err(SimulationError::UntrustedDelegatecall {
+                from: frame.from,
+                to: frame.to,
+                entity: entity(frame.from),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for DelegatecallTargetCheck {
+    /// The method implementation that checks the targets of the `DELEGATECALL`s.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `helper` - The [SimulationTraceHelper](crate::validate::SimulationTraceHelper)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        // the sender isn't deployed yet, so it has no implementation to delegate to and the
+        // factory's calls are covered by the other checks
+        if !uo.init_code.is_empty() {
+            return Ok(());
+        }
+
+        let frames = &helper.context.call_frames;
+        if !frames.iter().any(|frame| frame.call_type == DELEGATECALL_OPCODE) {
+            return Ok(());
+        }
+
+        let mut trusted = self.trusted_implementations.clone();
+        let eth_client = helper.entry_point.eth_client();
+        if let Some(implementation) =
+            Self::sender_implementation(eth_client.as_ref(), uo.sender).await?
+        {
+            trusted.insert(implementation);
+        }
+
+        Self::check_calls(uo, frames, &trusted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::utils::tests::check_simulation_trace;
+    use ethers::providers::{MockProvider, Provider};
+    use silius_contracts::tracer::Call;
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    fn call(typ: &str, from: Address, to: Address) -> Call {
+        Call { typ: typ.into(), from: Some(from), to: Some(to), ..Default::default() }
+    }
+
+    fn user_operation(init_code: Vec<u8>) -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default().sender(Address::random()).init_code(init_code.into()),
+        )
+    }
+
+    /// Runs the check on a trace where the sender delegates to `to`, the implementation slot of
+    /// the sender holds `implementation`
+    async fn check(
+        uo: &UserOperation,
+        to: Address,
+        implementation: Option<Address>,
+        trusted: HashSet<Address>,
+    ) -> Result<(), SimulationError> {
+        let (provider, mock) = Provider::<MockProvider>::mocked();
+        mock.push::<H256, _>(implementation.map(H256::from).unwrap_or_default()).unwrap();
+        let calls = vec![
+            call("CALL", Address::random(), uo.sender),
+            call(DELEGATECALL_OPCODE, uo.sender, to),
+        ];
+
+        check_simulation_trace(&DelegatecallTargetCheck::new(trusted), uo, calls, provider).await
+    }
+
+    #[tokio::test]
+    async fn self_delegatecall() {
+        let uo = user_operation(vec![]);
+        assert!(check(&uo, uo.sender, None, HashSet::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn known_implementation() {
+        let uo = user_operation(vec![]);
+        let implementation = Address::random();
+        assert!(check(&uo, implementation, Some(implementation), HashSet::default()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn trusted_implementation() {
+        let uo = user_operation(vec![]);
+        let implementation = Address::random();
+        assert!(check(&uo, implementation, None, HashSet::from([implementation])).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn unknown_address() {
+        let uo = user_operation(vec![]);
+        let to = Address::random();
+        assert!(matches!(
+            check(&uo, to, Some(Address::random()), HashSet::from([Address::random()])).await,
+            Err(SimulationError::UntrustedDelegatecall { from, to: addr, entity })
+                if from == uo.sender && addr == to && entity == SENDER
+        ));
+    }
+
+    #[tokio::test]
+    async fn undeployed_sender() {
+        let uo = user_operation(Address::random().as_bytes().to_vec());
+        assert!(check(&uo, Address::random(), None, HashSet::default()).await.is_ok());
+    }
+}
@@ -0,0 +1,165 @@
+use ethers::types::Address;
+use silius_contracts::tracer::Call;
+use silius_primitives::simulation::{CREATE2_OPCODE, CREATE_OPCODE, RETURN_OPCODE, REVERT_OPCODE};
+
+const CALL_OPCODE: &str = "CALL";
+const STATICCALL_OPCODE: &str = "STATICCALL";
+const DELEGATECALL_OPCODE: &str = "DELEGATECALL";
+const CALLCODE_OPCODE: &str = "CALLCODE";
+const STOP_OPCODE: &str = "STOP";
+
+/// Call frame opened in the simulation trace
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CallFrame {
+    /// Address the call is made on behalf of (inside a `DELEGATECALL` frame this is the
+    /// delegating account, not the library whose code runs)
+    pub from: Address,
+    /// Address of the called code
+    pub to: Address,
+    /// Opcode that opened the frame
+    pub call_type: String,
+    /// Number of frames the call is nested in
+    pub depth: usize,
+}
+
+impl CallFrame {
+    /// Address the frame executes as (storage, balance and `ADDRESS` of the frame)
+    pub fn context(&self) -> Address {
+        match self.call_type.as_str() {
+            DELEGATECALL_OPCODE | CALLCODE_OPCODE => self.from,
+            _ => self.to,
+        }
+    }
+}
+
+/// Rebuilds the call stack from the calls of the simulation trace
+///
+/// `DELEGATECALL` runs the code of the callee in the context of the caller, so the calls made
+/// from a delegated frame are attributed to the account that delegated, not to the library.
+#[derive(Clone, Debug, Default)]
+pub struct DepthTracker {
+    stack: Vec<CallFrame>,
+}
+
+impl DepthTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The innermost open frame
+    pub fn current(&self) -> Option<&CallFrame> {
+        self.stack.last()
+    }
+
+    /// Processes the next call of the trace
+    ///
+    /// # Returns
+    /// The frame opened by the call, None if the call closes a frame (or is not a call)
+    pub fn process(&mut self, call: &Call) -> Option<CallFrame> {
+        match call.typ.as_str() {
+            typ if typ == *RETURN_OPCODE || typ == *REVERT_OPCODE || typ == STOP_OPCODE => {
+                self.stack.pop();
+                None
+            }
+            typ if typ == CALL_OPCODE ||
+                typ == STATICCALL_OPCODE ||
+                typ == DELEGATECALL_OPCODE ||
+                typ == CALLCODE_OPCODE ||
+                typ == *CREATE_OPCODE ||
+                typ == *CREATE2_OPCODE =>
+            {
+                let from = match self.current() {
+                    Some(frame) => frame.context(),
+                    None => call.from.unwrap_or_default(),
+                };
+                let frame = CallFrame {
+                    from,
+                    to: call.to.unwrap_or_default(),
+                    call_type: call.typ.clone(),
+                    depth: self.stack.len(),
+                };
+                self.stack.push(frame.clone());
+                Some(frame)
+            }
+            _ => None,
+        }
+    }
+
+    /// All frames opened in the trace, in the order of the calls
+    pub fn call_frames(calls: &[Call]) -> Vec<CallFrame> {
+        let mut tracker = Self::new();
+        calls.iter().filter_map(|call| tracker.process(call)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(typ: &str, from: Address, to: Address) -> Call {
+        Call { typ: typ.into(), from: Some(from), to: Some(to), ..Default::default() }
+    }
+
+    fn ret() -> Call {
+        Call { typ: RETURN_OPCODE.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn nested_delegatecall_attribution() {
+        let (ep, sender, implementation, library, target) = (
+            Address::random(),
+            Address::random(),
+            Address::random(),
+            Address::random(),
+            Address::random(),
+        );
+        let other = Address::random();
+
+        // the trace reports the code address as the caller inside the delegated frames
+        let frames = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, sender),
+            call(DELEGATECALL_OPCODE, sender, implementation),
+            call(DELEGATECALL_OPCODE, implementation, library),
+            call(STATICCALL_OPCODE, library, target),
+            ret(),
+            ret(),
+            call(CALL_OPCODE, implementation, other),
+            ret(),
+            ret(),
+            ret(),
+        ]);
+
+        let expected = |from, to, call_type: &str, depth| CallFrame {
+            from,
+            to,
+            call_type: call_type.into(),
+            depth,
+        };
+        assert_eq!(
+            frames,
+            vec![
+                expected(ep, sender, CALL_OPCODE, 0),
+                expected(sender, implementation, DELEGATECALL_OPCODE, 1),
+                expected(sender, library, DELEGATECALL_OPCODE, 2),
+                expected(sender, target, STATICCALL_OPCODE, 3),
+                expected(sender, other, CALL_OPCODE, 2),
+            ]
+        );
+        assert_eq!(frames[2].context(), sender);
+        assert_eq!(frames[3].context(), target);
+    }
+
+    #[test]
+    fn stack_unwinds_on_revert() {
+        let (ep, sender, paymaster) = (Address::random(), Address::random(), Address::random());
+        let mut tracker = DepthTracker::new();
+
+        tracker.process(&call(CALL_OPCODE, ep, sender));
+        tracker.process(&Call { typ: REVERT_OPCODE.to_string(), ..Default::default() });
+        assert!(tracker.current().is_none());
+
+        let frame = tracker.process(&call(CALL_OPCODE, ep, paymaster)).unwrap();
+        assert_eq!(frame.depth, 0);
+        assert_eq!(tracker.current(), Some(&frame));
+    }
+}
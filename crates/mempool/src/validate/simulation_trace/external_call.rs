@@ -0,0 +1,279 @@
+use crate::{
+    mempool::Mempool,
+    validate::{
+        simulation_trace::{
+            delegatecall_target::DelegatecallTargetCheck, depth_tracker::CallFrame,
+        },
+        SimulationTraceCheck, SimulationTraceHelper,
+    },
+    Reputation, SimulationError,
+};
+use ethers::{providers::Middleware, types::Address};
+use silius_primitives::{
+    constants::validation::entities::{FACTORY, PAYMASTER, SENDER},
+    get_address, UserOperation,
+};
+use std::collections::HashMap;
+
+const CALL_OPCODE: &str = "CALL";
+const STATICCALL_OPCODE: &str = "STATICCALL";
+const DELEGATECALL_OPCODE: &str = "DELEGATECALL";
+
+/// Limits the calls entities make to external contracts during validation.
+///
+/// Every `CALL`/`STATICCALL` from an entity (sender, factory, paymaster) to an address outside the
+/// entity set, the entry point and the precompiles counts as an external call, the calls are
+/// counted separately in every frame the entity is called in. `DELEGATECALL` to such address is
+/// rejected, except for the sender delegating to its EIP-1967 implementation (proxy accounts).
+#[derive(Clone)]
+pub struct ExternalCallTracker {
+    /// Maximum number of external calls per entity frame
+    pub max_external_calls: usize,
+}
+
+impl Default for ExternalCallTracker {
+    fn default() -> Self {
+        Self { max_external_calls: 1 }
+    }
+}
+
+impl ExternalCallTracker {
+    /// The helper method that counts the external calls in each entity frame.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) whose entities are tracked
+    /// `entry_point` - The entry point address
+    /// `frames` - The call frames from the simulation trace
+    /// `implementation` - The implementation the sender may delegate to (if any)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    fn check_calls(
+        &self,
+        uo: &UserOperation,
+        entry_point: Address,
+        frames: &[CallFrame],
+        implementation: Option<Address>,
+    ) -> Result<(), SimulationError> {
+        let mut entities: HashMap<Address, &str> = HashMap::from([(uo.sender, SENDER)]);
+        if let Some(factory) = get_address(&uo.init_code) {
+            entities.insert(factory, FACTORY);
+        }
+        if let Some(paymaster) = get_address(&uo.paymaster_and_data) {
+            entities.insert(paymaster, PAYMASTER);
+        }
+
+        // [OP-062] - only the core precompiles are allowed
+        let is_trusted = |addr: &Address| {
+            entities.contains_key(addr) ||
+                *addr == entry_point ||
+                (*addr >= Address::from_low_u64_be(1) && *addr < Address::from_low_u64_be(10))
+        };
+
+        // frame the entity was last called in -> number of external calls made in the frame
+        let mut entity_frames: HashMap<Address, usize> = HashMap::new();
+        let mut counts: HashMap<usize, usize> = HashMap::new();
+
+        for (i, frame) in frames.iter().enumerate() {
+            let context = frame.context();
+            if entities.contains_key(&context) && frame.from != context {
+                entity_frames.insert(context, i);
+            }
+
+            let Some(entity) = entities.get(&frame.from) else {
+                continue;
+            };
+            let to = frame.to;
+            if is_trusted(&to) {
+                continue;
+            }
+
+            match frame.call_type.as_str() {
+                CALL_OPCODE | STATICCALL_OPCODE => {
+                    let entity_frame = entity_frames.get(&frame.from).copied().unwrap_or_default();
+                    let count = counts.entry(entity_frame).or_default();
+                    *count += 1;
+
+                    if *count > self.max_external_calls {
+                        return Err(SimulationError::TooManyExternalCalls {
+                            count: *count,
+                            max: self.max_external_calls,
+                            entity: entity.to_string(),
+                        });
+                    }
+                }
+                DELEGATECALL_OPCODE => {
+                    // the implementation of the account deployed by the user operation is set by
+                    // the factory, so it can't be read upfront
+                    if frame.from == uo.sender &&
+                        (Some(to) == implementation || !uo.init_code.is_empty())
+                    {
+                        continue;
+                    }
+
+                    return Err(SimulationError::UntrustedDelegatecall {
+                        from: frame.from,
+                        to,
+                        entity: entity.to_string(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for ExternalCallTracker {
+    /// The method implementation that performs the external calls check.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `helper` - The [SimulationTraceHelper](crate::validate::SimulationTraceHelper)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        let frames = &helper.context.call_frames;
+        let implementation = if uo.init_code.is_empty() &&
+            frames
+                .iter()
+                .any(|frame| frame.call_type == DELEGATECALL_OPCODE && frame.from == uo.sender)
+        {
+            let eth_client = helper.entry_point.eth_client();
+            DelegatecallTargetCheck::sender_implementation(eth_client.as_ref(), uo.sender).await?
+        } else {
+            None
+        };
+
+        self.check_calls(uo, helper.entry_point.address(), frames, implementation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::simulation_trace::depth_tracker::DepthTracker;
+    use silius_contracts::tracer::Call;
+    use silius_primitives::{UserOperationHash, UserOperationSigned};
+
+    fn call(typ: &str, from: Address, to: Address) -> Call {
+        Call { typ: typ.into(), from: Some(from), to: Some(to), ..Default::default() }
+    }
+
+    fn ret() -> Call {
+        Call { typ: "RETURN".into(), ..Default::default() }
+    }
+
+    fn setup() -> (UserOperation, Address) {
+        let uo = UserOperationSigned::default().sender(Address::random());
+        (
+            UserOperation::from_user_operation_signed(UserOperationHash::default(), uo),
+            Address::random(),
+        )
+    }
+
+    #[test]
+    fn no_external_calls() {
+        let (uo, ep) = setup();
+        let calls = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, uo.sender),
+            call(STATICCALL_OPCODE, uo.sender, Address::from_low_u64_be(1)),
+            ret(),
+            call(CALL_OPCODE, uo.sender, ep),
+            ret(),
+            ret(),
+        ]);
+        assert!(ExternalCallTracker::default().check_calls(&uo, ep, &calls, None).is_ok());
+    }
+
+    #[test]
+    fn one_external_call() {
+        let (uo, ep) = setup();
+        let calls = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, uo.sender),
+            call(STATICCALL_OPCODE, uo.sender, Address::random()),
+            ret(),
+            ret(),
+        ]);
+        assert!(ExternalCallTracker::default().check_calls(&uo, ep, &calls, None).is_ok());
+    }
+
+    #[test]
+    fn two_external_calls() {
+        let (uo, ep) = setup();
+        let calls = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, uo.sender),
+            call(STATICCALL_OPCODE, uo.sender, Address::random()),
+            ret(),
+            call(CALL_OPCODE, uo.sender, Address::random()),
+            ret(),
+            ret(),
+        ]);
+        assert!(matches!(
+            ExternalCallTracker::default().check_calls(&uo, ep, &calls, None),
+            Err(SimulationError::TooManyExternalCalls { count: 2, max: 1, entity }) if entity == SENDER
+        ));
+        assert!(ExternalCallTracker { max_external_calls: 2 }
+            .check_calls(&uo, ep, &calls, None)
+            .is_ok());
+    }
+
+    #[test]
+    fn delegate_call_to_external_address() {
+        let (uo, ep) = setup();
+        let calls = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, uo.sender),
+            call(DELEGATECALL_OPCODE, uo.sender, Address::random()),
+            ret(),
+            ret(),
+        ]);
+        assert!(matches!(
+            ExternalCallTracker::default().check_calls(&uo, ep, &calls, None),
+            Err(SimulationError::UntrustedDelegatecall { from, entity, .. })
+                if from == uo.sender && entity == SENDER
+        ));
+    }
+
+    #[test]
+    fn delegate_call_to_sender_implementation() {
+        let (uo, ep) = setup();
+        let implementation = Address::random();
+        let calls = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, uo.sender),
+            call(DELEGATECALL_OPCODE, uo.sender, implementation),
+            // the implementation code runs in the context of the sender
+            call(STATICCALL_OPCODE, uo.sender, Address::random()),
+            ret(),
+            ret(),
+            ret(),
+        ]);
+        let tracker = ExternalCallTracker::default();
+        assert!(tracker.check_calls(&uo, ep, &calls, Some(implementation)).is_ok());
+    }
+
+    #[test]
+    fn external_calls_counted_per_entity_frame() {
+        let (uo, ep) = setup();
+        // the sender is called twice, with one external call in each frame
+        let calls = DepthTracker::call_frames(&[
+            call(CALL_OPCODE, ep, uo.sender),
+            call(STATICCALL_OPCODE, uo.sender, Address::random()),
+            ret(),
+            ret(),
+            call(CALL_OPCODE, ep, uo.sender),
+            call(CALL_OPCODE, uo.sender, Address::random()),
+            ret(),
+            ret(),
+        ]);
+        assert!(ExternalCallTracker::default().check_calls(&uo, ep, &calls, None).is_ok());
+    }
+}
@@ -0,0 +1,172 @@
+use crate::{
+    mempool::Mempool,
+    validate::{
+        simulation_trace::depth_tracker::DepthTracker, SimulationTraceCheck, SimulationTraceHelper,
+    },
+    Reputation, SimulationError,
+};
+use ethers::{providers::Middleware, types::Address};
+use silius_contracts::tracer::Call;
+use silius_primitives::{
+    constants::validation::entities::{FACTORY, PAYMASTER, SENDER},
+    get_address, UserOperation,
+};
+
+const CALL_OPCODE: &str = "CALL";
+const STATICCALL_OPCODE: &str = "STATICCALL";
+
+/// Default minimum percentage of the available gas a call has to forward
+const MIN_GAS_RATIO: u8 = 50;
+
+/// Rejects user operations whose validation starves the calls it makes.
+///
+/// A `CALL` or `STATICCALL` made with a tiny gas stipend fails in the callee while the caller
+/// keeps the gas, which lets validation behave differently under the bundle's gas limits. The
+/// trace doesn't record the gas left at the call, so the gas the calling frame received is taken
+/// as the available gas. Calls made by the entry point itself are not checked.
+#[derive(Clone)]
+pub struct InnerCallGas {
+    /// Minimum percentage of the available gas a call has to forward
+    pub min_gas_ratio: u8,
+}
+
+impl Default for InnerCallGas {
+    fn default() -> Self {
+        Self { min_gas_ratio: MIN_GAS_RATIO }
+    }
+}
+
+impl InnerCallGas {
+    /// The helper method that checks the gas forwarded by the calls of the simulation trace.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `calls` - The calls from the simulation trace
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    fn check_calls(&self, uo: &UserOperation, calls: &[Call]) -> Result<(), SimulationError> {
+        let entity = |from: Address| match from {
+            addr if addr == uo.sender => SENDER.to_string(),
+            addr if Some(addr) == get_address(&uo.init_code) => FACTORY.to_string(),
+            addr if Some(addr) == get_address(&uo.paymaster_and_data) => PAYMASTER.to_string(),
+            addr => format!("{addr:?}"),
+        };
+
+        let mut tracker = DepthTracker::new();
+        // gas received by the open frames, indexed by depth
+        let mut frame_gas: Vec<Option<u64>> = vec![];
+
+        for call in calls.iter() {
+            let Some(frame) = tracker.process(call) else {
+                continue;
+            };
+            frame_gas.truncate(frame.depth);
+            frame_gas.push(call.gas);
+
+            if frame.depth == 0 ||
+                (frame.call_type != CALL_OPCODE && frame.call_type != STATICCALL_OPCODE)
+            {
+                continue;
+            }
+
+            if let (Some(available), Some(forwarded)) = (frame_gas[frame.depth - 1], call.gas) {
+                if (forwarded as u128) * 100 < (available as u128) * self.min_gas_ratio as u128 {
+                    return Err(SimulationError::InsufficientGasForwardedToCall {
+                        available,
+                        forwarded,
+                        entity: entity(frame.from),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for InnerCallGas {
+    /// The method implementation that checks the gas forwarded to inner calls.
+    ///
+    /// # Arguments
+    /// `uo` - The [UserOperation](UserOperation) to check
+    /// `helper` - The [SimulationTraceHelper](crate::validate::SimulationTraceHelper)
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation(
+        &self,
+        uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        self.check_calls(uo, &helper.js_trace.calls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use silius_primitives::{simulation::RETURN_OPCODE, UserOperationHash, UserOperationSigned};
+
+    fn call(typ: &str, from: Address, to: Address, gas: u64) -> Call {
+        Call {
+            typ: typ.into(),
+            from: Some(from),
+            to: Some(to),
+            gas: Some(gas),
+            ..Default::default()
+        }
+    }
+
+    fn ret() -> Call {
+        Call { typ: RETURN_OPCODE.to_string(), ..Default::default() }
+    }
+
+    fn user_operation() -> UserOperation {
+        UserOperation::from_user_operation_signed(
+            UserOperationHash::default(),
+            UserOperationSigned::default().sender(Address::random()),
+        )
+    }
+
+    #[test]
+    fn call_forwards_too_little_gas() {
+        let uo = user_operation();
+        let ep = Address::random();
+
+        let calls = [
+            call(CALL_OPCODE, ep, uo.sender, 100_000),
+            call(CALL_OPCODE, uo.sender, Address::random(), 1000),
+            ret(),
+            ret(),
+        ];
+        assert!(matches!(
+            InnerCallGas::default().check_calls(&uo, &calls),
+            Err(SimulationError::InsufficientGasForwardedToCall { available, forwarded, entity })
+                if available == 100_000 && forwarded == 1000 && entity == SENDER
+        ));
+    }
+
+    #[test]
+    fn call_forwards_enough_gas() {
+        let uo = user_operation();
+        let (ep, paymaster) = (Address::random(), Address::random());
+
+        // frames are unwound, the second call is compared with the gas of the paymaster frame
+        let calls = [
+            call(CALL_OPCODE, ep, uo.sender, 100_000),
+            call(STATICCALL_OPCODE, uo.sender, Address::random(), 90_000),
+            ret(),
+            ret(),
+            call(CALL_OPCODE, ep, paymaster, 20_000),
+            call(STATICCALL_OPCODE, paymaster, Address::random(), 10_000),
+            ret(),
+            ret(),
+        ];
+        assert!(InnerCallGas::default().check_calls(&uo, &calls).is_ok());
+        assert!(InnerCallGas { min_gas_ratio: 51 }.check_calls(&uo, &calls).is_err());
+    }
+}
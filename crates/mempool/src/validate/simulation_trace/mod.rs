@@ -1,9 +1,17 @@
 //! `SimulationTrace` module performs checks against a user operation's call stack,
 //! code hashes, external contract access, gas, opcodes, and storage access by initiating a
 //! `debug_traceCall` to a Ethereum execution client.
+pub mod balance_change;
+pub mod banned_accounts;
 pub mod call_stack;
+pub mod call_type_check;
 pub mod code_hashes;
+pub mod delegatecall_target;
+pub mod depth_tracker;
+pub mod external_call;
 pub mod external_contracts;
 pub mod gas;
+pub mod inner_call_gas;
 pub mod opcodes;
 pub mod storage_access;
+pub mod timestamp_check;
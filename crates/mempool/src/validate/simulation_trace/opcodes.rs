@@ -3,7 +3,7 @@ use crate::{
     Mempool, Reputation, SimulationError,
 };
 use ethers::providers::Middleware;
-use silius_contracts::entry_point::SELECTORS_INDICES;
+use silius_contracts::{entry_point::SELECTORS_INDICES, tracer::TopLevelCallInfo};
 use silius_primitives::{
     constants::validation::entities::{FACTORY, LEVEL_TO_ENTITY},
     simulation::{CREATE2_OPCODE, FORBIDDEN_OPCODES},
@@ -13,24 +13,16 @@ use silius_primitives::{
 #[derive(Clone)]
 pub struct Opcodes;
 
-#[async_trait::async_trait]
-impl<M: Middleware> SimulationTraceCheck<M> for Opcodes {
-    /// The method implementation that checks the use of forbidden opcodes
+impl Opcodes {
+    /// The helper method that checks the opcodes used by the validation frames.
     ///
     /// # Arguments
-    /// `_uo` - Not used
-    /// `helper` - The [SimulationTraceHelper]
+    /// `calls_from_entry_point` - The top level calls from the simulation trace
     ///
     /// # Returns
     /// None if the check passes, otherwise a [SimulationError] error.
-    async fn check_user_operation(
-        &self,
-        _uo: &UserOperation,
-        _mempool: &Mempool,
-        _reputation: &Reputation,
-        helper: &mut SimulationTraceHelper<M>,
-    ) -> Result<(), SimulationError> {
-        for call_info in helper.js_trace.calls_from_entry_point.iter() {
+    fn check_levels(calls_from_entry_point: &[TopLevelCallInfo]) -> Result<(), SimulationError> {
+        for call_info in calls_from_entry_point.iter() {
             let level = SELECTORS_INDICES.get(call_info.top_level_method_sig.as_ref()).cloned();
 
             if let Some(l) = level {
@@ -61,3 +53,71 @@ impl<M: Middleware> SimulationTraceCheck<M> for Opcodes {
         Ok(())
     }
 }
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for Opcodes {
+    /// The method implementation that checks the use of forbidden opcodes
+    ///
+    /// # Arguments
+    /// `_uo` - Not used
+    /// `helper` - The [SimulationTraceHelper]
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation(
+        &self,
+        _uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        Self::check_levels(&helper.js_trace.calls_from_entry_point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Bytes;
+    use silius_primitives::constants::validation::entities::SENDER;
+    use std::collections::HashMap;
+
+    fn level(entity: &str, opcodes: &[(&str, u64)]) -> TopLevelCallInfo {
+        let (selector, _) =
+            SELECTORS_INDICES.iter().find(|(_, l)| LEVEL_TO_ENTITY[**l] == entity).unwrap();
+        TopLevelCallInfo {
+            top_level_method_sig: Bytes::from(selector.to_vec()),
+            opcodes: opcodes.iter().map(|(op, c)| (op.to_string(), *c)).collect::<HashMap<_, _>>(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn forbidden_opcodes() {
+        for op in ["SELFDESTRUCT", "BLOBHASH", "BLOBBASEFEE", "ORIGIN"] {
+            let res = Opcodes::check_levels(&[level(SENDER, &[("CALL", 1), (op, 1)])]);
+            assert!(matches!(
+                res,
+                Err(SimulationError::Opcode { entity, opcode }) if entity == SENDER && opcode == op
+            ));
+        }
+    }
+
+    #[test]
+    fn allowed_opcodes() {
+        assert!(Opcodes::check_levels(&[level(SENDER, &[("CALL", 2), ("SLOAD", 3)])]).is_ok());
+    }
+
+    #[test]
+    fn create2() {
+        assert!(Opcodes::check_levels(&[level(FACTORY, &[(CREATE2_OPCODE.as_str(), 1)])]).is_ok());
+        assert!(matches!(
+            Opcodes::check_levels(&[level(FACTORY, &[(CREATE2_OPCODE.as_str(), 2)])]),
+            Err(SimulationError::Opcode { entity, .. }) if entity == FACTORY
+        ));
+        assert!(matches!(
+            Opcodes::check_levels(&[level(SENDER, &[(CREATE2_OPCODE.as_str(), 1)])]),
+            Err(SimulationError::Opcode { entity, .. }) if entity == SENDER
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     mempool::Mempool,
-    validate::{utils::extract_stake_info, SimulationTraceCheck, SimulationTraceHelper},
+    validate::{SimulationTraceCheck, SimulationTraceHelper},
     Reputation, SimulationError,
 };
 use ethers::{
@@ -110,19 +110,12 @@ impl<M: Middleware> SimulationTraceCheck<M> for StorageAccess {
         _reputation: &Reputation,
         helper: &mut SimulationTraceHelper<M>,
     ) -> Result<(), SimulationError> {
-        if helper.stake_info.is_none() {
-            helper.stake_info = Some(extract_stake_info(uo, helper.simulate_validation_result));
-        }
+        let stake_info = helper.context.stake_info();
 
         let mut slots = HashMap::new();
-        self.parse_slots(
-            helper.js_trace.keccak.clone(),
-            &helper.stake_info.unwrap_or_default(),
-            &mut slots,
-        );
+        self.parse_slots(helper.js_trace.keccak.clone(), &stake_info, &mut slots);
 
         let mut slot_staked = String::new();
-        let stake_info = helper.stake_info.unwrap_or_default();
 
         for call_info in helper.js_trace.calls_from_entry_point.iter() {
             let level = SELECTORS_INDICES.get(call_info.top_level_method_sig.as_ref()).cloned();
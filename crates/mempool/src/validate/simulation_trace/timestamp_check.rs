@@ -0,0 +1,107 @@
+use crate::{
+    validate::{SimulationTraceCheck, SimulationTraceHelper},
+    Mempool, Reputation, SimulationError,
+};
+use ethers::providers::Middleware;
+use silius_contracts::{entry_point::SELECTORS_INDICES, tracer::TopLevelCallInfo};
+use silius_primitives::{constants::validation::entities::LEVEL_TO_ENTITY, UserOperation};
+
+const TIMESTAMP_OPCODE: &str = "TIMESTAMP";
+
+/// Hint returned with [SimulationError::TimestampForbidden]
+const TIMESTAMP_HINT: &str = "block.timestamp can't be used during validation, return validUntil and validAfter in the validation data instead and the entry point will enforce the time range";
+
+/// Rejects the `TIMESTAMP` opcode in the validation frames with a hint pointing to the
+/// `validUntil`/`validAfter` mechanism, since wallets often read the timestamp to check
+/// signature expiry.
+#[derive(Clone)]
+pub struct TimestampOpcodeCheck;
+
+impl TimestampOpcodeCheck {
+    /// The helper method that checks the opcodes of the validation frames.
+    ///
+    /// # Arguments
+    /// `calls_from_entry_point` - The top level calls from the simulation trace
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    fn check_levels(calls_from_entry_point: &[TopLevelCallInfo]) -> Result<(), SimulationError> {
+        for call_info in calls_from_entry_point.iter() {
+            let level = SELECTORS_INDICES.get(call_info.top_level_method_sig.as_ref()).cloned();
+
+            if let Some(l) = level {
+                if call_info.opcodes.contains_key(TIMESTAMP_OPCODE) {
+                    return Err(SimulationError::TimestampForbidden {
+                        entity: LEVEL_TO_ENTITY[l].to_string(),
+                        hint: TIMESTAMP_HINT.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl<M: Middleware> SimulationTraceCheck<M> for TimestampOpcodeCheck {
+    /// The method implementation that checks the use of the `TIMESTAMP` opcode
+    ///
+    /// # Arguments
+    /// `_uo` - Not used
+    /// `helper` - The [SimulationTraceHelper]
+    ///
+    /// # Returns
+    /// None if the check passes, otherwise a [SimulationError] error.
+    async fn check_user_operation(
+        &self,
+        _uo: &UserOperation,
+        _mempool: &Mempool,
+        _reputation: &Reputation,
+        helper: &mut SimulationTraceHelper<M>,
+    ) -> Result<(), SimulationError> {
+        Self::check_levels(&helper.js_trace.calls_from_entry_point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Bytes;
+    use silius_primitives::constants::validation::entities::SENDER;
+    use std::collections::HashMap;
+
+    fn sender_level(opcodes: &[&str]) -> TopLevelCallInfo {
+        let (selector, _) =
+            SELECTORS_INDICES.iter().find(|(_, l)| LEVEL_TO_ENTITY[**l] == SENDER).unwrap();
+        TopLevelCallInfo {
+            top_level_method_sig: Bytes::from(selector.to_vec()),
+            opcodes: opcodes.iter().map(|op| (op.to_string(), 1)).collect::<HashMap<_, _>>(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn timestamp_in_validation() {
+        let res = TimestampOpcodeCheck::check_levels(&[sender_level(&["CALL", "TIMESTAMP"])]);
+        assert!(matches!(
+            res,
+            Err(SimulationError::TimestampForbidden { entity, hint })
+                if entity == SENDER && hint.contains("validUntil") && hint.contains("validAfter")
+        ));
+    }
+
+    #[test]
+    fn no_timestamp_in_validation() {
+        assert!(TimestampOpcodeCheck::check_levels(&[sender_level(&["CALL", "SLOAD"])]).is_ok());
+    }
+
+    #[test]
+    fn timestamp_outside_validation() {
+        let level = TopLevelCallInfo {
+            opcodes: HashMap::from([(TIMESTAMP_OPCODE.to_string(), 1)]),
+            ..Default::default()
+        };
+        assert!(TimestampOpcodeCheck::check_levels(&[level]).is_ok());
+    }
+}
@@ -1,8 +1,14 @@
 use ethers::types::{Address, U256};
-use silius_contracts::{entry_point::SimulateValidationResult, tracer::JsTracerFrame};
+use silius_contracts::{
+    entry_point::{SimulateValidationResult, SELECTORS_NAMES},
+    tracer::{Call, JsTracerFrame},
+};
 use silius_primitives::{
-    constants::validation::entities::NUMBER_OF_LEVELS, get_address, reputation::StakeInfo,
-    simulation::StorageMap, UserOperation,
+    constants::validation::entities::NUMBER_OF_LEVELS,
+    get_address,
+    reputation::StakeInfo,
+    simulation::{StorageMap, RETURN_OPCODE, REVERT_OPCODE, VALIDATE_PAYMASTER_USER_OP_FUNCTION},
+    UserOperation,
 };
 
 /// Helper function to extract the gas limit for verification from the simulation result
@@ -33,6 +39,22 @@ pub fn extract_pre_fund(sim_res: &SimulateValidationResult) -> U256 {
     }
 }
 
+/// Helper function to extract the signature aggregator from the simulation result
+///
+/// # Arguments
+/// `sim_res` - The [simulation result](SimulateValidationResult) from the simulation
+///
+/// # Returns
+/// The address of the aggregator, None if the user operation doesn't use an aggregator
+pub fn extract_aggregator(sim_res: &SimulateValidationResult) -> Option<Address> {
+    match sim_res {
+        SimulateValidationResult::ValidationResult(_) => None,
+        SimulateValidationResult::ValidationResultWithAggregation(res) => {
+            Some(res.aggregator_info.0).filter(|aggregator| !aggregator.is_zero())
+        }
+    }
+}
+
 /// Helper function to extract the post-fund for verification from the simulation result
 ///
 /// # Arguments
@@ -111,6 +133,34 @@ pub fn extract_storage_map(js_trace: &JsTracerFrame) -> StorageMap {
     storage_map
 }
 
+/// Helper function to extract the gas used by the `validatePaymasterUserOp` call from the
+/// simulation trace
+///
+/// # Arguments
+/// `js_trace` - The [js tracer frame](JsTracerFrame) of the simulation
+///
+/// # Returns
+/// The gas used by the paymaster, None if the paymaster wasn't called
+pub fn extract_paymaster_gas_used(js_trace: &JsTracerFrame) -> Option<U256> {
+    let mut st: Vec<&Call> = vec![];
+    let mut gas_used = None;
+
+    for call in js_trace.calls.iter() {
+        if call.typ == *REVERT_OPCODE || call.typ == *RETURN_OPCODE {
+            let is_paymaster = st.pop().and_then(|top| top.method.as_ref()).is_some_and(|m| {
+                SELECTORS_NAMES.get(m.as_ref()) == Some(&*VALIDATE_PAYMASTER_USER_OP_FUNCTION)
+            });
+            if is_paymaster {
+                gas_used = Some(call.gas_used.unwrap_or_default().into());
+            }
+        } else {
+            st.push(call);
+        }
+    }
+
+    gas_used
+}
+
 /// Helper function to merge multiple storage maps into one.
 ///
 /// # Arguments
@@ -145,3 +195,197 @@ pub fn merge_storage_maps(storage_maps: Vec<StorageMap>) -> StorageMap {
 
     merged_map
 }
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        mempool::Mempool,
+        utils::ChainPreVerificationGas,
+        validate::{
+            simulation::{code_hash_cache::CodeHashCache, simulation_helper::MockEntryPoint},
+            utils::extract_paymaster_gas_used,
+            SanityCheck, SanityHelper, SimulationTraceCheck, SimulationTraceHelper,
+        },
+        ReplacementPolicy, Reputation, SanityError, SimulationError,
+    };
+    use alloy_chains::Chain;
+    use ethers::{
+        providers::{MockProvider, Provider},
+        types::{Address, U256},
+    };
+    use parking_lot::RwLock;
+    use silius_contracts::{
+        entry_point::SELECTORS_NAMES,
+        tracer::{Call, JsTracerFrame},
+        EntryPoint,
+    };
+    use silius_primitives::{
+        constants::validation::reputation::{
+            BAN_SLACK, MIN_INCLUSION_RATE_DENOMINATOR, THROTTLING_SLACK,
+        },
+        reputation::ReputationEntry,
+        simulation::{
+            CodeHash, ValidationConfig, RETURN_OPCODE, REVERT_OPCODE,
+            VALIDATE_PAYMASTER_USER_OP_FUNCTION,
+        },
+        EntryPointVersion, UserOperation, UserOperationHash, UserOperationReceipt,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    };
+
+    /// Empty mempool backed by the in-memory tables
+    pub fn memory_mempool() -> Mempool {
+        Mempool::new(
+            Box::new(HashMap::<UserOperationHash, UserOperation>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<Address, HashSet<UserOperationHash>>::default()),
+            Box::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()),
+            Box::new(HashMap::<(Address, U256), UserOperationHash>::default()),
+            Box::new(HashMap::<UserOperationHash, UserOperationReceipt>::default()),
+        )
+    }
+
+    /// Empty reputation backed by the in-memory table
+    pub fn memory_reputation() -> Reputation {
+        Reputation::new(
+            MIN_INCLUSION_RATE_DENOMINATOR,
+            THROTTLING_SLACK,
+            BAN_SLACK,
+            U256::from(1),
+            U256::from(0),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            Arc::new(RwLock::new(HashSet::<Address>::default())),
+            Box::new(HashMap::<Address, ReputationEntry>::default()),
+        )
+    }
+
+    /// Runs the sanity check on the user operation, the calls of the entry point are answered by
+    /// the mocked provider
+    pub async fn check_sanity<C: SanityCheck<Provider<MockProvider>>>(
+        check: &C,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        provider: Provider<MockProvider>,
+    ) -> Result<(), SanityError> {
+        check_sanity_on_chain(check, uo, mempool, provider, ChainPreVerificationGas::default())
+            .await
+    }
+
+    /// Runs the sanity check on the user operation with the L1 calldata pricing of an L2
+    pub async fn check_sanity_on_chain<C: SanityCheck<Provider<MockProvider>>>(
+        check: &C,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        provider: Provider<MockProvider>,
+        chain_pre_verification_gas: ChainPreVerificationGas,
+    ) -> Result<(), SanityError> {
+        check_sanity_on_entry_point(
+            check,
+            uo,
+            mempool,
+            provider,
+            chain_pre_verification_gas,
+            EntryPointVersion::V0_6,
+        )
+        .await
+    }
+
+    /// Runs the sanity check on the user operation submitted to an entry point of the version
+    pub async fn check_sanity_on_entry_point<C: SanityCheck<Provider<MockProvider>>>(
+        check: &C,
+        uo: &UserOperation,
+        mempool: &Mempool,
+        provider: Provider<MockProvider>,
+        chain_pre_verification_gas: ChainPreVerificationGas,
+        entry_point_version: EntryPointVersion,
+    ) -> Result<(), SanityError> {
+        let reputation = memory_reputation();
+        let entry_point = EntryPoint::new(Arc::new(provider), Address::random())
+            .with_version(entry_point_version);
+        let helper = SanityHelper {
+            entry_point: &entry_point,
+            entry_point_version,
+            chain: Chain::dev(),
+            val_config: ValidationConfig::default(),
+            replacement_policy: ReplacementPolicy::default(),
+            chain_pre_verification_gas,
+        };
+
+        check.check_user_operation(uo, mempool, &reputation, &helper).await
+    }
+
+    /// Runs the simulation trace check on the user operation with the calls of the trace, the
+    /// calls of the entry point are answered by the mocked provider
+    pub async fn check_simulation_trace<C: SimulationTraceCheck<Provider<MockProvider>>>(
+        check: &C,
+        uo: &UserOperation,
+        calls: Vec<Call>,
+        provider: Provider<MockProvider>,
+    ) -> Result<(), SimulationError> {
+        let mempool = memory_mempool();
+        let reputation = memory_reputation();
+        let entry_point = EntryPoint::new(Arc::new(provider), Address::random());
+        let js_trace = JsTracerFrame { calls, ..Default::default() };
+        let mut context = MockEntryPoint::new().simulation_context(uo)?;
+        context.set_trace(&js_trace);
+        let code_hash_cache = CodeHashCache::default();
+        let mut helper = SimulationTraceHelper {
+            entry_point: &entry_point,
+            context: &context,
+            js_trace: &js_trace,
+            val_config: ValidationConfig::default(),
+            code_hashes: None,
+            code_hash_cache: &code_hash_cache,
+        };
+
+        check.check_user_operation(uo, &mempool, &reputation, &mut helper).await
+    }
+
+    /// Call of the method of the entry point or an entity
+    fn call(method: &str, gas_used: u64) -> Call {
+        let selector = SELECTORS_NAMES
+            .iter()
+            .find(|(_, name)| *name == method)
+            .map(|(selector, _)| selector.to_vec())
+            .unwrap();
+        Call { typ: "CALL".into(), method: Some(selector.into()), ..ret(gas_used) }
+    }
+
+    fn ret(gas_used: u64) -> Call {
+        Call { typ: RETURN_OPCODE.to_string(), gas_used: Some(gas_used), ..Default::default() }
+    }
+
+    #[test]
+    fn paymaster_gas_used() {
+        // the nested call of the paymaster returns first, only the frame of the paymaster
+        // validation counts
+        let calls = vec![
+            call("validateUserOp", 0),
+            ret(20_000),
+            call(&VALIDATE_PAYMASTER_USER_OP_FUNCTION, 0),
+            call("balanceOf", 0),
+            ret(2_600),
+            ret(15_000),
+        ];
+        let js_trace = JsTracerFrame { calls, ..Default::default() };
+        assert_eq!(extract_paymaster_gas_used(&js_trace), Some(U256::from(15_000)));
+
+        // the reverted validation is counted too
+        let calls = vec![
+            call(&VALIDATE_PAYMASTER_USER_OP_FUNCTION, 0),
+            Call { typ: REVERT_OPCODE.to_string(), ..ret(8_000) },
+        ];
+        let js_trace = JsTracerFrame { calls, ..Default::default() };
+        assert_eq!(extract_paymaster_gas_used(&js_trace), Some(U256::from(8_000)));
+
+        // no paymaster
+        let js_trace = JsTracerFrame {
+            calls: vec![call("validateUserOp", 0), ret(20_000)],
+            ..Default::default()
+        };
+        assert_eq!(extract_paymaster_gas_used(&js_trace), None);
+    }
+}
@@ -1,50 +1,110 @@
 use super::{
     sanity::{
-        call_gas::CallGas, entities::Entities, max_fee::MaxFee, paymaster::Paymaster,
-        sender::Sender, unstaked_entities::UnstakedEntities, verification_gas::VerificationGas,
+        call_data_min::CallGasMinimum, call_gas::CallGas, duplicate::DuplicateCheck,
+        entities::Entities, factory_stake::FactoryStake, init_code_factory::InitCodeFactory,
+        max_call_data_gas::MaxCallDataGas, max_call_data_size::MaxCallDataSize, max_fee::MaxFee,
+        nonce_gap::NonceGap, paymaster::Paymaster, paymaster_balance::PaymasterBalance,
+        paymaster_postop::PaymasterPostOp, paymaster_stake::PaymasterStake, sender::Sender,
+        sender_balance::SenderBalance, sender_stake::SenderStake, signature::SignatureFormat,
+        verification_gas::VerificationGas,
     },
     simulation::{
-        signature::Signature, timestamp::Timestamp, verification_extra_gas::VerificationExtraGas,
+        aggregator_staked::AggregatorStaked, code_hash_cache::CodeHashCache,
+        context::SimulationContext, signature::Signature, simulation_cache::SimulationCache,
+        timestamp::Timestamp, verification_extra_gas::VerificationExtraGas,
     },
     simulation_trace::{
-        call_stack::CallStack, code_hashes::CodeHashes, external_contracts::ExternalContracts,
-        gas::Gas, opcodes::Opcodes, storage_access::StorageAccess,
+        balance_change::BalanceChange, banned_accounts::BannedAccountCallCheck,
+        call_stack::CallStack, call_type_check::CallTypeCheck, code_hashes::CodeHashes,
+        delegatecall_target::DelegatecallTargetCheck, external_call::ExternalCallTracker,
+        external_contracts::ExternalContracts, gas::Gas, inner_call_gas::InnerCallGas,
+        opcodes::Opcodes, storage_access::StorageAccess, timestamp_check::TimestampOpcodeCheck,
+    },
+    utils::{
+        extract_aggregator, extract_paymaster_gas_used, extract_pre_fund, extract_storage_map,
+        extract_timestamps, extract_verification_gas_limit,
     },
-    utils::{extract_pre_fund, extract_storage_map, extract_verification_gas_limit},
     SanityCheck, SanityHelper, SimulationCheck, SimulationHelper, SimulationTraceCheck,
     SimulationTraceHelper, UserOperationValidationOutcome, UserOperationValidator,
     UserOperationValidatorMode,
 };
 use crate::{
-    mempool::Mempool, InvalidMempoolUserOperationError, Reputation, SanityError, SimulationError,
+    mempool::Mempool, utils::ChainPreVerificationGas, InvalidMempoolUserOperationError,
+    ReplacementPolicy, Reputation, SimulationError,
 };
 use alloy_chains::Chain;
 use enumset::EnumSet;
 use ethers::{
     providers::Middleware,
-    types::{BlockNumber, GethTrace, U256},
+    types::{Address, GethTrace, U256},
 };
+use parking_lot::RwLock;
 use silius_contracts::{
     entry_point::{EntryPointError, SimulateValidationResult},
     tracer::JsTracerFrame,
-    EntryPoint,
+    Aggregator, EntryPoint,
+};
+use silius_primitives::{
+    constants::validation::{sanity::MAX_INIT_CODE_SIZE, simulation::SIMULATION_CACHE_SIZE},
+    simulation::ValidationConfig,
+    UserOperation,
 };
-use silius_primitives::{simulation::ValidationConfig, UserOperation};
+use std::{sync::Arc, time::Duration};
 use tracing::debug;
 
+/// Sanity checks of the canonical mempool, the optional ones at the end are disabled by default.
+pub type CanonicalSanityChecks = (
+    DuplicateCheck,
+    MaxCallDataSize,
+    Sender,
+    NonceGap,
+    SignatureFormat,
+    VerificationGas,
+    CallGasMinimum,
+    CallGas,
+    MaxCallDataGas,
+    MaxFee,
+    Paymaster,
+    PaymasterPostOp,
+    InitCodeFactory,
+    Entities,
+    SenderStake,
+    FactoryStake,
+    PaymasterStake,
+    Option<PaymasterBalance>,
+    Option<SenderBalance>,
+);
+
+/// Simulation checks of the canonical mempool.
+pub type CanonicalSimulationChecks = (Signature, Timestamp, VerificationExtraGas, AggregatorStaked);
+
+/// Simulation trace checks of the canonical mempool, the optional ones at the end are disabled by
+/// default.
+pub type CanonicalSimulationTraceChecks = (
+    Gas,
+    TimestampOpcodeCheck,
+    Opcodes,
+    ExternalContracts,
+    StorageAccess,
+    BannedAccountCallCheck,
+    CallStack,
+    ExternalCallTracker,
+    BalanceChange,
+    CodeHashes,
+    Option<CallTypeCheck>,
+    Option<InnerCallGas>,
+    Option<DelegatecallTargetCheck>,
+);
+
 pub type StandardValidator<M> = StandardUserOperationValidator<
     M,
-    (Sender, VerificationGas, CallGas, MaxFee, Paymaster, Entities, UnstakedEntities),
-    (Signature, Timestamp, VerificationExtraGas),
-    (Gas, Opcodes, ExternalContracts, StorageAccess, CallStack, CodeHashes),
+    CanonicalSanityChecks,
+    CanonicalSimulationChecks,
+    CanonicalSimulationTraceChecks,
 >;
 
-type UnsafeValidator<M> = StandardUserOperationValidator<
-    M,
-    (Sender, VerificationGas, CallGas, MaxFee, Paymaster, Entities, UnstakedEntities),
-    (Signature, Timestamp, VerificationExtraGas),
-    (),
->;
+type UnsafeValidator<M> =
+    StandardUserOperationValidator<M, CanonicalSanityChecks, CanonicalSimulationChecks, ()>;
 
 /// Standard implementation of [UserOperationValidator].
 pub struct StandardUserOperationValidator<M: Middleware + 'static, SanCk, SimCk, SimTrCk>
@@ -63,6 +123,15 @@ where
     simulation_checks: SimCk,
     /// An array of [SimulationTraceChecks](SimulationTraceCheck).
     simulation_trace_checks: SimTrCk,
+    /// Rules for replacing a user operation with the same sender and nonce.
+    replacement_policy: ReplacementPolicy,
+    /// Code hashes fetched in the current block (shared by the clones of the validator).
+    code_hash_cache: Arc<CodeHashCache>,
+    /// Simulations made in the current block (shared by the clones of the validator).
+    simulation_cache: Arc<SimulationCache>,
+    /// L1 calldata pricing of the chain checked against the pre-verification gas (updated on new
+    /// blocks by the mempool).
+    chain_pre_verification_gas: Arc<RwLock<ChainPreVerificationGas>>,
 }
 
 impl<M: Middleware + Clone + 'static, SanCk, SimCk, SimTrCk> Clone
@@ -79,6 +148,10 @@ where
             sanity_checks: self.sanity_checks.clone(),
             simulation_checks: self.simulation_checks.clone(),
             simulation_trace_checks: self.simulation_trace_checks.clone(),
+            replacement_policy: self.replacement_policy,
+            code_hash_cache: self.code_hash_cache.clone(),
+            simulation_cache: self.simulation_cache.clone(),
+            chain_pre_verification_gas: self.chain_pre_verification_gas.clone(),
         }
     }
 }
@@ -94,6 +167,8 @@ where
 /// operation `max_uos_per_sender` - max user operations that bundler would accept from one sender
 /// `gas_increase_perc` - gas increase percentage that bundler would accept for overwriting one user
 /// operation
+/// `max_calldata_gas` - max calldata gas of the call data of one user operation
+/// `max_call_data_size` - max size of the call data of one user operation (in bytes)
 ///
 /// # Returns
 /// A new [StandardUserOperationValidator].
@@ -102,21 +177,49 @@ pub fn new_canonical<M: Middleware + 'static>(
     chain: Chain,
     max_verification_gas: U256,
     min_priority_fee_per_gas: U256,
+    max_calldata_gas: U256,
+    max_call_data_size: usize,
 ) -> StandardValidator<M> {
     StandardUserOperationValidator::new(
         entry_point,
         chain,
         (
+            DuplicateCheck,
+            MaxCallDataSize::new(max_call_data_size, MAX_INIT_CODE_SIZE),
             Sender,
-            VerificationGas { max_verification_gas },
+            NonceGap::default(),
+            SignatureFormat,
+            VerificationGas::new(max_verification_gas),
+            CallGasMinimum::default(),
             CallGas,
+            MaxCallDataGas::new(max_calldata_gas),
             MaxFee { min_priority_fee_per_gas },
             Paymaster,
+            PaymasterPostOp::default(),
+            InitCodeFactory,
             Entities,
-            UnstakedEntities,
+            SenderStake,
+            FactoryStake,
+            PaymasterStake,
+            None,
+            None,
+        ),
+        (Signature, Timestamp::default(), VerificationExtraGas, AggregatorStaked),
+        (
+            Gas,
+            TimestampOpcodeCheck,
+            Opcodes,
+            ExternalContracts,
+            StorageAccess,
+            BannedAccountCallCheck,
+            CallStack,
+            ExternalCallTracker::default(),
+            BalanceChange,
+            CodeHashes,
+            None,
+            None,
+            None,
         ),
-        (Signature, Timestamp, VerificationExtraGas),
-        (Gas, Opcodes, ExternalContracts, StorageAccess, CallStack, CodeHashes),
     )
 }
 
@@ -125,20 +228,34 @@ pub fn new_canonical_unsafe<M: Middleware + Clone + 'static>(
     chain: Chain,
     max_verification_gas: U256,
     min_priority_fee_per_gas: U256,
+    max_calldata_gas: U256,
+    max_call_data_size: usize,
 ) -> UnsafeValidator<M> {
     StandardUserOperationValidator::new(
         entry_point.clone(),
         chain,
         (
+            DuplicateCheck,
+            MaxCallDataSize::new(max_call_data_size, MAX_INIT_CODE_SIZE),
             Sender,
-            VerificationGas { max_verification_gas },
+            NonceGap::default(),
+            SignatureFormat,
+            VerificationGas::new(max_verification_gas),
+            CallGasMinimum::default(),
             CallGas,
+            MaxCallDataGas::new(max_calldata_gas),
             MaxFee { min_priority_fee_per_gas },
             Paymaster,
+            PaymasterPostOp::default(),
+            InitCodeFactory,
             Entities,
-            UnstakedEntities,
+            SenderStake,
+            FactoryStake,
+            PaymasterStake,
+            None,
+            None,
         ),
-        (Signature, Timestamp, VerificationExtraGas),
+        (Signature, Timestamp::default(), VerificationExtraGas, AggregatorStaked),
         (),
     )
 }
@@ -157,7 +274,59 @@ where
         simulation_checks: SimCk,
         simulation_trace_checks: SimTrCk,
     ) -> Self {
-        Self { entry_point, chain, sanity_checks, simulation_checks, simulation_trace_checks }
+        Self {
+            entry_point,
+            chain,
+            sanity_checks,
+            simulation_checks,
+            simulation_trace_checks,
+            replacement_policy: ReplacementPolicy::default(),
+            code_hash_cache: Arc::new(CodeHashCache::default()),
+            simulation_cache: Arc::new(SimulationCache::new(SIMULATION_CACHE_SIZE)),
+            chain_pre_verification_gas: Arc::new(RwLock::new(ChainPreVerificationGas::for_chain(
+                chain,
+            ))),
+        }
+    }
+
+    /// Sets the [EntryPoint] the user operations are validated against, the validator is shared by
+    /// the mempools of all entry points.
+    pub fn with_entry_point(mut self, entry_point: EntryPoint<M>) -> Self {
+        self.entry_point = entry_point;
+        self
+    }
+
+    /// Sets the [ReplacementPolicy] the sanity checks use for replacing user operations.
+    pub fn with_replacement_policy(mut self, replacement_policy: ReplacementPolicy) -> Self {
+        self.replacement_policy = replacement_policy;
+        self
+    }
+
+    pub fn replacement_policy(&self) -> ReplacementPolicy {
+        self.replacement_policy
+    }
+
+    /// The [CodeHashCache] of the validator, invalidated on every new block.
+    pub fn code_hash_cache(&self) -> &CodeHashCache {
+        &self.code_hash_cache
+    }
+
+    /// Sets the number of user operations whose simulations are cached for the current block
+    /// (0 disables the [SimulationCache]).
+    pub fn with_simulation_cache_size(mut self, size: usize) -> Self {
+        self.simulation_cache = Arc::new(SimulationCache::new(size));
+        self
+    }
+
+    /// The [SimulationCache] of the validator, invalidated on every new block.
+    pub fn simulation_cache(&self) -> &SimulationCache {
+        &self.simulation_cache
+    }
+
+    /// The [ChainPreVerificationGas] of the validator, shared with the mempool that keeps the L1
+    /// prices up to date.
+    pub fn chain_pre_verification_gas(&self) -> Arc<RwLock<ChainPreVerificationGas>> {
+        self.chain_pre_verification_gas.clone()
     }
 
     /// Simulates validation of a [UserOperation](UserOperation) via the
@@ -174,16 +343,57 @@ where
         &self,
         uo: &UserOperation,
     ) -> Result<SimulateValidationResult, SimulationError> {
+        let key = SimulationCache::key(&uo.user_operation);
+        if let Some(res) = self.simulation_cache.get_validation(&key) {
+            return res.map_err(|inner| SimulationError::Validation { inner });
+        }
+
+        let block_number = self.simulation_cache.block_number();
         match self.entry_point.simulate_validation(uo.user_operation.clone()).await {
-            Ok(res) => Ok(res),
+            Ok(res) => {
+                self.simulation_cache.insert_validation(key, Ok(res.clone()), block_number);
+                Ok(res)
+            }
             Err(err) => Err(match err {
-                EntryPointError::FailedOp(op) => SimulationError::Validation { inner: op.reason },
+                EntryPointError::FailedOp(op) => {
+                    self.simulation_cache.insert_validation(
+                        key,
+                        Err(op.reason.clone()),
+                        block_number,
+                    );
+                    SimulationError::Validation { inner: op.reason }
+                }
                 EntryPointError::Provider { inner } => SimulationError::Provider { inner },
                 _ => SimulationError::Other { inner: err.to_string() },
             }),
         }
     }
 
+    /// Validates the signature of a [UserOperation](UserOperation) with its signature aggregator
+    /// via the `validateUserOpSignature` method of the aggregator.
+    ///
+    /// # Arguments
+    /// `uo` - [UserOperation](UserOperation) whose signature is validated.
+    /// `aggregator` - The signature aggregator returned by the simulation.
+    ///
+    /// # Returns
+    /// None if the aggregator accepts the signature, otherwise a
+    /// [SimulationError](crate::error::SimulationError).
+    async fn validate_aggregator_signature(
+        &self,
+        uo: &UserOperation,
+        aggregator: Address,
+    ) -> Result<(), SimulationError> {
+        Aggregator::new(self.entry_point.eth_client(), aggregator)
+            .validate_user_op_signature(uo.user_operation.clone())
+            .await
+            .map(|_| ())
+            .map_err(|err| match err {
+                EntryPointError::Provider { inner } => SimulationError::Provider { inner },
+                _ => SimulationError::AggregatorSignature { aggregator, inner: err.to_string() },
+            })
+    }
+
     /// Simulates validation of a [UserOperation](UserOperation) via the
     /// [simulate_validation_trace](crate::entry_point::EntryPoint::simulate_validation_trace)
     /// method of the [entry_point](crate::entry_point::EntryPoint)
@@ -198,10 +408,22 @@ where
         &self,
         uo: &UserOperation,
     ) -> Result<GethTrace, SimulationError> {
+        let key = SimulationCache::key(&uo.user_operation);
+        if let Some(trace) = self.simulation_cache.get_trace(&key) {
+            return trace.map_err(|inner| SimulationError::Validation { inner });
+        }
+
+        let block_number = self.simulation_cache.block_number();
         match self.entry_point.simulate_validation_trace(uo.user_operation.clone()).await {
-            Ok(trace) => Ok(trace),
+            Ok(trace) => {
+                self.simulation_cache.insert_trace(key, Ok(trace.clone()), block_number);
+                Ok(trace)
+            }
             Err(err) => Err(match err {
-                EntryPointError::FailedOp(op) => SimulationError::Validation { inner: op.reason },
+                EntryPointError::FailedOp(op) => {
+                    self.simulation_cache.insert_trace(key, Err(op.reason.clone()), block_number);
+                    SimulationError::Validation { inner: op.reason }
+                }
                 EntryPointError::Provider { inner } => SimulationError::Provider { inner },
                 _ => SimulationError::Other { inner: err.to_string() },
             }),
@@ -209,6 +431,70 @@ where
     }
 }
 
+impl<M: Middleware + 'static, SimCk, SimTrCk>
+    StandardUserOperationValidator<M, CanonicalSanityChecks, SimCk, SimTrCk>
+where
+    SimCk: SimulationCheck,
+    SimTrCk: SimulationTraceCheck<M>,
+{
+    /// Enables the [PaymasterBalance] check of the paymaster deposit (disabled if None).
+    pub fn with_paymaster_balance(mut self, paymaster_balance: Option<PaymasterBalance>) -> Self {
+        self.sanity_checks.17 = paymaster_balance;
+        self
+    }
+
+    /// Enables the [SenderBalance] check of the deposit of senders without a paymaster.
+    pub fn with_sender_balance(mut self, enabled: bool) -> Self {
+        self.sanity_checks.18 = enabled.then_some(SenderBalance);
+        self
+    }
+}
+
+impl<M: Middleware + 'static, SanCk, SimTrCk>
+    StandardUserOperationValidator<M, SanCk, CanonicalSimulationChecks, SimTrCk>
+where
+    SanCk: SanityCheck<M>,
+    SimTrCk: SimulationTraceCheck<M>,
+{
+    /// Sets the max age of user operations in the mempool, the [Timestamp] check rejects user
+    /// operations that become valid later (they would be evicted before they can be included).
+    pub fn with_max_mempool_age(mut self, max_mempool_age: Duration) -> Self {
+        self.simulation_checks.1.max_valid_after = max_mempool_age.as_secs();
+        self
+    }
+}
+
+impl<M: Middleware + 'static, SanCk, SimCk>
+    StandardUserOperationValidator<M, SanCk, SimCk, CanonicalSimulationTraceChecks>
+where
+    SanCk: SanityCheck<M>,
+    SimCk: SimulationCheck,
+{
+    /// Enables the [CallTypeCheck] of the `CALLCODE` and `DELEGATECALL` calls made during
+    /// validation.
+    pub fn with_call_type_check(mut self, enabled: bool) -> Self {
+        self.simulation_trace_checks.10 = enabled.then_some(CallTypeCheck);
+        self
+    }
+
+    /// Enables the [InnerCallGas] check of the gas forwarded by the calls made during validation
+    /// (disabled if None).
+    pub fn with_inner_call_gas(mut self, inner_call_gas: Option<InnerCallGas>) -> Self {
+        self.simulation_trace_checks.11 = inner_call_gas;
+        self
+    }
+
+    /// Enables the [DelegatecallTargetCheck] of the `DELEGATECALL` targets during validation
+    /// (disabled if None).
+    pub fn with_delegatecall_target_check(
+        mut self,
+        delegatecall_target_check: Option<DelegatecallTargetCheck>,
+    ) -> Self {
+        self.simulation_trace_checks.12 = delegatecall_target_check;
+        self
+    }
+}
+
 #[async_trait::async_trait]
 impl<M: Middleware + 'static, SanCk, SimCk, SimTrCk> UserOperationValidator
     for StandardUserOperationValidator<M, SanCk, SimCk, SimTrCk>
@@ -258,8 +544,11 @@ where
         if mode.contains(UserOperationValidatorMode::Sanity) {
             let sanity_helper = SanityHelper {
                 entry_point: &self.entry_point,
+                entry_point_version: self.entry_point.version(),
                 chain: self.chain,
                 val_config: val_config.clone().unwrap_or_default(),
+                replacement_policy: self.replacement_policy,
+                chain_pre_verification_gas: *self.chain_pre_verification_gas.read(),
             };
 
             self.sanity_checks
@@ -273,10 +562,13 @@ where
 
         debug!("Simulate user operation from {:?}", uo.sender);
         let sim_res = self.simulate_validation(uo).await?;
+        let mut context =
+            SimulationContext::new(&self.entry_point, self.chain, uo, sim_res).await?;
 
         if mode.contains(UserOperationValidatorMode::Simulation) {
             let mut sim_helper = SimulationHelper {
-                simulate_validation_result: &sim_res,
+                context: &context,
+                reputation,
                 val_config: val_config.clone().unwrap_or_default(),
                 valid_after: None,
             };
@@ -286,32 +578,30 @@ where
             out.valid_after = sim_helper.valid_after;
         }
 
-        out.pre_fund = extract_pre_fund(&sim_res);
-        out.verification_gas_limit = extract_verification_gas_limit(&sim_res);
-
-        let block_number = self
-            .entry_point
-            .eth_client()
-            .get_block(BlockNumber::Latest)
-            .await
-            .map_err(|e| SanityError::Provider { inner: e.to_string() })?
-            .expect("block should exist");
-        out.verified_block = U256::from(block_number.hash.expect("block hash should exist").0);
+        out.pre_fund = extract_pre_fund(&context.validation_result);
+        out.aggregator = extract_aggregator(&context.validation_result);
+        // the account doesn't check the signature itself, the aggregator has to accept it
+        if let Some(aggregator) = out.aggregator {
+            self.validate_aggregator_signature(uo, aggregator).await?;
+        }
+        out.verification_gas_limit = extract_verification_gas_limit(&context.validation_result);
+        out.valid_until = extract_timestamps(&context.validation_result).1;
+        out.verified_block = U256::from(context.block.hash.expect("block hash should exist").0);
 
         if mode.contains(UserOperationValidatorMode::SimulationTrace) {
             debug!("Simulate user operation with trace from {:?}", uo.sender);
             let geth_trace = self.simulate_validation_trace(uo).await?;
             let js_trace: JsTracerFrame = JsTracerFrame::try_from(geth_trace)
                 .map_err(|error| SimulationError::Validation { inner: error.to_string() })?;
+            context.set_trace(&js_trace);
 
             let mut sim_helper = SimulationTraceHelper {
                 entry_point: &self.entry_point,
-                chain: self.chain,
-                simulate_validation_result: &sim_res,
+                context: &context,
                 js_trace: &js_trace,
                 val_config: val_config.unwrap_or_default(),
-                stake_info: None,
                 code_hashes: None,
+                code_hash_cache: &self.code_hash_cache,
             };
 
             self.simulation_trace_checks
@@ -320,6 +610,7 @@ where
 
             out.code_hashes = sim_helper.code_hashes;
             out.storage_map = extract_storage_map(&js_trace);
+            out.paymaster_gas_used = extract_paymaster_gas_used(&js_trace);
         }
 
         Ok(out)
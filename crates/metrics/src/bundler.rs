@@ -0,0 +1,39 @@
+use ethers::types::Address;
+use metrics::{counter, describe_counter, describe_histogram, histogram};
+use silius_bundler::metrics::{BUNDLES_RESUBMITTED, BUNDLE_RECOVERY_OPS_DROPPED};
+use std::time::Duration;
+
+const BUNDLES_SUBMITTED: &str = "silius_bundles_submitted_total";
+const BUNDLE_SUBMISSION_LATENCY: &str = "silius_bundle_submission_latency_seconds";
+
+/// Records a bundle handed to the bundle client
+///
+/// # Arguments
+/// * `entry_point` - The entry point the bundle is sent to
+/// * `latency` - The time it took to build and send the bundle
+pub fn record_bundle_submission(entry_point: Address, latency: Duration) {
+    let entry_point = format!("{entry_point:?}");
+    counter!(BUNDLES_SUBMITTED, "entry_point" => entry_point.clone()).increment(1);
+    histogram!(BUNDLE_SUBMISSION_LATENCY, "entry_point" => entry_point)
+        .record(latency.as_secs_f64());
+}
+
+pub fn describe_bundler_metrics() {
+    describe_counter!(BUNDLES_SUBMITTED, "The number of bundles submitted");
+    describe_histogram!(
+        BUNDLE_SUBMISSION_LATENCY,
+        metrics::Unit::Seconds,
+        "The time it takes to build and send a bundle"
+    );
+    describe_counter!(
+        BUNDLES_RESUBMITTED,
+        "The number of bundle transactions resubmitted with bumped fees"
+    );
+    describe_counter!(
+        BUNDLE_RECOVERY_OPS_DROPPED,
+        "The number of user operations of reverted bundles dropped after failing the simulation"
+    );
+    counter!(BUNDLES_SUBMITTED).absolute(0);
+    counter!(BUNDLES_RESUBMITTED).absolute(0);
+    counter!(BUNDLE_RECOVERY_OPS_DROPPED).absolute(0);
+}
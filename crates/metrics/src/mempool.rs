@@ -1,12 +1,16 @@
 use metrics::{counter, describe_counter, describe_gauge, gauge};
 use silius_mempool::{
-    AddRemoveUserOp, ClearOp, MempoolErrorKind, ReputationEntryOp, ReputationError, UserOperationOp,
+    metrics::MEMPOOL_EVICTIONS, AddRemoveUserOp, ClearOp, InvalidMempoolUserOperationError,
+    MempoolErrorKind, ReputationEntryOp, ReputationError, UserOperationOp,
 };
 use silius_primitives::{UserOperation, UserOperationHash};
 
 const MEMPOOL_SIZE: &str = "silius_mempool_size";
 const MEMPOOL_ADD_ERROR: &str = "silius_mempool_add_error";
 const MEMPOOL_REMOVE_ERROR: &str = "silius_mempool_remove_error";
+const UOS_ADDED: &str = "silius_uops_added_total";
+const UOS_REMOVED: &str = "silius_uops_removed_total";
+const VALIDATION_ERRORS: &str = "silius_validation_errors_total";
 const REPUTATION_UO_SEEN: &str = "silius_reputation_uo_seen";
 const REPUTATION_UO_INCLUDED: &str = "silius_reputation_uo_included";
 const REPUTATION_STATUS: &str = "silius_reputation_status";
@@ -28,6 +32,7 @@ impl<S: AddRemoveUserOp + Clone> AddRemoveUserOp for MetricsHandler<S> {
         match self.inner.add(uo) {
             Ok(res) => {
                 gauge!(MEMPOOL_SIZE).increment(1f64);
+                counter!(UOS_ADDED).increment(1);
                 Ok(res)
             }
             Err(e) => {
@@ -44,6 +49,7 @@ impl<S: AddRemoveUserOp + Clone> AddRemoveUserOp for MetricsHandler<S> {
         match self.inner.remove_by_uo_hash(uo_hash) {
             Ok(res) => {
                 gauge!(MEMPOOL_SIZE).decrement(1f64);
+                counter!(UOS_REMOVED).increment(1);
                 Ok(res)
             }
             Err(e) => {
@@ -52,6 +58,25 @@ impl<S: AddRemoveUserOp + Clone> AddRemoveUserOp for MetricsHandler<S> {
             }
         }
     }
+
+    fn replace(
+        &mut self,
+        prev_hash: &UserOperationHash,
+        uo: UserOperation,
+    ) -> Result<UserOperationHash, MempoolErrorKind> {
+        // the size of the mempool doesn't change
+        match self.inner.replace(prev_hash, uo) {
+            Ok(res) => {
+                counter!(UOS_ADDED).increment(1);
+                counter!(UOS_REMOVED).increment(1);
+                Ok(res)
+            }
+            Err(e) => {
+                counter!(MEMPOOL_ADD_ERROR, "error" => format!("{:?}", e)).increment(1);
+                Err(e)
+            }
+        }
+    }
 }
 
 impl<S: UserOperationOp + Clone> UserOperationOp for MetricsHandler<S> {
@@ -62,13 +87,20 @@ impl<S: UserOperationOp + Clone> UserOperationOp for MetricsHandler<S> {
         self.inner.get_by_uo_hash(uo_hash)
     }
 
-    fn get_sorted(&self) -> Result<Vec<silius_primitives::UserOperation>, MempoolErrorKind> {
-        self.inner.get_sorted()
+    fn get_sorted(
+        &self,
+        base_fee_per_gas: ethers::types::U256,
+    ) -> Result<Vec<silius_primitives::UserOperation>, MempoolErrorKind> {
+        self.inner.get_sorted(base_fee_per_gas)
     }
 
     fn get_all(&self) -> Result<Vec<silius_primitives::UserOperation>, MempoolErrorKind> {
         self.inner.get_all()
     }
+
+    fn len(&self) -> Result<usize, MempoolErrorKind> {
+        self.inner.len()
+    }
 }
 
 impl<S: ClearOp + Clone> ClearOp for MetricsHandler<S> {
@@ -116,10 +148,30 @@ impl<S: ReputationEntryOp + Clone> ReputationEntryOp for MetricsHandler<S> {
     }
 }
 
+/// Counts the user operation rejected by the validation, labelled by the check that failed
+pub fn record_validation_error(err: &InvalidMempoolUserOperationError) {
+    let (kind, error) = match err {
+        InvalidMempoolUserOperationError::Reputation(e) => ("reputation", e.variant()),
+        InvalidMempoolUserOperationError::Sanity(e) => ("sanity", e.variant()),
+        InvalidMempoolUserOperationError::Simulation(e) => ("simulation", e.variant()),
+    };
+    counter!(VALIDATION_ERRORS, "kind" => kind, "error" => error).increment(1);
+}
+
 pub fn describe_mempool_metrics() {
     describe_gauge!(MEMPOOL_SIZE, "The number of user operations in the mempool");
     describe_counter!(MEMPOOL_ADD_ERROR, "The number of errors when adding to the mempool");
     describe_counter!(MEMPOOL_REMOVE_ERROR, "The number of errors when removing from the mempool");
+    describe_counter!(UOS_ADDED, "The number of user operations added to the mempool");
+    describe_counter!(UOS_REMOVED, "The number of user operations removed from the mempool");
+    describe_counter!(
+        MEMPOOL_EVICTIONS,
+        "The number of user operations evicted from a full mempool"
+    );
+    describe_counter!(
+        VALIDATION_ERRORS,
+        "The number of user operations rejected by the validation"
+    );
     describe_gauge!(REPUTATION_UO_SEEN, "The number of user operations seen for an address");
     describe_gauge!(
         REPUTATION_UO_INCLUDED,
@@ -132,6 +184,8 @@ pub fn describe_mempool_metrics() {
     );
     counter!(MEMPOOL_ADD_ERROR).absolute(0);
     counter!(MEMPOOL_REMOVE_ERROR).absolute(0);
+    counter!(UOS_ADDED).absolute(0);
+    counter!(UOS_REMOVED).absolute(0);
     counter!(REPUTATION_SET_ENTRY_ERROR).absolute(0);
     gauge!(MEMPOOL_SIZE).set(0f64);
     gauge!(REPUTATION_UO_SEEN).set(0f64);
@@ -5,8 +5,8 @@ use sha2::{Digest, Sha256};
 use silius_primitives::{
     chain::ChainSpec,
     constants::p2p::{
-        IPV4_ADDRESS, MESSAGE_DOMAIN_VALID_SNAPPY, NODE_ENR_FILE_NAME, NODE_KEY_FILE_NAME,
-        TARGET_PEERS, TCP_PORT, UDP_PORT,
+        IPV4_ADDRESS, MAX_USER_OPERATIONS_PER_PEER, MESSAGE_DOMAIN_VALID_SNAPPY,
+        NODE_ENR_FILE_NAME, NODE_KEY_FILE_NAME, TARGET_PEERS, TCP_PORT, UDP_PORT,
     },
 };
 use std::{
@@ -63,6 +63,10 @@ pub struct Config {
 
     /// List of whitelisted IP addresses
     pub ips_whitelist: Vec<IpAddr>,
+
+    /// Maximum number of gossiped user operations accepted from a single peer per rate limit
+    /// window.
+    pub max_uos_per_peer: usize,
 }
 
 impl Default for Config {
@@ -93,6 +97,7 @@ impl Default for Config {
             bootnodes: vec![],
             peers_whitelist: vec![],
             ips_whitelist: vec![],
+            max_uos_per_peer: MAX_USER_OPERATIONS_PER_PEER,
         }
     }
 }
@@ -214,6 +219,12 @@ impl ConfigBuilder {
         self.config.ips_whitelist = ips_whitelist;
         self
     }
+
+    /// Set the maximum number of gossiped user operations accepted from a single peer.
+    pub fn max_uos_per_peer(mut self, max_uos_per_peer: usize) -> Self {
+        self.config.max_uos_per_peer = max_uos_per_peer;
+        self
+    }
 }
 
 /// Create a `GossipsubConfig`.
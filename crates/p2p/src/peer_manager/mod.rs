@@ -1,6 +1,7 @@
 pub mod network_behaviour;
 pub mod peer;
 pub mod peerdb;
+pub mod rate_limiter;
 
 use self::peer::peer_info::ConnectionDirection;
 use crate::{
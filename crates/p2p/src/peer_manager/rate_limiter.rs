@@ -0,0 +1,69 @@
+use libp2p::PeerId;
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Limits the number of gossiped user operations accepted from each peer in a fixed time window.
+#[derive(Debug)]
+pub struct PeerRateLimiter {
+    /// Maximum number of messages per window.
+    max_messages: usize,
+    /// Length of the window.
+    window: Duration,
+    /// Start of the current window and the number of messages received in it.
+    peers: HashMap<PeerId, (Instant, usize)>,
+}
+
+impl PeerRateLimiter {
+    pub fn new(max_messages: usize, window: Duration) -> Self {
+        Self { max_messages, window, peers: HashMap::new() }
+    }
+
+    /// Counts a message from the peer.
+    ///
+    /// # Returns
+    /// `false` if the peer exceeded the limit in the current window.
+    pub fn allow(&mut self, peer_id: &PeerId) -> bool {
+        self.allow_at(peer_id, Instant::now())
+    }
+
+    fn allow_at(&mut self, peer_id: &PeerId, now: Instant) -> bool {
+        let (start, count) = self.peers.entry(*peer_id).or_insert((now, 0));
+        if now.duration_since(*start) >= self.window {
+            *start = now;
+            *count = 0;
+        }
+
+        *count += 1;
+        *count <= self.max_messages
+    }
+
+    /// Forgets the peer (on disconnect).
+    pub fn remove(&mut self, peer_id: &PeerId) {
+        self.peers.remove(peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_per_window() {
+        let mut limiter = PeerRateLimiter::new(2, Duration::from_secs(60));
+        let peer = PeerId::random();
+        let other = PeerId::random();
+        let now = Instant::now();
+
+        assert!(limiter.allow_at(&peer, now));
+        assert!(limiter.allow_at(&peer, now));
+        assert!(!limiter.allow_at(&peer, now + Duration::from_secs(1)));
+
+        // other peers have their own limit
+        assert!(limiter.allow_at(&other, now));
+
+        // the limit resets in the next window
+        assert!(limiter.allow_at(&peer, now + Duration::from_secs(60)));
+    }
+}
@@ -13,7 +13,7 @@ use crate::{
         enr_ext::{CombinedPublicKeyExt, EnrExt},
         DiscoveredPeers, Discovery,
     },
-    peer_manager::{PeerManager, PeerManagerEvent},
+    peer_manager::{rate_limiter::PeerRateLimiter, PeerManager, PeerManagerEvent},
     rpc::{
         methods::{MetaData, MetaDataRequest, Ping, RPCResponse, RequestId, Status},
         outbound::OutboundRequest,
@@ -48,7 +48,10 @@ use libp2p::{
 };
 use libp2p_mplex::{MaxBufferBehaviour, MplexConfig};
 use silius_primitives::{
-    constants::p2p::{FIND_NODE_QUERY_CLOSEST_PEERS, MAX_IPFS_CID_LENGTH, MAX_SUPPORTED_MEMPOOLS},
+    constants::p2p::{
+        FIND_NODE_QUERY_CLOSEST_PEERS, MAX_IPFS_CID_LENGTH, MAX_SUPPORTED_MEMPOOLS,
+        PEER_RATE_LIMIT_WINDOW,
+    },
     p2p::NetworkMessage,
     simulation::ValidationConfig,
     MempoolConfig, UserOperation, VerifiedUserOperation,
@@ -58,6 +61,7 @@ use std::{
     env,
     sync::Arc,
     task::{Context, Poll},
+    time::Duration,
 };
 use tracing::{debug, error, info, warn};
 
@@ -120,6 +124,8 @@ pub struct Network {
     // Each entry point address has its own mempool channel.
     mempool_channels: Vec<MempoolChannel>,
     mempool_configs: Vec<(TopicHash, MempoolConfig)>,
+    // Limits the user operations each peer can gossip to us.
+    rate_limiter: PeerRateLimiter,
 }
 
 impl From<Network> for Swarm<Behaviour> {
@@ -250,7 +256,13 @@ impl Network {
             .expect("building p2p behaviour failed")
             .build();
 
-        let mut network = Network { swarm, network_globals, mempool_channels, mempool_configs };
+        let rate_limiter = PeerRateLimiter::new(
+            config.max_uos_per_peer,
+            Duration::from_secs(PEER_RATE_LIMIT_WINDOW),
+        );
+
+        let mut network =
+            Network { swarm, network_globals, mempool_channels, mempool_configs, rate_limiter };
 
         network.start(&config).await?;
 
@@ -293,9 +305,14 @@ impl Network {
     }
 
     /// handle gossipsub event
-    fn handle_gossipsub_event(&self, event: Box<gossipsub::Event>) -> Option<NetworkEvent> {
+    fn handle_gossipsub_event(&mut self, event: Box<gossipsub::Event>) -> Option<NetworkEvent> {
         match *event {
             gossipsub::Event::Message { propagation_source, message_id, message } => {
+                if !self.rate_limiter.allow(&propagation_source) {
+                    debug!("Peer {propagation_source:?} exceeded the user operations rate limit");
+                    return None;
+                }
+
                 let uo = match VerifiedUserOperation::deserialize(message.data.as_ref()) {
                     Ok(uo) => uo,
                     Err(e) => {
@@ -414,6 +431,7 @@ impl Network {
                 Some(NetworkEvent::PeerConnectedOutgoing(peer_id))
             }
             PeerManagerEvent::PeerDisconnected(peer_id) => {
+                self.rate_limiter.remove(&peer_id);
                 Some(NetworkEvent::PeerDisconnected(peer_id))
             }
             PeerManagerEvent::DiscoverPeers(peers_to_find) => {
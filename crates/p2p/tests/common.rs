@@ -5,7 +5,10 @@ use silius_p2p::{
     listen_addr::{ListenAddr, ListenAddress},
     service::{Network, NetworkEvent},
 };
-use silius_primitives::{chain::ChainSpec, constants::p2p::TARGET_PEERS};
+use silius_primitives::{
+    chain::ChainSpec,
+    constants::p2p::{MAX_USER_OPERATIONS_PER_PEER, TARGET_PEERS},
+};
 use std::{
     net::{Ipv4Addr, TcpListener},
     time::Duration,
@@ -61,6 +64,7 @@ async fn build_p2p_instance(bootnode: Option<Enr>) -> eyre::Result<Network> {
         bootnodes: if let Some(bootnode) = bootnode { vec![bootnode] } else { vec![] },
         peers_whitelist: vec![],
         ips_whitelist: vec![],
+        max_uos_per_peer: MAX_USER_OPERATIONS_PER_PEER,
     };
 
     let (_, receiver) = unbounded();
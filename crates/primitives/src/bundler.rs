@@ -1,6 +1,6 @@
 //! Bundler-related primitives
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use strum_macros::{EnumString, EnumVariantNames};
 
 /// Bundle modes
@@ -26,4 +26,53 @@ pub enum BundleStrategy {
     Conditional,
     /// Sends the bundle to the Fastlane relay
     Fastlane,
+    /// Sends the bundle to the Flashbots MEV-Share relay
+    MevShare,
+}
+
+/// Determines where bundle submission events are reported
+#[derive(Clone, Copy, Debug, EnumString, EnumVariantNames, PartialEq, Eq)]
+#[strum(serialize_all = "kebab_case")]
+pub enum BundleReporterType {
+    /// Bundle submissions are not reported
+    None,
+    /// Reports bundle submissions to a Kafka topic
+    Kafka,
+    /// Reports bundle submissions to a NATS subject
+    Nats,
+}
+
+/// Determines where the signing key of the bundler is held
+#[derive(Clone, Copy, Debug, EnumString, EnumVariantNames, PartialEq, Eq)]
+#[strum(serialize_all = "kebab_case")]
+pub enum SignerType {
+    /// Key derived from the mnemonic file or the private key
+    Mnemonic,
+    /// Key stored in AWS KMS
+    AwsKms,
+    /// Key stored on a Ledger hardware wallet
+    Ledger,
+}
+
+/// Health of a relay the bundles are sent to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelayHealth {
+    /// The last request succeeded
+    Ok,
+    /// Recent requests failed, but the relay is still tried
+    Degraded,
+    /// Too many consecutive failures, the relay is skipped until the retry interval passes
+    Down,
+}
+
+/// Status of a relay reported by `debug_bundler_getRelayStatus`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayStatus {
+    /// Endpoint of the relay
+    pub relay: String,
+    pub status: RelayHealth,
+    /// Error of the last failed request (if any)
+    pub last_error: Option<String>,
 }
@@ -0,0 +1,297 @@
+//! Decoding of the user operation call data of the common smart contract wallets
+
+use ethers::{
+    abi::{decode, ParamType, Token},
+    types::{Address, Bytes, U256},
+    utils::id,
+};
+
+/// `execute` of the reference `SimpleAccount` (and most of the ERC-4337 wallets)
+pub const EXECUTE_SIGNATURE: &str = "execute(address,uint256,bytes)";
+/// `executeBatch` with values
+pub const EXECUTE_BATCH_SIGNATURE: &str = "executeBatch(address[],uint256[],bytes[])";
+/// `executeBatch` of the entry point v0.6 `SimpleAccount` (without values)
+pub const EXECUTE_BATCH_NO_VALUE_SIGNATURE: &str = "executeBatch(address[],bytes[])";
+/// `executeUserOp` of the Safe 4337 module
+pub const SAFE_EXECUTE_USER_OP_SIGNATURE: &str = "executeUserOp(address,uint256,bytes,uint8)";
+/// `execTransaction` of the Safe (Gnosis) multisig
+pub const SAFE_EXEC_TRANSACTION_SIGNATURE: &str =
+    "execTransaction(address,uint256,bytes,uint8,uint256,uint256,uint256,address,address,bytes)";
+
+/// Error decoding the call data
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum DecodeError {
+    /// The call data doesn't start with the selector of the function
+    #[error("call data doesn't match the selector of {function}")]
+    Selector { function: String },
+    /// The arguments of the function can't be decoded
+    #[error("invalid arguments: {inner}")]
+    Abi { inner: String },
+    /// The arrays of the batch have different lengths
+    #[error("batch arrays have different lengths")]
+    BatchLength,
+    /// The Safe transaction is a delegate call
+    #[error("Safe delegate calls aren't supported")]
+    DelegateCall,
+}
+
+/// A call the wallet executes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecuteCall {
+    pub to: Address,
+    pub value: U256,
+    pub data: Bytes,
+}
+
+/// The format of the call data and the calls decoded from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalletFormat {
+    /// A single `execute` call
+    Execute(ExecuteCall),
+    /// An `executeBatch` call
+    ExecuteBatch(Vec<ExecuteCall>),
+    /// A Safe transaction (from the 4337 module or `execTransaction`)
+    Safe(ExecuteCall),
+    /// None of the known formats
+    Unknown,
+}
+
+/// Decodes the arguments of the function with the signature
+fn decode_args(
+    signature: &str,
+    params: &[ParamType],
+    call_data: &Bytes,
+) -> Result<Vec<Token>, DecodeError> {
+    if call_data.len() < 4 || call_data[..4] != id(signature) {
+        return Err(DecodeError::Selector { function: signature.into() });
+    }
+
+    decode(params, &call_data[4..]).map_err(|e| DecodeError::Abi { inner: e.to_string() })
+}
+
+fn abi_err(arg: &str) -> DecodeError {
+    DecodeError::Abi { inner: format!("unexpected type of {arg}") }
+}
+
+fn into_call(to: Token, value: Token, data: Token) -> Result<ExecuteCall, DecodeError> {
+    Ok(ExecuteCall {
+        to: to.into_address().ok_or_else(|| abi_err("to"))?,
+        value: value.into_uint().ok_or_else(|| abi_err("value"))?,
+        data: data.into_bytes().ok_or_else(|| abi_err("data"))?.into(),
+    })
+}
+
+fn into_array(token: Token, arg: &str) -> Result<Vec<Token>, DecodeError> {
+    token.into_array().ok_or_else(|| abi_err(arg))
+}
+
+/// Decodes the `execute(address,uint256,bytes)` call data
+pub fn decode_execute(call_data: &Bytes) -> Result<ExecuteCall, DecodeError> {
+    let mut args = decode_args(
+        EXECUTE_SIGNATURE,
+        &[ParamType::Address, ParamType::Uint(256), ParamType::Bytes],
+        call_data,
+    )?
+    .into_iter();
+
+    match (args.next(), args.next(), args.next()) {
+        (Some(to), Some(value), Some(data)) => into_call(to, value, data),
+        _ => Err(abi_err("arguments")),
+    }
+}
+
+/// Decodes the `executeBatch(address[],uint256[],bytes[])` call data, or the
+/// `executeBatch(address[],bytes[])` variant without values (the values are zero)
+pub fn decode_execute_batch(call_data: &Bytes) -> Result<Vec<ExecuteCall>, DecodeError> {
+    let address_array = ParamType::Array(Box::new(ParamType::Address));
+    let bytes_array = ParamType::Array(Box::new(ParamType::Bytes));
+
+    let (to, value, data) = match decode_args(
+        EXECUTE_BATCH_SIGNATURE,
+        &[
+            address_array.clone(),
+            ParamType::Array(Box::new(ParamType::Uint(256))),
+            bytes_array.clone(),
+        ],
+        call_data,
+    ) {
+        Ok(args) => {
+            let mut args = args.into_iter();
+            match (args.next(), args.next(), args.next()) {
+                (Some(to), Some(value), Some(data)) => {
+                    (into_array(to, "to")?, into_array(value, "value")?, into_array(data, "data")?)
+                }
+                _ => return Err(abi_err("arguments")),
+            }
+        }
+        Err(DecodeError::Selector { .. }) => {
+            let mut args = decode_args(
+                EXECUTE_BATCH_NO_VALUE_SIGNATURE,
+                &[address_array, bytes_array],
+                call_data,
+            )
+            .map_err(|_| DecodeError::Selector { function: EXECUTE_BATCH_SIGNATURE.into() })?
+            .into_iter();
+            match (args.next(), args.next()) {
+                (Some(to), Some(data)) => {
+                    let to = into_array(to, "to")?;
+                    let value = vec![Token::Uint(U256::zero()); to.len()];
+                    (to, value, into_array(data, "data")?)
+                }
+                _ => return Err(abi_err("arguments")),
+            }
+        }
+        Err(e) => return Err(e),
+    };
+
+    if to.len() != value.len() || to.len() != data.len() {
+        return Err(DecodeError::BatchLength);
+    }
+
+    to.into_iter()
+        .zip(value)
+        .zip(data)
+        .map(|((to, value), data)| into_call(to, value, data))
+        .collect()
+}
+
+/// Decodes the call data of a Safe: `executeUserOp` of the Safe 4337 module or `execTransaction`
+/// of the Safe itself. Delegate calls (operation 1) are rejected.
+pub fn decode_safe_exec_transaction(call_data: &Bytes) -> Result<ExecuteCall, DecodeError> {
+    let call = [ParamType::Address, ParamType::Uint(256), ParamType::Bytes, ParamType::Uint(8)];
+
+    let args = match decode_args(SAFE_EXECUTE_USER_OP_SIGNATURE, &call, call_data) {
+        Err(DecodeError::Selector { .. }) => {
+            let mut params = call.to_vec();
+            params.extend([
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Uint(256),
+                ParamType::Address,
+                ParamType::Address,
+                ParamType::Bytes,
+            ]);
+            decode_args(SAFE_EXEC_TRANSACTION_SIGNATURE, &params, call_data)?
+        }
+        res => res?,
+    };
+
+    let mut args = args.into_iter();
+    match (args.next(), args.next(), args.next(), args.next()) {
+        (Some(to), Some(value), Some(data), Some(operation)) => {
+            if !operation.into_uint().ok_or_else(|| abi_err("operation"))?.is_zero() {
+                return Err(DecodeError::DelegateCall);
+            }
+            into_call(to, value, data)
+        }
+        _ => Err(abi_err("arguments")),
+    }
+}
+
+/// Detects the format of the call data by trying the decoders in order
+pub fn detect_wallet_format(call_data: &Bytes) -> WalletFormat {
+    if let Ok(call) = decode_execute(call_data) {
+        WalletFormat::Execute(call)
+    } else if let Ok(calls) = decode_execute_batch(call_data) {
+        WalletFormat::ExecuteBatch(calls)
+    } else if let Ok(call) = decode_safe_exec_transaction(call_data) {
+        WalletFormat::Safe(call)
+    } else {
+        WalletFormat::Unknown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::encode;
+
+    fn call_data(signature: &str, args: &[Token]) -> Bytes {
+        [id(signature).to_vec(), encode(args)].concat().into()
+    }
+
+    fn call(n: u64) -> ExecuteCall {
+        ExecuteCall { to: Address::random(), value: n.into(), data: vec![n as u8; 36].into() }
+    }
+
+    fn tokens(call: &ExecuteCall) -> Vec<Token> {
+        vec![Token::Address(call.to), Token::Uint(call.value), Token::Bytes(call.data.to_vec())]
+    }
+
+    #[test]
+    fn execute() {
+        let c = call(1);
+        let data = call_data(EXECUTE_SIGNATURE, &tokens(&c));
+        assert_eq!(decode_execute(&data).unwrap(), c);
+        assert_eq!(detect_wallet_format(&data), WalletFormat::Execute(c));
+    }
+
+    #[test]
+    fn execute_batch() {
+        let calls = vec![call(1), call(2)];
+        let data = call_data(
+            EXECUTE_BATCH_SIGNATURE,
+            &[
+                Token::Array(calls.iter().map(|c| Token::Address(c.to)).collect()),
+                Token::Array(calls.iter().map(|c| Token::Uint(c.value)).collect()),
+                Token::Array(calls.iter().map(|c| Token::Bytes(c.data.to_vec())).collect()),
+            ],
+        );
+        assert_eq!(decode_execute_batch(&data).unwrap(), calls);
+        assert_eq!(detect_wallet_format(&data), WalletFormat::ExecuteBatch(calls.clone()));
+
+        let data = call_data(
+            EXECUTE_BATCH_NO_VALUE_SIGNATURE,
+            &[
+                Token::Array(calls.iter().map(|c| Token::Address(c.to)).collect()),
+                Token::Array(calls.iter().map(|c| Token::Bytes(c.data.to_vec())).collect()),
+            ],
+        );
+        let no_value =
+            calls.into_iter().map(|c| ExecuteCall { value: U256::zero(), ..c }).collect::<Vec<_>>();
+        assert_eq!(decode_execute_batch(&data).unwrap(), no_value);
+
+        let data = call_data(
+            EXECUTE_BATCH_SIGNATURE,
+            &[
+                Token::Array(vec![Token::Address(Address::random())]),
+                Token::Array(vec![]),
+                Token::Array(vec![]),
+            ],
+        );
+        assert_eq!(decode_execute_batch(&data), Err(DecodeError::BatchLength));
+    }
+
+    #[test]
+    fn safe() {
+        let c = call(3);
+        let mut args = tokens(&c);
+        args.push(Token::Uint(U256::zero()));
+        let data = call_data(SAFE_EXECUTE_USER_OP_SIGNATURE, &args);
+        assert_eq!(decode_safe_exec_transaction(&data).unwrap(), c);
+        assert_eq!(detect_wallet_format(&data), WalletFormat::Safe(c.clone()));
+
+        args.extend([
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Uint(U256::zero()),
+            Token::Address(Address::zero()),
+            Token::Address(Address::zero()),
+            Token::Bytes(vec![1; 65]),
+        ]);
+        let data = call_data(SAFE_EXEC_TRANSACTION_SIGNATURE, &args);
+        assert_eq!(decode_safe_exec_transaction(&data).unwrap(), c);
+
+        args[3] = Token::Uint(U256::one());
+        let data = call_data(SAFE_EXEC_TRANSACTION_SIGNATURE, &args);
+        assert_eq!(decode_safe_exec_transaction(&data), Err(DecodeError::DelegateCall));
+    }
+
+    #[test]
+    fn unknown() {
+        assert_eq!(detect_wallet_format(&Bytes::default()), WalletFormat::Unknown);
+        let data = call_data("transfer(address,uint256)", &tokens(&call(1))[..2]);
+        assert_eq!(detect_wallet_format(&data), WalletFormat::Unknown);
+        assert!(matches!(decode_execute(&data), Err(DecodeError::Selector { .. })));
+    }
+}
@@ -6,12 +6,36 @@ pub mod entry_point {
     pub const ADDRESS: &str = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789";
     /// Version of the entry point smart contract
     pub const VERSION: &str = "0.6.0";
+    /// Address of the entry point smart contract v0.7
+    pub const ADDRESS_V0_7: &str = "0x0000000071727De22E5E9d8BAf0edAc6f37da032";
+    /// Version of the entry point smart contract v0.7
+    pub const VERSION_V0_7: &str = "0.7.0";
 }
 
 /// Bundler
 pub mod bundler {
     /// Default time interval for auto bundling mode (in seconds)
     pub const BUNDLE_INTERVAL: u64 = 10;
+    /// Address of the Multicall3 contract (same on all chains)
+    pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+    /// Time interval for checking the inclusion of submitted user operations (in seconds)
+    pub const INCLUSION_POLL_INTERVAL: u64 = 2;
+    /// Number of blocks a submitted user operation is tracked for inclusion before it's dropped
+    pub const INCLUSION_MAX_PENDING_BLOCKS: u64 = 50;
+    /// Time to wait for the receipt of a submitted bundle before it's logged as dropped (in
+    /// seconds)
+    pub const SUBMISSION_CONFIRMATION_TIMEOUT: u64 = 300;
+    /// Number of consecutive failed requests after which a relay is skipped
+    pub const RELAY_FAILURE_THRESHOLD: u32 = 3;
+    /// Time after which a skipped relay is tried again (in seconds)
+    pub const RELAY_RETRY_INTERVAL: u64 = 60;
+    /// Number of blocks a bundle transaction can stay pending before it's resubmitted with bumped
+    /// fees
+    pub const RESUBMIT_INTERVAL_BLOCKS: u64 = 3;
+    /// Number of resubmissions after which a bundle transaction is dropped
+    pub const MAX_RESUBMIT_ATTEMPTS: u8 = 5;
+    /// Percentage increase of fees when resubmitting a bundle transaction
+    pub const RESUBMIT_FEE_INCREASE_PERC: u64 = 10;
 }
 
 /// User operation mempool
@@ -20,6 +44,37 @@ pub mod mempool {
     pub const GAS_INCREASE_PERC: u64 = 10;
     /// Depth scan when searching for previous user operations
     pub const LATEST_SCAN_DEPTH: u64 = 1000;
+    /// Percentile of the mempool priority fees suggested to wallets
+    pub const PRIORITY_FEE_PERCENTILE: usize = 75;
+    /// Percentage added on top of the suggested priority fee
+    pub const PRIORITY_FEE_TIP_PERC: u64 = 10;
+    /// Size (in bytes) of the dummy signature the pre-verification gas is estimated with (ECDSA)
+    pub const DEFAULT_SIGNATURE_SIZE: usize = 65;
+    /// Maximum number of simulations of the verification gas limit binary search
+    pub const ESTIMATION_BINARY_SEARCH_ROUNDS: u64 = 16;
+    /// Time a user operation can stay in the mempool before it's evicted (in seconds)
+    pub const MAX_MEMPOOL_AGE: u64 = 3600;
+    /// Time interval for evicting expired user operations from the mempool (in seconds)
+    pub const MEMPOOL_EXPIRY_INTERVAL: u64 = 60;
+    /// Maximum number of user operations in the mempool (the least profitable ones are evicted
+    /// when it's full)
+    pub const MAX_MEMPOOL_SIZE: usize = 10_000;
+    /// Maximum total gas of a bundle (gas limits of the user operations and the entry point
+    /// overhead)
+    pub const MAX_BUNDLE_GAS: u64 = 10_000_000;
+    /// Maximum number of blocks a reorg is followed back (user operations included in deeper
+    /// reorged out blocks aren't reinstated)
+    pub const REORG_DEPTH_LIMIT: u64 = 64;
+    /// Capacity of the channel the mempool changes are broadcast to (slow subscribers miss the
+    /// oldest changes)
+    pub const USER_OPERATION_EVENTS_CAPACITY: usize = 1024;
+    /// Maximum number of pending user operations of an unstaked sender (enforced when adding to
+    /// the mempool, also if the sanity checks are skipped)
+    pub const MAX_OPS_PER_UNSTAKED_SENDER: usize = 1;
+    /// Maximum number of pending user operations of a staked sender
+    pub const MAX_OPS_PER_STAKED_SENDER: usize = 4;
+    /// Maximum number of user operations of a batch validated concurrently
+    pub const MAX_PARALLEL_VALIDATIONS: usize = 8;
 }
 
 /// User operation validation
@@ -32,6 +87,7 @@ pub mod validation {
         pub const FACTORY: &str = "factory";
         pub const SENDER: &str = "account";
         pub const PAYMASTER: &str = "paymaster";
+        pub const AGGREGATOR: &str = "aggregator";
 
         pub const FACTORY_LEVEL: usize = 0;
         pub const SENDER_LEVEL: usize = 1;
@@ -57,9 +113,38 @@ pub mod validation {
         pub const BAN_SLACK: u64 = 50;
     }
 
+    /// Sanity checks
+    pub mod sanity {
+        /// Minimum verification gas limit (the entry point always charges at least this much)
+        pub const MIN_VERIFICATION_GAS: u64 = 1500;
+        /// Default maximum for verification gas limit + call gas limit of one user operation
+        pub const MAX_COMBINED_GAS: u64 = 10_000_000;
+        /// Minimum call gas limit (base transaction cost + minimum cost of CALL)
+        pub const MIN_CALL_GAS: u64 = 21_000 + 100;
+        /// Gas the entry point spends around the paymaster calls (validatePaymasterUserOp and
+        /// postOp) out of the verification gas limit
+        pub const PAYMASTER_VERIFICATION_GAS_OVERHEAD: u64 = 30_000;
+        /// Default maximum calldata gas of the call data of one user operation
+        pub const MAX_CALLDATA_GAS: u64 = 100_000;
+        /// Default maximum size of the call data of one user operation (in bytes)
+        pub const MAX_CALL_DATA_SIZE: usize = 3500;
+        /// Default maximum size of the init code of one user operation (in bytes)
+        pub const MAX_INIT_CODE_SIZE: usize = 3500;
+        /// Max number of nonces a user operation may be ahead of the on-chain nonce of the
+        /// sender
+        pub const MAX_NONCE_GAP: u64 = 10;
+    }
+
     /// Simulation
     pub mod simulation {
         pub const MIN_EXTRA_GAS: u64 = 2000;
+        /// Default number of user operations whose simulations are cached for the current block
+        pub const SIMULATION_CACHE_SIZE: usize = 500;
+        /// Default number of contracts whose code hashes are cached for the current block
+        pub const CODE_HASH_CACHE_SIZE: usize = 10_000;
+        /// Default time (in seconds) a cached code hash is served for, even if no new block
+        /// arrives
+        pub const CODE_HASH_CACHE_TTL: u64 = 60;
     }
 }
 
@@ -80,6 +165,10 @@ pub mod flashbots_relay_endpoints {
 
     // sepolia
     pub const FLASHBOTS_SEPOLIA: &str = "https://relay-sepolia.flashbots.net";
+
+    // mev-share (`mev_sendBundle` is served by the Flashbots relay)
+    pub const MEV_SHARE: &str = "https://relay.flashbots.net";
+    pub const MEV_SHARE_SEPOLIA: &str = "https://relay-sepolia.flashbots.net";
 }
 
 /// Fastlane relay endpoints
@@ -114,6 +203,8 @@ pub mod rpc {
     pub const HTTP_PORT: u16 = 3000;
     /// The default port for WS
     pub const WS_PORT: u16 = 3001;
+    /// The default port for the health check HTTP server
+    pub const HEALTH_PORT: u16 = 8080;
 }
 
 /// gRPC
@@ -128,6 +219,8 @@ pub mod grpc {
 pub mod storage {
     /// The default path for database
     pub const DATABASE_FOLDER_NAME: &str = "db";
+    /// The default name of the SQLite database file
+    pub const SQLITE_FILE_NAME: &str = "mempool.sqlite";
 }
 
 /// P2P
@@ -186,4 +279,8 @@ pub mod p2p {
     pub const MAX_IPFS_CID_LENGTH: usize = 256;
     /// Public IPFS gateway.
     pub const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs";
+    /// The maximum number of gossiped user operations accepted from a peer per rate limit window
+    pub const MAX_USER_OPERATIONS_PER_PEER: usize = 128;
+    /// The rate limit window for gossiped user operations
+    pub const PEER_RATE_LIMIT_WINDOW: u64 = 60; // seconds
 }
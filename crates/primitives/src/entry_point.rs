@@ -0,0 +1,49 @@
+//! Entry point smart contract versions
+
+use std::{fmt, str::FromStr};
+
+/// Version of the entry point smart contract
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EntryPointVersion {
+    V0_6,
+    V0_7,
+}
+
+impl FromStr for EntryPointVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let version = s.trim().trim_start_matches('v');
+        if version == "0.6" || version.starts_with("0.6.") {
+            Ok(EntryPointVersion::V0_6)
+        } else if version == "0.7" || version.starts_with("0.7.") {
+            Ok(EntryPointVersion::V0_7)
+        } else {
+            Err(format!("Entry point version {s} is not supported"))
+        }
+    }
+}
+
+impl fmt::Display for EntryPointVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntryPointVersion::V0_6 => write!(f, "0.6.0"),
+            EntryPointVersion::V0_7 => write!(f, "0.7.0"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_point_version_display() {
+        assert_eq!(EntryPointVersion::V0_6.to_string(), "0.6.0");
+        assert_eq!(EntryPointVersion::V0_7.to_string(), "0.7.0");
+        assert_eq!(
+            EntryPointVersion::V0_7.to_string().parse::<EntryPointVersion>().unwrap(),
+            EntryPointVersion::V0_7
+        );
+    }
+}
@@ -3,8 +3,10 @@
 //! This crate contains Account abstraction (ERC-4337) primitive types and helper functions.
 
 pub mod bundler;
+pub mod calldata;
 pub mod chain;
 pub mod constants;
+pub mod entry_point;
 pub mod mempool;
 pub mod p2p;
 pub mod provider;
@@ -14,10 +16,14 @@ mod user_operation;
 mod utils;
 mod wallet;
 
-pub use bundler::BundleMode;
-pub use mempool::Mode as UoPoolMode;
+pub use bundler::{BundleMode, RelayHealth, RelayStatus};
+pub use entry_point::EntryPointVersion;
+pub use mempool::{
+    BundleSelectorType, MempoolStats, Mode as UoPoolMode, UserOperationEvent, UserOperationStatus,
+};
 pub use p2p::{MempoolConfig, VerifiedUserOperation};
 pub use user_operation::{
+    get_domain_separator, get_user_op_typehash, pack_uint128, sign_user_op_712, unpack_uint128,
     UserOperation, UserOperationByHash, UserOperationGasEstimation, UserOperationHash,
     UserOperationReceipt, UserOperationRequest, UserOperationSigned,
 };
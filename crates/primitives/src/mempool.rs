@@ -1,5 +1,7 @@
 //! Mempool/related primitives
 
+use crate::UserOperationHash;
+use serde::{Deserialize, Serialize};
 use strum_macros::{EnumString, EnumVariantNames};
 
 /// Verification modes for user operation mempool
@@ -9,3 +11,49 @@ pub enum Mode {
     Standard,
     Unsafe,
 }
+
+/// Determines how the bundle candidates are picked out of the mempool
+#[derive(Clone, Copy, Debug, Default, EnumString, EnumVariantNames, PartialEq, Eq)]
+#[strum(serialize_all = "kebab_case")]
+pub enum BundleSelectorType {
+    /// Takes the user operations with the highest effective gas price first
+    #[default]
+    Greedy,
+    /// Drops the user operations failing `simulateHandleOp`
+    Simulation,
+    /// Maximizes the total fee of the bundle under the gas budget
+    Knapsack,
+}
+
+/// Status of a user operation reported to the mempool subscribers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UserOperationStatus {
+    /// Added to the mempool
+    Pending,
+    /// Removed from the mempool after being included on-chain
+    Included,
+    /// Removed from the mempool without being included (replaced or expired)
+    Dropped,
+}
+
+/// Change of a user operation in the mempool
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserOperationEvent {
+    pub hash: UserOperationHash,
+    pub status: UserOperationStatus,
+}
+
+/// Statistics of the user operations in the mempool
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolStats {
+    /// Number of user operations
+    pub total_ops: usize,
+    /// Number of distinct senders
+    pub unique_senders: usize,
+    /// Number of distinct paymasters
+    pub unique_paymasters: usize,
+    /// Time since the oldest user operation was added (in seconds)
+    pub oldest_op_age_secs: u64,
+}
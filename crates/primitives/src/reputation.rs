@@ -1,6 +1,6 @@
 //! Primitives for reputation
 
-use super::utils::{as_checksum_addr, as_hex_string, as_u64};
+use super::utils::{as_checksum_addr, as_hex_string, as_u64, deserialize_hex_or_number};
 use ethers::{
     prelude::{EthAbiCodec, EthAbiType},
     types::{Address, U256},
@@ -56,11 +56,23 @@ impl From<ReputationStatus> for Status {
 )]
 pub struct ReputationEntry {
     pub address: Address,
-    #[serde(rename = "opsSeen", serialize_with = "as_hex_string")]
+    #[serde(
+        rename = "opsSeen",
+        serialize_with = "as_hex_string",
+        deserialize_with = "deserialize_hex_or_number"
+    )]
     pub uo_seen: u64,
-    #[serde(rename = "opsIncluded", serialize_with = "as_hex_string")]
+    #[serde(
+        rename = "opsIncluded",
+        serialize_with = "as_hex_string",
+        deserialize_with = "deserialize_hex_or_number"
+    )]
     pub uo_included: u64,
-    #[serde(default, serialize_with = "as_hex_string")]
+    #[serde(
+        default,
+        serialize_with = "as_hex_string",
+        deserialize_with = "deserialize_hex_or_number"
+    )]
     pub status: ReputationStatus,
 }
 
@@ -70,6 +82,49 @@ impl ReputationEntry {
     }
 }
 
+/// Reputation status of an entity together with the numbers it's derived from, so operators can
+/// see why the entity is throttled or banned
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatusWithReason {
+    pub status: Status,
+    pub uo_seen: u64,
+    pub uo_included: u64,
+    /// Share of the seen user operations that were included
+    pub inclusion_rate: f64,
+    /// Inclusion rate below which the entity is throttled (banned if the entity is banned)
+    pub threshold: f64,
+    /// Why the entity is throttled or banned (None if the status is ok)
+    pub reason: Option<String>,
+}
+
+/// Reputation entry with the reason of its status (returned by `debug_bundler_dumpReputation`)
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReputationEntryWithReason {
+    #[serde(flatten)]
+    pub entry: ReputationEntry,
+    pub inclusion_rate: f64,
+    pub threshold: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ReputationEntryWithReason {
+    pub fn new(address: Address, status: StatusWithReason) -> Self {
+        Self {
+            entry: ReputationEntry {
+                address,
+                uo_seen: status.uo_seen,
+                uo_included: status.uo_included,
+                status: status.status.into(),
+            },
+            inclusion_rate: status.inclusion_rate,
+            threshold: status.threshold,
+            reason: status.reason,
+        }
+    }
+}
+
 /// Stake info
 #[derive(Clone, Copy, Default, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub struct StakeInfo {
@@ -95,3 +150,64 @@ pub struct StakeInfoResponse {
     #[serde(rename = "isStaked")]
     pub is_staked: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reputation_entry_serde() {
+        let entry = ReputationEntry {
+            address: Address::random(),
+            uo_seen: 10,
+            uo_included: 2,
+            status: Status::THROTTLED.into(),
+        };
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["opsSeen"], "0xa");
+        assert_eq!(json["opsIncluded"], "0x2");
+        assert_eq!(json["status"], "0x1");
+        // the output of `debug_bundler_dumpReputation` is accepted by `debug_bundler_setReputation`
+        assert_eq!(serde_json::from_value::<ReputationEntry>(json).unwrap(), entry);
+
+        let json = serde_json::json!({
+            "address": entry.address,
+            "opsSeen": 10,
+            "opsIncluded": "2",
+        });
+        let entry = serde_json::from_value::<ReputationEntry>(json).unwrap();
+        assert_eq!((entry.uo_seen, entry.uo_included), (10, 2));
+        assert_eq!(Status::from(entry.status), Status::OK);
+    }
+
+    #[test]
+    fn reputation_entry_with_reason_serde() {
+        let address = Address::random();
+        let entry = ReputationEntryWithReason::new(
+            address,
+            StatusWithReason {
+                status: Status::THROTTLED,
+                uo_seen: 200,
+                uo_included: 5,
+                inclusion_rate: 0.025,
+                threshold: 0.05,
+                reason: Some("inclusion rate too low".into()),
+            },
+        );
+
+        let json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["opsSeen"], "0xc8");
+        assert_eq!(json["status"], "0x1");
+        assert_eq!(json["inclusionRate"], 0.025);
+        assert_eq!(json["reason"], "inclusion rate too low");
+        // the entries can be passed to `debug_bundler_setReputation`
+        assert_eq!(
+            serde_json::from_value::<ReputationEntry>(json).unwrap(),
+            ReputationEntry { address, uo_seen: 200, uo_included: 5, status: 1 }
+        );
+
+        let json = serde_json::to_value(ReputationEntryWithReason::default()).unwrap();
+        assert!(json.get("reason").is_none());
+    }
+}
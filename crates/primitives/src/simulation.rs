@@ -35,6 +35,8 @@ lazy_static! {
         set.insert("SELFDESTRUCT".into());
         set.insert("RANDOM".into());
         set.insert("PREVRANDAO".into());
+        set.insert("BLOBHASH".into());
+        set.insert("BLOBBASEFEE".into());
         set
     };
 }
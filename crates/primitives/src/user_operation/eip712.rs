@@ -0,0 +1,190 @@
+//! EIP-712 structured data signing of user operations
+
+use super::UserOperationSigned;
+use crate::constants::entry_point::VERSION;
+use ethers::{
+    abi::{encode, Token},
+    signers::LocalWallet,
+    types::{Address, Bytes, H256, U256},
+    utils::keccak256,
+};
+use std::ops::Deref;
+
+/// EIP-712 domain type (the entry point is the verifying contract)
+const DOMAIN_TYPE: &str = "EIP712Domain(string version,uint256 chainId,address verifyingContract)";
+
+/// EIP-712 domain type with the name of the signing domain
+const NAMED_DOMAIN_TYPE: &str =
+    "EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+
+/// EIP-712 user operation type (bytes fields are hashed when encoded)
+const USER_OPERATION_TYPE: &str = "UserOperation(address sender,uint256 nonce,bytes initCode,bytes callData,uint256 callGasLimit,uint256 verificationGasLimit,uint256 preVerificationGas,uint256 maxFeePerGas,uint256 maxPriorityFeePerGas,bytes paymasterAndData)";
+
+/// Returns the EIP-712 domain separator for the entry point on the given chain
+///
+/// # Arguments
+/// * `entry_point` - The entry point contract address
+/// * `chain_id` - The chain id of the blockchain network
+///
+/// # Returns
+/// * `H256` - `keccak256(abi.encode(DOMAIN_TYPEHASH, VERSION_HASH, chain_id, entry_point))`
+pub fn get_domain_separator(entry_point: &Address, chain_id: &U256) -> H256 {
+    hash_domain(None, VERSION, chain_id, entry_point)
+}
+
+/// Hashes the EIP-712 domain (the name is left out of the domain type if not set)
+fn hash_domain(
+    name: Option<&str>,
+    version: &str,
+    chain_id: &U256,
+    verifying_contract: &Address,
+) -> H256 {
+    let mut tokens = vec![Token::FixedBytes(
+        keccak256(if name.is_some() { NAMED_DOMAIN_TYPE } else { DOMAIN_TYPE }).to_vec(),
+    )];
+    if let Some(name) = name {
+        tokens.push(Token::FixedBytes(keccak256(name).to_vec()));
+    }
+    tokens.extend([
+        Token::FixedBytes(keccak256(version).to_vec()),
+        Token::Uint(*chain_id),
+        Token::Address(*verifying_contract),
+    ]);
+
+    keccak256(encode(&tokens)).into()
+}
+
+/// Hashes the EIP-712 typed data: `keccak256("\x19\x01" || domainSeparator || hashStruct(message))`
+fn hash_typed_data(domain_separator: H256, struct_hash: H256) -> H256 {
+    keccak256([&[0x19, 0x01], domain_separator.as_bytes(), struct_hash.as_bytes()].concat()).into()
+}
+
+/// Signs the EIP-712 digest (without the EIP-191 personal message prefix)
+fn sign_typed_data_hash(signer: &LocalWallet, digest: H256) -> eyre::Result<Bytes> {
+    let sig = signer.sign_hash(digest)?;
+    Ok(sig.to_vec().into())
+}
+
+/// Returns the EIP-712 type hash of the user operation
+pub fn get_user_op_typehash() -> H256 {
+    keccak256(USER_OPERATION_TYPE).into()
+}
+
+impl UserOperationSigned {
+    /// Calculates the EIP-712 digest of the user operation (signature is not included)
+    pub fn hash_712(&self, entry_point: &Address, chain_id: &U256) -> H256 {
+        let struct_hash = keccak256(
+            [get_user_op_typehash().as_bytes(), self.pack_without_signature().deref()].concat(),
+        );
+
+        hash_typed_data(get_domain_separator(entry_point, chain_id), struct_hash.into())
+    }
+}
+
+/// Signs the user operation using EIP-712 structured data
+///
+/// # Arguments
+/// * `uo` - The [UserOperationSigned](UserOperationSigned) to be signed
+/// * `signer` - The wallet used for signing
+/// * `entry_point` - The entry point contract address
+/// * `chain_id` - The chain id of the blockchain network
+///
+/// # Returns
+/// * `Bytes` - The 65 bytes signature (r, s, v)
+pub async fn sign_user_op_712(
+    uo: &UserOperationSigned,
+    signer: &LocalWallet,
+    entry_point: &Address,
+    chain_id: &U256,
+) -> eyre::Result<Bytes> {
+    sign_typed_data_hash(signer, uo.hash_712(entry_point, chain_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::entry_point::ADDRESS;
+    use ethers::signers::Signer;
+
+    const KEY: &str = "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+
+    fn user_operation() -> UserOperationSigned {
+        UserOperationSigned::default()
+            .sender("0x9c5754De1443984659E1b3a8d1931D83475ba29C".parse().unwrap())
+            .call_gas_limit(200_000.into())
+            .verification_gas_limit(100_000.into())
+            .pre_verification_gas(21_000.into())
+            .max_fee_per_gas(3_000_000_000_u64.into())
+            .max_priority_fee_per_gas(1_000_000_000.into())
+    }
+
+    /// The `Mail` example of the EIP-712 specification (`assets/eip-712/Example.js`)
+    #[test]
+    fn eip712_reference_vectors() {
+        let domain_separator = hash_domain(
+            Some("Ether Mail"),
+            "1",
+            &U256::from(1),
+            &"0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC".parse().unwrap(),
+        );
+        assert_eq!(
+            domain_separator,
+            "0xf2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f".parse().unwrap()
+        );
+
+        let struct_hash =
+            "0xc52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e".parse().unwrap();
+        let digest = hash_typed_data(domain_separator, struct_hash);
+        assert_eq!(
+            digest,
+            "0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2".parse().unwrap()
+        );
+
+        // private key keccak256("cow")
+        let signer = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4"
+            .parse::<LocalWallet>()
+            .unwrap();
+        assert_eq!(
+            signer.address(),
+            "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826".parse::<Address>().unwrap()
+        );
+        let sig = sign_typed_data_hash(&signer, digest).unwrap();
+        assert_eq!(sig, "0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c".parse::<Bytes>().unwrap());
+    }
+
+    #[test]
+    fn domain_separator_and_typehash() {
+        let ep: Address = ADDRESS.parse().unwrap();
+        assert_eq!(
+            get_domain_separator(&ep, &U256::from(1)),
+            "0xad174fa74dc99bde79baa57cca5c3fb8978898dc40d11f1181536c640bbfb907".parse().unwrap()
+        );
+        assert_ne!(
+            get_domain_separator(&ep, &U256::from(1)),
+            get_domain_separator(&ep, &U256::from(5))
+        );
+        assert_eq!(
+            get_user_op_typehash(),
+            "0xf81bea993d11db0909d00c3af86d2329d5a9069b5297725281150d0eca354139".parse().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn user_operation_sign_712() {
+        let ep: Address = ADDRESS.parse().unwrap();
+        let signer = KEY.parse::<LocalWallet>().unwrap();
+        let uo = user_operation();
+
+        let digest = uo.hash_712(&ep, &U256::from(1));
+        assert_eq!(
+            digest,
+            "0x5b97aba746631a2632ab6740f64b1523eb08916a48cddf3af4dfa04d524a05ea".parse().unwrap()
+        );
+
+        let sig = sign_user_op_712(&uo, &signer, &ep, &U256::from(1)).await.unwrap();
+        assert_eq!(sig, "0xde89c97176a4d3158f01e6bc3757fac391c88a014bafd5cc8eafa382ede806037007abff94b69aa3fe76d644e2af2825ba76c4f952443d22db663f8173cd3e5e1c".parse::<Bytes>().unwrap());
+
+        let sig = ethers::types::Signature::try_from(sig.as_ref()).unwrap();
+        assert_eq!(sig.recover(digest).unwrap(), signer.address());
+    }
+}
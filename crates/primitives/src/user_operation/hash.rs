@@ -3,9 +3,21 @@
 use ethers::types::H256;
 use rustc_hex::FromHexError;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
+use std::{fmt, ops::Deref, str::FromStr};
 
 /// User operation hash
+///
+/// A newtype rather than an alias, so block or transaction hashes can't be passed by mistake:
+///
+/// ```compile_fail
+/// use ethers::types::H256;
+/// use silius_primitives::UserOperationHash;
+///
+/// fn remove(_hash: &UserOperationHash) {}
+///
+/// let block_hash = H256::zero();
+/// remove(&block_hash);
+/// ```
 #[derive(
     Eq, Hash, PartialEq, Debug, Serialize, Deserialize, Clone, Copy, Default, PartialOrd, Ord,
 )]
@@ -23,6 +35,21 @@ impl From<UserOperationHash> for H256 {
     }
 }
 
+impl Deref for UserOperationHash {
+    type Target = H256;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl fmt::Display for UserOperationHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // `H256` display shortens the hash
+        write!(f, "{:?}", self.0)
+    }
+}
+
 impl From<[u8; 32]> for UserOperationHash {
     fn from(value: [u8; 32]) -> Self {
         Self(H256::from_slice(&value))
@@ -67,3 +94,17 @@ impl UserOperationHash {
         ret
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_and_parse() {
+        let hash = "0x8c7a6d6ec1e0b3a8a8d3f2fdc2b63d4e1d9c4a3e6f0b2c1d7e5a4b3c2d1e0f9a";
+        let uo_hash = UserOperationHash::from_str(hash).unwrap();
+
+        assert_eq!(uo_hash.to_string(), hash);
+        assert_eq!(uo_hash.as_bytes(), H256::from(uo_hash).as_bytes());
+    }
+}
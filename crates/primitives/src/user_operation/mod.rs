@@ -1,10 +1,17 @@
 //! Basic transaction type for account abstraction (ERC-4337)
 
+mod eip712;
 mod hash;
 mod request;
 
-use crate::{get_address, utils::as_checksum_addr};
+use crate::{
+    calldata::{detect_wallet_format, WalletFormat},
+    get_address,
+    utils::as_checksum_addr,
+    EntryPointVersion,
+};
 use derive_more::{AsRef, Deref};
+pub use eip712::{get_domain_separator, get_user_op_typehash, sign_user_op_712};
 use ethers::{
     abi::AbiEncode,
     contract::{EthAbiCodec, EthAbiType},
@@ -27,6 +34,10 @@ pub struct UserOperation {
     #[deref]
     #[as_ref]
     pub user_operation: UserOperationSigned,
+
+    /// Signature aggregator of the user operation (known after the simulation)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aggregator: Option<Address>,
 }
 
 impl UserOperation {
@@ -34,7 +45,7 @@ impl UserOperation {
         hash: UserOperationHash,
         user_operation: UserOperationSigned,
     ) -> Self {
-        Self { hash, user_operation }
+        Self { hash, user_operation, aggregator: None }
     }
 }
 
@@ -129,32 +140,168 @@ impl From<UserOperationSigned> for UserOperationNoSignature {
     }
 }
 
+/// User operation without signature in the packed format of entry point v0.7 (helper for
+/// calculating the hash)
+#[derive(EthAbiCodec, EthAbiType)]
+struct PackedUserOperationNoSignature {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: H256,
+    pub call_data: H256,
+    pub account_gas_limits: H256,
+    pub pre_verification_gas: U256,
+    pub gas_fees: H256,
+    pub paymaster_and_data: H256,
+}
+
+/// User operation in the packed format of entry point v0.7 (helper for the encoding of the user
+/// operation in the call data of `handleOps`)
+#[derive(EthAbiCodec, EthAbiType)]
+struct PackedUserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    pub account_gas_limits: H256,
+    pub pre_verification_gas: U256,
+    pub gas_fees: H256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl From<UserOperationSigned> for PackedUserOperation {
+    fn from(value: UserOperationSigned) -> Self {
+        Self {
+            sender: value.sender,
+            nonce: value.nonce,
+            init_code: value.init_code,
+            call_data: value.call_data,
+            account_gas_limits: pack_uint128(value.verification_gas_limit, value.call_gas_limit),
+            pre_verification_gas: value.pre_verification_gas,
+            gas_fees: pack_uint128(value.max_priority_fee_per_gas, value.max_fee_per_gas),
+            paymaster_and_data: value.paymaster_and_data,
+            signature: value.signature,
+        }
+    }
+}
+
+/// Packs two 128-bit values into a single word (`high` in the upper 16 bytes)
+pub fn pack_uint128(high: U256, low: U256) -> H256 {
+    let mut word = [0u8; 32];
+    ((high << 128) | (low & U256::from(u128::MAX))).to_big_endian(&mut word);
+    word.into()
+}
+
+/// Unpacks a word into two 128-bit values (`high` from the upper 16 bytes)
+pub fn unpack_uint128(word: H256) -> (U256, U256) {
+    let value = U256::from_big_endian(word.as_bytes());
+    (value >> 128, value & U256::from(u128::MAX))
+}
+
+impl From<UserOperationSigned> for PackedUserOperationNoSignature {
+    fn from(value: UserOperationSigned) -> Self {
+        Self {
+            sender: value.sender,
+            nonce: value.nonce,
+            init_code: keccak256(value.init_code.deref()).into(),
+            call_data: keccak256(value.call_data.deref()).into(),
+            account_gas_limits: pack_uint128(value.verification_gas_limit, value.call_gas_limit),
+            pre_verification_gas: value.pre_verification_gas,
+            gas_fees: pack_uint128(value.max_priority_fee_per_gas, value.max_fee_per_gas),
+            paymaster_and_data: keccak256(value.paymaster_and_data.deref()).into(),
+        }
+    }
+}
+
+/// Hashes the packed user operation together with the entry point and the chain ID
+fn hash_packed(packed: &Bytes, entry_point: &Address, chain_id: &U256) -> H256 {
+    H256::from_slice(
+        keccak256(
+            [keccak256(packed.deref()).to_vec(), entry_point.encode(), chain_id.encode()].concat(),
+        )
+        .as_slice(),
+    )
+}
+
 impl UserOperationSigned {
-    /// Packs the user operation into bytes
+    /// Packs the user operation into bytes (ABI encoding of the whole user operation, including
+    /// the signature, as in the call data of `handleOps`)
     pub fn pack(&self) -> Bytes {
         self.clone().encode().into()
     }
 
+    /// Packs the user operation into bytes in the format of the entry point version (the
+    /// encoding in the call data of `handleOps`)
+    pub fn pack_for(&self, version: EntryPointVersion) -> Bytes {
+        match version {
+            EntryPointVersion::V0_6 => self.pack(),
+            EntryPointVersion::V0_7 => PackedUserOperation::from(self.clone()).encode().into(),
+        }
+    }
+
+    /// Returns the paymaster verification and post-op gas limits (entry point v0.7 stores them
+    /// in `paymaster_and_data` after the paymaster address)
+    pub fn paymaster_gas_limits(&self) -> Option<(U256, U256)> {
+        if self.paymaster_and_data.len() < 52 {
+            return None;
+        }
+        Some(unpack_uint128(H256::from_slice(&self.paymaster_and_data[20..52])))
+    }
+
+    /// Decodes the call data with the formats of the common wallets
+    pub fn decode_call_data(&self) -> WalletFormat {
+        detect_wallet_format(&self.call_data)
+    }
+
     /// Packs the user operation without signature to bytes (used for calculating the hash)
+    ///
+    /// This is the encoding `EntryPoint.getUserOpHash` hashes before adding the entry point and
+    /// the chain ID: the dynamic fields are replaced by their keccak256 hashes.
     pub fn pack_without_signature(&self) -> Bytes {
         let user_operation_packed = UserOperationNoSignature::from(self.clone());
         user_operation_packed.encode().into()
     }
 
+    /// Packs the user operation without signature in the format of the entry point version
+    ///
+    /// For v0.7 the gas limits and fees are packed in pairs, and `paymaster_and_data` is expected
+    /// to already contain the paymaster gas limits.
+    pub fn pack_without_signature_for(&self, version: EntryPointVersion) -> Bytes {
+        match version {
+            EntryPointVersion::V0_6 => self.pack_without_signature(),
+            EntryPointVersion::V0_7 => {
+                PackedUserOperationNoSignature::from(self.clone()).encode().into()
+            }
+        }
+    }
+
     /// Calculates the hash of the user operation
     pub fn hash(&self, entry_point: &Address, chain_id: u64) -> UserOperationHash {
-        H256::from_slice(
-            keccak256(
-                [
-                    keccak256(self.pack_without_signature().deref()).to_vec(),
-                    entry_point.encode(),
-                    U256::from(chain_id).encode(),
-                ]
-                .concat(),
-            )
-            .as_slice(),
-        )
-        .into()
+        self.hash_for(entry_point, chain_id, EntryPointVersion::V0_6)
+    }
+
+    /// Calculates the hash of the user operation for the entry point version
+    pub fn hash_for(
+        &self,
+        entry_point: &Address,
+        chain_id: u64,
+        version: EntryPointVersion,
+    ) -> UserOperationHash {
+        hash_packed(&self.pack_without_signature_for(version), entry_point, &U256::from(chain_id))
+            .into()
+    }
+
+    /// Calculates the hash the account signs, the same as `EntryPoint.getUserOpHash` (entry point
+    /// v0.6): `keccak256(abi.encode(keccak256(pack_without_signature()), entry_point, chain_id))`
+    ///
+    /// # Arguments
+    /// * `entry_point` - The address of the entry point
+    /// * `chain_id` - The chain ID
+    ///
+    /// # Returns
+    /// * `H256` - The hash of the user operation
+    pub fn hash_with_entry_point(&self, entry_point: &Address, chain_id: &U256) -> H256 {
+        hash_packed(&self.pack_without_signature(), entry_point, chain_id)
     }
 
     // Builder pattern helpers
@@ -447,9 +594,10 @@ pub struct UserOperationByHash {
     pub user_operation: UserOperationSigned,
     #[serde(serialize_with = "as_checksum_addr")]
     pub entry_point: Address,
-    pub transaction_hash: H256,
-    pub block_hash: H256,
-    pub block_number: U64,
+    /// None (null) while the user operation is pending in the mempool
+    pub transaction_hash: Option<H256>,
+    pub block_hash: Option<H256>,
+    pub block_number: Option<U64>,
 }
 
 /// Gas estimations for user operation (returned from the RPC endpoint eth_estimateUserOperationGas)
@@ -498,6 +646,61 @@ mod tests {
         assert_eq!(uos[1].pack_without_signature(), "0x0000000000000000000000009c5754de1443984659e1b3a8d1931d83475ba29c0000000000000000000000000000000000000000000000000000000000000001c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470f7def7aeb687d6992b466243b713223689982cefca0f91a1f5c5f60adb532b93000000000000000000000000000000000000000000000000000000000000814c000000000000000000000000000000000000000000000000000000000000ecd0000000000000000000000000000000000000000000000000000000000000ac18000000000000000000000000000000000000000000000000000000006507a5de000000000000000000000000000000000000000000000000000000006507a5c0c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470".parse::<Bytes>().unwrap());
     }
 
+    #[test]
+    fn user_operation_signed_pack_without_signature_v0_7() {
+        let uo = UserOperationSigned {
+            sender: "0x9c5754De1443984659E1b3a8d1931D83475ba29C".parse().unwrap(),
+            nonce: 1.into(),
+            init_code: Bytes::default(),
+            call_data: "0xb61d27f60000000000000000000000009c5754de1443984659e1b3a8d1931d83475ba29c00000000000000000000000000000000000000000000000000005af3107a400000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_gas_limit: 33_100.into(),
+            verification_gas_limit: 60_624.into(),
+            pre_verification_gas: 44_056.into(),
+            max_fee_per_gas: 1_695_000_030_u64.into(),
+            max_priority_fee_per_gas: 1_695_000_000.into(),
+            paymaster_and_data: Bytes::default(),
+            signature: Bytes::default(),
+        };
+        assert_eq!(
+            uo.pack_without_signature_for(EntryPointVersion::V0_6),
+            uo.pack_without_signature()
+        );
+        assert_eq!(uo.pack_without_signature_for(EntryPointVersion::V0_7), "0x0000000000000000000000009c5754de1443984659e1b3a8d1931d83475ba29c0000000000000000000000000000000000000000000000000000000000000001c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470f7def7aeb687d6992b466243b713223689982cefca0f91a1f5c5f60adb532b930000000000000000000000000000ecd00000000000000000000000000000814c000000000000000000000000000000000000000000000000000000000000ac180000000000000000000000006507a5c00000000000000000000000006507a5dec5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470".parse::<Bytes>().unwrap());
+        assert_ne!(
+            uo.hash_for(&Address::zero(), 1, EntryPointVersion::V0_7),
+            uo.hash(&Address::zero(), 1)
+        );
+    }
+
+    #[test]
+    fn user_operation_signed_pack_v0_7() {
+        let uo = UserOperationSigned::default()
+            .call_gas_limit(33_100.into())
+            .verification_gas_limit(60_624.into())
+            .max_fee_per_gas(1_695_000_030_u64.into())
+            .max_priority_fee_per_gas(1_695_000_000.into());
+        assert_eq!(uo.pack_for(EntryPointVersion::V0_6), uo.pack());
+        // two words less (the gas limits and the fees are packed in pairs)
+        assert_eq!(uo.pack_for(EntryPointVersion::V0_7).len(), uo.pack().len() - 64);
+        assert_eq!(
+            unpack_uint128(pack_uint128(uo.verification_gas_limit, uo.call_gas_limit)),
+            (uo.verification_gas_limit, uo.call_gas_limit)
+        );
+    }
+
+    #[test]
+    fn user_operation_signed_paymaster_gas_limits() {
+        let paymaster: Address = "0x9c5754De1443984659E1b3a8d1931D83475ba29C".parse().unwrap();
+        let uo = UserOperationSigned::default().paymaster_and_data(
+            [paymaster.as_bytes(), pack_uint128(100_000.into(), 50_000.into()).as_bytes()]
+                .concat()
+                .into(),
+        );
+        assert_eq!(uo.paymaster_gas_limits(), Some((100_000.into(), 50_000.into())));
+        assert_eq!(uo.get_entities().2, Some(paymaster));
+        assert_eq!(UserOperationSigned::default().paymaster_gas_limits(), None);
+    }
+
     #[test]
     fn user_operation_signed_hash() {
         let uos =  vec![
@@ -532,6 +735,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn user_operation_signed_hash_with_entry_point() {
+        // reference vector: user operation of a SimpleAccount for the v0.6 entry point on chain
+        // 80001, the hash matches `EntryPoint.getUserOpHash`
+        let uo = UserOperationSigned {
+            sender: "0x9c5754De1443984659E1b3a8d1931D83475ba29C".parse().unwrap(),
+            nonce: U256::zero(),
+            init_code: "0x9406cc6185a346906296840746125a0e449764545fbfb9cf000000000000000000000000ce0fefa6f7979c4c9b5373e0f5105b7259092c6d0000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_data: "0xb61d27f60000000000000000000000009c5754de1443984659e1b3a8d1931d83475ba29c00000000000000000000000000000000000000000000000000005af3107a400000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000".parse().unwrap(),
+            call_gas_limit: 33_100.into(),
+            verification_gas_limit: 361_460.into(),
+            pre_verification_gas: 44_980.into(),
+            max_fee_per_gas: 1_695_000_030_u64.into(),
+            max_priority_fee_per_gas: 1_695_000_000.into(),
+            paymaster_and_data: Bytes::default(),
+            signature: Bytes::default(),
+        };
+        let entry_point: Address = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".parse().unwrap();
+        let uo_hash = uo.hash_with_entry_point(&entry_point, &U256::from(80_001));
+
+        assert_eq!(
+            uo_hash,
+            "0x7c1b8c9df49a9e09ecef0f0fe6841d895850d29820f9a4b494097764085dcd7e"
+                .parse::<H256>()
+                .unwrap()
+        );
+        assert_eq!(UserOperationHash::from(uo_hash), uo.hash(&entry_point, 80_001));
+        // the signature isn't part of the hash, the other fields are
+        assert_eq!(
+            uo.clone()
+                .signature("0x1234".parse().unwrap())
+                .hash_with_entry_point(&entry_point, &U256::from(80_001)),
+            uo_hash
+        );
+        assert_ne!(
+            uo.clone().nonce(U256::one()).hash_with_entry_point(&entry_point, &U256::from(80_001)),
+            uo_hash
+        );
+        assert_ne!(uo.hash_with_entry_point(&entry_point, &U256::from(1)), uo_hash);
+    }
+
     #[test]
     fn user_operation_signed_ssz() {
         let uo = UserOperationSigned {
@@ -45,6 +45,29 @@ where
     serde_hex::SerHex::<serde_hex::StrictPfx>::serialize(val, s)
 }
 
+/// Deserializes u64 from a hex string, a decimal string or a number (the counterpart of
+/// [as_hex_string](as_hex_string))
+pub fn deserialize_hex_or_number<'de, D>(deserializer: D) -> Result<u64, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum HexOrNumber {
+        Number(u64),
+        String(String),
+    }
+
+    match HexOrNumber::deserialize(deserializer)? {
+        HexOrNumber::Number(n) => Ok(n),
+        HexOrNumber::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => s.parse(),
+        }
+        .map_err(serde::de::Error::custom),
+    }
+}
+
 /// Helper to deserialize float string to U256
 pub fn deserialize_stringified_float<'de, D>(deserializer: D) -> Result<U256, D::Error>
 where
@@ -189,6 +189,7 @@ impl Wallet {
         Ok(UserOperation {
             hash: h,
             user_operation: UserOperationSigned { signature: sig.to_vec().into(), ..uo.clone() },
+            aggregator: None,
         })
     }
 }
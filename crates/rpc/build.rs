@@ -0,0 +1,16 @@
+use std::{env, process::Command};
+
+fn main() {
+    // version of the compiler building the crate (reported by `web3_clientVersion`)
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .and_then(|version| version.split_whitespace().nth(1).map(str::to_string))
+        .unwrap_or_else(|| "unknown".into());
+
+    println!("cargo:rustc-env=SILIUS_RUSTC_VERSION={version}");
+    println!("cargo:rerun-if-env-changed=RUSTC");
+}
@@ -9,3 +9,4 @@ pub const SIGNATURE: i32 = -32507;
 pub const EXECUTION: i32 = -32521;
 pub const USER_OPERATION_HASH: i32 = -32601;
 pub const SANITY: i32 = -32602;
+pub const TOO_MANY_REQUESTS: i32 = -32429;
@@ -1,22 +1,31 @@
 use crate::{
-    debug_api::{DebugApiServer, ResponseSuccess},
-    error::JsonRpcError,
+    codes::EXECUTION,
+    debug_api::{
+        DebugApiServer, EntityRelationships, MempoolPage, PaymasterOperations, ResponseSuccess,
+        SetReputationEntry, UserOperationHashOrRequest,
+    },
+    error::RpcError,
+    silius_api::ValidationReport,
 };
 use async_trait::async_trait;
 use ethers::types::{Address, H256};
 use jsonrpsee::{
     core::RpcResult,
-    types::{error::INTERNAL_ERROR_CODE, ErrorObjectOwned},
+    types::{error::ErrorCode, ErrorObjectOwned},
 };
 use silius_grpc::{
     bundler_client::BundlerClient, uo_pool_client::UoPoolClient, AddMempoolRequest,
-    GetAllReputationRequest, GetAllRequest, GetStakeInfoRequest, Mode as GrpcMode,
-    SetBundleModeRequest, SetReputationRequest, SetReputationResult,
+    GetAllByEntityRequest, GetAllReputationRequest, GetAllRequest, GetEntityRelationshipsRequest,
+    GetMempoolPagedRequest, GetMempoolStatsRequest, GetStakeInfoRequest,
+    GetUserOperationsByPaymasterRequest, Mode as GrpcMode, SetBundleModeRequest,
+    SetReputationRequest, SetReputationResult, TraceUserOperationRequest,
+    ValidateUserOperationRequest,
 };
 use silius_primitives::{
     constants::bundler::BUNDLE_INTERVAL,
-    reputation::{ReputationEntry, StakeInfoResponse},
-    BundleMode, UserOperation, UserOperationRequest, UserOperationSigned,
+    reputation::{ReputationEntry, ReputationEntryWithReason, StakeInfoResponse, Status},
+    BundleMode, MempoolStats, RelayStatus, UserOperation, UserOperationRequest,
+    UserOperationSigned,
 };
 use tonic::Request;
 
@@ -40,7 +49,7 @@ impl DebugApiServer for DebugApiServerImpl {
         uopool_grpc_client
             .clear_mempool(Request::new(()))
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         Ok(ResponseSuccess::Ok)
@@ -57,7 +66,24 @@ impl DebugApiServer for DebugApiServerImpl {
         uopool_grpc_client
             .clear_reputation(Request::new(()))
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        Ok(ResponseSuccess::Ok)
+    }
+
+    /// Clears the stored receipts of the included user operations
+    ///
+    ///
+    /// # Returns
+    /// * `RpcResult<ResponseSuccess>` - Ok
+    async fn clear_receipts(&self) -> RpcResult<ResponseSuccess> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        uopool_grpc_client
+            .clear_receipts(Request::new(()))
+            .await
+            .map_err(RpcError::from)?
             .into_inner();
 
         Ok(ResponseSuccess::Ok)
@@ -71,7 +97,7 @@ impl DebugApiServer for DebugApiServerImpl {
     async fn clear_state(&self) -> RpcResult<ResponseSuccess> {
         let mut uopool_grpc_client = self.uopool_grpc_client.clone();
 
-        uopool_grpc_client.clear(Request::new(())).await.map_err(JsonRpcError::from)?.into_inner();
+        uopool_grpc_client.clear(Request::new(())).await.map_err(RpcError::from)?.into_inner();
 
         Ok(ResponseSuccess::Ok)
     }
@@ -97,7 +123,7 @@ impl DebugApiServer for DebugApiServerImpl {
         let res = uopool_grpc_client
             .get_chain_id(Request::new(()))
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         uopool_grpc_client
@@ -116,7 +142,7 @@ impl DebugApiServer for DebugApiServerImpl {
                 ep: Some(ep.into()),
             }))
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         Ok(ResponseSuccess::Ok)
@@ -135,7 +161,7 @@ impl DebugApiServer for DebugApiServerImpl {
 
         let req = Request::new(GetAllRequest { ep: Some(ep.into()) });
 
-        let res = uopool_grpc_client.get_all(req).await.map_err(JsonRpcError::from)?.into_inner();
+        let res = uopool_grpc_client.get_all(req).await.map_err(RpcError::from)?.into_inner();
 
         let mut uos: Vec<UserOperationRequest> = res
             .uos
@@ -146,12 +172,267 @@ impl DebugApiServer for DebugApiServerImpl {
         Ok(uos)
     }
 
+    /// Sending an [GetMempoolPagedRequest](GetMempoolPagedRequest) to the UoPool gRPC server
+    /// to get a page of the [UserOperations](UserOperationRequest) in the mempool.
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    /// * `page: u32` - The page number (starting at 0).
+    /// * `page_size: u32` - The maximum number of user operations in the page.
+    ///
+    /// # Returns
+    /// * `RpcResult<MempoolPage>` - The [UserOperations](UserOperationRequest) of the page and the
+    ///   total number of user operations and pages
+    async fn dump_mempool_paginated(
+        &self,
+        ep: Address,
+        page: u32,
+        page_size: u32,
+    ) -> RpcResult<MempoolPage> {
+        if page_size == 0 {
+            return Err(ErrorObjectOwned::owned(
+                ErrorCode::InvalidParams.code(),
+                "page size must be greater than 0",
+                None::<bool>,
+            ));
+        }
+
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let req = Request::new(GetMempoolPagedRequest {
+            ep: Some(ep.into()),
+            offset: u64::from(page) * u64::from(page_size),
+            limit: page_size,
+        });
+
+        let res =
+            uopool_grpc_client.get_mempool_paged(req).await.map_err(RpcError::from)?.into_inner();
+
+        let ops =
+            res.uos.into_iter().map(|uo| UserOperation::from(uo).user_operation.into()).collect();
+        let total_pages = res.total.div_ceil(u64::from(page_size));
+        Ok(MempoolPage {
+            ops,
+            total: res.total,
+            page,
+            total_pages: u32::try_from(total_pages).unwrap_or(u32::MAX),
+        })
+    }
+
+    /// Sending an [GetAllByEntityRequest](GetAllByEntityRequest) to the UoPool gRPC server
+    /// to get the [UserOperations](UserOperationRequest) that use the entity.
+    ///
+    /// # Arguments
+    /// * `entity: Address` - The address of the entity (factory or paymaster).
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<Vec<UserOperationRequest>>` - An array of [UserOperation](UserOperationRequest)
+    async fn get_operations_by_entity(
+        &self,
+        entity: Address,
+        ep: Address,
+    ) -> RpcResult<Vec<UserOperationRequest>> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let req = Request::new(GetAllByEntityRequest {
+            ep: Some(ep.into()),
+            entity: Some(entity.into()),
+        });
+
+        let res =
+            uopool_grpc_client.get_all_by_entity(req).await.map_err(RpcError::from)?.into_inner();
+
+        let mut uos: Vec<UserOperationRequest> = res
+            .uos
+            .iter()
+            .map(|uo| UserOperation::from(uo.clone()).user_operation.into())
+            .collect();
+        uos.sort_by(|a, b| a.nonce.cmp(&b.nonce));
+        Ok(uos)
+    }
+
+    /// Sending an [GetUserOperationsByPaymasterRequest](GetUserOperationsByPaymasterRequest) to
+    /// the UoPool gRPC server to get the [UserOperations](UserOperationRequest) sponsored by the
+    /// paymaster.
+    ///
+    /// # Arguments
+    /// * `paymaster: Address` - The address of the paymaster.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<PaymasterOperations>` - The [UserOperations](UserOperationRequest) and the
+    ///   total gas they commit the paymaster to
+    async fn get_operations_by_paymaster(
+        &self,
+        paymaster: Address,
+        ep: Address,
+    ) -> RpcResult<PaymasterOperations> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let req = Request::new(GetUserOperationsByPaymasterRequest {
+            ep: Some(ep.into()),
+            paymaster: Some(paymaster.into()),
+        });
+
+        let res = uopool_grpc_client
+            .get_user_operations_by_paymaster(req)
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        let mut user_operations: Vec<UserOperationRequest> =
+            res.uos.into_iter().map(|uo| UserOperation::from(uo).user_operation.into()).collect();
+        user_operations.sort_by(|a, b| a.nonce.cmp(&b.nonce));
+        Ok(PaymasterOperations { user_operations, total_committed_gas: res.total_committed_gas })
+    }
+
+    /// Sending a [GetEntityRelationshipsRequest](GetEntityRelationshipsRequest) to the UoPool gRPC
+    /// server to get the relationships of the address with the other entities of the
+    /// [UserOperations](UserOperationRequest) in the mempool.
+    ///
+    /// # Arguments
+    /// * `address: Address` - The address of the sender, factory or paymaster.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<EntityRelationships>` - The factories and paymasters used by the address as the
+    ///   sender, and the senders using the address as the factory or paymaster
+    async fn get_entity_relationships(
+        &self,
+        address: Address,
+        ep: Address,
+    ) -> RpcResult<EntityRelationships> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let req = Request::new(GetEntityRelationshipsRequest {
+            ep: Some(ep.into()),
+            addr: Some(address.into()),
+        });
+
+        let res = uopool_grpc_client
+            .get_entity_relationships(req)
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        let mut entities: Vec<Address> = res.entities.into_iter().map(Into::into).collect();
+        let mut senders: Vec<Address> = res.senders.into_iter().map(Into::into).collect();
+        entities.sort();
+        senders.sort();
+        Ok(EntityRelationships { entities, senders })
+    }
+
+    /// Get the statistics of the mempool from the UoPool gRPC service.
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<MempoolStats>` - The [statistics](MempoolStats) of the mempool
+    async fn get_mempool_stats(&self, ep: Address) -> RpcResult<MempoolStats> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let req = Request::new(GetMempoolStatsRequest { ep: Some(ep.into()) });
+
+        let res =
+            uopool_grpc_client.get_mempool_stats(req).await.map_err(RpcError::from)?.into_inner();
+
+        Ok(res.into())
+    }
+
+    /// Sending an [TraceUserOperationRequest](TraceUserOperationRequest) to the UoPool gRPC
+    /// server to trace `handleOps` with the user operation.
+    ///
+    /// # Arguments
+    /// * `hash_or_uo: UserOperationHashOrRequest` - The hash of the user operation or the user
+    ///   operation itself.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<serde_json::Value>` - The trace (`trace`) and a `note` if the mempool runs in
+    ///   unsafe mode
+    async fn trace_user_operation(
+        &self,
+        hash_or_uo: UserOperationHashOrRequest,
+        ep: Address,
+    ) -> RpcResult<serde_json::Value> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let req = match hash_or_uo {
+            UserOperationHashOrRequest::Hash(hash) => TraceUserOperationRequest {
+                uo: None,
+                hash: Some(hash.0.into()),
+                ep: Some(ep.into()),
+            },
+            UserOperationHashOrRequest::UserOperation(uo) => TraceUserOperationRequest {
+                uo: Some(UserOperationSigned::from(uo).into()),
+                hash: None,
+                ep: Some(ep.into()),
+            },
+        };
+
+        let res = uopool_grpc_client
+            .trace_user_operation(Request::new(req))
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        let trace: serde_json::Value = serde_json::from_str(&res.trace).map_err(RpcError::from)?;
+        let mut out = serde_json::json!({ "trace": trace });
+        if !res.note.is_empty() {
+            out["note"] = res.note.into();
+        }
+
+        Ok(out)
+    }
+
+    /// Validates the [UserOperation](UserOperationRequest) via the
+    /// [ValidateUserOperationRequest](ValidateUserOperationRequest) without adding it to the
+    /// mempool
+    ///
+    /// # Arguments
+    /// * `user_operation: UserOperationRequest` - The [UserOperation](UserOperationRequest) to be
+    ///   validated.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<ValidationReport>` - The [ValidationReport](ValidationReport).
+    async fn validate_user_operation(
+        &self,
+        uo: UserOperationRequest,
+        ep: Address,
+    ) -> RpcResult<ValidationReport> {
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+
+        let res = uopool_grpc_client
+            .get_chain_id(Request::new(()))
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        let uo: UserOperationSigned = uo.into();
+        let uo = UserOperation::from_user_operation_signed(uo.hash(&ep, res.chain_id), uo);
+
+        let res = uopool_grpc_client
+            .validate_user_operation(Request::new(ValidateUserOperationRequest {
+                uo: Some(uo.into()),
+                ep: Some(ep.into()),
+            }))
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        Ok(ValidationReport::try_from(res)?)
+    }
+
     /// Set the reputations for the given array of [ReputationEntry](ReputationEntry)
     /// and send it to the UoPool gRPC service through the
-    /// [SetReputationRequest](SetReputationRequest).
+    /// [SetReputationRequest](SetReputationRequest). The addresses with the status set are
+    /// whitelisted (`ok`) or blacklisted (`banned`).
     ///
     /// # Arguments
-    /// * `reputation_entries: Vec<ReputationEntry>` - The [ReputationEntry](ReputationEntry) to be
+    /// * `reputation_entries: Vec<SetReputationEntry>` - The [entries](SetReputationEntry) to be
     ///   set.
     /// * `entry_point: Address` - The address of the entry point.
     ///
@@ -159,39 +440,46 @@ impl DebugApiServer for DebugApiServerImpl {
     /// * `RpcResult<ResponseSuccess>` - Ok
     async fn set_reputation(
         &self,
-        entries: Vec<ReputationEntry>,
+        entries: Vec<SetReputationEntry>,
         ep: Address,
     ) -> RpcResult<ResponseSuccess> {
         let mut uopool_grpc_client = self.uopool_grpc_client.clone();
 
+        let listed = |status: Status| {
+            entries
+                .iter()
+                .filter(|re| re.list_status.as_ref() == Some(&status))
+                .map(|re| re.entry.address.into())
+                .collect()
+        };
+
         let req = Request::new(SetReputationRequest {
-            rep: entries.iter().map(|re| re.clone().into()).collect(),
+            rep: entries.iter().map(|re| re.entry.clone().into()).collect(),
             ep: Some(ep.into()),
+            whitelist: listed(Status::OK),
+            blacklist: listed(Status::BANNED),
         });
 
         let res =
-            uopool_grpc_client.set_reputation(req).await.map_err(JsonRpcError::from)?.into_inner();
+            uopool_grpc_client.set_reputation(req).await.map_err(RpcError::from)?.into_inner();
 
         if res.res == SetReputationResult::Set as i32 {
             return Ok(ResponseSuccess::Ok);
         }
 
-        Err(ErrorObjectOwned::owned(
-            INTERNAL_ERROR_CODE,
-            "Error setting reputation".to_string(),
-            None::<bool>,
-        ))
+        Err(RpcError::InternalError("Error setting reputation".into()).into())
     }
 
     /// Return the all of [ReputationEntries](ReputationEntry) in the mempool via the
-    /// [GetAllReputationRequest](GetAllReputationRequest).
+    /// [GetAllReputationRequest](GetAllReputationRequest), with the reasons of their statuses.
     ///
     /// # Arguments
     /// * `entry_point: Address` - The address of the entry point.
     ///
     /// # Returns
-    /// * `RpcResult<Vec<ReputationEntry>>` - An array of [ReputationEntries](ReputationEntry)
-    async fn dump_reputation(&self, ep: Address) -> RpcResult<Vec<ReputationEntry>> {
+    /// * `RpcResult<Vec<ReputationEntryWithReason>>` - An array of
+    ///   [ReputationEntryWithReason](ReputationEntryWithReason)
+    async fn dump_reputation(&self, ep: Address) -> RpcResult<Vec<ReputationEntryWithReason>> {
         let mut uopool_grpc_client = self.uopool_grpc_client.clone();
 
         let request = Request::new(GetAllReputationRequest { ep: Some(ep.into()) });
@@ -199,7 +487,7 @@ impl DebugApiServer for DebugApiServerImpl {
         let res = uopool_grpc_client
             .get_all_reputation(request)
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         Ok(res.rep.iter().map(|re| re.clone().into()).collect())
@@ -222,7 +510,7 @@ impl DebugApiServer for DebugApiServerImpl {
 
         match bundler_grpc_client.set_bundle_mode(req).await {
             Ok(_) => Ok(ResponseSuccess::Ok),
-            Err(s) => Err(JsonRpcError::from(s).into()),
+            Err(s) => Err(RpcError::from(s).into()),
         }
     }
 
@@ -231,15 +519,38 @@ impl DebugApiServer for DebugApiServerImpl {
     /// not desirable.
     ///
     /// # Returns
-    /// * `RpcResult<H256>` - The hash of the bundle that was sent.
+    /// * `RpcResult<H256>` - The hash of the bundle that was sent, or an error with the `FailedOp`
+    ///   reason if a user operation of the bundle failed.
     async fn send_bundle_now(&self) -> RpcResult<H256> {
         let mut bundler_grpc_client = self.bundler_grpc_client.clone();
 
         let req = Request::new(());
 
         match bundler_grpc_client.send_bundle_now(req).await {
-            Ok(res) => Ok(res.into_inner().res.expect("Must return send bundle tx data").into()),
-            Err(s) => Err(JsonRpcError::from(s).into()),
+            Ok(res) => {
+                let res = res.into_inner();
+                if !res.error.is_empty() {
+                    return Err(ErrorObjectOwned::owned(EXECUTION, res.error, None::<bool>));
+                }
+                // zero hash if there were no user operations to bundle
+                Ok(res.transaction_hash.map(|tx_hash| tx_hash.into()).unwrap_or_default())
+            }
+            Err(s) => Err(RpcError::from(s).into()),
+        }
+    }
+
+    /// Returns the status of the relays the bundles are sent to.
+    ///
+    /// # Returns
+    /// * `RpcResult<Vec<RelayStatus>>` - The [status](RelayStatus) of every relay
+    async fn get_relay_status(&self) -> RpcResult<Vec<RelayStatus>> {
+        let mut bundler_grpc_client = self.bundler_grpc_client.clone();
+
+        let req = Request::new(());
+
+        match bundler_grpc_client.get_relay_status(req).await {
+            Ok(res) => Ok(res.into_inner().relays.into_iter().map(|relay| relay.into()).collect()),
+            Err(s) => Err(RpcError::from(s).into()),
         }
     }
 
@@ -265,7 +576,7 @@ impl DebugApiServer for DebugApiServerImpl {
                     is_staked: res.is_staked,
                 }
             }),
-            Err(s) => Err(JsonRpcError::from(s).into()),
+            Err(s) => Err(RpcError::from(s).into()),
         }
     }
 }
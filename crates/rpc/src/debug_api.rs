@@ -1,10 +1,11 @@
 pub use crate::debug::DebugApiServerImpl;
+use crate::silius_api::ValidationReport;
 use ethers::types::{Address, H256};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use silius_primitives::{
-    reputation::{ReputationEntry, StakeInfoResponse},
-    BundleMode, UserOperationRequest,
+    reputation::{ReputationEntry, ReputationEntryWithReason, StakeInfoResponse, Status},
+    BundleMode, MempoolStats, RelayStatus, UserOperationHash, UserOperationRequest,
 };
 
 #[derive(Clone, Copy, Serialize, Deserialize)]
@@ -13,6 +14,74 @@ pub enum ResponseSuccess {
     Ok,
 }
 
+/// Entry of `setReputation`: the [ReputationEntry](ReputationEntry) and optionally the `status`
+/// `ok` (the address is whitelisted) or `banned` (the address is blacklisted). A numeric status
+/// (as returned by `dumpReputation`) leaves the lists unchanged.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SetReputationEntry {
+    #[serde(flatten)]
+    pub entry: ReputationEntry,
+    #[serde(default, rename = "status", deserialize_with = "deserialize_list_status")]
+    pub list_status: Option<Status>,
+}
+
+fn deserialize_list_status<'de, D>(deserializer: D) -> Result<Option<Status>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StatusOrNumber {
+        Status(Status),
+        Number(serde_json::Value),
+    }
+
+    match StatusOrNumber::deserialize(deserializer)? {
+        StatusOrNumber::Status(Status::THROTTLED) => {
+            Err(serde::de::Error::custom("status can only be set to `ok` or `banned`"))
+        }
+        StatusOrNumber::Status(status) => Ok(Some(status)),
+        StatusOrNumber::Number(_) => Ok(None),
+    }
+}
+
+/// The user operations sponsored by a paymaster and the gas they commit it to
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymasterOperations {
+    pub user_operations: Vec<UserOperationRequest>,
+    pub total_committed_gas: u64,
+}
+
+/// Relationships of an address with the other entities of the user operations in the mempool
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityRelationships {
+    /// Factories and paymasters used by the user operations of the address as the sender
+    pub entities: Vec<Address>,
+    /// Senders of the user operations using the address as the factory or paymaster
+    pub senders: Vec<Address>,
+}
+
+/// A page of the user operations in the mempool
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MempoolPage {
+    pub ops: Vec<UserOperationRequest>,
+    pub total: u64,
+    pub page: u32,
+    pub total_pages: u32,
+}
+
+/// The user operation to trace: either the hash of a user operation known to the bundler (in the
+/// mempool or included on-chain) or the user operation itself
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UserOperationHashOrRequest {
+    Hash(UserOperationHash),
+    UserOperation(UserOperationRequest),
+}
+
 /// The ERC-4337 `debug` namespace RPC methods trait
 #[rpc(server, namespace = "debug_bundler")]
 pub trait DebugApi {
@@ -32,6 +101,14 @@ pub trait DebugApi {
     #[method(name = "clearReputation")]
     async fn clear_reputation(&self) -> RpcResult<ResponseSuccess>;
 
+    /// Clears the stored receipts of the included user operations
+    ///
+    ///
+    /// # Returns
+    /// * `RpcResult<ResponseSuccess>` - Ok
+    #[method(name = "clearReceipts")]
+    async fn clear_receipts(&self) -> RpcResult<ResponseSuccess>;
+
     /// Clears the bundler mempool and reputation
     ///
     ///
@@ -67,10 +144,128 @@ pub trait DebugApi {
     #[method(name = "dumpMempool")]
     async fn dump_mempool(&self, entry_point: Address) -> RpcResult<Vec<UserOperationRequest>>;
 
-    /// Set the reputations for the given array of [ReputationEntry](ReputationEntry)
+    /// Get a page of the [UserOperations](UserOperationRequest) of the mempool, ordered by the
+    /// user operation hash. The page boundaries are best-effort: user operations added or removed
+    /// between the requests shift the following pages.
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    /// * `page: u32` - The page number (starting at 0).
+    /// * `page_size: u32` - The maximum number of user operations in the page.
+    ///
+    /// # Returns
+    /// * `RpcResult<MempoolPage>` - The [UserOperations](UserOperationRequest) of the page and the
+    ///   total number of user operations and pages
+    #[method(name = "dumpMempoolPaginated")]
+    async fn dump_mempool_paginated(
+        &self,
+        entry_point: Address,
+        page: u32,
+        page_size: u32,
+    ) -> RpcResult<MempoolPage>;
+
+    /// Get the [UserOperations](UserOperationRequest) of the mempool that use the entity as the
+    /// factory or paymaster
+    ///
+    /// # Arguments
+    /// * `entity: Address` - The address of the entity.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<Vec<UserOperationRequest>>` - A vector of
+    ///   [UserOperations](UserOperationRequest) returned
+    #[method(name = "getOperationsByEntity")]
+    async fn get_operations_by_entity(
+        &self,
+        entity: Address,
+        entry_point: Address,
+    ) -> RpcResult<Vec<UserOperationRequest>>;
+
+    /// Get the [UserOperations](UserOperationRequest) of the mempool sponsored by the paymaster
+    ///
+    /// # Arguments
+    /// * `paymaster: Address` - The address of the paymaster.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<PaymasterOperations>` - The [UserOperations](UserOperationRequest) and the
+    ///   total gas they commit the paymaster to
+    #[method(name = "getOperationsByPaymaster")]
+    async fn get_operations_by_paymaster(
+        &self,
+        paymaster: Address,
+        entry_point: Address,
+    ) -> RpcResult<PaymasterOperations>;
+
+    /// Get the relationships of the address with the other entities of the
+    /// [UserOperations](UserOperationRequest) in the mempool
+    ///
+    /// # Arguments
+    /// * `address: Address` - The address of the sender, factory or paymaster.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<EntityRelationships>` - The factories and paymasters used by the address as the
+    ///   sender, and the senders using the address as the factory or paymaster
+    #[method(name = "getEntityRelationships")]
+    async fn get_entity_relationships(
+        &self,
+        address: Address,
+        entry_point: Address,
+    ) -> RpcResult<EntityRelationships>;
+
+    /// Get the statistics of the mempool
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<MempoolStats>` - The number of user operations, distinct senders and
+    ///   paymasters, and the age of the oldest user operation
+    #[method(name = "getMempoolStats")]
+    async fn get_mempool_stats(&self, entry_point: Address) -> RpcResult<MempoolStats>;
+
+    /// Trace `handleOps` with the [UserOperation](UserOperationRequest) via `debug_traceCall`
+    ///
+    /// # Arguments
+    /// * `hash_or_uo: UserOperationHashOrRequest` - The hash of the user operation or the user
+    ///   operation itself.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<serde_json::Value>` - The trace (`trace`) and a `note` if the mempool runs in
+    ///   unsafe mode
+    #[method(name = "traceUserOperation")]
+    async fn trace_user_operation(
+        &self,
+        hash_or_uo: UserOperationHashOrRequest,
+        entry_point: Address,
+    ) -> RpcResult<serde_json::Value>;
+
+    /// Runs the sanity and simulation checks on the [UserOperation](UserOperationRequest) with its
+    /// gas values as submitted (unlike `eth_estimateUserOperationGas`), without adding it to the
+    /// mempool
     ///
     /// # Arguments
-    /// * `reputation_entries: Vec<ReputationEntry>` - The [ReputationEntry](ReputationEntry) to be
+    /// * `user_operation: UserOperationRequest` - The [UserOperation](UserOperationRequest) to be
+    ///   validated.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<ValidationReport>` - The [ValidationReport](ValidationReport) with the gas used
+    ///   by the validation and the paymaster, and the `validAfter`/`validUntil` of the simulation
+    #[method(name = "validateUserOperation")]
+    async fn validate_user_operation(
+        &self,
+        user_operation: UserOperationRequest,
+        entry_point: Address,
+    ) -> RpcResult<ValidationReport>;
+
+    /// Set the reputations for the given array of [ReputationEntry](ReputationEntry), and
+    /// whitelist (`ok`) or blacklist (`banned`) the addresses with the status set
+    ///
+    /// # Arguments
+    /// * `reputation_entries: Vec<SetReputationEntry>` - The [entries](SetReputationEntry) to be
     ///   set.
     /// * `entry_point: Address` - The address of the entry point.
     ///
@@ -79,19 +274,24 @@ pub trait DebugApi {
     #[method(name = "setReputation")]
     async fn set_reputation(
         &self,
-        reputation_entries: Vec<ReputationEntry>,
+        reputation_entries: Vec<SetReputationEntry>,
         entry_point: Address,
     ) -> RpcResult<ResponseSuccess>;
 
-    /// Return the all of [ReputationEntries](ReputationEntry) in the mempool.
+    /// Return the all of [ReputationEntries](ReputationEntry) in the mempool, with the inclusion
+    /// rate, the threshold it's compared against and why the entity is throttled or banned.
     ///
     /// # Arguments
     /// * `entry_point: Address` - The address of the entry point.
     ///
     /// # Returns
-    /// * `RpcResult<Vec<ReputationEntry>>` - An array of [ReputationEntry](ReputationEntry)
+    /// * `RpcResult<Vec<ReputationEntryWithReason>>` - An array of
+    ///   [ReputationEntryWithReason](ReputationEntryWithReason)
     #[method(name = "dumpReputation")]
-    async fn dump_reputation(&self, entry_point: Address) -> RpcResult<Vec<ReputationEntry>>;
+    async fn dump_reputation(
+        &self,
+        entry_point: Address,
+    ) -> RpcResult<Vec<ReputationEntryWithReason>>;
 
     /// Set the bundling mode.
     ///
@@ -109,10 +309,20 @@ pub trait DebugApi {
     ///
     ///
     /// # Returns
-    /// * `RpcResult<H256>` - The hash of the bundle that was sent.
+    /// * `RpcResult<H256>` - The hash of the bundle that was sent, or an error with the `FailedOp`
+    ///   reason if a user operation of the bundle failed.
     #[method(name = "sendBundleNow")]
     async fn send_bundle_now(&self) -> RpcResult<H256>;
 
+    /// Returns the status of the relays the bundles are sent to (ok, degraded or down) and the
+    /// last error of each relay. Empty if the bundles aren't sent to relays.
+    ///
+    ///
+    /// # Returns
+    /// * `RpcResult<Vec<RelayStatus>>` - The [status](RelayStatus) of every relay
+    #[method(name = "getRelayStatus")]
+    async fn get_relay_status(&self) -> RpcResult<Vec<RelayStatus>>;
+
     /// Returns the stake info of the given address.
     ///
     /// # Arguments
@@ -1,11 +1,8 @@
 use crate::codes::{
-    BANNED_OR_THROTTLED_ENTITY, EXECUTION, OPCODE, SANITY, SIGNATURE, STAKE_TOO_LOW, TIMESTAMP,
-    VALIDATION,
-};
-use jsonrpsee::types::{
-    error::{ErrorCode, INTERNAL_ERROR_CODE},
-    ErrorObject, ErrorObjectOwned,
+    BANNED_OR_THROTTLED_ENTITY, EXECUTION, OPCODE, SANITY, SIGNATURE, SIGNATURE_AGGREGATOR,
+    STAKE_TOO_LOW, TIMESTAMP, VALIDATION,
 };
+use jsonrpsee::types::{error::INTERNAL_ERROR_CODE, ErrorObject, ErrorObjectOwned};
 use silius_mempool::{
     InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, ReputationError, SanityError,
     SimulationError,
@@ -14,6 +11,100 @@ use silius_mempool::{
 /// A wrapper for the [ErrorObjectOwned](ErrorObjectOwned) type.
 pub struct JsonRpcError(pub ErrorObjectOwned);
 
+/// Internal errors that can be returned from the RPC handlers.
+#[derive(Debug)]
+pub enum RpcError {
+    /// User operation failed the sanity checks
+    ValidationError(SanityError),
+    /// User operation failed the simulation checks
+    SimulationError(SimulationError),
+    /// Mempool failed to process the request
+    MempoolError(MempoolError),
+    /// Ethereum execution client returned an error
+    ProviderError(String),
+    /// Any other error
+    InternalError(String),
+}
+
+impl RpcError {
+    /// Convert a [RpcError](RpcError) to a [ErrorObjectOwned](ErrorObjectOwned) with the
+    /// ERC-4337 error code of the error.
+    pub fn into_json_rpc_error(self) -> ErrorObjectOwned {
+        match self {
+            RpcError::ValidationError(err) => JsonRpcError::from(err).0,
+            RpcError::SimulationError(err) => JsonRpcError::from(err).0,
+            RpcError::MempoolError(err) => JsonRpcError::from(err).0,
+            RpcError::ProviderError(inner) => ErrorObject::owned(
+                INTERNAL_ERROR_CODE,
+                format!("provider error: {inner}"),
+                None::<bool>,
+            ),
+            RpcError::InternalError(inner) => {
+                ErrorObject::owned(INTERNAL_ERROR_CODE, inner, None::<bool>)
+            }
+        }
+    }
+}
+
+impl From<RpcError> for ErrorObjectOwned {
+    /// Convert a [RpcError](RpcError) to a [ErrorObjectOwned](ErrorObjectOwned).
+    fn from(err: RpcError) -> Self {
+        err.into_json_rpc_error()
+    }
+}
+
+impl From<SanityError> for RpcError {
+    /// Convert a [SanityError](SanityError) to a [RpcError](RpcError).
+    fn from(err: SanityError) -> Self {
+        RpcError::ValidationError(err)
+    }
+}
+
+impl From<SimulationError> for RpcError {
+    /// Convert a [SimulationError](SimulationError) to a [RpcError](RpcError).
+    fn from(err: SimulationError) -> Self {
+        RpcError::SimulationError(err)
+    }
+}
+
+impl From<MempoolError> for RpcError {
+    /// Convert a [MempoolError](MempoolError) to a [RpcError](RpcError).
+    fn from(err: MempoolError) -> Self {
+        match err.kind {
+            MempoolErrorKind::InvalidUserOperation(err) => match err {
+                InvalidMempoolUserOperationError::Sanity(err) => RpcError::ValidationError(err),
+                InvalidMempoolUserOperationError::Simulation(err) => RpcError::SimulationError(err),
+                InvalidMempoolUserOperationError::Reputation(err) => {
+                    RpcError::ValidationError(SanityError::Reputation(err))
+                }
+            },
+            MempoolErrorKind::Provider { inner } => RpcError::ProviderError(inner),
+            _ => RpcError::MempoolError(err),
+        }
+    }
+}
+
+impl From<eyre::Error> for RpcError {
+    /// Convert an [eyre error](eyre::Error) to a [RpcError](RpcError).
+    fn from(err: eyre::Error) -> Self {
+        RpcError::InternalError(err.to_string())
+    }
+}
+
+impl From<tonic::Status> for RpcError {
+    /// Convert a tonic status to a [RpcError](RpcError).
+    fn from(s: tonic::Status) -> Self {
+        RpcError::InternalError(format!("gRPC error: {}", s.message()))
+    }
+}
+
+impl From<serde_json::Error> for RpcError {
+    /// Convert a [serde_json error](serde_json::Error) to a [RpcError](RpcError).
+    fn from(err: serde_json::Error) -> Self {
+        RpcError::InternalError(format!("JSON serializing error: {err}"))
+    }
+}
+
 impl From<JsonRpcError> for ErrorObjectOwned {
     /// Convert a [JsonRpcError](JsonRpcError) to a [ErrorObjectOwned](ErrorObjectOwned).
     fn from(err: JsonRpcError) -> Self {
@@ -37,6 +128,9 @@ impl From<MempoolError> for JsonRpcError {
                 InvalidMempoolUserOperationError::Simulation(err) => err.into(),
                 InvalidMempoolUserOperationError::Reputation(err) => err.into(),
             },
+            MempoolErrorKind::TooManyOpsForSender { .. } => {
+                ErrorObject::owned(STAKE_TOO_LOW, err.to_string(), None::<bool>).into()
+            }
             _ => ErrorObject::owned(INTERNAL_ERROR_CODE, err.to_string(), None::<bool>).into(),
         }
     }
@@ -73,10 +167,20 @@ impl From<SanityError> for JsonRpcError {
     /// Convert a [SanityError](SanityError) to a [JsonRpcError](JsonRpcError).
     fn from(err: SanityError) -> Self {
         JsonRpcError(match err {
+            SanityError::AlreadyKnown { hash: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
             SanityError::VerificationGasLimitTooHigh {
                 verification_gas_limit: _,
                 verification_gas_limit_expected: _,
             } => ErrorObject::owned(SANITY, err.to_string(), None::<bool>),
+            SanityError::VerificationGasLimitTooLow {
+                verification_gas_limit: _,
+                verification_gas_limit_expected: _,
+            } => ErrorObject::owned(SANITY, err.to_string(), None::<bool>),
+            SanityError::CombinedGasLimitTooHigh { combined: _, max: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
             SanityError::PreVerificationGasTooLow {
                 pre_verification_gas: _,
                 pre_verification_gas_expected: _,
@@ -84,6 +188,9 @@ impl From<SanityError> for JsonRpcError {
             SanityError::CallGasLimitTooLow { call_gas_limit: _, call_gas_limit_expected: _ } => {
                 ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
             }
+            SanityError::CallGasTooLow { provided: _, minimum: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
             SanityError::MaxFeePerGasTooLow { max_fee_per_gas: _, base_fee_per_gas: _ } => {
                 ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
             }
@@ -95,12 +202,45 @@ impl From<SanityError> for JsonRpcError {
                 max_priority_fee_per_gas: _,
                 max_priority_fee_per_gas_expected: _,
             } => ErrorObject::owned(SANITY, err.to_string(), None::<bool>),
+            SanityError::PaymasterDepositTooLow { paymaster: _, deposit: _, required: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
+            SanityError::SenderInsufficientDeposit { sender: _, deposit: _, required: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
+            SanityError::InitCodeTooShort { len: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
+            SanityError::FactoryHasNoCode { factory: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
+            SanityError::InsufficientPostOpGasReserve { provided: _, required: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
+            SanityError::CallDataGasTooHigh { calldata_gas: _, max_calldata_gas: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
+            SanityError::CallDataTooLarge { actual: _, max: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
+            SanityError::InitCodeTooLarge { actual: _, max: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
             SanityError::Paymaster { inner: _ } => {
                 ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
             }
             SanityError::Sender { inner: _ } => {
                 ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
             }
+            SanityError::NonceTooHigh { nonce: _, on_chain_nonce: _ } => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
+            SanityError::Signature { inner: _ } => {
+                ErrorObject::owned(SIGNATURE, err.to_string(), None::<bool>)
+            }
+            SanityError::Replacement(_) => {
+                ErrorObject::owned(SANITY, err.to_string(), None::<bool>)
+            }
             SanityError::EntityRoles { entity: _, address: _, entity_other: _ } => {
                 ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
             }
@@ -138,6 +278,32 @@ impl From<SimulationError> for JsonRpcError {
             SimulationError::CallStack { inner: _ } => {
                 ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
             }
+            SimulationError::TooManyExternalCalls { count: _, max: _, entity: _ } => {
+                ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
+            }
+            SimulationError::TimestampForbidden { entity: _, hint: _ } => {
+                ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
+            }
+            SimulationError::ForbiddenCallType { call_type: _, to: _, entity: _ } => {
+                ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
+            }
+            SimulationError::UntrustedDelegatecall { from: _, to: _, entity: _ } => {
+                ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
+            }
+            SimulationError::InsufficientGasForwardedToCall {
+                available: _,
+                forwarded: _,
+                entity: _,
+            } => ErrorObject::owned(OPCODE, err.to_string(), None::<bool>),
+            SimulationError::SenderTransfersDuringValidation { amount: _ } => {
+                ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
+            }
+            SimulationError::CallToBannedEntity { caller_entity: _, banned_address: _ } => {
+                ErrorObject::owned(BANNED_OR_THROTTLED_ENTITY, err.to_string(), None::<bool>)
+            }
+            SimulationError::AggregatorSignature { aggregator: _, inner: _ } => {
+                ErrorObject::owned(SIGNATURE_AGGREGATOR, err.to_string(), None::<bool>)
+            }
             SimulationError::CodeHashes {} => {
                 ErrorObject::owned(OPCODE, err.to_string(), None::<bool>)
             }
@@ -150,24 +316,87 @@ impl From<SimulationError> for JsonRpcError {
     }
 }
 
-impl From<tonic::Status> for JsonRpcError {
-    /// Convert a tonic status to a [JsonRpcError](JsonRpcError).
-    fn from(s: tonic::Status) -> Self {
-        JsonRpcError(ErrorObject::owned(
-            ErrorCode::InternalError.code(),
-            format!("gRPC error: {}", s.message()),
-            None::<bool>,
-        ))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::{Address, U256};
+
+    fn code(err: RpcError) -> i32 {
+        ErrorObjectOwned::from(err).code()
     }
-}
 
-impl From<serde_json::Error> for JsonRpcError {
-    /// Convert a [serde_json error](serde_json::Error) to a [JsonRpcError](JsonRpcError).
-    fn from(err: serde_json::Error) -> Self {
-        JsonRpcError(ErrorObject::owned(
-            ErrorCode::ParseError.code(),
-            format!("JSON serializing error: {err}"),
-            None::<bool>,
-        ))
+    #[test]
+    fn rpc_error_codes() {
+        assert_eq!(
+            code(
+                SanityError::CallGasTooLow { provided: U256::zero(), minimum: U256::from(21100) }
+                    .into()
+            ),
+            SANITY
+        );
+        assert_eq!(
+            code(
+                SanityError::MaxFeePerGasTooLow {
+                    max_fee_per_gas: U256::zero(),
+                    base_fee_per_gas: U256::one(),
+                }
+                .into()
+            ),
+            SANITY
+        );
+        assert_eq!(code(SimulationError::Signature.into()), SIGNATURE);
+        assert_eq!(
+            code(
+                SimulationError::ForbiddenCallType {
+                    call_type: "CALLCODE".into(),
+                    to: Address::zero(),
+                    entity: "account".into(),
+                }
+                .into()
+            ),
+            OPCODE
+        );
+        assert_eq!(
+            code(
+                SimulationError::AggregatorSignature {
+                    aggregator: Address::zero(),
+                    inner: "invalid signature".into()
+                }
+                .into()
+            ),
+            SIGNATURE_AGGREGATOR
+        );
+        let mempool_error = |kind| MempoolError { hash: Default::default(), kind };
+        assert_eq!(code(mempool_error(MempoolErrorKind::MempoolFull).into()), INTERNAL_ERROR_CODE);
+        assert_eq!(
+            code(
+                mempool_error(MempoolErrorKind::TooManyOpsForSender {
+                    sender: Address::zero(),
+                    count: 4,
+                    max: 4,
+                })
+                .into()
+            ),
+            STAKE_TOO_LOW
+        );
+        assert_eq!(
+            code(
+                mempool_error(MempoolErrorKind::InvalidUserOperation(
+                    InvalidMempoolUserOperationError::Reputation(ReputationError::BannedEntity {
+                        entity: "paymaster".into(),
+                        address: Address::zero(),
+                    })
+                ))
+                .into()
+            ),
+            BANNED_OR_THROTTLED_ENTITY
+        );
+        assert_eq!(code(RpcError::ProviderError("provider".into())), INTERNAL_ERROR_CODE);
+        assert_eq!(code(eyre::eyre!("internal").into()), INTERNAL_ERROR_CODE);
+        assert_eq!(code(tonic::Status::internal("grpc").into()), INTERNAL_ERROR_CODE);
+        assert_eq!(
+            code(serde_json::from_str::<MempoolError>("").unwrap_err().into()),
+            INTERNAL_ERROR_CODE
+        );
     }
 }
@@ -1,18 +1,26 @@
-use crate::{codes::USER_OPERATION_HASH, error::JsonRpcError, eth_api::EthApiServer};
+use crate::{
+    codes::USER_OPERATION_HASH,
+    error::RpcError,
+    eth_api::{EthApiServer, PENDING_USER_OPERATIONS},
+};
 use async_trait::async_trait;
 use ethers::{
-    types::{Address, U64},
+    types::{Address, H256, U64},
     utils::to_checksum,
 };
-use jsonrpsee::{core::RpcResult, types::ErrorObjectOwned};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    types::{error::ErrorCode, ErrorObjectOwned},
+    PendingSubscriptionSink, SubscriptionMessage,
+};
 use silius_grpc::{
     uo_pool_client::UoPoolClient, AddRequest, AddResult, EstimateUserOperationGasRequest,
     EstimateUserOperationGasResult, UserOperationHashRequest,
 };
 use silius_mempool::MempoolError;
 use silius_primitives::{
-    UserOperation, UserOperationByHash, UserOperationGasEstimation, UserOperationHash,
-    UserOperationReceipt, UserOperationRequest, UserOperationSigned,
+    UserOperation, UserOperationByHash, UserOperationEvent, UserOperationGasEstimation,
+    UserOperationHash, UserOperationReceipt, UserOperationRequest, UserOperationSigned,
 };
 use std::str::FromStr;
 use tonic::Request;
@@ -36,7 +44,7 @@ impl EthApiServer for EthApiServerImpl {
         let res = uopool_grpc_client
             .get_chain_id(Request::new(()))
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         return Ok(res.chain_id.into());
@@ -52,7 +60,7 @@ impl EthApiServer for EthApiServerImpl {
         let res = uopool_grpc_client
             .get_supported_entry_points(Request::new(()))
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         return Ok(res.eps.into_iter().map(|ep| to_checksum(&ep.into(), None)).collect());
@@ -76,7 +84,7 @@ impl EthApiServer for EthApiServerImpl {
         let res = uopool_grpc_client
             .get_chain_id(Request::new(()))
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         let uo: UserOperationSigned = uo.into();
@@ -87,20 +95,21 @@ impl EthApiServer for EthApiServerImpl {
                     .into(),
             ),
             ep: Some(ep.into()),
+            uos: vec![],
         });
 
-        let res = uopool_grpc_client.add(req).await.map_err(JsonRpcError::from)?.into_inner();
+        let res = uopool_grpc_client.add(req).await.map_err(RpcError::from)?.into_inner();
 
         if res.res == AddResult::Added as i32 {
             let uo_hash =
-                serde_json::from_str::<UserOperationHash>(&res.data).map_err(JsonRpcError::from)?;
+                serde_json::from_str::<UserOperationHash>(&res.data).map_err(RpcError::from)?;
             return Ok(uo_hash);
         }
 
-        Err(JsonRpcError::from(
-            serde_json::from_str::<MempoolError>(&res.data).map_err(JsonRpcError::from)?,
+        Err(RpcError::from(
+            serde_json::from_str::<MempoolError>(&res.data).map_err(RpcError::from)?,
         )
-        .0)
+        .into())
     }
 
     /// Estimate the gas required for a [UserOperation](UserOperationRequest) via the
@@ -125,7 +134,7 @@ impl EthApiServer for EthApiServerImpl {
         let res = uopool_grpc_client
             .get_chain_id(Request::new(()))
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         let uo: UserOperationSigned = uo.into();
@@ -145,19 +154,19 @@ impl EthApiServer for EthApiServerImpl {
         let res = uopool_grpc_client
             .estimate_user_operation_gas(req)
             .await
-            .map_err(JsonRpcError::from)?
+            .map_err(RpcError::from)?
             .into_inner();
 
         if res.res == EstimateUserOperationGasResult::Estimated as i32 {
             let gas_est = serde_json::from_str::<UserOperationGasEstimation>(&res.data)
-                .map_err(JsonRpcError::from)?;
+                .map_err(RpcError::from)?;
             return Ok(gas_est);
         }
 
-        Err(JsonRpcError::from(
-            serde_json::from_str::<MempoolError>(&res.data).map_err(JsonRpcError::from)?,
+        Err(RpcError::from(
+            serde_json::from_str::<MempoolError>(&res.data).map_err(RpcError::from)?,
         )
-        .0)
+        .into())
     }
 
     /// Retrieve the receipt of a [UserOperation](UserOperation).
@@ -189,7 +198,7 @@ impl EthApiServer for EthApiServerImpl {
                                 actual_gas_cost: res.actual_gas_cost?.into(),
                                 actual_gas_used: res.actual_gas_used?.into(),
                                 success: res.success,
-                                reason: String::new(),
+                                reason: res.reason,
                                 logs: res.logs.into_iter().map(|l| l.into()).collect(),
                                 tx_receipt: res.tx_receipt?.into(),
                             })
@@ -238,13 +247,14 @@ impl EthApiServer for EthApiServerImpl {
 
                         let uo: Option<UserOperationByHash> = res.user_operation.and_then(|uo| {
                             let entry_point = res.entry_point?.into();
-                            let block_hash = res.block_hash?.into();
-                            let transaction_hash = res.transaction_hash?.into();
+                            // pending in the mempool if not included in a transaction yet
+                            let transaction_hash: Option<H256> =
+                                res.transaction_hash.map(Into::into);
                             Some(UserOperationByHash {
                                 user_operation: uo.into(),
                                 entry_point,
-                                block_number: res.block_number.into(),
-                                block_hash,
+                                block_number: transaction_hash.map(|_| res.block_number.into()),
+                                block_hash: res.block_hash.map(Into::into),
                                 transaction_hash,
                             })
                         });
@@ -267,4 +277,63 @@ impl EthApiServer for EthApiServerImpl {
             )),
         }
     }
+
+    /// Subscribe to the changes of the mempool. The events are streamed from the uopool
+    /// service and forwarded to the WebSocket connection until it is closed.
+    ///
+    /// # Arguments
+    /// * `pending: PendingSubscriptionSink` - The pending subscription.
+    /// * `kind: String` - The kind of the subscription.
+    ///
+    /// # Returns
+    /// * `SubscriptionResult` - Ok once the subscription ends.
+    async fn subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        kind: String,
+    ) -> SubscriptionResult {
+        if kind != PENDING_USER_OPERATIONS {
+            pending
+                .reject(ErrorObjectOwned::owned(
+                    ErrorCode::InvalidParams.code(),
+                    format!("Unsupported subscription {kind}"),
+                    None::<bool>,
+                ))
+                .await;
+            return Ok(());
+        }
+
+        let mut events = match self
+            .uopool_grpc_client
+            .clone()
+            .subscribe_user_operation_events(Request::new(()))
+            .await
+        {
+            Ok(res) => res.into_inner(),
+            Err(s) => {
+                pending.reject(RpcError::from(s)).await;
+                return Ok(());
+            }
+        };
+
+        let sink = pending.accept().await?;
+        loop {
+            tokio::select! {
+                _ = sink.closed() => break,
+                res = events.message() => match res? {
+                    Some(res) => {
+                        let status = res.status().into();
+                        let Some(hash) = res.hash else {
+                            continue;
+                        };
+                        let event = UserOperationEvent { hash: hash.into(), status };
+                        sink.send(SubscriptionMessage::from_json(&event)?).await?;
+                    }
+                    None => break,
+                },
+            }
+        }
+
+        Ok(())
+    }
 }
@@ -1,11 +1,17 @@
 pub use crate::eth::EthApiServerImpl;
 use ethers::types::{Address, U64};
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use jsonrpsee::{
+    core::{RpcResult, SubscriptionResult},
+    proc_macros::rpc,
+};
 use silius_primitives::{
-    UserOperationByHash, UserOperationGasEstimation, UserOperationHash, UserOperationReceipt,
-    UserOperationRequest,
+    UserOperationByHash, UserOperationEvent, UserOperationGasEstimation, UserOperationHash,
+    UserOperationReceipt, UserOperationRequest,
 };
 
+/// Kind of the `eth_subscribe` subscription to the changes of the mempool
+pub const PENDING_USER_OPERATIONS: &str = "pendingUserOperations";
+
 /// The ERC-4337 `eth` namespace RPC methods trait
 #[rpc(server, namespace = "eth")]
 pub trait EthApi {
@@ -86,4 +92,19 @@ pub trait EthApi {
         &self,
         user_operation_hash: String,
     ) -> RpcResult<Option<UserOperationByHash>>;
+
+    /// Subscribe to the user operations added to or removed from the mempool (WebSocket only).
+    ///
+    /// # Arguments
+    /// * `kind: String` - The kind of the subscription, only
+    ///   [pendingUserOperations](PENDING_USER_OPERATIONS) is supported.
+    ///
+    /// # Returns
+    /// * `SubscriptionResult` - Pushes a [UserOperationEvent](UserOperationEvent) on every change.
+    #[subscription(
+        name = "subscribe" => "subscription",
+        unsubscribe = "unsubscribe",
+        item = UserOperationEvent
+    )]
+    async fn subscribe(&self, kind: String) -> SubscriptionResult;
 }
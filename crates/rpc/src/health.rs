@@ -0,0 +1,115 @@
+use ethers::providers::Middleware;
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, Server, StatusCode,
+};
+use silius_grpc::uo_pool_client::UoPoolClient;
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+use tonic::transport::{Channel, Endpoint};
+use tracing::{debug, info};
+
+/// Path of the liveness probe
+pub const LIVENESS_PATH: &str = "/health/live";
+/// Path of the readiness probe
+pub const READINESS_PATH: &str = "/health/ready";
+/// Timeout of each of the readiness checks
+pub const READINESS_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Plain HTTP server with the liveness and readiness probes (e.g. for Docker or Kubernetes),
+/// separate from the JSON-RPC server.
+pub struct HealthServer<M: Middleware + 'static> {
+    /// Address to listen on
+    listen_addr: SocketAddr,
+    /// Ethereum execution client (not checked if None, e.g. for the RPC server alone)
+    eth_client: Option<Arc<M>>,
+    /// Client of the uopool gRPC service (connects lazily, so the server starts before it's up)
+    uopool_grpc_client: UoPoolClient<Channel>,
+}
+
+impl<M: Middleware + 'static> HealthServer<M> {
+    /// Create a new HealthServer
+    ///
+    /// # Arguments
+    /// * `listen_addr` - Address to listen on
+    /// * `uopool_grpc_listen_address` - Address of the uopool gRPC service
+    /// * `eth_client` - Ethereum execution client (not checked if None)
+    pub fn new(
+        listen_addr: SocketAddr,
+        uopool_grpc_listen_address: String,
+        eth_client: Option<Arc<M>>,
+    ) -> eyre::Result<Self> {
+        let channel = Endpoint::from_shared(uopool_grpc_listen_address)?
+            .connect_timeout(READINESS_CHECK_TIMEOUT)
+            .connect_lazy();
+        Ok(Self { listen_addr, eth_client, uopool_grpc_client: UoPoolClient::new(channel) })
+    }
+
+    /// Returns whether the execution client responds and the mempool of the uopool gRPC service
+    /// is initialized (it has entry points)
+    pub async fn is_ready(&self) -> bool {
+        if let Some(eth_client) = self.eth_client.as_ref() {
+            match tokio::time::timeout(READINESS_CHECK_TIMEOUT, eth_client.get_chainid()).await {
+                Ok(Ok(_)) => {}
+                res => {
+                    debug!("Readiness check of the execution client failed: {res:?}");
+                    return false;
+                }
+            }
+        }
+
+        let mut uopool_grpc_client = self.uopool_grpc_client.clone();
+        match tokio::time::timeout(
+            READINESS_CHECK_TIMEOUT,
+            uopool_grpc_client.get_supported_entry_points(tonic::Request::new(())),
+        )
+        .await
+        {
+            Ok(Ok(res)) => !res.into_inner().eps.is_empty(),
+            res => {
+                debug!("Readiness check of the uopool gRPC service failed: {res:?}");
+                false
+            }
+        }
+    }
+
+    async fn handle(&self, req: Request<Body>) -> Response<Body> {
+        let status = match (req.method(), req.uri().path()) {
+            (&Method::GET, LIVENESS_PATH) => StatusCode::OK,
+            (&Method::GET, READINESS_PATH) if self.is_ready().await => StatusCode::OK,
+            (&Method::GET, READINESS_PATH) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::NOT_FOUND,
+        };
+
+        let mut res = Response::new(Body::from(status.canonical_reason().unwrap_or_default()));
+        *res.status_mut() = status;
+        res
+    }
+
+    /// Starts the server in the background
+    ///
+    /// # Returns
+    /// * `SocketAddr` - The address the server is listening on
+    pub fn start(self) -> eyre::Result<SocketAddr> {
+        let server = Arc::new(self);
+        let make_service = make_service_fn({
+            let server = server.clone();
+            move |_| {
+                let server = server.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| {
+                        let server = server.clone();
+                        async move { Ok::<_, Infallible>(server.handle(req).await) }
+                    }))
+                }
+            }
+        });
+
+        let http_server = Server::try_bind(&server.listen_addr)?.serve(make_service);
+        let listen_addr = http_server.local_addr();
+        info!("Started health check server on {listen_addr:?}");
+
+        tokio::spawn(http_server);
+
+        Ok(listen_addr)
+    }
+}
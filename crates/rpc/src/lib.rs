@@ -7,9 +7,15 @@ pub mod debug_api;
 mod error;
 mod eth;
 pub mod eth_api;
+mod health;
 pub mod middleware;
 mod rpc;
+mod silius;
+pub mod silius_api;
+mod uopool;
 mod web3;
 pub mod web3_api;
 
+pub use health::{HealthServer, LIVENESS_PATH, READINESS_PATH};
 pub use rpc::{JsonRpcServer, JsonRpcServerType};
+pub use uopool::UoPoolServeHttp;
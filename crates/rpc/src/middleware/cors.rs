@@ -0,0 +1,228 @@
+use hyper::{
+    header::ORIGIN,
+    http::{HeaderValue, StatusCode},
+    Body, Method, Request, Response,
+};
+use std::{
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use thiserror::Error;
+use tower::{Layer, Service};
+use tower_http::cors::{self, AllowOrigin, Any, Cors};
+
+/// The origins the JSON-RPC server accepts requests from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CorsConfig {
+    /// Requests from any origin are allowed (the origin is reflected back).
+    AllowAll,
+    /// Only requests from the listed origins (`scheme://host[:port]`) are allowed. The host may
+    /// start with a wildcard label (`https://*.example.com`) to allow all of its subdomains.
+    AllowList(Vec<HeaderValue>),
+    /// Requests with the `Origin` header (sent by browsers) are rejected.
+    AllowNone,
+}
+
+impl CorsConfig {
+    /// Parses the CORS domains from the command line.
+    ///
+    /// # Arguments
+    /// * `domains: &[String]` - The allowed origins, `*` allows all of them
+    ///
+    /// # Returns
+    /// * `Result<Self, CorsConfigError>` - [AllowNone](CorsConfig::AllowNone) if there are no
+    ///   domains, otherwise an error if one of them isn't a valid header value
+    pub fn from_domains(domains: &[String]) -> Result<Self, CorsConfigError> {
+        if domains.iter().any(|d| d.trim() == "*") {
+            return Ok(Self::AllowAll);
+        }
+
+        let origins = domains
+            .iter()
+            .map(|d| d.trim())
+            .filter(|d| !d.is_empty())
+            .map(|d| {
+                d.parse::<HeaderValue>().map_err(|e| CorsConfigError::InvalidOrigin {
+                    origin: d.into(),
+                    reason: e.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(if origins.is_empty() { Self::AllowNone } else { Self::AllowList(origins) })
+    }
+}
+
+/// CORS configuration error
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CorsConfigError {
+    #[error("invalid CORS origin {origin:?}: {reason}")]
+    InvalidOrigin { origin: String, reason: String },
+}
+
+/// An entry of the [allow list](CorsConfig::AllowList)
+#[derive(Clone, Debug)]
+enum OriginPattern {
+    /// The origin has to be the same (ignoring case)
+    Exact(String),
+    /// `scheme://*.domain[:port]`, any subdomain of the domain matches
+    Wildcard { prefix: String, suffix: String },
+}
+
+impl OriginPattern {
+    fn parse(origin: &HeaderValue) -> Result<Self, CorsConfigError> {
+        let err = |reason: &str| CorsConfigError::InvalidOrigin {
+            origin: String::from_utf8_lossy(origin.as_bytes()).into(),
+            reason: reason.into(),
+        };
+
+        let origin = origin.to_str().map_err(|_| err("not a visible ASCII string"))?;
+        let (scheme, authority) = origin.split_once("://").ok_or_else(|| err("missing scheme"))?;
+
+        if scheme.is_empty() ||
+            !scheme.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        {
+            return Err(err("invalid scheme"));
+        }
+        if authority.is_empty() ||
+            !authority.chars().all(|c| c.is_ascii_alphanumeric() || "-.:[]*".contains(c))
+        {
+            return Err(err("expected scheme://host[:port]"));
+        }
+
+        let origin = origin.to_ascii_lowercase();
+        match authority.strip_prefix("*.") {
+            Some(domain) if !domain.is_empty() && !domain.contains('*') => Ok(Self::Wildcard {
+                prefix: format!("{}://", scheme.to_ascii_lowercase()),
+                suffix: format!(".{}", domain.to_ascii_lowercase()),
+            }),
+            None if !authority.contains('*') => Ok(Self::Exact(origin)),
+            _ => Err(err("wildcard is only allowed as the first label of the host")),
+        }
+    }
+
+    fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Exact(allowed) => *allowed == origin,
+            Self::Wildcard { prefix, suffix } => origin
+                .strip_prefix(prefix.as_str())
+                .and_then(|o| o.strip_suffix(suffix.as_str()))
+                .is_some_and(|subdomain| {
+                    !subdomain.is_empty() &&
+                        subdomain.chars().all(|c| c.is_ascii_alphanumeric() || "-.".contains(c))
+                }),
+        }
+    }
+}
+
+/// Checks the origin against the patterns (`None` allows every origin)
+fn is_allowed(patterns: Option<&[OriginPattern]>, origin: &HeaderValue) -> bool {
+    match patterns {
+        Some(patterns) => origin
+            .to_str()
+            .map(|origin| {
+                let origin = origin.to_ascii_lowercase();
+                patterns.iter().any(|pattern| pattern.matches(&origin))
+            })
+            .unwrap_or(false),
+        None => true,
+    }
+}
+
+/// The layer that enforces the [CORS configuration](CorsConfig).
+///
+/// Requests from origins that aren't allowed are answered with `403 Forbidden`, while the
+/// responses to the allowed ones get the CORS headers.
+#[derive(Clone, Debug)]
+pub struct CorsLayer {
+    /// Allowed origins (None if all of them are allowed)
+    patterns: Option<Arc<[OriginPattern]>>,
+    /// The layer that adds the CORS headers and answers the preflight requests
+    cors: cors::CorsLayer,
+}
+
+impl CorsLayer {
+    /// Create a new CORS layer
+    ///
+    /// # Arguments
+    /// * `config: CorsConfig` - The [CORS configuration](CorsConfig)
+    ///
+    /// # Returns
+    /// * `Result<Self, CorsConfigError>` - A CorsLayer instance, or an error if one of the
+    ///   allowed origins is malformed
+    pub fn new(config: CorsConfig) -> Result<Self, CorsConfigError> {
+        let cors = cors::CorsLayer::new().allow_headers(Any).allow_methods([Method::POST]);
+
+        Ok(match config {
+            CorsConfig::AllowAll => {
+                Self { patterns: None, cors: cors.allow_origin(AllowOrigin::mirror_request()) }
+            }
+            CorsConfig::AllowList(origins) => {
+                let patterns: Arc<[OriginPattern]> =
+                    origins.iter().map(OriginPattern::parse).collect::<Result<_, _>>()?;
+                let allowed = patterns.clone();
+
+                Self {
+                    patterns: Some(patterns),
+                    cors: cors.allow_origin(AllowOrigin::predicate(move |origin, _| {
+                        is_allowed(Some(allowed.as_ref()), origin)
+                    })),
+                }
+            }
+            CorsConfig::AllowNone => Self { patterns: Some(Arc::from([])), cors },
+        })
+    }
+}
+
+impl<S> Layer<S> for CorsLayer {
+    type Service = CorsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CorsService { inner: self.cors.layer(inner), patterns: self.patterns.clone() }
+    }
+}
+
+/// The service that enforces the [CORS configuration](CorsConfig).
+#[derive(Debug, Clone)]
+pub struct CorsService<S> {
+    /// The inner service (wrapped by the service that adds the CORS headers)
+    inner: Cors<S>,
+    /// Allowed origins (None if all of them are allowed)
+    patterns: Option<Arc<[OriginPattern]>>,
+}
+
+fn forbidden() -> Result<Response<Body>, hyper::http::Error> {
+    Response::builder().status(StatusCode::FORBIDDEN).body(Body::from("Origin not allowed"))
+}
+
+impl<S> Service<Request<Body>> for CorsService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Send + 'static,
+    S::Error: Into<Box<dyn Error + Send + Sync>> + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Box<dyn Error + Send + Sync + 'static>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // requests without the origin don't come from browsers
+        if let Some(origin) = req.headers().get(ORIGIN) {
+            if !is_allowed(self.patterns.as_deref(), origin) {
+                return Box::pin(async move { Ok(forbidden()?) });
+            }
+        }
+
+        let fut = self.inner.call(req);
+        Box::pin(async move { fut.await.map_err(Into::into) })
+    }
+}
@@ -0,0 +1,179 @@
+use dashmap::{mapref::entry::Entry, DashMap};
+use ethers::{types::H256, utils::keccak256};
+use hyper::{Body, Request, Response};
+use serde_json::Value;
+use std::{
+    error::Error,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::watch;
+use tower::{Layer, Service};
+
+/// Methods deduplicated by default
+const DEDUP_METHODS: &[&str] = &["eth_sendUserOperation"];
+
+type InFlight = DashMap<H256, Arc<watch::Receiver<Option<Value>>>>;
+
+/// The layer that merges identical in-flight JSON-RPC requests.
+///
+/// While a request is being processed, every request with the same method and params waits for
+/// its response instead of being passed to the inner service (and the uopool gRPC service).
+#[derive(Clone, Debug)]
+pub struct DedupLayer {
+    /// JSON-RPC methods to deduplicate
+    methods: Arc<Vec<String>>,
+    /// Shared by all services since the server creates one per connection
+    in_flight: Arc<InFlight>,
+}
+
+impl Default for DedupLayer {
+    fn default() -> Self {
+        Self::new(DEDUP_METHODS.iter().map(|m| m.to_string()).collect())
+    }
+}
+
+impl DedupLayer {
+    /// Create a new dedup layer
+    ///
+    /// # Arguments
+    /// * `methods: Vec<String>` - JSON-RPC methods to deduplicate
+    ///
+    /// # Returns
+    /// * `Self` - A DedupLayer instance
+    pub fn new(methods: Vec<String>) -> Self {
+        Self { methods: Arc::new(methods), in_flight: Arc::new(DashMap::new()) }
+    }
+}
+
+impl<S> Layer<S> for DedupLayer {
+    type Service = DedupService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        DedupService { inner, methods: self.methods.clone(), in_flight: self.in_flight.clone() }
+    }
+}
+
+/// The service that deduplicates in-flight JSON-RPC requests.
+#[derive(Debug, Clone)]
+pub struct DedupService<S> {
+    /// The inner service
+    inner: S,
+    /// JSON-RPC methods to deduplicate
+    methods: Arc<Vec<String>>,
+    /// Responses of the requests being processed, keyed by the hash of method and params
+    in_flight: Arc<InFlight>,
+}
+
+/// Removes the request from the in-flight requests once it's done (or dropped).
+struct InFlightGuard {
+    key: H256,
+    in_flight: Arc<InFlight>,
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.in_flight.remove(&self.key);
+    }
+}
+
+/// Returns the dedup key and the id of a single JSON-RPC request for one of the `methods`.
+fn dedup_key(methods: &[String], body: &[u8]) -> Option<(H256, Value)> {
+    let req: Value = serde_json::from_slice(body).ok()?;
+    let method = req.get("method")?.as_str()?;
+
+    if !methods.iter().any(|m| m == method) {
+        return None;
+    }
+
+    let params = req.get("params").cloned().unwrap_or(Value::Null);
+    let key = serde_json::to_vec(&(method, params)).ok()?;
+
+    Some((H256::from(keccak256(key)), req.get("id").cloned().unwrap_or(Value::Null)))
+}
+
+fn json_response(mut res: Value, id: Value) -> Result<Response<Body>, hyper::http::Error> {
+    res["id"] = id;
+
+    Response::builder()
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(res.to_string()))
+}
+
+impl<S> Service<Request<Body>> for DedupService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: Into<Box<dyn Error + Send + Sync>> + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Box<dyn Error + Send + Sync + 'static>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        // take the service that was ready
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let methods = self.methods.clone();
+        let in_flight = self.in_flight.clone();
+
+        Box::pin(async move {
+            let (req_h, req_b) = req.into_parts();
+            let req_bb = hyper::body::to_bytes(req_b).await?;
+
+            let Some((key, id)) = dedup_key(&methods, &req_bb) else {
+                return inner
+                    .call(Request::from_parts(req_h, Body::from(req_bb)))
+                    .await
+                    .map_err(Into::into);
+            };
+
+            let sender = match in_flight.entry(key) {
+                Entry::Occupied(entry) => Err(entry.get().as_ref().clone()),
+                Entry::Vacant(entry) => {
+                    let (sender, receiver) = watch::channel(None);
+                    entry.insert(Arc::new(receiver));
+                    Ok(sender)
+                }
+            };
+
+            let guard = match sender {
+                Ok(sender) => Some((sender, InFlightGuard { key, in_flight })),
+                Err(mut receiver) => {
+                    // if the first request fails without a response, process this one on its own
+                    if let Ok(res) = receiver.wait_for(Option::is_some).await {
+                        if let Some(res) = (*res).clone() {
+                            return Ok(json_response(res, id)?);
+                        }
+                    }
+                    None
+                }
+            };
+
+            let res = inner
+                .call(Request::from_parts(req_h, Body::from(req_bb)))
+                .await
+                .map_err(Into::into)?;
+
+            let Some((sender, _guard)) = guard else {
+                return Ok(res);
+            };
+
+            let (res_h, res_b) = res.into_parts();
+            let res_bb = hyper::body::to_bytes(res_b).await?;
+            if let Ok(value) = serde_json::from_slice::<Value>(&res_bb) {
+                sender.send_replace(Some(value));
+            }
+
+            Ok(Response::from_parts(res_h, Body::from(res_bb)))
+        })
+    }
+}
@@ -1,3 +1,11 @@
+mod cors;
+mod dedup;
+mod rate_limit;
+
+pub use cors::{CorsConfig, CorsConfigError, CorsLayer, CorsService};
+pub use dedup::{DedupLayer, DedupService};
+pub use rate_limit::{RateLimitLayer, RateLimitService, RATE_LIMIT_WINDOW};
+
 use hyper::{Body, Request, Response};
 use hyper_tls::HttpsConnector;
 use jsonrpsee::{
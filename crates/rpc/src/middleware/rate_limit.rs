@@ -0,0 +1,248 @@
+use crate::codes::TOO_MANY_REQUESTS;
+use dashmap::DashMap;
+use hyper::{Body, Request, Response};
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    future::Future,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+use tower::{Layer, Service};
+
+/// Length of the window the requests are counted in
+pub const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Requests of one client (and method) counted in the current and the previous window
+#[derive(Clone, Copy, Debug)]
+struct Counter {
+    window_start: Instant,
+    count: u64,
+    previous: u64,
+}
+
+/// Counters keyed by the client IP and the method (None for all methods of the client)
+type Counters = DashMap<(IpAddr, Option<String>), Counter>;
+
+/// The layer that limits the number of JSON-RPC requests per client IP.
+///
+/// Every client may send `max_requests` requests per window, and the methods in
+/// `method_limits` have a (usually tighter) limit of their own. The window slides: the requests
+/// of the previous window are weighted by how much of it still overlaps the last `window`.
+/// Requests over the limit are answered with the [TOO_MANY_REQUESTS](TOO_MANY_REQUESTS) error
+/// without reaching the inner service.
+///
+/// The client is the peer address of the connection (the [SocketAddr](SocketAddr) in the request
+/// extensions). The `X-Forwarded-For` and `X-Real-IP` headers are only used if the peer is one of
+/// the `trusted_proxies`.
+#[derive(Clone, Debug)]
+pub struct RateLimitLayer {
+    /// Max requests per client in one window
+    max_requests: u64,
+    /// Max requests per client in one window for specific methods
+    method_limits: Arc<HashMap<String, u64>>,
+    /// Reverse proxies whose forwarding headers are trusted
+    trusted_proxies: Arc<HashSet<IpAddr>>,
+    /// Length of the window
+    window: Duration,
+    /// Shared by all services since the server creates one per connection
+    counters: Arc<Counters>,
+    /// When the counters of past windows were last removed
+    last_prune: Arc<Mutex<Instant>>,
+}
+
+impl RateLimitLayer {
+    /// Create a new rate limit layer
+    ///
+    /// # Arguments
+    /// * `max_requests: u64` - Max requests per client IP in one window
+    /// * `method_limits: HashMap<String, u64>` - Max requests per client IP in one window for
+    ///   specific methods
+    /// * `trusted_proxies: HashSet<IpAddr>` - Reverse proxies whose forwarding headers are trusted
+    /// * `window: Duration` - Length of the window
+    ///
+    /// # Returns
+    /// * `Self` - A RateLimitLayer instance
+    pub fn new(
+        max_requests: u64,
+        method_limits: HashMap<String, u64>,
+        trusted_proxies: HashSet<IpAddr>,
+        window: Duration,
+    ) -> Self {
+        Self {
+            max_requests,
+            method_limits: Arc::new(method_limits),
+            trusted_proxies: Arc::new(trusted_proxies),
+            window,
+            counters: Arc::new(DashMap::new()),
+            last_prune: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Counts the request and checks whether the client is over one of the limits
+    fn allow(&self, ip: IpAddr, methods: &[String]) -> bool {
+        let now = Instant::now();
+        self.prune(now);
+
+        let mut allowed =
+            self.count((ip, None), methods.len().max(1) as u64, self.max_requests, now);
+        for method in methods {
+            if let Some(limit) = self.method_limits.get(method) {
+                allowed &= self.count((ip, Some(method.clone())), 1, *limit, now);
+            }
+        }
+
+        allowed
+    }
+
+    /// Adds `n` requests to the counter and checks the requests of the sliding window against
+    /// the limit
+    fn count(&self, key: (IpAddr, Option<String>), n: u64, limit: u64, now: Instant) -> bool {
+        let window = self.window.as_nanos().max(1);
+        let mut counter = self.counters.entry(key).or_insert(Counter {
+            window_start: now,
+            count: 0,
+            previous: 0,
+        });
+
+        let elapsed = now.duration_since(counter.window_start).as_nanos();
+        if elapsed >= window {
+            counter.previous = if elapsed < window * 2 { counter.count } else { 0 };
+            counter.count = 0;
+            // start of the window `now` is in
+            counter.window_start = now - Duration::from_nanos((elapsed % window) as u64);
+        }
+
+        // part of the previous window that still overlaps the sliding window (rounded up)
+        let remaining = window - now.duration_since(counter.window_start).as_nanos();
+        let previous = (counter.previous as u128 * remaining).div_ceil(window) as u64;
+
+        counter.count = counter.count.saturating_add(n);
+        previous.saturating_add(counter.count) <= limit
+    }
+
+    /// Removes the counters that no longer count towards any window (at most once per window)
+    fn prune(&self, now: Instant) {
+        let mut last_prune = self.last_prune.lock().expect("Lock is not poisoned");
+        if now.duration_since(*last_prune) < self.window {
+            return;
+        }
+
+        *last_prune = now;
+        self.counters
+            .retain(|_, counter| now.duration_since(counter.window_start) < self.window * 2);
+    }
+
+    /// Returns the IP of the client: the peer address, or the address set by the reverse proxy
+    /// if the peer is a trusted proxy (requests without a peer address share one counter)
+    fn client_ip(&self, req: &Request<Body>) -> IpAddr {
+        let peer = match req.extensions().get::<SocketAddr>() {
+            Some(addr) => addr.ip(),
+            None => return IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        };
+        if !self.trusted_proxies.contains(&peer) {
+            return peer;
+        }
+
+        // the last address in X-Forwarded-For that isn't one of the proxies (the addresses before
+        // it are set by the client)
+        let forwarded = req
+            .headers()
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(|addr| addr.trim().parse::<IpAddr>().ok())
+            .collect::<Vec<_>>();
+        if let Some(ip) = forwarded.into_iter().rev().find(|ip| !self.trusted_proxies.contains(ip))
+        {
+            return ip;
+        }
+
+        req.headers()
+            .get("x-real-ip")
+            .and_then(|value| value.to_str().ok()?.trim().parse().ok())
+            .unwrap_or(peer)
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService { inner, layer: self.clone() }
+    }
+}
+
+/// The service that limits the number of JSON-RPC requests per client IP.
+#[derive(Debug, Clone)]
+pub struct RateLimitService<S> {
+    /// The inner service
+    inner: S,
+    /// The limits and the counters shared by all services
+    layer: RateLimitLayer,
+}
+
+/// Returns the methods and the id of the JSON-RPC request (the id is null for batches)
+fn methods_and_id(body: &[u8]) -> (Vec<String>, Value) {
+    let method = |req: &Value| req.get("method")?.as_str().map(String::from);
+
+    match serde_json::from_slice::<Value>(body) {
+        Ok(Value::Array(reqs)) => (reqs.iter().filter_map(method).collect(), Value::Null),
+        Ok(req) => (method(&req).into_iter().collect(), req.get("id").cloned().unwrap_or_default()),
+        Err(_) => (vec![], Value::Null),
+    }
+}
+
+fn too_many_requests(id: Value) -> Result<Response<Body>, hyper::http::Error> {
+    let res = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": TOO_MANY_REQUESTS, "message": "Too many requests" },
+    });
+
+    Response::builder()
+        .header(hyper::header::CONTENT_TYPE, "application/json")
+        .body(Body::from(res.to_string()))
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: Into<Box<dyn Error + Send + Sync>> + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = Box<dyn Error + Send + Sync + 'static>;
+    type Future =
+        Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send + 'static>>;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        // take the service that was ready
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let layer = self.layer.clone();
+
+        Box::pin(async move {
+            let ip = layer.client_ip(&req);
+            let (req_h, req_b) = req.into_parts();
+            let req_bb = hyper::body::to_bytes(req_b).await?;
+
+            let (methods, id) = methods_and_id(&req_bb);
+            if !layer.allow(ip, &methods) {
+                return Ok(too_many_requests(id)?);
+            }
+
+            inner.call(Request::from_parts(req_h, Body::from(req_bb))).await.map_err(Into::into)
+        })
+    }
+}
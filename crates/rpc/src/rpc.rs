@@ -1,14 +1,24 @@
-use super::middleware::ProxyJsonRpcLayer;
+use super::middleware::{
+    CorsConfig, CorsConfigError, CorsLayer, DedupLayer, ProxyJsonRpcLayer, RateLimitLayer,
+    RATE_LIMIT_WINDOW,
+};
 use eyre::Error;
-use hyper::{http::HeaderValue, Method};
+use hyper::{
+    server::conn::AddrStream,
+    service::{make_service_fn, service_fn},
+    Body, Request, Server,
+};
 use jsonrpsee::{
-    server::{RpcServiceBuilder, ServerBuilder, ServerHandle},
+    server::{stop_channel, RpcServiceBuilder, ServerBuilder, ServerHandle},
     Methods,
 };
 use silius_metrics::rpc::MetricsLayer;
-use std::net::{IpAddr, SocketAddr};
-use tower::ServiceBuilder;
-use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+use tower::{Service, ServiceBuilder};
 
 /// JsonRpcServer is a wrapper around the `jsonrpsee` [ServerBuilder](https://docs.rs/jsonrpsee/3.0.0-beta.1/jsonrpsee/server/struct.ServerBuilder.html).
 pub struct JsonRpcServer {
@@ -34,6 +44,10 @@ pub struct JsonRpcServer {
     ws_cors_layer: Option<CorsLayer>,
     /// The [proxy layer](ProxyJsonRpcLayer) to forward requests.
     proxy_layer: Option<ProxyJsonRpcLayer>,
+    /// The [dedup layer](DedupLayer) to merge identical in-flight HTTP requests.
+    dedup_layer: Option<DedupLayer>,
+    /// The [rate limit layer](RateLimitLayer) to limit the HTTP requests per client IP.
+    rate_limit_layer: Option<RateLimitLayer>,
     /// This [metric layer](MetricsLayer) is used for collecting and reporting metrics related to
     /// RPC operations.
     metric_layer: Option<MetricsLayer>,
@@ -81,6 +95,8 @@ impl JsonRpcServer {
             ws_methods: Methods::new(),
             ws_cors_layer: None,
             proxy_layer: None,
+            dedup_layer: None,
+            rate_limit_layer: None,
             metric_layer: None,
         }
     }
@@ -88,28 +104,18 @@ impl JsonRpcServer {
     /// Add a cors layer to the server.
     ///
     /// # Arguments
-    /// * `cors_domain: Vec<String>` - A list of CORS filters in the form of String.
+    /// * `cors: CorsConfig` - The [CORS configuration](CorsConfig).
     /// * `typ: JsonRpcServerType` - The type of the server.
     ///
     /// # Returns
-    /// * `Self` - A new [JsonRpcServer](JsonRpcServer) instance.
-    pub fn with_cors(mut self, cors_domain: &[String], typ: JsonRpcServerType) -> Self {
-        let cors_layer = if cors_domain.iter().any(|d| d == "*") {
-            CorsLayer::new().allow_headers(Any).allow_methods([Method::POST]).allow_origin(Any)
-        } else {
-            let mut origins: Vec<HeaderValue> = vec![];
-
-            for domain in cors_domain.iter() {
-                if let Ok(origin) = domain.parse::<HeaderValue>() {
-                    origins.push(origin);
-                }
-            }
-
-            CorsLayer::new()
-                .allow_headers(Any)
-                .allow_methods([Method::POST])
-                .allow_origin(AllowOrigin::list(origins))
-        };
+    /// * `Result<Self, CorsConfigError>` - The JsonRpcServer instance, or an error if one of the
+    ///   allowed origins is malformed.
+    pub fn with_cors(
+        mut self,
+        cors: CorsConfig,
+        typ: JsonRpcServerType,
+    ) -> Result<Self, CorsConfigError> {
+        let cors_layer = CorsLayer::new(cors)?;
 
         match typ {
             JsonRpcServerType::Both => {
@@ -120,7 +126,7 @@ impl JsonRpcServer {
             JsonRpcServerType::Ws => self.ws_cors_layer = Some(cors_layer),
         }
 
-        self
+        Ok(self)
     }
 
     /// Add a proxy layer to the server.
@@ -135,6 +141,41 @@ impl JsonRpcServer {
         self
     }
 
+    /// Add a dedup layer to the HTTP server.
+    ///
+    /// # Returns
+    /// * `Self` - The JsonRpcServer instance.
+    pub fn with_dedup(mut self) -> Self {
+        self.dedup_layer = Some(DedupLayer::default());
+        self
+    }
+
+    /// Add a rate limit layer to the HTTP server.
+    ///
+    /// # Arguments
+    /// * `max_requests: u64` - Max requests per client IP per minute.
+    /// * `method_limits: HashMap<String, u64>` - Max requests per client IP per minute for specific
+    ///   methods.
+    /// * `trusted_proxies: HashSet<IpAddr>` - Reverse proxies whose `X-Forwarded-For` and
+    ///   `X-Real-IP` headers are used as the client IP.
+    ///
+    /// # Returns
+    /// * `Self` - The JsonRpcServer instance.
+    pub fn with_rate_limit(
+        mut self,
+        max_requests: u64,
+        method_limits: HashMap<String, u64>,
+        trusted_proxies: HashSet<IpAddr>,
+    ) -> Self {
+        self.rate_limit_layer = Some(RateLimitLayer::new(
+            max_requests,
+            method_limits,
+            trusted_proxies,
+            RATE_LIMIT_WINDOW,
+        ));
+        self
+    }
+
     pub fn with_metrics(mut self) -> Self {
         self.metric_layer = Some(MetricsLayer::new());
         self
@@ -175,17 +216,39 @@ impl JsonRpcServer {
         let http_handle = if self.http {
             let service = ServiceBuilder::new()
                 .option_layer(self.http_cors_layer.clone())
+                .option_layer(self.rate_limit_layer.clone())
+                .option_layer(self.dedup_layer.clone())
                 .option_layer(self.proxy_layer.clone());
             let rpc_service = RpcServiceBuilder::new().option_layer(self.metric_layer.clone());
 
-            let server = ServerBuilder::new()
+            let service_builder = ServerBuilder::new()
                 .http_only()
                 .set_rpc_middleware(rpc_service)
                 .set_http_middleware(service)
-                .build(SocketAddr::new(self.http_addr, self.http_port))
-                .await?;
+                .to_service_builder();
+            let methods = self.http_methods.clone();
+            let (stop_handle, server_handle) = stop_channel();
+            let shutdown = stop_handle.clone();
+
+            // the peer address of the connection is put in the request extensions (the rate limit
+            // layer counts the requests per client IP)
+            let make_service = make_service_fn(move |conn: &AddrStream| {
+                let peer_addr = conn.remote_addr();
+                let mut service = service_builder.build(methods.clone(), stop_handle.clone());
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |mut req: Request<Body>| {
+                        req.extensions_mut().insert(peer_addr);
+                        service.call(req)
+                    }))
+                }
+            });
+
+            let server = Server::try_bind(&SocketAddr::new(self.http_addr, self.http_port))?
+                .serve(make_service)
+                .with_graceful_shutdown(async move { shutdown.shutdown().await });
+            tokio::spawn(server);
 
-            Some(server.start(self.http_methods.clone()))
+            Some(server_handle)
         } else {
             None
         };
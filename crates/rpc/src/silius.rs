@@ -0,0 +1,265 @@
+use crate::{
+    error::RpcError,
+    silius_api::{
+        ChainConfig, EntryPointDeposit, SiliusApiServer, SimulateBundleFailure,
+        SimulateBundleResult, ValidationError, ValidationReport,
+    },
+};
+use alloy_chains::Chain;
+use async_trait::async_trait;
+use ethers::{
+    types::{Address, U256},
+    utils::to_checksum,
+};
+use jsonrpsee::{core::RpcResult, types::ErrorObjectOwned};
+use silius_grpc::{
+    uo_pool_client::UoPoolClient, GetDepositInfoRequest, SimulateBundleRequest,
+    SuggestMaxPriorityFeePerGasRequest, ValidateUserOperationRequest,
+    ValidateUserOperationResponse, ValidateUserOperationResult,
+};
+use silius_mempool::MempoolError;
+use silius_primitives::{
+    constants::{entry_point, supported_chains::CHAINS},
+    UserOperation, UserOperationRequest, UserOperationSigned,
+};
+use tonic::Request;
+
+/// SiliusApiServerImpl implements the `silius` namespace RPC methods trait
+/// [SiliusApiServer](SiliusApiServer).
+pub struct SiliusApiServerImpl {
+    /// The [UoPool gRPC client](UoPoolClient).
+    pub uopool_grpc_client: UoPoolClient<tonic::transport::Channel>,
+}
+
+impl SiliusApiServerImpl {
+    /// Returns the chain ID of the uopool
+    async fn chain_id(&self) -> RpcResult<u64> {
+        let res = self
+            .uopool_grpc_client
+            .clone()
+            .get_chain_id(Request::new(()))
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        Ok(res.chain_id)
+    }
+
+    /// Converts the [UserOperationRequest](UserOperationRequest) to the
+    /// [UserOperation](UserOperation) (hash is calculated with the chain ID of the uopool)
+    fn to_user_operation(uo: UserOperationRequest, ep: &Address, chain_id: u64) -> UserOperation {
+        let uo: UserOperationSigned = uo.into();
+        UserOperation::from_user_operation_signed(uo.hash(ep, chain_id), uo)
+    }
+}
+
+impl TryFrom<ValidateUserOperationResponse> for ValidationReport {
+    type Error = RpcError;
+
+    fn try_from(res: ValidateUserOperationResponse) -> Result<Self, Self::Error> {
+        if res.res == ValidateUserOperationResult::Valid as i32 {
+            return Ok(Self {
+                valid: true,
+                pre_fund: res.pre_fund.map(|v| v.into()),
+                verification_gas_limit: res.verification_gas_limit.map(|v| v.into()),
+                valid_after: res.valid_after.map(|v| v.into()),
+                valid_until: res.valid_until.map(|v| v.into()),
+                validation_gas_used: res.validation_gas_used.map(|v| v.into()),
+                paymaster_gas_used: res.paymaster_gas_used.map(|v| v.into()),
+                error: None,
+            });
+        }
+
+        let err = serde_json::from_str::<MempoolError>(&res.data)?;
+        Ok(Self {
+            valid: false,
+            pre_fund: None,
+            verification_gas_limit: None,
+            valid_after: None,
+            valid_until: None,
+            validation_gas_used: None,
+            paymaster_gas_used: None,
+            error: Some(err.into()),
+        })
+    }
+}
+
+impl From<MempoolError> for ValidationError {
+    fn from(err: MempoolError) -> Self {
+        let err = ErrorObjectOwned::from(RpcError::from(err));
+        Self { code: err.code(), message: err.message().to_string() }
+    }
+}
+
+#[async_trait]
+impl SiliusApiServer for SiliusApiServerImpl {
+    /// Returns the version of the bundler.
+    ///
+    /// # Returns
+    /// * `RpcResult<String>` - The version of the bundler.
+    async fn version(&self) -> RpcResult<String> {
+        Ok(env!("CARGO_PKG_VERSION").to_string())
+    }
+
+    /// Returns the chains supported by the bundler.
+    ///
+    /// # Returns
+    /// * `RpcResult<Vec<ChainConfig>>` - An array of [ChainConfig](ChainConfig).
+    async fn supported_chains(&self) -> RpcResult<Vec<ChainConfig>> {
+        let entry_point = to_checksum(
+            &entry_point::ADDRESS.parse::<Address>().expect("address should be valid"),
+            None,
+        );
+
+        Ok(CHAINS
+            .iter()
+            .map(|chain| ChainConfig {
+                chain_id: Chain::from(*chain).id().into(),
+                name: chain.to_string(),
+                entry_points: vec![entry_point.clone()],
+            })
+            .collect())
+    }
+
+    /// Estimates the size (in bytes) of the ABI-encoded user operation.
+    ///
+    /// # Arguments
+    /// * `user_operation: UserOperationRequest` - The [UserOperation](UserOperationRequest).
+    ///
+    /// # Returns
+    /// * `RpcResult<u64>` - The size of the user operation in bytes.
+    async fn estimate_user_operation_size(&self, uo: UserOperationRequest) -> RpcResult<u64> {
+        let uo: UserOperationSigned = uo.into();
+        Ok(uo.pack().len() as u64)
+    }
+
+    /// Validates the user operation via the
+    /// [ValidateUserOperationRequest](ValidateUserOperationRequest).
+    ///
+    /// # Arguments
+    /// * `user_operation: UserOperationRequest` - The [UserOperation](UserOperationRequest) to be
+    ///   validated.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<ValidationReport>` - The [ValidationReport](ValidationReport).
+    async fn validate_user_operation(
+        &self,
+        uo: UserOperationRequest,
+        ep: Address,
+    ) -> RpcResult<ValidationReport> {
+        let uo = Self::to_user_operation(uo, &ep, self.chain_id().await?);
+
+        let req =
+            Request::new(ValidateUserOperationRequest { uo: Some(uo.into()), ep: Some(ep.into()) });
+
+        let res = self
+            .uopool_grpc_client
+            .clone()
+            .validate_user_operation(req)
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        Ok(ValidationReport::try_from(res)?)
+    }
+
+    /// Simulates the bundle of user operations via the
+    /// [SimulateBundleRequest](SimulateBundleRequest).
+    ///
+    /// # Arguments
+    /// * `user_operations: Vec<UserOperationRequest>` - The [UserOperations](UserOperationRequest)
+    ///   to be simulated.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<SimulateBundleResult>` - The [SimulateBundleResult](SimulateBundleResult).
+    async fn simulate_bundle(
+        &self,
+        uos: Vec<UserOperationRequest>,
+        ep: Address,
+    ) -> RpcResult<SimulateBundleResult> {
+        let chain_id = self.chain_id().await?;
+        let uos_grpc =
+            uos.into_iter().map(|uo| Self::to_user_operation(uo, &ep, chain_id).into()).collect();
+
+        let req = Request::new(SimulateBundleRequest { uos: uos_grpc, ep: Some(ep.into()) });
+
+        let res = self
+            .uopool_grpc_client
+            .clone()
+            .simulate_bundle(req)
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        let mut failed = vec![];
+        for failure in res.failed {
+            let err =
+                serde_json::from_str::<MempoolError>(&failure.data).map_err(RpcError::from)?;
+            failed.push(SimulateBundleFailure { user_operation_hash: err.hash, error: err.into() });
+        }
+
+        Ok(SimulateBundleResult {
+            valid: res.valid.into_iter().map(|h| h.into()).collect(),
+            failed,
+            gas_total: res.gas_total.map(|g| g.into()).unwrap_or_default(),
+        })
+    }
+
+    /// Suggests the max priority fee per gas via the
+    /// [SuggestMaxPriorityFeePerGasRequest](SuggestMaxPriorityFeePerGasRequest).
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<U256>` - The suggested max priority fee per gas.
+    async fn suggest_max_priority_fee_per_gas(&self, ep: Address) -> RpcResult<U256> {
+        let req = Request::new(SuggestMaxPriorityFeePerGasRequest { ep: Some(ep.into()) });
+
+        let res = self
+            .uopool_grpc_client
+            .clone()
+            .suggest_max_priority_fee_per_gas(req)
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        Ok(res.max_priority_fee_per_gas.map(|fee| fee.into()).unwrap_or_default())
+    }
+
+    /// Returns the deposit and stake of the address via the
+    /// [GetDepositInfoRequest](GetDepositInfoRequest).
+    ///
+    /// # Arguments
+    /// * `address: Address` - The address of the account, paymaster or any other entity.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<EntryPointDeposit>` - The [EntryPointDeposit](EntryPointDeposit).
+    async fn get_entry_point_deposit(
+        &self,
+        addr: Address,
+        ep: Address,
+    ) -> RpcResult<EntryPointDeposit> {
+        let req =
+            Request::new(GetDepositInfoRequest { addr: Some(addr.into()), ep: Some(ep.into()) });
+
+        let res = self
+            .uopool_grpc_client
+            .clone()
+            .get_deposit_info(req)
+            .await
+            .map_err(RpcError::from)?
+            .into_inner();
+
+        Ok(EntryPointDeposit {
+            deposit: res.deposit.map(|d| d.into()).unwrap_or_default(),
+            staked: res.staked,
+            stake: res.stake.map(|s| s.into()).unwrap_or_default(),
+            unstake_delay: res.unstake_delay,
+            withdraw_time: res.withdraw_time,
+        })
+    }
+}
@@ -0,0 +1,162 @@
+pub use crate::silius::SiliusApiServerImpl;
+use ethers::types::{Address, U256, U64};
+use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use serde::{Deserialize, Serialize};
+use silius_primitives::{UserOperationHash, UserOperationRequest};
+
+/// Chain supported by the bundler
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainConfig {
+    pub chain_id: U64,
+    pub name: String,
+    pub entry_points: Vec<String>,
+}
+
+/// Error of the user operation validation (same code and message as in `eth_sendUserOperation`)
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationError {
+    pub code: i32,
+    pub message: String,
+}
+
+/// Result of the full user operation validation (sanity, simulation and simulation trace checks)
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_fund: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_gas_limit: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_after: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub valid_until: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_gas_used: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster_gas_used: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ValidationError>,
+}
+
+/// User operation that failed the bundle simulation
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateBundleFailure {
+    #[serde(rename = "userOpHash")]
+    pub user_operation_hash: UserOperationHash,
+    pub error: ValidationError,
+}
+
+/// Result of the bundle simulation
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateBundleResult {
+    pub valid: Vec<UserOperationHash>,
+    pub failed: Vec<SimulateBundleFailure>,
+    pub gas_total: U256,
+}
+
+/// Deposit and stake of an address in the entry point
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct EntryPointDeposit {
+    pub deposit: U256,
+    pub staked: bool,
+    pub stake: U256,
+    pub unstake_delay: u32,
+    pub withdraw_time: u64,
+}
+
+/// The `silius` namespace RPC methods trait (non-standard extensions useful for SDKs and wallet
+/// tooling)
+#[rpc(server, namespace = "silius")]
+pub trait SiliusApi {
+    /// Returns the version of the bundler.
+    ///
+    /// # Returns
+    /// * `RpcResult<String>` - The version of the bundler.
+    #[method(name = "version")]
+    async fn version(&self) -> RpcResult<String>;
+
+    /// Returns the chains supported by the bundler.
+    ///
+    /// # Returns
+    /// * `RpcResult<Vec<ChainConfig>>` - An array of [ChainConfig](ChainConfig).
+    #[method(name = "supportedChains")]
+    async fn supported_chains(&self) -> RpcResult<Vec<ChainConfig>>;
+
+    /// Estimates the size (in bytes) of the ABI-encoded user operation.
+    ///
+    /// # Arguments
+    /// * `user_operation: UserOperationRequest` - The [UserOperation](UserOperationRequest).
+    ///
+    /// # Returns
+    /// * `RpcResult<u64>` - The size of the user operation in bytes.
+    #[method(name = "estimateUserOperationSize")]
+    async fn estimate_user_operation_size(
+        &self,
+        user_operation: UserOperationRequest,
+    ) -> RpcResult<u64>;
+
+    /// Validates the user operation without adding it to the mempool.
+    ///
+    /// # Arguments
+    /// * `user_operation: UserOperationRequest` - The [UserOperation](UserOperationRequest) to be
+    ///   validated.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<ValidationReport>` - The [ValidationReport](ValidationReport).
+    #[method(name = "validateUserOperation")]
+    async fn validate_user_operation(
+        &self,
+        user_operation: UserOperationRequest,
+        entry_point: Address,
+    ) -> RpcResult<ValidationReport>;
+
+    /// Simulates the bundle of user operations.
+    ///
+    /// # Arguments
+    /// * `user_operations: Vec<UserOperationRequest>` - The [UserOperations](UserOperationRequest)
+    ///   to be simulated.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<SimulateBundleResult>` - The [SimulateBundleResult](SimulateBundleResult).
+    #[method(name = "simulateBundle")]
+    async fn simulate_bundle(
+        &self,
+        user_operations: Vec<UserOperationRequest>,
+        entry_point: Address,
+    ) -> RpcResult<SimulateBundleResult>;
+
+    /// Suggests the max priority fee per gas for a new user operation, based on the priority fees
+    /// of the user operations in the mempool.
+    ///
+    /// # Arguments
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<U256>` - The suggested max priority fee per gas.
+    #[method(name = "suggestMaxPriorityFeePerGas")]
+    async fn suggest_max_priority_fee_per_gas(&self, entry_point: Address) -> RpcResult<U256>;
+
+    /// Returns the deposit and stake of the address in the entry point.
+    ///
+    /// # Arguments
+    /// * `address: Address` - The address of the account, paymaster or any other entity.
+    /// * `entry_point: Address` - The address of the entry point.
+    ///
+    /// # Returns
+    /// * `RpcResult<EntryPointDeposit>` - The [EntryPointDeposit](EntryPointDeposit).
+    #[method(name = "getEntryPointDeposit")]
+    async fn get_entry_point_deposit(
+        &self,
+        address: Address,
+        entry_point: Address,
+    ) -> RpcResult<EntryPointDeposit>;
+}
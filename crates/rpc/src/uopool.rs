@@ -0,0 +1,119 @@
+use crate::{
+    eth_api::{EthApiServer, EthApiServerImpl},
+    silius_api::{SiliusApiServer, SiliusApiServerImpl},
+    web3_api::{Web3ApiServer, Web3ApiServerImpl},
+    JsonRpcServer, JsonRpcServerType,
+};
+use async_trait::async_trait;
+use ethers::providers::Middleware;
+use silius_grpc::{uo_pool_client::UoPoolClient, UoPoolServe};
+use silius_mempool::{SanityCheck, SimulationCheck, SimulationTraceCheck, UoPoolBuilder};
+use silius_primitives::provider::BlockStream;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use tokio::task::JoinHandle;
+
+/// Returns the address a client connects to for a server bound to `addr` (the loopback address
+/// if the server listens on all interfaces)
+fn connect_addr(addr: SocketAddr) -> SocketAddr {
+    match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => {
+            SocketAddr::new(Ipv4Addr::LOCALHOST.into(), addr.port())
+        }
+        IpAddr::V6(ip) if ip.is_unspecified() => {
+            SocketAddr::new(Ipv6Addr::LOCALHOST.into(), addr.port())
+        }
+        _ => addr,
+    }
+}
+
+/// Serves a user operation pool over HTTP JSON-RPC (`eth`, `silius` and `web3` namespaces)
+/// without running a separate RPC process
+#[async_trait]
+pub trait UoPoolServeHttp {
+    /// Builds the user operation pool, starts its gRPC server and the HTTP JSON-RPC server that
+    /// talks to it.
+    ///
+    /// # Arguments
+    /// * `grpc_addr` - Address the gRPC server listens on
+    /// * `http_addr` - Address the HTTP JSON-RPC server listens on
+    /// * `block_stream` - Stream of new blocks, block updates are not registered if None
+    ///
+    /// # Returns
+    /// The handle of a task that resolves when either of the servers stops.
+    async fn build_and_serve_http(
+        self,
+        grpc_addr: SocketAddr,
+        http_addr: SocketAddr,
+        block_stream: Option<BlockStream>,
+    ) -> eyre::Result<JoinHandle<()>>;
+}
+
+#[async_trait]
+impl<M, SanCk, SimCk, SimTrCk> UoPoolServeHttp for UoPoolBuilder<M, SanCk, SimCk, SimTrCk>
+where
+    M: Middleware + Clone + 'static,
+    SanCk: SanityCheck<M> + Clone + 'static,
+    SimCk: SimulationCheck + Clone + 'static,
+    SimTrCk: SimulationTraceCheck<M> + Clone + 'static,
+{
+    async fn build_and_serve_http(
+        self,
+        grpc_addr: SocketAddr,
+        http_addr: SocketAddr,
+        block_stream: Option<BlockStream>,
+    ) -> eyre::Result<JoinHandle<()>> {
+        let grpc_handle = self.build_and_serve(grpc_addr, block_stream).await?;
+        let uopool_grpc_client =
+            UoPoolClient::connect(format!("http://{}", connect_addr(grpc_addr))).await?;
+
+        let mut server = JsonRpcServer::new(
+            true,
+            http_addr.ip(),
+            http_addr.port(),
+            false,
+            http_addr.ip(),
+            http_addr.port(),
+        );
+        server.add_methods(Web3ApiServerImpl {}.into_rpc(), JsonRpcServerType::Http)?;
+        server.add_methods(
+            EthApiServerImpl { uopool_grpc_client: uopool_grpc_client.clone() }.into_rpc(),
+            JsonRpcServerType::Http,
+        )?;
+        server.add_methods(
+            SiliusApiServerImpl { uopool_grpc_client }.into_rpc(),
+            JsonRpcServerType::Http,
+        )?;
+
+        let (http_handle, _) = server.start().await?;
+        let http_handle =
+            http_handle.ok_or_else(|| eyre::eyre!("HTTP JSON-RPC server not started"))?;
+
+        Ok(tokio::spawn(async move {
+            tokio::select! {
+                _ = http_handle.stopped() => {}
+                _ = grpc_handle => {}
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grpc_connect_addr() {
+        assert_eq!(
+            format!("http://{}", connect_addr("0.0.0.0:3003".parse().unwrap())),
+            "http://127.0.0.1:3003"
+        );
+        assert_eq!(
+            format!("http://{}", connect_addr("[::]:3003".parse().unwrap())),
+            "http://[::1]:3003"
+        );
+        assert_eq!(
+            format!("http://{}", connect_addr("10.0.0.1:3003".parse().unwrap())),
+            "http://10.0.0.1:3003"
+        );
+    }
+}
@@ -1,14 +1,38 @@
 use crate::web3_api::Web3ApiServer;
 use async_trait::async_trait;
+use ethers::{
+    types::{Bytes, H256},
+    utils::keccak256,
+};
 use jsonrpsee::core::RpcResult;
-use silius_primitives::constants::entry_point::VERSION;
+
+/// Version of the bundler
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Version of the compiler the bundler was built with
+pub const RUSTC_VERSION: &str = env!("SILIUS_RUSTC_VERSION");
 
 pub struct Web3ApiServerImpl {}
 
 #[async_trait]
 impl Web3ApiServer for Web3ApiServerImpl {
+    /// Retrieves the client version in the `Silius/<version>/rust-<rustc version>` format.
+    ///
+    /// # Returns
+    /// * `RpcResult<String>` - The client version
     async fn client_version(&self) -> RpcResult<String> {
-        let git_version = git_version::git_version!(args = ["--tags"], fallback = "unknown");
-        return Ok(format!("silius/{VERSION}/{git_version}"));
+        Ok(format!("Silius/{VERSION}/rust-{RUSTC_VERSION}"))
+    }
+
+    /// Computes the Keccak-256 hash of the given data (without forwarding the request to the
+    /// execution client).
+    ///
+    /// # Arguments
+    /// * `data: Bytes` - The data to hash
+    ///
+    /// # Returns
+    /// * `RpcResult<H256>` - The Keccak-256 hash of the data
+    async fn sha3(&self, data: Bytes) -> RpcResult<H256> {
+        Ok(keccak256(data).into())
     }
 }
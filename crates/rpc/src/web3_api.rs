@@ -1,8 +1,12 @@
 pub use crate::web3::Web3ApiServerImpl;
+use ethers::types::{Bytes, H256};
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
 
 #[rpc(server, namespace = "web3")]
 pub trait Web3Api {
     #[method(name = "clientVersion")]
     async fn client_version(&self) -> RpcResult<String>;
+
+    #[method(name = "sha3")]
+    async fn sha3(&self, data: Bytes) -> RpcResult<H256>;
 }
@@ -0,0 +1,113 @@
+use hyper::{
+    header::{ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN},
+    http::{HeaderValue, StatusCode},
+    Body, Request, Response,
+};
+use silius_rpc::middleware::{CorsConfig, CorsLayer};
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower::{Layer, Service};
+
+#[derive(Clone)]
+struct EchoService;
+
+impl Service<Request<Body>> for EchoService {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _req: Request<Body>) -> Self::Future {
+        Box::pin(async move { Ok(Response::new(Body::from("{}"))) })
+    }
+}
+
+async fn call(layer: &CorsLayer, origin: Option<&str>) -> Response<Body> {
+    let mut req = Request::post("/");
+    if let Some(origin) = origin {
+        req = req.header(ORIGIN, origin);
+    }
+
+    layer.layer(EchoService).call(req.body(Body::empty()).unwrap()).await.unwrap()
+}
+
+fn allow_list(origins: &[&'static str]) -> CorsConfig {
+    CorsConfig::AllowList(origins.iter().map(|o| HeaderValue::from_static(o)).collect())
+}
+
+#[tokio::test]
+async fn origin_not_allowed() {
+    let layer = CorsLayer::new(allow_list(&["https://app.example.com"])).unwrap();
+
+    let res = call(&layer, Some("https://evil.com")).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = call(&layer, Some("https://app.example.com")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+
+    // not sent by a browser
+    let res = call(&layer, None).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn wildcard_subdomain() {
+    let layer = CorsLayer::new(allow_list(&["https://*.example.com"])).unwrap();
+
+    for origin in ["https://app.example.com", "https://a.b.example.com"] {
+        let res = call(&layer, Some(origin)).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], origin);
+    }
+
+    for origin in ["https://example.com", "http://app.example.com", "https://app.example.com.evil"]
+    {
+        let res = call(&layer, Some(origin)).await;
+        assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    }
+}
+
+#[tokio::test]
+async fn allow_all_reflects_origin() {
+    let layer = CorsLayer::new(CorsConfig::AllowAll).unwrap();
+
+    let res = call(&layer, Some("https://app.example.com")).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.example.com");
+}
+
+#[tokio::test]
+async fn allow_none() {
+    let layer = CorsLayer::new(CorsConfig::AllowNone).unwrap();
+
+    let res = call(&layer, Some("https://app.example.com")).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    let res = call(&layer, None).await;
+    assert_eq!(res.status(), StatusCode::OK);
+}
+
+#[test]
+fn config_from_domains() {
+    let domains = |d: &[&str]| d.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(CorsConfig::from_domains(&domains(&["*"])), Ok(CorsConfig::AllowAll));
+    assert_eq!(CorsConfig::from_domains(&domains(&[])), Ok(CorsConfig::AllowNone));
+    assert_eq!(
+        CorsConfig::from_domains(&domains(&["https://app.example.com"])),
+        Ok(allow_list(&["https://app.example.com"]))
+    );
+    assert!(CorsConfig::from_domains(&domains(&["https://app\nexample.com"])).is_err());
+
+    for origin in ["app.example.com", "https://app.*.com", "https://*.example.com/path"] {
+        assert!(CorsLayer::new(allow_list(&[origin])).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+use hyper::{Body, Request, Response};
+use serde_json::{json, Value};
+use silius_rpc::middleware::DedupLayer;
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower::{Layer, Service};
+
+/// Stands in for the RPC server forwarding `eth_sendUserOperation` to the uopool gRPC service.
+#[derive(Clone)]
+struct UoPoolService {
+    calls: Arc<AtomicUsize>,
+}
+
+impl Service<Request<Body>> for UoPoolService {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let calls = self.calls.clone();
+        Box::pin(async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            let req: Value = serde_json::from_slice(&body).unwrap();
+            tokio::time::sleep(Duration::from_millis(200)).await;
+
+            let res = json!({ "jsonrpc": "2.0", "id": req["id"], "result": "0x1234" });
+            Ok(Response::new(Body::from(res.to_string())))
+        })
+    }
+}
+
+fn request(id: u64, method: &str) -> Request<Body> {
+    let req = json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": method,
+        "params": [{ "sender": "0x0000000000000000000000000000000000000001" }, "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789"],
+    });
+    Request::post("/").body(Body::from(req.to_string())).unwrap()
+}
+
+async fn response(res: Response<Body>) -> Value {
+    serde_json::from_slice(&hyper::body::to_bytes(res.into_body()).await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn concurrent_requests_call_once() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let layer = DedupLayer::default();
+
+    let handles: Vec<_> = (0..10)
+        .map(|id| {
+            let mut service = layer.layer(UoPoolService { calls: calls.clone() });
+            tokio::spawn(async move { service.call(request(id, "eth_sendUserOperation")).await })
+        })
+        .collect();
+
+    for (id, handle) in handles.into_iter().enumerate() {
+        let res = response(handle.await.unwrap().unwrap()).await;
+        assert_eq!(res["id"], id);
+        assert_eq!(res["result"], "0x1234");
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // once the first request completes, the next one is processed again
+    let mut service = layer.layer(UoPoolService { calls: calls.clone() });
+    service.call(request(10, "eth_sendUserOperation")).await.unwrap();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn other_methods_not_deduplicated() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let layer = DedupLayer::default();
+
+    let handles: Vec<_> = (0..3)
+        .map(|id| {
+            let mut service = layer.layer(UoPoolService { calls: calls.clone() });
+            tokio::spawn(
+                async move { service.call(request(id, "eth_estimateUserOperationGas")).await },
+            )
+        })
+        .collect();
+
+    for handle in handles {
+        handle.await.unwrap().unwrap();
+    }
+
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,428 @@
+#[allow(dead_code)]
+mod common;
+
+use crate::common::{build_http_client, build_ws_client, test_port, ADDRESS};
+use async_trait::async_trait;
+use ethers::types::{Address, Log, TransactionReceipt, H256, U256};
+use futures::{stream, Stream, StreamExt};
+use jsonrpsee::{
+    core::client::{ClientT, Subscription, SubscriptionClientT},
+    rpc_params,
+};
+use serde_json::{json, Value};
+use silius_grpc::{
+    uo_pool_client::UoPoolClient, uo_pool_server, AddMempoolRequest, AddMempoolResponse,
+    AddRequest, AddResponse, AddResult, EstimateUserOperationGasRequest,
+    EstimateUserOperationGasResponse, GetAllByEntityRequest, GetAllReputationRequest,
+    GetAllReputationResponse, GetAllRequest, GetAllResponse, GetChainIdResponse,
+    GetDepositInfoRequest, GetDepositInfoResponse, GetEntityRelationshipsRequest,
+    GetEntityRelationshipsResponse, GetMempoolPagedRequest, GetMempoolPagedResponse,
+    GetMempoolStatsRequest, GetMempoolStatsResponse, GetSortedRequest, GetSortedResponse,
+    GetStakeInfoRequest, GetStakeInfoResponse, GetSupportedEntryPointsResponse,
+    GetUserOperationByHashResponse, GetUserOperationReceiptResponse,
+    GetUserOperationsByPaymasterRequest, GetUserOperationsByPaymasterResponse, RemoveFailedRequest,
+    RemoveRequest, SetReputationRequest, SetReputationResponse, SimulateBundleRequest,
+    SimulateBundleResponse, SuggestMaxPriorityFeePerGasRequest,
+    SuggestMaxPriorityFeePerGasResponse, TraceUserOperationRequest, TraceUserOperationResponse,
+    UserOperationEventResponse, UserOperationHashRequest, UserOperationStatus,
+    ValidateUserOperationRequest, ValidateUserOperationResponse,
+};
+use silius_primitives::{
+    UserOperation, UserOperationEvent, UserOperationReceipt, UserOperationRequest,
+    UserOperationSigned,
+};
+use silius_rpc::{
+    eth_api::{EthApiServer, EthApiServerImpl, PENDING_USER_OPERATIONS},
+    JsonRpcServer, JsonRpcServerType,
+};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    pin::Pin,
+    time::Duration,
+};
+use tokio::sync::broadcast;
+use tonic::{Request, Response, Status};
+
+const CHAIN_ID: u64 = 1337;
+const TX_HASH: H256 = H256::repeat_byte(0x11);
+
+/// Mock of the uopool gRPC service: only the `included` user operation has a receipt (other user
+/// operations are pending in the mempool), the added user operations are broadcast to the
+/// subscribers as pending
+struct MockUoPool {
+    included: H256,
+    events: broadcast::Sender<UserOperationEventResponse>,
+}
+
+#[async_trait]
+impl uo_pool_server::UoPool for MockUoPool {
+    type SubscribeUserOperationEventsStream =
+        Pin<Box<dyn Stream<Item = Result<UserOperationEventResponse, Status>> + Send>>;
+
+    async fn add(&self, req: Request<AddRequest>) -> Result<Response<AddResponse>, Status> {
+        let uo: UserOperation = req.into_inner().uo.unwrap().into();
+        let _ = self.events.send(UserOperationEventResponse {
+            hash: Some(uo.hash.into()),
+            status: UserOperationStatus::Pending.into(),
+        });
+
+        Ok(Response::new(AddResponse {
+            res: AddResult::Added as i32,
+            data: serde_json::to_string(&uo.hash).unwrap(),
+            results: vec![],
+        }))
+    }
+
+    async fn remove(&self, _req: Request<RemoveRequest>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("remove"))
+    }
+
+    async fn remove_failed(
+        &self,
+        _req: Request<RemoveFailedRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("remove_failed"))
+    }
+
+    async fn get_chain_id(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetChainIdResponse>, Status> {
+        Ok(Response::new(GetChainIdResponse { chain_id: CHAIN_ID }))
+    }
+
+    async fn get_supported_entry_points(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetSupportedEntryPointsResponse>, Status> {
+        Err(Status::unimplemented("get_supported_entry_points"))
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        _req: Request<EstimateUserOperationGasRequest>,
+    ) -> Result<Response<EstimateUserOperationGasResponse>, Status> {
+        Err(Status::unimplemented("estimate_user_operation_gas"))
+    }
+
+    async fn get_sorted_user_operations(
+        &self,
+        _req: Request<GetSortedRequest>,
+    ) -> Result<Response<GetSortedResponse>, Status> {
+        Err(Status::unimplemented("get_sorted_user_operations"))
+    }
+
+    async fn get_user_operation_by_hash(
+        &self,
+        req: Request<UserOperationHashRequest>,
+    ) -> Result<Response<GetUserOperationByHashResponse>, Status> {
+        let uo_hash: H256 = req.into_inner().hash.unwrap().into();
+        let included = uo_hash == self.included;
+
+        Ok(Response::new(GetUserOperationByHashResponse {
+            user_operation: Some(UserOperationSigned::default().into()),
+            entry_point: Some(Address::random().into()),
+            transaction_hash: included.then(|| TX_HASH.into()),
+            block_hash: included.then(|| H256::random().into()),
+            block_number: u64::from(included),
+        }))
+    }
+
+    async fn get_user_operation_receipt(
+        &self,
+        req: Request<UserOperationHashRequest>,
+    ) -> Result<Response<GetUserOperationReceiptResponse>, Status> {
+        let uo_hash: H256 = req.into_inner().hash.unwrap().into();
+        if uo_hash != self.included {
+            return Err(Status::not_found("User operation receipt not found"));
+        }
+
+        let log = Log {
+            address: Address::random(),
+            topics: vec![H256::random()],
+            transaction_hash: Some(TX_HASH),
+            ..Default::default()
+        };
+        Ok(Response::new(GetUserOperationReceiptResponse {
+            user_operation_hash: Some(uo_hash.into()),
+            sender: Some(Address::random().into()),
+            nonce: Some(U256::zero().into()),
+            paymaster: None,
+            actual_gas_cost: Some(U256::from(100_000).into()),
+            actual_gas_used: Some(U256::from(50_000).into()),
+            success: true,
+            tx_receipt: Some(
+                TransactionReceipt {
+                    transaction_hash: TX_HASH,
+                    block_number: Some(1.into()),
+                    logs: vec![log.clone()],
+                    ..Default::default()
+                }
+                .into(),
+            ),
+            logs: vec![log.into()],
+            reason: String::new(),
+        }))
+    }
+
+    async fn get_stake_info(
+        &self,
+        _req: Request<GetStakeInfoRequest>,
+    ) -> Result<Response<GetStakeInfoResponse>, Status> {
+        Err(Status::unimplemented("get_stake_info"))
+    }
+
+    async fn subscribe_user_operation_events(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<Self::SubscribeUserOperationEventsStream>, Status> {
+        let events = stream::unfold(self.events.subscribe(), |mut receiver| async move {
+            receiver.recv().await.ok().map(|event| (Ok(event), receiver))
+        });
+        Ok(Response::new(Box::pin(events)))
+    }
+
+    async fn get_all(
+        &self,
+        _req: Request<GetAllRequest>,
+    ) -> Result<Response<GetAllResponse>, Status> {
+        Err(Status::unimplemented("get_all"))
+    }
+
+    async fn get_mempool_paged(
+        &self,
+        _req: Request<GetMempoolPagedRequest>,
+    ) -> Result<Response<GetMempoolPagedResponse>, Status> {
+        Err(Status::unimplemented("get_mempool_paged"))
+    }
+
+    async fn get_all_by_entity(
+        &self,
+        _req: Request<GetAllByEntityRequest>,
+    ) -> Result<Response<GetAllResponse>, Status> {
+        Err(Status::unimplemented("get_all_by_entity"))
+    }
+
+    async fn get_user_operations_by_paymaster(
+        &self,
+        _req: Request<GetUserOperationsByPaymasterRequest>,
+    ) -> Result<Response<GetUserOperationsByPaymasterResponse>, Status> {
+        Err(Status::unimplemented("get_user_operations_by_paymaster"))
+    }
+
+    async fn get_entity_relationships(
+        &self,
+        _req: Request<GetEntityRelationshipsRequest>,
+    ) -> Result<Response<GetEntityRelationshipsResponse>, Status> {
+        Err(Status::unimplemented("get_entity_relationships"))
+    }
+
+    async fn get_mempool_stats(
+        &self,
+        _req: Request<GetMempoolStatsRequest>,
+    ) -> Result<Response<GetMempoolStatsResponse>, Status> {
+        Err(Status::unimplemented("get_mempool_stats"))
+    }
+
+    async fn trace_user_operation(
+        &self,
+        _req: Request<TraceUserOperationRequest>,
+    ) -> Result<Response<TraceUserOperationResponse>, Status> {
+        Err(Status::unimplemented("trace_user_operation"))
+    }
+
+    async fn clear_mempool(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("clear_mempool"))
+    }
+
+    async fn clear_reputation(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("clear_reputation"))
+    }
+
+    async fn clear_receipts(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("clear_receipts"))
+    }
+
+    async fn clear(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("clear"))
+    }
+
+    async fn get_all_reputation(
+        &self,
+        _req: Request<GetAllReputationRequest>,
+    ) -> Result<Response<GetAllReputationResponse>, Status> {
+        Err(Status::unimplemented("get_all_reputation"))
+    }
+
+    async fn set_reputation(
+        &self,
+        _req: Request<SetReputationRequest>,
+    ) -> Result<Response<SetReputationResponse>, Status> {
+        Err(Status::unimplemented("set_reputation"))
+    }
+
+    async fn add_mempool(
+        &self,
+        _req: Request<AddMempoolRequest>,
+    ) -> Result<Response<AddMempoolResponse>, Status> {
+        Err(Status::unimplemented("add_mempool"))
+    }
+
+    async fn validate_user_operation(
+        &self,
+        _req: Request<ValidateUserOperationRequest>,
+    ) -> Result<Response<ValidateUserOperationResponse>, Status> {
+        Err(Status::unimplemented("validate_user_operation"))
+    }
+
+    async fn simulate_bundle(
+        &self,
+        _req: Request<SimulateBundleRequest>,
+    ) -> Result<Response<SimulateBundleResponse>, Status> {
+        Err(Status::unimplemented("simulate_bundle"))
+    }
+
+    async fn suggest_max_priority_fee_per_gas(
+        &self,
+        _req: Request<SuggestMaxPriorityFeePerGasRequest>,
+    ) -> Result<Response<SuggestMaxPriorityFeePerGasResponse>, Status> {
+        Err(Status::unimplemented("suggest_max_priority_fee_per_gas"))
+    }
+
+    async fn get_deposit_info(
+        &self,
+        _req: Request<GetDepositInfoRequest>,
+    ) -> Result<Response<GetDepositInfoResponse>, Status> {
+        Err(Status::unimplemented("get_deposit_info"))
+    }
+}
+
+/// Starts the mock uopool gRPC service and the JSON-RPC server (HTTP and WS) with the `eth`
+/// namespace
+async fn setup(included: H256) -> (u16, u16) {
+    let grpc_port = test_port();
+    let (events, _) = broadcast::channel(16);
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(uo_pool_server::UoPoolServer::new(MockUoPool { included, events }))
+            .serve(SocketAddr::new(IpAddr::from(ADDRESS), grpc_port)),
+    );
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let uopool_grpc_client =
+        UoPoolClient::connect(format!("http://{}:{grpc_port}", Ipv4Addr::LOCALHOST)).await.unwrap();
+
+    let addr = IpAddr::from(ADDRESS);
+    let port = test_port();
+    let ws_port = test_port();
+    let mut server = JsonRpcServer::new(true, addr, port, true, addr, ws_port);
+    server
+        .add_methods(
+            EthApiServerImpl { uopool_grpc_client: uopool_grpc_client.clone() }.into_rpc(),
+            JsonRpcServerType::Http,
+        )
+        .unwrap();
+    server
+        .add_methods(EthApiServerImpl { uopool_grpc_client }.into_rpc(), JsonRpcServerType::Ws)
+        .unwrap();
+
+    let (http_handle, ws_handle) = server.start().await.unwrap();
+    tokio::spawn(http_handle.unwrap().stopped());
+    tokio::spawn(ws_handle.unwrap().stopped());
+
+    (port, ws_port)
+}
+
+#[tokio::test]
+async fn eth_get_user_operation_receipt() {
+    let included = H256::random();
+    let (port, _) = setup(included).await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let receipt: Option<UserOperationReceipt> = client
+        .request("eth_getUserOperationReceipt", rpc_params![format!("{included:?}")])
+        .await
+        .unwrap();
+    let receipt = receipt.unwrap();
+    assert_eq!(receipt.user_operation_hash, included.into());
+    assert!(receipt.success);
+    assert_eq!(receipt.actual_gas_used, 50_000.into());
+    assert_eq!(receipt.tx_receipt.transaction_hash, TX_HASH);
+    assert_eq!(receipt.logs.len(), 1);
+
+    // still pending in the mempool
+    let pending = H256::random();
+    let receipt: Option<UserOperationReceipt> = client
+        .request("eth_getUserOperationReceipt", rpc_params![format!("{pending:?}")])
+        .await
+        .unwrap();
+    assert!(receipt.is_none());
+}
+
+#[tokio::test]
+async fn eth_get_user_operation_by_hash() {
+    let included = H256::random();
+    let (port, _) = setup(included).await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let uo: Value = client
+        .request("eth_getUserOperationByHash", rpc_params![format!("{included:?}")])
+        .await
+        .unwrap();
+    assert_eq!(uo["transactionHash"], json!(TX_HASH));
+    assert_eq!(uo["blockNumber"], "0x1");
+
+    // still pending in the mempool
+    let pending = H256::random();
+    let uo: Value = client
+        .request("eth_getUserOperationByHash", rpc_params![format!("{pending:?}")])
+        .await
+        .unwrap();
+    assert!(uo["userOperation"].is_object());
+    assert!(uo["transactionHash"].is_null());
+    assert!(uo["blockHash"].is_null());
+    assert!(uo["blockNumber"].is_null());
+}
+
+#[tokio::test]
+async fn eth_subscribe_pending_user_operations() {
+    let (port, ws_port) = setup(H256::random()).await;
+    let http_client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+    let ws_client = build_ws_client(IpAddr::from(ADDRESS), ws_port).await.unwrap();
+
+    let mut subscription: Subscription<UserOperationEvent> = ws_client
+        .subscribe("eth_subscribe", rpc_params![PENDING_USER_OPERATIONS], "eth_unsubscribe")
+        .await
+        .unwrap();
+
+    let ep = Address::random();
+    let uo = UserOperationSigned::default().sender(Address::random());
+    let uo_hash: H256 = http_client
+        .request("eth_sendUserOperation", rpc_params![UserOperationRequest::from(uo.clone()), ep])
+        .await
+        .unwrap();
+    assert_eq!(uo_hash, uo.hash(&ep, CHAIN_ID).0);
+
+    let event = tokio::time::timeout(Duration::from_secs(5), subscription.next())
+        .await
+        .expect("Notification arrives in time")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        event,
+        UserOperationEvent {
+            hash: uo_hash.into(),
+            status: silius_primitives::UserOperationStatus::Pending
+        }
+    );
+
+    // unknown subscription kinds are rejected
+    assert!(ws_client
+        .subscribe::<UserOperationEvent, _>(
+            "eth_subscribe",
+            rpc_params!["newHeads"],
+            "eth_unsubscribe"
+        )
+        .await
+        .is_err());
+}
@@ -0,0 +1,36 @@
+use ethers::{
+    providers::{MockProvider, Provider},
+    types::U256,
+};
+use hyper::{Client, StatusCode, Uri};
+use silius_rpc::{HealthServer, LIVENESS_PATH, READINESS_PATH};
+use std::{net::SocketAddr, sync::Arc};
+
+async fn get(addr: SocketAddr, path: &str) -> StatusCode {
+    let uri: Uri = format!("http://{addr}{path}").parse().unwrap();
+    Client::new().get(uri).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn liveness_and_readiness() -> eyre::Result<()> {
+    let (provider, mock) = Provider::<MockProvider>::mocked();
+    // the uopool gRPC service isn't running
+    let addr = HealthServer::new(
+        "127.0.0.1:0".parse()?,
+        "http://127.0.0.1:1".into(),
+        Some(Arc::new(provider)),
+    )?
+    .start()?;
+
+    assert_eq!(get(addr, LIVENESS_PATH).await, StatusCode::OK);
+    assert_eq!(get(addr, "/health").await, StatusCode::NOT_FOUND);
+
+    // the execution client doesn't respond
+    assert_eq!(get(addr, READINESS_PATH).await, StatusCode::SERVICE_UNAVAILABLE);
+
+    // the execution client responds, but the uopool gRPC service is unreachable
+    mock.push(U256::from(1337))?;
+    assert_eq!(get(addr, READINESS_PATH).await, StatusCode::SERVICE_UNAVAILABLE);
+
+    Ok(())
+}
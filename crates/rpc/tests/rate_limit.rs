@@ -0,0 +1,170 @@
+use hyper::{Body, Request, Response};
+use serde_json::{json, Value};
+use silius_rpc::{codes::TOO_MANY_REQUESTS, middleware::RateLimitLayer};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tower::{Layer, Service};
+
+#[derive(Clone)]
+struct EchoService;
+
+impl Service<Request<Body>> for EchoService {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        Box::pin(async move {
+            let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            let req: Value = serde_json::from_slice(&body).unwrap();
+
+            let res = json!({ "jsonrpc": "2.0", "id": req["id"], "result": "0x1" });
+            Ok(Response::new(Body::from(res.to_string())))
+        })
+    }
+}
+
+const PROXY: &str = "10.0.0.100";
+
+fn layer(
+    max_requests: u64,
+    method_limits: HashMap<String, u64>,
+    window: Duration,
+) -> RateLimitLayer {
+    RateLimitLayer::new(
+        max_requests,
+        method_limits,
+        HashSet::from([PROXY.parse().unwrap()]),
+        window,
+    )
+}
+
+/// JSON-RPC request from the peer `ip`, with the `X-Forwarded-For` header if `forwarded_for` is
+/// set
+fn request(id: u64, method: &str, ip: &str, forwarded_for: Option<&str>) -> Request<Body> {
+    let req = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": [] });
+    let mut builder = Request::post("/");
+    if let Some(forwarded_for) = forwarded_for {
+        builder = builder.header("x-forwarded-for", forwarded_for);
+    }
+    let mut req = builder.body(Body::from(req.to_string())).unwrap();
+    req.extensions_mut().insert(SocketAddr::new(ip.parse().unwrap(), 4321));
+    req
+}
+
+async fn call_forwarded(
+    layer: &RateLimitLayer,
+    id: u64,
+    method: &str,
+    ip: &str,
+    forwarded_for: Option<&str>,
+) -> Value {
+    let res = layer.layer(EchoService).call(request(id, method, ip, forwarded_for)).await.unwrap();
+    serde_json::from_slice(&hyper::body::to_bytes(res.into_body()).await.unwrap()).unwrap()
+}
+
+async fn call(layer: &RateLimitLayer, id: u64, method: &str, ip: &str) -> Value {
+    call_forwarded(layer, id, method, ip, None).await
+}
+
+#[tokio::test]
+async fn limit_per_peer_without_headers() {
+    let layer = layer(3, HashMap::new(), Duration::from_secs(60));
+
+    for id in 0..3 {
+        let res = call(&layer, id, "eth_chainId", "10.0.0.1").await;
+        assert_eq!(res["result"], "0x1");
+    }
+
+    let res = call(&layer, 3, "eth_chainId", "10.0.0.1").await;
+    assert_eq!(res["id"], 3);
+    assert_eq!(res["error"]["code"], TOO_MANY_REQUESTS);
+
+    // other clients have their own limit
+    let res = call(&layer, 4, "eth_chainId", "10.0.0.2").await;
+    assert_eq!(res["result"], "0x1");
+}
+
+#[tokio::test]
+async fn forwarded_headers_from_untrusted_peer() {
+    let layer = layer(1, HashMap::new(), Duration::from_secs(60));
+
+    let res = call_forwarded(&layer, 0, "eth_chainId", "10.0.0.1", Some("1.1.1.1")).await;
+    assert_eq!(res["result"], "0x1");
+
+    // the client can't get a new limit by changing the header
+    let res = call_forwarded(&layer, 1, "eth_chainId", "10.0.0.1", Some("2.2.2.2")).await;
+    assert_eq!(res["error"]["code"], TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn forwarded_headers_from_trusted_proxy() {
+    let layer = layer(1, HashMap::new(), Duration::from_secs(60));
+
+    let res = call_forwarded(&layer, 0, "eth_chainId", PROXY, Some("1.1.1.1")).await;
+    assert_eq!(res["result"], "0x1");
+    let res = call_forwarded(&layer, 1, "eth_chainId", PROXY, Some("2.2.2.2")).await;
+    assert_eq!(res["result"], "0x1");
+
+    // the addresses before the one appended by the proxy are set by the client
+    let res = call_forwarded(&layer, 2, "eth_chainId", PROXY, Some("3.3.3.3, 1.1.1.1")).await;
+    assert_eq!(res["error"]["code"], TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn limit_per_method() {
+    let layer = layer(
+        10,
+        HashMap::from([("eth_sendUserOperation".to_string(), 1)]),
+        Duration::from_secs(60),
+    );
+
+    let res = call(&layer, 0, "eth_sendUserOperation", "10.0.0.1").await;
+    assert_eq!(res["result"], "0x1");
+    let res = call(&layer, 1, "eth_sendUserOperation", "10.0.0.1").await;
+    assert_eq!(res["error"]["code"], TOO_MANY_REQUESTS);
+
+    let res = call(&layer, 2, "eth_getUserOperationByHash", "10.0.0.1").await;
+    assert_eq!(res["result"], "0x1");
+}
+
+#[tokio::test]
+async fn limit_sliding_window() {
+    let layer = layer(2, HashMap::new(), Duration::from_millis(200));
+
+    assert_eq!(call(&layer, 0, "eth_chainId", "10.0.0.1").await["result"], "0x1");
+    assert_eq!(call(&layer, 1, "eth_chainId", "10.0.0.1").await["result"], "0x1");
+
+    // the requests of the previous window still count for the part of it in the sliding window
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(call(&layer, 2, "eth_chainId", "10.0.0.1").await["result"], "0x1");
+    assert_eq!(
+        call(&layer, 3, "eth_chainId", "10.0.0.1").await["error"]["code"],
+        TOO_MANY_REQUESTS
+    );
+}
+
+#[tokio::test]
+async fn limit_reset_after_window() {
+    let layer = layer(1, HashMap::new(), Duration::from_millis(100));
+
+    assert_eq!(call(&layer, 0, "eth_chainId", "10.0.0.1").await["result"], "0x1");
+    assert_eq!(
+        call(&layer, 1, "eth_chainId", "10.0.0.1").await["error"]["code"],
+        TOO_MANY_REQUESTS
+    );
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(call(&layer, 2, "eth_chainId", "10.0.0.1").await["result"], "0x1");
+}
@@ -0,0 +1,487 @@
+#[allow(dead_code)]
+mod common;
+
+use crate::common::{build_http_client, test_port, ADDRESS};
+use async_trait::async_trait;
+use ethers::types::{Address, U256};
+use futures::Stream;
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use silius_grpc::{
+    bundler_client::BundlerClient, uo_pool_client::UoPoolClient, uo_pool_server, AddMempoolRequest,
+    AddMempoolResponse, AddRequest, AddResponse, EstimateUserOperationGasRequest,
+    EstimateUserOperationGasResponse, GetAllByEntityRequest, GetAllReputationRequest,
+    GetAllReputationResponse, GetAllRequest, GetAllResponse, GetChainIdResponse,
+    GetDepositInfoRequest, GetDepositInfoResponse, GetEntityRelationshipsRequest,
+    GetEntityRelationshipsResponse, GetMempoolPagedRequest, GetMempoolPagedResponse,
+    GetMempoolStatsRequest, GetMempoolStatsResponse, GetSortedRequest, GetSortedResponse,
+    GetStakeInfoRequest, GetStakeInfoResponse, GetSupportedEntryPointsResponse,
+    GetUserOperationByHashResponse, GetUserOperationReceiptResponse,
+    GetUserOperationsByPaymasterRequest, GetUserOperationsByPaymasterResponse, RemoveFailedRequest,
+    RemoveRequest, SetReputationRequest, SetReputationResponse, SimulateBundleFailure,
+    SimulateBundleRequest, SimulateBundleResponse, SuggestMaxPriorityFeePerGasRequest,
+    SuggestMaxPriorityFeePerGasResponse, TraceUserOperationRequest, TraceUserOperationResponse,
+    UserOperationEventResponse, UserOperationHashRequest, ValidateUserOperationRequest,
+    ValidateUserOperationResponse, ValidateUserOperationResult,
+};
+use silius_mempool::{
+    InvalidMempoolUserOperationError, MempoolError, MempoolErrorKind, SanityError,
+};
+use silius_primitives::{UserOperation, UserOperationRequest, UserOperationSigned};
+use silius_rpc::{
+    codes::SANITY,
+    debug_api::{DebugApiServer, DebugApiServerImpl},
+    silius_api::{
+        ChainConfig, EntryPointDeposit, SiliusApiServer, SiliusApiServerImpl, SimulateBundleResult,
+        ValidationReport,
+    },
+    JsonRpcServer, JsonRpcServerType,
+};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    pin::Pin,
+    time::Duration,
+};
+use tonic::{transport::Channel, Request, Response, Status};
+
+const CHAIN_ID: u64 = 1337;
+
+/// Mock of the uopool gRPC service: user operations with zero sender are invalid
+struct MockUoPool;
+
+fn sender_error(uo: &UserOperation) -> String {
+    serde_json::to_string(&MempoolError {
+        hash: uo.hash,
+        kind: MempoolErrorKind::InvalidUserOperation(InvalidMempoolUserOperationError::Sanity(
+            SanityError::Sender { inner: "zero sender".into() },
+        )),
+    })
+    .unwrap()
+}
+
+#[async_trait]
+impl uo_pool_server::UoPool for MockUoPool {
+    type SubscribeUserOperationEventsStream =
+        Pin<Box<dyn Stream<Item = Result<UserOperationEventResponse, Status>> + Send>>;
+
+    async fn add(&self, _req: Request<AddRequest>) -> Result<Response<AddResponse>, Status> {
+        Err(Status::unimplemented("add"))
+    }
+
+    async fn remove(&self, _req: Request<RemoveRequest>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("remove"))
+    }
+
+    async fn remove_failed(
+        &self,
+        _req: Request<RemoveFailedRequest>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("remove_failed"))
+    }
+
+    async fn get_chain_id(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetChainIdResponse>, Status> {
+        Ok(Response::new(GetChainIdResponse { chain_id: CHAIN_ID }))
+    }
+
+    async fn get_supported_entry_points(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<GetSupportedEntryPointsResponse>, Status> {
+        Err(Status::unimplemented("get_supported_entry_points"))
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        _req: Request<EstimateUserOperationGasRequest>,
+    ) -> Result<Response<EstimateUserOperationGasResponse>, Status> {
+        Err(Status::unimplemented("estimate_user_operation_gas"))
+    }
+
+    async fn get_sorted_user_operations(
+        &self,
+        _req: Request<GetSortedRequest>,
+    ) -> Result<Response<GetSortedResponse>, Status> {
+        Err(Status::unimplemented("get_sorted_user_operations"))
+    }
+
+    async fn get_user_operation_by_hash(
+        &self,
+        _req: Request<UserOperationHashRequest>,
+    ) -> Result<Response<GetUserOperationByHashResponse>, Status> {
+        Err(Status::unimplemented("get_user_operation_by_hash"))
+    }
+
+    async fn get_user_operation_receipt(
+        &self,
+        _req: Request<UserOperationHashRequest>,
+    ) -> Result<Response<GetUserOperationReceiptResponse>, Status> {
+        Err(Status::unimplemented("get_user_operation_receipt"))
+    }
+
+    async fn get_stake_info(
+        &self,
+        _req: Request<GetStakeInfoRequest>,
+    ) -> Result<Response<GetStakeInfoResponse>, Status> {
+        Err(Status::unimplemented("get_stake_info"))
+    }
+
+    async fn subscribe_user_operation_events(
+        &self,
+        _req: Request<()>,
+    ) -> Result<Response<Self::SubscribeUserOperationEventsStream>, Status> {
+        Err(Status::unimplemented("subscribe_user_operation_events"))
+    }
+
+    async fn get_all(
+        &self,
+        _req: Request<GetAllRequest>,
+    ) -> Result<Response<GetAllResponse>, Status> {
+        Err(Status::unimplemented("get_all"))
+    }
+
+    async fn get_mempool_paged(
+        &self,
+        _req: Request<GetMempoolPagedRequest>,
+    ) -> Result<Response<GetMempoolPagedResponse>, Status> {
+        Err(Status::unimplemented("get_mempool_paged"))
+    }
+
+    async fn get_all_by_entity(
+        &self,
+        _req: Request<GetAllByEntityRequest>,
+    ) -> Result<Response<GetAllResponse>, Status> {
+        Err(Status::unimplemented("get_all_by_entity"))
+    }
+
+    async fn get_user_operations_by_paymaster(
+        &self,
+        _req: Request<GetUserOperationsByPaymasterRequest>,
+    ) -> Result<Response<GetUserOperationsByPaymasterResponse>, Status> {
+        Err(Status::unimplemented("get_user_operations_by_paymaster"))
+    }
+
+    async fn get_entity_relationships(
+        &self,
+        _req: Request<GetEntityRelationshipsRequest>,
+    ) -> Result<Response<GetEntityRelationshipsResponse>, Status> {
+        Err(Status::unimplemented("get_entity_relationships"))
+    }
+
+    async fn get_mempool_stats(
+        &self,
+        _req: Request<GetMempoolStatsRequest>,
+    ) -> Result<Response<GetMempoolStatsResponse>, Status> {
+        Err(Status::unimplemented("get_mempool_stats"))
+    }
+
+    async fn trace_user_operation(
+        &self,
+        _req: Request<TraceUserOperationRequest>,
+    ) -> Result<Response<TraceUserOperationResponse>, Status> {
+        Err(Status::unimplemented("trace_user_operation"))
+    }
+
+    async fn clear_mempool(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("clear_mempool"))
+    }
+
+    async fn clear_reputation(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("clear_reputation"))
+    }
+
+    async fn clear_receipts(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("clear_receipts"))
+    }
+
+    async fn clear(&self, _req: Request<()>) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented("clear"))
+    }
+
+    async fn get_all_reputation(
+        &self,
+        _req: Request<GetAllReputationRequest>,
+    ) -> Result<Response<GetAllReputationResponse>, Status> {
+        Err(Status::unimplemented("get_all_reputation"))
+    }
+
+    async fn set_reputation(
+        &self,
+        _req: Request<SetReputationRequest>,
+    ) -> Result<Response<SetReputationResponse>, Status> {
+        Err(Status::unimplemented("set_reputation"))
+    }
+
+    async fn add_mempool(
+        &self,
+        _req: Request<AddMempoolRequest>,
+    ) -> Result<Response<AddMempoolResponse>, Status> {
+        Err(Status::unimplemented("add_mempool"))
+    }
+
+    async fn validate_user_operation(
+        &self,
+        req: Request<ValidateUserOperationRequest>,
+    ) -> Result<Response<ValidateUserOperationResponse>, Status> {
+        let uo: UserOperation = req.into_inner().uo.unwrap().into();
+
+        if uo.sender.is_zero() {
+            return Ok(Response::new(ValidateUserOperationResponse {
+                res: ValidateUserOperationResult::NotValid as i32,
+                pre_fund: None,
+                verification_gas_limit: None,
+                valid_after: None,
+                data: sender_error(&uo),
+                valid_until: None,
+                validation_gas_used: None,
+                paymaster_gas_used: None,
+            }));
+        }
+
+        Ok(Response::new(ValidateUserOperationResponse {
+            res: ValidateUserOperationResult::Valid as i32,
+            pre_fund: Some(U256::from(1_000).into()),
+            verification_gas_limit: Some(uo.verification_gas_limit.into()),
+            valid_after: None,
+            data: String::new(),
+            valid_until: Some(U256::from(u64::MAX).into()),
+            validation_gas_used: Some(U256::from(50_000).into()),
+            paymaster_gas_used: (!uo.paymaster_and_data.is_empty())
+                .then(|| U256::from(20_000).into()),
+        }))
+    }
+
+    async fn simulate_bundle(
+        &self,
+        req: Request<SimulateBundleRequest>,
+    ) -> Result<Response<SimulateBundleResponse>, Status> {
+        let mut res = SimulateBundleResponse::default();
+        let mut gas_total = U256::zero();
+
+        for uo in req.into_inner().uos.into_iter().map(UserOperation::from) {
+            if uo.sender.is_zero() {
+                res.failed.push(SimulateBundleFailure {
+                    hash: Some(uo.hash.into()),
+                    data: sender_error(&uo),
+                });
+            } else {
+                gas_total += uo.verification_gas_limit + uo.call_gas_limit;
+                res.valid.push(uo.hash.into());
+            }
+        }
+
+        res.gas_total = Some(gas_total.into());
+        Ok(Response::new(res))
+    }
+
+    async fn suggest_max_priority_fee_per_gas(
+        &self,
+        _req: Request<SuggestMaxPriorityFeePerGasRequest>,
+    ) -> Result<Response<SuggestMaxPriorityFeePerGasResponse>, Status> {
+        Ok(Response::new(SuggestMaxPriorityFeePerGasResponse {
+            max_priority_fee_per_gas: Some(U256::from(1_500_000_000).into()),
+        }))
+    }
+
+    async fn get_deposit_info(
+        &self,
+        _req: Request<GetDepositInfoRequest>,
+    ) -> Result<Response<GetDepositInfoResponse>, Status> {
+        Ok(Response::new(GetDepositInfoResponse {
+            deposit: Some(U256::from(2_000_000).into()),
+            staked: true,
+            stake: Some(U256::from(1_000_000).into()),
+            unstake_delay: 86400,
+            withdraw_time: 0,
+        }))
+    }
+}
+
+/// Starts the mock uopool gRPC service and the JSON-RPC server with the `silius` and
+/// `debug_bundler` namespaces
+async fn setup() -> u16 {
+    let grpc_port = test_port();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_service(uo_pool_server::UoPoolServer::new(MockUoPool))
+            .serve(SocketAddr::new(IpAddr::from(ADDRESS), grpc_port)),
+    );
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let uopool_grpc_client =
+        UoPoolClient::connect(format!("http://{}:{grpc_port}", Ipv4Addr::LOCALHOST)).await.unwrap();
+
+    let addr = IpAddr::from(ADDRESS);
+    let port = test_port();
+    let mut server = JsonRpcServer::new(true, addr, port, false, addr, port);
+    server
+        .add_methods(
+            SiliusApiServerImpl { uopool_grpc_client: uopool_grpc_client.clone() }.into_rpc(),
+            JsonRpcServerType::Http,
+        )
+        .unwrap();
+    // the bundler isn't called by the tested methods
+    let bundler_grpc_client =
+        BundlerClient::new(Channel::from_static("http://127.0.0.1:1").connect_lazy());
+    server
+        .add_methods(
+            DebugApiServerImpl { uopool_grpc_client, bundler_grpc_client }.into_rpc(),
+            JsonRpcServerType::Http,
+        )
+        .unwrap();
+
+    let (http_handle, _ws_handle) = server.start().await.unwrap();
+    tokio::spawn(http_handle.unwrap().stopped());
+
+    port
+}
+
+fn user_operation(sender: Address) -> UserOperationRequest {
+    UserOperationSigned::default()
+        .sender(sender)
+        .call_gas_limit(50_000.into())
+        .verification_gas_limit(100_000.into())
+        .pre_verification_gas(21_000.into())
+        .into()
+}
+
+#[tokio::test]
+async fn silius_version() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let version: String = client.request("silius_version", rpc_params![]).await.unwrap();
+    assert_eq!(version, env!("CARGO_PKG_VERSION"));
+}
+
+#[tokio::test]
+async fn silius_supported_chains() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let chains: Vec<ChainConfig> =
+        client.request("silius_supportedChains", rpc_params![]).await.unwrap();
+    assert!(chains.iter().any(|c| c.chain_id == 1.into() && c.name == "mainnet"));
+    assert!(chains.iter().all(|c| c.entry_points.len() == 1));
+}
+
+#[tokio::test]
+async fn silius_estimate_user_operation_size() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let uo = user_operation(Address::random());
+    let size: u64 =
+        client.request("silius_estimateUserOperationSize", rpc_params![uo.clone()]).await.unwrap();
+    assert_eq!(size, UserOperationSigned::from(uo).pack().len() as u64);
+}
+
+#[tokio::test]
+async fn silius_validate_user_operation() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+    let ep = Address::random();
+
+    let report: ValidationReport = client
+        .request("silius_validateUserOperation", rpc_params![user_operation(Address::random()), ep])
+        .await
+        .unwrap();
+    assert!(report.valid);
+    assert_eq!(report.pre_fund, Some(1_000.into()));
+    assert_eq!(report.verification_gas_limit, Some(100_000.into()));
+    assert_eq!(report.valid_until, Some(u64::MAX.into()));
+    assert_eq!(report.validation_gas_used, Some(50_000.into()));
+    assert!(report.paymaster_gas_used.is_none());
+    assert!(report.error.is_none());
+
+    let report: ValidationReport = client
+        .request("silius_validateUserOperation", rpc_params![user_operation(Address::zero()), ep])
+        .await
+        .unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.error.unwrap().code, SANITY);
+}
+
+#[tokio::test]
+async fn debug_bundler_validate_user_operation() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+    let ep = Address::random();
+
+    let uo = UserOperationSigned::from(user_operation(Address::random()))
+        .paymaster_and_data(Address::random().as_bytes().to_vec().into());
+    let report: ValidationReport = client
+        .request(
+            "debug_bundler_validateUserOperation",
+            rpc_params![UserOperationRequest::from(uo), ep],
+        )
+        .await
+        .unwrap();
+    assert!(report.valid);
+    assert_eq!(report.valid_until, Some(u64::MAX.into()));
+    assert_eq!(report.validation_gas_used, Some(50_000.into()));
+    assert_eq!(report.paymaster_gas_used, Some(20_000.into()));
+    assert!(report.error.is_none());
+
+    let report: ValidationReport = client
+        .request(
+            "debug_bundler_validateUserOperation",
+            rpc_params![user_operation(Address::zero()), ep],
+        )
+        .await
+        .unwrap();
+    assert!(!report.valid);
+    assert_eq!(report.error.unwrap().code, SANITY);
+}
+
+#[tokio::test]
+async fn silius_simulate_bundle() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+    let ep = Address::random();
+
+    let uos = vec![user_operation(Address::random()), user_operation(Address::zero())];
+    let uo_invalid = UserOperationSigned::from(uos[1].clone()).hash(&ep, CHAIN_ID);
+
+    let res: SimulateBundleResult =
+        client.request("silius_simulateBundle", rpc_params![uos, ep]).await.unwrap();
+    assert_eq!(res.valid.len(), 1);
+    assert_eq!(res.failed.len(), 1);
+    assert_eq!(res.failed[0].user_operation_hash, uo_invalid);
+    assert_eq!(res.failed[0].error.code, SANITY);
+    assert_eq!(res.gas_total, 150_000.into());
+}
+
+#[tokio::test]
+async fn silius_suggest_max_priority_fee_per_gas() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let fee: U256 = client
+        .request("silius_suggestMaxPriorityFeePerGas", rpc_params![Address::random()])
+        .await
+        .unwrap();
+    assert_eq!(fee, 1_500_000_000.into());
+}
+
+#[tokio::test]
+async fn silius_get_entry_point_deposit() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let deposit: EntryPointDeposit = client
+        .request("silius_getEntryPointDeposit", rpc_params![Address::random(), Address::random()])
+        .await
+        .unwrap();
+    assert_eq!(
+        deposit,
+        EntryPointDeposit {
+            deposit: 2_000_000.into(),
+            staked: true,
+            stake: 1_000_000.into(),
+            unstake_delay: 86400,
+            withdraw_time: 0,
+        }
+    );
+}
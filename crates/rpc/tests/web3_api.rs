@@ -0,0 +1,51 @@
+#[allow(dead_code)]
+mod common;
+
+use crate::common::{build_http_client, test_port, ADDRESS};
+use ethers::{
+    types::{Bytes, H256},
+    utils::keccak256,
+};
+use jsonrpsee::{core::client::ClientT, rpc_params};
+use silius_rpc::{
+    web3_api::{Web3ApiServer, Web3ApiServerImpl},
+    JsonRpcServer, JsonRpcServerType,
+};
+use std::net::IpAddr;
+
+async fn setup() -> u16 {
+    let addr = IpAddr::from(ADDRESS);
+    let port = test_port();
+    let mut server = JsonRpcServer::new(true, addr, port, false, addr, port);
+    server.add_methods(Web3ApiServerImpl {}.into_rpc(), JsonRpcServerType::Http).unwrap();
+
+    let (http_handle, _ws_handle) = server.start().await.unwrap();
+    tokio::spawn(http_handle.unwrap().stopped());
+
+    port
+}
+
+#[tokio::test]
+async fn web3_client_version() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let version: String = client.request("web3_clientVersion", rpc_params![]).await.unwrap();
+    assert!(version.starts_with(&format!("Silius/{}/rust-", env!("CARGO_PKG_VERSION"))));
+}
+
+#[tokio::test]
+async fn web3_sha3() {
+    let port = setup().await;
+    let client = build_http_client(IpAddr::from(ADDRESS), port).unwrap();
+
+    let hash: H256 = client.request("web3_sha3", rpc_params![Bytes::default()]).await.unwrap();
+    assert_eq!(
+        hash,
+        "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470".parse().unwrap()
+    );
+
+    let data = Bytes::from(b"silius".to_vec());
+    let hash: H256 = client.request("web3_sha3", rpc_params![data.clone()]).await.unwrap();
+    assert_eq!(hash, H256::from(keccak256(data)));
+}
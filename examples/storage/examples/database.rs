@@ -3,8 +3,10 @@ use ethers::types::{Address, U256};
 use parking_lot::RwLock;
 use silius_contracts::EntryPoint;
 use silius_mempool::{
-    init_env, validate::validator::new_canonical, CodeHashes, DatabaseTable, Mempool, Reputation,
-    UoPoolBuilder, UserOperations, UserOperationsByEntity, UserOperationsBySender, WriteMap,
+    init_env, validate::validator::new_canonical, CodeHashes, DatabaseTable, EntryPointOverhead,
+    Mempool, Reputation, UoPoolBuilder, UserOperationReceipts, UserOperations,
+    UserOperationsByAggregator, UserOperationsByEntity, UserOperationsByNonce,
+    UserOperationsBySender, WriteMap,
 };
 use silius_primitives::{
     constants::{
@@ -15,7 +17,7 @@ use silius_primitives::{
     },
     provider::create_http_provider,
     reputation::ReputationEntry,
-    UoPoolMode,
+    EntryPointVersion, UoPoolMode,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -45,7 +47,10 @@ async fn main() -> eyre::Result<()> {
             Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
             Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
             Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
             Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+            Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env.clone())),
         );
         let reputation = Reputation::new(
             MIN_INCLUSION_RATE_DENOMINATOR,
@@ -61,11 +66,20 @@ async fn main() -> eyre::Result<()> {
             UoPoolMode::Standard,
             provider.clone(),
             ep.clone(),
+            EntryPointVersion::V0_6,
             chain,
             U256::from(5000000),
+            EntryPointOverhead::default(),
             mempool,
             reputation,
-            new_canonical(entry_point, chain, U256::from(5000000), U256::from(1)),
+            new_canonical(
+                entry_point,
+                chain,
+                U256::from(5000000),
+                U256::from(1),
+                U256::from(100000),
+                3500,
+            ),
             None,
         );
 
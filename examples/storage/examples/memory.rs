@@ -2,7 +2,9 @@ use alloy_chains::Chain;
 use ethers::types::{Address, U256};
 use parking_lot::RwLock;
 use silius_contracts::EntryPoint;
-use silius_mempool::{validate::validator::new_canonical, Mempool, Reputation, UoPoolBuilder};
+use silius_mempool::{
+    validate::validator::new_canonical, EntryPointOverhead, Mempool, Reputation, UoPoolBuilder,
+};
 use silius_primitives::{
     constants::{
         entry_point::ADDRESS,
@@ -13,7 +15,7 @@ use silius_primitives::{
     provider::create_http_provider,
     reputation::ReputationEntry,
     simulation::CodeHash,
-    UoPoolMode, UserOperationHash, UserOperationSigned,
+    EntryPointVersion, UoPoolMode, UserOperation, UserOperationHash, UserOperationReceipt,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -33,8 +35,9 @@ async fn main() -> eyre::Result<()> {
         let chain = Chain::dev();
         let entry_point = EntryPoint::new(provider.clone(), ep);
         let mempool = Mempool::new(
+            Box::new(Arc::new(RwLock::new(HashMap::<UserOperationHash, UserOperation>::default()))),
             Box::new(Arc::new(RwLock::new(
-                HashMap::<UserOperationHash, UserOperationSigned>::default(),
+                HashMap::<Address, HashSet<UserOperationHash>>::default(),
             ))),
             Box::new(Arc::new(RwLock::new(
                 HashMap::<Address, HashSet<UserOperationHash>>::default(),
@@ -43,6 +46,12 @@ async fn main() -> eyre::Result<()> {
                 HashMap::<Address, HashSet<UserOperationHash>>::default(),
             ))),
             Box::new(Arc::new(RwLock::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()))),
+            Box::new(Arc::new(RwLock::new(
+                HashMap::<(Address, U256), UserOperationHash>::default(),
+            ))),
+            Box::new(Arc::new(RwLock::new(
+                HashMap::<UserOperationHash, UserOperationReceipt>::default(),
+            ))),
         );
         let reputation = Reputation::new(
             MIN_INCLUSION_RATE_DENOMINATOR,
@@ -58,11 +67,20 @@ async fn main() -> eyre::Result<()> {
             UoPoolMode::Standard,
             provider.clone(),
             ep.clone(),
+            EntryPointVersion::V0_6,
             chain,
             U256::from(5000000),
+            EntryPointOverhead::default(),
             mempool,
             reputation,
-            new_canonical(entry_point, chain, U256::from(5000000), U256::from(1)),
+            new_canonical(
+                entry_point,
+                chain,
+                U256::from(5000000),
+                U256::from(1),
+                U256::from(100000),
+                3500,
+            ),
             None,
         );
 
@@ -12,11 +12,13 @@ use ethers::{
 };
 use parking_lot::RwLock;
 use silius_mempool::{
-    init_env, CodeHashes, DatabaseTable, EntitiesReputation, Mempool, Reputation, UserOperations,
-    UserOperationsByEntity, UserOperationsBySender, WriteMap,
+    init_env, CodeHashes, DatabaseTable, EntitiesReputation, Mempool, Reputation,
+    UserOperationReceipts, UserOperations, UserOperationsByAggregator, UserOperationsByEntity,
+    UserOperationsByNonce, UserOperationsBySender, WriteMap,
 };
 use silius_primitives::{
-    reputation::ReputationEntry, simulation::CodeHash, UserOperationHash, UserOperationSigned,
+    reputation::ReputationEntry, simulation::CodeHash, UserOperation, UserOperationHash,
+    UserOperationReceipt,
 };
 use std::{
     collections::{HashMap, HashSet},
@@ -164,7 +166,10 @@ pub fn setup_database_mempool_reputation() -> (Mempool, Reputation) {
         Box::new(DatabaseTable::<WriteMap, UserOperations>::new(env.clone())),
         Box::new(DatabaseTable::<WriteMap, UserOperationsBySender>::new(env.clone())),
         Box::new(DatabaseTable::<WriteMap, UserOperationsByEntity>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, UserOperationsByAggregator>::new(env.clone())),
         Box::new(DatabaseTable::<WriteMap, CodeHashes>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, UserOperationsByNonce>::new(env.clone())),
+        Box::new(DatabaseTable::<WriteMap, UserOperationReceipts>::new(env.clone())),
     );
     let reputation = Reputation::new(
         10,
@@ -182,12 +187,15 @@ pub fn setup_database_mempool_reputation() -> (Mempool, Reputation) {
 #[allow(clippy::type_complexity)]
 pub fn setup_memory_mempool_reputation() -> (Mempool, Reputation) {
     let mempool = Mempool::new(
-        Box::new(Arc::new(RwLock::new(
-            HashMap::<UserOperationHash, UserOperationSigned>::default(),
-        ))),
+        Box::new(Arc::new(RwLock::new(HashMap::<UserOperationHash, UserOperation>::default()))),
+        Box::new(Arc::new(RwLock::new(HashMap::<Address, HashSet<UserOperationHash>>::default()))),
         Box::new(Arc::new(RwLock::new(HashMap::<Address, HashSet<UserOperationHash>>::default()))),
         Box::new(Arc::new(RwLock::new(HashMap::<Address, HashSet<UserOperationHash>>::default()))),
         Box::new(Arc::new(RwLock::new(HashMap::<UserOperationHash, Vec<CodeHash>>::default()))),
+        Box::new(Arc::new(RwLock::new(HashMap::<(Address, U256), UserOperationHash>::default()))),
+        Box::new(Arc::new(RwLock::new(
+            HashMap::<UserOperationHash, UserOperationReceipt>::default(),
+        ))),
     );
     let reputation = Reputation::new(
         10,
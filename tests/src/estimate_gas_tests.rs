@@ -11,7 +11,7 @@ use ethers::{
     utils::GethInstance,
 };
 use silius_contracts::EntryPoint;
-use silius_mempool::{validate::validator::new_canonical, UoPool};
+use silius_mempool::{validate::validator::new_canonical, EntryPointOverhead, UoPool};
 use silius_primitives::{UoPoolMode, UserOperationSigned, Wallet as UoWallet};
 use std::sync::Arc;
 
@@ -40,7 +40,14 @@ async fn estimate_with_zero() -> eyre::Result<()> {
     let entry = EntryPoint::new(client.clone(), entry_point.address);
     let entry_for_uopool = EntryPoint::new(client.clone(), entry_point.address);
     let min_priority_fee_per_gas = 0.into();
-    let validator = new_canonical(entry, chain, max_verification_gas, min_priority_fee_per_gas);
+    let validator = new_canonical(
+        entry,
+        chain,
+        max_verification_gas,
+        min_priority_fee_per_gas,
+        U256::from(100000),
+        3500,
+    );
     let mut uopool = UoPool::new(
         UoPoolMode::Standard,
         entry_for_uopool,
@@ -48,6 +55,7 @@ async fn estimate_with_zero() -> eyre::Result<()> {
         mempool,
         reputation,
         max_verification_gas,
+        EntryPointOverhead::default(),
         chain,
         None,
     );
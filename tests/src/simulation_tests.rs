@@ -130,8 +130,14 @@ async fn setup_database() -> eyre::Result<TestContext<ClientType>> {
     let entry_point = EntryPoint::new(client.clone(), ep.address);
     let c = Chain::from(chain_id);
 
-    let validator =
-        new_canonical(entry_point, c.clone(), U256::from(3000000_u64), U256::from(1u64));
+    let validator = new_canonical(
+        entry_point,
+        c.clone(),
+        U256::from(3000000_u64),
+        U256::from(1u64),
+        U256::from(100000_u64),
+        3500,
+    );
 
     Ok(TestContext {
         client: client.clone(),
@@ -155,8 +161,14 @@ async fn setup_memory() -> eyre::Result<TestContext<ClientType>> {
     let entry_point = EntryPoint::new(client.clone(), ep.address);
     let c = Chain::from(chain_id);
 
-    let validator =
-        new_canonical(entry_point, c.clone(), U256::from(3000000_u64), U256::from(1u64));
+    let validator = new_canonical(
+        entry_point,
+        c.clone(),
+        U256::from(3000000_u64),
+        U256::from(1u64),
+        U256::from(100000_u64),
+        3500,
+    );
     Ok(TestContext {
         client: client.clone(),
         _geth,